@@ -0,0 +1,60 @@
+-- Stocktake (physical count) sessions
+-- Lifecycle: open → closed | cancelled
+-- Closing a session posts ADJUSTMENT movements (signed quantity: negative = shrinkage)
+-- and freezes the variance report in stocktake_variances.
+
+CREATE TABLE IF NOT EXISTS stocktake_sessions (
+    id          UUID PRIMARY KEY,
+    tenant_id   UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    opened_by   UUID NOT NULL,
+    closed_by   UUID,
+    status      TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'closed', 'cancelled')),
+    note        TEXT,
+    opened_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    closed_at   TIMESTAMPTZ,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stocktake_sessions_tenant ON stocktake_sessions (tenant_id, opened_at DESC);
+
+-- Only one open count per tenant at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_stocktake_sessions_one_open
+    ON stocktake_sessions (tenant_id) WHERE status = 'open';
+
+DROP TRIGGER IF EXISTS stocktake_sessions_set_updated_at ON stocktake_sessions;
+CREATE TRIGGER stocktake_sessions_set_updated_at
+    BEFORE UPDATE ON stocktake_sessions
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- Count lines posted by staff. Several lines for the same ingredient
+-- (e.g. walk-in + dry store) are summed on close.
+CREATE TABLE IF NOT EXISTS stocktake_counts (
+    id                      UUID PRIMARY KEY,
+    session_id              UUID NOT NULL REFERENCES stocktake_sessions(id) ON DELETE CASCADE,
+    tenant_id               UUID NOT NULL,
+    catalog_ingredient_id   UUID NOT NULL REFERENCES catalog_ingredients(id),
+    counted_quantity        NUMERIC NOT NULL CHECK (counted_quantity >= 0),
+    counted_by              UUID NOT NULL,
+    note                    TEXT,
+    counted_at              TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_stocktake_counts_session ON stocktake_counts (session_id, catalog_ingredient_id);
+
+-- Frozen variance report, one row per counted ingredient
+CREATE TABLE IF NOT EXISTS stocktake_variances (
+    session_id              UUID NOT NULL REFERENCES stocktake_sessions(id) ON DELETE CASCADE,
+    tenant_id               UUID NOT NULL,
+    catalog_ingredient_id   UUID NOT NULL REFERENCES catalog_ingredients(id),
+    category_id             UUID,
+    expected_quantity       NUMERIC NOT NULL,
+    counted_quantity        NUMERIC NOT NULL,
+    variance_quantity       NUMERIC NOT NULL,
+    unposted_quantity       NUMERIC NOT NULL DEFAULT 0,
+    variance_cents          BIGINT NOT NULL DEFAULT 0,
+    created_at              TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (session_id, catalog_ingredient_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_stocktake_variances_tenant ON stocktake_variances (tenant_id, created_at DESC);
@@ -0,0 +1,39 @@
+-- Tenant stock policy + negative-stock (shortfall) ledger
+-- stock_policy decides what a sale does when FIFO batches cannot cover it:
+--   reject           → the whole sale is rolled back
+--   allow            → deduct what is available, ignore the rest
+--   record_shortfall → deduct what is available, log the rest in inventory_shortfalls
+
+CREATE TABLE IF NOT EXISTS tenant_inventory_settings (
+    tenant_id       UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    stock_policy    TEXT NOT NULL DEFAULT 'record_shortfall'
+                    CHECK (stock_policy IN ('reject', 'allow', 'record_shortfall')),
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+DROP TRIGGER IF EXISTS tenant_inventory_settings_set_updated_at ON tenant_inventory_settings;
+CREATE TRIGGER tenant_inventory_settings_set_updated_at
+    BEFORE UPDATE ON tenant_inventory_settings
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- Unbacked consumption: quantity sold without stock on hand.
+-- estimated_cost_cents uses the last known batch price of the ingredient.
+CREATE TABLE IF NOT EXISTS inventory_shortfalls (
+    id                      UUID PRIMARY KEY,
+    tenant_id               UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    catalog_ingredient_id   UUID NOT NULL REFERENCES catalog_ingredients(id),
+    quantity                NUMERIC NOT NULL CHECK (quantity > 0),
+    unit_cost_cents         BIGINT NOT NULL DEFAULT 0,
+    estimated_cost_cents    BIGINT NOT NULL DEFAULT 0,
+    reference_id            UUID,
+    reference_type          VARCHAR(50),
+    notes                   TEXT,
+    created_at              TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at             TIMESTAMPTZ,
+    resolved_by             UUID
+);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_shortfalls_tenant ON inventory_shortfalls (tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_inventory_shortfalls_open
+    ON inventory_shortfalls (tenant_id, catalog_ingredient_id) WHERE resolved_at IS NULL;
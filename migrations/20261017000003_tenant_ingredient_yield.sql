@@ -0,0 +1,9 @@
+-- Per-tenant edible yield override (trim / peeling loss)
+-- Effective yield for costing and FIFO deduction:
+--   tenant_ingredients.yield_percent → catalog_ingredients.edible_yield_percent → 100
+
+ALTER TABLE tenant_ingredients
+ADD COLUMN IF NOT EXISTS yield_percent NUMERIC(5,2)
+    CHECK (yield_percent IS NULL OR (yield_percent > 0 AND yield_percent <= 100));
+
+COMMENT ON COLUMN tenant_ingredients.yield_percent IS 'Overrides catalog edible_yield_percent: usable share of the as-purchased weight';
@@ -0,0 +1,72 @@
+-- Suppliers + per-ingredient purchase price history
+-- Replaces free-text supplier strings with a tenant-scoped supplier entity.
+-- Text columns (inventory_batches.supplier, tenant_ingredients.supplier,
+-- purchase_drafts.supplier_name) are kept for display and backward compatibility.
+
+CREATE TABLE IF NOT EXISTS suppliers (
+    id                  UUID PRIMARY KEY,
+    tenant_id           UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name                TEXT NOT NULL,
+    contact_name        TEXT,
+    email               TEXT,
+    phone               TEXT,
+    address             TEXT,
+    notes               TEXT,
+    lead_time_days      INTEGER NOT NULL DEFAULT 1 CHECK (lead_time_days >= 0),
+    min_order_cents     BIGINT NOT NULL DEFAULT 0 CHECK (min_order_cents >= 0),
+    -- ISO weekdays the supplier delivers on (1 = Monday … 7 = Sunday); empty = any day
+    delivery_weekdays   SMALLINT[] NOT NULL DEFAULT '{}',
+    is_active           BOOLEAN NOT NULL DEFAULT true,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_suppliers_tenant ON suppliers (tenant_id) WHERE is_active = true;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_suppliers_tenant_name
+    ON suppliers (tenant_id, LOWER(name)) WHERE is_active = true;
+
+DROP TRIGGER IF EXISTS suppliers_set_updated_at ON suppliers;
+CREATE TRIGGER suppliers_set_updated_at
+    BEFORE UPDATE ON suppliers
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- Links from existing entities
+ALTER TABLE inventory_batches
+ADD COLUMN IF NOT EXISTS supplier_id UUID REFERENCES suppliers(id) ON DELETE SET NULL;
+
+-- Preferred supplier of an ingredient for this tenant
+ALTER TABLE tenant_ingredients
+ADD COLUMN IF NOT EXISTS supplier_id UUID REFERENCES suppliers(id) ON DELETE SET NULL;
+
+ALTER TABLE purchase_drafts
+ADD COLUMN IF NOT EXISTS supplier_id UUID REFERENCES suppliers(id) ON DELETE SET NULL;
+
+-- One price point per received batch
+CREATE TABLE IF NOT EXISTS ingredient_price_history (
+    id                      UUID PRIMARY KEY,
+    tenant_id               UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    catalog_ingredient_id   UUID NOT NULL REFERENCES catalog_ingredients(id),
+    supplier_id             UUID REFERENCES suppliers(id) ON DELETE SET NULL,
+    batch_id                UUID REFERENCES inventory_batches(id) ON DELETE SET NULL,
+    price_per_unit_cents    BIGINT NOT NULL CHECK (price_per_unit_cents >= 0),
+    quantity                NUMERIC NOT NULL DEFAULT 0,
+    recorded_at             TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_history_ingredient
+    ON ingredient_price_history (tenant_id, catalog_ingredient_id, recorded_at DESC);
+CREATE INDEX IF NOT EXISTS idx_price_history_supplier
+    ON ingredient_price_history (tenant_id, supplier_id, recorded_at DESC);
+
+-- Backfill from batches already on record
+INSERT INTO ingredient_price_history
+    (id, tenant_id, catalog_ingredient_id, supplier_id, batch_id, price_per_unit_cents, quantity, recorded_at)
+SELECT gen_random_uuid(), ib.tenant_id, ib.catalog_ingredient_id, NULL, ib.id,
+       ib.price_per_unit_cents, ib.quantity, ib.received_at
+FROM inventory_batches ib
+WHERE NOT EXISTS (SELECT 1 FROM ingredient_price_history h WHERE h.batch_id = ib.id);
+
+-- Which unit price recipe costing uses
+ALTER TABLE tenant_inventory_settings
+ADD COLUMN IF NOT EXISTS costing_strategy TEXT NOT NULL DEFAULT 'fifo_actual'
+    CHECK (costing_strategy IN ('fifo_actual', 'last_price', 'weighted_average', 'cheapest_supplier'));
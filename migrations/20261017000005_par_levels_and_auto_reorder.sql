@@ -0,0 +1,17 @@
+-- Par levels + automatic reorder proposals
+-- Reorder service projects consumption from recent OUT_SALE movements and
+-- turns ingredients below their reorder point into one purchase draft per supplier.
+
+ALTER TABLE tenant_ingredients
+ADD COLUMN IF NOT EXISTS par_level NUMERIC(12, 3) CHECK (par_level >= 0),
+ADD COLUMN IF NOT EXISTS reorder_point NUMERIC(12, 3) CHECK (reorder_point >= 0),
+ADD COLUMN IF NOT EXISTS pack_size NUMERIC(12, 3) CHECK (pack_size > 0);
+
+ALTER TABLE tenant_inventory_settings
+ADD COLUMN IF NOT EXISTS auto_reorder_enabled BOOLEAN NOT NULL DEFAULT false,
+ADD COLUMN IF NOT EXISTS reorder_lookback_days INTEGER NOT NULL DEFAULT 14
+    CHECK (reorder_lookback_days BETWEEN 1 AND 90);
+
+-- Open drafts are counted as stock on order
+CREATE INDEX IF NOT EXISTS idx_purchase_draft_items_ingredient
+    ON purchase_draft_items (catalog_ingredient_id);
@@ -0,0 +1,42 @@
+-- Goods-received notes (GRN) for purchase drafts
+-- Lifecycle: draft → sent → (partially_received →) received | cancelled
+-- Each receipt creates inventory batches with IN movements referencing the draft.
+
+ALTER TABLE purchase_draft_items
+ADD COLUMN IF NOT EXISTS received_quantity DOUBLE PRECISION NOT NULL DEFAULT 0;
+
+ALTER TABLE purchase_drafts
+ADD COLUMN IF NOT EXISTS received_at TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS purchase_receipts (
+    id              UUID PRIMARY KEY,
+    tenant_id       UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    draft_id        UUID NOT NULL REFERENCES purchase_drafts(id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL,
+    invoice_number  TEXT,
+    note            TEXT,
+    received_at     TIMESTAMPTZ NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_purchase_receipts_draft ON purchase_receipts (draft_id, received_at);
+CREATE INDEX IF NOT EXISTS idx_purchase_receipts_tenant ON purchase_receipts (tenant_id, received_at DESC);
+
+CREATE TABLE IF NOT EXISTS purchase_receipt_lines (
+    id                      UUID PRIMARY KEY,
+    receipt_id              UUID NOT NULL REFERENCES purchase_receipts(id) ON DELETE CASCADE,
+    draft_item_id           UUID NOT NULL REFERENCES purchase_draft_items(id) ON DELETE CASCADE,
+    catalog_ingredient_id   UUID NOT NULL,
+    -- NULL when nothing was delivered for the item
+    batch_id                UUID REFERENCES inventory_batches(id) ON DELETE SET NULL,
+    -- Still outstanding on the item before this receipt
+    expected_quantity       DOUBLE PRECISION NOT NULL,
+    received_quantity       DOUBLE PRECISION NOT NULL CHECK (received_quantity >= 0),
+    ordered_price_cents     BIGINT,
+    price_per_unit_cents    BIGINT NOT NULL CHECK (price_per_unit_cents >= 0),
+    expires_at              TIMESTAMPTZ,
+    -- over_delivery | under_delivery | price_increase | price_decrease
+    flags                   TEXT[] NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX IF NOT EXISTS idx_purchase_receipt_lines_receipt ON purchase_receipt_lines (receipt_id);
@@ -0,0 +1,72 @@
+-- Multi-location stock within a tenant (central kitchen + outlets)
+-- Batches live at a storage location; transfers move quantity between
+-- locations FIFO with paired TRANSFER_OUT / TRANSFER_IN movements.
+
+CREATE TABLE IF NOT EXISTS storage_locations (
+    id          UUID PRIMARY KEY,
+    tenant_id   UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    kind        TEXT NOT NULL DEFAULT 'walk_in'
+                CHECK (kind IN ('walk_in', 'freezer', 'dry_store', 'bar', 'outlet', 'other')),
+    -- Receives batches created without an explicit location
+    is_default  BOOLEAN NOT NULL DEFAULT false,
+    is_active   BOOLEAN NOT NULL DEFAULT true,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_storage_locations_tenant_name
+    ON storage_locations (tenant_id, LOWER(name)) WHERE is_active = true;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_storage_locations_default
+    ON storage_locations (tenant_id) WHERE is_default = true;
+
+DROP TRIGGER IF EXISTS storage_locations_set_updated_at ON storage_locations;
+CREATE TRIGGER storage_locations_set_updated_at
+    BEFORE UPDATE ON storage_locations
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+ALTER TABLE inventory_batches
+ADD COLUMN IF NOT EXISTS location_id UUID REFERENCES storage_locations(id) ON DELETE RESTRICT;
+
+CREATE INDEX IF NOT EXISTS idx_inventory_batches_location
+    ON inventory_batches (tenant_id, location_id, catalog_ingredient_id)
+    WHERE status = 'active';
+
+-- Selling outlet of a dish sale (NULL → deducted from any location)
+ALTER TABLE dish_sales
+ADD COLUMN IF NOT EXISTS location_id UUID REFERENCES storage_locations(id) ON DELETE SET NULL;
+
+-- Backfill: one default location per tenant that already has stock
+INSERT INTO storage_locations (id, tenant_id, name, kind, is_default)
+SELECT gen_random_uuid(), t.tenant_id, 'Main storage', 'walk_in', true
+FROM (SELECT DISTINCT tenant_id FROM inventory_batches) t
+WHERE NOT EXISTS (
+    SELECT 1 FROM storage_locations sl WHERE sl.tenant_id = t.tenant_id AND sl.is_default
+);
+
+UPDATE inventory_batches ib
+SET location_id = sl.id
+FROM storage_locations sl
+WHERE sl.tenant_id = ib.tenant_id AND sl.is_default AND ib.location_id IS NULL;
+
+-- Transfer header; both movements reference it (reference_type = 'transfer')
+CREATE TABLE IF NOT EXISTS inventory_transfers (
+    id                      UUID PRIMARY KEY,
+    tenant_id               UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    from_location_id        UUID NOT NULL REFERENCES storage_locations(id),
+    to_location_id          UUID NOT NULL REFERENCES storage_locations(id),
+    catalog_ingredient_id   UUID NOT NULL REFERENCES catalog_ingredients(id),
+    quantity                NUMERIC(12, 3) NOT NULL CHECK (quantity > 0),
+    total_cost_cents        BIGINT NOT NULL DEFAULT 0,
+    user_id                 UUID NOT NULL,
+    note                    TEXT,
+    created_at              TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (from_location_id <> to_location_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_transfers_tenant
+    ON inventory_transfers (tenant_id, created_at DESC);
+
+ALTER TABLE inventory_movements DROP CONSTRAINT IF EXISTS inventory_movements_type_check;
+ALTER TABLE inventory_movements ADD CONSTRAINT inventory_movements_type_check
+    CHECK (type IN ('IN', 'OUT_SALE', 'OUT_EXPIRE', 'ADJUSTMENT', 'OUT', 'TRANSFER_OUT', 'TRANSFER_IN'));
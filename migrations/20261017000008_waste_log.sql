@@ -0,0 +1,35 @@
+-- Manual waste log (spoilage, breakage, staff meals, returned plates, ...)
+-- Each entry writes off stock FIFO (or from one batch) with OUT_WASTE movements
+-- referencing the entry; the loss report combines them with OUT_EXPIRE.
+
+CREATE TABLE IF NOT EXISTS waste_entries (
+    id                      UUID PRIMARY KEY,
+    tenant_id               UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    catalog_ingredient_id   UUID NOT NULL REFERENCES catalog_ingredients(id),
+    -- Set when a specific batch was written off instead of FIFO
+    batch_id                UUID REFERENCES inventory_batches(id) ON DELETE SET NULL,
+    location_id             UUID REFERENCES storage_locations(id) ON DELETE SET NULL,
+    reason                  TEXT NOT NULL
+                            CHECK (reason IN ('spoilage', 'expired', 'breakage', 'staff_meal',
+                                              'returned_plate', 'overproduction', 'other')),
+    quantity                NUMERIC(12, 3) NOT NULL CHECK (quantity > 0),
+    cost_cents              BIGINT NOT NULL DEFAULT 0,
+    photo_url               TEXT,
+    note                    TEXT,
+    -- Who logged it
+    user_id                 UUID NOT NULL,
+    created_at              TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_waste_entries_tenant_created
+    ON waste_entries (tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_waste_entries_tenant_reason
+    ON waste_entries (tenant_id, reason);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_movements_tenant_type_created
+    ON inventory_movements (tenant_id, type, created_at);
+
+ALTER TABLE inventory_movements DROP CONSTRAINT IF EXISTS inventory_movements_type_check;
+ALTER TABLE inventory_movements ADD CONSTRAINT inventory_movements_type_check
+    CHECK (type IN ('IN', 'OUT_SALE', 'OUT_EXPIRE', 'OUT_WASTE', 'ADJUSTMENT', 'OUT',
+                    'TRANSFER_OUT', 'TRANSFER_IN'));
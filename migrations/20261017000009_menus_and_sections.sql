@@ -0,0 +1,56 @@
+-- Menus (lunch, dinner, bar) with ordered sections and availability windows.
+-- Dishes are assigned to sections; menu engineering classifies each dish
+-- against the other dishes of its section (Kasavana–Smith).
+
+CREATE TABLE IF NOT EXISTS menus (
+    id              UUID PRIMARY KEY,
+    tenant_id       UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name            TEXT NOT NULL,
+    description     TEXT,
+    sort_order      INTEGER NOT NULL DEFAULT 0,
+    is_active       BOOLEAN NOT NULL DEFAULT true,
+    -- ISO weekdays (1 = Monday … 7 = Sunday); empty = every day
+    available_days  SMALLINT[] NOT NULL DEFAULT '{}',
+    -- Both NULL = all day; until < from wraps past midnight
+    available_from  TIME,
+    available_until TIME,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT menus_window_check
+        CHECK ((available_from IS NULL) = (available_until IS NULL))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_menus_tenant_name
+    ON menus (tenant_id, LOWER(name));
+
+DROP TRIGGER IF EXISTS menus_set_updated_at ON menus;
+CREATE TRIGGER menus_set_updated_at
+    BEFORE UPDATE ON menus
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+CREATE TABLE IF NOT EXISTS menu_sections (
+    id          UUID PRIMARY KEY,
+    tenant_id   UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    menu_id     UUID NOT NULL REFERENCES menus(id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    sort_order  INTEGER NOT NULL DEFAULT 0,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_menu_sections_menu_name
+    ON menu_sections (menu_id, LOWER(name));
+
+CREATE TABLE IF NOT EXISTS menu_section_dishes (
+    section_id  UUID NOT NULL REFERENCES menu_sections(id) ON DELETE CASCADE,
+    dish_id     UUID NOT NULL REFERENCES dishes(id) ON DELETE CASCADE,
+    tenant_id   UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    sort_order  INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (section_id, dish_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_menu_section_dishes_dish
+    ON menu_section_dishes (tenant_id, dish_id);
+
+-- Section the dish was sold from (NULL → the dish's first section)
+ALTER TABLE dish_sales
+ADD COLUMN IF NOT EXISTS menu_section_id UUID REFERENCES menu_sections(id) ON DELETE SET NULL;
@@ -0,0 +1,68 @@
+-- POS sales import: CSV uploads and signed webhooks.
+-- Tickets are de-duplicated by the POS ticket ID; ticket lines are mapped to
+-- dishes by PLU or item name, and lines that can't be booked wait in a
+-- review queue. Recipe cost is always computed server-side at sale time.
+
+-- Per-tenant webhook secret (HMAC-SHA256 over "timestamp.body").
+-- Webhook sales are attributed to the user who set the integration up.
+CREATE TABLE IF NOT EXISTS pos_integrations (
+    tenant_id       UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    webhook_secret  TEXT NOT NULL,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+DROP TRIGGER IF EXISTS pos_integrations_set_updated_at ON pos_integrations;
+CREATE TRIGGER pos_integrations_set_updated_at
+    BEFORE UPDATE ON pos_integrations
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- POS item → dish. `item_key` is "plu:<PLU>" or "name:<normalised name>".
+CREATE TABLE IF NOT EXISTS pos_item_mappings (
+    id          UUID PRIMARY KEY,
+    tenant_id   UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    item_key    TEXT NOT NULL,
+    plu         TEXT,
+    item_name   TEXT,
+    dish_id     UUID NOT NULL REFERENCES dishes(id) ON DELETE CASCADE,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT pos_item_mappings_tenant_key UNIQUE (tenant_id, item_key)
+);
+
+-- One row per imported ticket; the unique key makes re-delivery a no-op
+CREATE TABLE IF NOT EXISTS pos_tickets (
+    id                  UUID PRIMARY KEY,
+    tenant_id           UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    external_ticket_id  TEXT NOT NULL,
+    source              TEXT NOT NULL CHECK (source IN ('csv', 'webhook')),
+    sold_at             TIMESTAMPTZ NOT NULL,
+    location_id         UUID REFERENCES storage_locations(id) ON DELETE SET NULL,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT pos_tickets_tenant_external UNIQUE (tenant_id, external_ticket_id)
+);
+
+-- Ticket lines that weren't booked: unknown item, or booking failed
+CREATE TABLE IF NOT EXISTS pos_review_queue (
+    id                  UUID PRIMARY KEY,
+    tenant_id           UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    ticket_id           UUID NOT NULL REFERENCES pos_tickets(id) ON DELETE CASCADE,
+    item_key            TEXT NOT NULL,
+    plu                 TEXT,
+    item_name           TEXT NOT NULL,
+    quantity            INT NOT NULL CHECK (quantity > 0),
+    unit_price_cents    INT NOT NULL CHECK (unit_price_cents > 0),
+    reason              TEXT NOT NULL CHECK (reason IN ('unmapped', 'failed')),
+    error               TEXT,
+    status              TEXT NOT NULL DEFAULT 'pending'
+                        CHECK (status IN ('pending', 'booked', 'ignored')),
+    dish_id             UUID REFERENCES dishes(id) ON DELETE SET NULL,
+    resolved_at         TIMESTAMPTZ,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_pos_review_queue_pending
+    ON pos_review_queue (tenant_id, item_key) WHERE status = 'pending';
+
+ALTER TABLE dish_sales
+ADD COLUMN IF NOT EXISTS pos_ticket_id UUID REFERENCES pos_tickets(id) ON DELETE SET NULL;
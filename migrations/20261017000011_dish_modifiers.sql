@@ -0,0 +1,64 @@
+-- Dish modifiers: groups (Size, Extras, Without) with options that carry a
+-- price delta and a recipe delta (scale the base recipe, add or remove
+-- catalog ingredients). Sales keep a snapshot of the options chosen so menu
+-- engineering can report attach rates and modifier margin.
+
+CREATE TABLE IF NOT EXISTS dish_modifier_groups (
+    id          UUID PRIMARY KEY,
+    tenant_id   UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    dish_id     UUID NOT NULL REFERENCES dishes(id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    min_select  INTEGER NOT NULL DEFAULT 0,
+    max_select  INTEGER NOT NULL DEFAULT 1,
+    sort_order  INTEGER NOT NULL DEFAULT 0,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT dish_modifier_groups_select_check
+        CHECK (min_select >= 0 AND max_select >= 1 AND min_select <= max_select)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dish_modifier_groups_dish
+    ON dish_modifier_groups (tenant_id, dish_id);
+
+CREATE TABLE IF NOT EXISTS dish_modifier_options (
+    id                  UUID PRIMARY KEY,
+    tenant_id           UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    group_id            UUID NOT NULL REFERENCES dish_modifier_groups(id) ON DELETE CASCADE,
+    name                TEXT NOT NULL,
+    price_delta_cents   BIGINT NOT NULL DEFAULT 0,
+    -- Multiplies the whole base recipe (sizes); NULL = unchanged
+    quantity_scale      NUMERIC(6, 3) CHECK (quantity_scale > 0),
+    sort_order          INTEGER NOT NULL DEFAULT 0,
+    is_active           BOOLEAN NOT NULL DEFAULT true,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_dish_modifier_options_group
+    ON dish_modifier_options (group_id);
+
+-- Recipe delta of an option: 'add' a net quantity or 'remove' the ingredient
+CREATE TABLE IF NOT EXISTS dish_modifier_option_ingredients (
+    option_id               UUID NOT NULL REFERENCES dish_modifier_options(id) ON DELETE CASCADE,
+    catalog_ingredient_id   UUID NOT NULL REFERENCES catalog_ingredients(id),
+    action                  TEXT NOT NULL CHECK (action IN ('add', 'remove')),
+    quantity                NUMERIC(12, 3),
+    PRIMARY KEY (option_id, catalog_ingredient_id),
+    CONSTRAINT dish_modifier_option_ingredients_quantity_check
+        CHECK ((action = 'add' AND quantity > 0) OR (action = 'remove' AND quantity IS NULL))
+);
+
+-- Options chosen for a sale, with price and cost deltas per portion at sale time
+CREATE TABLE IF NOT EXISTS dish_sale_modifiers (
+    sale_id             UUID NOT NULL REFERENCES dish_sales(id) ON DELETE CASCADE,
+    option_id           UUID REFERENCES dish_modifier_options(id) ON DELETE SET NULL,
+    tenant_id           UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    group_name          TEXT NOT NULL,
+    option_name         TEXT NOT NULL,
+    quantity            INT NOT NULL CHECK (quantity > 0),
+    price_delta_cents   BIGINT NOT NULL,
+    cost_delta_cents    BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_dish_sale_modifiers_sale
+    ON dish_sale_modifiers (sale_id);
+CREATE INDEX IF NOT EXISTS idx_dish_sale_modifiers_tenant_option
+    ON dish_sale_modifiers (tenant_id, option_id);
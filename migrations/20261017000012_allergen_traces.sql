@@ -0,0 +1,19 @@
+-- "May contain" declarations for dish allergen labelling. A row targets
+-- either a catalog ingredient (supplier trace warning) or a recipe (kitchen
+-- cross-contact such as a shared fryer). Ingredient allergens themselves
+-- come from catalog_ingredients.allergens.
+
+CREATE TABLE IF NOT EXISTS allergen_trace_declarations (
+    id                      UUID PRIMARY KEY,
+    tenant_id               UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    catalog_ingredient_id   UUID REFERENCES catalog_ingredients(id) ON DELETE CASCADE,
+    recipe_id               UUID REFERENCES recipes(id) ON DELETE CASCADE,
+    allergen                allergen_type NOT NULL,
+    note                    TEXT,
+    created_at              TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT allergen_trace_declarations_target_check
+        CHECK ((catalog_ingredient_id IS NULL) <> (recipe_id IS NULL))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_allergen_trace_declarations_unique
+    ON allergen_trace_declarations (tenant_id, COALESCE(catalog_ingredient_id, recipe_id), allergen);
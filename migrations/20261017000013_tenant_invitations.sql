@@ -0,0 +1,25 @@
+-- Team invitations: an owner or manager invites a colleague by email with a
+-- role. Only the SHA-256 of the token is stored; the raw token is returned
+-- once at creation and travels in the invite link.
+
+CREATE TABLE IF NOT EXISTS tenant_invitations (
+    id              UUID PRIMARY KEY,
+    tenant_id       UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    email           TEXT NOT NULL,
+    role            TEXT NOT NULL CHECK (role IN ('manager', 'staff')),
+    token_hash      TEXT NOT NULL UNIQUE,
+    invited_by      UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at      TIMESTAMPTZ NOT NULL,
+    accepted_at     TIMESTAMPTZ,
+    accepted_by     UUID REFERENCES users(id) ON DELETE SET NULL,
+    revoked_at      TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_invitations_tenant
+    ON tenant_invitations (tenant_id, created_at DESC);
+
+-- One open invitation per email and tenant
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tenant_invitations_open
+    ON tenant_invitations (tenant_id, email)
+    WHERE accepted_at IS NULL AND revoked_at IS NULL;
@@ -0,0 +1,16 @@
+-- Refresh-token rotation: each refresh replaces the token with a successor
+-- in the same family (one family per signed-in device). A replaced token
+-- presented again is treated as stolen and revokes its whole family.
+
+ALTER TABLE refresh_tokens
+    ADD COLUMN IF NOT EXISTS family_id   UUID,
+    ADD COLUMN IF NOT EXISTS replaced_by UUID,
+    ADD COLUMN IF NOT EXISTS user_agent  TEXT,
+    ADD COLUMN IF NOT EXISTS ip_address  TEXT;
+
+-- Existing tokens each become their own session
+UPDATE refresh_tokens SET family_id = id WHERE family_id IS NULL;
+
+ALTER TABLE refresh_tokens ALTER COLUMN family_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_family_id ON refresh_tokens(family_id);
@@ -0,0 +1,19 @@
+-- Password reset and email verification. Tokens are single-use and
+-- time-limited; only their SHA-256 is stored, the raw token travels in the
+-- emailed link.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS user_action_tokens (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    purpose         TEXT NOT NULL CHECK (purpose IN ('password_reset', 'email_verification')),
+    token_hash      TEXT NOT NULL UNIQUE,
+    expires_at      TIMESTAMPTZ NOT NULL,
+    used_at         TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_action_tokens_user
+    ON user_action_tokens (user_id, purpose)
+    WHERE used_at IS NULL;
@@ -0,0 +1,39 @@
+-- Tenant-wide audit trail for business entities (dishes, prices, recipes,
+-- tenant ingredients, purchase drafts). Rows are append-only: updates are
+-- rejected by a trigger; deletes happen only through the retention purge
+-- (or tenant deletion).
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    id              UUID PRIMARY KEY,
+    tenant_id       UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- No FK: removing a user keeps their history
+    actor_id        UUID NOT NULL,
+    entity_type     TEXT NOT NULL,
+    entity_id       UUID NOT NULL,
+    action          TEXT NOT NULL,
+    -- {"field": {"before": .., "after": ..}} for the fields that changed
+    changes         JSONB NOT NULL DEFAULT '{}',
+    before          JSONB,
+    after           JSONB,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_tenant_time
+    ON audit_log (tenant_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_entity
+    ON audit_log (tenant_id, entity_type, entity_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created
+    ON audit_log (created_at);
+
+CREATE OR REPLACE FUNCTION audit_log_reject_update() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_log_no_update ON audit_log;
+CREATE TRIGGER audit_log_no_update
+    BEFORE UPDATE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_reject_update();
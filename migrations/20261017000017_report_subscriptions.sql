@@ -0,0 +1,54 @@
+-- Scheduled owner reports: per-tenant subscriptions (which report, when,
+-- to whom) and the history of every delivery with its rendered files, so a
+-- past digest can be downloaded again.
+
+CREATE TABLE IF NOT EXISTS report_subscriptions (
+    id              UUID PRIMARY KEY,
+    tenant_id       UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- Reports are built with this user's view of the data
+    created_by      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    report_kind     TEXT NOT NULL CHECK (report_kind IN
+                        ('summary', 'loss_report', 'menu_engineering', 'usage_variance')),
+    frequency       TEXT NOT NULL CHECK (frequency IN ('weekly', 'monthly')),
+    -- ISO weekday (1 = Monday) for weekly, day of month (1-28) for monthly
+    send_day        SMALLINT NOT NULL CHECK (send_day BETWEEN 1 AND 28),
+    send_hour_utc   SMALLINT NOT NULL CHECK (send_hour_utc BETWEEN 0 AND 23),
+    recipients      TEXT[] NOT NULL,
+    language        TEXT NOT NULL DEFAULT 'en',
+    active          BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at     TIMESTAMPTZ NOT NULL,
+    last_run_at     TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_report_subscriptions_tenant
+    ON report_subscriptions (tenant_id);
+
+CREATE INDEX IF NOT EXISTS idx_report_subscriptions_due
+    ON report_subscriptions (next_run_at) WHERE active;
+
+CREATE TABLE IF NOT EXISTS report_deliveries (
+    id              UUID PRIMARY KEY,
+    tenant_id       UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    -- Kept when the subscription is deleted
+    subscription_id UUID REFERENCES report_subscriptions(id) ON DELETE SET NULL,
+    report_kind     TEXT NOT NULL,
+    period_start    TIMESTAMPTZ NOT NULL,
+    period_end      TIMESTAMPTZ NOT NULL,
+    recipients      TEXT[] NOT NULL,
+    subject         TEXT NOT NULL,
+    -- 'sent' or 'failed' (rendering failed, or at least one recipient failed)
+    status          TEXT NOT NULL CHECK (status IN ('sent', 'failed')),
+    error           TEXT,
+    html_body       TEXT,
+    csv_data        BYTEA,
+    xlsx_data       BYTEA,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_report_deliveries_tenant_time
+    ON report_deliveries (tenant_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_report_deliveries_subscription
+    ON report_deliveries (subscription_id, created_at DESC);
@@ -0,0 +1,15 @@
+-- Copilot undo: every executed write action stores its compensating
+-- operation, and an undo is logged as its own row pointing at the action.
+--
+--   compensation → inverse operation recorded at execution time (NULL = irreversible)
+--   undone_at    → when the action was undone (status = 'undone')
+--   undo_of      → on an undo row: the action it reverted
+
+ALTER TABLE copilot_action_log
+    ADD COLUMN IF NOT EXISTS compensation JSONB,
+    ADD COLUMN IF NOT EXISTS undone_at    TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS undo_of      UUID REFERENCES copilot_action_log(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_copilot_log_undo_of
+    ON copilot_action_log (undo_of)
+    WHERE undo_of IS NOT NULL;
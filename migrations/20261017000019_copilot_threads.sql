@@ -0,0 +1,38 @@
+-- Copilot conversation memory: threads of turns per user within a tenant.
+--
+--   copilot_threads.summary  → rolling summary of turns with summarized = TRUE
+--   copilot_turns            → one user message + copilot answer each,
+--                              compacted tool results and the action plan
+--                              the turn proposed (if any)
+--
+-- The planner gets the summary plus the most recent turns, so follow-ups
+-- like "make it 20 kg instead" or "the second one" can be resolved.
+
+CREATE TABLE IF NOT EXISTS copilot_threads (
+    id               UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id        UUID        NOT NULL,
+    user_id          UUID        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    title            TEXT        NOT NULL DEFAULT '',
+    summary          TEXT,
+    turn_count       INT         NOT NULL DEFAULT 0,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_copilot_threads_user
+    ON copilot_threads (tenant_id, user_id, updated_at DESC);
+
+CREATE TABLE IF NOT EXISTS copilot_turns (
+    id               UUID        PRIMARY KEY DEFAULT gen_random_uuid(),
+    thread_id        UUID        NOT NULL REFERENCES copilot_threads(id) ON DELETE CASCADE,
+    seq              INT         NOT NULL,
+    user_message     TEXT        NOT NULL,
+    answer           TEXT        NOT NULL,
+    intent           TEXT        NOT NULL DEFAULT '',
+    used_tools       JSONB       NOT NULL DEFAULT '[]',
+    tool_results     JSONB       NOT NULL DEFAULT '[]',
+    action_id        UUID        REFERENCES copilot_action_log(id) ON DELETE SET NULL,
+    summarized       BOOLEAN     NOT NULL DEFAULT FALSE,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (thread_id, seq)
+);
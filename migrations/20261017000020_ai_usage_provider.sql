@@ -0,0 +1,10 @@
+-- LLM gateway: which provider and model answered each call.
+ALTER TABLE ai_usage_stats ADD COLUMN IF NOT EXISTS provider TEXT NOT NULL DEFAULT '';
+ALTER TABLE ai_usage_stats ADD COLUMN IF NOT EXISTS model TEXT NOT NULL DEFAULT '';
+
+-- Usage is logged without a site scope; attribute it to the kitchen site
+-- (the backfill value used when site_id was introduced).
+ALTER TABLE ai_usage_stats ALTER COLUMN site_id SET DEFAULT '00000000-0000-0000-0000-000000000103';
+
+CREATE INDEX IF NOT EXISTS idx_ai_usage_stats_provider_created
+    ON ai_usage_stats(provider, created_at DESC);
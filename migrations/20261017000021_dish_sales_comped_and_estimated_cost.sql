@@ -0,0 +1,12 @@
+-- Comped lines are sold at zero; a sale whose recipe could not be costed
+-- (an ingredient with no purchase price yet) is booked at the dish's last
+-- materialized cost and flagged.
+ALTER TABLE dish_sales DROP CONSTRAINT IF EXISTS dish_sales_selling_price_positive;
+ALTER TABLE dish_sales DROP CONSTRAINT IF EXISTS dish_sales_selling_price_non_negative;
+ALTER TABLE dish_sales ADD CONSTRAINT dish_sales_selling_price_non_negative
+    CHECK (selling_price_cents >= 0);
+
+ALTER TABLE dish_sales ADD COLUMN IF NOT EXISTS cost_estimated BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN dish_sales.cost_estimated IS
+    'recipe_cost_cents is the last known dish cost (or 0), not a cost at sale time';
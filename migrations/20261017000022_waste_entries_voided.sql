@@ -0,0 +1,8 @@
+-- Undo no longer deletes history: a voided waste entry stays in the log
+-- with voided_at set, and its OUT_WASTE movements are offset by ADJUSTMENT
+-- movements with reference_type = 'copilot_undo' and the same reference_id.
+ALTER TABLE waste_entries ADD COLUMN IF NOT EXISTS voided_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_inventory_movements_tenant_reference
+    ON inventory_movements (tenant_id, reference_id)
+    WHERE reference_id IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Copilot undo progress for multi-step plans: compensations are applied one
+-- at a time and the count is saved after each, so a failed undo resumes
+-- where it stopped instead of starting over.
+--
+--   undone_steps → compensation steps (in undo order) already applied
+--   status 'partially_undone' → some steps are reverted, the rest are not
+--                                (an undo stopped midway, or the rollback of
+--                                a failed plan could not revert every step)
+
+ALTER TABLE copilot_action_log
+    ADD COLUMN IF NOT EXISTS undone_steps INTEGER NOT NULL DEFAULT 0;
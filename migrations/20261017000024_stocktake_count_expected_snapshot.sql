@@ -0,0 +1,6 @@
+-- Book stock of the ingredient when it was first counted in the session.
+-- Closing measures the variance against it (not against stock at close
+-- time), so sales and receipts between counting and closing are not
+-- booked as shrinkage or surplus. NULL on lines posted before this column:
+-- those fall back to the stock at close.
+ALTER TABLE stocktake_counts ADD COLUMN IF NOT EXISTS expected_quantity NUMERIC;
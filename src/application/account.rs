@@ -0,0 +1,376 @@
+//! Account self-service over email: password reset and address verification.
+//!
+//! Both flows email a single-use, time-limited link. Only the SHA-256 of the
+//! token is stored in `user_action_tokens`; redeeming marks it used in the
+//! same statement that checks it, so a link cannot be used twice. Emails are
+//! written in the user's language through [`Mailer`], whatever the transport.
+
+use std::sync::Arc;
+
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::domain::{Email, Password};
+use crate::infrastructure::mail::{EmailMessage, Mailer};
+use crate::infrastructure::{PasswordHasher, RefreshTokenRepository, RefreshTokenRepositoryTrait};
+use crate::shared::{translate_email_text, AppError, AppResult, EmailText, Language, UserId};
+
+const RESET_TTL_HOURS: i64 = 1;
+const VERIFICATION_TTL_HOURS: i64 = 48;
+/// At most one reset email per user in this window, however often the form
+/// is submitted
+const RESET_COOLDOWN_SECS: i64 = 120;
+
+#[derive(Debug, Clone, Copy)]
+enum TokenPurpose {
+    PasswordReset,
+    EmailVerification,
+}
+
+impl TokenPurpose {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::PasswordReset => "password_reset",
+            Self::EmailVerification => "email_verification",
+        }
+    }
+
+    fn ttl_hours(self) -> i64 {
+        match self {
+            Self::PasswordReset => RESET_TTL_HOURS,
+            Self::EmailVerification => VERIFICATION_TTL_HOURS,
+        }
+    }
+
+    fn cooldown(self) -> Option<Duration> {
+        match self {
+            Self::PasswordReset => Some(Duration::seconds(RESET_COOLDOWN_SECS)),
+            Self::EmailVerification => None,
+        }
+    }
+
+    fn token_prefix(self) -> &'static str {
+        match self {
+            Self::PasswordReset => "rst_",
+            Self::EmailVerification => "vfy_",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordCommand {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordCommand {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailCommand {
+    pub token: String,
+}
+
+/// How `find_recipient` looks a user up
+enum RecipientKey<'a> {
+    Email(&'a str),
+    Id(UserId),
+}
+
+struct Recipient {
+    id: UserId,
+    email: String,
+    display_name: Option<String>,
+    language: Language,
+    verified: bool,
+}
+
+#[derive(Clone)]
+pub struct AccountService {
+    pool: PgPool,
+    mailer: Arc<dyn Mailer>,
+    password_hasher: PasswordHasher,
+    refresh_token_repo: RefreshTokenRepository,
+    /// Frontend base URL, links point at `/reset-password` and `/verify-email`
+    app_url: String,
+}
+
+impl AccountService {
+    pub fn new(
+        pool: PgPool,
+        mailer: Arc<dyn Mailer>,
+        password_hasher: PasswordHasher,
+        refresh_token_repo: RefreshTokenRepository,
+        app_url: String,
+    ) -> Self {
+        Self {
+            pool,
+            mailer,
+            password_hasher,
+            refresh_token_repo,
+            app_url,
+        }
+    }
+
+    /// Always succeeds for a well-formed address so the endpoint does not
+    /// reveal which emails have accounts. Lookup and delivery run in the
+    /// background, so the response time does not reveal it either. A new
+    /// request voids older links.
+    pub async fn request_password_reset(&self, command: ForgotPasswordCommand) -> AppResult<()> {
+        let email = Email::new(command.email)?;
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = service.send_password_reset(&email).await {
+                tracing::error!("Failed to send password reset email: {:?}", e);
+            }
+        });
+        Ok(())
+    }
+
+    async fn send_password_reset(&self, email: &Email) -> AppResult<()> {
+        let Some(user) = self
+            .find_recipient(RecipientKey::Email(email.as_str()))
+            .await?
+        else {
+            tracing::info!("Password reset requested for unknown email");
+            return Ok(());
+        };
+        self.send_link(&user, TokenPurpose::PasswordReset).await
+    }
+
+    /// Sets the new password and signs out every device. Following the link
+    /// also proves the address, so it counts as verified.
+    pub async fn reset_password(&self, command: ResetPasswordCommand) -> AppResult<()> {
+        let password = Password::new(command.new_password)?;
+        let password_hash = self.password_hasher.hash_password(password.as_str())?;
+
+        let mut tx = self.pool.begin().await?;
+        let user_id = consume_token(&mut tx, TokenPurpose::PasswordReset, &command.token).await?;
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET password_hash = $2, email_verified_at = COALESCE(email_verified_at, NOW())
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id.as_uuid())
+        .bind(password_hash)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.refresh_token_repo.revoke_all_for_user(user_id).await?;
+        tracing::info!("Password reset for user {}", user_id);
+        Ok(())
+    }
+
+    /// Email a verification link; voids links sent earlier
+    pub async fn send_verification(&self, user_id: UserId) -> AppResult<()> {
+        let user = self
+            .find_recipient(RecipientKey::Id(user_id))
+            .await?
+            .ok_or_else(|| AppError::not_found("User not found"))?;
+        if user.verified {
+            return Err(AppError::conflict("Email is already verified"));
+        }
+        self.send_link(&user, TokenPurpose::EmailVerification).await
+    }
+
+    pub async fn verify_email(&self, command: VerifyEmailCommand) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        let user_id =
+            consume_token(&mut tx, TokenPurpose::EmailVerification, &command.token).await?;
+        sqlx::query(
+            "UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1",
+        )
+        .bind(user_id.as_uuid())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn find_recipient(&self, key: RecipientKey<'_>) -> AppResult<Option<Recipient>> {
+        let query = match key {
+            RecipientKey::Email(email) => sqlx::query(
+                "SELECT id, email, display_name, language, email_verified_at FROM users \
+                 WHERE email = $1",
+            )
+            .bind(email),
+            RecipientKey::Id(id) => sqlx::query(
+                "SELECT id, email, display_name, language, email_verified_at FROM users \
+                 WHERE id = $1",
+            )
+            .bind(*id.as_uuid()),
+        };
+        let row = query.fetch_optional(&self.pool).await?;
+
+        Ok(row.map(|row| Recipient {
+            id: UserId::from_uuid(row.get("id")),
+            email: row.get("email"),
+            display_name: row.get("display_name"),
+            language: Language::from_code(row.get::<String, _>("language").as_str())
+                .unwrap_or_default(),
+            verified: row
+                .get::<Option<OffsetDateTime>, _>("email_verified_at")
+                .is_some(),
+        }))
+    }
+
+    async fn send_link(&self, user: &Recipient, purpose: TokenPurpose) -> AppResult<()> {
+        let token = generate_token(purpose);
+        let mut tx = self.pool.begin().await?;
+        if let Some(cooldown) = purpose.cooldown() {
+            // The row lock serialises concurrent requests for the same user
+            sqlx::query("SELECT id FROM users WHERE id = $1 FOR UPDATE")
+                .bind(user.id.as_uuid())
+                .execute(&mut *tx)
+                .await?;
+            let recent: bool = sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM user_action_tokens
+                    WHERE user_id = $1 AND purpose = $2 AND created_at > $3
+                )
+                "#,
+            )
+            .bind(user.id.as_uuid())
+            .bind(purpose.as_str())
+            .bind(OffsetDateTime::now_utc() - cooldown)
+            .fetch_one(&mut *tx)
+            .await?;
+            if recent {
+                tracing::info!("{} for user {} throttled", purpose.as_str(), user.id);
+                return Ok(());
+            }
+        }
+        sqlx::query(
+            r#"
+            UPDATE user_action_tokens SET used_at = NOW()
+            WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL
+            "#,
+        )
+        .bind(user.id.as_uuid())
+        .bind(purpose.as_str())
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO user_action_tokens (id, user_id, purpose, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user.id.as_uuid())
+        .bind(purpose.as_str())
+        .bind(hash_token(&token))
+        .bind(OffsetDateTime::now_utc() + Duration::hours(purpose.ttl_hours()))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let path = match purpose {
+            TokenPurpose::PasswordReset => "reset-password",
+            TokenPurpose::EmailVerification => "verify-email",
+        };
+        let link = format!("{}/{}?token={}", self.app_url, path, token);
+        self.mailer.send(&render_email(user, purpose, &link)).await
+    }
+}
+
+/// Marks the token used and returns its user, or fails if it is unknown,
+/// already used or expired
+async fn consume_token(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    purpose: TokenPurpose,
+    token: &str,
+) -> AppResult<UserId> {
+    let user_id: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE user_action_tokens SET used_at = NOW()
+        WHERE token_hash = $1 AND purpose = $2 AND used_at IS NULL AND expires_at > NOW()
+        RETURNING user_id
+        "#,
+    )
+    .bind(hash_token(token.trim()))
+    .bind(purpose.as_str())
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    user_id
+        .map(UserId::from_uuid)
+        .ok_or_else(|| AppError::validation("Link is invalid or has expired"))
+}
+
+fn render_email(user: &Recipient, purpose: TokenPurpose, link: &str) -> EmailMessage {
+    let t = |key| translate_email_text(key, user.language);
+    let (subject, intro, action, ignore) = match purpose {
+        TokenPurpose::PasswordReset => (
+            EmailText::ResetSubject,
+            EmailText::ResetIntro,
+            EmailText::ResetAction,
+            EmailText::ResetIgnore,
+        ),
+        TokenPurpose::EmailVerification => (
+            EmailText::VerifySubject,
+            EmailText::VerifyIntro,
+            EmailText::VerifyAction,
+            EmailText::VerifyIgnore,
+        ),
+    };
+    let greeting = match &user.display_name {
+        Some(name) => format!("{}, {}!", t(EmailText::Greeting), name),
+        None => format!("{}!", t(EmailText::Greeting)),
+    };
+    let expires = t(EmailText::LinkExpires).replace("{hours}", &purpose.ttl_hours().to_string());
+
+    let text_body = format!(
+        "{greeting}\n\n{}\n\n{}: {link}\n\n{expires}\n{}\n\n— {}\n",
+        t(intro),
+        t(action),
+        t(ignore),
+        t(EmailText::Signature),
+    );
+    let html_body = format!(
+        "<p>{}</p><p>{}</p><p><a href=\"{}\">{}</a></p><p>{}<br>{}</p><p>— {}</p>",
+        escape_html(&greeting),
+        t(intro),
+        escape_html(link),
+        t(action),
+        expires,
+        t(ignore),
+        t(EmailText::Signature),
+    );
+
+    EmailMessage {
+        to: user.email.clone(),
+        subject: t(subject).to_string(),
+        text_body,
+        html_body: Some(html_body),
+        attachments: Vec::new(),
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn generate_token(purpose: TokenPurpose) -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", purpose.token_prefix(), hex::encode(bytes))
+}
+
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
@@ -105,6 +105,7 @@ impl AssistantService {
                 .dish_service
                 .create_dish(
                     tenant_id,
+                    user_id,
                     recipe_id,
                     dish_name,
                     payload.description.clone(),
@@ -0,0 +1,364 @@
+//! Tenant-wide audit trail for business entities.
+//!
+//! Application services call [`AuditLog::record`] after a successful write
+//! with a JSON snapshot of the entity before and after. Only the fields that
+//! changed end up in `changes`; an update that changed nothing is not
+//! recorded. Recording never fails the write it describes — the write is
+//! already committed, so a failed insert is logged instead.
+//!
+//! Rows are append-only (a trigger rejects updates) and are purged after
+//! `AUDIT_RETENTION_DAYS` by the background job in `routes.rs`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{PgPool, Row};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::shared::{AppError, AppResult, TenantId, UserId};
+
+/// Default retention when `AUDIT_RETENTION_DAYS` is not set
+pub const DEFAULT_RETENTION_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntityType {
+    Dish,
+    Recipe,
+    TenantIngredient,
+    PurchaseDraft,
+}
+
+impl AuditEntityType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dish => "dish",
+            Self::Recipe => "recipe",
+            Self::TenantIngredient => "tenant_ingredient",
+            Self::PurchaseDraft => "purchase_draft",
+        }
+    }
+
+    fn parse(value: &str) -> AppResult<Self> {
+        match value {
+            "dish" => Ok(Self::Dish),
+            "recipe" => Ok(Self::Recipe),
+            "tenant_ingredient" => Ok(Self::TenantIngredient),
+            "purchase_draft" => Ok(Self::PurchaseDraft),
+            other => Err(AppError::internal(format!("Unknown audit entity: {other}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    /// An update that touched only the price, kept apart for filtering
+    PriceChange,
+    Publish,
+    Send,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::PriceChange => "price_change",
+            Self::Publish => "publish",
+            Self::Send => "send",
+        }
+    }
+
+    fn parse(value: &str) -> AppResult<Self> {
+        match value {
+            "create" => Ok(Self::Create),
+            "update" => Ok(Self::Update),
+            "delete" => Ok(Self::Delete),
+            "price_change" => Ok(Self::PriceChange),
+            "publish" => Ok(Self::Publish),
+            "send" => Ok(Self::Send),
+            other => Err(AppError::internal(format!("Unknown audit action: {other}"))),
+        }
+    }
+}
+
+/// One write to record
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub tenant_id: TenantId,
+    pub actor: UserId,
+    pub entity_type: AuditEntityType,
+    pub entity_id: Uuid,
+    pub action: AuditAction,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl AuditEvent {
+    pub fn new(
+        tenant_id: TenantId,
+        actor: UserId,
+        entity_type: AuditEntityType,
+        entity_id: Uuid,
+        action: AuditAction,
+    ) -> Self {
+        Self {
+            tenant_id,
+            actor,
+            entity_type,
+            entity_id,
+            action,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn before(mut self, snapshot: Value) -> Self {
+        self.before = Some(snapshot);
+        self
+    }
+
+    pub fn after(mut self, snapshot: Value) -> Self {
+        self.after = Some(snapshot);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub actor_id: Uuid,
+    /// Display name or email of the actor, if the user still exists
+    pub actor_name: Option<String>,
+    pub entity_type: AuditEntityType,
+    pub entity_id: Uuid,
+    pub action: AuditAction,
+    pub changes: Value,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditFilter {
+    pub entity_type: Option<AuditEntityType>,
+    pub entity_id: Option<Uuid>,
+    pub actor_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Clone)]
+pub struct AuditLog {
+    pool: PgPool,
+}
+
+impl AuditLog {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Append an event. Failures are logged, never returned.
+    pub async fn record(&self, event: AuditEvent) {
+        let changes = diff(
+            event.before.as_ref().unwrap_or(&Value::Null),
+            event.after.as_ref().unwrap_or(&Value::Null),
+        );
+        if event.action == AuditAction::Update && changes.as_object().is_some_and(Map::is_empty) {
+            return;
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO audit_log
+                (id, tenant_id, actor_id, entity_type, entity_id, action, changes, before, after)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(event.tenant_id.as_uuid())
+        .bind(event.actor.as_uuid())
+        .bind(event.entity_type.as_str())
+        .bind(event.entity_id)
+        .bind(event.action.as_str())
+        .bind(changes)
+        .bind(event.before)
+        .bind(event.after)
+        .execute(&self.pool)
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!(
+                "Failed to write audit log ({} {} {}): {}",
+                event.action.as_str(),
+                event.entity_type.as_str(),
+                event.entity_id,
+                e
+            );
+        }
+    }
+
+    /// Newest first
+    pub async fn list(
+        &self,
+        tenant_id: TenantId,
+        filter: AuditFilter,
+    ) -> AppResult<Vec<AuditEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT a.id, a.actor_id, COALESCE(u.display_name, u.email) AS actor_name,
+                   a.entity_type, a.entity_id, a.action, a.changes, a.before, a.after, a.created_at
+            FROM audit_log a
+            LEFT JOIN users u ON u.id = a.actor_id
+            WHERE a.tenant_id = $1
+              AND ($2::TEXT IS NULL OR a.entity_type = $2)
+              AND ($3::UUID IS NULL OR a.entity_id = $3)
+              AND ($4::UUID IS NULL OR a.actor_id = $4)
+              AND ($5::TEXT IS NULL OR a.action = $5)
+              AND ($6::TIMESTAMPTZ IS NULL OR a.created_at >= $6)
+              AND ($7::TIMESTAMPTZ IS NULL OR a.created_at < $7)
+            ORDER BY a.created_at DESC, a.id
+            LIMIT $8 OFFSET $9
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(filter.entity_type.map(|t| t.as_str()))
+        .bind(filter.entity_id)
+        .bind(filter.actor_id)
+        .bind(filter.action.map(|a| a.as_str()))
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.limit.unwrap_or(100).clamp(1, 500))
+        .bind(filter.offset.unwrap_or(0).max(0))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(AuditEntry {
+                    id: row.try_get("id")?,
+                    actor_id: row.try_get("actor_id")?,
+                    actor_name: row.try_get("actor_name")?,
+                    entity_type: AuditEntityType::parse(row.try_get("entity_type")?)?,
+                    entity_id: row.try_get("entity_id")?,
+                    action: AuditAction::parse(row.try_get("action")?)?,
+                    changes: row.try_get("changes")?,
+                    before: row.try_get("before")?,
+                    after: row.try_get("after")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Delete entries older than `retention_days`; returns how many
+    pub async fn purge_expired(&self, retention_days: i64) -> AppResult<u64> {
+        let result = sqlx::query(
+            "DELETE FROM audit_log WHERE created_at < NOW() - make_interval(days => $1)",
+        )
+        .bind(retention_days.clamp(1, i32::MAX as i64) as i32)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// `PriceChange` when `price_field` is the only field that changed, else `Update`
+pub fn update_action(before: &Value, after: &Value, price_field: &str) -> AuditAction {
+    let changes = diff(before, after);
+    match changes.as_object() {
+        Some(fields) if fields.len() == 1 && fields.contains_key(price_field) => {
+            AuditAction::PriceChange
+        }
+        _ => AuditAction::Update,
+    }
+}
+
+/// Field-level difference of two snapshots: `{"field": {"before", "after"}}`.
+/// A missing side (create/delete) counts as `null` for every field.
+pub fn diff(before: &Value, after: &Value) -> Value {
+    let fields = |value: &Value| match value {
+        Value::Object(map) => Some(map.clone()),
+        Value::Null => Some(Map::new()),
+        _ => None,
+    };
+
+    let (Some(old), Some(new)) = (fields(before), fields(after)) else {
+        if before == after {
+            return Value::Object(Map::new());
+        }
+        return serde_json::json!({ "value": { "before": before, "after": after } });
+    };
+
+    let mut changes = Map::new();
+    for key in old.keys().chain(new.keys()) {
+        if changes.contains_key(key) {
+            continue;
+        }
+        let was = old.get(key).unwrap_or(&Value::Null);
+        let now = new.get(key).unwrap_or(&Value::Null);
+        if was != now {
+            changes.insert(
+                key.clone(),
+                serde_json::json!({ "before": was, "after": now }),
+            );
+        }
+    }
+    Value::Object(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_keeps_only_changed_fields() {
+        let before = json!({ "name": "Soup", "price_cents": 1200, "active": true });
+        let after = json!({ "name": "Soup", "price_cents": 1400, "active": true });
+        assert_eq!(
+            diff(&before, &after),
+            json!({ "price_cents": { "before": 1200, "after": 1400 } })
+        );
+    }
+
+    #[test]
+    fn diff_of_create_lists_every_field() {
+        let after = json!({ "name": "Soup", "price_cents": 1200 });
+        let changes = diff(&Value::Null, &after);
+        assert_eq!(changes["name"], json!({ "before": null, "after": "Soup" }));
+        assert_eq!(changes.as_object().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn price_only_update_is_a_price_change() {
+        let before = json!({ "name": "Soup", "price_cents": 1200 });
+        let repriced = json!({ "name": "Soup", "price_cents": 1400 });
+        let renamed = json!({ "name": "Broth", "price_cents": 1400 });
+        assert_eq!(
+            update_action(&before, &repriced, "price_cents"),
+            AuditAction::PriceChange
+        );
+        assert_eq!(
+            update_action(&before, &renamed, "price_cents"),
+            AuditAction::Update
+        );
+    }
+
+    #[test]
+    fn diff_of_identical_snapshots_is_empty() {
+        let snapshot = json!({ "name": "Soup" });
+        assert_eq!(diff(&snapshot, &snapshot), json!({}));
+    }
+}
@@ -1,5 +1,7 @@
+use crate::application::account::AccountService;
 use crate::domain::{
-    DisplayName, Email, Password, RefreshToken, Tenant, TenantName, User, UserRole,
+    AuthSession, ClientInfo, DisplayName, Email, Password, RefreshToken, Tenant, TenantName, User,
+    UserRole,
 };
 use crate::infrastructure::{
     JwtService, PasswordHasher, RefreshTokenRepository, RefreshTokenRepositoryTrait,
@@ -9,6 +11,7 @@ use crate::shared::{AppError, AppResult, Language, TenantId, UserId};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct AuthService {
@@ -17,6 +20,7 @@ pub struct AuthService {
     refresh_token_repo: RefreshTokenRepository,
     password_hasher: PasswordHasher,
     jwt_service: JwtService,
+    account_service: AccountService,
 }
 
 impl AuthService {
@@ -26,6 +30,7 @@ impl AuthService {
         refresh_token_repo: RefreshTokenRepository,
         password_hasher: PasswordHasher,
         jwt_service: JwtService,
+        account_service: AccountService,
     ) -> Self {
         Self {
             user_repo,
@@ -33,10 +38,20 @@ impl AuthService {
             refresh_token_repo,
             password_hasher,
             jwt_service,
+            account_service,
         }
     }
 
-    pub async fn register(&self, command: RegisterCommand) -> AppResult<AuthResponse> {
+    /// Password reset and email verification
+    pub fn account(&self) -> &AccountService {
+        &self.account_service
+    }
+
+    pub async fn register(
+        &self,
+        command: RegisterCommand,
+        client: ClientInfo,
+    ) -> AppResult<AuthResponse> {
         // Validate input
         let email = Email::new(command.email)?;
         let password = Password::new(command.password)?;
@@ -67,31 +82,20 @@ impl AuthService {
         );
         self.user_repo.create(&user).await?;
 
-        // Generate tokens
-        let access_token = self
-            .jwt_service
-            .generate_access_token(user.id, user.tenant_id)?;
-        let refresh_token_str = self.jwt_service.generate_refresh_token();
-
-        // Hash refresh token for storage using SHA256
-        let mut hasher = Sha256::new();
-        hasher.update(refresh_token_str.as_bytes());
-        let refresh_token_hash = format!("{:x}", hasher.finalize());
-
-        let expires_at = OffsetDateTime::now_utc() + self.jwt_service.get_refresh_token_ttl();
-
-        let refresh_token = RefreshToken::new(user.id, refresh_token_hash, expires_at);
-        self.refresh_token_repo.create(&refresh_token).await?;
+        // The account works before the address is confirmed; a failed send
+        // can be retried from the profile
+        if let Err(e) = self.account_service.send_verification(user.id).await {
+            tracing::warn!("Failed to send verification email: {}", e);
+        }
 
-        Ok(AuthResponse {
-            access_token,
-            refresh_token: refresh_token_str,
-            user_id: user.id,
-            tenant_id: user.tenant_id,
-        })
+        self.issue_tokens(user.id, user.tenant_id, client).await
     }
 
-    pub async fn login(&self, command: LoginCommand) -> AppResult<AuthResponse> {
+    pub async fn login(
+        &self,
+        command: LoginCommand,
+        client: ClientInfo,
+    ) -> AppResult<AuthResponse> {
         // Validate input
         let email = Email::new(command.email)?;
         let password = Password::new(command.password)?;
@@ -117,45 +121,60 @@ impl AuthService {
             tracing::warn!("Failed to update login statistics: {}", e);
         }
 
-        // Generate tokens
-        let access_token = self
-            .jwt_service
-            .generate_access_token(user.id, user.tenant_id)?;
-        let refresh_token_str = self.jwt_service.generate_refresh_token();
+        self.issue_tokens(user.id, user.tenant_id, client).await
+    }
 
-        // Hash refresh token for storage using SHA256
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(refresh_token_str.as_bytes());
-        let refresh_token_hash = format!("{:x}", hasher.finalize());
+    /// Access token plus the first refresh token of a new session
+    pub async fn issue_tokens(
+        &self,
+        user_id: UserId,
+        tenant_id: TenantId,
+        client: ClientInfo,
+    ) -> AppResult<AuthResponse> {
+        if let Err(e) = self
+            .refresh_token_repo
+            .delete_expired_for_user(user_id)
+            .await
+        {
+            tracing::warn!("Failed to purge expired refresh tokens: {}", e);
+        }
 
+        let access_token = self.jwt_service.generate_access_token(user_id, tenant_id)?;
+        let refresh_token_str = self.jwt_service.generate_refresh_token();
         let expires_at = OffsetDateTime::now_utc() + self.jwt_service.get_refresh_token_ttl();
 
-        let refresh_token = RefreshToken::new(user.id, refresh_token_hash, expires_at);
+        let refresh_token = RefreshToken::new(
+            user_id,
+            hash_refresh_token(&refresh_token_str),
+            expires_at,
+            client,
+        );
         self.refresh_token_repo.create(&refresh_token).await?;
 
         Ok(AuthResponse {
             access_token,
             refresh_token: refresh_token_str,
-            user_id: user.id,
-            tenant_id: user.tenant_id,
+            user_id,
+            tenant_id,
         })
     }
 
-    pub async fn refresh(&self, command: RefreshCommand) -> AppResult<AuthResponse> {
-        // Hash the provided refresh token using SHA256 to match against stored hash
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(command.refresh_token.as_bytes());
-        let token_hash = format!("{:x}", hasher.finalize());
-
+    /// Exchange a refresh token for a new pair. The presented token is
+    /// spent; presenting a spent token again revokes the whole session.
+    pub async fn refresh(
+        &self,
+        command: RefreshCommand,
+        client: ClientInfo,
+    ) -> AppResult<AuthResponse> {
         let stored_token = self
             .refresh_token_repo
-            .find_by_token_hash(&token_hash)
+            .find_by_token_hash(&hash_refresh_token(&command.refresh_token))
             .await?
             .ok_or_else(|| AppError::authentication("Invalid refresh token"))?;
 
-        // Validate token
+        if stored_token.is_rotated() {
+            return Err(self.reuse_detected(&stored_token).await);
+        }
         if !stored_token.is_valid() {
             return Err(AppError::authentication("Refresh token expired or revoked"));
         }
@@ -167,18 +186,78 @@ impl AuthService {
             .await?
             .ok_or_else(|| AppError::authentication("User not found"))?;
 
-        // Generate new access token
+        let refresh_token_str = self.jwt_service.generate_refresh_token();
+        let expires_at = OffsetDateTime::now_utc() + self.jwt_service.get_refresh_token_ttl();
+        let next = stored_token.rotate(hash_refresh_token(&refresh_token_str), expires_at, client);
+
+        // Lost a race with another refresh of the same token: same as reuse.
+        // A failed insert rolls the rotation back, so the client can retry.
+        if !self
+            .refresh_token_repo
+            .rotate(stored_token.id, &next)
+            .await?
+        {
+            return Err(self.reuse_detected(&stored_token).await);
+        }
+
         let access_token = self
             .jwt_service
             .generate_access_token(user.id, user.tenant_id)?;
 
         Ok(AuthResponse {
             access_token,
-            refresh_token: command.refresh_token, // Return the same refresh token
+            refresh_token: refresh_token_str,
             user_id: user.id,
             tenant_id: user.tenant_id,
         })
     }
+
+    /// End the session the refresh token belongs to. Unknown tokens are
+    /// ignored so the endpoint reveals nothing.
+    pub async fn logout(&self, refresh_token: &str) -> AppResult<()> {
+        if let Some(token) = self
+            .refresh_token_repo
+            .find_by_token_hash(&hash_refresh_token(refresh_token))
+            .await?
+        {
+            self.refresh_token_repo
+                .revoke_family(token.family_id)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Sign out every device of the user
+    pub async fn logout_all(&self, user_id: UserId) -> AppResult<()> {
+        self.refresh_token_repo.revoke_all_for_user(user_id).await
+    }
+
+    pub async fn list_sessions(&self, user_id: UserId) -> AppResult<Vec<AuthSession>> {
+        self.refresh_token_repo.list_sessions(user_id).await
+    }
+
+    pub async fn revoke_session(&self, user_id: UserId, session_id: Uuid) -> AppResult<()> {
+        if !self
+            .refresh_token_repo
+            .revoke_family_for_user(user_id, session_id)
+            .await?
+        {
+            return Err(AppError::not_found("Session not found"));
+        }
+        Ok(())
+    }
+
+    async fn reuse_detected(&self, token: &RefreshToken) -> AppError {
+        tracing::warn!(
+            "Refresh token reuse detected: user={} family={}, revoking session",
+            token.user_id,
+            token.family_id
+        );
+        if let Err(e) = self.refresh_token_repo.revoke_family(token.family_id).await {
+            tracing::error!("Failed to revoke refresh token family: {}", e);
+        }
+        AppError::authentication("Refresh token was already used; please sign in again")
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -208,3 +287,10 @@ pub struct AuthResponse {
     pub user_id: UserId,
     pub tenant_id: TenantId,
 }
+
+/// Refresh tokens are stored as SHA-256 hex
+fn hash_refresh_token(token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
@@ -1,12 +1,12 @@
 //! CookSuggestionService — smart recipe suggestions from user's inventory.
 //!
 //! Architecture:
-//!   Inventory → ingredient names → Gemini (suggest dishes) → recipe_engine (resolve) → diff with inventory → classify
+//!   Inventory → ingredient names → LLM gateway (suggest dishes) → recipe_engine (resolve) → diff with inventory → classify
 //!
 //! Flow:
 //!   1. Load user's inventory (with details: name, category, quantity, expiry)
 //!   2. Build context: available ingredients, expiring items
-//!   3. Ask Gemini for 5-8 dish candidates based on available ingredients
+//!   3. Ask the LLM for 5-8 dish candidates based on available ingredients
 //!   4. For each dish: resolve via recipe_engine → get full TechCard
 //!   5. Diff TechCard ingredients vs inventory → missing count
 //!   6. Classify: can_cook (0 missing), almost (1-2 missing), strategic (smart picks)
@@ -19,6 +19,7 @@ use std::sync::Arc;
 use crate::application::inventory::{InventoryService, InventoryView};
 use crate::application::preferences_service::PreferencesService;
 use crate::domain::user_preferences::UserPreferences;
+use crate::infrastructure::llm::LlmRequest;
 use crate::infrastructure::llm_adapter::LlmAdapter;
 use crate::infrastructure::IngredientCache;
 use crate::shared::{AppResult, Language, TenantId, UserId};
@@ -516,14 +517,13 @@ Only suggest dishes where at least 60% of ingredients are available in stock."#,
             lang = lang_label,
         );
 
-        let raw = match self
-            .llm_adapter
-            .groq_raw_request_with_model(&prompt, 4000, "gemini-3-flash-preview")
-            .await
-        {
+        let request = LlmRequest::new("cook_suggestions", prompt)
+            .temperature(0.1)
+            .max_tokens(4000);
+        let raw = match self.llm_adapter.complete(request).await {
             Ok(r) => r,
             Err(e) => {
-                tracing::warn!("⚠️ Gemini dish candidates failed: {e}");
+                tracing::warn!("⚠️ LLM dish candidates failed: {e:?}");
                 return self.fallback_candidates(ctx);
             }
         };
@@ -635,14 +635,13 @@ Only suggest dishes where at least 60% of ingredients are available in stock."#,
             lang = lang_label,
         );
 
-        let raw = match self
-            .llm_adapter
-            .groq_raw_request_with_model(&prompt, 4000, "gemini-3-flash-preview")
-            .await
-        {
+        let request = LlmRequest::new("cook_suggestions_personalized", prompt)
+            .temperature(0.1)
+            .max_tokens(4000);
+        let raw = match self.llm_adapter.complete(request).await {
             Ok(r) => r,
             Err(e) => {
-                tracing::warn!("⚠️ Gemini dish candidates failed: {e}");
+                tracing::warn!("⚠️ LLM dish candidates failed: {e:?}");
                 return self.fallback_candidates(ctx);
             }
         };
@@ -36,6 +36,8 @@ pub enum ActionPlanType {
     SimulateLabProduct,
     CreateRecipe,
     CreateDish,
+    /// Несколько write шагов под одним подтверждением (см. `ActionPlan.steps`).
+    MultiStep,
     NoWriteAction,
 }
 
@@ -53,6 +55,24 @@ pub struct ActionPlan {
     pub write_tool: Option<CopilotTool>,
     /// Raw payload для write tool (сериализованные аргументы).
     pub payload: serde_json::Value,
+    /// Write шаги многошагового плана (`plan_type = MultiStep`).
+    /// Выполняются по порядку; при ошибке выполненные откатываются.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<ActionStep>,
+}
+
+/// Один write шаг многошагового ActionPlan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionStep {
+    /// Id шага из плана — на него ссылаются `$ref` следующих шагов.
+    pub id: String,
+    pub plan_type: ActionPlanType,
+    pub write_tool: CopilotTool,
+    /// Изменения шага (в `ActionPlan.changes` — изменения всех шагов подряд).
+    pub changes: Vec<ActionChange>,
+    /// Payload; может содержать `$ref` на предыдущие write шаги,
+    /// которые резолвятся только при выполнении.
+    pub payload: serde_json::Value,
 }
 
 impl ActionPlan {
@@ -63,6 +83,7 @@ impl ActionPlan {
             changes: vec![],
             write_tool: None,
             payload: serde_json::Value::Null,
+            steps: vec![],
         }
     }
 }
@@ -99,6 +120,8 @@ pub struct CopilotResponse {
     /// Immediate workspace/scene commands from the planner (geometry_op, spawn_shape, …).
     /// Frontend dispatches these without requiring confirmation.
     pub workspace_commands: Vec<serde_json::Value>,
+    /// Тред разговора — передать в следующем сообщении, чтобы продолжить его.
+    pub thread_id: Option<Uuid>,
 }
 
 impl CopilotResponse {
@@ -113,6 +136,7 @@ impl CopilotResponse {
             risk_level: RiskLevel::Low,
             billing_warning: None,
             workspace_commands: vec![],
+            thread_id: None,
         }
     }
 
@@ -127,6 +151,7 @@ impl CopilotResponse {
             risk_level: RiskLevel::Low,
             billing_warning: None,
             workspace_commands: vec![],
+            thread_id: None,
         }
     }
 
@@ -142,6 +167,7 @@ impl CopilotResponse {
             risk_level: RiskLevel::Low,
             billing_warning: None,
             workspace_commands: vec![],
+            thread_id: None,
         }
     }
 }
@@ -154,3 +180,14 @@ pub struct ConfirmResult {
     pub action_id: Uuid,
     pub executed_at: chrono::DateTime<chrono::Utc>,
 }
+
+/// Результат undo выполненного action.
+#[derive(Debug, Serialize)]
+pub struct UndoResult {
+    pub success: bool,
+    pub message: String,
+    pub action_id: Uuid,
+    /// Запись отмены в copilot_action_log (`undo_of` = action_id).
+    pub undo_id: Uuid,
+    pub undone_at: chrono::DateTime<chrono::Utc>,
+}
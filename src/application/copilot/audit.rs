@@ -6,6 +6,10 @@
 //!   executed             — выполнен
 //!   cancelled            — отменён пользователем
 //!   failed               — ошибка при выполнении
+//!   undone               — выполнен и затем отменён через undo
+//!   partially_undone     — откачена только часть шагов (`undone_steps`)
+//!
+//! Отмена (undo) пишется отдельной записью с `undo_of` = id исходного action.
 
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
@@ -15,6 +19,7 @@ use crate::shared::{AppResult, TenantId, UserId};
 
 use super::actions::ActionPlan;
 use super::context::CopilotScreen;
+use super::undo::Compensation;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
 #[sqlx(type_name = "text")]
@@ -25,6 +30,8 @@ pub enum AuditStatus {
     Executed,
     Cancelled,
     Failed,
+    Undone,
+    PartiallyUndone,
 }
 
 /// Запись в аудит-логе.
@@ -43,6 +50,13 @@ pub struct CopilotAuditEntry {
     pub confirmed_at: Option<time::OffsetDateTime>,
     pub executed_at: Option<time::OffsetDateTime>,
     pub created_at: time::OffsetDateTime,
+    /// Обратная операция, сохранённая при выполнении (None — необратимо).
+    pub compensation: Option<Compensation>,
+    pub undone_at: Option<time::OffsetDateTime>,
+    /// Сколько шагов компенсации (в порядке undo) уже применено.
+    pub undone_steps: usize,
+    /// Для записи-отмены: id отменённого action.
+    pub undo_of: Option<Uuid>,
 }
 
 pub struct CopilotAuditService {
@@ -116,7 +130,8 @@ impl CopilotAuditService {
         let row = sqlx::query_as::<_, CopilotAuditRow>(
             r#"SELECT id, user_id, tenant_id, screen, input_message, intent,
                       used_tools, action_payload, status,
-                      requires_confirmation, confirmed_at, executed_at, created_at
+                      requires_confirmation, confirmed_at, executed_at, created_at,
+                      compensation, undone_at, undone_steps, undo_of
                FROM copilot_action_log
                WHERE id = $1 AND user_id = $2"#,
         )
@@ -128,14 +143,123 @@ impl CopilotAuditService {
         Ok(row.map(row_to_entry))
     }
 
-    /// Обновить статус на executed.
-    pub async fn mark_executed(&self, action_id: Uuid) -> AppResult<()> {
+    /// Обновить статус на executed и сохранить компенсацию для undo.
+    pub async fn mark_executed(
+        &self,
+        action_id: Uuid,
+        compensation: Option<&Compensation>,
+    ) -> AppResult<()> {
+        let compensation = compensation.map(|c| serde_json::to_value(c).unwrap_or_default());
         sqlx::query(
             "UPDATE copilot_action_log \
-             SET status = 'executed', executed_at = NOW() \
+             SET status = 'executed', executed_at = NOW(), compensation = $2 \
              WHERE id = $1",
         )
         .bind(action_id)
+        .bind(compensation)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Сохранить компенсацию плана, чей откат после ошибки не удался
+    /// целиком: action становится partially_undone, и undo доводит откат.
+    pub async fn mark_partially_undone(
+        &self,
+        action_id: Uuid,
+        compensation: &Compensation,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE copilot_action_log \
+             SET status = 'partially_undone', executed_at = NOW(), compensation = $2, \
+                 undone_steps = 0 \
+             WHERE id = $1",
+        )
+        .bind(action_id)
+        .bind(serde_json::to_value(compensation).unwrap_or_default())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Занять action под undo: executed / partially_undone → undone.
+    /// Возвращает число уже откаченных шагов; None — уже отменён
+    /// (или отменяется параллельным запросом).
+    pub async fn claim_undo(&self, action_id: Uuid) -> AppResult<Option<usize>> {
+        let undone_steps: Option<i32> = sqlx::query_scalar(
+            "UPDATE copilot_action_log \
+             SET status = 'undone', undone_at = NOW() \
+             WHERE id = $1 AND status IN ('executed', 'partially_undone') \
+             RETURNING undone_steps",
+        )
+        .bind(action_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(undone_steps.map(|n| n.max(0) as usize))
+    }
+
+    /// Запомнить, сколько шагов уже откачено (после каждого шага).
+    pub async fn record_undo_progress(
+        &self,
+        action_id: Uuid,
+        undone_steps: usize,
+    ) -> AppResult<()> {
+        sqlx::query("UPDATE copilot_action_log SET undone_steps = $2 WHERE id = $1")
+            .bind(action_id)
+            .bind(undone_steps as i32)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Undo упал: ничего не откачено → снова executed, иначе
+    /// partially_undone — повторный undo продолжит с `undone_steps`.
+    pub async fn release_undo(&self, action_id: Uuid, undone_steps: usize) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE copilot_action_log \
+             SET status = CASE WHEN $2 > 0 THEN 'partially_undone' ELSE 'executed' END, \
+                 undone_steps = $2, undone_at = NULL \
+             WHERE id = $1 AND status = 'undone'",
+        )
+        .bind(action_id)
+        .bind(undone_steps as i32)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Записать саму отмену: отдельная запись с `undo_of`, статус
+    /// executed (компенсация применена) или failed (конфликт / ошибка).
+    pub async fn record_undo(
+        &self,
+        undo_id: Uuid,
+        user_id: UserId,
+        original: &CopilotAuditEntry,
+        result: Result<&str, &str>,
+    ) -> AppResult<()> {
+        let (status, outcome) = match result {
+            Ok(message) => ("executed", serde_json::json!({ "message": message })),
+            Err(error) => ("failed", serde_json::json!({ "error": error })),
+        };
+        let payload = serde_json::json!({
+            "compensation": original.compensation,
+            "outcome": outcome,
+        });
+
+        sqlx::query(
+            "INSERT INTO copilot_action_log \
+             (id, user_id, tenant_id, screen, input_message, intent, used_tools, action_payload, \
+              status, requires_confirmation, confirmed_at, executed_at, undo_of) \
+             VALUES ($1, $2, $3, $4, $5, 'undo', '[]', $6, $7, FALSE, NOW(), NOW(), $8)",
+        )
+        .bind(undo_id)
+        .bind(*user_id.as_uuid())
+        .bind(original.tenant_id)
+        .bind(&original.screen)
+        .bind(&original.input_message)
+        .bind(payload)
+        .bind(status)
+        .bind(original.id)
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -196,6 +320,10 @@ struct CopilotAuditRow {
     confirmed_at: Option<time::OffsetDateTime>,
     executed_at: Option<time::OffsetDateTime>,
     created_at: time::OffsetDateTime,
+    compensation: Option<serde_json::Value>,
+    undone_at: Option<time::OffsetDateTime>,
+    undone_steps: i32,
+    undo_of: Option<Uuid>,
 }
 
 fn row_to_entry(r: CopilotAuditRow) -> CopilotAuditEntry {
@@ -204,6 +332,8 @@ fn row_to_entry(r: CopilotAuditRow) -> CopilotAuditEntry {
         "executed" => AuditStatus::Executed,
         "cancelled" => AuditStatus::Cancelled,
         "failed" => AuditStatus::Failed,
+        "undone" => AuditStatus::Undone,
+        "partially_undone" => AuditStatus::PartiallyUndone,
         _ => AuditStatus::Planned,
     };
     let used_tools: Vec<String> = r
@@ -230,5 +360,9 @@ fn row_to_entry(r: CopilotAuditRow) -> CopilotAuditEntry {
         confirmed_at: r.confirmed_at,
         executed_at: r.executed_at,
         created_at: r.created_at,
+        compensation: r.compensation.and_then(|v| serde_json::from_value(v).ok()),
+        undone_at: r.undone_at,
+        undone_steps: r.undone_steps.max(0) as usize,
+        undo_of: r.undo_of,
     }
 }
@@ -1,31 +1,39 @@
 //! CopilotEngine — главный оркестратор.
 //!
 //! Flow:
-//!   1. Billing check (UsageService)
+//!   1. Rule planner: уверенный план по ключевым словам — бесплатно, без LLM.
+//!      Иначе billing check (UsageService); нет баланса — rule plan как free tier
 //!   2. Safety pre-check
-//!   3. Planner (LLM) → ToolPlan
+//!   3. Planner (LLM) → ToolPlan, с памятью треда; упал — rule plan
 //!   4. Execute read tools
 //!   5. Prepare ActionPlan (write tools → requires_confirmation)
 //!   6. Synthesize final answer (LLM или прямой ответ для simple read)
 //!   7. Audit log
-//!   8. Return CopilotResponse
+//!   8. Memory: записать ход, при необходимости свернуть старые в summary
+//!   9. Return CopilotResponse
+//!
+//! Undo: выполненный action можно отменить в течение окна
+//! (`COPILOT_UNDO_WINDOW_MINUTES`) через сохранённую компенсацию.
 
 use std::sync::Arc;
 
 use serde_json::json;
 
 use crate::application::usage_service::UsageService;
-use crate::infrastructure::gemini_service::GeminiService;
+use crate::infrastructure::llm::{LlmGateway, LlmRequest};
 use crate::shared::{AppError, AppResult};
 
-use super::actions::{ActionPlan, ConfirmResult, CopilotResponse};
+use super::actions::{ActionPlan, ConfirmResult, CopilotResponse, UndoResult};
 use super::audit::CopilotAuditService;
 use super::billing::{check_and_deduct, AiFeature};
 use super::context::CopilotContext;
-use super::planner::CopilotPlanner;
+use super::memory::{self, CopilotMemoryService, CopilotThread, CopilotThreadDetail, NewTurn};
+use super::planner::{CopilotPlanner, ToolPlan};
+use super::rule_planner;
 use super::safety;
 use super::tool_executor::{ToolExecutor, ToolExecutorServices};
 use super::tools::CopilotTool;
+use super::undo;
 
 #[derive(Debug, serde::Serialize)]
 pub struct CancelResult {
@@ -39,34 +47,106 @@ pub struct CopilotEngine {
     executor: ToolExecutor,
     usage: UsageService,
     audit: CopilotAuditService,
-    gemini: Arc<GeminiService>,
+    memory: CopilotMemoryService,
+    llm: Arc<dyn LlmGateway>,
+    undo_window_minutes: i64,
 }
 
 impl CopilotEngine {
     pub fn new(
-        gemini: Arc<GeminiService>,
+        llm: Arc<dyn LlmGateway>,
         services: ToolExecutorServices,
         usage: UsageService,
         audit: CopilotAuditService,
+        memory: CopilotMemoryService,
+        undo_window_minutes: i64,
     ) -> Self {
         Self {
-            planner: CopilotPlanner::new(gemini.clone()),
+            planner: CopilotPlanner::new(llm.clone()),
             executor: ToolExecutor::new(services),
             usage,
             audit,
-            gemini,
+            memory,
+            llm,
+            undo_window_minutes,
         }
     }
 
-    /// Главная точка входа — обрабатывает сообщение пользователя.
-    pub async fn handle_message(&self, ctx: &CopilotContext, message: &str) -> CopilotResponse {
-        match self.handle_inner(ctx, message).await {
+    /// Главная точка входа — обрабатывает сообщение пользователя в треде.
+    pub async fn handle_message(
+        &self,
+        ctx: &CopilotContext,
+        message: &str,
+        thread: &CopilotThread,
+    ) -> CopilotResponse {
+        let mut response = match self.handle_inner(ctx, message, thread).await {
             Ok(resp) => resp,
             Err(e) => {
                 tracing::error!("CopilotEngine error: {e}");
                 CopilotResponse::error(e.to_string())
             }
+        };
+        response.thread_id = Some(thread.id);
+        response
+    }
+
+    /// Продолжить тред пользователя или начать новый с этого сообщения.
+    pub async fn open_thread(
+        &self,
+        ctx: &CopilotContext,
+        thread_id: Option<uuid::Uuid>,
+        message: &str,
+    ) -> AppResult<CopilotThread> {
+        match thread_id {
+            Some(id) => self
+                .memory
+                .get_thread(id, ctx.user_id, ctx.tenant_id)
+                .await?
+                .ok_or_else(|| AppError::not_found("Conversation thread not found")),
+            None => {
+                self.memory
+                    .create_thread(ctx.user_id, ctx.tenant_id, &memory::thread_title(message))
+                    .await
+            }
+        }
+    }
+
+    pub async fn list_threads(
+        &self,
+        user_id: crate::shared::UserId,
+        tenant_id: crate::shared::TenantId,
+        limit: i64,
+    ) -> AppResult<Vec<CopilotThread>> {
+        self.memory.list_threads(user_id, tenant_id, limit).await
+    }
+
+    pub async fn get_thread(
+        &self,
+        user_id: crate::shared::UserId,
+        tenant_id: crate::shared::TenantId,
+        thread_id: uuid::Uuid,
+    ) -> AppResult<CopilotThreadDetail> {
+        self.memory
+            .thread_detail(thread_id, user_id, tenant_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Conversation thread not found"))
+    }
+
+    /// Удалить тред со всеми ходами. Записи в audit log остаются.
+    pub async fn delete_thread(
+        &self,
+        user_id: crate::shared::UserId,
+        tenant_id: crate::shared::TenantId,
+        thread_id: uuid::Uuid,
+    ) -> AppResult<()> {
+        if !self
+            .memory
+            .delete_thread(thread_id, user_id, tenant_id)
+            .await?
+        {
+            return Err(AppError::not_found("Conversation thread not found"));
         }
+        Ok(())
     }
 
     /// Подтвердить и выполнить action plan.
@@ -109,8 +189,10 @@ impl CopilotEngine {
             .await;
 
         match exec_result {
-            Ok(msg) => {
-                self.audit.mark_executed(action_id).await?;
+            Ok(outcome) => {
+                self.audit
+                    .mark_executed(action_id, outcome.compensation.as_ref())
+                    .await?;
                 tracing::info!(
                     "✅ Copilot confirm: action_id={} type={:?} user={}",
                     action_id,
@@ -119,13 +201,123 @@ impl CopilotEngine {
                 );
                 Ok(ConfirmResult {
                     success: true,
-                    message: msg,
+                    message: outcome.message,
                     action_id,
                     executed_at: chrono::Utc::now(),
                 })
             }
+            Err(failure) => {
+                match &failure.leftover {
+                    // Откат упавшего плана не прошёл целиком — доводится через undo
+                    Some(leftover) => {
+                        self.audit
+                            .mark_partially_undone(action_id, leftover)
+                            .await?
+                    }
+                    None => {
+                        self.audit
+                            .mark_failed(action_id, &failure.error.to_string())
+                            .await?
+                    }
+                }
+                Err(failure.error)
+            }
+        }
+    }
+
+    /// Отменить уже выполненный action через его компенсацию.
+    /// Проверяет окно отмены и что сущность не менялась (иначе 409).
+    /// Шаги применяются по одному, прогресс сохраняется после каждого;
+    /// partially_undone action продолжает с первого неоткаченного шага.
+    /// Каждая попытка пишется в лог отдельной записью с `undo_of`.
+    pub async fn undo_action(
+        &self,
+        ctx: &CopilotContext,
+        action_id: uuid::Uuid,
+    ) -> AppResult<UndoResult> {
+        let entry = self
+            .audit
+            .get(action_id, ctx.user_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Action not found or access denied"))?;
+
+        match entry.status {
+            super::audit::AuditStatus::Executed | super::audit::AuditStatus::PartiallyUndone => {}
+            super::audit::AuditStatus::Undone => {
+                return Err(AppError::validation("Action is already undone."))
+            }
+            _ => return Err(AppError::validation("Only executed actions can be undone.")),
+        }
+
+        let compensation = entry
+            .compensation
+            .clone()
+            .ok_or_else(|| AppError::validation("This action cannot be undone."))?;
+
+        undo::check_undo_window(
+            entry.executed_at,
+            time::OffsetDateTime::now_utc(),
+            self.undo_window_minutes,
+        )?;
+
+        // Те же права, что и на выполнение исходного плана
+        let plan: ActionPlan = serde_json::from_value(entry.action_payload.clone())
+            .map_err(|e| AppError::internal(format!("Invalid action payload: {e}")))?;
+        safety::validate_write_execution(ctx, &plan)?;
+
+        let Some(mut undone) = self.audit.claim_undo(action_id).await? else {
+            return Err(AppError::conflict("Action is already being undone."));
+        };
+
+        let undo_id = uuid::Uuid::new_v4();
+        let steps = compensation.undo_steps();
+        let mut messages = Vec::new();
+        let mut result = Ok(());
+        for step in steps.iter().skip(undone) {
+            match self
+                .executor
+                .execute_compensation(ctx.user_id, ctx.tenant_id, step)
+                .await
+            {
+                Ok(message) => {
+                    undone += 1;
+                    messages.push(message);
+                    if let Err(e) = self.audit.record_undo_progress(action_id, undone).await {
+                        result = Err(e);
+                        break;
+                    }
+                }
+                Err(e) => {
+                    result = Err(undo::partial_undo_error(e, undone, steps.len()));
+                    break;
+                }
+            }
+        }
+
+        match result.map(|()| messages.join("; ")) {
+            Ok(message) => {
+                self.audit
+                    .record_undo(undo_id, ctx.user_id, &entry, Ok(&message))
+                    .await?;
+                tracing::info!(
+                    "↩️ Copilot undo: action_id={} undo_id={} user={}",
+                    action_id,
+                    undo_id,
+                    *ctx.user_id.as_uuid(),
+                );
+                Ok(UndoResult {
+                    success: true,
+                    message,
+                    action_id,
+                    undo_id,
+                    undone_at: chrono::Utc::now(),
+                })
+            }
             Err(e) => {
-                self.audit.mark_failed(action_id, &e.to_string()).await?;
+                self.audit.release_undo(action_id, undone).await?;
+                self.audit
+                    .record_undo(undo_id, ctx.user_id, &entry, Err(&e.to_string()))
+                    .await?;
                 Err(e)
             }
         }
@@ -144,7 +336,10 @@ impl CopilotEngine {
             .await?
             .ok_or_else(|| AppError::not_found("Action plan not found or access denied"))?;
 
-        if entry.status == super::audit::AuditStatus::Executed {
+        if matches!(
+            entry.status,
+            super::audit::AuditStatus::Executed | super::audit::AuditStatus::Undone
+        ) {
             return Err(AppError::validation(
                 "Cannot cancel an already executed action.",
             ));
@@ -170,42 +365,66 @@ impl CopilotEngine {
         &self,
         ctx: &CopilotContext,
         message: &str,
+        thread: &CopilotThread,
     ) -> AppResult<CopilotResponse> {
-        // ── Step 1: Billing check ─────────────────────────────────────────────
-        let billing =
-            check_and_deduct(&self.usage, ctx.user_id.clone(), AiFeature::CopilotChat).await?;
-
-        if !billing.allowed {
-            return Ok(CopilotResponse::denied(
-                billing
-                    .deny_message
-                    .unwrap_or_else(|| "Insufficient AI actions.".to_string()),
-                billing.actions_left,
-            ));
-        }
-
-        // ── Step 2: Planner → ToolPlan ────────────────────────────────────────
-        let plan = match self.planner.plan(ctx, message).await {
-            Ok(p) => p,
-            Err(e) => {
-                tracing::warn!("Planner failed: {e}");
-                // Safety: if message looks like a write request, refuse with safe error
-                if looks_like_write_request(message) {
-                    return Ok(CopilotResponse::safe_error(
-                        "Could not prepare a safe action plan. No changes were made. Please try again.",
+        // ── Step 1: Rule planner / billing check ──────────────────────────────
+        // Уверенный rule plan не вызывает LLM и не списывает AI actions.
+        let rule_plan = rule_planner::plan(message);
+        let confident = rule_plan.as_ref().is_some_and(|p| !p.should_escalate());
+        let (plan, rule_based, cost, actions_left) = if let Some(rule) =
+            rule_plan.as_ref().filter(|_| confident)
+        {
+            tracing::debug!(
+                "Copilot rule plan: intent={} confidence={:.2}",
+                rule.plan.intent,
+                rule.confidence
+            );
+            let actions_left = self
+                .usage
+                .get_today(ctx.user_id)
+                .await
+                .map(|(_, balance)| balance.purchased_actions)
+                .unwrap_or(0);
+            (rule.plan.clone(), true, 0, actions_left)
+        } else {
+            let billing =
+                check_and_deduct(&self.usage, ctx.user_id, AiFeature::CopilotChat).await?;
+            if !billing.allowed {
+                // Free tier: без баланса — хотя бы то, что понятно без LLM
+                let Some(rule) = rule_plan else {
+                    return Ok(CopilotResponse::denied(
+                        billing
+                            .deny_message
+                            .unwrap_or_else(|| "Insufficient AI actions.".to_string()),
                         billing.actions_left,
                     ));
-                }
-                // Read/chat fallback is safe
-                super::planner::ToolPlan {
-                    intent: "general_question".to_string(),
-                    tools: vec![CopilotTool::GeneralChefAnswer],
-                    tool_calls: vec![super::planner::ToolCall {
-                        tool: CopilotTool::GeneralChefAnswer,
-                        args: std::collections::HashMap::new(),
-                    }],
-                    requires_confirmation: false,
-                    workspace_commands: vec![],
+                };
+                (rule.plan, true, 0, billing.actions_left)
+            } else {
+                // ── Step 2: Planner → ToolPlan ────────────────────────────────
+                match (self.plan_with_llm(ctx, message, thread).await, rule_plan) {
+                    (Ok(plan), _) => (plan, false, billing.cost, billing.actions_left),
+                    (Err(e), Some(rule)) => {
+                        tracing::warn!("Planner failed, using rule plan: {e}");
+                        (rule.plan, true, billing.cost, billing.actions_left)
+                    }
+                    (Err(e), None) => {
+                        tracing::warn!("Planner failed: {e}");
+                        // Safety: if message looks like a write request, refuse with safe error
+                        if looks_like_write_request(message) {
+                            return Ok(CopilotResponse::safe_error(
+                                "Could not prepare a safe action plan. No changes were made. Please try again.",
+                                billing.actions_left,
+                            ));
+                        }
+                        // Read/chat fallback is safe
+                        (
+                            general_answer_plan(),
+                            false,
+                            billing.cost,
+                            billing.actions_left,
+                        )
+                    }
                 }
             }
         };
@@ -217,12 +436,13 @@ impl CopilotEngine {
                 safety_result
                     .deny_reason
                     .unwrap_or_else(|| "Not allowed.".to_string()),
-                billing.actions_left,
+                actions_left,
             ));
         }
 
-        // ── Step 4: Execute read tools ────────────────────────────────────────
-        let mut tool_results = self.executor.run_read_tools(ctx, &plan.tool_calls).await;
+        // ── Step 4: Execute read tools (in order, with $ref bindings) ─────────
+        let read_phase = self.executor.run_read_tools(ctx, &plan.tool_calls).await;
+        let mut tool_results = read_phase.results;
         let used_tools: Vec<String> = plan.tools.iter().map(|t| t.name().to_string()).collect();
 
         // ── Step 5: Prepare ActionPlan for write tools ────────────────────────
@@ -230,7 +450,7 @@ impl CopilotEngine {
         let action_plan = if has_write {
             let (plan_opt, extra_result) = self
                 .executor
-                .prepare_action_plan(ctx, &plan.tool_calls, &tool_results)
+                .prepare_action_plan(ctx, &read_phase.write_calls, &tool_results)
                 .await;
             if let Some(extra) = extra_result {
                 tool_results.push(extra);
@@ -241,12 +461,16 @@ impl CopilotEngine {
         };
 
         // ── Step 6: Synthesize answer ─────────────────────────────────────────
-        let answer = self
-            .synthesize_answer(ctx, message, &plan.intent, &tool_results, &action_plan)
-            .await;
+        // Rule plan без write — ответ собирается из данных, без LLM
+        let answer = if rule_based && action_plan.is_none() {
+            rule_planner::render_answer(ctx.locale, &tool_results)
+        } else {
+            self.synthesize_answer(ctx, message, &plan.intent, &tool_results, &action_plan)
+                .await
+        };
 
         // ── Step 7: Audit log ─────────────────────────────────────────────────
-        let _audit_id = self
+        let audit_id = self
             .audit
             .record(
                 ctx.user_id.clone(),
@@ -264,11 +488,34 @@ impl CopilotEngine {
                 uuid::Uuid::nil()
             });
 
-        // ── Step 8: Response ──────────────────────────────────────────────────
-        let billing_warning = if billing.actions_left <= 5 {
+        // ── Step 8: Memory ────────────────────────────────────────────────────
+        // action_id только если запись в audit log есть (FK)
+        let action_id = action_plan
+            .as_ref()
+            .map(|p| p.id)
+            .filter(|id| *id == audit_id);
+        let turn = NewTurn {
+            user_message: message,
+            answer: &answer,
+            intent: &plan.intent,
+            used_tools: &used_tools,
+            tool_results: &tool_results,
+            action_id,
+        };
+        match self.memory.append_turn(thread.id, turn).await {
+            Ok(()) => {
+                if let Err(e) = self.summarize_thread(thread).await {
+                    tracing::warn!("Copilot thread summary failed (non-blocking): {e}");
+                }
+            }
+            Err(e) => tracing::warn!("Copilot memory append failed (non-blocking): {e}"),
+        }
+
+        // ── Step 9: Response ──────────────────────────────────────────────────
+        let billing_warning = if actions_left <= 5 {
             Some(format!(
                 "Low AI actions balance: {} remaining.",
-                billing.actions_left
+                actions_left
             ))
         } else {
             None
@@ -279,14 +526,61 @@ impl CopilotEngine {
             used_tools,
             requires_confirmation: action_plan.is_some(),
             action_plan,
-            actions_cost: billing.cost,
-            actions_left: billing.actions_left,
+            actions_cost: cost,
+            actions_left,
             risk_level: safety_result.risk_level,
             billing_warning,
             workspace_commands: plan.workspace_commands,
+            thread_id: Some(thread.id),
         })
     }
 
+    /// LLM planner с памятью треда (сбой загрузки памяти не блокирует).
+    async fn plan_with_llm(
+        &self,
+        ctx: &CopilotContext,
+        message: &str,
+        thread: &CopilotThread,
+    ) -> AppResult<ToolPlan> {
+        let conversation = self.memory.load_memory(thread).await.unwrap_or_else(|e| {
+            tracing::warn!("Copilot memory load failed (non-blocking): {e}");
+            memory::ConversationMemory::default()
+        });
+        self.planner.plan(ctx, message, &conversation).await
+    }
+
+    /// Свернуть старые ходы треда в summary, если их накопилось много.
+    /// Если LLM недоступен — summary собирается из сообщений пользователя.
+    async fn summarize_thread(&self, thread: &CopilotThread) -> AppResult<()> {
+        let turns = self.memory.unsummarized_turns(thread.id).await?;
+        let count = memory::turns_to_summarize(turns.len());
+        let Some(last) = turns[..count].last() else {
+            return Ok(());
+        };
+        let batch = &turns[..count];
+
+        let request = LlmRequest::new(
+            "copilot_summary",
+            memory::summary_prompt(thread.summary.as_deref(), batch),
+        )
+        .temperature(0.2)
+        .max_tokens(400);
+        let summary = match self.llm.complete(&request).await {
+            Ok(completion) if !completion.content.trim().is_empty() => {
+                memory::truncate(completion.content.trim(), memory::SUMMARY_CHARS)
+            }
+            Ok(_) => memory::fallback_summary(thread.summary.as_deref(), batch),
+            Err(e) => {
+                tracing::warn!("Copilot summary LLM call failed: {e}");
+                memory::fallback_summary(thread.summary.as_deref(), batch)
+            }
+        };
+
+        self.memory
+            .store_summary(thread.id, &summary, last.seq)
+            .await
+    }
+
     /// Синтезировать финальный текстовый ответ через LLM.
     async fn synthesize_answer(
         &self,
@@ -356,15 +650,12 @@ impl CopilotEngine {
             data = serde_json::to_string_pretty(&context_data).unwrap_or_default(),
         );
 
-        let request_body = json!({
-            "model": "gemini-3-flash-preview",
-            "messages": [{"role": "user", "content": synthesis_prompt}],
-            "temperature": 0.3,
-            "max_tokens": 1200
-        });
+        let request = LlmRequest::new("copilot_answer", synthesis_prompt)
+            .temperature(0.3)
+            .max_tokens(1200);
 
-        match self.gemini.send_raw_request(&request_body).await {
-            Ok(text) => text,
+        match self.llm.complete(&request).await {
+            Ok(completion) => completion.content,
             Err(e) => {
                 tracing::warn!("Synthesis LLM call failed: {e}");
                 // Fallback: краткое текстовое представление без LLM
@@ -409,6 +700,21 @@ fn limit_tool_data(tool_name: &str, data: &serde_json::Value) -> serde_json::Val
     data.clone()
 }
 
+/// План "просто ответь" — когда planner недоступен и запрос не про запись.
+fn general_answer_plan() -> ToolPlan {
+    ToolPlan {
+        intent: "general_question".to_string(),
+        tools: vec![CopilotTool::GeneralChefAnswer],
+        tool_calls: vec![super::planner::ToolCall {
+            id: "s1".to_string(),
+            tool: CopilotTool::GeneralChefAnswer,
+            args: std::collections::HashMap::new(),
+        }],
+        requires_confirmation: false,
+        workspace_commands: vec![],
+    }
+}
+
 /// Detect if user message looks like a write/mutation request.
 /// Used to decide safe fallback when planner fails.
 fn looks_like_write_request(message: &str) -> bool {
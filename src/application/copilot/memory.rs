@@ -0,0 +1,586 @@
+//! Conversation memory — треды диалога с Copilot-ом.
+//!
+//! Каждый ход (сообщение + ответ) пишется в copilot_turns вместе со сжатыми
+//! результатами tools и ссылкой на предложенный ActionPlan. Planner получает
+//! summary треда и последние `RECENT_TURNS` ходов — так резолвятся
+//! "сделай 20 кг" или "второй из списка".
+//!
+//! Когда несуммированных ходов больше `SUMMARIZE_AFTER_TURNS`, все кроме
+//! последних `RECENT_TURNS` сворачиваются в `copilot_threads.summary`.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::shared::{AppResult, TenantId, UserId};
+
+use super::tool_executor::ToolResult;
+
+/// Сколько последних ходов planner видит целиком.
+pub const RECENT_TURNS: usize = 6;
+/// Порог несуммированных ходов, после которого старые сворачиваются.
+pub const SUMMARIZE_AFTER_TURNS: usize = 12;
+
+/// Ограничения на размер того, что попадает в prompt и в БД.
+const TITLE_CHARS: usize = 80;
+const ANSWER_CHARS: usize = 400;
+const STRING_CHARS: usize = 200;
+const ARRAY_ITEMS: usize = 8;
+const TOOL_DATA_CHARS: usize = 800;
+const ACTION_CHARS: usize = 400;
+pub const SUMMARY_CHARS: usize = 2000;
+/// Данные tools показываются planner-у только для последних ходов.
+const TURNS_WITH_DATA: usize = 2;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CopilotThread {
+    pub id: Uuid,
+    pub title: String,
+    pub summary: Option<String>,
+    pub turn_count: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CopilotTurn {
+    pub id: Uuid,
+    pub seq: i32,
+    pub user_message: String,
+    pub answer: String,
+    pub intent: String,
+    pub used_tools: Vec<String>,
+    /// Сжатые результаты read tools: `[{ "tool", "data" }]`.
+    pub tool_results: Value,
+    /// ActionPlan, предложенный в этом ходе.
+    pub action_id: Option<Uuid>,
+    /// Текущий статус этого action (awaiting_confirmation, executed, …).
+    pub action_status: Option<String>,
+    #[serde(skip)]
+    pub action_payload: Option<Value>,
+    pub summarized: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CopilotThreadDetail {
+    #[serde(flatten)]
+    pub thread: CopilotThread,
+    pub turns: Vec<CopilotTurn>,
+}
+
+/// Новый ход для записи.
+pub struct NewTurn<'a> {
+    pub user_message: &'a str,
+    pub answer: &'a str,
+    pub intent: &'a str,
+    pub used_tools: &'a [String],
+    pub tool_results: &'a [ToolResult],
+    pub action_id: Option<Uuid>,
+}
+
+/// То, что planner знает о предыдущем разговоре.
+#[derive(Debug, Default)]
+pub struct ConversationMemory {
+    pub summary: Option<String>,
+    /// Последние ходы, от старых к новым.
+    pub turns: Vec<CopilotTurn>,
+}
+
+impl ConversationMemory {
+    pub fn is_empty(&self) -> bool {
+        self.summary.is_none() && self.turns.is_empty()
+    }
+
+    /// Блок для system prompt planner-а.
+    pub fn to_prompt(&self) -> String {
+        let mut out = String::new();
+        if let Some(summary) = &self.summary {
+            out.push_str(&format!("Summary of earlier turns: {}\n", summary));
+        }
+        let with_data_from = self.turns.len().saturating_sub(TURNS_WITH_DATA);
+        for (idx, turn) in self.turns.iter().enumerate() {
+            out.push_str(&format!(
+                "[{}] User: {}\n    Copilot: {}\n",
+                turn.seq,
+                turn.user_message,
+                truncate(&turn.answer, ANSWER_CHARS)
+            ));
+            if !turn.used_tools.is_empty() {
+                out.push_str(&format!("    Tools: {}\n", turn.used_tools.join(", ")));
+            }
+            if idx >= with_data_from && has_data(&turn.tool_results) {
+                out.push_str(&format!(
+                    "    Data: {}\n",
+                    truncate(&turn.tool_results.to_string(), TOOL_DATA_CHARS)
+                ));
+            }
+            if let Some(action) = describe_action(turn) {
+                out.push_str(&format!("    {}\n", action));
+            }
+        }
+        out
+    }
+}
+
+fn has_data(tool_results: &Value) -> bool {
+    tool_results.as_array().is_some_and(|a| !a.is_empty())
+}
+
+/// `Proposed action (awaiting_confirmation): {"tool": …, "args": …}`
+fn describe_action(turn: &CopilotTurn) -> Option<String> {
+    let payload = turn.action_payload.as_ref()?;
+    let steps: Vec<Value> = match payload.get("steps").and_then(|s| s.as_array()) {
+        Some(steps) if !steps.is_empty() => steps
+            .iter()
+            .map(|s| json!({ "tool": s["write_tool"], "args": s["payload"] }))
+            .collect(),
+        _ => vec![json!({ "tool": payload["write_tool"], "args": payload["payload"] })],
+    };
+    Some(format!(
+        "Proposed action ({}): {}",
+        turn.action_status.as_deref().unwrap_or("unknown"),
+        truncate(&Value::Array(steps).to_string(), ACTION_CHARS)
+    ))
+}
+
+/// Сжать результаты tools перед записью: длинные массивы и строки обрезаются.
+pub fn compact_tool_results(results: &[ToolResult]) -> Value {
+    Value::Array(
+        results
+            .iter()
+            .map(|r| json!({ "tool": r.tool_name, "data": compact_value(&r.data) }))
+            .collect(),
+    )
+}
+
+fn compact_value(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => {
+            Value::Array(items.iter().take(ARRAY_ITEMS).map(compact_value).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), compact_value(v)))
+                .collect(),
+        ),
+        Value::String(s) => Value::String(truncate(s, STRING_CHARS)),
+        other => other.clone(),
+    }
+}
+
+/// Сколько самых старых несуммированных ходов свернуть в summary.
+pub fn turns_to_summarize(unsummarized: usize) -> usize {
+    if unsummarized > SUMMARIZE_AFTER_TURNS {
+        unsummarized - RECENT_TURNS
+    } else {
+        0
+    }
+}
+
+/// Prompt для LLM-суммаризации.
+pub fn summary_prompt(previous: Option<&str>, turns: &[CopilotTurn]) -> String {
+    let transcript = turns
+        .iter()
+        .map(|t| {
+            format!(
+                "User: {}\nCopilot: {}",
+                t.user_message,
+                truncate(&t.answer, ANSWER_CHARS)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "Summarise this conversation between a restaurant team member and the ChefOS Copilot \
+         in at most 120 words, in English. Keep ingredient and dish names, quantities, prices \
+         and any actions that were proposed or applied. Plain text only.\n\
+         Earlier summary: {}\n\
+         Conversation:\n{}",
+        previous.unwrap_or("none"),
+        transcript
+    )
+}
+
+/// Summary без LLM: сообщения пользователя подряд, с ограничением длины.
+pub fn fallback_summary(previous: Option<&str>, turns: &[CopilotTurn]) -> String {
+    let asked = turns
+        .iter()
+        .map(|t| truncate(&t.user_message, STRING_CHARS))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let summary = match previous {
+        Some(previous) => format!("{} The user then asked: {}", previous, asked),
+        None => format!("The user asked: {}", asked),
+    };
+    // Старое обрезается первым — свежие запросы важнее
+    let chars: Vec<char> = summary.chars().collect();
+    if chars.len() > SUMMARY_CHARS {
+        format!(
+            "…{}",
+            chars[chars.len() - SUMMARY_CHARS..]
+                .iter()
+                .collect::<String>()
+        )
+    } else {
+        summary
+    }
+}
+
+/// Название треда по первому сообщению.
+pub fn thread_title(message: &str) -> String {
+    truncate(message.trim(), TITLE_CHARS)
+}
+
+pub fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        s.to_string()
+    } else {
+        format!("{}…", s.chars().take(max_chars).collect::<String>())
+    }
+}
+
+pub struct CopilotMemoryService {
+    pool: PgPool,
+}
+
+impl CopilotMemoryService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_thread(
+        &self,
+        user_id: UserId,
+        tenant_id: TenantId,
+        title: &str,
+    ) -> AppResult<CopilotThread> {
+        let row = sqlx::query_as::<_, ThreadRow>(
+            "INSERT INTO copilot_threads (tenant_id, user_id, title) VALUES ($1, $2, $3) \
+             RETURNING id, title, summary, turn_count, created_at, updated_at",
+        )
+        .bind(*tenant_id.as_uuid())
+        .bind(*user_id.as_uuid())
+        .bind(title)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.into())
+    }
+
+    /// Тред пользователя в рамках тенанта.
+    pub async fn get_thread(
+        &self,
+        thread_id: Uuid,
+        user_id: UserId,
+        tenant_id: TenantId,
+    ) -> AppResult<Option<CopilotThread>> {
+        let row = sqlx::query_as::<_, ThreadRow>(
+            "SELECT id, title, summary, turn_count, created_at, updated_at \
+             FROM copilot_threads WHERE id = $1 AND user_id = $2 AND tenant_id = $3",
+        )
+        .bind(thread_id)
+        .bind(*user_id.as_uuid())
+        .bind(*tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// Треды пользователя, свежие первыми.
+    pub async fn list_threads(
+        &self,
+        user_id: UserId,
+        tenant_id: TenantId,
+        limit: i64,
+    ) -> AppResult<Vec<CopilotThread>> {
+        let rows = sqlx::query_as::<_, ThreadRow>(
+            "SELECT id, title, summary, turn_count, created_at, updated_at \
+             FROM copilot_threads WHERE user_id = $1 AND tenant_id = $2 \
+             ORDER BY updated_at DESC LIMIT $3",
+        )
+        .bind(*user_id.as_uuid())
+        .bind(*tenant_id.as_uuid())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Тред со всеми ходами.
+    pub async fn thread_detail(
+        &self,
+        thread_id: Uuid,
+        user_id: UserId,
+        tenant_id: TenantId,
+    ) -> AppResult<Option<CopilotThreadDetail>> {
+        let Some(thread) = self.get_thread(thread_id, user_id, tenant_id).await? else {
+            return Ok(None);
+        };
+        let turns = self.turns(thread_id, i64::MAX, false).await?;
+        Ok(Some(CopilotThreadDetail { thread, turns }))
+    }
+
+    pub async fn delete_thread(
+        &self,
+        thread_id: Uuid,
+        user_id: UserId,
+        tenant_id: TenantId,
+    ) -> AppResult<bool> {
+        let rows = sqlx::query(
+            "DELETE FROM copilot_threads WHERE id = $1 AND user_id = $2 AND tenant_id = $3",
+        )
+        .bind(thread_id)
+        .bind(*user_id.as_uuid())
+        .bind(*tenant_id.as_uuid())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(rows == 1)
+    }
+
+    /// Summary + последние ходы для planner-а.
+    pub async fn load_memory(&self, thread: &CopilotThread) -> AppResult<ConversationMemory> {
+        let turns = self.turns(thread.id, RECENT_TURNS as i64, false).await?;
+        Ok(ConversationMemory {
+            summary: thread.summary.clone(),
+            turns,
+        })
+    }
+
+    /// Ходы, ещё не свёрнутые в summary, от старых к новым.
+    pub async fn unsummarized_turns(&self, thread_id: Uuid) -> AppResult<Vec<CopilotTurn>> {
+        self.turns(thread_id, i64::MAX, true).await
+    }
+
+    /// Последние `limit` ходов треда, от старых к новым.
+    async fn turns(
+        &self,
+        thread_id: Uuid,
+        limit: i64,
+        only_unsummarized: bool,
+    ) -> AppResult<Vec<CopilotTurn>> {
+        let rows = sqlx::query_as::<_, TurnRow>(
+            r#"SELECT t.id, t.seq, t.user_message, t.answer, t.intent, t.used_tools,
+                      t.tool_results, t.action_id, t.summarized, t.created_at,
+                      a.status AS action_status, a.action_payload
+               FROM copilot_turns t
+               LEFT JOIN copilot_action_log a ON a.id = t.action_id
+               WHERE t.thread_id = $1 AND (NOT $3 OR NOT t.summarized)
+               ORDER BY t.seq DESC
+               LIMIT $2"#,
+        )
+        .bind(thread_id)
+        .bind(limit)
+        .bind(only_unsummarized)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().rev().map(Into::into).collect())
+    }
+
+    /// Дописать ход в конец треда.
+    pub async fn append_turn(&self, thread_id: Uuid, turn: NewTurn<'_>) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        let seq: i32 = sqlx::query_scalar(
+            "UPDATE copilot_threads SET turn_count = turn_count + 1, updated_at = NOW() \
+             WHERE id = $1 RETURNING turn_count",
+        )
+        .bind(thread_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO copilot_turns \
+             (thread_id, seq, user_message, answer, intent, used_tools, tool_results, action_id) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(thread_id)
+        .bind(seq)
+        .bind(turn.user_message)
+        .bind(turn.answer)
+        .bind(turn.intent)
+        .bind(serde_json::to_value(turn.used_tools).unwrap_or_default())
+        .bind(compact_tool_results(turn.tool_results))
+        .bind(turn.action_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Сохранить summary и пометить ходы до `up_to_seq` включительно свёрнутыми.
+    pub async fn store_summary(
+        &self,
+        thread_id: Uuid,
+        summary: &str,
+        up_to_seq: i32,
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE copilot_threads SET summary = $2 WHERE id = $1")
+            .bind(thread_id)
+            .bind(summary)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "UPDATE copilot_turns SET summarized = TRUE WHERE thread_id = $1 AND seq <= $2",
+        )
+        .bind(thread_id)
+        .bind(up_to_seq)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+// ── DB row types ─────────────────────────────────────────────────────────────
+
+#[derive(sqlx::FromRow)]
+struct ThreadRow {
+    id: Uuid,
+    title: String,
+    summary: Option<String>,
+    turn_count: i32,
+    created_at: time::OffsetDateTime,
+    updated_at: time::OffsetDateTime,
+}
+
+impl From<ThreadRow> for CopilotThread {
+    fn from(r: ThreadRow) -> Self {
+        CopilotThread {
+            id: r.id,
+            title: r.title,
+            summary: r.summary,
+            turn_count: r.turn_count,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TurnRow {
+    id: Uuid,
+    seq: i32,
+    user_message: String,
+    answer: String,
+    intent: String,
+    used_tools: Value,
+    tool_results: Value,
+    action_id: Option<Uuid>,
+    summarized: bool,
+    created_at: time::OffsetDateTime,
+    action_status: Option<String>,
+    action_payload: Option<Value>,
+}
+
+impl From<TurnRow> for CopilotTurn {
+    fn from(r: TurnRow) -> Self {
+        CopilotTurn {
+            id: r.id,
+            seq: r.seq,
+            user_message: r.user_message,
+            answer: r.answer,
+            intent: r.intent,
+            used_tools: serde_json::from_value(r.used_tools).unwrap_or_default(),
+            tool_results: r.tool_results,
+            action_id: r.action_id,
+            action_status: r.action_status,
+            action_payload: r.action_payload.filter(|p| !p.is_null()),
+            summarized: r.summarized,
+            created_at: r.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn turn(seq: i32, user_message: &str) -> CopilotTurn {
+        CopilotTurn {
+            id: Uuid::nil(),
+            seq,
+            user_message: user_message.to_string(),
+            answer: "Done.".to_string(),
+            intent: "inventory_view".to_string(),
+            used_tools: vec![],
+            tool_results: json!([]),
+            action_id: None,
+            action_status: None,
+            action_payload: None,
+            summarized: false,
+            created_at: datetime!(2026-10-17 12:00 UTC),
+        }
+    }
+
+    #[test]
+    fn summarises_all_but_the_recent_turns_past_the_threshold() {
+        assert_eq!(turns_to_summarize(SUMMARIZE_AFTER_TURNS), 0);
+        assert_eq!(
+            turns_to_summarize(SUMMARIZE_AFTER_TURNS + 1),
+            SUMMARIZE_AFTER_TURNS + 1 - RECENT_TURNS
+        );
+    }
+
+    #[test]
+    fn prompt_shows_pending_action_and_recent_data() {
+        let mut first = turn(1, "Show inventory");
+        first.used_tools = vec!["get_inventory".to_string()];
+        first.tool_results = json!([{ "tool": "get_inventory", "data": { "inventory": [] } }]);
+
+        let mut second = turn(2, "Write off 10 kg of salmon");
+        second.action_id = Some(Uuid::nil());
+        second.action_status = Some("awaiting_confirmation".to_string());
+        second.action_payload = Some(json!({
+            "write_tool": "write_off_inventory",
+            "payload": { "ingredient_name": "salmon", "quantity": 10, "unit": "kg" },
+        }));
+
+        let memory = ConversationMemory {
+            summary: Some("The user manages a sushi bar.".to_string()),
+            turns: vec![first, second],
+        };
+        let prompt = memory.to_prompt();
+
+        assert!(prompt.starts_with("Summary of earlier turns: The user manages a sushi bar."));
+        assert!(prompt.contains("[2] User: Write off 10 kg of salmon"));
+        assert!(prompt.contains("Tools: get_inventory"));
+        assert!(prompt.contains(r#"Data: [{"data":{"inventory":[]},"tool":"get_inventory"}]"#));
+        assert!(prompt.contains("Proposed action (awaiting_confirmation): [{"));
+        assert!(prompt.contains(r#""quantity":10"#));
+    }
+
+    #[test]
+    fn tool_results_are_compacted() {
+        let results = [ToolResult {
+            tool_name: "get_inventory".to_string(),
+            data: json!({ "inventory": (0..20).collect::<Vec<_>>(), "note": "x".repeat(500) }),
+        }];
+        let compact = compact_tool_results(&results);
+        assert_eq!(
+            compact[0]["data"]["inventory"].as_array().unwrap().len(),
+            ARRAY_ITEMS
+        );
+        assert_eq!(
+            compact[0]["data"]["note"].as_str().unwrap().chars().count(),
+            STRING_CHARS + 1
+        );
+    }
+
+    #[test]
+    fn fallback_summary_keeps_the_latest_requests() {
+        let turns: Vec<CopilotTurn> = (1..=40)
+            .map(|i| turn(i, &format!("request number {} {}", i, "y".repeat(100))))
+            .collect();
+        let summary = fallback_summary(Some("Earlier."), &turns);
+        assert_eq!(summary.chars().count(), SUMMARY_CHARS + 1);
+        assert!(summary.contains("request number 40"));
+        assert!(!summary.contains("Earlier."));
+    }
+}
@@ -2,30 +2,45 @@
 //!
 //! Architecture:
 //!   POST /api/copilot/message
-//!     → CopilotEngine::handle_message(context, message)
-//!       → billing check (UsageService)
-//!       → Planner (LLM) → ToolPlan
+//!     → CopilotEngine::handle_message(context, message, thread)
+//!       → RulePlanner (keywords, no LLM) → confident? free ToolPlan
+//!       → otherwise: billing check (UsageService)
+//!       → Memory::load(thread) → summary + recent turns
+//!       → Planner (LLM) → ToolPlan   (rule plan as fallback)
 //!       → SafetyLayer::validate(plan)
-//!       → ToolExecutor::run_read_tools(plan)
-//!       → build ActionPlan (write tools → requires_confirmation)
+//!       → ToolExecutor::run_read_tools(plan)   (read steps in order, $ref bindings)
+//!       → build ActionPlan (all write steps → one confirmation)
 //!       → AuditLog::record(pending)
-//!       → CopilotResponse { answer, action_plan, actions_cost, ... }
+//!       → Memory::append_turn (+ summarise older turns)
+//!       → CopilotResponse { answer, action_plan, actions_cost, thread_id, ... }
 //!
 //!   POST /api/copilot/actions/{action_id}/confirm
 //!     → AuditLog::get(action_id) → ActionPlan
 //!     → SafetyLayer::validate_write(plan)
-//!     → ToolExecutor::run_write_tool(plan)
-//!     → AuditLog::mark_executed(action_id)
+//!     → ToolExecutor::run_write_tool(plan)   (multi-step: rollback on failure)
+//!     → AuditLog::mark_executed(action_id, compensation)
+//!       (rollback incomplete: mark_partially_undone(action_id, leftover))
+//!
+//!   POST /api/copilot/actions/{action_id}/undo
+//!     → check undo window + entity unchanged since execution
+//!     → ToolExecutor::execute_compensation(step) per Compensation::undo_steps,
+//!       AuditLog::record_undo_progress after each (resumes from undone_steps)
+//!     → AuditLog::record_undo(action_id)
 //!
 //! Modules:
 //!   context        — CopilotContext, CopilotScreen
 //!   tools          — CopilotTool enum (read/write split)
 //!   planner        — LLM prompt → ToolPlan (JSON structured output)
+//!   rule_planner   — keyword ToolPlan without LLM (free tier + fallback)
+//!   rule_keywords  — RU/EN/PL/UK keyword tables for rule_planner
+//!   plan_graph     — plan steps with $ref bindings to earlier step outputs
 //!   tool_executor  — dispatches tools → real backend services
-//!   actions        — ActionPlan, ActionChange, ConfirmResult
+//!   actions        — ActionPlan, ActionChange, ConfirmResult, UndoResult
 //!   safety         — validates permissions, write guards
 //!   billing        — AiFeature costs, integration with UsageService
 //!   audit          — copilot_action_log table CRUD
+//!   memory         — conversation threads (copilot_threads / copilot_turns)
+//!   undo           — Compensation (inverse of a write action), undo window
 //!   engine         — orchestrates all of the above
 
 pub mod actions;
@@ -33,13 +48,20 @@ pub mod audit;
 pub mod billing;
 pub mod context;
 pub mod engine;
+pub mod memory;
+pub mod plan_graph;
 pub mod planner;
+pub mod rule_keywords;
+pub mod rule_planner;
 pub mod safety;
 pub mod tool_executor;
 pub mod tools;
+pub mod undo;
 
-pub use actions::{ActionPlan, ConfirmResult, CopilotResponse};
+pub use actions::{ActionPlan, ActionStep, ConfirmResult, CopilotResponse, UndoResult};
 pub use audit::CopilotAuditService;
 pub use billing::AiFeature;
 pub use context::{CopilotContext, CopilotScreen};
 pub use engine::CopilotEngine;
+pub use memory::CopilotMemoryService;
+pub use undo::Compensation;
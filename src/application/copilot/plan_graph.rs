@@ -0,0 +1,287 @@
+//! Plan graph — многошаговые планы со ссылками на результаты шагов.
+//!
+//! Planner может вернуть `steps`: каждый шаг — id + tool + args, где любое
+//! значение аргумента может быть ссылкой на выход предыдущего шага:
+//!   {"$ref": "s1.expiring.0.name"}   → одно значение
+//!   {"$ref": "s1.expiring.*.name"}   → массив по всем элементам
+//!
+//! Read шаги выполняются сразу и по порядку, их данные подставляются в
+//! аргументы следующих шагов. Write шаги собираются в один ActionPlan.
+//! Ссылка на write шаг (например id созданного рецепта) остаётся
+//! отложенной до выполнения — тогда выходом шага становится его payload
+//! плюс то, что вернул сам write tool.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use crate::shared::{AppError, AppResult};
+
+use super::planner::{ToolArgs, ToolCall};
+
+/// Ключ ссылки в аргументах шага.
+pub const REF_KEY: &str = "$ref";
+
+/// Цель ссылки, если значение — `{"$ref": "<step>.<path>"}`.
+fn ref_target(value: &Value) -> Option<&str> {
+    let obj = value.as_object()?;
+    if obj.len() != 1 {
+        return None;
+    }
+    obj.get(REF_KEY)?.as_str()
+}
+
+/// `"s1.expiring.0.name"` → `("s1", ["expiring", "0", "name"])`.
+fn split_ref(target: &str) -> (&str, Vec<&str>) {
+    let mut parts = target.split('.').filter(|p| !p.is_empty());
+    let step = parts.next().unwrap_or("");
+    (step, parts.collect())
+}
+
+fn collect_refs<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    if let Some(target) = ref_target(value) {
+        out.push(target);
+        return;
+    }
+    match value {
+        Value::Array(items) => items.iter().for_each(|v| collect_refs(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_refs(v, out)),
+        _ => {}
+    }
+}
+
+/// Id шагов, на которые ссылаются аргументы.
+pub fn referenced_steps(args: &ToolArgs) -> Vec<String> {
+    let mut targets = Vec::new();
+    args.values().for_each(|v| collect_refs(v, &mut targets));
+    targets
+        .into_iter()
+        .map(|t| split_ref(t).0.to_string())
+        .collect()
+}
+
+/// Остались ли в значении неразрешённые ссылки.
+pub fn has_refs(value: &Value) -> bool {
+    let mut targets = Vec::new();
+    collect_refs(value, &mut targets);
+    !targets.is_empty()
+}
+
+/// Проверить граф: id шагов уникальны, ссылки только на более ранние шаги.
+pub fn validate_steps(calls: &[ToolCall]) -> AppResult<()> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    for call in calls {
+        if call.id.is_empty() {
+            return Err(AppError::validation("Every plan step needs an id."));
+        }
+        for step in referenced_steps(&call.args) {
+            if !seen.contains(step.as_str()) {
+                return Err(AppError::validation(format!(
+                    "Step '{}' references '{}', which is not an earlier step.",
+                    call.id, step
+                )));
+            }
+        }
+        if !seen.insert(call.id.as_str()) {
+            return Err(AppError::validation(format!(
+                "Duplicate plan step id '{}'.",
+                call.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Пройти путь по JSON. `*` — по всем элементам массива.
+fn lookup(value: &Value, path: &[&str]) -> Option<Value> {
+    let Some((head, rest)) = path.split_first() else {
+        return Some(value.clone());
+    };
+    match value {
+        Value::Array(items) if *head == "*" => items
+            .iter()
+            .map(|item| lookup(item, rest))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+        Value::Array(items) => head
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| items.get(i))
+            .and_then(|item| lookup(item, rest)),
+        Value::Object(map) => map.get(*head).and_then(|v| lookup(v, rest)),
+        _ => None,
+    }
+}
+
+/// Выходы уже отработавших шагов, по id.
+#[derive(Debug, Default)]
+pub struct StepOutputs {
+    outputs: HashMap<String, Value>,
+    /// Шаги, чей выход пока неполон (write шаг до выполнения — только payload).
+    pending: HashSet<String>,
+}
+
+impl StepOutputs {
+    /// Выход выполненного шага.
+    pub fn insert(&mut self, step_id: &str, output: Value) {
+        self.pending.remove(step_id);
+        self.outputs.insert(step_id.to_string(), output);
+    }
+
+    /// Preview write шага: ссылки на то, чего в нём ещё нет, откладываются.
+    pub fn insert_pending(&mut self, step_id: &str, preview: Value) {
+        self.pending.insert(step_id.to_string());
+        self.outputs.insert(step_id.to_string(), preview);
+    }
+
+    /// Подставить известные выходы в аргументы шага.
+    /// Ссылки на шаги, которых ещё нет, остаются как есть.
+    pub fn resolve_args(&self, args: &ToolArgs) -> AppResult<ToolArgs> {
+        args.iter()
+            .map(|(k, v)| Ok((k.clone(), self.resolve(v)?)))
+            .collect()
+    }
+
+    pub fn resolve(&self, value: &Value) -> AppResult<Value> {
+        if let Some(target) = ref_target(value) {
+            let (step, path) = split_ref(target);
+            let Some(output) = self.outputs.get(step) else {
+                return Ok(value.clone());
+            };
+            return match lookup(output, &path) {
+                Some(resolved) => Ok(resolved),
+                None if self.pending.contains(step) => Ok(value.clone()),
+                None => Err(AppError::validation(format!(
+                    "Step '{}' has no value at '{}'.",
+                    step,
+                    path.join(".")
+                ))),
+            };
+        }
+        match value {
+            Value::Array(items) => items
+                .iter()
+                .map(|v| self.resolve(v))
+                .collect::<AppResult<Vec<_>>>()
+                .map(Value::Array),
+            Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), self.resolve(v)?)))
+                .collect::<AppResult<serde_json::Map<_, _>>>()
+                .map(Value::Object),
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+/// Выход write шага: payload, поверх — поля, которые вернул tool.
+pub fn merge_output(payload: &Value, output: &Value) -> Value {
+    let mut merged = payload.clone();
+    if let (Some(target), Some(extra)) = (merged.as_object_mut(), output.as_object()) {
+        for (k, v) in extra {
+            target.insert(k.clone(), v.clone());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::copilot::tools::CopilotTool;
+    use serde_json::json;
+
+    fn call(id: &str, tool: CopilotTool, args: Value) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            tool,
+            args: serde_json::from_value(args).unwrap(),
+        }
+    }
+
+    #[test]
+    fn resolves_indexes_and_wildcards() {
+        let mut outputs = StepOutputs::default();
+        outputs.insert(
+            "s1",
+            json!({ "expiring": [{ "name": "Salmon" }, { "name": "Dill" }] }),
+        );
+
+        let args: ToolArgs = serde_json::from_value(json!({
+            "recipe_name": "Salmon bowl",
+            "first": { "$ref": "s1.expiring.0.name" },
+            "names": { "$ref": "s1.expiring.*.name" },
+            "ingredients": [{ "ingredient_name": { "$ref": "s1.expiring.1.name" }, "quantity": 10 }],
+        }))
+        .unwrap();
+        let resolved = outputs.resolve_args(&args).unwrap();
+
+        assert_eq!(resolved["first"], "Salmon");
+        assert_eq!(resolved["names"], json!(["Salmon", "Dill"]));
+        assert_eq!(resolved["ingredients"][0]["ingredient_name"], "Dill");
+        assert_eq!(resolved["recipe_name"], "Salmon bowl");
+    }
+
+    #[test]
+    fn refs_to_pending_steps_are_deferred() {
+        let mut outputs = StepOutputs::default();
+        outputs.insert_pending("s2", json!({ "recipe_name": "Salmon bowl" }));
+
+        let args: ToolArgs = serde_json::from_value(json!({
+            "recipe_name": { "$ref": "s2.recipe_name" },
+            "recipe_id": { "$ref": "s2.recipe_id" },
+        }))
+        .unwrap();
+        let resolved = outputs.resolve_args(&args).unwrap();
+        assert_eq!(resolved["recipe_name"], "Salmon bowl");
+        assert_eq!(resolved["recipe_id"], json!({ "$ref": "s2.recipe_id" }));
+
+        // После выполнения шага тот же путь обязан разрешиться
+        outputs.insert("s2", json!({ "recipe_name": "Salmon bowl" }));
+        assert!(outputs.resolve_args(&args).is_err());
+        outputs.insert(
+            "s2",
+            merge_output(
+                &json!({ "recipe_name": "Salmon bowl" }),
+                &json!({ "recipe_id": "r-1" }),
+            ),
+        );
+        assert_eq!(outputs.resolve_args(&args).unwrap()["recipe_id"], "r-1");
+    }
+
+    #[test]
+    fn graph_only_references_earlier_steps() {
+        let ok = [
+            call("s1", CopilotTool::GetExpiringSoon, json!({})),
+            call(
+                "s2",
+                CopilotTool::CreateRecipe,
+                json!({ "recipe_name": { "$ref": "s1.expiring.0.name" } }),
+            ),
+        ];
+        assert!(validate_steps(&ok).is_ok());
+
+        let forward = [
+            call(
+                "s1",
+                CopilotTool::CreateRecipe,
+                json!({ "recipe_name": { "$ref": "s2.name" } }),
+            ),
+            call("s2", CopilotTool::GetExpiringSoon, json!({})),
+        ];
+        assert!(validate_steps(&forward).is_err());
+
+        let duplicate = [
+            call("s1", CopilotTool::GetInventory, json!({})),
+            call("s1", CopilotTool::GetDishes, json!({})),
+        ];
+        assert!(validate_steps(&duplicate).is_err());
+
+        let self_ref = [call(
+            "s1",
+            CopilotTool::CreateDish,
+            json!({ "recipe_id": { "$ref": "s1.recipe_id" } }),
+        )];
+        assert!(validate_steps(&self_ref).is_err());
+    }
+}
@@ -1,19 +1,25 @@
 //! Planner — главный LLM решает что делать.
 //!
-//! Берёт: CopilotContext + user message + tool catalog
+//! Берёт: CopilotContext + user message + tool catalog + память треда
 //! Возвращает: ToolPlan (список tools + args + risk level)
 //!
-//! Structured output — Gemini отвечает строго JSON.
+//! Многошаговые запросы приходят как `steps` — шаги с id, аргументы которых
+//! могут ссылаться на выходы предыдущих шагов (`{"$ref": "s1.path"}`),
+//! см. `plan_graph`. Старый формат `tools` + `args` по-прежнему принимается.
+//!
+//! Structured output — LLM (через `LlmGateway`) отвечает строго JSON.
 //! Backend валидирует и нормализует — никогда не доверяет сырому LLM output.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::infrastructure::gemini_service::GeminiService;
+use crate::infrastructure::llm::{LlmGateway, LlmRequest};
 use crate::shared::AppError;
 
 use super::context::CopilotContext;
+use super::memory::ConversationMemory;
+use super::plan_graph;
 use super::tools::CopilotTool;
 
 /// Аргументы для одного tool call (произвольный JSON map).
@@ -22,6 +28,9 @@ pub type ToolArgs = HashMap<String, serde_json::Value>;
 /// Один вызов tool в плане.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
+    /// Id шага (`s1`, `s2`, …) — на него ссылаются `$ref` в следующих шагах.
+    #[serde(default)]
+    pub id: String,
     pub tool: CopilotTool,
     pub args: ToolArgs,
 }
@@ -33,7 +42,7 @@ pub struct ToolPlan {
     pub intent: String,
     /// Список tools в порядке выполнения.
     pub tools: Vec<CopilotTool>,
-    /// Аргументы для каждого tool (по имени).
+    /// Шаги плана в порядке выполнения (args могут содержать `$ref`).
     pub tool_calls: Vec<ToolCall>,
     /// Нужна ли confirmation (если есть write tools — всегда true).
     pub requires_confirmation: bool,
@@ -43,7 +52,7 @@ pub struct ToolPlan {
 }
 
 impl ToolPlan {
-    /// Есть ли AI tools требующие Gemini вызова?
+    /// Есть ли AI tools требующие LLM вызова?
     pub fn requires_ai_tools(&self) -> bool {
         use CopilotTool::*;
         self.tools.iter().any(|t| {
@@ -67,52 +76,66 @@ impl ToolPlan {
 #[derive(Debug, Deserialize)]
 struct PlannerLlmResponse {
     intent: String,
+    #[serde(default)]
     tools: Vec<String>,
     args: Option<HashMap<String, serde_json::Value>>,
+    /// Многошаговый план — если задан, `tools`/`args` игнорируются.
+    steps: Option<Vec<PlannerLlmStep>>,
     requires_confirmation: Option<bool>,
     workspace_commands: Option<Vec<serde_json::Value>>,
 }
 
+#[derive(Debug, Deserialize)]
+struct PlannerLlmStep {
+    id: Option<String>,
+    tool: String,
+    #[serde(default)]
+    args: ToolArgs,
+}
+
 pub struct CopilotPlanner {
-    gemini: Arc<GeminiService>,
+    llm: Arc<dyn LlmGateway>,
 }
 
 impl CopilotPlanner {
-    pub fn new(gemini: Arc<GeminiService>) -> Self {
-        Self { gemini }
+    pub fn new(llm: Arc<dyn LlmGateway>) -> Self {
+        Self { llm }
     }
 
     /// Вызвать LLM и получить ToolPlan.
-    pub async fn plan(&self, ctx: &CopilotContext, message: &str) -> Result<ToolPlan, AppError> {
-        let system_prompt = self.build_system_prompt(ctx);
-        let request_body = serde_json::json!({
-            "model": "gemini-3-flash-preview",
-            "messages": [
-                {
-                    "role": "system",
-                    "content": system_prompt
-                },
-                {
-                    "role": "user",
-                    "content": message
-                }
-            ],
-            "temperature": 0.1,
-            "max_tokens": 2048,
-            "response_format": { "type": "json_object" }
-        });
-
-        let raw = self.gemini.send_raw_request(&request_body).await?;
+    /// `memory` — предыдущие ходы треда, для ссылок вроде "сделай 20 кг".
+    pub async fn plan(
+        &self,
+        ctx: &CopilotContext,
+        message: &str,
+        memory: &ConversationMemory,
+    ) -> Result<ToolPlan, AppError> {
+        let request = LlmRequest::new("copilot_plan", message)
+            .system(self.build_system_prompt(ctx, memory))
+            .temperature(0.1)
+            .max_tokens(2048)
+            .json();
+
+        let raw = self.llm.complete(&request).await?.content;
         self.parse_plan(&raw)
     }
 
-    fn build_system_prompt(&self, ctx: &CopilotContext) -> String {
+    fn build_system_prompt(&self, ctx: &CopilotContext, memory: &ConversationMemory) -> String {
         let tool_catalog = CopilotTool::tool_catalog_prompt();
+        let conversation = if memory.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\nCONVERSATION SO FAR (oldest first). Resolve follow-ups (\"it\", \"the second one\", \"20 kg instead\") against it; \
+                 a follow-up to a proposed action is a NEW full plan with the corrected args:\n{}",
+                memory.to_prompt()
+            )
+        };
         format!(
             r##"You are ChefOS Copilot. Output ONLY compact valid JSON. No markdown. No explanations.
 
 CONTEXT: {context}
-
+{conversation}
 TOOLS: {tools}
 
 RULES:
@@ -121,7 +144,7 @@ RULES:
 - intent: short snake_case label (e.g. "inventory_add", "inventory_view", "inventory_writeoff", "dish_price_update").
 - args: short keys, English values only.
 - Unknown request → use "general_chef_answer".
-- Keep total response under 400 tokens.
+- Keep total response under 400 tokens (multi-step plans: under 800).
 
 INVENTORY ARGS SCHEMA (use exactly these keys):
 - prepare_inventory_update: {{ "ingredient_name": "<en>", "quantity": <number>, "unit": "<kg|l|pcs>" }}
@@ -134,7 +157,7 @@ INVENTORY ARGS SCHEMA (use exactly these keys):
 - send_purchase_order:      {{ "id": "<uuid|'last'>" }}
 - get_daily_briefing:       {{ "expiring_days": <int, optional, default 3>, "low_stock_threshold": <number, optional, default 1.0> }}
 - create_recipe:            {{ "recipe_name": "<English title>", "servings": <positive int>, "ingredients": [{{ "ingredient_name": "<English catalog name>", "quantity": <number>, "unit": "<g|kg|ml|l|pcs>" }}] }}
-- create_dish:              {{ "dish_name": "<English name of the dish>", "recipe_name": "<English name of existing recipe>", "selling_price_cents": <positive int>, "description": "<optional str>" }}
+- create_dish:              {{ "dish_name": "<English name of the dish>", "recipe_name": "<English name of existing recipe>", "selling_price_cents": <positive int>, "target_food_cost_percent": <number, instead of selling_price_cents>, "description": "<optional str>" }}
 
 DAILY BRIEFING INTENT HINTS (HIGHEST PRIORITY for these phrases):
 - "что сегодня важно / brief me / daily briefing / daily report / что нужно сделать / today summary / overview / morning briefing" → get_daily_briefing
@@ -160,6 +183,7 @@ CREATE DISH INTENT HINTS:
 - "create dish / add dish / add to menu / put on menu" → create_dish (requires_confirmation=true)
 - dish_name: translate to English. recipe_name: existing recipe, translate to English.
 - selling_price_cents: convert to cents.
+- "at 30% food cost" → target_food_cost_percent=30 instead of selling_price_cents (price is computed from the recipe cost).
 
 INVENTORY ADJUSTMENT INTENT HINTS:
 - "fix / set / correct / adjust / should be / make it" with TARGET quantity → adjust_inventory_quantity
@@ -196,9 +220,21 @@ BEVEL / ROUNDED CORNERS RULES:
 - Vision feedback loop shows you the result — if not rounded enough, increase bevel in correction.
 - Plain cube (no rounding) → spawn_shape is sufficient.
 
+MULTI-STEP PLANS (when later tools need results of earlier ones):
+- Use "steps" instead of "tools"/"args". Each step: {{ "id": "s1", "tool": "<tool_name>", "args": {{...}} }}, in execution order.
+- Any arg value may be a reference to an EARLIER step: {{ "$ref": "<step_id>.<path>" }}. Path: object keys and array indexes separated by dots; "*" maps over an array.
+  get_expiring_soon returns {{ "expiring": [{{ "name", "quantity", "unit", "expires_at" }}] }} → {{ "$ref": "s1.expiring.0.name" }}.
+  create_recipe → recipe_id, recipe_name; create_dish → dish_id, dish_name; prepare_purchase_draft → draft_id.
+- All write steps are confirmed together in one preview and applied all-or-nothing.
+- Example "find what's expiring, build a recipe from it, create the dish at 30% food cost":
+  {{"intent":"expiring_to_dish","steps":[{{"id":"s1","tool":"get_expiring_soon","args":{{}}}},{{"id":"s2","tool":"create_recipe","args":{{"recipe_name":"Salmon Bowl","servings":1,"ingredients":[{{"ingredient_name":{{"$ref":"s1.expiring.0.name"}},"quantity":150,"unit":"g"}}]}}}},{{"id":"s3","tool":"create_dish","args":{{"dish_name":"Salmon Bowl","recipe_name":{{"$ref":"s2.recipe_name"}},"recipe_id":{{"$ref":"s2.recipe_id"}},"target_food_cost_percent":30}}}}],"requires_confirmation":true,"workspace_commands":[]}}
+
 OUTPUT FORMAT (exactly):
-{{"intent":"<snake_case>","tools":["tool_name"],"args":{{"tool_name":{{"key":"value"}}}},"requires_confirmation":false,"workspace_commands":[]}}"##,
+{{"intent":"<snake_case>","tools":["tool_name"],"args":{{"tool_name":{{"key":"value"}}}},"requires_confirmation":false,"workspace_commands":[]}}
+or, for multi-step plans:
+{{"intent":"<snake_case>","steps":[{{"id":"s1","tool":"tool_name","args":{{"key":"value"}}}}],"requires_confirmation":false,"workspace_commands":[]}}"##,
             context = ctx.to_prompt_context(),
+            conversation = conversation,
             tools = tool_catalog,
         )
     }
@@ -212,6 +248,15 @@ OUTPUT FORMAT (exactly):
             AppError::internal(format!("Planner response parse error: {e}"))
         })?;
 
+        if let Some(steps) = parsed.steps.filter(|s| !s.is_empty()) {
+            return parse_steps(
+                parsed.intent,
+                steps,
+                parsed.requires_confirmation,
+                parsed.workspace_commands.unwrap_or_default(),
+            );
+        }
+
         // Парсить tool names → CopilotTool enum
         let tools: Vec<CopilotTool> = parsed
             .tools
@@ -225,6 +270,7 @@ OUTPUT FORMAT (exactly):
                 intent: parsed.intent,
                 tools: vec![CopilotTool::GeneralChefAnswer],
                 tool_calls: vec![ToolCall {
+                    id: "s1".to_string(),
                     tool: CopilotTool::GeneralChefAnswer,
                     args: HashMap::new(),
                 }],
@@ -239,13 +285,15 @@ OUTPUT FORMAT (exactly):
         let args_map = parsed.args.unwrap_or_default();
         let tool_calls: Vec<ToolCall> = tools
             .iter()
-            .map(|t| {
+            .enumerate()
+            .map(|(i, t)| {
                 let args = args_map
                     .get(t.name())
                     .and_then(|v| v.as_object())
                     .map(|m| m.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
                     .unwrap_or_default();
                 ToolCall {
+                    id: format!("s{}", i + 1),
                     tool: t.clone(),
                     args,
                 }
@@ -262,6 +310,46 @@ OUTPUT FORMAT (exactly):
     }
 }
 
+/// Многошаговый план: каждый tool обязан распознаться, иначе ссылки на
+/// этот шаг повиснут — весь план отклоняется.
+fn parse_steps(
+    intent: String,
+    steps: Vec<PlannerLlmStep>,
+    requires_confirmation: Option<bool>,
+    workspace_commands: Vec<serde_json::Value>,
+) -> Result<ToolPlan, AppError> {
+    let tool_calls = steps
+        .into_iter()
+        .enumerate()
+        .map(|(i, step)| {
+            let tool = parse_tool_name(&step.tool).ok_or_else(|| {
+                AppError::validation(format!("Unknown tool in plan step: {}", step.tool))
+            })?;
+            Ok(ToolCall {
+                id: step
+                    .id
+                    .filter(|id| !id.trim().is_empty())
+                    .unwrap_or_else(|| format!("s{}", i + 1)),
+                tool,
+                args: step.args,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    plan_graph::validate_steps(&tool_calls)?;
+
+    let tools: Vec<CopilotTool> = tool_calls.iter().map(|c| c.tool.clone()).collect();
+    let has_write = tools.iter().any(|t| t.is_write());
+
+    Ok(ToolPlan {
+        intent,
+        tools,
+        tool_calls,
+        requires_confirmation: has_write || requires_confirmation.unwrap_or(false),
+        workspace_commands,
+    })
+}
+
 /// Парсить строковое имя tool → CopilotTool.
 fn parse_tool_name(name: &str) -> Option<CopilotTool> {
     match name {
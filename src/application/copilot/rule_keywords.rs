@@ -0,0 +1,411 @@
+//! Rule Keywords — таблицы ключевых слов для офлайн planner-а Copilot.
+//!
+//! Та же схема, что `rulebot::intent_keywords`: данные отдельно от логики.
+//! Скоринг и извлечение аргументов — в `rule_planner`.
+//!
+//! Каждая таблица: `&[(&str, i32)]` — подстрока (lowercase) + вес.
+//! Покрыты RU / EN / PL / UK формулировки.
+
+use crate::application::rulebot::intent_keywords::ScoredKeyword;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+//  READ
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub const INVENTORY: &[ScoredKeyword] = &[
+    ("склад", 3),
+    ("остатк", 3),
+    ("остаток", 3),
+    ("инвентар", 2),
+    ("что у нас есть", 3),
+    ("что есть", 2),
+    ("продукты", 1),
+    ("inventory", 3),
+    ("stock", 3),
+    ("what do we have", 3),
+    ("what do i have", 3),
+    ("on hand", 2),
+    ("magazyn", 3),
+    ("zapas", 3),
+    ("co mamy", 3),
+    ("stan", 1),
+    ("залишк", 3),
+    ("запас", 2),
+    ("що є", 2),
+    ("що в нас є", 3),
+];
+
+pub const EXPIRING: &[ScoredKeyword] = &[
+    ("срок годности", 4),
+    ("истека", 4),
+    ("испортится", 3),
+    ("портится", 3),
+    ("скоро пропад", 3),
+    ("expir", 4),
+    ("going bad", 3),
+    ("go off", 3),
+    ("best before", 3),
+    ("use by", 2),
+    ("termin ważności", 4),
+    ("kończy się termin", 4),
+    ("przetermin", 3),
+    ("zepsuje", 3),
+    ("термін придатності", 4),
+    ("спливає", 4),
+    ("зіпсується", 3),
+    ("псується", 3),
+];
+
+pub const DISHES: &[ScoredKeyword] = &[
+    ("блюд", 3),
+    ("меню", 2),
+    ("dishes", 3),
+    ("menu", 2),
+    ("dania", 3),
+    ("dań", 3),
+    ("страв", 3),
+];
+
+/// Только список рецептов — "рецепт борща" это генерация, не read tool.
+pub const RECIPES: &[ScoredKeyword] = &[
+    ("рецепты", 3),
+    ("рецептов", 3),
+    ("recipes", 3),
+    ("przepisy", 3),
+    ("przepisów", 3),
+    ("рецепти", 3),
+    ("рецептів", 3),
+];
+
+pub const PURCHASE_DRAFTS: &[ScoredKeyword] = &[
+    ("закупк", 3),
+    ("закупок", 3),
+    ("черновик", 3),
+    ("заказы", 2),
+    ("purchase", 3),
+    ("draft", 3),
+    ("orders", 2),
+    ("zamówieni", 3),
+    ("zakup", 3),
+    ("закупівл", 3),
+    ("чернетк", 3),
+    ("замовлення", 2),
+];
+
+/// "Последний" черновик → get_purchase_draft с id = "last".
+pub const LAST: &[&str] = &["последн", "last", "latest", "ostatni", "останн"];
+
+pub const BRIEFING: &[ScoredKeyword] = &[
+    ("что сегодня важно", 5),
+    ("брифинг", 5),
+    ("сводк", 4),
+    ("что нужно сделать", 4),
+    ("обзор", 3),
+    ("briefing", 5),
+    ("brief me", 5),
+    ("daily report", 4),
+    ("today summary", 4),
+    ("what's important", 4),
+    ("what is important", 4),
+    ("overview", 3),
+    ("co dziś ważne", 5),
+    ("co jest dziś ważne", 5),
+    ("podsumowanie", 4),
+    ("raport dzienny", 4),
+    ("przegląd", 3),
+    ("що сьогодні важливо", 5),
+    ("брифінг", 5),
+    ("зведення", 4),
+    ("огляд", 3),
+];
+
+// ═══════════════════════════════════════════════════════════════════════════════
+//  WRITE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub const ADD_STOCK: &[ScoredKeyword] = &[
+    ("добав", 4),
+    ("пришл", 3),
+    ("пришёл", 3),
+    ("приход", 3),
+    ("поступил", 3),
+    ("купил", 2),
+    ("add", 4),
+    ("arrived", 3),
+    ("received", 3),
+    ("delivered", 3),
+    ("bought", 2),
+    ("dodaj", 4),
+    ("przyszł", 3),
+    ("przyjęt", 3),
+    ("kupił", 2),
+    ("додай", 4),
+    ("додати", 4),
+    ("надійшл", 3),
+    ("прийшл", 3),
+];
+
+pub const WRITE_OFF: &[ScoredKeyword] = &[
+    ("спиши", 5),
+    ("списа", 5),
+    ("испорти", 3),
+    ("протух", 3),
+    ("выброс", 3),
+    ("write off", 5),
+    ("write-off", 5),
+    ("spoiled", 3),
+    ("throw away", 3),
+    ("threw away", 3),
+    ("wasted", 3),
+    ("spisz", 5),
+    ("spisać", 5),
+    ("zepsu", 3),
+    ("wyrzu", 3),
+    ("списати", 5),
+    ("зіпсува", 3),
+    ("викин", 3),
+];
+
+pub const ADJUST_STOCK: &[ScoredKeyword] = &[
+    ("должно быть", 5),
+    ("на самом деле", 3),
+    ("исправь", 4),
+    ("поправь", 4),
+    ("скорректир", 4),
+    ("инвентаризац", 3),
+    ("should be", 5),
+    ("actually", 3),
+    ("correct", 3),
+    ("set ", 2),
+    ("stock count", 3),
+    ("stocktake", 3),
+    ("powinno być", 5),
+    ("popraw", 4),
+    ("korekt", 4),
+    ("inwentaryzac", 3),
+    ("має бути", 5),
+    ("виправ", 4),
+    ("скоригуй", 4),
+    ("інвентаризац", 3),
+];
+
+/// Инвентаризация → reason = inventory_check вместо correction.
+pub const STOCK_COUNT: &[&str] = &[
+    "инвентаризац",
+    "stock count",
+    "stocktake",
+    "inventory check",
+    "inwentaryzac",
+    "інвентаризац",
+];
+
+pub const DISH_PRICE: &[ScoredKeyword] = &[
+    ("цену", 4),
+    ("цена", 4),
+    ("стоимость", 2),
+    ("price", 4),
+    ("cena", 4),
+    ("cenę", 4),
+    ("ціну", 4),
+    ("ціна", 4),
+];
+
+pub const SEND_ORDER: &[ScoredKeyword] = &[
+    ("отправь заказ", 6),
+    ("отправь закупку", 6),
+    ("отправить заказ", 6),
+    ("send the order", 6),
+    ("send order", 6),
+    ("send the purchase", 6),
+    ("send purchase", 6),
+    ("mark as sent", 6),
+    ("wyślij zamówienie", 6),
+    ("надішли замовлення", 6),
+    ("відправ замовлення", 6),
+];
+
+// ═══════════════════════════════════════════════════════════════════════════════
+//  SIGNALS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Несколько действий в одном сообщении — это работа для LLM планировщика.
+pub const MULTI_STEP_SIGNALS: &[&str] = &[
+    " и потом ",
+    " затем ",
+    " после этого ",
+    " and then ",
+    " then ",
+    " afterwards ",
+    " a potem ",
+    " następnie ",
+    " і потім ",
+    " потім ",
+];
+
+/// Ссылки на предыдущий ход ("его", "instead") — без памяти треда не разрешить.
+pub const FOLLOWUP_SIGNALS: &[&str] = &[
+    "вместо",
+    " его ",
+    " её ",
+    " их ",
+    "instead",
+    " it ",
+    " them ",
+    "zamiast",
+    "замість",
+];
+
+/// Валюта рядом с числом → это цена.
+pub const CURRENCY: &[&str] = &["€", "eur", "euro", "евро", "євро"];
+
+/// Валюта — копейки/центы.
+pub const CENTS: &[&str] = &["cent", "цент", "коп", "groszy"];
+
+/// Служебные слова, которые не входят в название ингредиента / блюда.
+pub const STOPWORDS: &[&str] = &[
+    // RU
+    "и",
+    "в",
+    "на",
+    "до",
+    "для",
+    "по",
+    "с",
+    "со",
+    "у",
+    "из",
+    "от",
+    "за",
+    "мне",
+    "нам",
+    "пожалуйста",
+    "ещё",
+    "еще",
+    "нужно",
+    "надо",
+    "сейчас",
+    "сегодня",
+    "теперь",
+    "блюдо",
+    "блюда",
+    "ингредиент",
+    "ингредиента",
+    "штук",
+    "штуки",
+    "шт",
+    "евро",
+    "поставь",
+    "поменяй",
+    "измени",
+    "установи",
+    "сделай",
+    "обнови",
+    "потому",
+    "что",
+    "так",
+    "как",
+    "было",
+    "склад",
+    "остаток",
+    "остатки",
+    "просрочено",
+    "просрочен",
+    "просрочка",
+    "срок",
+    "вышел",
+    "истёк",
+    // EN
+    "a",
+    "an",
+    "the",
+    "of",
+    "to",
+    "for",
+    "in",
+    "on",
+    "at",
+    "from",
+    "by",
+    "with",
+    "into",
+    "please",
+    "some",
+    "more",
+    "now",
+    "today",
+    "stock",
+    "inventory",
+    "dish",
+    "item",
+    "items",
+    "pieces",
+    "piece",
+    "pcs",
+    "set",
+    "change",
+    "update",
+    "make",
+    "it",
+    "is",
+    "are",
+    "was",
+    "because",
+    "expired",
+    "spoiled",
+    "new",
+    "our",
+    "my",
+    "went",
+    "gone",
+    "bad",
+    "off",
+    // PL
+    "i",
+    "w",
+    "na",
+    "do",
+    "dla",
+    "z",
+    "ze",
+    "od",
+    "proszę",
+    "jeszcze",
+    "teraz",
+    "dziś",
+    "danie",
+    "sztuk",
+    "szt",
+    "zmień",
+    "ustaw",
+    "magazyn",
+    "magazynu",
+    "bo",
+    "się",
+    "przeterminowane",
+    // UK
+    "і",
+    "й",
+    "в",
+    "у",
+    "на",
+    "до",
+    "для",
+    "з",
+    "із",
+    "від",
+    "будь",
+    "ласка",
+    "ще",
+    "зараз",
+    "сьогодні",
+    "страва",
+    "страву",
+    "штук",
+    "змін",
+    "зміни",
+    "встанови",
+    "постав",
+    "склад",
+    "бо",
+    "прострочено",
+];
@@ -0,0 +1,984 @@
+//! Rule Planner — детерминированный planner без LLM.
+//!
+//! Строит тот же `ToolPlan`, что и LLM planner: intent по ключевым словам
+//! (`rule_keywords`, скоринг как в `rulebot::intent_router`) + аргументы,
+//! извлечённые из текста: ингредиент, количество и единица, цена, срок.
+//!
+//! Используется:
+//!   - как free tier — уверенный план (confidence ≥ `ESCALATE_BELOW`) не
+//!     вызывает LLM и не тратит AI actions;
+//!   - как fallback — LLM недоступен или баланс AI actions исчерпан.
+//!
+//! AI tools, многошаговые и уточняющие ("сделай 20 кг вместо") запросы
+//! сюда не попадают — их планирует LLM.
+//!
+//! ```text
+//! "что истекает?"                  → get_expiring_soon
+//! "добавь 5 кг лосося"             → prepare_inventory_update { лосося, 5, kg }
+//! "spisz 500 g masła, zepsute"     → write_off_inventory { masła, 0.5, kg, waste }
+//! "set price of caesar to €14"     → update_dish_price { caesar, 1400 }
+//! ```
+
+use serde_json::{json, Value};
+
+use crate::application::rulebot::intent_keywords::ScoredKeyword;
+use crate::application::rulebot::intent_router::sum_scores;
+use crate::shared::Language;
+
+use super::planner::{ToolArgs, ToolCall, ToolPlan};
+use super::rule_keywords as kw;
+use super::tool_executor::ToolResult;
+use super::tools::CopilotTool;
+
+/// Ниже — план отдаётся LLM planner-у (если LLM доступен и баланс есть).
+pub const ESCALATE_BELOW: f32 = 0.7;
+/// Ниже — план не строится вовсе, даже как fallback.
+pub const MIN_CONFIDENCE: f32 = 0.4;
+/// Минимальный score intent-а (как MIN_THRESHOLD в intent_router).
+const MIN_SCORE: i32 = 2;
+/// Score, при котором intent однозначен.
+const FULL_SCORE: i32 = 4;
+/// Длинные сообщения редко бывают простыми командами.
+const MAX_SIMPLE_WORDS: usize = 15;
+/// Название длиннее — скорее всего извлечение захватило лишнее.
+const MAX_NAME_WORDS: usize = 4;
+/// Минимальная длина основы слова при поиске по каталогу.
+const MIN_STEM_CHARS: usize = 4;
+/// Строк в ответе без LLM.
+const MAX_LINES: usize = 15;
+
+/// Окончания, которые срезаются для поиска по подстроке ("лосося" → "лосос").
+/// Длинные первыми.
+const ENDINGS: &[&str] = &[
+    "ами", "ями", "ого", "его", "ому", "ему", "ыми", "ими", "ach", "ami", "ies", "oes", "ów", "ах",
+    "ях", "ов", "ев", "ей", "ой", "ом", "ем", "ам", "ям", "ую", "ая", "ое", "ые", "ий", "ый", "ів",
+    "ою", "ia", "ie", "а", "я", "у", "ю", "ы", "и", "е", "о", "ь", "й", "і", "ї", "a", "y", "i",
+    "u", "e", "ę", "ą", "o", "s",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleIntent {
+    Inventory,
+    Expiring,
+    Dishes,
+    Recipes,
+    PurchaseDrafts,
+    Briefing,
+    AddStock,
+    WriteOff,
+    AdjustStock,
+    DishPrice,
+    SendOrder,
+}
+
+impl RuleIntent {
+    const ALL: [RuleIntent; 11] = [
+        Self::Inventory,
+        Self::Expiring,
+        Self::Dishes,
+        Self::Recipes,
+        Self::PurchaseDrafts,
+        Self::Briefing,
+        Self::AddStock,
+        Self::WriteOff,
+        Self::AdjustStock,
+        Self::DishPrice,
+        Self::SendOrder,
+    ];
+
+    fn keywords(self) -> &'static [ScoredKeyword] {
+        match self {
+            Self::Inventory => kw::INVENTORY,
+            Self::Expiring => kw::EXPIRING,
+            Self::Dishes => kw::DISHES,
+            Self::Recipes => kw::RECIPES,
+            Self::PurchaseDrafts => kw::PURCHASE_DRAFTS,
+            Self::Briefing => kw::BRIEFING,
+            Self::AddStock => kw::ADD_STOCK,
+            Self::WriteOff => kw::WRITE_OFF,
+            Self::AdjustStock => kw::ADJUST_STOCK,
+            Self::DishPrice => kw::DISH_PRICE,
+            Self::SendOrder => kw::SEND_ORDER,
+        }
+    }
+
+    /// Тот же snake_case label, что выдаёт LLM planner.
+    fn label(self) -> &'static str {
+        match self {
+            Self::Inventory => "inventory_view",
+            Self::Expiring => "inventory_expiring",
+            Self::Dishes => "dish_list",
+            Self::Recipes => "recipe_list",
+            Self::PurchaseDrafts => "purchase_draft_view",
+            Self::Briefing => "daily_briefing",
+            Self::AddStock => "inventory_add",
+            Self::WriteOff => "inventory_writeoff",
+            Self::AdjustStock => "inventory_adjust",
+            Self::DishPrice => "dish_price_update",
+            Self::SendOrder => "purchase_order_send",
+        }
+    }
+
+    fn is_write(self) -> bool {
+        matches!(
+            self,
+            Self::AddStock | Self::WriteOff | Self::AdjustStock | Self::DishPrice | Self::SendOrder
+        )
+    }
+}
+
+/// План от rule planner-а + насколько ему можно верить.
+#[derive(Debug, Clone)]
+pub struct RulePlan {
+    pub plan: ToolPlan,
+    /// 0.0–1.0: однозначность intent-а × полнота аргументов.
+    pub confidence: f32,
+}
+
+impl RulePlan {
+    /// Отдать запрос LLM planner-у, если он доступен.
+    pub fn should_escalate(&self) -> bool {
+        self.confidence < ESCALATE_BELOW
+    }
+}
+
+/// Построить план без LLM. `None` — запрос не распознан достаточно уверенно.
+pub fn plan(message: &str) -> Option<RulePlan> {
+    let tokens = tokenize(message);
+    if tokens.is_empty() {
+        return None;
+    }
+    // Пробелы по краям — чтобы сигналы вида " it " ловились и на границах
+    let text = format!(" {} ", tokens.join(" "));
+
+    let scores: Vec<(RuleIntent, i32)> = RuleIntent::ALL
+        .iter()
+        .map(|intent| (*intent, sum_scores(&text, intent.keywords())))
+        .collect();
+    let (intent, best) = scores.iter().copied().max_by_key(|(_, score)| *score)?;
+    if best < MIN_SCORE {
+        return None;
+    }
+
+    let (call, arg_quality) = build_call(intent, &text, &tokens)?;
+
+    let mut confidence = (best as f32 / FULL_SCORE as f32).min(1.0) * arg_quality;
+    confidence *= ambiguity_factor(intent, best, &scores);
+    if kw::MULTI_STEP_SIGNALS.iter().any(|s| text.contains(s))
+        || kw::FOLLOWUP_SIGNALS.iter().any(|s| text.contains(s))
+    {
+        confidence *= 0.5;
+    }
+    if tokens.len() > MAX_SIMPLE_WORDS {
+        confidence *= 0.7;
+    }
+    if confidence < MIN_CONFIDENCE {
+        return None;
+    }
+
+    let requires_confirmation = call.tool.is_write();
+    Some(RulePlan {
+        plan: ToolPlan {
+            intent: intent.label().to_string(),
+            tools: vec![call.tool.clone()],
+            tool_calls: vec![call],
+            requires_confirmation,
+            workspace_commands: vec![],
+        },
+        confidence,
+    })
+}
+
+/// Конкурирующие intent-ы снижают уверенность.
+/// Read слова в write команде ("добавь на склад") — контекст, не конкурент.
+fn ambiguity_factor(intent: RuleIntent, best: i32, scores: &[(RuleIntent, i32)]) -> f32 {
+    let rival = scores
+        .iter()
+        .filter(|(other, _)| *other != intent)
+        .map(|(other, score)| {
+            let weight = match (intent.is_write(), other.is_write()) {
+                (true, false) => 0.0,
+                (false, false) => 0.25,
+                _ => 1.0,
+            };
+            *score as f32 * weight
+        })
+        .fold(0.0_f32, f32::max);
+    (1.0 - 0.5 * rival / best as f32).max(0.0)
+}
+
+/// Tool call + полнота аргументов (1.0 — всё извлечено).
+fn build_call(intent: RuleIntent, text: &str, tokens: &[String]) -> Option<(ToolCall, f32)> {
+    let mut args = ToolArgs::new();
+    let (tool, quality) = match intent {
+        RuleIntent::Inventory => (CopilotTool::GetInventory, 1.0),
+        RuleIntent::Expiring => {
+            if let Some(days) = find_days(text, tokens) {
+                args.insert("days".to_string(), json!(days));
+            }
+            (CopilotTool::GetExpiringSoon, 1.0)
+        }
+        RuleIntent::Dishes => (CopilotTool::GetDishes, 1.0),
+        RuleIntent::Recipes => (CopilotTool::GetRecipes, 1.0),
+        RuleIntent::PurchaseDrafts => {
+            if kw::LAST.iter().any(|w| text.contains(w)) {
+                args.insert("id".to_string(), json!("last"));
+                (CopilotTool::GetPurchaseDraft, 1.0)
+            } else {
+                (CopilotTool::ListPurchaseDrafts, 1.0)
+            }
+        }
+        RuleIntent::Briefing => (CopilotTool::GetDailyBriefing, 1.0),
+        RuleIntent::AddStock | RuleIntent::WriteOff | RuleIntent::AdjustStock => {
+            let quantity = find_quantity(tokens)?;
+            if intent != RuleIntent::AdjustStock && quantity.value <= 0.0 {
+                return None;
+            }
+            let name = extract_name(tokens, &quantity.consumed)?;
+            args.insert("ingredient_name".to_string(), json!(name));
+            let quantity_key = if intent == RuleIntent::AdjustStock {
+                "target_quantity"
+            } else {
+                "quantity"
+            };
+            args.insert(quantity_key.to_string(), json!(quantity.value));
+            // Без единицы executor подставит kg — пусть лучше решит LLM
+            let quality = match quantity.unit {
+                Some(unit) => {
+                    args.insert("unit".to_string(), json!(unit));
+                    1.0
+                }
+                None => 0.6,
+            };
+            let tool = match intent {
+                RuleIntent::AddStock => CopilotTool::PrepareInventoryUpdate,
+                RuleIntent::WriteOff => {
+                    args.insert("reason".to_string(), json!(write_off_reason(text)));
+                    CopilotTool::WriteOffInventory
+                }
+                _ => {
+                    let reason = if kw::STOCK_COUNT.iter().any(|w| text.contains(w)) {
+                        "inventory_check"
+                    } else {
+                        "correction"
+                    };
+                    args.insert("reason".to_string(), json!(reason));
+                    CopilotTool::AdjustInventoryQuantity
+                }
+            };
+            (tool, quality)
+        }
+        RuleIntent::DishPrice => {
+            let price = find_price(tokens)?;
+            let name = extract_name(tokens, &price.consumed)?;
+            // Блюдо ищется по подстроке названия — основа слова надёжнее падежа
+            args.insert("dish_name".to_string(), json!(search_stem(&name)));
+            args.insert("new_price_cents".to_string(), json!(price.cents));
+            args.insert("currency".to_string(), json!("EUR"));
+            let quality = if price.has_currency { 1.0 } else { 0.8 };
+            (CopilotTool::UpdateDishPrice, quality)
+        }
+        RuleIntent::SendOrder => {
+            args.insert("id".to_string(), json!("last"));
+            (CopilotTool::SendPurchaseOrder, 1.0)
+        }
+    };
+
+    Some((
+        ToolCall {
+            id: "s1".to_string(),
+            tool,
+            args,
+        },
+        quality,
+    ))
+}
+
+// ── Argument extraction ──────────────────────────────────────────────────────
+
+/// Lowercase слова без пунктуации по краям ("12.50" и "5kg" не трогаются).
+fn tokenize(message: &str) -> Vec<String> {
+    message
+        .to_lowercase()
+        .split_whitespace()
+        .map(|t| {
+            t.trim_matches(|c: char| ",.!?;:\"'()«»“”".contains(c))
+                .to_string()
+        })
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// `"12,5kg"` → `(12.5, "kg")`, `"€14"` → `(14.0, "")`.
+fn split_number(token: &str) -> Option<(f64, &str)> {
+    let token = token.trim_start_matches('€');
+    let end = token
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .unwrap_or(token.len());
+    if end == 0 {
+        return None;
+    }
+    let value = token[..end]
+        .trim_end_matches(['.', ','])
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()?;
+    Some((value, &token[end..]))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Kg,
+    G,
+    L,
+    Ml,
+    Pcs,
+}
+
+fn parse_unit(word: &str) -> Option<Unit> {
+    let starts = |prefixes: &[&str]| prefixes.iter().any(|p| word.starts_with(p));
+    match word {
+        "kg" | "кг" | "kilo" | "kilos" => Some(Unit::Kg),
+        "g" | "г" | "гр" | "gr" => Some(Unit::G),
+        "l" | "л" | "lt" => Some(Unit::L),
+        "ml" | "мл" => Some(Unit::Ml),
+        "pcs" | "pc" | "шт" | "szt" => Some(Unit::Pcs),
+        _ if starts(&["kilogram", "килограм", "кілограм"]) => Some(Unit::Kg),
+        _ if starts(&["milli", "mili", "миллилитр", "мілілітр"]) => Some(Unit::Ml),
+        _ if starts(&["gram", "грам"]) => Some(Unit::G),
+        _ if starts(&["liter", "litre", "litr", "литр", "літр"]) => Some(Unit::L),
+        _ if starts(&["piece", "штук", "sztuk"]) => Some(Unit::Pcs),
+        _ => None,
+    }
+}
+
+/// Количество в единицах склада (kg / l / pcs).
+#[derive(Debug)]
+struct Quantity {
+    value: f64,
+    unit: Option<&'static str>,
+    /// Индексы токенов числа и единицы — не входят в название.
+    consumed: Vec<usize>,
+}
+
+fn find_quantity(tokens: &[String]) -> Option<Quantity> {
+    tokens.iter().enumerate().find_map(|(i, token)| {
+        let (value, suffix) = split_number(token)?;
+        if is_currency(suffix) {
+            return None;
+        }
+        let mut consumed = vec![i];
+        let unit = if !suffix.is_empty() {
+            parse_unit(suffix)
+        } else {
+            let next = tokens.get(i + 1).and_then(|t| parse_unit(t));
+            if next.is_some() {
+                consumed.push(i + 1);
+            }
+            next
+        };
+        let (value, unit) = match unit {
+            Some(Unit::Kg) => (value, Some("kg")),
+            Some(Unit::G) => (value / 1000.0, Some("kg")),
+            Some(Unit::L) => (value, Some("l")),
+            Some(Unit::Ml) => (value / 1000.0, Some("l")),
+            Some(Unit::Pcs) => (value, Some("pcs")),
+            None => (value, None),
+        };
+        Some(Quantity {
+            value,
+            unit,
+            consumed,
+        })
+    })
+}
+
+#[derive(Debug)]
+struct Price {
+    cents: i64,
+    has_currency: bool,
+    consumed: Vec<usize>,
+}
+
+/// Цена: число рядом с валютой, иначе первое число в сообщении.
+fn find_price(tokens: &[String]) -> Option<Price> {
+    let mut first = None;
+    for (i, token) in tokens.iter().enumerate() {
+        let Some((value, suffix)) = split_number(token) else {
+            continue;
+        };
+        let mut consumed = vec![i];
+        let prev_currency = i > 0 && is_currency(&tokens[i - 1]);
+        if prev_currency {
+            consumed.push(i - 1);
+        }
+        let next = tokens.get(i + 1).map(String::as_str).unwrap_or("");
+        let next_cents = is_cents(next);
+        let next_currency = is_currency(next);
+        if next_cents || next_currency {
+            consumed.push(i + 1);
+        }
+        let price = Price {
+            cents: if next_cents || is_cents(suffix) {
+                value.round() as i64
+            } else {
+                (value * 100.0).round() as i64
+            },
+            has_currency: token.starts_with('€')
+                || is_currency(suffix)
+                || is_cents(suffix)
+                || prev_currency
+                || next_currency
+                || next_cents,
+            consumed,
+        };
+        if price.has_currency {
+            return Some(price).filter(|p| p.cents > 0);
+        }
+        first.get_or_insert(price);
+    }
+    first.filter(|p| p.cents > 0)
+}
+
+fn is_currency(word: &str) -> bool {
+    !word.is_empty() && kw::CURRENCY.iter().any(|c| word.starts_with(c))
+}
+
+fn is_cents(word: &str) -> bool {
+    !word.is_empty() && kw::CENTS.iter().any(|c| word.starts_with(c))
+}
+
+/// "на 7 дней" / "next 5 days" / "this week" → дни для get_expiring_soon.
+fn find_days(text: &str, tokens: &[String]) -> Option<i64> {
+    const DAY_WORDS: &[&str] = &["дн", "день", "day", "dni", "dzie", "дні", "доб"];
+    const WEEK_WORDS: &[&str] = &["недел", "week", "tydzie", "tygodni", "тиж"];
+    let explicit = tokens.windows(2).find_map(|pair| {
+        let (value, _) = split_number(&pair[0])?;
+        DAY_WORDS
+            .iter()
+            .any(|w| pair[1].starts_with(w))
+            .then_some(value as i64)
+    });
+    explicit
+        .or_else(|| WEEK_WORDS.iter().any(|w| text.contains(w)).then_some(7))
+        .filter(|days| (1..=30).contains(days))
+}
+
+fn write_off_reason(text: &str) -> &'static str {
+    const EXPIRED: &[&str] = &[
+        "просроч",
+        "expired",
+        "przetermin",
+        "простроч",
+        "срок",
+        "termin",
+    ];
+    if EXPIRED.iter().any(|w| text.contains(w)) {
+        "expired"
+    } else {
+        "waste"
+    }
+}
+
+/// Название = слова, оставшиеся после команды, чисел, единиц и служебных слов.
+fn extract_name(tokens: &[String], consumed: &[usize]) -> Option<String> {
+    let words: Vec<&str> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !consumed.contains(i))
+        .map(|(_, t)| t.as_str())
+        .filter(|t| !is_noise(t))
+        .collect();
+    if words.is_empty() || words.len() > MAX_NAME_WORDS {
+        return None;
+    }
+    Some(words.join(" "))
+}
+
+fn is_noise(token: &str) -> bool {
+    split_number(token).is_some()
+        || parse_unit(token).is_some()
+        || is_currency(token)
+        || is_cents(token)
+        || kw::STOPWORDS.contains(&token)
+        || is_keyword(token)
+}
+
+/// Слово из любой таблицы intent-ов: короткие ключи — по префиксу
+/// ("add" не должен съесть "haddock"), фразы — по отдельным словам.
+fn is_keyword(token: &str) -> bool {
+    RuleIntent::ALL
+        .iter()
+        .flat_map(|intent| intent.keywords())
+        .any(|(keyword, _)| {
+            let keyword = keyword.trim();
+            if keyword.contains(' ') {
+                keyword.split(' ').any(|w| w == token)
+            } else if keyword.chars().count() < 4 {
+                token.starts_with(keyword)
+            } else {
+                token.contains(keyword)
+            }
+        })
+}
+
+/// Основа названия для поиска по подстроке: "лосося" → "лосос",
+/// "tomatoes" → "tomat". Слова короче `MIN_STEM_CHARS` не трогаются.
+pub fn search_stem(name: &str) -> String {
+    name.split_whitespace()
+        .map(|word| {
+            let word = word.to_lowercase();
+            ENDINGS
+                .iter()
+                .find_map(|ending| {
+                    word.strip_suffix(ending)
+                        .filter(|stem| stem.chars().count() >= MIN_STEM_CHARS)
+                })
+                .map(str::to_string)
+                .unwrap_or(word)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// ── Answer without LLM ───────────────────────────────────────────────────────
+
+struct Labels {
+    inventory: &'static str,
+    expiring: &'static str,
+    nothing_expiring: &'static str,
+    dishes: &'static str,
+    recipes: &'static str,
+    drafts: &'static str,
+    draft: &'static str,
+    briefing: &'static str,
+    low_stock: &'static str,
+    margin: &'static str,
+    expired: &'static str,
+    items: &'static str,
+    empty: &'static str,
+    /// `{}` → сколько строк не показано.
+    more: &'static str,
+}
+
+fn labels(locale: Language) -> Labels {
+    match locale {
+        Language::Ru => Labels {
+            inventory: "Склад",
+            expiring: "Скоро истекает срок",
+            nothing_expiring: "В ближайшие дни ничего не истекает.",
+            dishes: "Блюда",
+            recipes: "Рецепты",
+            drafts: "Черновики закупок",
+            draft: "Черновик закупки",
+            briefing: "Сводка на сегодня",
+            low_stock: "Заканчивается",
+            margin: "Низкая маржа",
+            expired: "просрочено",
+            items: "позиций",
+            empty: "Ничего не найдено.",
+            more: "…и ещё {}",
+        },
+        Language::Pl => Labels {
+            inventory: "Magazyn",
+            expiring: "Kończy się termin",
+            nothing_expiring: "W najbliższych dniach nic się nie przeterminuje.",
+            dishes: "Dania",
+            recipes: "Przepisy",
+            drafts: "Szkice zamówień",
+            draft: "Szkic zamówienia",
+            briefing: "Podsumowanie dnia",
+            low_stock: "Kończy się",
+            margin: "Niska marża",
+            expired: "przeterminowane",
+            items: "pozycji",
+            empty: "Nic nie znaleziono.",
+            more: "…i jeszcze {}",
+        },
+        Language::Uk => Labels {
+            inventory: "Склад",
+            expiring: "Незабаром спливає термін",
+            nothing_expiring: "Найближчими днями нічого не спливає.",
+            dishes: "Страви",
+            recipes: "Рецепти",
+            drafts: "Чернетки закупівель",
+            draft: "Чернетка закупівлі",
+            briefing: "Зведення на сьогодні",
+            low_stock: "Закінчується",
+            margin: "Низька маржа",
+            expired: "прострочено",
+            items: "позицій",
+            empty: "Нічого не знайдено.",
+            more: "…і ще {}",
+        },
+        Language::En => Labels {
+            inventory: "Inventory",
+            expiring: "Expiring soon",
+            nothing_expiring: "Nothing expires in the coming days.",
+            dishes: "Dishes",
+            recipes: "Recipes",
+            drafts: "Purchase drafts",
+            draft: "Purchase draft",
+            briefing: "Today's briefing",
+            low_stock: "Running low",
+            margin: "Low margin",
+            expired: "expired",
+            items: "items",
+            empty: "Nothing found.",
+            more: "…and {} more",
+        },
+    }
+}
+
+/// Ответ по результатам read tools без LLM — для планов rule planner-а.
+pub fn render_answer(locale: Language, results: &[ToolResult]) -> String {
+    let labels = labels(locale);
+    let sections: Vec<String> = results
+        .iter()
+        .filter_map(|r| render_result(&labels, r))
+        .collect();
+    if sections.is_empty() {
+        return labels.empty.to_string();
+    }
+    sections.join("\n\n")
+}
+
+fn render_result(l: &Labels, result: &ToolResult) -> Option<String> {
+    let data = &result.data;
+    if let Some(explanation) = data.get("explanation").and_then(Value::as_str) {
+        return Some(explanation.to_string());
+    }
+    let section = match result.tool_name.as_str() {
+        "get_inventory" => list_section(l, l.inventory, array(data, "inventory"), |item| {
+            let expired = if str_field(item, "severity") == "Expired" {
+                format!(" ({})", l.expired)
+            } else {
+                String::new()
+            };
+            format!(
+                "{}: {}{}",
+                str_field(item, "name"),
+                amount(item, "quantity"),
+                expired
+            )
+        }),
+        "get_expiring_soon" => {
+            let items = array(data, "expiring");
+            if items.is_empty() {
+                return Some(l.nothing_expiring.to_string());
+            }
+            list_section(l, l.expiring, items, |item| {
+                format!(
+                    "{}: {} — {}",
+                    str_field(item, "name"),
+                    amount(item, "quantity"),
+                    date(str_field(item, "expires_at"))
+                )
+            })
+        }
+        "get_dishes" => list_section(l, l.dishes, array(data, "dishes"), |item| {
+            format!(
+                "{} — €{:.2}",
+                str_field(item, "name"),
+                item["price_cents"].as_i64().unwrap_or(0) as f64 / 100.0
+            )
+        }),
+        "get_recipes" => list_section(l, l.recipes, array(data, "recipes"), |item| {
+            str_field(item, "name").to_string()
+        }),
+        "list_purchase_drafts" => list_section(l, l.drafts, array(data, "drafts"), |item| {
+            format!(
+                "{} — {} — {} — {} {}",
+                date(str_field(item, "created_at")),
+                item["supplier_name"].as_str().unwrap_or("—"),
+                str_field(item, "status"),
+                item["items_count"].as_i64().unwrap_or(0),
+                l.items
+            )
+        }),
+        "get_purchase_draft" => {
+            let draft = data.get("draft").filter(|d| !d.is_null())?;
+            let title = format!(
+                "{} ({}, {})",
+                l.draft,
+                draft["supplier_name"].as_str().unwrap_or("—"),
+                str_field(draft, "status")
+            );
+            list_section(l, &title, array(draft, "items"), |item| {
+                format!(
+                    "{}: {} {}",
+                    str_field(item, "ingredient_name"),
+                    number(&item["quantity"]),
+                    str_field(item, "unit")
+                )
+            })
+        }
+        "get_daily_briefing" => {
+            let briefing = data.get("briefing")?;
+            let expiring = list_section(
+                l,
+                l.expiring,
+                array(&briefing["expiring_soon"], "items"),
+                |item| {
+                    format!(
+                        "{}: {} — {}",
+                        str_field(item, "name"),
+                        amount(item, "remaining"),
+                        str_field(item, "expires_at")
+                    )
+                },
+            );
+            let low_stock = list_section(
+                l,
+                l.low_stock,
+                array(&briefing["low_stock"], "items"),
+                |item| format!("{}: {}", str_field(item, "name"), amount(item, "remaining")),
+            );
+            let drafts = list_section(
+                l,
+                l.drafts,
+                array(&briefing["open_purchase_drafts"], "items"),
+                |item| {
+                    format!(
+                        "{} — {} {}",
+                        str_field(item, "supplier"),
+                        item["items_count"].as_i64().unwrap_or(0),
+                        l.items
+                    )
+                },
+            );
+            let margin = list_section(
+                l,
+                l.margin,
+                array(&briefing["dish_margin_warnings"], "items"),
+                |item| {
+                    format!(
+                        "{} — food cost {}%",
+                        str_field(item, "name"),
+                        str_field(item, "food_cost_percent")
+                    )
+                },
+            );
+            let parts: Vec<String> = [expiring, low_stock, drafts, margin]
+                .into_iter()
+                .filter(|s| !s.is_empty())
+                .collect();
+            if parts.is_empty() {
+                format!("{}: {}", l.briefing, l.empty)
+            } else {
+                format!("{}\n\n{}", l.briefing, parts.join("\n\n"))
+            }
+        }
+        _ => return None,
+    };
+    (!section.is_empty()).then_some(section)
+}
+
+/// "Заголовок:" + bullet-строки, не больше `MAX_LINES`. Пустой список → "".
+fn list_section(
+    l: &Labels,
+    title: &str,
+    items: &[Value],
+    line: impl Fn(&Value) -> String,
+) -> String {
+    if items.is_empty() {
+        return String::new();
+    }
+    let mut lines: Vec<String> = vec![format!("{}:", title)];
+    lines.extend(
+        items
+            .iter()
+            .take(MAX_LINES)
+            .map(|i| format!("• {}", line(i))),
+    );
+    if items.len() > MAX_LINES {
+        lines.push(l.more.replace("{}", &(items.len() - MAX_LINES).to_string()));
+    }
+    lines.join("\n")
+}
+
+fn array<'a>(data: &'a Value, key: &str) -> &'a [Value] {
+    data.get(key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+fn str_field<'a>(item: &'a Value, key: &str) -> &'a str {
+    item.get(key).and_then(Value::as_str).unwrap_or("")
+}
+
+/// "5 kg" из `quantity` / `remaining` + `unit`.
+fn amount(item: &Value, key: &str) -> String {
+    format!("{} {}", number(&item[key]), str_field(item, "unit"))
+        .trim_end()
+        .to_string()
+}
+
+/// 5.0 → "5", 0.25 → "0.25".
+fn number(value: &Value) -> String {
+    let n = value.as_f64().unwrap_or(0.0);
+    let s = format!("{:.2}", n);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Дата без времени из `OffsetDateTime::to_string()`.
+fn date(value: &str) -> &str {
+    value.split(' ').next().unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planned(message: &str) -> RulePlan {
+        plan(message).unwrap_or_else(|| panic!("no plan for {message:?}"))
+    }
+
+    fn only_call(plan: &RulePlan) -> &ToolCall {
+        assert_eq!(plan.plan.tool_calls.len(), 1);
+        &plan.plan.tool_calls[0]
+    }
+
+    #[test]
+    fn read_tools_in_all_languages() {
+        let cases = [
+            ("покажи склад", CopilotTool::GetInventory),
+            ("show inventory", CopilotTool::GetInventory),
+            ("co mamy na magazynie?", CopilotTool::GetInventory),
+            ("що в нас є на складі", CopilotTool::GetInventory),
+            ("что истекает?", CopilotTool::GetExpiringSoon),
+            ("what's expiring", CopilotTool::GetExpiringSoon),
+            (
+                "czemu kończy się termin ważności",
+                CopilotTool::GetExpiringSoon,
+            ),
+            ("що спливає цього тижня", CopilotTool::GetExpiringSoon),
+            ("покажи блюда", CopilotTool::GetDishes),
+            ("my recipes", CopilotTool::GetRecipes),
+            ("list purchase drafts", CopilotTool::ListPurchaseDrafts),
+            ("покажи последнюю закупку", CopilotTool::GetPurchaseDraft),
+            ("brief me", CopilotTool::GetDailyBriefing),
+            ("що сьогодні важливо?", CopilotTool::GetDailyBriefing),
+        ];
+        for (message, tool) in cases {
+            let plan = planned(message);
+            assert_eq!(only_call(&plan).tool, tool, "{message}");
+            assert!(!plan.should_escalate(), "{message}: {}", plan.confidence);
+            assert!(!plan.plan.requires_confirmation);
+        }
+    }
+
+    #[test]
+    fn expiring_window_is_extracted() {
+        let plan = planned("что истекает в ближайшие 5 дней");
+        assert_eq!(only_call(&plan).args["days"], 5);
+        let plan = planned("what expires this week");
+        assert_eq!(only_call(&plan).args["days"], 7);
+    }
+
+    #[test]
+    fn stock_writes_extract_name_quantity_and_unit() {
+        let plan = planned("добавь 5 кг лосося");
+        let call = only_call(&plan);
+        assert_eq!(call.tool, CopilotTool::PrepareInventoryUpdate);
+        assert_eq!(call.args["ingredient_name"], "лосося");
+        assert_eq!(call.args["quantity"], 5.0);
+        assert_eq!(call.args["unit"], "kg");
+        assert!(plan.plan.requires_confirmation);
+        assert!(!plan.should_escalate());
+
+        let call = planned("add 3 pcs of haddock to stock").plan.tool_calls[0].clone();
+        assert_eq!(call.args["ingredient_name"], "haddock");
+        assert_eq!(call.args["unit"], "pcs");
+
+        let call = planned("spisz 500 g masła, bo się zepsuło").plan.tool_calls[0].clone();
+        assert_eq!(call.tool, CopilotTool::WriteOffInventory);
+        assert_eq!(call.args["ingredient_name"], "masła");
+        assert_eq!(call.args["quantity"], 0.5);
+        assert_eq!(call.args["reason"], "waste");
+
+        let call = planned("write off 2 l of milk, it expired").plan.tool_calls[0].clone();
+        assert_eq!(call.args["ingredient_name"], "milk");
+        assert_eq!(call.args["reason"], "expired");
+
+        let call = planned("лосося должно быть 3кг после инвентаризации")
+            .plan
+            .tool_calls[0]
+            .clone();
+        assert_eq!(call.tool, CopilotTool::AdjustInventoryQuantity);
+        assert_eq!(call.args["target_quantity"], 3.0);
+        assert_eq!(call.args["reason"], "inventory_check");
+
+        let call = planned("додай 2 кілограми моркви").plan.tool_calls[0].clone();
+        assert_eq!(call.args["ingredient_name"], "моркви");
+        assert_eq!(call.args["unit"], "kg");
+    }
+
+    #[test]
+    fn dish_price_is_converted_to_cents() {
+        let call = planned("измени цену цезаря на 12,50 евро").plan.tool_calls[0].clone();
+        assert_eq!(call.tool, CopilotTool::UpdateDishPrice);
+        assert_eq!(call.args["dish_name"], "цезар");
+        assert_eq!(call.args["new_price_cents"], 1250);
+
+        let call = planned("set price of caesar salad to €14").plan.tool_calls[0].clone();
+        assert_eq!(call.args["dish_name"], "caesar salad");
+        assert_eq!(call.args["new_price_cents"], 1400);
+    }
+
+    #[test]
+    fn incomplete_or_complex_requests_go_to_the_llm() {
+        // Нет количества — нечего подтверждать
+        assert!(plan("добавь лосось").is_none());
+        // Нет цены
+        assert!(plan("what's the price of caesar").is_none());
+        // AI запросы и болтовня
+        assert!(plan("что приготовить на ужин?").is_none());
+        assert!(plan("asdf qwerty").is_none());
+        // Уточнение к прошлому ходу
+        assert!(plan("make it 20 kg instead").is_none_or(|p| p.should_escalate()));
+        // Несколько действий
+        assert!(plan("add 5 kg salmon and then create a recipe with it")
+            .is_none_or(|p| p.should_escalate()));
+        // Без единицы — только как fallback
+        assert!(planned("add 5 salmon").should_escalate());
+    }
+
+    #[test]
+    fn stems_strip_case_endings_only() {
+        assert_eq!(search_stem("лосося"), "лосос");
+        assert_eq!(search_stem("Tomatoes"), "tomat");
+        assert_eq!(search_stem("куриное филе"), "курин филе");
+        assert_eq!(search_stem("rice"), "rice");
+        assert_eq!(search_stem("salmon"), "salmon");
+    }
+
+    #[test]
+    fn answers_render_without_llm() {
+        let results = [
+            ToolResult {
+                tool_name: "get_expiring_soon".to_string(),
+                data: json!({ "expiring": [
+                    { "name": "Salmon", "quantity": 1.5, "unit": "kg", "expires_at": "2026-10-18 0:00:00.0 +00:00:00" }
+                ] }),
+            },
+            ToolResult {
+                tool_name: "get_dishes".to_string(),
+                data: json!({ "dishes": [{ "name": "Caesar", "price_cents": 1250 }], "total": 1 }),
+            },
+        ];
+        assert_eq!(
+            render_answer(Language::En, &results),
+            "Expiring soon:\n• Salmon: 1.5 kg — 2026-10-18\n\nDishes:\n• Caesar — €12.50"
+        );
+        let empty = [ToolResult {
+            tool_name: "get_expiring_soon".to_string(),
+            data: json!({ "expiring": [] }),
+        }];
+        assert_eq!(
+            render_answer(Language::Ru, &empty),
+            "В ближайшие дни ничего не истекает."
+        );
+    }
+}
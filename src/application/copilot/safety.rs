@@ -84,8 +84,29 @@ pub fn validate_write_execution(ctx: &CopilotContext, plan: &ActionPlan) -> Resu
         ));
     }
 
-    // Проверить write permission для типа операции
-    match plan.plan_type {
+    // Многошаговый план — каждый шаг проверяется как отдельный план
+    if matches!(plan.plan_type, ActionPlanType::MultiStep) {
+        if plan.steps.is_empty() {
+            return Err(AppError::validation("Multi-step plan has no steps."));
+        }
+        for step in &plan.steps {
+            if matches!(step.plan_type, ActionPlanType::MultiStep) {
+                return Err(AppError::validation("Plan steps cannot be nested."));
+            }
+            check_plan_type_permission(ctx, &step.plan_type)?;
+        }
+        return Ok(());
+    }
+
+    check_plan_type_permission(ctx, &plan.plan_type)
+}
+
+/// Write permission для типа операции.
+fn check_plan_type_permission(
+    ctx: &CopilotContext,
+    plan_type: &ActionPlanType,
+) -> Result<(), AppError> {
+    match plan_type {
         ActionPlanType::AddInventoryItems
         | ActionPlanType::UpdateInventoryItems
         | ActionPlanType::AdjustInventoryQuantity
@@ -136,7 +157,7 @@ pub fn validate_write_execution(ctx: &CopilotContext, plan: &ActionPlan) -> Resu
                 return Err(AppError::authorization("No permission to create dishes."));
             }
         }
-        ActionPlanType::NoWriteAction => {}
+        ActionPlanType::MultiStep | ActionPlanType::NoWriteAction => {}
     }
 
     Ok(())
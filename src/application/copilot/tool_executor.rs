@@ -3,6 +3,10 @@
 //! Read tools выполняются немедленно.
 //! Write tools только подготавливают ActionPlan (не выполняют).
 //! Фактическое выполнение write actions — через confirm endpoint.
+//!
+//! Многошаговый план: read шаги по порядку с подстановкой `$ref`, все write
+//! шаги — в один ActionPlan; при подтверждении шаги выполняются по порядку,
+//! а если какой-то падает — выполненные откатываются компенсациями.
 
 use std::sync::Arc;
 
@@ -19,14 +23,19 @@ use crate::application::purchase_draft::{
 use crate::application::recipe::RecipeService;
 use crate::application::recipe_v2_service::RecipeV2Service;
 use crate::application::sous_chef::{PlanRequest, SousChefPlannerService};
+use crate::application::waste::{LogWasteInput, WasteService};
+use crate::domain::waste::WasteReason;
 use crate::shared::Language;
 use crate::shared::PaginationParams;
 use crate::shared::{AppError, AppResult, TenantId, UserId};
 
-use super::actions::{ActionChange, ActionPlan, ActionPlanType};
+use super::actions::{ActionChange, ActionPlan, ActionPlanType, ActionStep};
 use super::context::CopilotContext;
+use super::plan_graph::{self, StepOutputs};
 use super::planner::ToolCall;
+use super::rule_planner;
 use super::tools::CopilotTool;
+use super::undo::{self, Compensation};
 
 /// Результат выполнения одного read tool.
 #[derive(Debug)]
@@ -36,6 +45,66 @@ pub struct ToolResult {
     pub data: serde_json::Value,
 }
 
+/// Результат выполнения write tool.
+#[derive(Debug)]
+pub struct WriteOutcome {
+    /// Сообщение для пользователя.
+    pub message: String,
+    /// Обратная операция для undo (None — действие необратимо).
+    pub compensation: Option<Compensation>,
+    /// Что создал tool (id и т.п.) — доступно следующим шагам через `$ref`.
+    pub output: serde_json::Value,
+}
+
+/// Ошибка write action. `leftover` — компенсации шагов MultiStep плана,
+/// которые не удалось откатить после ошибки: их сохраняют, чтобы undo
+/// довёл откат.
+#[derive(Debug)]
+pub struct WriteError {
+    pub error: AppError,
+    pub leftover: Option<Compensation>,
+}
+
+impl From<AppError> for WriteError {
+    fn from(error: AppError) -> Self {
+        Self {
+            error,
+            leftover: None,
+        }
+    }
+}
+
+impl WriteOutcome {
+    fn new(message: String, compensation: Compensation) -> Self {
+        Self {
+            message,
+            compensation: Some(compensation),
+            output: serde_json::Value::Null,
+        }
+    }
+
+    fn irreversible(message: String) -> Self {
+        Self {
+            message,
+            compensation: None,
+            output: serde_json::Value::Null,
+        }
+    }
+
+    fn with_output(mut self, output: serde_json::Value) -> Self {
+        self.output = output;
+        self
+    }
+}
+
+/// Итог read-фазы плана.
+#[derive(Debug, Default)]
+pub struct ReadPhase {
+    pub results: Vec<ToolResult>,
+    /// Write шаги с подставленными результатами read шагов.
+    pub write_calls: Vec<ToolCall>,
+}
+
 /// Сервисы доступные ToolExecutor-у.
 pub struct ToolExecutorServices {
     pub inventory: Arc<InventoryService>,
@@ -46,6 +115,7 @@ pub struct ToolExecutorServices {
     pub sous_chef: Arc<SousChefPlannerService>,
     pub catalog: Arc<CatalogService>,
     pub purchase_drafts: Arc<PurchaseDraftService>,
+    pub waste: Arc<WasteService>,
 }
 
 pub struct ToolExecutor {
@@ -57,36 +127,76 @@ impl ToolExecutor {
         Self { services }
     }
 
-    /// Выполнить все READ tools из плана.
-    /// Write tools пропускаются — они попадут в ActionPlan.
-    pub async fn run_read_tools(
-        &self,
-        ctx: &CopilotContext,
-        tool_calls: &[ToolCall],
-    ) -> Vec<ToolResult> {
-        let mut results = Vec::new();
+    /// Выполнить все READ tools из плана по порядку, подставляя `$ref`
+    /// на результаты предыдущих шагов.
+    /// Write tools не выполняются — они попадут в ActionPlan. Если аргументы
+    /// write шага не резолвятся, пропускаются все write шаги: план
+    /// подтверждается только целиком.
+    pub async fn run_read_tools(&self, ctx: &CopilotContext, tool_calls: &[ToolCall]) -> ReadPhase {
+        let mut phase = ReadPhase::default();
+        let mut outputs = StepOutputs::default();
+        let mut writes_valid = true;
 
         for call in tool_calls {
+            let args = match outputs.resolve_args(&call.args) {
+                Ok(args) => args,
+                Err(e) => {
+                    tracing::warn!(
+                        "Plan step {} ({}) not resolved: {e}",
+                        call.id,
+                        call.tool.name()
+                    );
+                    phase.results.push(ToolResult {
+                        tool_name: format!("{}_skipped", call.tool.name()),
+                        data: json!({
+                            "skipped": true,
+                            "reason": "unresolved_reference",
+                            "step": call.id,
+                            "explanation": e.to_string(),
+                        }),
+                    });
+                    writes_valid &= !call.tool.is_write();
+                    continue;
+                }
+            };
+
             if call.tool.is_write() {
-                continue; // write tools выполняются только после confirmation
+                // write tools выполняются только после confirmation
+                phase.write_calls.push(ToolCall {
+                    id: call.id.clone(),
+                    tool: call.tool.clone(),
+                    args,
+                });
+                continue;
             }
-            match self.execute_read_tool(ctx, &call.tool, &call.args).await {
-                Ok(result) => results.push(result),
+
+            match self.execute_read_tool(ctx, &call.tool, &args).await {
+                Ok(result) => {
+                    outputs.insert(&call.id, result.data.clone());
+                    phase.results.push(result);
+                }
                 Err(e) => {
                     tracing::warn!("Tool {} failed: {e}", call.tool.name());
-                    results.push(ToolResult {
+                    let data = json!({ "error": e.to_string() });
+                    // Ссылки на упавший шаг не резолвятся
+                    outputs.insert(&call.id, data.clone());
+                    phase.results.push(ToolResult {
                         tool_name: call.tool.name().to_string(),
-                        data: json!({ "error": e.to_string() }),
+                        data,
                     });
                 }
             }
         }
 
-        results
+        if !writes_valid {
+            phase.write_calls.clear();
+        }
+        phase
     }
 
-    /// Подготовить ActionPlan для первого write tool в списке.
-    /// (По одному write tool за раз — пользователь должен подтвердить каждый).
+    /// Подготовить ActionPlan для write tools плана.
+    /// Один write tool — обычный план; несколько — MultiStep план со всеми
+    /// изменениями на одном экране подтверждения.
     ///
     /// Возвращает (Option<ActionPlan>, Option<ToolResult>):
     /// - ToolResult генерируется когда write tool не нужно выполнять
@@ -97,10 +207,126 @@ impl ToolExecutor {
         tool_calls: &[ToolCall],
         tool_results: &[ToolResult],
     ) -> (Option<ActionPlan>, Option<ToolResult>) {
-        let Some(write_call) = tool_calls.iter().find(|c| c.tool.is_write()) else {
-            return (None, None);
-        };
+        let write_calls: Vec<&ToolCall> = tool_calls.iter().filter(|c| c.tool.is_write()).collect();
+        match write_calls.as_slice() {
+            [] => (None, None),
+            [write_call] => self.prepare_write_call(ctx, write_call, tool_results).await,
+            _ => {
+                self.prepare_multi_step_plan(ctx, &write_calls, tool_results)
+                    .await
+            }
+        }
+    }
 
+    /// Собрать write шаги в один MultiStep план.
+    /// Каждый шаг проходит тот же preview, что и одиночный write tool;
+    /// если хоть один шаг пропущен — пропускается весь план.
+    async fn prepare_multi_step_plan(
+        &self,
+        ctx: &CopilotContext,
+        write_calls: &[&ToolCall],
+        tool_results: &[ToolResult],
+    ) -> (Option<ActionPlan>, Option<ToolResult>) {
+        let mut previews = StepOutputs::default();
+        // Рецепты, которые создаёт этот же план: lowercase name → step id
+        let mut planned_recipes: Vec<(String, String)> = Vec::new();
+        let mut steps: Vec<ActionStep> = Vec::with_capacity(write_calls.len());
+
+        for (idx, call) in write_calls.iter().enumerate() {
+            let mut args = match previews.resolve_args(&call.args) {
+                Ok(args) => args,
+                Err(e) => {
+                    let info = ToolResult {
+                        tool_name: format!("{}_skipped", call.tool.name()),
+                        data: json!({
+                            "skipped": true,
+                            "reason": "unresolved_reference",
+                            "step": call.id,
+                            "explanation": e.to_string(),
+                        }),
+                    };
+                    return (None, Some(info));
+                }
+            };
+
+            // Блюдо по рецепту, который создаётся раньше в этом же плане:
+            // id появится только при выполнении — ссылаемся на шаг.
+            if matches!(call.tool, CopilotTool::CreateDish) && !args.contains_key("recipe_id") {
+                let recipe_name = args
+                    .get("recipe_name")
+                    .or_else(|| args.get("recipe"))
+                    .and_then(|v| v.as_str())
+                    .map(|n| n.trim().to_lowercase());
+                if let Some((_, step_id)) = planned_recipes
+                    .iter()
+                    .find(|(name, _)| Some(name) == recipe_name.as_ref())
+                {
+                    args.insert(
+                        "recipe_id".to_string(),
+                        json!({ (plan_graph::REF_KEY): format!("{}.recipe_id", step_id) }),
+                    );
+                }
+            }
+
+            let call = ToolCall {
+                id: call.id.clone(),
+                tool: call.tool.clone(),
+                args,
+            };
+            let (step_plan, info) = self.prepare_write_call(ctx, &call, tool_results).await;
+            let Some(step_plan) = step_plan else {
+                return (None, info);
+            };
+
+            if matches!(call.tool, CopilotTool::CreateRecipe) {
+                if let Some(name) = step_plan
+                    .payload
+                    .get("recipe_name")
+                    .and_then(|v| v.as_str())
+                {
+                    planned_recipes.push((name.to_lowercase(), call.id.clone()));
+                }
+            }
+            previews.insert_pending(&call.id, step_plan.payload.clone());
+
+            let step_no = idx + 1;
+            steps.push(ActionStep {
+                id: call.id,
+                plan_type: step_plan.plan_type,
+                write_tool: call.tool,
+                changes: step_plan
+                    .changes
+                    .into_iter()
+                    .map(|c| ActionChange {
+                        entity: format!("Step {}: {}", step_no, c.entity),
+                        ..c
+                    })
+                    .collect(),
+                payload: step_plan.payload,
+            });
+        }
+
+        let changes = steps.iter().flat_map(|s| s.changes.clone()).collect();
+        (
+            Some(ActionPlan {
+                id: Uuid::new_v4(),
+                plan_type: ActionPlanType::MultiStep,
+                changes,
+                write_tool: None,
+                payload: serde_json::Value::Null,
+                steps,
+            }),
+            None,
+        )
+    }
+
+    /// Подготовить preview одного write tool.
+    async fn prepare_write_call(
+        &self,
+        ctx: &CopilotContext,
+        write_call: &ToolCall,
+        tool_results: &[ToolResult],
+    ) -> (Option<ActionPlan>, Option<ToolResult>) {
         // Для SendPurchaseOrder: резолвим "last" → реальный uuid и добавляем
         // позиции в preview из БД.
         let mut payload = serde_json::to_value(&write_call.args).unwrap_or(json!({}));
@@ -358,17 +584,24 @@ impl ToolExecutor {
                     dish_name,
                     recipe_id,
                     recipe_name,
-                    selling_price_cents,
+                    pricing,
                     description,
                     dish_changes,
                 } => {
                     if let Some(obj) = payload.as_object_mut() {
                         obj.insert("dish_name".to_string(), json!(dish_name));
-                        obj.insert("recipe_id".to_string(), json!(recipe_id.to_string()));
-                        obj.insert(
-                            "selling_price_cents".to_string(),
-                            json!(selling_price_cents),
-                        );
+                        // Без id payload сохраняет `$ref` на шаг create_recipe
+                        if let Some(recipe_id) = recipe_id {
+                            obj.insert("recipe_id".to_string(), json!(recipe_id.to_string()));
+                        }
+                        match pricing {
+                            DishPricing::Fixed(cents) => {
+                                obj.insert("selling_price_cents".to_string(), json!(cents));
+                            }
+                            DishPricing::FoodCostPercent(pct) => {
+                                obj.insert("target_food_cost_percent".to_string(), json!(pct));
+                            }
+                        }
                         if let Some(d) = description {
                             obj.insert("description".to_string(), json!(d));
                         }
@@ -437,10 +670,12 @@ impl ToolExecutor {
                 changes,
                 write_tool: Some(write_call.tool.clone()),
                 payload,
+                steps: vec![],
             }),
             None,
         )
     }
+
     async fn resolve_send_purchase_order_preview(
         &self,
         ctx: &CopilotContext,
@@ -911,7 +1146,7 @@ impl ToolExecutor {
         user_id: UserId,
         tenant_id: TenantId,
         payload: &serde_json::Value,
-    ) -> AppResult<String> {
+    ) -> AppResult<WriteOutcome> {
         let name_str = payload
             .get("recipe_name")
             .and_then(|v| v.as_str())
@@ -970,13 +1205,24 @@ impl ToolExecutor {
             )
             .await?;
 
-        Ok(format!(
+        let message = format!(
             "Created recipe '{}' (id: {}) with {} ingredient(s), {} serving(s).",
             recipe.name().as_str(),
             recipe.id().as_uuid(),
             recipe.ingredients().len(),
             recipe.servings().count(),
-        ))
+        );
+        Ok(WriteOutcome::new(
+            message,
+            Compensation::DeleteRecipe {
+                recipe_id: recipe.id().as_uuid(),
+                recipe_name: recipe.name().as_str().to_string(),
+            },
+        )
+        .with_output(json!({
+            "recipe_id": recipe.id().as_uuid().to_string(),
+            "recipe_name": recipe.name().as_str(),
+        })))
     }
 
     /// Pre-validate args для CreateDish: резолвим recipe_name → recipe_id,
@@ -1000,21 +1246,33 @@ impl ToolExecutor {
             }
         };
 
-        // 2. selling_price_cents (required, > 0)
-        let selling_price_cents: i64 =
-            if let Some(c) = args.get("selling_price_cents").and_then(|v| v.as_i64()) {
-                c
-            } else if let Some(eur) = args.get("selling_price").and_then(|v| v.as_f64()) {
-                (eur * 100.0).round() as i64
-            } else {
-                return CreateDishPreview::Invalid {
-                    reason: "missing selling_price_cents (or selling_price in EUR)".into(),
-                };
-            };
-        if selling_price_cents <= 0 {
+        // 2. selling_price_cents (> 0) или target_food_cost_percent (0–100)
+        let pricing = if let Some(c) = args.get("selling_price_cents").and_then(|v| v.as_i64()) {
+            DishPricing::Fixed(c)
+        } else if let Some(eur) = args.get("selling_price").and_then(|v| v.as_f64()) {
+            DishPricing::Fixed((eur * 100.0).round() as i64)
+        } else if let Some(pct) = args
+            .get("target_food_cost_percent")
+            .and_then(|v| v.as_f64())
+        {
+            DishPricing::FoodCostPercent(pct)
+        } else {
             return CreateDishPreview::Invalid {
-                reason: "selling_price must be greater than 0".into(),
+                reason: "missing selling_price_cents (or selling_price in EUR, or target_food_cost_percent)".into(),
             };
+        };
+        match pricing {
+            DishPricing::Fixed(cents) if cents <= 0 => {
+                return CreateDishPreview::Invalid {
+                    reason: "selling_price must be greater than 0".into(),
+                }
+            }
+            DishPricing::FoodCostPercent(pct) if pct <= 0.0 || pct >= 100.0 => {
+                return CreateDishPreview::Invalid {
+                    reason: "target_food_cost_percent must be between 0 and 100".into(),
+                }
+            }
+            _ => {}
         }
 
         // 3. recipe_name (required) → resolve to recipe_id
@@ -1037,63 +1295,100 @@ impl ToolExecutor {
             .and_then(|v| v.as_str())
             .map(str::to_string);
 
-        // 4. Search recipes by name (list all, filter case-insensitive)
-        let pagination = crate::shared::pagination::PaginationParams {
-            page: Some(1),
-            per_page: Some(200),
-        };
-        let recipes = match self
-            .services
-            .recipes_v1
-            .list_recipes(ctx.tenant_id.clone(), &pagination)
-            .await
-        {
-            Ok(p) => p.items,
-            Err(_) => {
-                return CreateDishPreview::Invalid {
-                    reason: "could not load recipes".into(),
-                }
-            }
-        };
+        // Рецепт создаётся раньше в этом же плане — id будет при выполнении
+        let deferred_recipe = args.get("recipe_id").is_some_and(plan_graph::has_refs);
 
-        let q_low = recipe_query.to_lowercase();
-        let exact: Vec<_> = recipes
-            .iter()
-            .filter(|r| r.name().as_str().to_lowercase() == q_low)
-            .collect();
-        let partial: Vec<_> = recipes
-            .iter()
-            .filter(|r| r.name().as_str().to_lowercase().contains(&q_low))
-            .collect();
-
-        let chosen_recipe = if exact.len() == 1 {
-            exact[0]
-        } else if exact.is_empty() && partial.len() == 1 {
-            partial[0]
-        } else if exact.is_empty() && partial.is_empty() {
-            return CreateDishPreview::RecipeNotFound {
-                query: recipe_query,
-            };
+        let (recipe_id, recipe_name) = if deferred_recipe {
+            (None, recipe_query)
         } else {
-            let candidates: Vec<String> = if !exact.is_empty() {
-                exact
-                    .iter()
-                    .map(|r| r.name().as_str().to_string())
-                    .collect()
-            } else {
-                partial
-                    .iter()
-                    .map(|r| r.name().as_str().to_string())
-                    .collect()
+            // 4. Search recipes by name (list all, filter case-insensitive)
+            let pagination = crate::shared::pagination::PaginationParams {
+                page: Some(1),
+                per_page: Some(200),
+            };
+            let recipes = match self
+                .services
+                .recipes_v1
+                .list_recipes(ctx.tenant_id.clone(), &pagination)
+                .await
+            {
+                Ok(p) => p.items,
+                Err(_) => {
+                    return CreateDishPreview::Invalid {
+                        reason: "could not load recipes".into(),
+                    }
+                }
             };
-            return CreateDishPreview::AmbiguousRecipe {
-                query: recipe_query,
-                candidates,
+
+            let q_low = recipe_query.to_lowercase();
+            let exact: Vec<_> = recipes
+                .iter()
+                .filter(|r| r.name().as_str().to_lowercase() == q_low)
+                .collect();
+            let partial: Vec<_> = recipes
+                .iter()
+                .filter(|r| r.name().as_str().to_lowercase().contains(&q_low))
+                .collect();
+
+            let chosen_recipe = if exact.len() == 1 {
+                exact[0]
+            } else if exact.is_empty() && partial.len() == 1 {
+                partial[0]
+            } else if exact.is_empty() && partial.is_empty() {
+                return CreateDishPreview::RecipeNotFound {
+                    query: recipe_query,
+                };
+            } else {
+                let candidates: Vec<String> = if !exact.is_empty() {
+                    exact
+                        .iter()
+                        .map(|r| r.name().as_str().to_string())
+                        .collect()
+                } else {
+                    partial
+                        .iter()
+                        .map(|r| r.name().as_str().to_string())
+                        .collect()
+                };
+                return CreateDishPreview::AmbiguousRecipe {
+                    query: recipe_query,
+                    candidates,
+                };
             };
+
+            (
+                Some(chosen_recipe.id()),
+                chosen_recipe.name().as_str().to_string(),
+            )
         };
 
-        let recipe_id = chosen_recipe.id();
-        let recipe_name = chosen_recipe.name().as_str().to_string();
+        // Цена от себестоимости существующего рецепта считается сразу,
+        // чтобы preview показывал ту же цену, что будет применена
+        let pricing = match (pricing, recipe_id) {
+            (DishPricing::FoodCostPercent(pct), Some(id)) => {
+                match self
+                    .services
+                    .recipes_v1
+                    .calculate_cost(id, ctx.tenant_id)
+                    .await
+                {
+                    Ok(cost) => match price_for_food_cost(cost.cost_per_serving.as_cents(), pct) {
+                        Ok(cents) => DishPricing::Fixed(cents),
+                        Err(e) => {
+                            return CreateDishPreview::Invalid {
+                                reason: e.to_string(),
+                            }
+                        }
+                    },
+                    Err(_) => {
+                        return CreateDishPreview::Invalid {
+                            reason: "could not calculate recipe cost".into(),
+                        }
+                    }
+                }
+            }
+            (pricing, _) => pricing,
+        };
 
         // 5. Check dish name uniqueness (case-insensitive)
         let dish_pagination = crate::shared::pagination::PaginationParams {
@@ -1128,16 +1423,21 @@ impl ToolExecutor {
                 entity: format!("Dish: {}", dish_name),
                 field: "selling_price".to_string(),
                 before: None,
-                after: format!("€{:.2}", selling_price_cents as f64 / 100.0),
+                after: match pricing {
+                    DishPricing::Fixed(cents) => format!("€{:.2}", cents as f64 / 100.0),
+                    DishPricing::FoodCostPercent(pct) => {
+                        format!("{}% food cost (priced from the recipe cost)", pct)
+                    }
+                },
                 unit: Some("EUR".to_string()),
             },
         ];
 
         CreateDishPreview::Ok {
             dish_name,
-            recipe_id: recipe_id.as_uuid(),
+            recipe_id: recipe_id.map(|id| id.as_uuid()),
             recipe_name,
-            selling_price_cents,
+            pricing,
             description,
             dish_changes,
         }
@@ -1146,9 +1446,10 @@ impl ToolExecutor {
     /// Создать блюдо из уже резолвнутого payload (после confirmation).
     async fn execute_create_dish(
         &self,
+        user_id: UserId,
         tenant_id: TenantId,
         payload: &serde_json::Value,
-    ) -> AppResult<String> {
+    ) -> AppResult<WriteOutcome> {
         let dish_name_str = payload
             .get("dish_name")
             .and_then(|v| v.as_str())
@@ -1159,10 +1460,6 @@ impl ToolExecutor {
             .ok_or_else(|| {
                 AppError::validation("recipe_id is required (resolver must run first)")
             })?;
-        let selling_price_cents = payload
-            .get("selling_price_cents")
-            .and_then(|v| v.as_i64())
-            .ok_or_else(|| AppError::validation("selling_price_cents is required"))?;
         let description = payload
             .get("description")
             .and_then(|v| v.as_str())
@@ -1171,6 +1468,24 @@ impl ToolExecutor {
         let recipe_uuid = Uuid::parse_str(recipe_id_str)
             .map_err(|_| AppError::validation("invalid recipe_id UUID"))?;
         let recipe_id = crate::domain::RecipeId::from_uuid(recipe_uuid);
+
+        // Рецепт из этого же плана: цена от его себестоимости
+        let selling_price_cents = match payload.get("selling_price_cents").and_then(|v| v.as_i64())
+        {
+            Some(cents) => cents,
+            None => {
+                let pct = payload
+                    .get("target_food_cost_percent")
+                    .and_then(|v| v.as_f64())
+                    .ok_or_else(|| AppError::validation("selling_price_cents is required"))?;
+                let cost = self
+                    .services
+                    .recipes_v1
+                    .calculate_cost(recipe_id, tenant_id)
+                    .await?;
+                price_for_food_cost(cost.cost_per_serving.as_cents(), pct)?
+            }
+        };
         let dish_name = crate::domain::DishName::new(dish_name_str)?;
         let selling_price = crate::domain::Money::from_cents(selling_price_cents)?;
 
@@ -1179,6 +1494,7 @@ impl ToolExecutor {
             .dishes
             .create_dish(
                 tenant_id,
+                user_id,
                 recipe_id,
                 dish_name,
                 description,
@@ -1196,14 +1512,27 @@ impl ToolExecutor {
             None => String::new(),
         };
 
-        Ok(format!(
+        let message = format!(
             "Created dish '{}' (id: {}) linked to recipe. Price: €{:.2}{}{} .",
             dish.name().as_str(),
             dish.id().as_uuid(),
             selling_price_cents as f64 / 100.0,
             cost_info,
             margin_info,
-        ))
+        );
+        Ok(WriteOutcome::new(
+            message,
+            Compensation::DeleteDish {
+                dish_id: dish.id().as_uuid(),
+                dish_name: dish.name().as_str().to_string(),
+                selling_price_cents,
+            },
+        )
+        .with_output(json!({
+            "dish_id": dish.id().as_uuid().to_string(),
+            "dish_name": dish.name().as_str(),
+            "selling_price_cents": selling_price_cents,
+        })))
     }
 
     // ── Private: execute individual read tools ──────────────────────────────
@@ -1246,18 +1575,24 @@ impl ToolExecutor {
             }
 
             CopilotTool::GetExpiringSoon => {
+                // С названиями — чтобы следующие шаги плана могли на них сослаться
+                let days = args.get("days").and_then(|v| v.as_i64()).unwrap_or(3);
+                let limit = time::OffsetDateTime::now_utc() + time::Duration::days(days);
                 let items = self
                     .services
                     .inventory
-                    .get_expiring_products(ctx.user_id.clone(), ctx.tenant_id.clone(), 3)
+                    .list_products_with_details(ctx.user_id, ctx.tenant_id, Language::En)
                     .await?;
                 let summary: Vec<serde_json::Value> = items
                     .iter()
-                    .map(|b| {
+                    .filter(|i| i.remaining_quantity > 0.0 && i.expires_at <= limit)
+                    .map(|i| {
                         json!({
-                            "id": b.id,
-                            "expires_at": b.expires_at.to_string(),
-                            "quantity": b.quantity,
+                            "id": i.id,
+                            "name": i.product.name,
+                            "quantity": i.remaining_quantity,
+                            "unit": i.product.base_unit,
+                            "expires_at": i.expires_at.to_string(),
                         })
                     })
                     .collect();
@@ -1684,58 +2019,388 @@ impl ToolExecutor {
     }
 
     /// Выполнить реальный write action после confirmation.
-    /// Поддерживает: PrepareInventoryUpdate (add/update quantity).
+    /// Возвращает сообщение и компенсацию для undo.
     pub async fn execute_write_tool(
         &self,
         user_id: UserId,
         tenant_id: TenantId,
         plan: &super::actions::ActionPlan,
-    ) -> AppResult<String> {
+    ) -> Result<WriteOutcome, WriteError> {
+        if matches!(plan.plan_type, ActionPlanType::MultiStep) {
+            return self.execute_plan_steps(user_id, tenant_id, plan).await;
+        }
+
         let tool = plan
             .write_tool
             .as_ref()
             .ok_or_else(|| AppError::internal("Action plan has no write_tool"))?;
 
+        Ok(self
+            .execute_write_call(user_id, tenant_id, plan.id, tool, &plan.payload)
+            .await?)
+    }
+
+    /// Выполнить шаги MultiStep плана по порядку.
+    /// Отложенные `$ref` резолвятся выходами уже выполненных шагов.
+    /// Если шаг падает — выполненные шаги откатываются их компенсациями
+    /// в обратном порядке, и план целиком считается невыполненным.
+    /// Шаги, которые откатить не удалось, возвращаются в `WriteError::leftover`.
+    async fn execute_plan_steps(
+        &self,
+        user_id: UserId,
+        tenant_id: TenantId,
+        plan: &super::actions::ActionPlan,
+    ) -> Result<WriteOutcome, WriteError> {
+        let mut outputs = StepOutputs::default();
+        let mut completed: Vec<Compensation> = Vec::with_capacity(plan.steps.len());
+        let mut irreversible: Vec<&str> = Vec::new();
+        let mut messages: Vec<String> = Vec::with_capacity(plan.steps.len());
+
+        for (idx, step) in plan.steps.iter().enumerate() {
+            let result = match outputs.resolve(&step.payload) {
+                Ok(payload) if plan_graph::has_refs(&payload) => Err(AppError::validation(
+                    "arguments reference a step that has not run",
+                )),
+                // Свой reference_id на шаг — чтобы списания разных шагов
+                // откатывались независимо
+                Ok(payload) => self
+                    .execute_write_call(
+                        user_id,
+                        tenant_id,
+                        Uuid::new_v4(),
+                        &step.write_tool,
+                        &payload,
+                    )
+                    .await
+                    .map(|outcome| (payload, outcome)),
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok((payload, outcome)) => {
+                    outputs.insert(
+                        &step.id,
+                        plan_graph::merge_output(&payload, &outcome.output),
+                    );
+                    messages.push(format!("{}. {}", idx + 1, outcome.message));
+                    match outcome.compensation {
+                        Some(compensation) => completed.push(compensation),
+                        None => irreversible.push(step.write_tool.name()),
+                    }
+                }
+                Err(e) => {
+                    let (rollback, leftover) = self
+                        .rollback_steps(user_id, tenant_id, &completed, &irreversible)
+                        .await;
+                    return Err(WriteError {
+                        error: step_failed(idx + 1, &step.write_tool, e, &rollback),
+                        leftover: (!leftover.is_empty())
+                            .then_some(Compensation::Sequence { steps: leftover }),
+                    });
+                }
+            }
+        }
+
+        tracing::info!(
+            "✅ Copilot multi-step plan {}: {} step(s)",
+            plan.id,
+            plan.steps.len()
+        );
+
+        // Undo возможен, только если обратим каждый шаг
+        let compensation = irreversible
+            .is_empty()
+            .then_some(Compensation::Sequence { steps: completed });
+        Ok(WriteOutcome {
+            message: messages.join("\n"),
+            compensation,
+            output: serde_json::Value::Null,
+        })
+    }
+
+    /// Откатить выполненные шаги в обратном порядке.
+    /// Возвращает описание результата для сообщения об ошибке и
+    /// компенсации шагов, которые откатить не удалось (в порядке выполнения).
+    async fn rollback_steps(
+        &self,
+        user_id: UserId,
+        tenant_id: TenantId,
+        completed: &[Compensation],
+        irreversible: &[&str],
+    ) -> (String, Vec<Compensation>) {
+        let mut failed = Vec::new();
+        let mut leftover = Vec::new();
+        for compensation in completed.iter().rev() {
+            if let Err(e) = self
+                .execute_compensation(user_id, tenant_id, compensation)
+                .await
+            {
+                tracing::error!("Copilot rollback step failed: {e}");
+                failed.push(format!("{} ({})", compensation.describe(), e));
+                leftover.push(compensation.clone());
+            }
+        }
+        leftover.reverse();
+
+        let mut summary = if completed.is_empty() {
+            "No changes were made.".to_string()
+        } else if failed.is_empty() {
+            format!("Rolled back {} completed step(s).", completed.len())
+        } else {
+            format!(
+                "Rollback incomplete, could not revert: {}. Undo the action to retry.",
+                failed.join("; ")
+            )
+        };
+        if !irreversible.is_empty() {
+            summary.push_str(&format!(
+                " Kept steps that cannot be reverted: {}.",
+                irreversible.join(", ")
+            ));
+        }
+        (summary, leftover)
+    }
+
+    /// Выполнить один write tool с уже резолвнутым payload.
+    /// `reference_id` помечает движения склада (для отката списаний).
+    async fn execute_write_call(
+        &self,
+        user_id: UserId,
+        tenant_id: TenantId,
+        reference_id: Uuid,
+        tool: &CopilotTool,
+        payload: &serde_json::Value,
+    ) -> AppResult<WriteOutcome> {
         match tool {
             CopilotTool::PrepareInventoryUpdate => {
-                self.execute_inventory_add(user_id, tenant_id, &plan.payload)
+                self.execute_inventory_add(user_id, tenant_id, payload)
                     .await
             }
             CopilotTool::WriteOffInventory => {
-                self.execute_inventory_writeoff(tenant_id, &plan.payload)
+                self.execute_inventory_writeoff(user_id, tenant_id, reference_id, payload)
                     .await
             }
             CopilotTool::AdjustInventoryQuantity => {
-                self.execute_adjust_quantity(user_id, tenant_id, &plan.payload)
+                self.execute_adjust_quantity(user_id, tenant_id, reference_id, payload)
                     .await
             }
             CopilotTool::PreparePurchaseDraft => {
-                self.execute_purchase_draft(user_id, tenant_id, &plan.payload)
+                self.execute_purchase_draft(user_id, tenant_id, payload)
                     .await
             }
             CopilotTool::SendPurchaseOrder => {
-                self.execute_send_purchase_order(user_id, &plan.payload)
-                    .await
+                self.execute_send_purchase_order(user_id, payload).await
             }
             CopilotTool::UpdateDishPrice => {
-                self.execute_update_dish_price(tenant_id, &plan.payload)
+                self.execute_update_dish_price(user_id, tenant_id, payload)
                     .await
             }
             CopilotTool::CreateRecipe => {
-                self.execute_create_recipe(user_id, tenant_id, &plan.payload)
+                self.execute_create_recipe(user_id, tenant_id, payload)
                     .await
             }
-            CopilotTool::CreateDish => self.execute_create_dish(tenant_id, &plan.payload).await,
+            CopilotTool::CreateDish => self.execute_create_dish(user_id, tenant_id, payload).await,
             _ => {
                 tracing::warn!("execute_write_tool: tool {:?} not yet implemented", tool);
-                Ok(format!(
+                Ok(WriteOutcome::irreversible(format!(
                     "Action {} logged (execution pending implementation).",
                     tool.name()
-                ))
+                )))
             }
         }
     }
 
+    /// Применить компенсацию выполненного action (undo).
+    /// Сначала проверяет, что сущность не менялась с момента выполнения —
+    /// иначе `AppError::Conflict` и ничего не меняется.
+    pub async fn execute_compensation(
+        &self,
+        user_id: UserId,
+        tenant_id: TenantId,
+        compensation: &Compensation,
+    ) -> AppResult<String> {
+        match compensation {
+            Compensation::RemoveBatch {
+                batch_id,
+                catalog_ingredient_id,
+                ingredient,
+                quantity,
+                stock_after,
+            } => {
+                let stock = self
+                    .current_stock(user_id, tenant_id, *catalog_ingredient_id)
+                    .await?;
+                undo::check_stock_unchanged(ingredient, *stock_after, stock)?;
+
+                let batch_id = crate::domain::inventory::InventoryBatchId::from_uuid(*batch_id);
+                let batch = self
+                    .services
+                    .inventory
+                    .get_product(user_id, tenant_id, batch_id)
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::conflict(format!(
+                            "The {} batch added by this action no longer exists.",
+                            ingredient
+                        ))
+                    })?;
+                undo::check_stock_unchanged(
+                    ingredient,
+                    *quantity,
+                    batch.remaining_quantity.value(),
+                )?;
+
+                self.services
+                    .inventory
+                    .delete_product(batch_id, user_id, tenant_id)
+                    .await?;
+            }
+            Compensation::RestoreDeduction {
+                reference_id,
+                catalog_ingredient_id,
+                ingredient,
+                stock_after,
+                ..
+            } => {
+                let stock = self
+                    .current_stock(user_id, tenant_id, *catalog_ingredient_id)
+                    .await?;
+                undo::check_stock_unchanged(ingredient, *stock_after, stock)?;
+
+                self.services
+                    .inventory
+                    .restore_deduction(tenant_id, *reference_id)
+                    .await?;
+            }
+            Compensation::VoidWaste {
+                entry_id,
+                catalog_ingredient_id,
+                ingredient,
+                stock_after,
+                ..
+            } => {
+                let stock = self
+                    .current_stock(user_id, tenant_id, *catalog_ingredient_id)
+                    .await?;
+                undo::check_stock_unchanged(ingredient, *stock_after, stock)?;
+
+                self.services.waste.void_entry(tenant_id, *entry_id).await?;
+            }
+            Compensation::RestoreDishPrice {
+                dish_id,
+                dish_name,
+                previous_price_cents,
+                applied_price_cents,
+            } => {
+                let dish_id = crate::domain::DishId::from_uuid(*dish_id);
+                let dish = self
+                    .services
+                    .dishes
+                    .get_dish(dish_id, tenant_id)
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::conflict(format!("Dish '{}' no longer exists.", dish_name))
+                    })?;
+                if dish.selling_price().as_cents() != *applied_price_cents {
+                    return Err(AppError::conflict(format!(
+                        "Price of '{}' changed since the action (now €{:.2}).",
+                        dish_name,
+                        dish.selling_price().as_cents() as f64 / 100.0
+                    )));
+                }
+
+                let previous = crate::domain::Money::from_cents(*previous_price_cents)?;
+                self.services
+                    .dishes
+                    .set_selling_price(dish_id, tenant_id, user_id, previous)
+                    .await?;
+            }
+            Compensation::DeleteRecipe {
+                recipe_id,
+                recipe_name,
+            } => {
+                let recipe_id = crate::domain::RecipeId::from_uuid(*recipe_id);
+                let pagination = PaginationParams {
+                    page: Some(1),
+                    per_page: Some(200),
+                };
+                let (dishes, _) = self
+                    .services
+                    .dishes
+                    .list_dishes(tenant_id, false, &pagination)
+                    .await?;
+                if let Some(dish) = dishes.iter().find(|d| d.recipe_id() == recipe_id) {
+                    return Err(AppError::conflict(format!(
+                        "Recipe '{}' is now used by dish '{}'.",
+                        recipe_name,
+                        dish.name().as_str()
+                    )));
+                }
+
+                let deleted = self
+                    .services
+                    .recipes_v1
+                    .delete_recipe(recipe_id, tenant_id, user_id)
+                    .await?;
+                if !deleted {
+                    return Err(AppError::conflict(format!(
+                        "Recipe '{}' no longer exists.",
+                        recipe_name
+                    )));
+                }
+            }
+            Compensation::DeleteDish {
+                dish_id,
+                dish_name,
+                selling_price_cents,
+            } => {
+                let dish_id = crate::domain::DishId::from_uuid(*dish_id);
+                let dish = self
+                    .services
+                    .dishes
+                    .get_dish(dish_id, tenant_id)
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::conflict(format!("Dish '{}' no longer exists.", dish_name))
+                    })?;
+                if dish.name().as_str() != dish_name
+                    || dish.selling_price().as_cents() != *selling_price_cents
+                {
+                    return Err(AppError::conflict(format!(
+                        "Dish '{}' was edited since the action.",
+                        dish_name
+                    )));
+                }
+
+                self.services
+                    .dishes
+                    .delete_dish(dish_id, tenant_id, user_id)
+                    .await?;
+            }
+            Compensation::CancelPurchaseDraft { draft_id } => {
+                self.services
+                    .purchase_drafts
+                    .cancel(*draft_id, user_id)
+                    .await?;
+            }
+            Compensation::ReopenPurchaseDraft { draft_id } => {
+                self.services
+                    .purchase_drafts
+                    .reopen(*draft_id, user_id)
+                    .await?;
+            }
+            Compensation::Sequence { .. } => {
+                // CopilotEngine::undo_action применяет шаги по одному
+                // (Compensation::undo_steps) и сохраняет прогресс после каждого
+                return Err(AppError::internal(
+                    "A step sequence is undone one step at a time",
+                ));
+            }
+        }
+
+        Ok(compensation.describe())
+    }
+
     /// Извлечь {name, quantity, unit, reason?} из payload (поддерживает items[] и flat).
     fn extract_item(
         payload: &serde_json::Value,
@@ -1801,8 +2466,24 @@ impl ToolExecutor {
             .search_ingredients(name, Language::Ru, 5)
             .await
             .unwrap_or_default();
+        if let Some(ing) = candidates_ru.into_iter().next() {
+            return Ok(ing.id);
+        }
 
-        candidates_ru
+        // Падежные формы ("лосося", "tomatoes") — по основам слов.
+        // Поиск каталога — LIKE по подстроке, `%` между основами.
+        let stem = rule_planner::search_stem(name).replace(' ', "%");
+        let candidates_stem = if stem.is_empty() || stem == name.to_lowercase() {
+            vec![]
+        } else {
+            self.services
+                .catalog
+                .search_ingredients(&stem, Language::En, 5)
+                .await
+                .unwrap_or_default()
+        };
+
+        candidates_stem
             .into_iter()
             .next()
             .map(|i| i.id)
@@ -1814,6 +2495,25 @@ impl ToolExecutor {
             })
     }
 
+    /// Текущий остаток ингредиента (сумма remaining по всем партиям).
+    async fn current_stock(
+        &self,
+        user_id: UserId,
+        tenant_id: TenantId,
+        catalog_id: Uuid,
+    ) -> AppResult<f64> {
+        let items = self
+            .services
+            .inventory
+            .list_products_with_details(user_id, tenant_id, Language::En)
+            .await?;
+        Ok(items
+            .iter()
+            .filter(|i| i.product.id == catalog_id)
+            .map(|i| i.remaining_quantity)
+            .sum())
+    }
+
     /// Добавить/обновить позицию в инвентаре.
     /// Ищет ингредиент в каталоге по имени, создаёт batch.
     async fn execute_inventory_add(
@@ -1821,7 +2521,7 @@ impl ToolExecutor {
         user_id: UserId,
         tenant_id: TenantId,
         payload: &serde_json::Value,
-    ) -> AppResult<String> {
+    ) -> AppResult<WriteOutcome> {
         let (name, quantity, unit, _reason) = Self::extract_item(payload)?;
         let catalog_id = self.find_catalog_id(&name).await?;
 
@@ -1829,7 +2529,8 @@ impl ToolExecutor {
         let now = time::OffsetDateTime::now_utc();
         let expires_at = now + time::Duration::days(30);
 
-        self.services
+        let batch_id = self
+            .services
             .inventory
             .add_product(
                 user_id, tenant_id, catalog_id,
@@ -1837,6 +2538,9 @@ impl ToolExecutor {
                 quantity, now, expires_at,
             )
             .await?;
+        let stock_after = self
+            .current_stock(user_id, tenant_id, catalog_id.as_uuid())
+            .await?;
 
         tracing::info!(
             "✅ Copilot write: added {} {} {} to inventory",
@@ -1844,19 +2548,28 @@ impl ToolExecutor {
             unit,
             name
         );
-        Ok(format!(
-            "Added {} {} of {} to inventory.",
-            quantity, unit, name
+        Ok(WriteOutcome::new(
+            format!("Added {} {} of {} to inventory.", quantity, unit, name),
+            Compensation::RemoveBatch {
+                batch_id: batch_id.as_uuid(),
+                catalog_ingredient_id: catalog_id.as_uuid(),
+                ingredient: name,
+                quantity,
+                stock_after,
+            },
         ))
     }
 
     /// Списать со склада (FIFO).
     /// Поддерживает причины: expired, used_in_production, waste, correction, manual.
+    /// expired / waste попадают в журнал потерь (waste log), остальные — обычное списание.
     async fn execute_inventory_writeoff(
         &self,
+        user_id: UserId,
         tenant_id: TenantId,
+        reference_id: Uuid,
         payload: &serde_json::Value,
-    ) -> AppResult<String> {
+    ) -> AppResult<WriteOutcome> {
         let (name, quantity, unit, reason_opt) = Self::extract_item(payload)?;
         let catalog_id = self.find_catalog_id(&name).await?;
 
@@ -1867,16 +2580,50 @@ impl ToolExecutor {
             .unwrap_or("manual")
             .to_string();
 
-        self.services
-            .inventory
-            .deduct_fifo(
-                tenant_id,
-                catalog_id,
-                quantity,
-                None,
-                Some(format!("copilot_writeoff:{}", reason)),
-                Some(format!("Copilot write-off: {} ({})", name, reason)),
-            )
+        let waste_reason = match reason.as_str() {
+            "expired" => Some(WasteReason::Expired),
+            "waste" => Some(WasteReason::Spoilage),
+            _ => None,
+        };
+
+        // Записи о потерях аннулируются целиком, обычное списание
+        // возвращается по движениям с этим reference_id
+        let waste_entry_id = if let Some(waste_reason) = waste_reason {
+            let entry = self
+                .services
+                .waste
+                .log_waste(
+                    tenant_id,
+                    user_id,
+                    LogWasteInput {
+                        catalog_ingredient_id: catalog_id.as_uuid(),
+                        quantity,
+                        reason: waste_reason,
+                        batch_id: None,
+                        location_id: None,
+                        photo_url: None,
+                        note: Some(format!("Copilot write-off: {}", name)),
+                    },
+                    Language::default(),
+                )
+                .await?;
+            Some(entry.id)
+        } else {
+            self.services
+                .inventory
+                .deduct_fifo(
+                    tenant_id,
+                    catalog_id,
+                    quantity,
+                    Some(reference_id),
+                    Some(format!("copilot_writeoff:{}", reason)),
+                    Some(format!("Copilot write-off: {} ({})", name, reason)),
+                )
+                .await?;
+            None
+        };
+        let stock_after = self
+            .current_stock(user_id, tenant_id, catalog_id.as_uuid())
             .await?;
 
         tracing::info!(
@@ -1886,10 +2633,27 @@ impl ToolExecutor {
             name,
             reason
         );
-        Ok(format!(
+        let message = format!(
             "Wrote off {} {} of {} (reason: {}).",
             quantity, unit, name, reason
-        ))
+        );
+        let compensation = match waste_entry_id {
+            Some(entry_id) => Compensation::VoidWaste {
+                entry_id,
+                catalog_ingredient_id: catalog_id.as_uuid(),
+                ingredient: name,
+                quantity,
+                stock_after,
+            },
+            None => Compensation::RestoreDeduction {
+                reference_id,
+                catalog_ingredient_id: catalog_id.as_uuid(),
+                ingredient: name,
+                quantity,
+                stock_after,
+            },
+        };
+        Ok(WriteOutcome::new(message, compensation))
     }
 
     /// Создать purchase draft.
@@ -1899,7 +2663,7 @@ impl ToolExecutor {
         user_id: UserId,
         tenant_id: TenantId,
         payload: &serde_json::Value,
-    ) -> AppResult<String> {
+    ) -> AppResult<WriteOutcome> {
         let supplier = payload
             .get("supplier_name")
             .or_else(|| payload.get("supplier"))
@@ -1979,6 +2743,7 @@ impl ToolExecutor {
                 user_id,
                 tenant_id,
                 CreatePurchaseDraftInput {
+                    supplier_id: None,
                     supplier_name: supplier.clone(),
                     delivery_date,
                     note,
@@ -1994,7 +2759,7 @@ impl ToolExecutor {
             supplier
         );
 
-        Ok(format!(
+        let message = format!(
             "Purchase draft created with {} item(s){}{}.",
             count,
             supplier
@@ -2003,7 +2768,11 @@ impl ToolExecutor {
             delivery_date
                 .map(|d| format!(" (delivery {})", d))
                 .unwrap_or_default(),
-        ))
+        );
+        Ok(
+            WriteOutcome::new(message, Compensation::CancelPurchaseDraft { draft_id })
+                .with_output(json!({ "draft_id": draft_id.to_string() })),
+        )
     }
 
     /// Перевести purchase draft в статус 'sent'.
@@ -2012,7 +2781,7 @@ impl ToolExecutor {
         &self,
         user_id: UserId,
         payload: &serde_json::Value,
-    ) -> AppResult<String> {
+    ) -> AppResult<WriteOutcome> {
         let id_str = payload
             .get("id")
             .and_then(|v| v.as_str())
@@ -2033,14 +2802,18 @@ impl ToolExecutor {
             items_count
         );
 
-        Ok(format!(
+        let message = format!(
             "Purchase draft {} marked as sent ({} item(s){}). Status: draft → sent.",
             &id.to_string()[..8],
             items_count,
             supplier
                 .map(|s| format!(", supplier {}", s))
                 .unwrap_or_default(),
-        ))
+        );
+        Ok(
+            WriteOutcome::new(message, Compensation::ReopenPurchaseDraft { draft_id: id })
+                .with_output(json!({ "draft_id": id.to_string() })),
+        )
     }
 
     /// Скорректировать остаток до целевого значения.
@@ -2050,8 +2823,9 @@ impl ToolExecutor {
         &self,
         user_id: UserId,
         tenant_id: TenantId,
+        reference_id: Uuid,
         payload: &serde_json::Value,
-    ) -> AppResult<String> {
+    ) -> AppResult<WriteOutcome> {
         let name = payload
             .get("ingredient_name")
             .or_else(|| payload.get("ingredient"))
@@ -2090,31 +2864,23 @@ impl ToolExecutor {
         let cat_uuid = catalog_id.as_uuid();
 
         // Текущий остаток
-        let items = self
-            .services
-            .inventory
-            .list_products_with_details(user_id.clone(), tenant_id.clone(), Language::En)
-            .await?;
-        let current: f64 = items
-            .iter()
-            .filter(|i| i.product.id == cat_uuid)
-            .map(|i| i.remaining_quantity)
-            .sum();
+        let current = self.current_stock(user_id, tenant_id, cat_uuid).await?;
 
         let diff = target - current;
         if diff.abs() < 0.0001 {
-            return Ok(format!(
+            return Ok(WriteOutcome::irreversible(format!(
                 "No change: {} already at {} {}.",
                 name, current, unit
-            ));
+            )));
         }
 
-        if diff > 0.0 {
+        let added_batch = if diff > 0.0 {
             // Добавляем batch с положительной корректировкой.
             // price=0 (correction), expires_at = +365 days.
             let now = time::OffsetDateTime::now_utc();
             let expires = now + time::Duration::days(365);
-            self.services
+            let batch_id = self
+                .services
                 .inventory
                 .add_product(
                     user_id.clone(),
@@ -2133,6 +2899,7 @@ impl ToolExecutor {
                 name,
                 reason
             );
+            Some(batch_id)
         } else {
             // FIFO write-off на |diff|
             self.services
@@ -2141,7 +2908,7 @@ impl ToolExecutor {
                     tenant_id.clone(),
                     catalog_id,
                     diff.abs(),
-                    None,
+                    Some(reference_id),
                     Some(reason.to_string()),
                     Some(format!("Copilot correction: {} ({})", name, reason)),
                 )
@@ -2153,21 +2920,42 @@ impl ToolExecutor {
                 name,
                 reason
             );
-        }
+            None
+        };
+        let stock_after = self.current_stock(user_id, tenant_id, cat_uuid).await?;
 
-        Ok(format!(
+        let message = format!(
             "Adjusted {}: {} {} → {} {} (diff {:+.3} {}, reason: {}).",
             name, current, unit, target, unit, diff, unit, reason
-        ))
+        );
+        let ingredient = name.to_string();
+        let compensation = match added_batch {
+            Some(batch_id) => Compensation::RemoveBatch {
+                batch_id: batch_id.as_uuid(),
+                catalog_ingredient_id: cat_uuid,
+                ingredient,
+                quantity: diff,
+                stock_after,
+            },
+            None => Compensation::RestoreDeduction {
+                reference_id,
+                catalog_ingredient_id: cat_uuid,
+                ingredient,
+                quantity: diff.abs(),
+                stock_after,
+            },
+        };
+        Ok(WriteOutcome::new(message, compensation))
     }
 
     /// Update dish selling price.
     /// Payload (resolved by prepare_action_plan): { dish_id: "<uuid>", dish_name, new_price_cents }
     async fn execute_update_dish_price(
         &self,
+        user_id: UserId,
         tenant_id: TenantId,
         payload: &serde_json::Value,
-    ) -> AppResult<String> {
+    ) -> AppResult<WriteOutcome> {
         let dish_id_str = payload
             .get("dish_id")
             .and_then(|v| v.as_str())
@@ -2196,10 +2984,19 @@ impl ToolExecutor {
         let new_price = crate::domain::Money::from_cents(new_price_cents)?;
         let dish_id = crate::domain::DishId::from_uuid(dish_uuid);
 
+        let previous_price_cents = self
+            .services
+            .dishes
+            .get_dish(dish_id, tenant_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Dish not found"))?
+            .selling_price()
+            .as_cents();
+
         let updated = self
             .services
             .dishes
-            .set_selling_price(dish_id, tenant_id, new_price)
+            .set_selling_price(dish_id, tenant_id, user_id, new_price)
             .await?;
 
         let margin_str = updated
@@ -2214,15 +3011,44 @@ impl ToolExecutor {
             margin_str,
         );
 
-        Ok(format!(
+        let message = format!(
             "Updated price of '{}' to €{:.2}{}.",
             dish_name,
             new_price_cents as f64 / 100.0,
             margin_str,
+        );
+        Ok(WriteOutcome::new(
+            message,
+            Compensation::RestoreDishPrice {
+                dish_id: dish_uuid,
+                dish_name,
+                previous_price_cents,
+                applied_price_cents: new_price_cents,
+            },
         ))
     }
 }
 
+/// Ошибка шага MultiStep плана: номер шага и что стало с выполненными.
+fn step_failed(step_no: usize, tool: &CopilotTool, error: AppError, rollback: &str) -> AppError {
+    let describe = |reason: &str| {
+        format!(
+            "Step {} ({}) failed: {}. {}",
+            step_no,
+            tool.name(),
+            reason,
+            rollback
+        )
+    };
+    match error {
+        AppError::Validation(m) => AppError::validation(describe(&m)),
+        AppError::NotFound(m) => AppError::not_found(describe(&m)),
+        AppError::Conflict(m) => AppError::conflict(describe(&m)),
+        AppError::Authorization(m) => AppError::authorization(describe(&m)),
+        other => AppError::internal(describe(&other.to_string())),
+    }
+}
+
 /// Нормализовать причину correction к одному из допустимых значений.
 fn normalize_correction_reason(input: &str) -> &'static str {
     let s = input.to_lowercase();
@@ -2348,9 +3174,10 @@ enum CreateDishPreview {
     /// Recipe найден, имя блюда уникально, цена валидна — готов к подтверждению.
     Ok {
         dish_name: String,
-        recipe_id: Uuid,
+        /// None — рецепт создаётся раньше в этом же плане (`$ref` в payload).
+        recipe_id: Option<Uuid>,
         recipe_name: String,
-        selling_price_cents: i64,
+        pricing: DishPricing,
         description: Option<String>,
         dish_changes: Vec<ActionChange>,
     },
@@ -2367,6 +3194,25 @@ enum CreateDishPreview {
     Invalid { reason: String },
 }
 
+/// Цена нового блюда.
+#[derive(Debug, Clone, Copy)]
+enum DishPricing {
+    Fixed(i64),
+    /// Цена = себестоимость порции / доля food cost; считается, когда
+    /// себестоимость рецепта известна.
+    FoodCostPercent(f64),
+}
+
+/// Цена в центах, при которой себестоимость порции составит `percent` %.
+fn price_for_food_cost(cost_per_serving_cents: i64, percent: f64) -> AppResult<i64> {
+    if cost_per_serving_cents <= 0 {
+        return Err(AppError::validation(
+            "Recipe has no cost yet — cannot price the dish from a food cost target",
+        ));
+    }
+    Ok((cost_per_serving_cents as f64 * 100.0 / percent).round() as i64)
+}
+
 /// Конвертирует quantity из user_unit в target_unit (default_unit ингредиента).
 /// Поддерживает g↔kg и l↔ml. Прочие комбинации требуют точного совпадения юнитов.
 fn convert_quantity(
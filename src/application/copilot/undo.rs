@@ -0,0 +1,297 @@
+//! Undo — компенсирующие операции для выполненных write actions.
+//!
+//! При выполнении каждый write tool возвращает `Compensation` — обратную
+//! операцию, которая сохраняется в `copilot_action_log.compensation`.
+//! `POST /api/copilot/actions/{id}/undo` применяет её, если:
+//!   - действие выполнено не раньше чем `COPILOT_UNDO_WINDOW_MINUTES` назад;
+//!   - сущность не изменилась с момента выполнения (иначе 409 Conflict).
+//!
+//! Сама отмена тоже пишется в лог отдельной записью с `undo_of`.
+//!
+//! Многошаговый план хранит `Compensation::Sequence` — компенсации шагов
+//! в порядке выполнения; применяются в обратном порядке. Тот же механизм
+//! откатывает уже выполненные шаги, если следующий шаг упал.
+//!
+//! Шаги откатываются по одному, и после каждого число откаченных
+//! сохраняется в `copilot_action_log.undone_steps`. Если шаг упал, action
+//! остаётся `partially_undone` (а не executed), и повторный undo
+//! продолжает с первого неоткаченного шага.
+
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::shared::{AppError, AppResult};
+
+/// Окно отмены по умолчанию, если `COPILOT_UNDO_WINDOW_MINUTES` не задан
+pub const DEFAULT_UNDO_WINDOW_MINUTES: i64 = 30;
+
+/// Допуск при сравнении остатков (f64 после Decimal)
+const STOCK_EPSILON: f64 = 0.0001;
+
+/// Обратная операция для одного выполненного write action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Compensation {
+    /// Приход на склад → удалить созданную партию (если она не тронута).
+    RemoveBatch {
+        batch_id: Uuid,
+        catalog_ingredient_id: Uuid,
+        ingredient: String,
+        quantity: f64,
+        stock_after: f64,
+    },
+    /// FIFO-списание под `reference_id` → вернуть в те же партии встречными
+    /// движениями ADJUSTMENT; исходные движения остаются в журнале.
+    RestoreDeduction {
+        reference_id: Uuid,
+        catalog_ingredient_id: Uuid,
+        ingredient: String,
+        quantity: f64,
+        stock_after: f64,
+    },
+    /// Запись в журнале потерь → аннулировать её и вернуть остаток.
+    VoidWaste {
+        entry_id: Uuid,
+        catalog_ingredient_id: Uuid,
+        ingredient: String,
+        quantity: f64,
+        stock_after: f64,
+    },
+    /// Новая цена блюда → вернуть прежнюю.
+    RestoreDishPrice {
+        dish_id: Uuid,
+        dish_name: String,
+        previous_price_cents: i64,
+        applied_price_cents: i64,
+    },
+    /// Созданный рецепт → удалить (пока на нём нет блюд).
+    DeleteRecipe {
+        recipe_id: Uuid,
+        recipe_name: String,
+    },
+    /// Созданное блюдо → удалить (пока цена не менялась).
+    DeleteDish {
+        dish_id: Uuid,
+        dish_name: String,
+        selling_price_cents: i64,
+    },
+    /// Созданный purchase draft → cancelled (пока он ещё draft).
+    CancelPurchaseDraft { draft_id: Uuid },
+    /// Отправленный заказ → обратно в draft (пока ничего не принято).
+    ReopenPurchaseDraft { draft_id: Uuid },
+    /// Шаги многошагового плана в порядке выполнения.
+    Sequence { steps: Vec<Compensation> },
+}
+
+impl Compensation {
+    /// Отдельные операции в порядке применения при undo: шаги `Sequence`
+    /// с конца, вложенные `Sequence` раскрываются.
+    pub fn undo_steps(&self) -> Vec<&Compensation> {
+        match self {
+            Self::Sequence { steps } => steps.iter().rev().flat_map(Self::undo_steps).collect(),
+            single => vec![single],
+        }
+    }
+
+    /// Краткое описание для UI / ответа undo.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::RemoveBatch {
+                ingredient,
+                quantity,
+                ..
+            } => format!("Removed the added {} of {}", quantity, ingredient),
+            Self::RestoreDeduction {
+                ingredient,
+                quantity,
+                ..
+            } => format!("Returned {} of {} to stock", quantity, ingredient),
+            Self::VoidWaste {
+                ingredient,
+                quantity,
+                ..
+            } => format!(
+                "Voided the waste entry and returned {} of {} to stock",
+                quantity, ingredient
+            ),
+            Self::RestoreDishPrice {
+                dish_name,
+                previous_price_cents,
+                ..
+            } => format!(
+                "Restored price of '{}' to €{:.2}",
+                dish_name,
+                *previous_price_cents as f64 / 100.0
+            ),
+            Self::DeleteRecipe { recipe_name, .. } => {
+                format!("Deleted recipe '{}'", recipe_name)
+            }
+            Self::DeleteDish { dish_name, .. } => format!("Deleted dish '{}'", dish_name),
+            Self::CancelPurchaseDraft { .. } => "Cancelled the purchase draft".to_string(),
+            Self::ReopenPurchaseDraft { .. } => {
+                "Purchase order moved back to draft (status: sent → draft)".to_string()
+            }
+            Self::Sequence { steps } => steps
+                .iter()
+                .rev()
+                .map(Self::describe)
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+    }
+}
+
+/// Проверить, что окно отмены ещё открыто.
+pub fn check_undo_window(
+    executed_at: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+    window_minutes: i64,
+) -> AppResult<()> {
+    let executed_at =
+        executed_at.ok_or_else(|| AppError::validation("Action has not been executed."))?;
+    if now - executed_at > Duration::minutes(window_minutes) {
+        return Err(AppError::validation(format!(
+            "Undo window has passed: actions can be undone within {} minutes.",
+            window_minutes
+        )));
+    }
+    Ok(())
+}
+
+/// Остаток ингредиента должен совпадать с тем, что было сразу после действия.
+pub fn check_stock_unchanged(ingredient: &str, expected: f64, actual: f64) -> AppResult<()> {
+    if (expected - actual).abs() > STOCK_EPSILON {
+        return Err(AppError::conflict(format!(
+            "Stock of {} changed since the action (was {}, now {}). Undo it manually.",
+            ingredient, expected, actual
+        )));
+    }
+    Ok(())
+}
+
+/// Undo остановился на шаге. Если часть шагов уже откачена — 409 с
+/// прогрессом: повторный undo продолжит с этого шага.
+pub fn partial_undo_error(error: AppError, undone: usize, total: usize) -> AppError {
+    if undone == 0 {
+        return error;
+    }
+    let reason = match &error {
+        AppError::Validation(m)
+        | AppError::NotFound(m)
+        | AppError::Conflict(m)
+        | AppError::Authorization(m) => m.clone(),
+        _ => return error,
+    };
+    AppError::conflict(format!(
+        "Undid {} of {} steps, then stopped: {} Undo again to continue from this step.",
+        undone, total, reason
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn undo_window_is_inclusive() {
+        let executed = datetime!(2026-10-17 12:00 UTC);
+        assert!(check_undo_window(Some(executed), datetime!(2026-10-17 12:30 UTC), 30).is_ok());
+        assert!(check_undo_window(Some(executed), datetime!(2026-10-17 12:31 UTC), 30).is_err());
+        assert!(check_undo_window(None, datetime!(2026-10-17 12:01 UTC), 30).is_err());
+    }
+
+    #[test]
+    fn stock_change_is_a_conflict() {
+        assert!(check_stock_unchanged("Salmon", 4.5, 4.50001).is_ok());
+        assert!(matches!(
+            check_stock_unchanged("Salmon", 4.5, 3.0),
+            Err(AppError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn compensation_round_trips_with_a_kind_tag() {
+        let compensation = Compensation::RestoreDishPrice {
+            dish_id: Uuid::nil(),
+            dish_name: "Caesar".to_string(),
+            previous_price_cents: 1200,
+            applied_price_cents: 1450,
+        };
+        let value = serde_json::to_value(&compensation).unwrap();
+        assert_eq!(value["kind"], "restore_dish_price");
+        assert_eq!(
+            serde_json::from_value::<Compensation>(value).unwrap(),
+            compensation
+        );
+        assert_eq!(
+            compensation.describe(),
+            "Restored price of 'Caesar' to €12.00"
+        );
+    }
+
+    #[test]
+    fn sequence_describes_steps_in_undo_order() {
+        let sequence = Compensation::Sequence {
+            steps: vec![
+                Compensation::DeleteRecipe {
+                    recipe_id: Uuid::nil(),
+                    recipe_name: "Salmon Bowl".to_string(),
+                },
+                Compensation::DeleteDish {
+                    dish_id: Uuid::nil(),
+                    dish_name: "Salmon Bowl".to_string(),
+                    selling_price_cents: 1500,
+                },
+            ],
+        };
+        assert_eq!(
+            sequence.describe(),
+            "Deleted dish 'Salmon Bowl'; Deleted recipe 'Salmon Bowl'"
+        );
+        let value = serde_json::to_value(&sequence).unwrap();
+        assert_eq!(value["kind"], "sequence");
+        assert_eq!(value["steps"][1]["kind"], "delete_dish");
+    }
+
+    #[test]
+    fn partial_undo_reports_progress() {
+        match partial_undo_error(AppError::conflict("Dish 'Bowl' was edited."), 1, 3) {
+            AppError::Conflict(message) => {
+                assert!(message.starts_with("Undid 1 of 3 steps, then stopped: Dish 'Bowl'"))
+            }
+            other => panic!("expected a conflict, got {other:?}"),
+        }
+        assert!(matches!(
+            partial_undo_error(AppError::validation("nope"), 0, 3),
+            AppError::Validation(_)
+        ));
+    }
+
+    #[test]
+    fn undo_steps_run_last_step_first() {
+        let draft = Compensation::CancelPurchaseDraft {
+            draft_id: Uuid::nil(),
+        };
+        let recipe = Compensation::DeleteRecipe {
+            recipe_id: Uuid::nil(),
+            recipe_name: "Salmon Bowl".to_string(),
+        };
+        let dish = Compensation::DeleteDish {
+            dish_id: Uuid::nil(),
+            dish_name: "Salmon Bowl".to_string(),
+            selling_price_cents: 1500,
+        };
+        let sequence = Compensation::Sequence {
+            steps: vec![
+                draft.clone(),
+                Compensation::Sequence {
+                    steps: vec![recipe.clone(), dish.clone()],
+                },
+            ],
+        };
+        assert_eq!(sequence.undo_steps(), vec![&dish, &recipe, &draft]);
+        assert_eq!(draft.undo_steps(), vec![&draft]);
+    }
+}
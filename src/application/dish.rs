@@ -1,27 +1,38 @@
+use crate::application::audit::{
+    update_action, AuditAction, AuditEntityType, AuditEvent, AuditLog,
+};
 use crate::application::RecipeService;
 use crate::domain::{Dish, DishFinancials, DishId, DishName, Money, RecipeId};
 use crate::infrastructure::persistence::DishRepositoryTrait;
-use crate::shared::{AppError, AppResult, PaginationParams, TenantId};
+use crate::shared::{AppError, AppResult, PaginationParams, TenantId, UserId};
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct DishService {
     dish_repo: Arc<dyn DishRepositoryTrait>,
     recipe_service: RecipeService,
+    audit: AuditLog,
 }
 
 impl DishService {
-    pub fn new(dish_repo: Arc<dyn DishRepositoryTrait>, recipe_service: RecipeService) -> Self {
+    pub fn new(
+        dish_repo: Arc<dyn DishRepositoryTrait>,
+        recipe_service: RecipeService,
+        audit: AuditLog,
+    ) -> Self {
         Self {
             dish_repo,
             recipe_service,
+            audit,
         }
     }
 
     /// Create new dish — materializes cost immediately if recipe cost is available
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_dish(
         &self,
         tenant_id: TenantId,
+        user_id: UserId,
         recipe_id: RecipeId,
         name: DishName,
         description: Option<String>,
@@ -64,6 +75,18 @@ impl DishService {
         }
 
         self.dish_repo.create(&dish).await?;
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::Dish,
+                    dish.id().as_uuid(),
+                    AuditAction::Create,
+                )
+                .after(audit_snapshot(&dish)),
+            )
+            .await;
         Ok(dish)
     }
 
@@ -180,11 +203,13 @@ impl DishService {
         Ok(RecalculateResult { updated, errors })
     }
 
-    /// Update dish
+    /// Update dish. A change of only the selling price is audited as `price_change`.
+    #[allow(clippy::too_many_arguments)]
     pub async fn update_dish(
         &self,
         id: DishId,
         tenant_id: TenantId,
+        user_id: UserId,
         name: Option<DishName>,
         description: Option<Option<String>>,
         selling_price: Option<Money>,
@@ -194,6 +219,7 @@ impl DishService {
             .get_dish(id, tenant_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Dish not found".to_string()))?;
+        let before = audit_snapshot(&dish);
 
         if let Some(new_name) = name {
             dish.update_name(new_name);
@@ -227,12 +253,49 @@ impl DishService {
         }
 
         self.dish_repo.update(&dish).await?;
+        let after = audit_snapshot(&dish);
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::Dish,
+                    id.as_uuid(),
+                    update_action(&before, &after, "selling_price_cents"),
+                )
+                .before(before)
+                .after(after),
+            )
+            .await;
         Ok(dish)
     }
 
     /// Delete dish
-    pub async fn delete_dish(&self, id: DishId, tenant_id: TenantId) -> AppResult<bool> {
-        self.dish_repo.delete(id, tenant_id).await
+    pub async fn delete_dish(
+        &self,
+        id: DishId,
+        tenant_id: TenantId,
+        user_id: UserId,
+    ) -> AppResult<bool> {
+        let Some(dish) = self.get_dish(id, tenant_id).await? else {
+            return Ok(false);
+        };
+        let deleted = self.dish_repo.delete(id, tenant_id).await?;
+        if deleted {
+            self.audit
+                .record(
+                    AuditEvent::new(
+                        tenant_id,
+                        user_id,
+                        AuditEntityType::Dish,
+                        id.as_uuid(),
+                        AuditAction::Delete,
+                    )
+                    .before(audit_snapshot(&dish)),
+                )
+                .await;
+        }
+        Ok(deleted)
     }
 
     /// Find dishes by case-insensitive name substring. Returns up to `limit` matches.
@@ -270,16 +333,29 @@ impl DishService {
         &self,
         id: DishId,
         tenant_id: TenantId,
+        user_id: UserId,
         new_price: Money,
     ) -> AppResult<Dish> {
         if new_price.as_cents() <= 0 {
             return Err(AppError::validation("Selling price must be greater than 0"));
         }
-        self.update_dish(id, tenant_id, None, None, Some(new_price), None)
+        self.update_dish(id, tenant_id, user_id, None, None, Some(new_price), None)
             .await
     }
 }
 
+/// Fields a person edits; materialized cost is derived and left out
+fn audit_snapshot(dish: &Dish) -> serde_json::Value {
+    serde_json::json!({
+        "name": dish.name().as_str(),
+        "description": dish.description(),
+        "recipe_id": dish.recipe_id().as_uuid(),
+        "selling_price_cents": dish.selling_price().as_cents(),
+        "active": dish.is_active(),
+        "image_url": dish.image_url(),
+    })
+}
+
 /// Result of batch recalculation
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct RecalculateResult {
@@ -0,0 +1,554 @@
+//! Allergen matrix and nutrition labels for a tenant's dishes.
+//!
+//! Resolves each dish's recipe tree (components included) against catalog
+//! allergens, "may contain" declarations and catalog nutrition data. Labels
+//! are rendered in the four UI languages by the HTTP layer.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::application::modifier::fetch_modifier_groups;
+use crate::application::recipe::load_component_graph;
+use crate::domain::catalog::{Allergen, CatalogIngredientId, Unit};
+use crate::domain::dish_label::{
+    per_portion, portion_nutrition, resolve_allergens, AllergenPresence, AllergenTrace,
+    DishAllergen, LabelIngredient, NutrientsPer100g, NutritionLabel, EU_ALLERGENS,
+};
+use crate::domain::menu::MenuId;
+use crate::domain::modifier::IngredientDelta;
+use crate::domain::recipe::{flatten_ingredients, Recipe, RecipeId};
+use crate::domain::DishId;
+use crate::infrastructure::persistence::RecipeRepositoryTrait;
+use crate::shared::{translate_allergen, AppError, AppResult, Language, TenantId};
+
+/// What a "may contain" declaration is attached to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum TraceTarget {
+    /// Supplier trace warning on a catalog ingredient
+    Ingredient(Uuid),
+    /// Kitchen cross-contact on a recipe (shared fryer, shared board)
+    Recipe(Uuid),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AllergenTraceDeclaration {
+    pub id: Uuid,
+    pub target: TraceTarget,
+    pub allergen: Allergen,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeclareTraceInput {
+    pub target: TraceTarget,
+    pub allergen: Allergen,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Allergens an add-on brings that the plain dish does not contain
+#[derive(Debug, Clone, Serialize)]
+pub struct ModifierAllergens {
+    pub group_name: String,
+    pub option_name: String,
+    pub allergens: Vec<Allergen>,
+    /// Names in the label language
+    pub labels: Vec<String>,
+}
+
+/// Allergen declaration and nutrition of one dish
+#[derive(Debug, Clone, Serialize)]
+pub struct DishLabel {
+    pub dish_id: DishId,
+    pub dish_name: String,
+    pub language: Language,
+    pub allergens: Vec<DishAllergen>,
+    pub modifier_allergens: Vec<ModifierAllergens>,
+    pub nutrition: NutritionLabel,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AllergenColumn {
+    pub allergen: Allergen,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AllergenMatrixRow {
+    pub dish_id: DishId,
+    pub dish_name: String,
+    /// One cell per column; `None` = not present
+    pub cells: Vec<Option<AllergenPresence>>,
+}
+
+/// Every active dish against the 14 EU allergens
+#[derive(Debug, Clone, Serialize)]
+pub struct AllergenMatrix {
+    pub language: Language,
+    pub columns: Vec<AllergenColumn>,
+    pub rows: Vec<AllergenMatrixRow>,
+}
+
+#[derive(Clone)]
+pub struct DishLabelService {
+    pool: PgPool,
+    recipe_repo: Arc<dyn RecipeRepositoryTrait>,
+}
+
+/// Recipe tree of one dish
+struct DishTree {
+    dish_id: DishId,
+    dish_name: String,
+    root: RecipeId,
+    graph: HashMap<RecipeId, Recipe>,
+}
+
+impl DishLabelService {
+    pub fn new(pool: PgPool, recipe_repo: Arc<dyn RecipeRepositoryTrait>) -> Self {
+        Self { pool, recipe_repo }
+    }
+
+    /// Allergens (with provenance), add-on allergens and per-portion nutrition
+    pub async fn dish_label(
+        &self,
+        tenant_id: TenantId,
+        dish_id: DishId,
+        language: Language,
+    ) -> AppResult<DishLabel> {
+        let row =
+            sqlx::query("SELECT id, name, recipe_id FROM dishes WHERE id = $1 AND tenant_id = $2")
+                .bind(dish_id.as_uuid())
+                .bind(tenant_id.as_uuid())
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::not_found("Dish not found"))?;
+        let tree = self.load_tree(tenant_id, &row).await?;
+
+        let groups = fetch_modifier_groups(&self.pool, tenant_id, dish_id).await?;
+        let mut ids = tree_ingredient_ids(&tree);
+        for option in groups.iter().flat_map(|g| g.options.iter()) {
+            ids.extend(option.ingredients.iter().map(|d| d.catalog_ingredient_id()));
+        }
+        let ingredients = self.label_ingredients(tenant_id, &ids, language).await?;
+        let recipe_traces = self.recipe_traces(tenant_id).await?;
+
+        let allergens =
+            resolve_allergens(tree.root, &tree.graph, &ingredients, &recipe_traces, |a| {
+                translate_allergen(a.as_str(), language)
+            })?;
+
+        let mut modifier_allergens = Vec::new();
+        for group in &groups {
+            for option in group.options.iter().filter(|o| o.is_active) {
+                let mut added: Vec<Allergen> = Vec::new();
+                for delta in &option.ingredients {
+                    let IngredientDelta::Add {
+                        catalog_ingredient_id,
+                        ..
+                    } = delta
+                    else {
+                        continue;
+                    };
+                    let Some(ingredient) = ingredients.get(catalog_ingredient_id) else {
+                        continue;
+                    };
+                    for allergen in &ingredient.allergens {
+                        let in_base = allergens.iter().any(|a| {
+                            a.allergen == *allergen && a.presence == AllergenPresence::Contains
+                        });
+                        if !in_base && !added.contains(allergen) {
+                            added.push(*allergen);
+                        }
+                    }
+                }
+                if added.is_empty() {
+                    continue;
+                }
+                added.sort_by_key(|a| EU_ALLERGENS.iter().position(|e| e == a));
+                modifier_allergens.push(ModifierAllergens {
+                    group_name: group.name.clone(),
+                    option_name: option.name.clone(),
+                    labels: added
+                        .iter()
+                        .map(|a| translate_allergen(a.as_str(), language))
+                        .collect(),
+                    allergens: added,
+                });
+            }
+        }
+
+        let servings = tree
+            .graph
+            .get(&tree.root)
+            .map(|r| r.servings().count())
+            .unwrap_or(1);
+        let portion = per_portion(flatten_ingredients(tree.root, &tree.graph)?, servings);
+        let nutrition = portion_nutrition(&portion, &ingredients)?;
+
+        Ok(DishLabel {
+            dish_id: tree.dish_id,
+            dish_name: tree.dish_name,
+            language,
+            allergens,
+            modifier_allergens,
+            nutrition,
+        })
+    }
+
+    /// Matrix of active dishes (optionally one menu's) × EU-14 allergens
+    pub async fn allergen_matrix(
+        &self,
+        tenant_id: TenantId,
+        language: Language,
+        menu_id: Option<MenuId>,
+    ) -> AppResult<AllergenMatrix> {
+        let rows = sqlx::query(
+            r#"
+            SELECT d.id, d.name, d.recipe_id
+            FROM dishes d
+            WHERE d.tenant_id = $1 AND d.active
+              AND ($2::UUID IS NULL OR EXISTS (
+                  SELECT 1
+                  FROM menu_section_dishes sd
+                  JOIN menu_sections s ON s.id = sd.section_id
+                  WHERE sd.dish_id = d.id AND s.menu_id = $2
+              ))
+            ORDER BY d.name
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(menu_id.map(|id| id.as_uuid()))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut trees = Vec::with_capacity(rows.len());
+        for row in &rows {
+            trees.push(self.load_tree(tenant_id, row).await?);
+        }
+        let ids: HashSet<CatalogIngredientId> =
+            trees.iter().flat_map(tree_ingredient_ids).collect();
+        let ingredients = self.label_ingredients(tenant_id, &ids, language).await?;
+        let recipe_traces = self.recipe_traces(tenant_id).await?;
+
+        let rows = trees
+            .into_iter()
+            .map(|tree| {
+                let allergens =
+                    resolve_allergens(tree.root, &tree.graph, &ingredients, &recipe_traces, |a| {
+                        a.as_str().to_string()
+                    })?;
+                Ok(AllergenMatrixRow {
+                    dish_id: tree.dish_id,
+                    dish_name: tree.dish_name,
+                    cells: EU_ALLERGENS
+                        .iter()
+                        .map(|e| {
+                            allergens
+                                .iter()
+                                .find(|a| a.allergen == *e)
+                                .map(|a| a.presence)
+                        })
+                        .collect(),
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(AllergenMatrix {
+            language,
+            columns: EU_ALLERGENS
+                .iter()
+                .map(|a| AllergenColumn {
+                    allergen: *a,
+                    label: translate_allergen(a.as_str(), language),
+                })
+                .collect(),
+            rows,
+        })
+    }
+
+    pub async fn list_traces(
+        &self,
+        tenant_id: TenantId,
+    ) -> AppResult<Vec<AllergenTraceDeclaration>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, catalog_ingredient_id, recipe_id, allergen::text AS allergen, note, created_at
+            FROM allergen_trace_declarations
+            WHERE tenant_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let recipe_id: Option<Uuid> = row.try_get("recipe_id")?;
+                let target = match recipe_id {
+                    Some(id) => TraceTarget::Recipe(id),
+                    None => TraceTarget::Ingredient(row.try_get("catalog_ingredient_id")?),
+                };
+                Ok(AllergenTraceDeclaration {
+                    id: row.try_get("id")?,
+                    target,
+                    allergen: Allergen::from_str(row.try_get("allergen")?)?,
+                    note: row.try_get("note")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn declare_trace(
+        &self,
+        tenant_id: TenantId,
+        input: DeclareTraceInput,
+    ) -> AppResult<AllergenTraceDeclaration> {
+        let (ingredient_id, recipe_id) = match input.target {
+            TraceTarget::Ingredient(id) => {
+                let exists: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM catalog_ingredients WHERE id = $1)",
+                )
+                .bind(id)
+                .fetch_one(&self.pool)
+                .await?;
+                if !exists {
+                    return Err(AppError::not_found("Catalog ingredient not found"));
+                }
+                (Some(id), None)
+            }
+            TraceTarget::Recipe(id) => {
+                let exists: bool = sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM recipes WHERE id = $1 AND tenant_id = $2)",
+                )
+                .bind(id)
+                .bind(tenant_id.as_uuid())
+                .fetch_one(&self.pool)
+                .await?;
+                if !exists {
+                    return Err(AppError::not_found("Recipe not found"));
+                }
+                (None, Some(id))
+            }
+        };
+        let note = input
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        let declaration = AllergenTraceDeclaration {
+            id: Uuid::new_v4(),
+            target: input.target,
+            allergen: input.allergen,
+            note,
+            created_at: OffsetDateTime::now_utc(),
+        };
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO allergen_trace_declarations
+                (id, tenant_id, catalog_ingredient_id, recipe_id, allergen, note, created_at)
+            VALUES ($1, $2, $3, $4, $5::allergen_type, $6, $7)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(declaration.id)
+        .bind(tenant_id.as_uuid())
+        .bind(ingredient_id)
+        .bind(recipe_id)
+        .bind(declaration.allergen.as_str())
+        .bind(&declaration.note)
+        .bind(declaration.created_at)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            return Err(AppError::conflict(
+                "This allergen is already declared for the target",
+            ));
+        }
+
+        Ok(declaration)
+    }
+
+    pub async fn delete_trace(&self, tenant_id: TenantId, id: Uuid) -> AppResult<()> {
+        let deleted =
+            sqlx::query("DELETE FROM allergen_trace_declarations WHERE id = $1 AND tenant_id = $2")
+                .bind(id)
+                .bind(tenant_id.as_uuid())
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        if deleted == 0 {
+            return Err(AppError::not_found("Allergen declaration not found"));
+        }
+        Ok(())
+    }
+
+    async fn load_tree(
+        &self,
+        tenant_id: TenantId,
+        dish: &sqlx::postgres::PgRow,
+    ) -> AppResult<DishTree> {
+        let recipe_id = RecipeId::from_uuid(dish.try_get("recipe_id")?);
+        let recipe = self
+            .recipe_repo
+            .find_by_id(recipe_id, tenant_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Recipe not found"))?;
+        let graph = load_component_graph(self.recipe_repo.as_ref(), recipe, tenant_id).await?;
+        Ok(DishTree {
+            dish_id: DishId::from_uuid(dish.try_get("id")?),
+            dish_name: dish.try_get("name")?,
+            root: recipe_id,
+            graph,
+        })
+    }
+
+    /// Catalog allergens, trace declarations and nutrition per 100 g.
+    /// Dedicated columns win; the nutrition tables fill the gaps
+    /// (salt from sodium × 2.5).
+    async fn label_ingredients(
+        &self,
+        tenant_id: TenantId,
+        ids: &HashSet<CatalogIngredientId>,
+        language: Language,
+    ) -> AppResult<HashMap<CatalogIngredientId, LabelIngredient>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let uuids: Vec<Uuid> = ids.iter().map(|id| id.as_uuid()).collect();
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                ci.id, ci.name_en, ci.name_pl, ci.name_uk, ci.name_ru,
+                ci.default_unit::text AS unit,
+                ARRAY(SELECT unnest(ci.allergens)::text) AS allergens,
+                ci.density_g_per_ml,
+                ci.typical_portion_g,
+                COALESCE(ci.calories_per_100g::float8, nm.calories_kcal::float8) AS energy_kcal,
+                COALESCE(ci.fat_per_100g::float8, nm.fat_g::float8) AS fat_g,
+                fa.saturated_fat::float8 AS saturated_fat_g,
+                COALESCE(ci.carbs_per_100g::float8, nm.carbs_g::float8) AS carbs_g,
+                COALESCE(ci.sugar_per_100g::float8, nm.sugar_g::float8) AS sugars_g,
+                COALESCE(ci.fiber_per_100g::float8, nm.fiber_g::float8) AS fiber_g,
+                COALESCE(ci.protein_per_100g::float8, nm.protein_g::float8) AS protein_g,
+                COALESCE(ci.salt_per_100g::float8, mi.sodium::float8 * 2.5 / 1000) AS salt_g
+            FROM catalog_ingredients ci
+            LEFT JOIN nutrition_macros nm ON nm.product_id = ci.id
+            LEFT JOIN nutrition_fatty_acids fa ON fa.product_id = ci.id
+            LEFT JOIN nutrition_minerals mi ON mi.product_id = ci.id
+            WHERE ci.id = ANY($1)
+            "#,
+        )
+        .bind(&uuids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let trace_rows = sqlx::query(
+            r#"
+            SELECT catalog_ingredient_id, allergen::text AS allergen, note
+            FROM allergen_trace_declarations
+            WHERE tenant_id = $1 AND catalog_ingredient_id = ANY($2)
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(&uuids)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut traces: HashMap<Uuid, Vec<AllergenTrace>> = HashMap::new();
+        for row in trace_rows {
+            traces
+                .entry(row.try_get("catalog_ingredient_id")?)
+                .or_default()
+                .push(AllergenTrace {
+                    allergen: Allergen::from_str(row.try_get("allergen")?)?,
+                    note: row.try_get("note")?,
+                });
+        }
+
+        let mut out = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let id: Uuid = row.try_get("id")?;
+            let name_en: Option<String> = row.try_get("name_en")?;
+            let localized: Option<String> = row.try_get(match language {
+                Language::En => "name_en",
+                Language::Pl => "name_pl",
+                Language::Uk => "name_uk",
+                Language::Ru => "name_ru",
+            })?;
+            let name = localized
+                .filter(|n| !n.is_empty())
+                .or(name_en)
+                .unwrap_or_default();
+            let allergens: Vec<String> = row.try_get("allergens")?;
+            out.insert(
+                CatalogIngredientId::from_uuid(id),
+                LabelIngredient {
+                    name,
+                    allergens: allergens
+                        .iter()
+                        .filter_map(|a| Allergen::from_str(a).ok())
+                        .collect(),
+                    traces: traces.remove(&id).unwrap_or_default(),
+                    unit: Unit::from_str(row.try_get("unit")?)?,
+                    density_g_per_ml: row.try_get("density_g_per_ml")?,
+                    typical_portion_g: row.try_get("typical_portion_g")?,
+                    nutrients: NutrientsPer100g {
+                        energy_kcal: row.try_get("energy_kcal")?,
+                        fat_g: row.try_get("fat_g")?,
+                        saturated_fat_g: row.try_get("saturated_fat_g")?,
+                        carbs_g: row.try_get("carbs_g")?,
+                        sugars_g: row.try_get("sugars_g")?,
+                        fiber_g: row.try_get("fiber_g")?,
+                        protein_g: row.try_get("protein_g")?,
+                        salt_g: row.try_get("salt_g")?,
+                    },
+                },
+            );
+        }
+        Ok(out)
+    }
+
+    /// Cross-contact declarations per recipe
+    async fn recipe_traces(
+        &self,
+        tenant_id: TenantId,
+    ) -> AppResult<HashMap<RecipeId, Vec<AllergenTrace>>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT recipe_id, allergen::text AS allergen, note
+            FROM allergen_trace_declarations
+            WHERE tenant_id = $1 AND recipe_id IS NOT NULL
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut out: HashMap<RecipeId, Vec<AllergenTrace>> = HashMap::new();
+        for row in rows {
+            out.entry(RecipeId::from_uuid(row.try_get("recipe_id")?))
+                .or_default()
+                .push(AllergenTrace {
+                    allergen: Allergen::from_str(row.try_get("allergen")?)?,
+                    note: row.try_get("note")?,
+                });
+        }
+        Ok(out)
+    }
+}
+
+fn tree_ingredient_ids(tree: &DishTree) -> HashSet<CatalogIngredientId> {
+    tree.graph
+        .values()
+        .flat_map(|r| r.ingredients().iter().map(|i| i.catalog_ingredient_id()))
+        .collect()
+}
@@ -1,9 +1,13 @@
 use crate::domain::{
     catalog::CatalogIngredientId,
     inventory::{
-        calculate_expiration_status, BatchStatus, ExpirationSeverity, InventoryBatch,
-        InventoryBatchId, InventoryMovement, Money, MovementType, Quantity,
+        calculate_expiration_status, BatchStatus, CostingStrategy, ExpirationSeverity,
+        InventoryBatch, InventoryBatchId, InventoryMovement, Money, MovementType, Quantity,
+        StockPolicy,
     },
+    storage_location::StorageLocationId,
+    supplier::SupplierId,
+    waste::{loss_reason_label, LossBucket, AUTO_EXPIRY_REASON},
 };
 use crate::infrastructure::persistence::{InventoryBatchRepository, InventoryBatchRepositoryTrait};
 use crate::shared::{AppError, AppResult, Language, TenantId, UserId};
@@ -11,6 +15,7 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::Serialize;
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 use std::sync::Arc;
 use time::OffsetDateTime;
 
@@ -23,6 +28,8 @@ pub struct InventoryStatus {
     pub warning: usize,
     pub expired: usize,
     pub low_stock: usize,
+    /// Ingredients with unresolved shortfalls (sold without stock)
+    pub shortfall: usize,
     /// Total count for the red badge (expired + critical)
     pub badge_count: usize,
 }
@@ -35,6 +42,10 @@ pub struct InventoryDashboard {
     pub health_score: i32,
     pub stockout_risks: Vec<StockoutPrediction>,
     pub expired_risks: Vec<RiskProduct>,
+    /// Unresolved shortfall entries (unbacked sales)
+    pub open_shortfalls: i64,
+    /// Estimated value of unbacked consumption (last known prices)
+    pub shortfall_cents: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -64,6 +75,7 @@ impl Default for InventoryStatus {
             warning: 0,
             expired: 0,
             low_stock: 0,
+            shortfall: 0,
             badge_count: 0,
         }
     }
@@ -99,16 +111,31 @@ impl InventoryService {
         &self,
         tenant_id: TenantId,
         language: Language,
+    ) -> AppResult<InventoryDashboard> {
+        self.get_dashboard_at(tenant_id, language, None).await
+    }
+
+    /// Dashboard restricted to one storage location (`None` → whole tenant).
+    /// Stock value, stockout predictions and risk products follow the location;
+    /// health, waste and shortfalls stay tenant-wide.
+    pub async fn get_dashboard_at(
+        &self,
+        tenant_id: TenantId,
+        language: Language,
+        location: Option<StorageLocationId>,
     ) -> AppResult<InventoryDashboard> {
         let lang_code = language.code();
+        let location_uuid = location.map(|id| id.as_uuid());
 
         // 1. Get current stock value (cents)
         let total_stock_value_cents: i64 = sqlx::query_scalar(
             "SELECT COALESCE(SUM(remaining_quantity * price_per_unit_cents), 0)::BIGINT 
              FROM inventory_batches 
-             WHERE tenant_id = $1 AND status = 'active'",
+             WHERE tenant_id = $1 AND status = 'active'
+               AND ($2::UUID IS NULL OR location_id = $2)",
         )
         .bind(tenant_id.as_uuid())
+        .bind(location_uuid)
         .fetch_one(&self.pool)
         .await?;
 
@@ -120,11 +147,23 @@ impl InventoryService {
 
         // 4. Calculate Stockout Predictions
         let stockout_risks = self
-            .calculate_stockout_predictions(tenant_id, lang_code)
+            .calculate_stockout_predictions(tenant_id, lang_code, location_uuid)
             .await?;
 
         // 5. Identify Risk Products
-        let expired_risks = self.identify_risk_products(tenant_id, lang_code).await?;
+        let expired_risks = self
+            .identify_risk_products(tenant_id, lang_code, location_uuid)
+            .await?;
+
+        // 6. Unresolved shortfalls
+        let (open_shortfalls, shortfall_cents): (i64, i64) = sqlx::query_as(
+            "SELECT COUNT(*), COALESCE(SUM(estimated_cost_cents), 0)::BIGINT
+             FROM inventory_shortfalls
+             WHERE tenant_id = $1 AND resolved_at IS NULL",
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_one(&self.pool)
+        .await?;
 
         Ok(InventoryDashboard {
             total_stock_value_cents,
@@ -133,6 +172,8 @@ impl InventoryService {
             health_score: health.health_score,
             stockout_risks,
             expired_risks,
+            open_shortfalls,
+            shortfall_cents,
         })
     }
 
@@ -140,6 +181,7 @@ impl InventoryService {
         &self,
         tenant_id: TenantId,
         lang_code: &str,
+        location: Option<uuid::Uuid>,
     ) -> AppResult<Vec<StockoutPrediction>> {
         let query = r#"
             WITH consumption AS (
@@ -150,7 +192,13 @@ impl InventoryService {
                 JOIN inventory_batches ib ON im.batch_id = ib.id
                 WHERE ib.tenant_id = $1 
                   AND im.type = 'OUT_SALE'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM inventory_movements u
+                      WHERE u.tenant_id = im.tenant_id AND u.reference_id = im.reference_id
+                        AND u.reference_type = 'copilot_undo'
+                  )
                   AND im.created_at > NOW() - INTERVAL '14 days'
+                  AND ($3::UUID IS NULL OR ib.location_id = $3)
                 GROUP BY ib.catalog_ingredient_id
             ),
             current_stock AS (
@@ -166,6 +214,7 @@ impl InventoryService {
                 FROM inventory_batches ib
                 JOIN catalog_ingredients ci ON ib.catalog_ingredient_id = ci.id
                 WHERE ib.tenant_id = $1 AND ib.status = 'active'
+                  AND ($3::UUID IS NULL OR ib.location_id = $3)
                 GROUP BY ib.catalog_ingredient_id, ingredient_name
             )
             SELECT 
@@ -183,6 +232,7 @@ impl InventoryService {
         let rows = sqlx::query(query)
             .bind(tenant_id.as_uuid())
             .bind(lang_code)
+            .bind(location)
             .fetch_all(&self.pool)
             .await?;
 
@@ -213,6 +263,7 @@ impl InventoryService {
         &self,
         tenant_id: TenantId,
         lang_code: &str,
+        location: Option<uuid::Uuid>,
     ) -> AppResult<Vec<RiskProduct>> {
         let query = r#"
             SELECT 
@@ -236,6 +287,7 @@ impl InventoryService {
               AND ib.status = 'active'
               AND ib.expires_at < NOW() + INTERVAL '3 days'
               AND ib.remaining_quantity > 0
+              AND ($3::UUID IS NULL OR ib.location_id = $3)
             ORDER BY ib.expires_at ASC
             LIMIT 10
         "#;
@@ -243,6 +295,7 @@ impl InventoryService {
         let rows = sqlx::query(query)
             .bind(tenant_id.as_uuid())
             .bind(lang_code)
+            .bind(location)
             .fetch_all(&self.pool)
             .await?;
 
@@ -271,14 +324,31 @@ impl InventoryService {
         catalog_ingredient_id: CatalogIngredientId,
         price_per_unit_cents: i64,
         quantity: f64,
+        supplier_id: Option<SupplierId>,
         supplier: Option<String>,
         invoice_number: Option<String>,
+        location_id: Option<StorageLocationId>,
         received_at: OffsetDateTime,
         expires_at: OffsetDateTime,
     ) -> AppResult<InventoryBatchId> {
         let price = Money::from_cents(price_per_unit_cents)?;
         let qty = Quantity::new(quantity)?;
 
+        // Supplier entity wins; its name is copied into the free-text column
+        let supplier = match supplier_id {
+            Some(id) => {
+                let name: Option<String> = sqlx::query_scalar(
+                    "SELECT name FROM suppliers WHERE id = $1 AND tenant_id = $2",
+                )
+                .bind(id.as_uuid())
+                .bind(tenant_id.as_uuid())
+                .fetch_optional(&self.pool)
+                .await?;
+                Some(name.ok_or_else(|| AppError::not_found("Supplier not found"))?)
+            }
+            None => supplier,
+        };
+
         let mut batch = InventoryBatch::new(
             user_id,
             tenant_id,
@@ -290,13 +360,19 @@ impl InventoryService {
         );
 
         batch.supplier = supplier;
+        batch.supplier_id = supplier_id;
         batch.invoice_number = invoice_number;
+        batch.location_id = location_id;
 
         let batch_id = batch.id;
 
         // Use transaction to ensure both batch and movement are created
         let mut tx = self.pool.begin().await?;
 
+        if let Some(location) = location_id {
+            ensure_active_location(&mut tx, tenant_id, location).await?;
+        }
+
         self.inventory_repo
             .create_in_transaction(&mut tx, &batch)
             .await?;
@@ -317,6 +393,11 @@ impl InventoryService {
             .record_movement(&mut tx, &movement)
             .await?;
 
+        // 📈 Price history point for supplier comparison / costing
+        self.inventory_repo
+            .record_price_point(&mut tx, &batch)
+            .await?;
+
         tx.commit().await?;
 
         Ok(batch_id)
@@ -341,6 +422,8 @@ impl InventoryService {
             quantity,
             None,
             None,
+            None,
+            None,
             received_at,
             expires_at,
         )
@@ -349,6 +432,15 @@ impl InventoryService {
 
     /// Get stock summary (runtime calculation from batches)
     pub async fn get_stock_summary(&self, tenant_id: TenantId) -> AppResult<Vec<StockSummary>> {
+        self.get_stock_summary_at(tenant_id, None).await
+    }
+
+    /// Stock summary of one storage location (`None` → whole tenant)
+    pub async fn get_stock_summary_at(
+        &self,
+        tenant_id: TenantId,
+        location: Option<StorageLocationId>,
+    ) -> AppResult<Vec<StockSummary>> {
         let query = r#"
             SELECT 
                 ib.catalog_ingredient_id,
@@ -362,12 +454,14 @@ impl InventoryService {
             FROM inventory_batches ib
             JOIN catalog_ingredients ci ON ib.catalog_ingredient_id = ci.id
             WHERE ib.tenant_id = $1 AND ib.status = 'active'
+              AND ($2::UUID IS NULL OR ib.location_id = $2)
             GROUP BY ib.catalog_ingredient_id, ci.name_en
             HAVING SUM(ib.remaining_quantity) > 0
         "#;
 
         let rows = sqlx::query(query)
             .bind(tenant_id.as_uuid())
+            .bind(location.map(|id| id.as_uuid()))
             .fetch_all(&self.pool)
             .await?;
 
@@ -520,6 +614,7 @@ impl InventoryService {
                 ip.quantity,
                 ip.remaining_quantity,
                 ip.price_per_unit_cents,
+                ip.location_id,
                 ip.received_at,
                 ip.expires_at,
                 ip.created_at,
@@ -592,6 +687,9 @@ impl InventoryService {
                     .try_get("price_per_unit_cents")
                     .map_err(|e| AppError::internal(&format!("DB: {}", e)))?,
                 severity: status,
+                location_id: row
+                    .try_get("location_id")
+                    .map_err(|e| AppError::internal(format!("DB: {}", e)))?,
                 received_at: row
                     .try_get("received_at")
                     .map_err(|e| AppError::internal(&format!("DB: {}", e)))?,
@@ -617,6 +715,31 @@ impl InventoryService {
         reference_id: Option<uuid::Uuid>,
         reference_type: Option<String>,
         notes: Option<String>,
+    ) -> AppResult<()> {
+        self.deduct_fifo_at(
+            tenant_id,
+            catalog_ingredient_id,
+            quantity_to_deduct,
+            None,
+            reference_id,
+            reference_type,
+            notes,
+        )
+        .await
+    }
+
+    /// FIFO deduction limited to the batches of one storage location
+    /// (`None` → any location)
+    #[allow(clippy::too_many_arguments)]
+    pub async fn deduct_fifo_at(
+        &self,
+        tenant_id: TenantId,
+        catalog_ingredient_id: CatalogIngredientId,
+        quantity_to_deduct: f64,
+        location: Option<StorageLocationId>,
+        reference_id: Option<uuid::Uuid>,
+        reference_type: Option<String>,
+        notes: Option<String>,
     ) -> AppResult<()> {
         let target_qty = Quantity::new(quantity_to_deduct)?.decimal();
 
@@ -629,7 +752,12 @@ impl InventoryService {
         // 1. Get active batches with FOR UPDATE lock
         let batches = self
             .inventory_repo
-            .list_active_by_ingredient_for_update(&mut tx, tenant_id, catalog_ingredient_id)
+            .list_active_by_ingredient_for_update(
+                &mut tx,
+                tenant_id,
+                catalog_ingredient_id,
+                location,
+            )
             .await?;
 
         let mut remaining_to_deduct = target_qty;
@@ -688,6 +816,20 @@ impl InventoryService {
         Ok(())
     }
 
+    /// Undo a FIFO deduction made with `reference_id`: the stock goes back
+    /// to the batches it came from. Returns the restored quantity.
+    pub async fn restore_deduction(
+        &self,
+        tenant_id: TenantId,
+        reference_id: uuid::Uuid,
+    ) -> AppResult<f64> {
+        let mut tx = self.pool.begin().await?;
+        let restored =
+            restore_movements(&self.inventory_repo, &mut tx, tenant_id, reference_id).await?;
+        tx.commit().await?;
+        Ok(restored.to_f64().unwrap_or(0.0))
+    }
+
     /// Process expirations (automagically exhaust batches)
     pub async fn process_expirations(&self, tenant_id: TenantId) -> AppResult<usize> {
         let now = OffsetDateTime::now_utc();
@@ -743,14 +885,28 @@ impl InventoryService {
         Ok(processed_count)
     }
 
-    /// Get loss report (expired products) for the last N days
+    /// Get loss report (auto-expired + logged waste) for the last N days
     pub async fn get_loss_report(
         &self,
         tenant_id: TenantId,
         days: i32,
         lang_code: &str,
     ) -> AppResult<LossReport> {
-        // 1. Get losses (OUT_EXPIRE)
+        self.get_loss_report_bucketed(tenant_id, days, lang_code, LossBucket::for_period(days))
+            .await
+    }
+
+    /// Loss report with an explicit timeline bucket (day/week/month)
+    pub async fn get_loss_report_bucketed(
+        &self,
+        tenant_id: TenantId,
+        days: i32,
+        lang_code: &str,
+        bucket: LossBucket,
+    ) -> AppResult<LossReport> {
+        let language = Language::from_str(lang_code).unwrap_or_default();
+
+        // 1. Get losses per ingredient (OUT_EXPIRE + OUT_WASTE)
         let loss_query = r#"
             SELECT 
                 ci.id as ingredient_id,
@@ -766,7 +922,12 @@ impl InventoryService {
             JOIN inventory_batches ib ON im.batch_id = ib.id
             JOIN catalog_ingredients ci ON ib.catalog_ingredient_id = ci.id
             WHERE im.tenant_id = $1 
-              AND im.type = 'OUT_EXPIRE'
+              AND im.type IN ('OUT_EXPIRE', 'OUT_WASTE')
+              AND NOT EXISTS (
+                  SELECT 1 FROM inventory_movements u
+                  WHERE u.tenant_id = im.tenant_id AND u.reference_id = im.reference_id
+                    AND u.reference_type = 'copilot_undo'
+              )
               AND im.created_at >= NOW() - ($2 * INTERVAL '1 day')
             GROUP BY ci.id, ingredient_name
             ORDER BY total_loss_cents DESC
@@ -797,7 +958,76 @@ impl InventoryService {
             });
         }
 
-        // 2. Get total purchases (IN) to calculate KPI
+        // 2. Losses per reason (auto expiry has no waste entry)
+        let reason_rows = sqlx::query(
+            r#"
+            SELECT
+                CASE WHEN type = 'OUT_EXPIRE' THEN $3 ELSE COALESCE(reason, 'other') END AS reason,
+                COUNT(DISTINCT COALESCE(reference_id, id)) AS entries,
+                SUM(total_cost_cents)::BIGINT AS loss_cents
+            FROM inventory_movements im
+            WHERE tenant_id = $1
+              AND type IN ('OUT_EXPIRE', 'OUT_WASTE')
+              AND NOT EXISTS (
+                  SELECT 1 FROM inventory_movements u
+                  WHERE u.tenant_id = im.tenant_id AND u.reference_id = im.reference_id
+                    AND u.reference_type = 'copilot_undo'
+              )
+              AND created_at >= NOW() - ($2 * INTERVAL '1 day')
+            GROUP BY 1
+            ORDER BY loss_cents DESC
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(days)
+        .bind(AUTO_EXPIRY_REASON)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut by_reason = Vec::with_capacity(reason_rows.len());
+        for row in reason_rows {
+            let reason: String = row.try_get("reason")?;
+            by_reason.push(LossByReason {
+                label: loss_reason_label(&reason, language).to_string(),
+                reason,
+                entries: row.try_get("entries")?,
+                loss_value_cents: row.try_get("loss_cents")?,
+            });
+        }
+
+        // 3. Loss timeline
+        let timeline_rows = sqlx::query(
+            r#"
+            SELECT date_trunc($3, created_at) AS period_start,
+                   SUM(total_cost_cents)::BIGINT AS loss_cents
+            FROM inventory_movements im
+            WHERE tenant_id = $1
+              AND type IN ('OUT_EXPIRE', 'OUT_WASTE')
+              AND NOT EXISTS (
+                  SELECT 1 FROM inventory_movements u
+                  WHERE u.tenant_id = im.tenant_id AND u.reference_id = im.reference_id
+                    AND u.reference_type = 'copilot_undo'
+              )
+              AND created_at >= NOW() - ($2 * INTERVAL '1 day')
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(days)
+        .bind(bucket.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut timeline = Vec::with_capacity(timeline_rows.len());
+        for row in timeline_rows {
+            timeline.push(LossPeriod {
+                period_start: row.try_get("period_start")?,
+                loss_value_cents: row.try_get("loss_cents")?,
+            });
+        }
+
+        // 4. Get total purchases (IN) to calculate KPI
         let purchase_query = r#"
             SELECT COALESCE(SUM(total_cost_cents), 0)::BIGINT as total_purchased_cents
             FROM inventory_movements
@@ -820,6 +1050,9 @@ impl InventoryService {
 
         Ok(LossReport {
             items,
+            by_reason,
+            bucket,
+            timeline,
             total_loss_cents,
             total_purchased_cents,
             waste_percentage,
@@ -839,6 +1072,293 @@ impl InventoryService {
     ) -> AppResult<Vec<crate::domain::inventory::InventoryAlert>> {
         self.alert_service.get_alerts(tenant_id).await
     }
+
+    /// Get tenant stock policy (what a sale does when stock runs out)
+    pub async fn get_stock_policy(&self, tenant_id: TenantId) -> AppResult<StockPolicy> {
+        fetch_stock_policy(&self.pool, tenant_id).await
+    }
+
+    /// Set tenant stock policy
+    pub async fn set_stock_policy(
+        &self,
+        tenant_id: TenantId,
+        policy: StockPolicy,
+    ) -> AppResult<StockPolicy> {
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_inventory_settings (tenant_id, stock_policy)
+            VALUES ($1, $2)
+            ON CONFLICT (tenant_id) DO UPDATE SET stock_policy = EXCLUDED.stock_policy
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(policy.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// Get tenant costing strategy (which unit price recipe costing uses)
+    pub async fn get_costing_strategy(&self, tenant_id: TenantId) -> AppResult<CostingStrategy> {
+        self.inventory_repo.costing_strategy(tenant_id).await
+    }
+
+    /// Unit prices per ingredient under the tenant's costing strategy
+    pub async fn unit_prices(
+        &self,
+        tenant_id: TenantId,
+        ids: &[CatalogIngredientId],
+    ) -> AppResult<HashMap<CatalogIngredientId, Money>> {
+        let strategy = self.inventory_repo.costing_strategy(tenant_id).await?;
+        self.inventory_repo
+            .unit_prices(tenant_id, strategy, ids)
+            .await
+    }
+
+    /// Set tenant costing strategy
+    pub async fn set_costing_strategy(
+        &self,
+        tenant_id: TenantId,
+        strategy: CostingStrategy,
+    ) -> AppResult<CostingStrategy> {
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_inventory_settings (tenant_id, costing_strategy)
+            VALUES ($1, $2)
+            ON CONFLICT (tenant_id) DO UPDATE SET costing_strategy = EXCLUDED.costing_strategy
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(strategy.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(strategy)
+    }
+
+    /// List shortfall entries (unbacked sales), newest first
+    pub async fn list_shortfalls(
+        &self,
+        tenant_id: TenantId,
+        include_resolved: bool,
+        lang_code: &str,
+    ) -> AppResult<Vec<ShortfallView>> {
+        let query = r#"
+            SELECT
+                s.id,
+                s.catalog_ingredient_id,
+                CASE
+                    WHEN $3 = 'ru' THEN COALESCE(ci.name_ru, ci.name_en)
+                    WHEN $3 = 'pl' THEN COALESCE(ci.name_pl, ci.name_en)
+                    WHEN $3 = 'uk' THEN COALESCE(ci.name_uk, ci.name_en)
+                    ELSE ci.name_en
+                END as ingredient_name,
+                s.quantity,
+                s.unit_cost_cents,
+                s.estimated_cost_cents,
+                s.reference_id,
+                s.reference_type,
+                s.notes,
+                s.created_at,
+                s.resolved_at
+            FROM inventory_shortfalls s
+            JOIN catalog_ingredients ci ON s.catalog_ingredient_id = ci.id
+            WHERE s.tenant_id = $1
+              AND ($2 OR s.resolved_at IS NULL)
+            ORDER BY s.created_at DESC
+            LIMIT 500
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(tenant_id.as_uuid())
+            .bind(include_resolved)
+            .bind(lang_code)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(ShortfallView {
+                id: row.try_get("id")?,
+                ingredient_id: row.try_get("catalog_ingredient_id")?,
+                ingredient_name: row.try_get("ingredient_name")?,
+                quantity: row
+                    .try_get::<Decimal, _>("quantity")?
+                    .to_f64()
+                    .unwrap_or(0.0),
+                unit_cost_cents: row.try_get("unit_cost_cents")?,
+                estimated_cost_cents: row.try_get("estimated_cost_cents")?,
+                reference_id: row.try_get("reference_id")?,
+                reference_type: row.try_get("reference_type")?,
+                notes: row.try_get("notes")?,
+                created_at: row.try_get("created_at")?,
+                resolved_at: row.try_get("resolved_at")?,
+            });
+        }
+
+        Ok(items)
+    }
+
+    /// Mark a shortfall as resolved (e.g. stock was back-filled or written off)
+    pub async fn resolve_shortfall(
+        &self,
+        tenant_id: TenantId,
+        shortfall_id: uuid::Uuid,
+        user_id: UserId,
+    ) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE inventory_shortfalls
+            SET resolved_at = NOW(), resolved_by = $3
+            WHERE id = $1 AND tenant_id = $2 AND resolved_at IS NULL
+            "#,
+        )
+        .bind(shortfall_id)
+        .bind(tenant_id.as_uuid())
+        .bind(user_id.as_uuid())
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Open shortfall not found"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Tenant stock policy (defaults to RecordShortfall when not configured)
+pub(crate) async fn fetch_stock_policy(
+    pool: &PgPool,
+    tenant_id: TenantId,
+) -> AppResult<StockPolicy> {
+    let policy: Option<String> = sqlx::query_scalar(
+        "SELECT stock_policy FROM tenant_inventory_settings WHERE tenant_id = $1",
+    )
+    .bind(tenant_id.as_uuid())
+    .fetch_optional(pool)
+    .await?;
+
+    match policy {
+        Some(value) => StockPolicy::parse(&value),
+        None => Ok(StockPolicy::default()),
+    }
+}
+
+/// Fail with NotFound unless the location belongs to the tenant and is active
+pub(crate) async fn ensure_active_location(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    tenant_id: TenantId,
+    location: StorageLocationId,
+) -> AppResult<()> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM storage_locations WHERE id = $1 AND tenant_id = $2 AND is_active)",
+    )
+    .bind(location.as_uuid())
+    .bind(tenant_id.as_uuid())
+    .fetch_one(&mut **tx)
+    .await?;
+
+    if !exists {
+        return Err(AppError::not_found("Storage location not found"));
+    }
+    Ok(())
+}
+
+/// `reference_type` of the ADJUSTMENT movements that offset an undone
+/// deduction; they share the `reference_id` of the movements they reverse
+pub(crate) const UNDO_REFERENCE_TYPE: &str = "copilot_undo";
+
+/// Put back the stock taken out (sale or waste movements) under
+/// `reference_id`, batch by batch; exhausted batches become active again.
+/// Each movement is offset by an ADJUSTMENT with [`UNDO_REFERENCE_TYPE`] and
+/// the same reference, so the ledger keeps both and loss and usage reports
+/// skip the reversed pair.
+pub(crate) async fn restore_movements(
+    repo: &InventoryBatchRepository,
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    tenant_id: TenantId,
+    reference_id: uuid::Uuid,
+) -> AppResult<Decimal> {
+    // Lock first, then look for a reversal in a fresh statement: a
+    // concurrent undo of the same reference waits here and sees ours
+    let rows = sqlx::query(
+        r#"
+        SELECT batch_id, type, quantity, unit_cost_cents
+        FROM inventory_movements
+        WHERE tenant_id = $1 AND reference_id = $2 AND type IN ('OUT_SALE', 'OUT_WASTE')
+        ORDER BY batch_id
+        FOR UPDATE
+        "#,
+    )
+    .bind(tenant_id.as_uuid())
+    .bind(reference_id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    if rows.is_empty() {
+        return Err(AppError::not_found("No stock movements to restore"));
+    }
+
+    let reversed: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM inventory_movements
+            WHERE tenant_id = $1 AND reference_id = $2 AND reference_type = $3
+        )
+        "#,
+    )
+    .bind(tenant_id.as_uuid())
+    .bind(reference_id)
+    .bind(UNDO_REFERENCE_TYPE)
+    .fetch_one(&mut **tx)
+    .await?;
+    if reversed {
+        return Err(AppError::conflict(
+            "These stock movements were already restored",
+        ));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let mut restored = Decimal::ZERO;
+    for row in rows {
+        let batch_id = InventoryBatchId::from_uuid(row.try_get("batch_id")?);
+        let original_type: String = row.try_get("type")?;
+        let quantity: Decimal = row.try_get("quantity")?;
+
+        let mut batch = repo
+            .find_by_id_for_update(tx, batch_id, tenant_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::conflict("An inventory batch was deleted since; stock cannot be restored")
+            })?;
+
+        batch.remaining_quantity =
+            Quantity::from_decimal(batch.remaining_quantity.decimal() + quantity)?;
+        if batch.status == BatchStatus::Exhausted {
+            batch.status = BatchStatus::Active;
+        }
+        batch.updated_at = now;
+        repo.update_in_transaction(tx, &batch).await?;
+
+        let mut movement = InventoryMovement::new(
+            tenant_id,
+            batch_id,
+            MovementType::Adjustment,
+            quantity,
+            row.try_get("unit_cost_cents")?,
+        );
+        movement.reference_id = Some(reference_id);
+        movement.reference_type = Some(UNDO_REFERENCE_TYPE.to_string());
+        movement.reason = Some("Undo".to_string());
+        movement.notes = Some(format!("Reverses {}", original_type));
+        repo.record_movement(tx, &movement).await?;
+
+        restored += quantity;
+    }
+
+    Ok(restored)
 }
 
 /// Rich inventory view DTO (returned from query with JOINs)
@@ -851,6 +1371,8 @@ pub struct InventoryView {
     pub price_per_unit_cents: i64,
     /// Expiration severity (for row highlighting on frontend)
     pub severity: ExpirationSeverity,
+    /// Storage location holding the batch
+    pub location_id: Option<uuid::Uuid>,
     #[serde(with = "time::serde::rfc3339")]
     pub received_at: OffsetDateTime,
     #[serde(with = "time::serde::rfc3339")]
@@ -880,12 +1402,51 @@ pub struct LossReportItem {
     pub loss_value_cents: i64,
 }
 
+/// Loss of one reason (waste reason code or `auto_expiry`)
+#[derive(Debug, Clone, Serialize)]
+pub struct LossByReason {
+    pub reason: String,
+    pub label: String,
+    pub entries: i64,
+    pub loss_value_cents: i64,
+}
+
+/// Loss of one timeline bucket
+#[derive(Debug, Clone, Serialize)]
+pub struct LossPeriod {
+    #[serde(with = "time::serde::rfc3339")]
+    pub period_start: OffsetDateTime,
+    pub loss_value_cents: i64,
+}
+
 /// Loss report summary
 #[derive(Debug, Clone, Serialize)]
 pub struct LossReport {
+    /// Per ingredient
     pub items: Vec<LossReportItem>,
+    pub by_reason: Vec<LossByReason>,
+    pub bucket: LossBucket,
+    pub timeline: Vec<LossPeriod>,
     pub total_loss_cents: i64,
     pub total_purchased_cents: i64,
     pub waste_percentage: f64,
     pub period_days: i32,
 }
+
+/// Shortfall ledger entry DTO (quantity sold without stock on hand)
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortfallView {
+    pub id: uuid::Uuid,
+    pub ingredient_id: uuid::Uuid,
+    pub ingredient_name: String,
+    pub quantity: f64,
+    pub unit_cost_cents: i64,
+    pub estimated_cost_cents: i64,
+    pub reference_id: Option<uuid::Uuid>,
+    pub reference_type: Option<String>,
+    pub notes: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub resolved_at: Option<OffsetDateTime>,
+}
@@ -26,11 +26,13 @@ impl InventoryAlertService {
         let mut has_expiring_soon = false;
         let mut has_low_stock = false;
         let mut has_zero_stock = false;
+        let mut has_shortfall = false;
 
         let mut expired_count = 0;
         let mut critical_count = 0;
         let mut warning_count = 0;
         let mut low_stock_count = 0;
+        let mut shortfall_count = 0;
 
         for a in &alerts {
             match a.alert_type {
@@ -59,6 +61,12 @@ impl InventoryAlertService {
                     }
                     _ => {}
                 },
+                InventoryAlertType::Shortfall => {
+                    // Sold without stock — books are already wrong
+                    has_shortfall = true;
+                    critical_count += 1;
+                    shortfall_count += 1;
+                }
             }
         }
 
@@ -79,6 +87,9 @@ impl InventoryAlertService {
         if has_zero_stock {
             score -= 25;
         }
+        if has_shortfall {
+            score -= 20;
+        }
 
         let health_score = score.max(0);
 
@@ -100,6 +111,7 @@ impl InventoryAlertService {
             warning: warning_count,
             expired: expired_count,
             low_stock: low_stock_count,
+            shortfall: shortfall_count,
             badge_count,
         })
     }
@@ -116,6 +128,10 @@ impl InventoryAlertService {
         let low_stock_alerts = self.get_low_stock_alerts(tenant_id).await?;
         alerts.extend(low_stock_alerts);
 
+        // 3. Get Shortfall Alerts (unbacked sales)
+        let shortfall_alerts = self.get_shortfall_alerts(tenant_id).await?;
+        alerts.extend(shortfall_alerts);
+
         // Sort by severity (Expired -> Critical -> Warning -> Info)
         alerts.sort_by(|a, b| {
             let severity_score = |s: AlertSeverity| match s {
@@ -224,15 +240,19 @@ impl InventoryAlertService {
             SELECT 
                 ci.id as ingredient_id,
                 ci.name_en as ingredient_name,
-                ci.min_stock_threshold,
+                -- Tenant reorder point overrides the catalog threshold
+                COALESCE(ti.reorder_point, ci.min_stock_threshold) as min_stock_threshold,
                 COALESCE(SUM(ib.remaining_quantity), 0) as total_remaining
             FROM catalog_ingredients ci
             LEFT JOIN inventory_batches ib ON ci.id = ib.catalog_ingredient_id 
                 AND ib.tenant_id = $1 
                 AND ib.status = 'active'
+            LEFT JOIN tenant_ingredients ti ON ti.catalog_ingredient_id = ci.id
+                AND ti.tenant_id = $1
+                AND ti.is_active = true
             WHERE ci.is_active = true
-            GROUP BY ci.id, ci.name_en, ci.min_stock_threshold
-            HAVING COALESCE(SUM(ib.remaining_quantity), 0) <= ci.min_stock_threshold
+            GROUP BY ci.id, ci.name_en, ci.min_stock_threshold, ti.reorder_point
+            HAVING COALESCE(SUM(ib.remaining_quantity), 0) <= COALESCE(ti.reorder_point, ci.min_stock_threshold)
                OR COALESCE(SUM(ib.remaining_quantity), 0) = 0
         "#;
 
@@ -284,4 +304,50 @@ impl InventoryAlertService {
 
         Ok(alerts)
     }
+
+    /// Fetch ingredients with open shortfall entries (sold without stock)
+    async fn get_shortfall_alerts(&self, tenant_id: TenantId) -> AppResult<Vec<InventoryAlert>> {
+        let query = r#"
+            SELECT
+                ci.id as ingredient_id,
+                ci.name_en as ingredient_name,
+                SUM(s.quantity) as missing_quantity,
+                COUNT(*) as entries
+            FROM inventory_shortfalls s
+            JOIN catalog_ingredients ci ON ci.id = s.catalog_ingredient_id
+            WHERE s.tenant_id = $1 AND s.resolved_at IS NULL
+            GROUP BY ci.id, ci.name_en
+            ORDER BY missing_quantity DESC
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(tenant_id.as_uuid())
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut alerts = Vec::new();
+        for row in rows {
+            let missing = row.try_get::<Decimal, _>("missing_quantity")?;
+            let entries: i64 = row.try_get("entries")?;
+            let ingredient_name: String = row.try_get("ingredient_name")?;
+
+            alerts.push(InventoryAlert {
+                alert_type: InventoryAlertType::Shortfall,
+                severity: AlertSeverity::Critical,
+                ingredient_id: row.try_get("ingredient_id")?,
+                message: format!(
+                    "{} was sold without stock: {} missing ({} unbacked sales)",
+                    ingredient_name,
+                    missing.round_dp(2),
+                    entries
+                ),
+                ingredient_name,
+                batch_id: None,
+                current_value: -missing.to_f64().unwrap_or(0.0),
+                threshold_value: None,
+            });
+        }
+
+        Ok(alerts)
+    }
 }
@@ -0,0 +1,606 @@
+//! Menus, ordered menu sections and the dishes listed in them.
+//!
+//! A dish can appear in several sections (the same burger on the lunch and
+//! the bar menu). Sales may name the section they were rung up from; sales
+//! without one count toward the dish's first section (menu order, then
+//! section order) in menu engineering.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::{HashMap, HashSet};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::menu::{AvailabilityWindow, Menu, MenuId, MenuSection, MenuSectionId};
+use crate::shared::{AppError, AppResult, TenantId};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateMenuInput {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub sort_order: i32,
+    #[serde(default)]
+    pub availability: AvailabilityWindow,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateMenuInput {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub sort_order: Option<i32>,
+    pub is_active: Option<bool>,
+    pub availability: Option<AvailabilityWindow>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateMenuSectionInput {
+    pub name: String,
+    /// Defaults to after the last section
+    pub sort_order: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateMenuSectionInput {
+    pub name: Option<String>,
+    pub sort_order: Option<i32>,
+}
+
+/// Replace the dishes of a section; list order becomes display order
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetSectionDishesInput {
+    pub dish_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionDishView {
+    pub dish_id: Uuid,
+    pub name: String,
+    pub selling_price_cents: i32,
+    pub active: bool,
+    pub sort_order: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuSectionView {
+    #[serde(flatten)]
+    pub section: MenuSection,
+    pub dishes: Vec<SectionDishView>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuView {
+    #[serde(flatten)]
+    pub menu: Menu,
+    /// Active and inside its availability window right now (UTC)
+    pub available_now: bool,
+    pub sections: Vec<MenuSectionView>,
+}
+
+#[derive(Clone)]
+pub struct MenuService {
+    pool: PgPool,
+}
+
+impl MenuService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_menus(
+        &self,
+        tenant_id: TenantId,
+        include_inactive: bool,
+    ) -> AppResult<Vec<MenuView>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, tenant_id, name, description, sort_order, is_active,
+                   available_days, available_from, available_until, created_at, updated_at
+            FROM menus
+            WHERE tenant_id = $1 AND ($2 OR is_active)
+            ORDER BY sort_order, name
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(include_inactive)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let menus = rows
+            .iter()
+            .map(row_to_menu)
+            .collect::<AppResult<Vec<_>>>()?;
+        self.with_sections(tenant_id, menus).await
+    }
+
+    pub async fn get_menu(&self, tenant_id: TenantId, id: MenuId) -> AppResult<MenuView> {
+        let menu = self.find_menu(tenant_id, id).await?;
+        let mut views = self.with_sections(tenant_id, vec![menu]).await?;
+        Ok(views.remove(0))
+    }
+
+    pub async fn create_menu(
+        &self,
+        tenant_id: TenantId,
+        input: CreateMenuInput,
+    ) -> AppResult<MenuView> {
+        let mut menu = Menu::new(tenant_id, input.name, input.availability)?;
+        menu.description = clean_description(input.description);
+        menu.sort_order = input.sort_order;
+
+        self.ensure_unique_menu_name(tenant_id, &menu.name, None)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO menus (
+                id, tenant_id, name, description, sort_order, is_active,
+                available_days, available_from, available_until, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+        )
+        .bind(menu.id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .bind(&menu.name)
+        .bind(&menu.description)
+        .bind(menu.sort_order)
+        .bind(menu.is_active)
+        .bind(days_to_db(&menu.availability))
+        .bind(menu.availability.from)
+        .bind(menu.availability.until)
+        .bind(menu.created_at)
+        .bind(menu.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!("Menu created: {} ({})", menu.name, menu.id.as_uuid());
+        Ok(MenuView {
+            available_now: is_available_now(&menu),
+            menu,
+            sections: Vec::new(),
+        })
+    }
+
+    pub async fn update_menu(
+        &self,
+        tenant_id: TenantId,
+        id: MenuId,
+        input: UpdateMenuInput,
+    ) -> AppResult<MenuView> {
+        let mut menu = self.find_menu(tenant_id, id).await?;
+
+        if let Some(name) = input.name {
+            menu.rename(name)?;
+            self.ensure_unique_menu_name(tenant_id, &menu.name, Some(id))
+                .await?;
+        }
+        if input.description.is_some() {
+            menu.description = clean_description(input.description);
+        }
+        if let Some(sort_order) = input.sort_order {
+            menu.sort_order = sort_order;
+        }
+        if let Some(is_active) = input.is_active {
+            menu.is_active = is_active;
+        }
+        if let Some(availability) = input.availability {
+            availability.validate()?;
+            menu.availability = availability;
+        }
+        menu.updated_at = OffsetDateTime::now_utc();
+
+        sqlx::query(
+            r#"
+            UPDATE menus
+            SET name = $3, description = $4, sort_order = $5, is_active = $6,
+                available_days = $7, available_from = $8, available_until = $9,
+                updated_at = $10
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+        )
+        .bind(id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .bind(&menu.name)
+        .bind(&menu.description)
+        .bind(menu.sort_order)
+        .bind(menu.is_active)
+        .bind(days_to_db(&menu.availability))
+        .bind(menu.availability.from)
+        .bind(menu.availability.until)
+        .bind(menu.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        self.get_menu(tenant_id, id).await
+    }
+
+    /// Delete a menu with its sections. Past sales keep their dish, the
+    /// section reference is cleared.
+    pub async fn delete_menu(&self, tenant_id: TenantId, id: MenuId) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM menus WHERE id = $1 AND tenant_id = $2")
+            .bind(id.as_uuid())
+            .bind(tenant_id.as_uuid())
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Menu not found"));
+        }
+        Ok(())
+    }
+
+    pub async fn create_section(
+        &self,
+        tenant_id: TenantId,
+        menu_id: MenuId,
+        input: CreateMenuSectionInput,
+    ) -> AppResult<MenuSection> {
+        self.find_menu(tenant_id, menu_id).await?;
+
+        let sort_order = match input.sort_order {
+            Some(sort_order) => sort_order,
+            None => {
+                let last: Option<i32> = sqlx::query_scalar(
+                    "SELECT MAX(sort_order) FROM menu_sections WHERE menu_id = $1",
+                )
+                .bind(menu_id.as_uuid())
+                .fetch_one(&self.pool)
+                .await?;
+                last.map_or(0, |last| last + 1)
+            }
+        };
+        let section = MenuSection::new(menu_id, input.name, sort_order)?;
+        self.ensure_unique_section_name(menu_id, &section.name, None)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO menu_sections (id, tenant_id, menu_id, name, sort_order)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(section.id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .bind(menu_id.as_uuid())
+        .bind(&section.name)
+        .bind(section.sort_order)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(section)
+    }
+
+    pub async fn update_section(
+        &self,
+        tenant_id: TenantId,
+        menu_id: MenuId,
+        section_id: MenuSectionId,
+        input: UpdateMenuSectionInput,
+    ) -> AppResult<MenuSection> {
+        let mut section = self.find_section(tenant_id, menu_id, section_id).await?;
+
+        if let Some(name) = input.name {
+            section.rename(name)?;
+            self.ensure_unique_section_name(menu_id, &section.name, Some(section_id))
+                .await?;
+        }
+        if let Some(sort_order) = input.sort_order {
+            section.sort_order = sort_order;
+        }
+
+        sqlx::query("UPDATE menu_sections SET name = $2, sort_order = $3 WHERE id = $1")
+            .bind(section_id.as_uuid())
+            .bind(&section.name)
+            .bind(section.sort_order)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(section)
+    }
+
+    pub async fn delete_section(
+        &self,
+        tenant_id: TenantId,
+        menu_id: MenuId,
+        section_id: MenuSectionId,
+    ) -> AppResult<()> {
+        self.find_section(tenant_id, menu_id, section_id).await?;
+
+        sqlx::query("DELETE FROM menu_sections WHERE id = $1")
+            .bind(section_id.as_uuid())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Replace the dishes listed in a section (order = display order)
+    pub async fn set_section_dishes(
+        &self,
+        tenant_id: TenantId,
+        menu_id: MenuId,
+        section_id: MenuSectionId,
+        input: SetSectionDishesInput,
+    ) -> AppResult<MenuSectionView> {
+        let section = self.find_section(tenant_id, menu_id, section_id).await?;
+
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = input.dish_ids.iter().find(|id| !seen.insert(**id)) {
+            return Err(AppError::validation(format!(
+                "Dish {} is listed twice in the section",
+                duplicate
+            )));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let known: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM dishes WHERE tenant_id = $1 AND id = ANY($2)")
+                .bind(tenant_id.as_uuid())
+                .bind(&input.dish_ids)
+                .fetch_one(&mut *tx)
+                .await?;
+        if known != input.dish_ids.len() as i64 {
+            return Err(AppError::not_found("One or more dishes not found"));
+        }
+
+        sqlx::query("DELETE FROM menu_section_dishes WHERE section_id = $1")
+            .bind(section_id.as_uuid())
+            .execute(&mut *tx)
+            .await?;
+
+        for (position, dish_id) in input.dish_ids.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO menu_section_dishes (section_id, dish_id, tenant_id, sort_order)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(section_id.as_uuid())
+            .bind(dish_id)
+            .bind(tenant_id.as_uuid())
+            .bind(position as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        let mut dishes = self
+            .section_dishes(tenant_id, &[section_id.as_uuid()])
+            .await?;
+        Ok(MenuSectionView {
+            section,
+            dishes: dishes.remove(&section_id.as_uuid()).unwrap_or_default(),
+        })
+    }
+
+    async fn find_menu(&self, tenant_id: TenantId, id: MenuId) -> AppResult<Menu> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, tenant_id, name, description, sort_order, is_active,
+                   available_days, available_from, available_until, created_at, updated_at
+            FROM menus
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+        )
+        .bind(id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Menu not found"))?;
+
+        row_to_menu(&row)
+    }
+
+    async fn find_section(
+        &self,
+        tenant_id: TenantId,
+        menu_id: MenuId,
+        section_id: MenuSectionId,
+    ) -> AppResult<MenuSection> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, menu_id, name, sort_order
+            FROM menu_sections
+            WHERE id = $1 AND menu_id = $2 AND tenant_id = $3
+            "#,
+        )
+        .bind(section_id.as_uuid())
+        .bind(menu_id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Menu section not found"))?;
+
+        row_to_section(&row)
+    }
+
+    /// Attach sections and their dishes to menus (keeps menu order)
+    async fn with_sections(
+        &self,
+        tenant_id: TenantId,
+        menus: Vec<Menu>,
+    ) -> AppResult<Vec<MenuView>> {
+        let menu_ids: Vec<Uuid> = menus.iter().map(|m| m.id.as_uuid()).collect();
+        let rows = sqlx::query(
+            r#"
+            SELECT id, menu_id, name, sort_order
+            FROM menu_sections
+            WHERE tenant_id = $1 AND menu_id = ANY($2)
+            ORDER BY sort_order, name
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(&menu_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let sections = rows
+            .iter()
+            .map(row_to_section)
+            .collect::<AppResult<Vec<_>>>()?;
+        let section_ids: Vec<Uuid> = sections.iter().map(|s| s.id.as_uuid()).collect();
+        let mut dishes = self.section_dishes(tenant_id, &section_ids).await?;
+
+        let mut by_menu: HashMap<Uuid, Vec<MenuSectionView>> = HashMap::new();
+        for section in sections {
+            let section_dishes = dishes.remove(&section.id.as_uuid()).unwrap_or_default();
+            by_menu
+                .entry(section.menu_id.as_uuid())
+                .or_default()
+                .push(MenuSectionView {
+                    section,
+                    dishes: section_dishes,
+                });
+        }
+
+        Ok(menus
+            .into_iter()
+            .map(|menu| MenuView {
+                available_now: is_available_now(&menu),
+                sections: by_menu.remove(&menu.id.as_uuid()).unwrap_or_default(),
+                menu,
+            })
+            .collect())
+    }
+
+    async fn section_dishes(
+        &self,
+        tenant_id: TenantId,
+        section_ids: &[Uuid],
+    ) -> AppResult<HashMap<Uuid, Vec<SectionDishView>>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT sd.section_id, sd.dish_id, sd.sort_order,
+                   d.name, d.selling_price_cents, d.active
+            FROM menu_section_dishes sd
+            JOIN dishes d ON d.id = sd.dish_id
+            WHERE sd.tenant_id = $1 AND sd.section_id = ANY($2)
+            ORDER BY sd.sort_order, d.name
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(section_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut map: HashMap<Uuid, Vec<SectionDishView>> = HashMap::new();
+        for row in rows {
+            map.entry(row.try_get("section_id")?)
+                .or_default()
+                .push(SectionDishView {
+                    dish_id: row.try_get("dish_id")?,
+                    name: row.try_get("name")?,
+                    selling_price_cents: row.try_get("selling_price_cents")?,
+                    active: row.try_get("active")?,
+                    sort_order: row.try_get("sort_order")?,
+                });
+        }
+        Ok(map)
+    }
+
+    async fn ensure_unique_menu_name(
+        &self,
+        tenant_id: TenantId,
+        name: &str,
+        exclude: Option<MenuId>,
+    ) -> AppResult<()> {
+        let taken: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM menus
+                WHERE tenant_id = $1 AND LOWER(name) = LOWER($2)
+                  AND ($3::UUID IS NULL OR id <> $3)
+            )
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(name)
+        .bind(exclude.map(|id| id.as_uuid()))
+        .fetch_one(&self.pool)
+        .await?;
+
+        if taken {
+            return Err(AppError::conflict(format!(
+                "Menu '{}' already exists",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    async fn ensure_unique_section_name(
+        &self,
+        menu_id: MenuId,
+        name: &str,
+        exclude: Option<MenuSectionId>,
+    ) -> AppResult<()> {
+        let taken: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM menu_sections
+                WHERE menu_id = $1 AND LOWER(name) = LOWER($2)
+                  AND ($3::UUID IS NULL OR id <> $3)
+            )
+            "#,
+        )
+        .bind(menu_id.as_uuid())
+        .bind(name)
+        .bind(exclude.map(|id| id.as_uuid()))
+        .fetch_one(&self.pool)
+        .await?;
+
+        if taken {
+            return Err(AppError::conflict(format!(
+                "Section '{}' already exists in this menu",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn is_available_now(menu: &Menu) -> bool {
+    menu.is_active && menu.availability.covers_datetime(OffsetDateTime::now_utc())
+}
+
+fn clean_description(description: Option<String>) -> Option<String> {
+    description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+}
+
+fn days_to_db(availability: &AvailabilityWindow) -> Vec<i16> {
+    let mut days: Vec<i16> = availability.days.iter().map(|d| *d as i16).collect();
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+fn row_to_menu(row: &sqlx::postgres::PgRow) -> AppResult<Menu> {
+    let days: Vec<i16> = row.try_get("available_days")?;
+    Ok(Menu {
+        id: MenuId::from_uuid(row.try_get("id")?),
+        tenant_id: TenantId::from_uuid(row.try_get("tenant_id")?),
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        sort_order: row.try_get("sort_order")?,
+        is_active: row.try_get("is_active")?,
+        availability: AvailabilityWindow {
+            days: days.into_iter().map(|d| d as u8).collect(),
+            from: row.try_get("available_from")?,
+            until: row.try_get("available_until")?,
+        },
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn row_to_section(row: &sqlx::postgres::PgRow) -> AppResult<MenuSection> {
+    Ok(MenuSection {
+        id: MenuSectionId::from_uuid(row.try_get("id")?),
+        menu_id: MenuId::from_uuid(row.try_get("menu_id")?),
+        name: row.try_get("name")?,
+        sort_order: row.try_get("sort_order")?,
+    })
+}
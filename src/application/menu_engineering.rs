@@ -1,17 +1,31 @@
-use std::collections::HashMap;
-
+use crate::application::audit::AuditLog;
+use crate::application::inventory::{ensure_active_location, fetch_stock_policy};
+use crate::application::modifier::fetch_modifier_groups;
+use crate::application::recipe::{load_component_graph, RecipeService};
 use crate::domain::{
-    inventory::{BatchStatus, InventoryMovement, MovementType, Quantity},
-    DishId, DishPerformance, MenuEngineeringMatrix,
+    inventory::{
+        BatchStatus, InventoryMovement, InventoryShortfall, MovementType, Quantity, StockPolicy,
+    },
+    menu::{MenuId, MenuSectionId},
+    modifier::{
+        apply_modifiers, cost_delta_cents, recorded_options, resolve_selection, IngredientDelta,
+        ModifierOptionId, ModifierPerformance,
+    },
+    recipe::{flatten_ingredients, portion_ingredients},
+    storage_location::StorageLocationId,
+    CatalogIngredientId, DishId, DishPerformance, MenuEngineeringMatrix,
 };
 use crate::infrastructure::persistence::{
-    DishRepositoryTrait, InventoryBatchRepositoryTrait, RecipeRepositoryTrait,
+    CatalogIngredientRepositoryTrait, DishRepositoryTrait, InventoryBatchRepositoryTrait,
+    RecipeRepositoryTrait,
 };
 use crate::shared::{AppError, AppResult, Language, TenantId, UserId};
 
 use rust_decimal::Decimal;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 /// Service for Menu Engineering analysis
@@ -22,6 +36,7 @@ pub struct MenuEngineeringService {
     inventory_repo: Arc<dyn InventoryBatchRepositoryTrait>,
     dish_repo: Arc<dyn DishRepositoryTrait>,
     recipe_repo: Arc<dyn RecipeRepositoryTrait>,
+    catalog_repo: Arc<dyn CatalogIngredientRepositoryTrait>,
 }
 
 impl MenuEngineeringService {
@@ -30,67 +45,71 @@ impl MenuEngineeringService {
         inventory_repo: Arc<dyn InventoryBatchRepositoryTrait>,
         dish_repo: Arc<dyn DishRepositoryTrait>,
         recipe_repo: Arc<dyn RecipeRepositoryTrait>,
+        catalog_repo: Arc<dyn CatalogIngredientRepositoryTrait>,
     ) -> Self {
         Self {
             pool,
             inventory_repo,
             dish_repo,
             recipe_repo,
+            catalog_repo,
         }
     }
 
     /// Analyze menu performance for a tenant
     ///
-    /// Returns MenuEngineeringMatrix with all dishes classified by profitability and popularity
+    /// Returns MenuEngineeringMatrix with every dish classified against the
+    /// other dishes of its menu section (Kasavana–Smith). Dishes listed on an
+    /// active menu are included even without sales; dishes on no menu are
+    /// analysed together as one group. `menu_id` limits the analysis to the
+    /// sections of one menu.
     pub async fn analyze_menu(
         &self,
         _user_id: UserId,
         tenant_id: TenantId,
         language: Language,
         period_days: u32,
+        menu_id: Option<MenuId>,
     ) -> AppResult<MenuEngineeringMatrix> {
-        // 1. Get all dishes with their financials and sales data
-        let sales_data = self.fetch_sales_data(tenant_id, period_days).await?;
+        // 1. Sales per dish and section, plus unsold dishes listed on menus
+        let sales_data = self
+            .fetch_sales_data(tenant_id, period_days, menu_id)
+            .await?;
 
         if sales_data.is_empty() {
-            return Ok(MenuEngineeringMatrix::analyze(vec![]));
+            return Ok(MenuEngineeringMatrix::analyze(vec![], language));
         }
 
-        // 2. Calculate popularity scores (normalize by max sales volume)
-        let max_volume = sales_data
-            .values()
-            .map(|d| d.sales_volume)
-            .max()
-            .unwrap_or(1) as f64;
-
-        // 3. Build DishPerformance for each dish
+        // 2. Build DishPerformance for each dish (popularity is the menu-mix
+        //    share within the section, filled in by the matrix)
         let mut performances: Vec<DishPerformance> = sales_data
             .into_iter()
-            .map(|(dish_id, data)| {
-                let popularity_score = if max_volume > 0.0 {
-                    data.sales_volume as f64 / max_volume
-                } else {
-                    0.0
-                };
-
-                DishPerformance::new(
-                    dish_id,
+            .map(|data| {
+                let mut perf = DishPerformance::new(
+                    data.dish_id,
                     data.dish_name,
-                    data.avg_profit_margin_percent,
-                    popularity_score,
+                    data.profit_margin_percent,
+                    0.0,
                     data.sales_volume,
                     data.total_revenue_cents,
                     data.total_profit_cents,
                     0.0, // cumulative_revenue_share placeholder (calculated below)
                     language,
-                )
+                );
+                perf.contribution_margin_per_item_cents = data.margin_per_item_cents;
+                match data.section {
+                    Some((section_id, section_name, menu_name)) => {
+                        perf.in_section(section_id, section_name, menu_name)
+                    }
+                    None => perf,
+                }
             })
             .collect();
 
-        // 4. Sort by revenue descending (for ABC analysis)
+        // 3. Sort by revenue descending (for ABC analysis)
         performances.sort_by(|a, b| b.total_revenue_cents.cmp(&a.total_revenue_cents));
 
-        // 5. Calculate cumulative revenue share for ABC classification
+        // 4. Calculate cumulative revenue share for ABC classification
         let total_revenue: i64 = performances.iter().map(|p| p.total_revenue_cents).sum();
         let mut cumulative_revenue: i64 = 0;
 
@@ -101,95 +120,268 @@ impl MenuEngineeringService {
             } else {
                 0.0
             };
-            // Recalculate ABC class with correct cumulative share
-            perf.abc_class = crate::domain::AbcClass::classify(perf.cumulative_revenue_share);
         }
 
-        // 6. Sort by contribution margin (profit × volume) descending for display
+        // 5. Sort by contribution margin (profit × volume) descending for display
         performances.sort_by(|a, b| {
             b.contribution_margin_cents
                 .cmp(&a.contribution_margin_cents)
         });
 
-        // 7. Return matrix
-        Ok(MenuEngineeringMatrix::analyze(performances))
+        // 6. Classify within sections and return matrix
+        Ok(MenuEngineeringMatrix::analyze(performances, language))
     }
 
-    /// Fetch aggregated sales data for all dishes in tenant
-    async fn fetch_sales_data(
+    /// Attach rate and margin of every modifier option sold in the period.
+    ///
+    /// Options are grouped by their sale-time snapshot, so deleted options
+    /// still report. Sorted by dish, then attach rate descending.
+    pub async fn analyze_modifiers(
         &self,
         tenant_id: TenantId,
         period_days: u32,
-    ) -> AppResult<HashMap<DishId, AggregatedDishData>> {
-        let tenant_uuid = *tenant_id.as_uuid();
-        let period_days_str = period_days.to_string();
+    ) -> AppResult<Vec<ModifierPerformance>> {
+        use sqlx::Row;
 
-        // 🎯 FIX: Use manual query instead of query! macro to avoid DB-at-build-time issues
         let rows = sqlx::query(
             r#"
+            WITH sales AS (
+                SELECT id, dish_id, quantity
+                FROM dish_sales
+                WHERE tenant_id = $1
+                  AND sold_at >= NOW() - ($2 * INTERVAL '1 day')
+            ),
+            dish_units AS (
+                SELECT dish_id, SUM(quantity)::BIGINT AS units
+                FROM sales
+                GROUP BY dish_id
+            )
             SELECT
-                ds.dish_id,
-                d.name as dish_name,
-                COUNT(*) as sales_count,
-                SUM(ds.quantity) as total_quantity,
-                SUM(ds.selling_price_cents * ds.quantity) as total_revenue_cents,
-                SUM(ds.profit_cents * ds.quantity) as total_profit_cents,
-                AVG(
-                    CAST(ds.profit_cents AS FLOAT) / NULLIF(ds.selling_price_cents, 0) * 100.0
-                ) as avg_profit_margin_percent
-            FROM dish_sales ds
-            JOIN dishes d ON d.id = ds.dish_id AND d.tenant_id = ds.tenant_id
-            WHERE
-                ds.tenant_id = $1
-                AND ds.sold_at >= NOW() - ($2 || ' days')::INTERVAL
-            GROUP BY ds.dish_id, d.name
+                s.dish_id,
+                d.name AS dish_name,
+                m.group_name,
+                m.option_id,
+                m.option_name,
+                du.units AS dish_units_sold,
+                SUM(m.quantity)::BIGINT AS attached_units,
+                SUM(m.price_delta_cents * m.quantity)::BIGINT AS revenue_cents,
+                SUM(m.cost_delta_cents * m.quantity)::BIGINT AS cost_cents
+            FROM dish_sale_modifiers m
+            JOIN sales s ON s.id = m.sale_id
+            JOIN dish_units du ON du.dish_id = s.dish_id
+            JOIN dishes d ON d.id = s.dish_id
+            WHERE m.tenant_id = $1
+            GROUP BY s.dish_id, d.name, m.group_name, m.option_id, m.option_name, du.units
             "#,
         )
-        .bind(tenant_uuid)
-        .bind(period_days_str)
+        .bind(tenant_id.as_uuid())
+        .bind(period_days as i32)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut map = HashMap::new();
+        let mut report = rows
+            .iter()
+            .map(|row| {
+                Ok(ModifierPerformance::new(
+                    DishId::from_uuid(row.try_get("dish_id")?),
+                    row.try_get("dish_name")?,
+                    row.try_get("group_name")?,
+                    row.try_get::<Option<Uuid>, _>("option_id")?
+                        .map(ModifierOptionId::from_uuid),
+                    row.try_get("option_name")?,
+                    row.try_get("dish_units_sold")?,
+                    row.try_get("attached_units")?,
+                    row.try_get("revenue_cents")?,
+                    row.try_get("cost_cents")?,
+                ))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        report.sort_by(|a, b| {
+            a.dish_name
+                .cmp(&b.dish_name)
+                .then(b.attach_rate.total_cmp(&a.attach_rate))
+        });
+        Ok(report)
+    }
+
+    /// Fetch aggregated sales per (dish, menu section) for a tenant.
+    ///
+    /// Sales without a section count toward the dish's first section on an
+    /// active menu (menu order, then section order). Active dishes listed on
+    /// active menus without sales come back with zero volume and the margin
+    /// of their current price and recipe cost.
+    async fn fetch_sales_data(
+        &self,
+        tenant_id: TenantId,
+        period_days: u32,
+        menu_id: Option<MenuId>,
+    ) -> AppResult<Vec<AggregatedDishData>> {
+        let rows = sqlx::query(
+            r#"
+            WITH primary_section AS (
+                SELECT DISTINCT ON (sd.dish_id) sd.dish_id, sd.section_id
+                FROM menu_section_dishes sd
+                JOIN menu_sections s ON s.id = sd.section_id
+                JOIN menus m ON m.id = s.menu_id
+                WHERE sd.tenant_id = $1 AND m.is_active
+                ORDER BY sd.dish_id, m.sort_order, m.name, s.sort_order, s.name
+            ),
+            sales AS (
+                SELECT
+                    ds.dish_id,
+                    COALESCE(ds.menu_section_id, ps.section_id) AS section_id,
+                    SUM(ds.quantity)::BIGINT AS total_quantity,
+                    SUM(ds.selling_price_cents::BIGINT * ds.quantity)::BIGINT AS total_revenue_cents,
+                    -- profit_cents is stored per sale row (already × quantity)
+                    SUM(ds.profit_cents)::BIGINT AS total_profit_cents
+                FROM dish_sales ds
+                LEFT JOIN primary_section ps ON ps.dish_id = ds.dish_id
+                WHERE ds.tenant_id = $1
+                  AND ds.sold_at >= NOW() - ($2 * INTERVAL '1 day')
+                GROUP BY 1, 2
+            ),
+            listed AS (
+                SELECT sd.dish_id, sd.section_id
+                FROM menu_section_dishes sd
+                JOIN menu_sections s ON s.id = sd.section_id
+                JOIN menus m ON m.id = s.menu_id
+                JOIN dishes d ON d.id = sd.dish_id
+                WHERE sd.tenant_id = $1 AND m.is_active AND d.active
+            )
+            SELECT
+                d.id AS dish_id,
+                d.name AS dish_name,
+                s.id AS section_id,
+                s.name AS section_name,
+                m.name AS menu_name,
+                COALESCE(sa.total_quantity, 0) AS total_quantity,
+                COALESCE(sa.total_revenue_cents, 0) AS total_revenue_cents,
+                COALESCE(sa.total_profit_cents, 0) AS total_profit_cents,
+                d.selling_price_cents::BIGINT AS selling_price_cents,
+                COALESCE(d.recipe_cost_cents, 0)::BIGINT AS recipe_cost_cents
+            FROM sales sa
+            FULL JOIN listed l ON l.dish_id = sa.dish_id AND l.section_id = sa.section_id
+            JOIN dishes d ON d.id = COALESCE(sa.dish_id, l.dish_id) AND d.tenant_id = $1
+            LEFT JOIN menu_sections s ON s.id = COALESCE(sa.section_id, l.section_id)
+            LEFT JOIN menus m ON m.id = s.menu_id
+            WHERE $3::UUID IS NULL OR m.id = $3
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(period_days as i32)
+        .bind(menu_id.map(|id| id.as_uuid()))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut data = Vec::with_capacity(rows.len());
 
         for row in rows {
             use sqlx::Row;
-            let dish_id: Uuid = row
-                .try_get("dish_id")
-                .map_err(|e| AppError::internal(&format!("DB: {}", e)))?;
-            let dish_name: String = row
-                .try_get("dish_name")
-                .map_err(|e| AppError::internal(&format!("DB: {}", e)))?;
-            let total_quantity: i64 = row
-                .try_get("total_quantity")
-                .map_err(|e| AppError::internal(&format!("DB: {}", e)))?;
-            let total_revenue_cents: i64 = row
-                .try_get("total_revenue_cents")
-                .map_err(|e| AppError::internal(&format!("DB: {}", e)))?;
-            let total_profit_cents: i64 = row
-                .try_get("total_profit_cents")
-                .map_err(|e| AppError::internal(&format!("DB: {}", e)))?;
-            let avg_profit_margin_percent: f64 = row
-                .try_get("avg_profit_margin_percent")
-                .map_err(|e| AppError::internal(&format!("DB: {}", e)))?;
-
-            map.insert(
-                DishId::from_uuid(dish_id),
-                AggregatedDishData {
-                    dish_name,
-                    sales_volume: total_quantity as u32,
-                    total_revenue_cents,
-                    total_profit_cents,
-                    avg_profit_margin_percent,
-                },
-            );
+            let dish_id: Uuid = row.try_get("dish_id")?;
+            let total_quantity: i64 = row.try_get("total_quantity")?;
+            let total_revenue_cents: i64 = row.try_get("total_revenue_cents")?;
+            let total_profit_cents: i64 = row.try_get("total_profit_cents")?;
+            let selling_price_cents: i64 = row.try_get("selling_price_cents")?;
+            let recipe_cost_cents: i64 = row.try_get("recipe_cost_cents")?;
+
+            // Sold: realised figures. Unsold: current price − recipe cost
+            let (margin_per_item_cents, profit_margin_percent) = if total_quantity > 0 {
+                (
+                    total_profit_cents / total_quantity,
+                    percent(total_profit_cents, total_revenue_cents),
+                )
+            } else {
+                let margin = selling_price_cents - recipe_cost_cents;
+                (margin, percent(margin, selling_price_cents))
+            };
+
+            let section_id: Option<Uuid> = row.try_get("section_id")?;
+            let section = match section_id {
+                Some(section_id) => Some((
+                    section_id,
+                    row.try_get("section_name")?,
+                    row.try_get("menu_name")?,
+                )),
+                None => None,
+            };
+
+            data.push(AggregatedDishData {
+                dish_id: DishId::from_uuid(dish_id),
+                dish_name: row.try_get("dish_name")?,
+                section,
+                sales_volume: total_quantity as u32,
+                total_revenue_cents,
+                total_profit_cents,
+                margin_per_item_cents,
+                profit_margin_percent,
+            });
         }
 
-        Ok(map)
+        Ok(data)
+    }
+
+    /// As-purchased quantity of every raw ingredient in one portion of a dish:
+    /// component recipes expanded, trim loss (edible yield) added back, the
+    /// batch divided by the recipe's servings like its cost per serving
+    pub(crate) async fn raw_ingredients_per_portion(
+        &self,
+        tenant_id: TenantId,
+        dish_id: DishId,
+    ) -> AppResult<Vec<(CatalogIngredientId, Decimal)>> {
+        let dish = self
+            .dish_repo
+            .find_by_id(dish_id, tenant_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Dish not found"))?;
+
+        // Find the recipe (🔒 TENANT ISOLATION: use tenant_id)
+        let recipe = self
+            .recipe_repo
+            .find_by_id(dish.recipe_id, tenant_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Recipe not found"))?;
+
+        // Expand component recipes (preps) down to raw ingredients
+        let recipe_id = recipe.id();
+        let graph = load_component_graph(self.recipe_repo.as_ref(), recipe, tenant_id).await?;
+        let net_ingredients = flatten_ingredients(recipe_id, &graph)?;
+
+        // As-purchased weight: trim loss leaves the stock too
+        let ingredient_ids: Vec<_> = net_ingredients.iter().map(|(id, _)| *id).collect();
+        let yields = self
+            .catalog_repo
+            .effective_yield_percents(tenant_id, &ingredient_ids)
+            .await?;
+        let raw_ingredients = portion_ingredients(recipe_id, &graph, &yields)?;
+
+        tracing::info!(
+            "Found recipe {} with {} raw ingredients ({} recipes in component tree)",
+            recipe_id.as_uuid(),
+            raw_ingredients.len(),
+            graph.len()
+        );
+
+        Ok(raw_ingredients)
     }
 
     /// Record a dish sale (called after successful order/payment)
-    /// Also automatically deducts ingredients from inventory (FIFO)
+    /// Also automatically deducts ingredients from inventory (FIFO).
+    ///
+    /// The recipe cost is computed server-side at sale time from the dish's
+    /// recipe; it is never taken from the caller.
+    ///
+    /// `location_id` is the selling outlet: only its batches are consumed.
+    /// Without it the sale deducts from any location.
+    ///
+    /// `menu_section_id` is the section the dish was sold from; without it
+    /// menu engineering counts the sale toward the dish's first section.
+    ///
+    /// `modifier_option_ids` are the options chosen (size, add-ons,
+    /// removals); the portion is costed and deducted with them applied.
+    /// `selling_price_cents` is the price charged, modifiers included; zero
+    /// for a comped portion.
+    #[allow(clippy::too_many_arguments)]
     pub async fn record_sale(
         &self,
         tenant_id: TenantId,
@@ -197,14 +389,147 @@ impl MenuEngineeringService {
         user_id: UserId,
         quantity: u32,
         selling_price_cents: i32,
-        recipe_cost_cents: i32,
+        location_id: Option<StorageLocationId>,
+        menu_section_id: Option<MenuSectionId>,
+        modifier_option_ids: Vec<ModifierOptionId>,
+    ) -> AppResult<()> {
+        self.book_sale(
+            tenant_id,
+            user_id,
+            SaleRecord {
+                dish_id: DishId::from_uuid(dish_id),
+                quantity,
+                selling_price_cents,
+                location_id,
+                menu_section_id,
+                sold_at: None,
+                pos_ticket_id: None,
+                modifier_option_ids,
+            },
+        )
+        .await
+    }
+
+    /// Book one sale and deduct its ingredients.
+    ///
+    /// The sale row and every deduction share one transaction: either the
+    /// whole sale is booked or nothing is. Missing stock is handled by the
+    /// tenant's `StockPolicy`.
+    pub(crate) async fn book_sale(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        sale: SaleRecord,
     ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        self.book_sale_in(&mut tx, tenant_id, user_id, sale).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// [`Self::book_sale`] inside the caller's transaction, which commits
+    /// or rolls back the sale together with its own writes.
+    pub(crate) async fn book_sale_in(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        tenant_id: TenantId,
+        user_id: UserId,
+        sale: SaleRecord,
+    ) -> AppResult<()> {
+        let SaleRecord {
+            dish_id,
+            quantity,
+            selling_price_cents,
+            location_id,
+            menu_section_id,
+            sold_at,
+            pos_ticket_id,
+            modifier_option_ids,
+        } = sale;
+        if quantity == 0 {
+            return Err(AppError::validation("Quantity must be greater than 0"));
+        }
+        // Zero is a comped line: stock still leaves, nothing is charged
+        if selling_price_cents < 0 {
+            return Err(AppError::validation("Selling price cannot be negative"));
+        }
         let tenant_uuid = *tenant_id.as_uuid();
         let user_uuid = *user_id.as_uuid();
+
+        // 1. Cost the portion at today's prices (🔒 TENANT ISOLATION)
+        let dish = self
+            .dish_repo
+            .find_by_id(dish_id, tenant_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Dish not found"))?;
+        // An ingredient never purchased has no price; that must not lose the
+        // sale. Book it at the dish's last materialized cost (or zero) and
+        // flag the line as estimated.
+        let (cost_per_serving_cents, cost_estimated) = match RecipeService::new(
+            self.recipe_repo.clone(),
+            self.inventory_repo.clone(),
+            self.catalog_repo.clone(),
+            AuditLog::new(self.pool.clone()),
+        )
+        .calculate_cost(dish.recipe_id, tenant_id)
+        .await
+        {
+            Ok(recipe_cost) => (recipe_cost.cost_per_serving.as_cents(), false),
+            Err(AppError::NotFound(reason)) => {
+                tracing::warn!(
+                    "Dish {} sold without a current recipe cost: {}",
+                    dish_id.as_uuid(),
+                    reason
+                );
+                (dish.recipe_cost_cents.unwrap_or(0), true)
+            }
+            Err(e) => return Err(e),
+        };
+
+        // 2. Resolve dish → recipe → raw ingredients before touching the DB,
+        //    with the chosen modifiers applied
+        let base_ingredients = self.raw_ingredients_per_portion(tenant_id, dish_id).await?;
+        let (mut raw_ingredients, modifiers) = if modifier_option_ids.is_empty() {
+            (base_ingredients, Vec::new())
+        } else {
+            self.apply_sale_modifiers(tenant_id, dish_id, base_ingredients, &modifier_option_ids)
+                .await?
+        };
+        let modifier_cost_cents: i64 = modifiers.iter().map(|m| m.cost_delta_cents).sum();
+
+        let recipe_cost_cents = i32::try_from(cost_per_serving_cents + modifier_cost_cents)
+            .map_err(|_| AppError::internal("Recipe cost out of range"))?;
         let profit_cents = (selling_price_cents - recipe_cost_cents) * quantity as i32;
+        let dish_id = dish_id.as_uuid();
+
+        let policy = fetch_stock_policy(&self.pool, tenant_id).await?;
 
-        // 1. Record the sale for analytics
-        sqlx::query(
+        if let Some(location) = location_id {
+            ensure_active_location(tx, tenant_id, location).await?;
+        }
+        if let Some(section) = menu_section_id {
+            let listed: bool = sqlx::query_scalar(
+                r#"
+                SELECT EXISTS(
+                    SELECT 1 FROM menu_section_dishes
+                    WHERE section_id = $1 AND dish_id = $2 AND tenant_id = $3
+                )
+                "#,
+            )
+            .bind(section.as_uuid())
+            .bind(dish_id)
+            .bind(tenant_uuid)
+            .fetch_one(&mut **tx)
+            .await?;
+            if !listed {
+                return Err(AppError::validation(
+                    "Dish is not listed in the given menu section",
+                ));
+            }
+        }
+
+        // 3. Record the sale for analytics
+        let sale_id: Uuid = sqlx::query_scalar(
             r#"
             INSERT INTO dish_sales (
                 tenant_id,
@@ -213,8 +538,14 @@ impl MenuEngineeringService {
                 quantity,
                 selling_price_cents,
                 recipe_cost_cents,
-                profit_cents
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                profit_cents,
+                location_id,
+                menu_section_id,
+                sold_at,
+                pos_ticket_id,
+                cost_estimated
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, COALESCE($10, NOW()), $11, $12)
+            RETURNING id
             "#,
         )
         .bind(tenant_uuid)
@@ -224,42 +555,49 @@ impl MenuEngineeringService {
         .bind(selling_price_cents)
         .bind(recipe_cost_cents)
         .bind(profit_cents)
-        .execute(&self.pool)
+        .bind(location_id.map(|id| id.as_uuid()))
+        .bind(menu_section_id.map(|id| id.as_uuid()))
+        .bind(sold_at)
+        .bind(pos_ticket_id)
+        .bind(cost_estimated)
+        .fetch_one(&mut **tx)
         .await?;
 
-        // 2. 🚀 AUTOMATIC INVENTORY DEDUCTION (The "Business Flow" Logic)
-        tracing::info!(
-            "Starting automatic inventory deduction for dish {}",
-            dish_id
-        );
-
-        // Find the dish to get the recipe
-        let dish = self
-            .dish_repo
-            .find_by_id(DishId::from_uuid(dish_id), tenant_id)
-            .await?
-            .ok_or_else(|| AppError::not_found("Dish not found"))?;
-
-        // Find the recipe (🔒 TENANT ISOLATION: use tenant_id)
-        let recipe = self
-            .recipe_repo
-            .find_by_id(dish.recipe_id, tenant_id)
-            .await?
-            .ok_or_else(|| AppError::not_found("Recipe not found"))?;
+        for modifier in &modifiers {
+            sqlx::query(
+                r#"
+                INSERT INTO dish_sale_modifiers (
+                    sale_id, option_id, tenant_id, group_name, option_name,
+                    quantity, price_delta_cents, cost_delta_cents
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(sale_id)
+            .bind(modifier.option_id.as_uuid())
+            .bind(tenant_uuid)
+            .bind(&modifier.group_name)
+            .bind(&modifier.option_name)
+            .bind(quantity as i32)
+            .bind(modifier.price_delta_cents)
+            .bind(modifier.cost_delta_cents)
+            .execute(&mut **tx)
+            .await?;
+        }
 
+        // 4. 🚀 AUTOMATIC INVENTORY DEDUCTION (The "Business Flow" Logic)
         tracing::info!(
-            "Found recipe {} with {} ingredients",
-            recipe.id().as_uuid(),
-            recipe.ingredients().len()
+            "Starting automatic inventory deduction for dish {} (policy: {})",
+            dish_id,
+            policy.as_str()
         );
 
-        // For each ingredient in the recipe, deduct from inventory using FIFO with locking
-        for ingredient in recipe.ingredients() {
-            let catalog_id = ingredient.catalog_ingredient_id();
-            let quantity_per_dish = ingredient.quantity().value();
-            let total_to_deduct_val = quantity_per_dish * (quantity as f64);
-
-            let target_qty = Quantity::new(total_to_deduct_val)?.decimal();
+        // For each raw ingredient, deduct from inventory using FIFO with locking.
+        // Locks are always taken in catalog ID order, so two concurrent sales
+        // sharing ingredients cannot deadlock.
+        raw_ingredients.sort_by_key(|(id, _)| id.as_uuid());
+        for (catalog_id, quantity_per_dish) in raw_ingredients {
+            let target_qty =
+                Quantity::from_decimal(quantity_per_dish * Decimal::from(quantity))?.decimal();
             if target_qty <= Decimal::ZERO {
                 continue;
             }
@@ -270,12 +608,10 @@ impl MenuEngineeringService {
                 catalog_id.as_uuid()
             );
 
-            let mut tx = self.pool.begin().await?;
-
-            // 1. Get deliveries for this ingredient with FOR UPDATE lock
+            // Get deliveries for this ingredient with FOR UPDATE lock
             let batches = self
                 .inventory_repo
-                .list_active_by_ingredient_for_update(&mut tx, tenant_id, catalog_id)
+                .list_active_by_ingredient_for_update(tx, tenant_id, catalog_id, location_id)
                 .await?;
 
             let mut remaining_to_deduct = target_qty;
@@ -291,7 +627,7 @@ impl MenuEngineeringService {
                     remaining_to_deduct
                 };
 
-                // 2. Update batch stock
+                // Update batch stock
                 let new_remaining = batch_available - deduction;
                 batch.remaining_quantity = Quantity::from_decimal(new_remaining)?;
 
@@ -300,10 +636,10 @@ impl MenuEngineeringService {
                 }
 
                 self.inventory_repo
-                    .update_in_transaction(&mut tx, &batch)
+                    .update_in_transaction(tx, &batch)
                     .await?;
 
-                // 3. Record movement for audit log (OutSale)
+                // Record movement for audit log (OutSale)
                 let mut movement = InventoryMovement::new(
                     tenant_id,
                     batch.id,
@@ -311,39 +647,206 @@ impl MenuEngineeringService {
                     deduction,
                     batch.price_per_unit.as_cents(),
                 );
-                movement.reference_id = Some(dish_id);
+                movement.reference_id = Some(sale_id);
                 movement.reference_type = Some("DISH_SALE".to_string());
-                movement.notes = Some(format!("Sold {} x dish", quantity));
+                movement.notes = Some(format!("Sold {} x dish {}", quantity, dish_id));
 
-                self.inventory_repo
-                    .record_movement(&mut tx, &movement)
-                    .await?;
+                self.inventory_repo.record_movement(tx, &movement).await?;
 
                 remaining_to_deduct -= deduction;
             }
 
             if remaining_to_deduct > Decimal::ZERO {
-                tracing::warn!(
-                    "Insufficient stock for {} during sale. Missing: {}",
-                    catalog_id.as_uuid(),
-                    remaining_to_deduct
-                );
-                // In some systems you might want to allow negative stock or stop the sale.
-                // For now, we continue but warn.
+                match policy {
+                    // Dropping `tx` rolls back the sale row and all deductions
+                    StockPolicy::Reject => {
+                        return Err(AppError::validation(format!(
+                            "Insufficient stock for ingredient {}. Missing: {}",
+                            catalog_id.as_uuid(),
+                            remaining_to_deduct
+                        )));
+                    }
+                    StockPolicy::Allow => {
+                        tracing::warn!(
+                            "Insufficient stock for {} during sale. Missing: {}",
+                            catalog_id.as_uuid(),
+                            remaining_to_deduct
+                        );
+                    }
+                    StockPolicy::RecordShortfall => {
+                        let unit_cost = self
+                            .inventory_repo
+                            .last_known_price(tx, tenant_id, catalog_id)
+                            .await?
+                            .map(|p| p.as_cents())
+                            .unwrap_or(0);
+
+                        let mut shortfall = InventoryShortfall::new(
+                            tenant_id,
+                            catalog_id,
+                            remaining_to_deduct,
+                            unit_cost,
+                        );
+                        shortfall.reference_id = Some(sale_id);
+                        shortfall.reference_type = Some("DISH_SALE".to_string());
+                        shortfall.notes = Some(format!("Sold {} x dish {}", quantity, dish_id));
+
+                        self.inventory_repo.record_shortfall(tx, &shortfall).await?;
+
+                        tracing::warn!(
+                            "Shortfall recorded for {} during sale {}. Missing: {}",
+                            catalog_id.as_uuid(),
+                            sale_id,
+                            remaining_to_deduct
+                        );
+                    }
+                }
             }
-
-            tx.commit().await?;
         }
 
         Ok(())
     }
+
+    /// One portion's ingredients with the modifiers recorded on past sales
+    /// applied, for usage reports. `base` is one serving as returned by
+    /// `raw_ingredients_per_portion`; options deleted since are skipped.
+    pub(crate) async fn apply_recorded_modifiers(
+        &self,
+        tenant_id: TenantId,
+        dish_id: DishId,
+        base: &[(CatalogIngredientId, Decimal)],
+        option_ids: &[ModifierOptionId],
+    ) -> AppResult<Vec<(CatalogIngredientId, Decimal)>> {
+        let groups = fetch_modifier_groups(&self.pool, tenant_id, dish_id).await?;
+        let options = recorded_options(&groups, option_ids);
+
+        let added: Vec<CatalogIngredientId> = options
+            .iter()
+            .flat_map(|o| o.ingredients.iter())
+            .filter(|d| matches!(d, IngredientDelta::Add { .. }))
+            .map(|d| d.catalog_ingredient_id())
+            .collect();
+        let yields = self
+            .catalog_repo
+            .effective_yield_percents(tenant_id, &added)
+            .await?;
+        Ok(apply_modifiers(base, &options, &yields))
+    }
+
+    /// Apply the chosen modifier options to one portion's ingredients.
+    ///
+    /// `base` must be one serving (`raw_ingredients_per_portion`), the same
+    /// basis as the recipe's cost per serving, so the ingredient deltas and
+    /// the cost deltas added to that cost are both per portion.
+    ///
+    /// Returns the modified ingredient list and, per option, its snapshot
+    /// with the cost delta against the plain dish. The combined cost delta
+    /// is split so that the per-option deltas always add up to it (scales
+    /// and add-ons interact).
+    async fn apply_sale_modifiers(
+        &self,
+        tenant_id: TenantId,
+        dish_id: DishId,
+        base: Vec<(CatalogIngredientId, Decimal)>,
+        option_ids: &[ModifierOptionId],
+    ) -> AppResult<(Vec<(CatalogIngredientId, Decimal)>, Vec<SaleModifier>)> {
+        let groups = fetch_modifier_groups(&self.pool, tenant_id, dish_id).await?;
+        let options = resolve_selection(&groups, option_ids)?;
+
+        let added: Vec<CatalogIngredientId> = options
+            .iter()
+            .flat_map(|o| o.ingredients.iter())
+            .filter(|d| matches!(d, IngredientDelta::Add { .. }))
+            .map(|d| d.catalog_ingredient_id())
+            .collect();
+        let yields = self
+            .catalog_repo
+            .effective_yield_percents(tenant_id, &added)
+            .await?;
+        let modified = apply_modifiers(&base, &options, &yields);
+
+        let mut priced: Vec<CatalogIngredientId> = base.iter().map(|(id, _)| *id).collect();
+        priced.extend(added);
+        priced.sort_by_key(|id| id.as_uuid());
+        priced.dedup();
+        let strategy = self.inventory_repo.costing_strategy(tenant_id).await?;
+        let unit_prices: HashMap<CatalogIngredientId, i64> = self
+            .inventory_repo
+            .unit_prices(tenant_id, strategy, &priced)
+            .await?
+            .into_iter()
+            .map(|(id, price)| (id, price.as_cents()))
+            .collect();
+
+        let total_delta = cost_delta_cents(&base, &modified, &unit_prices)?;
+        let mut modifiers = Vec::with_capacity(options.len());
+        for option in &options {
+            let alone = apply_modifiers(&base, &[*option], &yields);
+            let group_name = groups
+                .iter()
+                .find(|g| g.id == option.group_id)
+                .map(|g| g.name.clone())
+                .unwrap_or_default();
+            modifiers.push(SaleModifier {
+                option_id: option.id,
+                group_name,
+                option_name: option.name.clone(),
+                price_delta_cents: option.price_delta_cents,
+                cost_delta_cents: cost_delta_cents(&base, &alone, &unit_prices)?,
+            });
+        }
+        // Interaction (e.g. an add-on on a large size) goes to the last option
+        let individual: i64 = modifiers.iter().map(|m| m.cost_delta_cents).sum();
+        if let Some(last) = modifiers.last_mut() {
+            last.cost_delta_cents += total_delta - individual;
+        }
+
+        Ok((modified, modifiers))
+    }
+}
+
+/// Snapshot of one modifier option on a sale (per portion)
+struct SaleModifier {
+    option_id: ModifierOptionId,
+    group_name: String,
+    option_name: String,
+    price_delta_cents: i64,
+    cost_delta_cents: i64,
+}
+
+/// One sale to book; the recipe cost is always computed server-side
+#[derive(Debug, Clone)]
+pub(crate) struct SaleRecord {
+    pub dish_id: DishId,
+    pub quantity: u32,
+    pub selling_price_cents: i32,
+    pub location_id: Option<StorageLocationId>,
+    pub menu_section_id: Option<MenuSectionId>,
+    /// Defaults to now
+    pub sold_at: Option<OffsetDateTime>,
+    /// POS ticket the sale was imported from
+    pub pos_ticket_id: Option<Uuid>,
+    /// Modifier options chosen for every portion of the sale
+    pub modifier_option_ids: Vec<ModifierOptionId>,
 }
 
 /// Aggregated sales data for a dish
 struct AggregatedDishData {
+    dish_id: DishId,
     dish_name: String,
+    /// (section id, section name, menu name)
+    section: Option<(Uuid, String, String)>,
     sales_volume: u32,
     total_revenue_cents: i64,
     total_profit_cents: i64,
-    avg_profit_margin_percent: f64,
+    margin_per_item_cents: i64,
+    profit_margin_percent: f64,
+}
+
+fn percent(part: i64, whole: i64) -> f64 {
+    if whole > 0 {
+        part as f64 / whole as f64 * 100.0
+    } else {
+        0.0
+    }
 }
@@ -1,9 +1,11 @@
+pub mod account; // 🆕 Password reset + email verification (Mailer port)
 pub mod admin_auth;
 pub mod admin_catalog;
 pub mod admin_nutrition;
 pub mod ai_sous_chef; // AI Sous Chef — deterministic state generation
 pub mod analytics;
 pub mod assistant_service;
+pub mod audit; // 🆕 Tenant-wide audit trail (who changed what, before/after diff)
 pub mod auth;
 pub mod catalog;
 pub mod chat_events_service; // 🆕 Chat telemetry (Step 4)
@@ -11,28 +13,42 @@ pub mod cms_service;
 pub mod cook_suggestions; // 🆕 Smart recipe suggestions from inventory
 pub mod copilot;
 pub mod dish;
+pub mod dish_label; // 🆕 EU-14 allergen matrix + nutrition labels
 pub mod intent_pages; // 🆕 Intent pages CRUD + batch + publish pipeline
 pub mod inventory;
 pub mod inventory_alert;
 pub mod laboratory; // 🆕 Food-tech Laboratory — analysis projects on top of catalog
+pub mod menu; // 🆕 Menus, sections, dish assignments
 pub mod menu_engineering;
+pub mod modifier; // 🆕 Dish modifier groups/options (price + recipe deltas)
+pub mod pos_import; // 🆕 POS sales import (CSV, signed webhook, review queue)
 pub mod prayer_visualizer; // 🆕 Prayer-mode particle map preprocessing (backend-side, R2-cached)
 pub mod preferences_service; // ChefOS user preferences
+pub mod pricing; // 🆕 Price optimisation + what-if simulator
 pub mod public_nutrition;
 pub mod public_seo_content; // 🆕 AI SEO content for programmatic pages
 pub mod purchase_draft; // 🆕 Purchase drafts (Copilot)
+pub mod purchase_receipt; // 🆕 Goods received against purchase drafts
 pub mod recipe;
 pub mod recipe_ai_insights_service; // AI insights service
 pub mod recipe_translation_service; // V2 translation service
 pub mod recipe_v2_service; // V2 recipe service
 pub mod recipe_validator; // Rule-based validator
+pub mod reorder; // 🆕 Par levels → automatic purchase drafts
 pub mod report;
+pub mod report_export; // 🆕 Owner reports as CSV / XLSX / HTML email body
+pub mod report_subscription; // 🆕 Scheduled report emails + delivery history
 pub mod rulebot; // 🆕 RuleBot orchestrator — Culinary Intelligence Platform
 pub mod smart_parse; // 🆕 SmartParse — deterministic text → ingredient parser
 pub mod smart_service; // 🆕 SmartService — intelligent ingredient aggregator
 pub mod sous_chef; // 🆕 Sous Chef — AI meal planner
+pub mod stocktake; // 🆕 Physical stocktake sessions
+pub mod storage_location; // 🆕 Storage locations + inventory transfers
+pub mod supplier; // 🆕 Suppliers + price history
+pub mod team; // 🆕 Team invitations, member roles, ownership transfer
 pub mod tenant_ingredient;
 pub mod usage_service; // ChefOS iOS usage tracking
+pub mod waste; // 🆕 Manual waste log (reasons, photos, FIFO write-off)
 pub mod user; // 🆕 Copilot — главный LLM Brain над всеми ботами
 
 pub use admin_auth::*;
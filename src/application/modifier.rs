@@ -0,0 +1,472 @@
+//! Dish modifier groups and options (sizes, add-ons, removals).
+//!
+//! Options carry a price delta and a recipe delta. Sales name the options
+//! chosen; `MenuEngineeringService::book_sale` costs and deducts the
+//! modified recipe and keeps a snapshot per option for attach-rate reports.
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::modifier::{
+    IngredientDelta, ModifierGroup, ModifierGroupId, ModifierOption, ModifierOptionId,
+};
+use crate::domain::{CatalogIngredientId, DishId};
+use crate::shared::{AppError, AppResult, TenantId};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModifierOptionInput {
+    pub name: String,
+    #[serde(default)]
+    pub price_delta_cents: i64,
+    #[serde(default)]
+    pub quantity_scale: Option<Decimal>,
+    #[serde(default)]
+    pub ingredients: Vec<IngredientDelta>,
+    #[serde(default)]
+    pub sort_order: i32,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_select() -> i32 {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateModifierGroupInput {
+    pub name: String,
+    #[serde(default)]
+    pub min_select: i32,
+    #[serde(default = "default_max_select")]
+    pub max_select: i32,
+    #[serde(default)]
+    pub sort_order: i32,
+    #[serde(default)]
+    pub options: Vec<ModifierOptionInput>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateModifierGroupInput {
+    pub name: Option<String>,
+    pub min_select: Option<i32>,
+    pub max_select: Option<i32>,
+    pub sort_order: Option<i32>,
+}
+
+#[derive(Clone)]
+pub struct ModifierService {
+    pool: PgPool,
+}
+
+impl ModifierService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Modifier groups of a dish with their options (inactive included)
+    pub async fn list_groups(
+        &self,
+        tenant_id: TenantId,
+        dish_id: DishId,
+    ) -> AppResult<Vec<ModifierGroup>> {
+        self.ensure_dish(tenant_id, dish_id).await?;
+        fetch_modifier_groups(&self.pool, tenant_id, dish_id).await
+    }
+
+    pub async fn create_group(
+        &self,
+        tenant_id: TenantId,
+        dish_id: DishId,
+        input: CreateModifierGroupInput,
+    ) -> AppResult<ModifierGroup> {
+        self.ensure_dish(tenant_id, dish_id).await?;
+        let mut group =
+            ModifierGroup::new(dish_id, input.name, input.min_select, input.max_select)?;
+        group.sort_order = input.sort_order;
+        for option in input.options {
+            group.options.push(build_option(group.id, option)?);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO dish_modifier_groups (id, tenant_id, dish_id, name, min_select, max_select, sort_order)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(group.id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .bind(dish_id.as_uuid())
+        .bind(&group.name)
+        .bind(group.min_select)
+        .bind(group.max_select)
+        .bind(group.sort_order)
+        .execute(&mut *tx)
+        .await?;
+        for option in &group.options {
+            insert_option(&mut tx, tenant_id, option).await?;
+        }
+        tx.commit().await?;
+
+        Ok(group)
+    }
+
+    pub async fn update_group(
+        &self,
+        tenant_id: TenantId,
+        group_id: ModifierGroupId,
+        input: UpdateModifierGroupInput,
+    ) -> AppResult<ModifierGroup> {
+        let mut group = self.load_group(tenant_id, group_id).await?;
+        if let Some(name) = input.name {
+            group.rename(name)?;
+        }
+        group.min_select = input.min_select.unwrap_or(group.min_select);
+        group.max_select = input.max_select.unwrap_or(group.max_select);
+        group.sort_order = input.sort_order.unwrap_or(group.sort_order);
+        group.validate_limits()?;
+
+        sqlx::query(
+            r#"
+            UPDATE dish_modifier_groups
+            SET name = $3, min_select = $4, max_select = $5, sort_order = $6
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+        )
+        .bind(group_id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .bind(&group.name)
+        .bind(group.min_select)
+        .bind(group.max_select)
+        .bind(group.sort_order)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    pub async fn delete_group(
+        &self,
+        tenant_id: TenantId,
+        group_id: ModifierGroupId,
+    ) -> AppResult<()> {
+        let deleted =
+            sqlx::query("DELETE FROM dish_modifier_groups WHERE id = $1 AND tenant_id = $2")
+                .bind(group_id.as_uuid())
+                .bind(tenant_id.as_uuid())
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        if deleted == 0 {
+            return Err(AppError::not_found("Modifier group not found"));
+        }
+        Ok(())
+    }
+
+    pub async fn add_option(
+        &self,
+        tenant_id: TenantId,
+        group_id: ModifierGroupId,
+        input: ModifierOptionInput,
+    ) -> AppResult<ModifierOption> {
+        self.load_group(tenant_id, group_id).await?;
+        let option = build_option(group_id, input)?;
+
+        let mut tx = self.pool.begin().await?;
+        insert_option(&mut tx, tenant_id, &option).await?;
+        tx.commit().await?;
+
+        Ok(option)
+    }
+
+    /// Replace an option (name, deltas, order, active flag)
+    pub async fn update_option(
+        &self,
+        tenant_id: TenantId,
+        option_id: ModifierOptionId,
+        input: ModifierOptionInput,
+    ) -> AppResult<ModifierOption> {
+        let group_id: Uuid = sqlx::query_scalar(
+            "SELECT group_id FROM dish_modifier_options WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(option_id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Modifier option not found"))?;
+
+        let mut option = build_option(ModifierGroupId::from_uuid(group_id), input)?;
+        option.id = option_id;
+
+        // Updated in place so past sales keep pointing at the option
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE dish_modifier_options
+            SET name = $2, price_delta_cents = $3, quantity_scale = $4, sort_order = $5, is_active = $6
+            WHERE id = $1
+            "#,
+        )
+        .bind(option_id.as_uuid())
+        .bind(&option.name)
+        .bind(option.price_delta_cents)
+        .bind(option.quantity_scale)
+        .bind(option.sort_order)
+        .bind(option.is_active)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM dish_modifier_option_ingredients WHERE option_id = $1")
+            .bind(option_id.as_uuid())
+            .execute(&mut *tx)
+            .await?;
+        insert_option_ingredients(&mut tx, &option).await?;
+        tx.commit().await?;
+
+        Ok(option)
+    }
+
+    pub async fn delete_option(
+        &self,
+        tenant_id: TenantId,
+        option_id: ModifierOptionId,
+    ) -> AppResult<()> {
+        let deleted =
+            sqlx::query("DELETE FROM dish_modifier_options WHERE id = $1 AND tenant_id = $2")
+                .bind(option_id.as_uuid())
+                .bind(tenant_id.as_uuid())
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+        if deleted == 0 {
+            return Err(AppError::not_found("Modifier option not found"));
+        }
+        Ok(())
+    }
+
+    async fn load_group(
+        &self,
+        tenant_id: TenantId,
+        group_id: ModifierGroupId,
+    ) -> AppResult<ModifierGroup> {
+        let dish_id: Uuid = sqlx::query_scalar(
+            "SELECT dish_id FROM dish_modifier_groups WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(group_id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Modifier group not found"))?;
+
+        fetch_modifier_groups(&self.pool, tenant_id, DishId::from_uuid(dish_id))
+            .await?
+            .into_iter()
+            .find(|g| g.id == group_id)
+            .ok_or_else(|| AppError::not_found("Modifier group not found"))
+    }
+
+    async fn ensure_dish(&self, tenant_id: TenantId, dish_id: DishId) -> AppResult<()> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM dishes WHERE id = $1 AND tenant_id = $2)",
+        )
+        .bind(dish_id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .fetch_one(&self.pool)
+        .await?;
+        if !exists {
+            return Err(AppError::not_found("Dish not found"));
+        }
+        Ok(())
+    }
+}
+
+fn build_option(
+    group_id: ModifierGroupId,
+    input: ModifierOptionInput,
+) -> AppResult<ModifierOption> {
+    let mut seen = Vec::with_capacity(input.ingredients.len());
+    for delta in &input.ingredients {
+        if seen.contains(&delta.catalog_ingredient_id()) {
+            return Err(AppError::validation(
+                "An ingredient can appear only once per modifier option",
+            ));
+        }
+        seen.push(delta.catalog_ingredient_id());
+    }
+    let mut option = ModifierOption::new(
+        group_id,
+        input.name,
+        input.price_delta_cents,
+        input.quantity_scale,
+        input.ingredients,
+    )?;
+    option.sort_order = input.sort_order;
+    option.is_active = input.is_active;
+    Ok(option)
+}
+
+async fn insert_option(
+    tx: &mut Transaction<'static, Postgres>,
+    tenant_id: TenantId,
+    option: &ModifierOption,
+) -> AppResult<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO dish_modifier_options (
+            id, tenant_id, group_id, name, price_delta_cents, quantity_scale, sort_order, is_active
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(option.id.as_uuid())
+    .bind(tenant_id.as_uuid())
+    .bind(option.group_id.as_uuid())
+    .bind(&option.name)
+    .bind(option.price_delta_cents)
+    .bind(option.quantity_scale)
+    .bind(option.sort_order)
+    .bind(option.is_active)
+    .execute(&mut **tx)
+    .await?;
+
+    insert_option_ingredients(tx, option).await
+}
+
+async fn insert_option_ingredients(
+    tx: &mut Transaction<'static, Postgres>,
+    option: &ModifierOption,
+) -> AppResult<()> {
+    for delta in &option.ingredients {
+        let (action, quantity) = match *delta {
+            IngredientDelta::Add { quantity, .. } => ("add", Some(quantity)),
+            IngredientDelta::Remove { .. } => ("remove", None),
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO dish_modifier_option_ingredients (option_id, catalog_ingredient_id, action, quantity)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(option.id.as_uuid())
+        .bind(delta.catalog_ingredient_id().as_uuid())
+        .bind(action)
+        .bind(quantity)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Modifier groups of a dish with options and recipe deltas, in display order
+pub(crate) async fn fetch_modifier_groups(
+    pool: &PgPool,
+    tenant_id: TenantId,
+    dish_id: DishId,
+) -> AppResult<Vec<ModifierGroup>> {
+    let group_rows = sqlx::query(
+        r#"
+        SELECT id, name, min_select, max_select, sort_order
+        FROM dish_modifier_groups
+        WHERE tenant_id = $1 AND dish_id = $2
+        ORDER BY sort_order, name
+        "#,
+    )
+    .bind(tenant_id.as_uuid())
+    .bind(dish_id.as_uuid())
+    .fetch_all(pool)
+    .await?;
+    if group_rows.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let option_rows = sqlx::query(
+        r#"
+        SELECT o.id, o.group_id, o.name, o.price_delta_cents, o.quantity_scale,
+               o.sort_order, o.is_active
+        FROM dish_modifier_options o
+        JOIN dish_modifier_groups g ON g.id = o.group_id
+        WHERE g.tenant_id = $1 AND g.dish_id = $2
+        ORDER BY o.sort_order, o.name
+        "#,
+    )
+    .bind(tenant_id.as_uuid())
+    .bind(dish_id.as_uuid())
+    .fetch_all(pool)
+    .await?;
+
+    let delta_rows = sqlx::query(
+        r#"
+        SELECT i.option_id, i.catalog_ingredient_id, i.action, i.quantity
+        FROM dish_modifier_option_ingredients i
+        JOIN dish_modifier_options o ON o.id = i.option_id
+        JOIN dish_modifier_groups g ON g.id = o.group_id
+        WHERE g.tenant_id = $1 AND g.dish_id = $2
+        "#,
+    )
+    .bind(tenant_id.as_uuid())
+    .bind(dish_id.as_uuid())
+    .fetch_all(pool)
+    .await?;
+
+    let mut deltas: HashMap<Uuid, Vec<IngredientDelta>> = HashMap::new();
+    for row in delta_rows {
+        let catalog_ingredient_id =
+            CatalogIngredientId::from_uuid(row.try_get("catalog_ingredient_id")?);
+        let action: String = row.try_get("action")?;
+        let delta = match action.as_str() {
+            "add" => IngredientDelta::Add {
+                catalog_ingredient_id,
+                quantity: row
+                    .try_get::<Option<Decimal>, _>("quantity")?
+                    .unwrap_or(Decimal::ZERO),
+            },
+            _ => IngredientDelta::Remove {
+                catalog_ingredient_id,
+            },
+        };
+        deltas
+            .entry(row.try_get("option_id")?)
+            .or_default()
+            .push(delta);
+    }
+
+    let mut groups: Vec<ModifierGroup> = group_rows
+        .iter()
+        .map(|row| {
+            Ok(ModifierGroup {
+                id: ModifierGroupId::from_uuid(row.try_get("id")?),
+                dish_id,
+                name: row.try_get("name")?,
+                min_select: row.try_get("min_select")?,
+                max_select: row.try_get("max_select")?,
+                sort_order: row.try_get("sort_order")?,
+                options: Vec::new(),
+            })
+        })
+        .collect::<AppResult<_>>()?;
+
+    for row in option_rows {
+        let id: Uuid = row.try_get("id")?;
+        let group_id = ModifierGroupId::from_uuid(row.try_get("group_id")?);
+        let option = ModifierOption {
+            id: ModifierOptionId::from_uuid(id),
+            group_id,
+            name: row.try_get("name")?,
+            price_delta_cents: row.try_get("price_delta_cents")?,
+            quantity_scale: row.try_get("quantity_scale")?,
+            ingredients: deltas.remove(&id).unwrap_or_default(),
+            sort_order: row.try_get("sort_order")?,
+            is_active: row.try_get("is_active")?,
+        };
+        if let Some(group) = groups.iter_mut().find(|g| g.id == group_id) {
+            group.options.push(option);
+        }
+    }
+
+    Ok(groups)
+}
@@ -0,0 +1,760 @@
+//! POS sales import.
+//!
+//! Tickets arrive as a CSV export or through a signed JSON webhook (same
+//! HMAC scheme as the Stripe webhook, with a per-tenant secret). Each ticket
+//! is imported once — the POS ticket ID is the idempotency key. Lines are
+//! mapped to dishes by PLU or item name and booked through
+//! `MenuEngineeringService::book_sale`, which costs the recipe server-side
+//! and deducts stock. Lines without a mapping, or whose booking fails, wait
+//! in the review queue until a dish is assigned or they are ignored. A
+//! ticket's claim and all of its lines commit in one transaction, so an
+//! import that dies half-way leaves the ticket free for the retry.
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::application::menu_engineering::SaleRecord;
+use crate::application::MenuEngineeringService;
+use crate::domain::pos_import::{
+    parse_pos_csv, pos_item_key, verify_pos_signature, PosImportSource, PosReviewReason,
+    PosReviewStatus, PosTicket, PosTicketLine, PosWebhookPayload,
+};
+use crate::domain::storage_location::StorageLocationId;
+use crate::domain::DishId;
+use crate::shared::{AppError, AppResult, TenantId, UserId};
+
+/// Header carrying `t=<unix>,v1=<hex hmac>`
+pub const POS_SIGNATURE_HEADER: &str = "x-pos-signature";
+
+/// Webhook credentials, shown once after (re)generating the secret
+#[derive(Debug, Clone, Serialize)]
+pub struct PosWebhookSecret {
+    pub webhook_path: String,
+    pub signature_header: &'static str,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PosImportSummary {
+    pub tickets_received: usize,
+    pub tickets_imported: usize,
+    /// Ticket IDs that were already imported (skipped)
+    pub duplicate_ticket_ids: Vec<String>,
+    pub lines_booked: usize,
+    pub lines_queued: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpsertPosMappingInput {
+    #[serde(default)]
+    pub plu: Option<String>,
+    #[serde(default)]
+    pub item_name: Option<String>,
+    pub dish_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PosItemMapping {
+    pub id: Uuid,
+    pub item_key: String,
+    pub plu: Option<String>,
+    pub item_name: Option<String>,
+    pub dish_id: Uuid,
+    pub dish_name: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Mapping saved plus the queued lines it let us book
+#[derive(Debug, Clone, Serialize)]
+pub struct PosMappingResult {
+    pub mapping: PosItemMapping,
+    pub lines_booked: usize,
+    pub lines_failed: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResolveReviewLineInput {
+    pub dish_id: Uuid,
+    /// Also map the item to the dish (and book its other queued lines)
+    #[serde(default = "default_remember")]
+    pub remember: bool,
+}
+
+fn default_remember() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PosReviewLine {
+    pub id: Uuid,
+    pub external_ticket_id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub sold_at: OffsetDateTime,
+    pub item_key: String,
+    pub plu: Option<String>,
+    pub item_name: String,
+    pub quantity: i32,
+    pub unit_price_cents: i32,
+    pub reason: PosReviewReason,
+    pub error: Option<String>,
+    pub status: PosReviewStatus,
+    pub dish_id: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PosReplaySummary {
+    pub lines_booked: usize,
+    pub lines_failed: usize,
+}
+
+#[derive(Clone)]
+pub struct PosImportService {
+    pool: PgPool,
+    menu_engineering_service: MenuEngineeringService,
+}
+
+impl PosImportService {
+    pub fn new(pool: PgPool, menu_engineering_service: MenuEngineeringService) -> Self {
+        Self {
+            pool,
+            menu_engineering_service,
+        }
+    }
+
+    /// Generate (or rotate) the tenant's webhook secret. Webhook sales are
+    /// attributed to `user_id`.
+    pub async fn rotate_webhook_secret(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+    ) -> AppResult<PosWebhookSecret> {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let secret = format!("pos_{}", hex::encode(bytes));
+
+        sqlx::query(
+            r#"
+            INSERT INTO pos_integrations (tenant_id, webhook_secret, user_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET webhook_secret = EXCLUDED.webhook_secret, user_id = EXCLUDED.user_id
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(&secret)
+        .bind(user_id.as_uuid())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(PosWebhookSecret {
+            webhook_path: format!("/webhooks/pos/{}", tenant_id.as_uuid()),
+            signature_header: "X-Pos-Signature",
+            secret,
+        })
+    }
+
+    /// Import a POS CSV export (see `parse_pos_csv` for the columns).
+    /// `location_id` applies to every ticket in the file.
+    pub async fn import_csv(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        csv: &str,
+        location_id: Option<StorageLocationId>,
+    ) -> AppResult<PosImportSummary> {
+        let mut tickets = parse_pos_csv(csv)?;
+        if let Some(location) = location_id {
+            for ticket in tickets.iter_mut() {
+                ticket.location_id = Some(location.as_uuid());
+            }
+        }
+        self.import_tickets(tenant_id, user_id, PosImportSource::Csv, tickets)
+            .await
+    }
+
+    /// Verify and import a webhook delivery. Fails closed: no configured
+    /// secret or a bad signature rejects the whole body.
+    pub async fn ingest_webhook(
+        &self,
+        tenant_uuid: Uuid,
+        signature_header: &str,
+        body: &[u8],
+    ) -> AppResult<PosImportSummary> {
+        let row = sqlx::query(
+            "SELECT webhook_secret, user_id FROM pos_integrations WHERE tenant_id = $1",
+        )
+        .bind(tenant_uuid)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::authentication("POS webhook is not configured"))?;
+        let secret: String = row.try_get("webhook_secret")?;
+        let user_uuid: Uuid = row.try_get("user_id")?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        verify_pos_signature(&secret, body, signature_header, now)?;
+
+        let payload: PosWebhookPayload = serde_json::from_slice(body)
+            .map_err(|e| AppError::validation(format!("Invalid POS payload: {}", e)))?;
+        for ticket in &payload.tickets {
+            ticket.validate()?;
+        }
+
+        self.import_tickets(
+            TenantId::from(tenant_uuid),
+            UserId::from(user_uuid),
+            PosImportSource::Webhook,
+            payload.tickets,
+        )
+        .await
+    }
+
+    async fn import_tickets(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        source: PosImportSource,
+        tickets: Vec<PosTicket>,
+    ) -> AppResult<PosImportSummary> {
+        // Reject unknown or foreign outlets before claiming any ticket
+        let mut location_ids: Vec<Uuid> = tickets.iter().filter_map(|t| t.location_id).collect();
+        location_ids.sort();
+        location_ids.dedup();
+        if !location_ids.is_empty() {
+            let known: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM storage_locations WHERE tenant_id = $1 AND id = ANY($2) AND is_active",
+            )
+            .bind(tenant_id.as_uuid())
+            .bind(&location_ids)
+            .fetch_one(&self.pool)
+            .await?;
+            if known != location_ids.len() as i64 {
+                return Err(AppError::not_found("Storage location not found"));
+            }
+        }
+
+        let mappings = self.mapping_index(tenant_id).await?;
+        let now = OffsetDateTime::now_utc();
+        let mut summary = PosImportSummary {
+            tickets_received: tickets.len(),
+            ..Default::default()
+        };
+
+        for ticket in tickets {
+            let sold_at = ticket.sold_at.unwrap_or(now);
+            let mut tx = self.pool.begin().await?;
+
+            // Claim the ticket ID first: a re-delivery finds it taken
+            let ticket_uuid: Option<Uuid> = sqlx::query_scalar(
+                r#"
+                INSERT INTO pos_tickets (id, tenant_id, external_ticket_id, source, sold_at, location_id)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (tenant_id, external_ticket_id) DO NOTHING
+                RETURNING id
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(tenant_id.as_uuid())
+            .bind(ticket.ticket_id.trim())
+            .bind(source.as_str())
+            .bind(sold_at)
+            .bind(ticket.location_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(ticket_uuid) = ticket_uuid else {
+                summary.duplicate_ticket_ids.push(ticket.ticket_id);
+                continue;
+            };
+
+            let (mut booked, mut queued) = (0, 0);
+            for line in &ticket.lines {
+                let dish_id = line
+                    .lookup_keys()
+                    .iter()
+                    .find_map(|key| mappings.get(key).copied());
+                let Some(dish_id) = dish_id else {
+                    enqueue(
+                        &mut tx,
+                        tenant_id,
+                        ticket_uuid,
+                        line,
+                        PosReviewReason::Unmapped,
+                        None,
+                    )
+                    .await?;
+                    queued += 1;
+                    continue;
+                };
+
+                let sale = SaleRecord {
+                    dish_id,
+                    quantity: line.quantity,
+                    selling_price_cents: line.unit_price_cents as i32,
+                    location_id: ticket.location_id.map(StorageLocationId::from_uuid),
+                    menu_section_id: None,
+                    sold_at: Some(sold_at),
+                    pos_ticket_id: Some(ticket_uuid),
+                    modifier_option_ids: Vec::new(),
+                };
+                // A line that fails is rolled back on its own and queued
+                sqlx::query("SAVEPOINT pos_line").execute(&mut *tx).await?;
+                match self
+                    .menu_engineering_service
+                    .book_sale_in(&mut tx, tenant_id, user_id, sale)
+                    .await
+                {
+                    Ok(()) => {
+                        sqlx::query("RELEASE SAVEPOINT pos_line")
+                            .execute(&mut *tx)
+                            .await?;
+                        booked += 1;
+                    }
+                    Err(e) => {
+                        sqlx::query("ROLLBACK TO SAVEPOINT pos_line")
+                            .execute(&mut *tx)
+                            .await?;
+                        tracing::warn!(
+                            "POS ticket {} line '{}' not booked: {}",
+                            ticket.ticket_id,
+                            line.display_name(),
+                            e
+                        );
+                        enqueue(
+                            &mut tx,
+                            tenant_id,
+                            ticket_uuid,
+                            line,
+                            PosReviewReason::Failed,
+                            Some(e.to_string()),
+                        )
+                        .await?;
+                        queued += 1;
+                    }
+                }
+            }
+
+            tx.commit().await?;
+            summary.tickets_imported += 1;
+            summary.lines_booked += booked;
+            summary.lines_queued += queued;
+        }
+
+        tracing::info!(
+            "POS {} import: {}/{} tickets imported, {} lines booked, {} queued for review",
+            source.as_str(),
+            summary.tickets_imported,
+            summary.tickets_received,
+            summary.lines_booked,
+            summary.lines_queued
+        );
+
+        Ok(summary)
+    }
+
+    async fn mapping_index(&self, tenant_id: TenantId) -> AppResult<HashMap<String, DishId>> {
+        let rows =
+            sqlx::query("SELECT item_key, dish_id FROM pos_item_mappings WHERE tenant_id = $1")
+                .bind(tenant_id.as_uuid())
+                .fetch_all(&self.pool)
+                .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("item_key")?,
+                    DishId::from_uuid(row.try_get("dish_id")?),
+                ))
+            })
+            .collect()
+    }
+
+    pub async fn list_mappings(&self, tenant_id: TenantId) -> AppResult<Vec<PosItemMapping>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.id, m.item_key, m.plu, m.item_name, m.dish_id, d.name AS dish_name, m.created_at
+            FROM pos_item_mappings m
+            JOIN dishes d ON d.id = m.dish_id
+            WHERE m.tenant_id = $1
+            ORDER BY m.item_key
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(mapping_from_row).collect()
+    }
+
+    /// Map a PLU or item name to a dish (replacing an existing mapping for
+    /// the same key), then book the queued unmapped lines it now resolves
+    pub async fn upsert_mapping(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        input: UpsertPosMappingInput,
+    ) -> AppResult<PosMappingResult> {
+        let item_name = input.item_name.unwrap_or_default();
+        let item_key = pos_item_key(input.plu.as_deref(), &item_name)?;
+        self.ensure_dish(tenant_id, input.dish_id).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO pos_item_mappings (id, tenant_id, item_key, plu, item_name, dish_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id, item_key) DO UPDATE
+            SET plu = EXCLUDED.plu, item_name = EXCLUDED.item_name, dish_id = EXCLUDED.dish_id
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id.as_uuid())
+        .bind(&item_key)
+        .bind(
+            input
+                .plu
+                .as_deref()
+                .map(str::trim)
+                .filter(|p| !p.is_empty()),
+        )
+        .bind(Some(item_name.trim()).filter(|n| !n.is_empty()))
+        .bind(input.dish_id)
+        .execute(&self.pool)
+        .await?;
+
+        let replay = self
+            .replay_unmapped(
+                tenant_id,
+                user_id,
+                &item_key,
+                DishId::from_uuid(input.dish_id),
+            )
+            .await?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT m.id, m.item_key, m.plu, m.item_name, m.dish_id, d.name AS dish_name, m.created_at
+            FROM pos_item_mappings m
+            JOIN dishes d ON d.id = m.dish_id
+            WHERE m.tenant_id = $1 AND m.item_key = $2
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(&item_key)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(PosMappingResult {
+            mapping: mapping_from_row(&row)?,
+            lines_booked: replay.lines_booked,
+            lines_failed: replay.lines_failed,
+        })
+    }
+
+    pub async fn delete_mapping(&self, tenant_id: TenantId, mapping_id: Uuid) -> AppResult<()> {
+        let deleted = sqlx::query("DELETE FROM pos_item_mappings WHERE id = $1 AND tenant_id = $2")
+            .bind(mapping_id)
+            .bind(tenant_id.as_uuid())
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            return Err(AppError::not_found("POS mapping not found"));
+        }
+        Ok(())
+    }
+
+    /// Review queue, oldest first (pending lines by default)
+    pub async fn list_review_queue(
+        &self,
+        tenant_id: TenantId,
+        status: Option<PosReviewStatus>,
+    ) -> AppResult<Vec<PosReviewLine>> {
+        let status = status.unwrap_or(PosReviewStatus::Pending);
+        let rows = sqlx::query(
+            r#"
+            SELECT q.id, t.external_ticket_id, t.sold_at, q.item_key, q.plu, q.item_name,
+                   q.quantity, q.unit_price_cents, q.reason, q.error, q.status,
+                   q.dish_id, q.created_at
+            FROM pos_review_queue q
+            JOIN pos_tickets t ON t.id = q.ticket_id
+            WHERE q.tenant_id = $1 AND q.status = $2
+            ORDER BY t.sold_at, q.created_at
+            LIMIT 500
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(status.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(PosReviewLine {
+                    id: row.try_get("id")?,
+                    external_ticket_id: row.try_get("external_ticket_id")?,
+                    sold_at: row.try_get("sold_at")?,
+                    item_key: row.try_get("item_key")?,
+                    plu: row.try_get("plu")?,
+                    item_name: row.try_get("item_name")?,
+                    quantity: row.try_get("quantity")?,
+                    unit_price_cents: row.try_get("unit_price_cents")?,
+                    reason: PosReviewReason::parse(row.try_get("reason")?)?,
+                    error: row.try_get("error")?,
+                    status: PosReviewStatus::parse(row.try_get("status")?)?,
+                    dish_id: row.try_get("dish_id")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Book a queued line as `dish_id`. With `remember`, the item is mapped
+    /// to the dish and its other queued unmapped lines are booked as well.
+    pub async fn resolve_review_line(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        line_id: Uuid,
+        input: ResolveReviewLineInput,
+    ) -> AppResult<PosReplaySummary> {
+        let row = sqlx::query(
+            "SELECT item_key, plu, item_name FROM pos_review_queue WHERE id = $1 AND tenant_id = $2 AND status = 'pending'",
+        )
+        .bind(line_id)
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Pending review line not found"))?;
+        self.ensure_dish(tenant_id, input.dish_id).await?;
+        let dish_id = DishId::from_uuid(input.dish_id);
+
+        let mut summary = PosReplaySummary::default();
+        if self
+            .book_queued_line(tenant_id, user_id, line_id, dish_id)
+            .await?
+        {
+            summary.lines_booked += 1;
+        } else {
+            summary.lines_failed += 1;
+        }
+
+        if input.remember {
+            let item_key: String = row.try_get("item_key")?;
+            let plu: Option<String> = row.try_get("plu")?;
+            let item_name: String = row.try_get("item_name")?;
+            let result = self
+                .upsert_mapping(
+                    tenant_id,
+                    user_id,
+                    UpsertPosMappingInput {
+                        plu: if item_key.starts_with("plu:") {
+                            plu
+                        } else {
+                            None
+                        },
+                        item_name: Some(item_name),
+                        dish_id: input.dish_id,
+                    },
+                )
+                .await?;
+            summary.lines_booked += result.lines_booked;
+            summary.lines_failed += result.lines_failed;
+        }
+
+        Ok(summary)
+    }
+
+    pub async fn ignore_review_line(&self, tenant_id: TenantId, line_id: Uuid) -> AppResult<()> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE pos_review_queue SET status = 'ignored', resolved_at = NOW()
+            WHERE id = $1 AND tenant_id = $2 AND status = 'pending'
+            "#,
+        )
+        .bind(line_id)
+        .bind(tenant_id.as_uuid())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(AppError::not_found("Pending review line not found"));
+        }
+        Ok(())
+    }
+
+    /// Book pending unmapped lines whose PLU or name resolves to `item_key`
+    async fn replay_unmapped(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        item_key: &str,
+        dish_id: DishId,
+    ) -> AppResult<PosReplaySummary> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, plu, item_name FROM pos_review_queue
+            WHERE tenant_id = $1 AND status = 'pending' AND reason = 'unmapped'
+            ORDER BY created_at
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut summary = PosReplaySummary::default();
+        for row in rows {
+            let plu: Option<String> = row.try_get("plu")?;
+            let item_name: String = row.try_get("item_name")?;
+            let by_plu = pos_item_key(plu.as_deref(), &item_name)?;
+            let by_name = pos_item_key(None, &item_name)?;
+            if by_plu != item_key && by_name != item_key {
+                continue;
+            }
+            if self
+                .book_queued_line(tenant_id, user_id, row.try_get("id")?, dish_id)
+                .await?
+            {
+                summary.lines_booked += 1;
+            } else {
+                summary.lines_failed += 1;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Claim a pending line, book it as `dish_id` and mark it booked, in one
+    /// transaction. A failed booking leaves the line pending as `failed`
+    /// with the error.
+    async fn book_queued_line(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        line_id: Uuid,
+        dish_id: DishId,
+    ) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
+        let claimed = sqlx::query(
+            r#"
+            UPDATE pos_review_queue q
+            SET status = 'booked', dish_id = $3, resolved_at = NOW()
+            FROM pos_tickets t
+            WHERE q.id = $1 AND q.tenant_id = $2 AND q.status = 'pending' AND t.id = q.ticket_id
+            RETURNING q.ticket_id, q.quantity, q.unit_price_cents, t.sold_at, t.location_id
+            "#,
+        )
+        .bind(line_id)
+        .bind(tenant_id.as_uuid())
+        .bind(dish_id.as_uuid())
+        .fetch_optional(&mut *tx)
+        .await?;
+        // Taken by a concurrent resolve
+        let Some(row) = claimed else {
+            return Ok(false);
+        };
+
+        let quantity: i32 = row.try_get("quantity")?;
+        let location_id: Option<Uuid> = row.try_get("location_id")?;
+        let sale = SaleRecord {
+            dish_id,
+            quantity: quantity as u32,
+            selling_price_cents: row.try_get("unit_price_cents")?,
+            location_id: location_id.map(StorageLocationId::from_uuid),
+            menu_section_id: None,
+            sold_at: Some(row.try_get("sold_at")?),
+            pos_ticket_id: Some(row.try_get("ticket_id")?),
+            modifier_option_ids: Vec::new(),
+        };
+        match self
+            .menu_engineering_service
+            .book_sale_in(&mut tx, tenant_id, user_id, sale)
+            .await
+        {
+            Ok(()) => {
+                tx.commit().await?;
+                Ok(true)
+            }
+            Err(e) => {
+                // Releases the claim together with the partial sale
+                tx.rollback().await?;
+                sqlx::query(
+                    r#"
+                    UPDATE pos_review_queue SET reason = 'failed', error = $2
+                    WHERE id = $1 AND status = 'pending'
+                    "#,
+                )
+                .bind(line_id)
+                .bind(e.to_string())
+                .execute(&self.pool)
+                .await?;
+                Ok(false)
+            }
+        }
+    }
+
+    async fn ensure_dish(&self, tenant_id: TenantId, dish_id: Uuid) -> AppResult<()> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM dishes WHERE id = $1 AND tenant_id = $2)",
+        )
+        .bind(dish_id)
+        .bind(tenant_id.as_uuid())
+        .fetch_one(&self.pool)
+        .await?;
+        if !exists {
+            return Err(AppError::not_found("Dish not found"));
+        }
+        Ok(())
+    }
+}
+
+/// Queue a ticket line for review inside the ticket's transaction
+async fn enqueue(
+    tx: &mut Transaction<'static, Postgres>,
+    tenant_id: TenantId,
+    ticket_uuid: Uuid,
+    line: &PosTicketLine,
+    reason: PosReviewReason,
+    error: Option<String>,
+) -> AppResult<()> {
+    let item_key = pos_item_key(line.plu.as_deref(), &line.item_name)?;
+    sqlx::query(
+        r#"
+        INSERT INTO pos_review_queue (
+            id, tenant_id, ticket_id, item_key, plu, item_name,
+            quantity, unit_price_cents, reason, error
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(tenant_id.as_uuid())
+    .bind(ticket_uuid)
+    .bind(item_key)
+    .bind(line.plu.as_deref().map(str::trim))
+    .bind(line.display_name())
+    .bind(line.quantity as i32)
+    .bind(line.unit_price_cents as i32)
+    .bind(reason.as_str())
+    .bind(error)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+fn mapping_from_row(row: &sqlx::postgres::PgRow) -> AppResult<PosItemMapping> {
+    Ok(PosItemMapping {
+        id: row.try_get("id")?,
+        item_key: row.try_get("item_key")?,
+        plu: row.try_get("plu")?,
+        item_name: row.try_get("item_name")?,
+        dish_id: row.try_get("dish_id")?,
+        dish_name: row.try_get("dish_name")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
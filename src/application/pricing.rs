@@ -0,0 +1,277 @@
+use crate::application::{DishService, MenuEngineeringService, RecipeService};
+use crate::domain::pricing::{
+    food_cost_percent, IngredientPriceChange, PriceEnding, PricingTarget,
+};
+use crate::domain::{CatalogIngredientId, Dish, DishId, MenuCategory, MenuEngineeringMatrix};
+use crate::shared::{AppError, AppResult, Language, PaginationParams, TenantId, UserId};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What-if pricing: proposes selling prices and re-costs dishes under
+/// hypothetical ingredient prices. Nothing is persisted.
+#[derive(Clone)]
+pub struct PricingService {
+    dish_service: DishService,
+    recipe_service: RecipeService,
+    menu_engineering_service: MenuEngineeringService,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceSimulationInput {
+    /// Dishes to simulate (empty = every active dish)
+    #[serde(default)]
+    pub dish_ids: Vec<DishId>,
+    /// Propose prices that meet this target (none = keep current prices)
+    pub target: Option<PricingTarget>,
+    #[serde(default)]
+    pub price_ending: PriceEnding,
+    #[serde(default)]
+    pub ingredient_changes: Vec<IngredientPriceChange>,
+    /// Sales volumes of the last N days drive the profit projection
+    #[serde(default = "default_period_days")]
+    pub period_days: u32,
+}
+
+fn default_period_days() -> u32 {
+    30
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DishPriceSimulation {
+    pub dish_id: DishId,
+    pub dish_name: String,
+    pub current_price_cents: i64,
+    pub proposed_price_cents: i64,
+    pub current_recipe_cost_cents: i64,
+    pub simulated_recipe_cost_cents: i64,
+    pub current_food_cost_percent: f64,
+    pub simulated_food_cost_percent: f64,
+    /// Recipe tree uses at least one of the changed ingredients
+    pub affected_by_ingredient_changes: bool,
+    /// `None` = not on an active menu and not sold in the period
+    pub current_category: Option<MenuCategory>,
+    pub simulated_category: Option<MenuCategory>,
+    pub units_sold: i64,
+    /// Units sold × margin per portion, before and after the change
+    pub current_profit_cents: i64,
+    pub projected_profit_cents: i64,
+    pub profit_delta_cents: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceSimulationResult {
+    pub period_days: u32,
+    pub dishes: Vec<DishPriceSimulation>,
+    /// Dishes whose recipe could not be costed
+    pub skipped_dishes: u32,
+    pub current_profit_cents: i64,
+    pub projected_profit_cents: i64,
+    pub profit_delta_cents: i64,
+}
+
+impl PricingService {
+    pub fn new(
+        dish_service: DishService,
+        recipe_service: RecipeService,
+        menu_engineering_service: MenuEngineeringService,
+    ) -> Self {
+        Self {
+            dish_service,
+            recipe_service,
+            menu_engineering_service,
+        }
+    }
+
+    /// 🔒 TENANT ISOLATION: Simulate prices and ingredient price changes.
+    ///
+    /// Profit is projected at constant volume: the units each dish sold in
+    /// the last `period_days` days, at the current and at the simulated
+    /// margin per portion. Menu engineering is re-run with the simulated
+    /// margins to show how each dish's category would move.
+    pub async fn simulate(
+        &self,
+        user_id: UserId,
+        tenant_id: TenantId,
+        language: Language,
+        input: PriceSimulationInput,
+    ) -> AppResult<PriceSimulationResult> {
+        if let Some(target) = &input.target {
+            target.validate()?;
+        }
+        let mut price_factors: HashMap<CatalogIngredientId, Decimal> = HashMap::new();
+        for change in &input.ingredient_changes {
+            price_factors.insert(change.catalog_ingredient_id, change.factor()?);
+        }
+
+        let dishes = self.load_dishes(tenant_id, &input.dish_ids).await?;
+
+        let mut simulations = Vec::with_capacity(dishes.len());
+        let mut skipped_dishes = 0u32;
+        for dish in dishes {
+            let (current, simulated) = match self
+                .recipe_service
+                .simulate_cost(dish.recipe_id(), tenant_id, &price_factors)
+                .await
+            {
+                Ok(costs) => costs,
+                Err(e) => {
+                    tracing::warn!(
+                        "⚠️ Price simulation skipped dish '{}' ({}): {}",
+                        dish.name().as_str(),
+                        dish.id().as_uuid(),
+                        e
+                    );
+                    skipped_dishes += 1;
+                    continue;
+                }
+            };
+
+            let current_price = dish.selling_price().as_cents();
+            let current_cost = current.cost_per_serving.as_cents();
+            let affected = simulated.is_some();
+            let simulated_cost = simulated
+                .map(|cost| cost.cost_per_serving.as_cents())
+                .unwrap_or(current_cost);
+
+            let base_price = input
+                .target
+                .map(|target| target.price_for_cost(simulated_cost))
+                .unwrap_or(current_price);
+            let proposed_price = input.price_ending.apply(base_price);
+
+            simulations.push(DishPriceSimulation {
+                dish_id: dish.id(),
+                dish_name: dish.name().as_str().to_string(),
+                current_price_cents: current_price,
+                proposed_price_cents: proposed_price,
+                current_recipe_cost_cents: current_cost,
+                simulated_recipe_cost_cents: simulated_cost,
+                current_food_cost_percent: food_cost_percent(current_cost, current_price),
+                simulated_food_cost_percent: food_cost_percent(simulated_cost, proposed_price),
+                affected_by_ingredient_changes: affected,
+                current_category: None,
+                simulated_category: None,
+                units_sold: 0,
+                current_profit_cents: 0,
+                projected_profit_cents: 0,
+                profit_delta_cents: 0,
+            });
+        }
+
+        // Current matrix: sales volumes and categories of every analysed dish
+        let matrix = self
+            .menu_engineering_service
+            .analyze_menu(user_id, tenant_id, language, input.period_days, None)
+            .await?;
+
+        let mut units_sold: HashMap<DishId, i64> = HashMap::new();
+        let mut current_categories: HashMap<DishId, MenuCategory> = HashMap::new();
+        for perf in &matrix.dishes {
+            *units_sold.entry(perf.dish_id).or_default() += perf.sales_volume as i64;
+            current_categories
+                .entry(perf.dish_id)
+                .or_insert(perf.category);
+        }
+
+        // Re-run the matrix with simulated margins for the changed dishes
+        let changed: HashMap<DishId, (i64, i64)> = simulations
+            .iter()
+            .filter(|s| {
+                s.proposed_price_cents != s.current_price_cents
+                    || s.simulated_recipe_cost_cents != s.current_recipe_cost_cents
+            })
+            .map(|s| {
+                (
+                    s.dish_id,
+                    (s.proposed_price_cents, s.simulated_recipe_cost_cents),
+                )
+            })
+            .collect();
+        let mut performances = matrix.dishes;
+        for perf in performances.iter_mut() {
+            if let Some(&(price, cost)) = changed.get(&perf.dish_id) {
+                let margin = price - cost;
+                let volume = perf.sales_volume as i64;
+                perf.contribution_margin_per_item_cents = margin;
+                perf.total_revenue_cents = price * volume;
+                perf.total_profit_cents = margin * volume;
+                perf.contribution_margin_cents = margin * volume;
+                perf.profit_margin_percent = 100.0 - food_cost_percent(cost, price);
+            }
+        }
+        let simulated_matrix = MenuEngineeringMatrix::analyze(performances, language);
+        let mut simulated_categories: HashMap<DishId, MenuCategory> = HashMap::new();
+        for perf in &simulated_matrix.dishes {
+            simulated_categories
+                .entry(perf.dish_id)
+                .or_insert(perf.category);
+        }
+
+        for simulation in simulations.iter_mut() {
+            let units = units_sold.get(&simulation.dish_id).copied().unwrap_or(0);
+            simulation.units_sold = units;
+            simulation.current_profit_cents =
+                units * (simulation.current_price_cents - simulation.current_recipe_cost_cents);
+            simulation.projected_profit_cents =
+                units * (simulation.proposed_price_cents - simulation.simulated_recipe_cost_cents);
+            simulation.profit_delta_cents =
+                simulation.projected_profit_cents - simulation.current_profit_cents;
+            simulation.current_category = current_categories.get(&simulation.dish_id).copied();
+            simulation.simulated_category = simulated_categories.get(&simulation.dish_id).copied();
+        }
+
+        // Biggest profit swings first
+        simulations.sort_by_key(|s| std::cmp::Reverse(s.profit_delta_cents.abs()));
+
+        let current_profit_cents = simulations.iter().map(|s| s.current_profit_cents).sum();
+        let projected_profit_cents = simulations.iter().map(|s| s.projected_profit_cents).sum();
+
+        Ok(PriceSimulationResult {
+            period_days: input.period_days,
+            dishes: simulations,
+            skipped_dishes,
+            current_profit_cents,
+            projected_profit_cents,
+            profit_delta_cents: projected_profit_cents - current_profit_cents,
+        })
+    }
+
+    /// Requested dishes, or every active dish of the tenant
+    async fn load_dishes(&self, tenant_id: TenantId, dish_ids: &[DishId]) -> AppResult<Vec<Dish>> {
+        if !dish_ids.is_empty() {
+            let mut dishes = Vec::with_capacity(dish_ids.len());
+            for &dish_id in dish_ids {
+                let dish = self
+                    .dish_service
+                    .get_dish(dish_id, tenant_id)
+                    .await?
+                    .ok_or_else(|| AppError::not_found("Dish not found"))?;
+                dishes.push(dish);
+            }
+            return Ok(dishes);
+        }
+
+        let mut dishes = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let pagination = PaginationParams {
+                page: Some(page),
+                per_page: Some(100),
+            };
+            let (batch, total) = self
+                .dish_service
+                .list_dishes(tenant_id, true, &pagination)
+                .await?;
+            if batch.is_empty() {
+                break;
+            }
+            dishes.extend(batch);
+            if (page as i64 * 100) >= total {
+                break;
+            }
+            page += 1;
+        }
+        Ok(dishes)
+    }
+}
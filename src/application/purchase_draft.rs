@@ -1,12 +1,14 @@
 //! PurchaseDraftService — создание/чтение/отмена заготовок закупок.
 //!
 //! Используется Copilot-ом для tool `prepare_purchase_draft`.
-//! Жизненный цикл: draft → (sent | cancelled).
+//! Жизненный цикл: draft → (sent | cancelled) → (partially_received →) received.
+//! Приёмка товара — `PurchaseReceiptService`.
 
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::application::audit::{AuditAction, AuditEntityType, AuditEvent, AuditLog};
 use crate::shared::{AppError, AppResult, TenantId, UserId};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +22,8 @@ pub struct PurchaseDraftItemInput {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreatePurchaseDraftInput {
+    #[serde(default)]
+    pub supplier_id: Option<Uuid>,
     pub supplier_name: Option<String>,
     pub delivery_date: Option<time::Date>,
     pub note: Option<String>,
@@ -31,6 +35,7 @@ pub struct PurchaseDraft {
     pub id: Uuid,
     pub user_id: Uuid,
     pub tenant_id: Uuid,
+    pub supplier_id: Option<Uuid>,
     pub supplier_name: Option<String>,
     pub delivery_date: Option<time::Date>,
     pub note: Option<String>,
@@ -48,15 +53,21 @@ pub struct PurchaseDraftItem {
     pub quantity: f64,
     pub unit: String,
     pub price_per_unit_cents: Option<i64>,
+    /// Delivered so far (goods-received notes)
+    pub received_quantity: f64,
 }
 
 pub struct PurchaseDraftService {
     pool: PgPool,
+    audit: AuditLog,
 }
 
 impl PurchaseDraftService {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            audit: AuditLog::new(pool.clone()),
+            pool,
+        }
     }
 
     /// Создать новый draft с позициями.
@@ -89,8 +100,8 @@ impl PurchaseDraftService {
 
         sqlx::query(
             "INSERT INTO purchase_drafts \
-             (id, user_id, tenant_id, supplier_name, delivery_date, note, status, total_cost_cents) \
-             VALUES ($1, $2, $3, $4, $5, $6, 'draft', $7)"
+             (id, user_id, tenant_id, supplier_name, delivery_date, note, status, total_cost_cents, supplier_id) \
+             VALUES ($1, $2, $3, $4, $5, $6, 'draft', $7, $8)"
         )
         .bind(draft_id)
         .bind(uid)
@@ -99,6 +110,7 @@ impl PurchaseDraftService {
         .bind(input.delivery_date)
         .bind(&input.note)
         .bind(total)
+        .bind(input.supplier_id)
         .execute(&mut *tx)
         .await?;
 
@@ -121,6 +133,27 @@ impl PurchaseDraftService {
 
         tx.commit().await?;
 
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::PurchaseDraft,
+                    draft_id,
+                    AuditAction::Create,
+                )
+                .after(serde_json::json!({
+                    "supplier_id": input.supplier_id,
+                    "supplier_name": input.supplier_name,
+                    "delivery_date": input.delivery_date,
+                    "note": input.note,
+                    "status": "draft",
+                    "total_cost_cents": total,
+                    "items": input.items,
+                })),
+            )
+            .await;
+
         tracing::info!(
             "✅ Purchase draft created: id={} items={} supplier={:?}",
             draft_id,
@@ -136,7 +169,7 @@ impl PurchaseDraftService {
         let uid = *user_id.as_uuid();
 
         let row: Option<DraftRow> = sqlx::query_as::<_, DraftRow>(
-            "SELECT id, user_id, tenant_id, supplier_name, delivery_date, note, status, total_cost_cents, created_at \
+            "SELECT id, user_id, tenant_id, supplier_id, supplier_name, delivery_date, note, status, total_cost_cents, created_at \
              FROM purchase_drafts WHERE id = $1 AND user_id = $2"
         )
         .bind(draft_id)
@@ -149,7 +182,7 @@ impl PurchaseDraftService {
         };
 
         let items: Vec<ItemRow> = sqlx::query_as::<_, ItemRow>(
-            "SELECT id, catalog_ingredient_id, ingredient_name, quantity, unit, price_per_unit_cents \
+            "SELECT id, catalog_ingredient_id, ingredient_name, quantity, unit, price_per_unit_cents, received_quantity \
              FROM purchase_draft_items WHERE draft_id = $1 ORDER BY created_at"
         )
         .bind(draft_id)
@@ -160,6 +193,7 @@ impl PurchaseDraftService {
             id: r.id,
             user_id: r.user_id,
             tenant_id: r.tenant_id,
+            supplier_id: r.supplier_id,
             supplier_name: r.supplier_name,
             delivery_date: r.delivery_date,
             note: r.note,
@@ -175,6 +209,7 @@ impl PurchaseDraftService {
                     quantity: i.quantity,
                     unit: i.unit,
                     price_per_unit_cents: i.price_per_unit_cents,
+                    received_quantity: i.received_quantity,
                 })
                 .collect(),
         }))
@@ -184,7 +219,7 @@ impl PurchaseDraftService {
     pub async fn list(&self, tenant_id: TenantId, limit: i64) -> AppResult<Vec<PurchaseDraft>> {
         let tid = *tenant_id.as_uuid();
         let drafts: Vec<DraftRow> = sqlx::query_as::<_, DraftRow>(
-            "SELECT id, user_id, tenant_id, supplier_name, delivery_date, note, status, total_cost_cents, created_at \
+            "SELECT id, user_id, tenant_id, supplier_id, supplier_name, delivery_date, note, status, total_cost_cents, created_at \
              FROM purchase_drafts WHERE tenant_id = $1 ORDER BY created_at DESC LIMIT $2"
         )
         .bind(tid)
@@ -195,7 +230,7 @@ impl PurchaseDraftService {
         let mut out = Vec::with_capacity(drafts.len());
         for r in drafts {
             let items: Vec<ItemRow> = sqlx::query_as::<_, ItemRow>(
-                "SELECT id, catalog_ingredient_id, ingredient_name, quantity, unit, price_per_unit_cents \
+                "SELECT id, catalog_ingredient_id, ingredient_name, quantity, unit, price_per_unit_cents, received_quantity \
                  FROM purchase_draft_items WHERE draft_id = $1 ORDER BY created_at"
             )
             .bind(r.id)
@@ -206,6 +241,7 @@ impl PurchaseDraftService {
                 id: r.id,
                 user_id: r.user_id,
                 tenant_id: r.tenant_id,
+                supplier_id: r.supplier_id,
                 supplier_name: r.supplier_name,
                 delivery_date: r.delivery_date,
                 note: r.note,
@@ -221,6 +257,7 @@ impl PurchaseDraftService {
                         quantity: i.quantity,
                         unit: i.unit,
                         price_per_unit_cents: i.price_per_unit_cents,
+                        received_quantity: i.received_quantity,
                     })
                     .collect(),
             });
@@ -265,6 +302,20 @@ impl PurchaseDraftService {
             ));
         }
 
+        self.audit
+            .record(
+                AuditEvent::new(
+                    TenantId::from_uuid(d.tenant_id),
+                    user_id,
+                    AuditEntityType::PurchaseDraft,
+                    draft_id,
+                    AuditAction::Send,
+                )
+                .before(serde_json::json!({ "status": d.status }))
+                .after(serde_json::json!({ "status": "sent" })),
+            )
+            .await;
+
         tracing::info!(
             "📤 Purchase draft {} marked as sent ({} items)",
             draft_id,
@@ -272,6 +323,63 @@ impl PurchaseDraftService {
         );
         Ok((draft_id, d.supplier_name, d.items.len()))
     }
+
+    /// Перевести draft → cancelled. Только для ещё не отправленных.
+    pub async fn cancel(&self, draft_id: Uuid, user_id: UserId) -> AppResult<()> {
+        self.transition(draft_id, user_id, "draft", "cancelled")
+            .await
+    }
+
+    /// Вернуть отправленный заказ в draft (пока по нему ничего не принято).
+    pub async fn reopen(&self, draft_id: Uuid, user_id: UserId) -> AppResult<()> {
+        self.transition(draft_id, user_id, "sent", "draft").await
+    }
+
+    async fn transition(
+        &self,
+        draft_id: Uuid,
+        user_id: UserId,
+        from: &str,
+        to: &str,
+    ) -> AppResult<()> {
+        let Some(d) = self.get(draft_id, user_id).await? else {
+            return Err(AppError::not_found("Purchase draft not found"));
+        };
+
+        let rows = sqlx::query(
+            "UPDATE purchase_drafts SET status = $3, updated_at = now() \
+             WHERE id = $1 AND user_id = $2 AND status = $4",
+        )
+        .bind(draft_id)
+        .bind(*user_id.as_uuid())
+        .bind(to)
+        .bind(from)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if rows == 0 {
+            return Err(AppError::conflict(format!(
+                "Purchase draft status is '{}', expected '{}'",
+                d.status, from
+            )));
+        }
+
+        self.audit
+            .record(
+                AuditEvent::new(
+                    TenantId::from_uuid(d.tenant_id),
+                    user_id,
+                    AuditEntityType::PurchaseDraft,
+                    draft_id,
+                    AuditAction::Update,
+                )
+                .before(serde_json::json!({ "status": from }))
+                .after(serde_json::json!({ "status": to })),
+            )
+            .await;
+        Ok(())
+    }
 }
 
 #[derive(sqlx::FromRow)]
@@ -279,6 +387,7 @@ struct DraftRow {
     id: Uuid,
     user_id: Uuid,
     tenant_id: Uuid,
+    supplier_id: Option<Uuid>,
     supplier_name: Option<String>,
     delivery_date: Option<time::Date>,
     note: Option<String>,
@@ -295,4 +404,5 @@ struct ItemRow {
     quantity: f64,
     unit: String,
     price_per_unit_cents: Option<i64>,
+    received_quantity: f64,
 }
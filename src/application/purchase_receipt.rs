@@ -0,0 +1,493 @@
+//! Goods-received notes for purchase drafts.
+//!
+//! Receiving a draft records actual quantities, prices, invoice and expiry per item,
+//! creates inventory batches with IN movements that reference the draft, flags
+//! over/under deliveries and price deviations, and moves the draft to
+//! `partially_received` or `received`.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::application::inventory::ensure_active_location;
+use crate::application::purchase_draft::{PurchaseDraft, PurchaseDraftService};
+use crate::domain::{
+    catalog::CatalogIngredientId,
+    inventory::{InventoryBatch, InventoryMovement, Money, MovementType, Quantity},
+    purchase_receipt::{
+        evaluate_line, price_deviation_percent, received_status, ReceiptLineFlag,
+        RECEIVABLE_STATUSES,
+    },
+    storage_location::StorageLocationId,
+    supplier::SupplierId,
+};
+use crate::infrastructure::persistence::{InventoryBatchRepository, InventoryBatchRepositoryTrait};
+use crate::shared::{AppError, AppResult, TenantId, UserId};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceiveItemInput {
+    /// Purchase draft item ID
+    pub item_id: Uuid,
+    pub received_quantity: f64,
+    /// Actual invoice price; ordered price when omitted
+    pub price_per_unit_cents: Option<i64>,
+    /// Defaults to received_at + tenant/catalog shelf life
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceivePurchaseDraftInput {
+    pub invoice_number: Option<String>,
+    pub note: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub received_at: Option<OffsetDateTime>,
+    /// Storage location receiving the goods (tenant default when omitted)
+    #[serde(default)]
+    pub location_id: Option<Uuid>,
+    pub items: Vec<ReceiveItemInput>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PurchaseReceiptLine {
+    pub id: Uuid,
+    pub draft_item_id: Uuid,
+    pub catalog_ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub batch_id: Option<Uuid>,
+    pub expected_quantity: f64,
+    pub received_quantity: f64,
+    pub ordered_price_cents: Option<i64>,
+    pub price_per_unit_cents: i64,
+    pub price_deviation_percent: Option<f64>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    pub flags: Vec<ReceiptLineFlag>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PurchaseReceipt {
+    pub id: Uuid,
+    pub draft_id: Uuid,
+    /// Draft status after this receipt
+    pub draft_status: String,
+    pub invoice_number: Option<String>,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub received_at: OffsetDateTime,
+    pub total_cost_cents: i64,
+    pub flagged_lines: usize,
+    pub lines: Vec<PurchaseReceiptLine>,
+}
+
+struct DraftItemState {
+    catalog_ingredient_id: Option<Uuid>,
+    ingredient_name: String,
+    quantity: f64,
+    received_quantity: f64,
+    price_per_unit_cents: Option<i64>,
+    shelf_life_days: Option<i32>,
+}
+
+#[derive(Clone)]
+pub struct PurchaseReceiptService {
+    pool: PgPool,
+    inventory_repo: Arc<InventoryBatchRepository>,
+    drafts: Arc<PurchaseDraftService>,
+}
+
+impl PurchaseReceiptService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            inventory_repo: Arc::new(InventoryBatchRepository::new(pool.clone())),
+            drafts: Arc::new(PurchaseDraftService::new(pool.clone())),
+            pool,
+        }
+    }
+
+    /// Purchase drafts of the tenant (newest first)
+    pub async fn list_drafts(
+        &self,
+        tenant_id: TenantId,
+        limit: i64,
+    ) -> AppResult<Vec<PurchaseDraft>> {
+        self.drafts.list(tenant_id, limit).await
+    }
+
+    /// Receive goods against a purchase draft (goods-received note)
+    pub async fn receive(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        draft_id: Uuid,
+        input: ReceivePurchaseDraftInput,
+    ) -> AppResult<PurchaseReceipt> {
+        if input.items.is_empty() {
+            return Err(AppError::validation(
+                "Receipt must contain at least one item",
+            ));
+        }
+        let received_at = input.received_at.unwrap_or_else(OffsetDateTime::now_utc);
+
+        let location_id = input.location_id.map(StorageLocationId::from_uuid);
+
+        let mut tx = self.pool.begin().await?;
+
+        if let Some(location) = location_id {
+            ensure_active_location(&mut tx, tenant_id, location).await?;
+        }
+
+        // Lock the draft so concurrent receipts cannot double-count
+        let draft = sqlx::query(
+            "SELECT status, supplier_id, supplier_name FROM purchase_drafts
+             WHERE id = $1 AND tenant_id = $2 FOR UPDATE",
+        )
+        .bind(draft_id)
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("Purchase draft not found"))?;
+
+        let status: String = draft.try_get("status")?;
+        if !RECEIVABLE_STATUSES.contains(&status.as_str()) {
+            return Err(AppError::validation(format!(
+                "Cannot receive purchase draft: current status is '{}'",
+                status
+            )));
+        }
+        let supplier_id: Option<Uuid> = draft.try_get("supplier_id")?;
+        let supplier_name: Option<String> = draft.try_get("supplier_name")?;
+
+        let item_rows = sqlx::query(
+            r#"
+            SELECT pdi.id, pdi.catalog_ingredient_id, pdi.ingredient_name, pdi.quantity,
+                   pdi.received_quantity, pdi.price_per_unit_cents,
+                   COALESCE(ti.custom_expiration_days, ci.default_shelf_life_days) AS shelf_life_days
+            FROM purchase_draft_items pdi
+            LEFT JOIN catalog_ingredients ci ON ci.id = pdi.catalog_ingredient_id
+            LEFT JOIN tenant_ingredients ti ON ti.catalog_ingredient_id = pdi.catalog_ingredient_id
+                AND ti.tenant_id = $2 AND ti.is_active = true
+            WHERE pdi.draft_id = $1
+            "#,
+        )
+        .bind(draft_id)
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut items: HashMap<Uuid, DraftItemState> = HashMap::with_capacity(item_rows.len());
+        for row in &item_rows {
+            items.insert(
+                row.try_get("id")?,
+                DraftItemState {
+                    catalog_ingredient_id: row.try_get("catalog_ingredient_id")?,
+                    ingredient_name: row.try_get("ingredient_name")?,
+                    quantity: row.try_get("quantity")?,
+                    received_quantity: row.try_get("received_quantity")?,
+                    price_per_unit_cents: row.try_get("price_per_unit_cents")?,
+                    shelf_life_days: row.try_get("shelf_life_days")?,
+                },
+            );
+        }
+
+        let receipt_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO purchase_receipts
+             (id, tenant_id, draft_id, user_id, invoice_number, note, received_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(receipt_id)
+        .bind(tenant_id.as_uuid())
+        .bind(draft_id)
+        .bind(user_id.as_uuid())
+        .bind(&input.invoice_number)
+        .bind(&input.note)
+        .bind(received_at)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut lines = Vec::with_capacity(input.items.len());
+        let mut total_cost_cents = 0i64;
+
+        for line in &input.items {
+            if line.received_quantity < 0.0 {
+                return Err(AppError::validation("Received quantity cannot be negative"));
+            }
+            let item = items
+                .get_mut(&line.item_id)
+                .ok_or_else(|| AppError::not_found("Purchase draft item not found"))?;
+            let catalog_ingredient_id = item.catalog_ingredient_id.ok_or_else(|| {
+                AppError::validation(format!(
+                    "Item '{}' is not linked to a catalog ingredient",
+                    item.ingredient_name
+                ))
+            })?;
+            let price_per_unit_cents = line
+                .price_per_unit_cents
+                .or(item.price_per_unit_cents)
+                .ok_or_else(|| {
+                    AppError::validation(format!(
+                        "Price is required for item '{}'",
+                        item.ingredient_name
+                    ))
+                })?;
+
+            let expected_quantity = (item.quantity - item.received_quantity).max(0.0);
+            let flags = evaluate_line(
+                expected_quantity,
+                line.received_quantity,
+                item.price_per_unit_cents,
+                price_per_unit_cents,
+            );
+
+            let mut batch_id = None;
+            let mut expires_at = None;
+            if line.received_quantity > 0.0 {
+                let expiry = match (line.expires_at, item.shelf_life_days) {
+                    (Some(at), _) => at,
+                    (None, Some(days)) => received_at + Duration::days(days as i64),
+                    (None, None) => {
+                        return Err(AppError::validation(format!(
+                            "Expiry date is required for item '{}'",
+                            item.ingredient_name
+                        )))
+                    }
+                };
+                expires_at = Some(expiry);
+
+                let price = Money::from_cents(price_per_unit_cents)?;
+                let qty = Quantity::new(line.received_quantity)?;
+                let mut batch = InventoryBatch::new(
+                    user_id,
+                    tenant_id,
+                    CatalogIngredientId::from_uuid(catalog_ingredient_id),
+                    price,
+                    qty,
+                    received_at,
+                    expiry,
+                );
+                batch.supplier = supplier_name.clone();
+                batch.supplier_id = supplier_id.map(SupplierId::from_uuid);
+                batch.invoice_number = input.invoice_number.clone();
+                batch.location_id = location_id;
+
+                self.inventory_repo
+                    .create_in_transaction(&mut tx, &batch)
+                    .await?;
+
+                let mut movement = InventoryMovement::new(
+                    tenant_id,
+                    batch.id,
+                    MovementType::In,
+                    qty.decimal(),
+                    price.as_cents(),
+                );
+                movement.reference_id = Some(draft_id);
+                movement.reference_type = Some("purchase_draft".to_string());
+                movement.reason = Some("Goods received".to_string());
+                movement.notes = input.invoice_number.clone();
+                total_cost_cents += movement.total_cost_cents;
+
+                self.inventory_repo
+                    .record_movement(&mut tx, &movement)
+                    .await?;
+                self.inventory_repo
+                    .record_price_point(&mut tx, &batch)
+                    .await?;
+
+                batch_id = Some(batch.id.as_uuid());
+            }
+
+            item.received_quantity += line.received_quantity;
+            sqlx::query("UPDATE purchase_draft_items SET received_quantity = $2 WHERE id = $1")
+                .bind(line.item_id)
+                .bind(item.received_quantity)
+                .execute(&mut *tx)
+                .await?;
+
+            let flag_strs: Vec<&str> = flags.iter().map(|f| f.as_str()).collect();
+            let line_id = Uuid::new_v4();
+            sqlx::query(
+                r#"
+                INSERT INTO purchase_receipt_lines (
+                    id, receipt_id, draft_item_id, catalog_ingredient_id, batch_id,
+                    expected_quantity, received_quantity, ordered_price_cents,
+                    price_per_unit_cents, expires_at, flags
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                "#,
+            )
+            .bind(line_id)
+            .bind(receipt_id)
+            .bind(line.item_id)
+            .bind(catalog_ingredient_id)
+            .bind(batch_id)
+            .bind(expected_quantity)
+            .bind(line.received_quantity)
+            .bind(item.price_per_unit_cents)
+            .bind(price_per_unit_cents)
+            .bind(expires_at)
+            .bind(&flag_strs)
+            .execute(&mut *tx)
+            .await?;
+
+            lines.push(PurchaseReceiptLine {
+                id: line_id,
+                draft_item_id: line.item_id,
+                catalog_ingredient_id,
+                ingredient_name: item.ingredient_name.clone(),
+                batch_id,
+                expected_quantity,
+                received_quantity: line.received_quantity,
+                ordered_price_cents: item.price_per_unit_cents,
+                price_per_unit_cents,
+                price_deviation_percent: price_deviation_percent(
+                    item.price_per_unit_cents,
+                    price_per_unit_cents,
+                ),
+                expires_at,
+                flags,
+            });
+        }
+
+        let progress: Vec<(f64, f64)> = items
+            .values()
+            .map(|i| (i.quantity, i.received_quantity))
+            .collect();
+        let draft_status = received_status(&progress);
+
+        sqlx::query(
+            "UPDATE purchase_drafts
+             SET status = $2,
+                 received_at = CASE WHEN $2 = 'received' THEN $3 ELSE received_at END,
+                 updated_at = now()
+             WHERE id = $1",
+        )
+        .bind(draft_id)
+        .bind(draft_status.as_str())
+        .bind(received_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let flagged_lines = lines.iter().filter(|l| !l.flags.is_empty()).count();
+        tracing::info!(
+            "📦 Purchase draft {} received: {} line(s), {} flagged, status={}",
+            draft_id,
+            lines.len(),
+            flagged_lines,
+            draft_status.as_str()
+        );
+
+        Ok(PurchaseReceipt {
+            id: receipt_id,
+            draft_id,
+            draft_status: draft_status.as_str().to_string(),
+            invoice_number: input.invoice_number,
+            note: input.note,
+            received_at,
+            total_cost_cents,
+            flagged_lines,
+            lines,
+        })
+    }
+
+    /// Receipts recorded against a draft (oldest first)
+    pub async fn list_receipts(
+        &self,
+        tenant_id: TenantId,
+        draft_id: Uuid,
+    ) -> AppResult<Vec<PurchaseReceipt>> {
+        let draft_status: String = sqlx::query_scalar(
+            "SELECT status FROM purchase_drafts WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(draft_id)
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Purchase draft not found"))?;
+
+        let receipt_rows = sqlx::query(
+            "SELECT id, invoice_number, note, received_at FROM purchase_receipts
+             WHERE draft_id = $1 AND tenant_id = $2 ORDER BY received_at, created_at",
+        )
+        .bind(draft_id)
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let line_rows = sqlx::query(
+            r#"
+            SELECT l.id, l.receipt_id, l.draft_item_id, l.catalog_ingredient_id,
+                   pdi.ingredient_name, l.batch_id, l.expected_quantity, l.received_quantity,
+                   l.ordered_price_cents, l.price_per_unit_cents, l.expires_at, l.flags
+            FROM purchase_receipt_lines l
+            JOIN purchase_receipts r ON r.id = l.receipt_id
+            JOIN purchase_draft_items pdi ON pdi.id = l.draft_item_id
+            WHERE r.draft_id = $1 AND r.tenant_id = $2
+            "#,
+        )
+        .bind(draft_id)
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut lines_by_receipt: HashMap<Uuid, Vec<PurchaseReceiptLine>> = HashMap::new();
+        for row in line_rows {
+            let flags: Vec<String> = row.try_get("flags")?;
+            let ordered_price_cents: Option<i64> = row.try_get("ordered_price_cents")?;
+            let price_per_unit_cents: i64 = row.try_get("price_per_unit_cents")?;
+            lines_by_receipt
+                .entry(row.try_get("receipt_id")?)
+                .or_default()
+                .push(PurchaseReceiptLine {
+                    id: row.try_get("id")?,
+                    draft_item_id: row.try_get("draft_item_id")?,
+                    catalog_ingredient_id: row.try_get("catalog_ingredient_id")?,
+                    ingredient_name: row.try_get("ingredient_name")?,
+                    batch_id: row.try_get("batch_id")?,
+                    expected_quantity: row.try_get("expected_quantity")?,
+                    received_quantity: row.try_get("received_quantity")?,
+                    ordered_price_cents,
+                    price_per_unit_cents,
+                    price_deviation_percent: price_deviation_percent(
+                        ordered_price_cents,
+                        price_per_unit_cents,
+                    ),
+                    expires_at: row.try_get("expires_at")?,
+                    flags: flags
+                        .iter()
+                        .filter_map(|f| ReceiptLineFlag::parse(f))
+                        .collect(),
+                });
+        }
+
+        let mut receipts = Vec::with_capacity(receipt_rows.len());
+        for row in receipt_rows {
+            let id: Uuid = row.try_get("id")?;
+            let lines = lines_by_receipt.remove(&id).unwrap_or_default();
+            let total_cost_cents = lines
+                .iter()
+                .filter(|l| l.batch_id.is_some())
+                .map(|l| (l.received_quantity * l.price_per_unit_cents as f64).round() as i64)
+                .sum();
+            receipts.push(PurchaseReceipt {
+                id,
+                draft_id,
+                draft_status: draft_status.clone(),
+                invoice_number: row.try_get("invoice_number")?,
+                note: row.try_get("note")?,
+                received_at: row.try_get("received_at")?,
+                total_cost_cents,
+                flagged_lines: lines.iter().filter(|l| !l.flags.is_empty()).count(),
+                lines,
+            });
+        }
+
+        Ok(receipts)
+    }
+}
@@ -1,6 +1,9 @@
+use crate::application::audit::{AuditAction, AuditEntityType, AuditEvent, AuditLog};
 use crate::domain::{
-    CatalogIngredientId, IngredientCost, Money, Quantity, Recipe, RecipeCost, RecipeId,
-    RecipeIngredient, RecipeName, RecipeType, Servings,
+    inventory::CostingStrategy,
+    recipe::{gross_quantity, validate_component_graph, MAX_COMPONENT_DEPTH},
+    CatalogIngredientId, ComponentCost, IngredientCost, Money, Quantity, Recipe, RecipeCost,
+    RecipeId, RecipeIngredient, RecipeName, RecipeType, Servings,
 };
 use crate::infrastructure::persistence::{
     CatalogIngredientRepositoryTrait, InventoryBatchRepositoryTrait, RecipeRepositoryTrait,
@@ -8,6 +11,7 @@ use crate::infrastructure::persistence::{
 use crate::shared::{
     AppError, AppResult, Language, PaginatedResponse, PaginationParams, TenantId, UserId,
 };
+use rust_decimal::Decimal;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -16,6 +20,7 @@ pub struct RecipeService {
     recipe_repo: Arc<dyn RecipeRepositoryTrait>,
     inventory_repo: Arc<dyn InventoryBatchRepositoryTrait>,
     catalog_repo: Arc<dyn CatalogIngredientRepositoryTrait>,
+    audit: AuditLog,
 }
 
 impl RecipeService {
@@ -23,11 +28,13 @@ impl RecipeService {
         recipe_repo: Arc<dyn RecipeRepositoryTrait>,
         inventory_repo: Arc<dyn InventoryBatchRepositoryTrait>,
         catalog_repo: Arc<dyn CatalogIngredientRepositoryTrait>,
+        audit: AuditLog,
     ) -> Self {
         Self {
             recipe_repo,
             inventory_repo,
             catalog_repo,
+            audit,
         }
     }
 
@@ -84,7 +91,24 @@ impl RecipeService {
             None, // Instructions not provided via simple V1 API
         )?;
 
+        // Reject too deep component nesting before persisting
+        if !recipe.components().is_empty() {
+            load_component_graph(self.recipe_repo.as_ref(), recipe.clone(), tenant_id).await?;
+        }
+
         self.recipe_repo.create(&recipe, user_id, tenant_id).await?;
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::Recipe,
+                    recipe.id().as_uuid(),
+                    AuditAction::Create,
+                )
+                .after(audit_snapshot(&recipe)),
+            )
+            .await;
         Ok(recipe)
     }
 
@@ -103,59 +127,126 @@ impl RecipeService {
     }
 
     /// 🔒 TENANT ISOLATION: Delete recipe within tenant
-    pub async fn delete_recipe(&self, id: RecipeId, tenant_id: TenantId) -> AppResult<bool> {
-        self.recipe_repo.delete(id, tenant_id).await
+    pub async fn delete_recipe(
+        &self,
+        id: RecipeId,
+        tenant_id: TenantId,
+        user_id: UserId,
+    ) -> AppResult<bool> {
+        let Some(recipe) = self.recipe_repo.find_by_id(id, tenant_id).await? else {
+            return Ok(false);
+        };
+        let deleted = self.recipe_repo.delete(id, tenant_id).await?;
+        if deleted {
+            self.audit
+                .record(
+                    AuditEvent::new(
+                        tenant_id,
+                        user_id,
+                        AuditEntityType::Recipe,
+                        id.as_uuid(),
+                        AuditAction::Delete,
+                    )
+                    .before(audit_snapshot(&recipe)),
+                )
+                .await;
+        }
+        Ok(deleted)
     }
 
     /// 🔒 TENANT ISOLATION: Calculate recipe cost within tenant
+    /// Component recipes (preps) are costed recursively.
+    /// Ingredients are costed at gross (as-purchased) quantity using their edible yield.
     pub async fn calculate_cost(
         &self,
         recipe_id: RecipeId,
         tenant_id: TenantId,
     ) -> AppResult<RecipeCost> {
-        // Load recipe
-        let recipe = self
-            .recipe_repo
-            .find_by_id(recipe_id, tenant_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Recipe not found".to_string()))?;
+        self.calculate_cost_with_strategy(recipe_id, tenant_id, None)
+            .await
+    }
 
-        // Load all inventory products for this tenant to build price map
-        let inventory_products = self
-            .inventory_repo
-            .list_by_tenant(recipe.tenant_id())
-            .await?;
+    /// 🔒 TENANT ISOLATION: Calculate recipe cost with an explicit costing strategy
+    /// (`None` = tenant setting)
+    pub async fn calculate_cost_with_strategy(
+        &self,
+        recipe_id: RecipeId,
+        tenant_id: TenantId,
+        strategy: Option<CostingStrategy>,
+    ) -> AppResult<RecipeCost> {
+        let inputs = self.costing_inputs(recipe_id, tenant_id, strategy).await?;
+        cost_from_graph(
+            recipe_id,
+            &inputs.graph,
+            &inputs.price_map,
+            &inputs.names,
+            &inputs.yields,
+        )
+    }
 
-        // Build map: catalog_ingredient_id -> (quantity, price)
-        let mut price_map: HashMap<CatalogIngredientId, (Quantity, Money)> = HashMap::new();
+    /// 🔒 TENANT ISOLATION: What-if costing. Returns the cost at current prices
+    /// and, when the recipe tree uses any of the scaled ingredients, the cost
+    /// with their unit prices multiplied by the given factors (1.18 = +18%).
+    /// Nothing is persisted.
+    pub async fn simulate_cost(
+        &self,
+        recipe_id: RecipeId,
+        tenant_id: TenantId,
+        price_factors: &HashMap<CatalogIngredientId, Decimal>,
+    ) -> AppResult<(RecipeCost, Option<RecipeCost>)> {
+        let mut inputs = self.costing_inputs(recipe_id, tenant_id, None).await?;
+        let current = cost_from_graph(
+            recipe_id,
+            &inputs.graph,
+            &inputs.price_map,
+            &inputs.names,
+            &inputs.yields,
+        )?;
 
-        for product in inventory_products {
-            let key = product.catalog_ingredient_id;
-            price_map.insert(key, (product.quantity, product.price_per_unit));
+        if !inputs.names.keys().any(|id| price_factors.contains_key(id)) {
+            return Ok((current, None));
         }
 
-        // Calculate cost for each ingredient
-        let mut ingredients_breakdown = Vec::new();
-
-        for recipe_ingredient in recipe.ingredients() {
-            let ingredient_id = recipe_ingredient.catalog_ingredient_id();
+        for (ingredient_id, price) in inputs.price_map.iter_mut() {
+            if let Some(factor) = price_factors.get(ingredient_id) {
+                *price = price.multiply(*factor)?;
+            }
+        }
+        let simulated = cost_from_graph(
+            recipe_id,
+            &inputs.graph,
+            &inputs.price_map,
+            &inputs.names,
+            &inputs.yields,
+        )?;
 
-            // Get inventory price data
-            let (_inventory_qty, inventory_price) =
-                price_map.get(&ingredient_id).ok_or_else(|| {
-                    AppError::NotFound(format!(
-                        "No inventory data for ingredient {}. Cannot calculate cost.",
-                        ingredient_id.as_uuid()
-                    ))
-                })?;
+        Ok((current, Some(simulated)))
+    }
 
-            let unit_price_cents = inventory_price.as_cents() as f64;
-            let unit_price = Money::from_cents(unit_price_cents.round() as i64)?;
+    /// Load everything `cost_from_graph` needs for a recipe tree
+    async fn costing_inputs(
+        &self,
+        recipe_id: RecipeId,
+        tenant_id: TenantId,
+        strategy: Option<CostingStrategy>,
+    ) -> AppResult<CostingInputs> {
+        // Load recipe
+        let recipe = self
+            .recipe_repo
+            .find_by_id(recipe_id, tenant_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Recipe not found".to_string()))?;
 
-            let ingredient_cost_cents = recipe_ingredient.quantity().value() * unit_price_cents;
-            let ingredient_cost = Money::from_cents(ingredient_cost_cents.round() as i64)?;
+        // Load the whole component tree (cycle + depth checked)
+        let graph = load_component_graph(self.recipe_repo.as_ref(), recipe, tenant_id).await?;
 
-            // Load ingredient name from catalog
+        // Load ingredient names from catalog (once per ingredient in the tree)
+        let mut names: HashMap<CatalogIngredientId, String> = HashMap::new();
+        for ingredient in graph.values().flat_map(|r| r.ingredients()) {
+            let ingredient_id = ingredient.catalog_ingredient_id();
+            if names.contains_key(&ingredient_id) {
+                continue;
+            }
             let catalog_ingredient = self
                 .catalog_repo
                 .find_by_id(ingredient_id)
@@ -166,26 +257,36 @@ impl RecipeService {
                         ingredient_id.as_uuid()
                     ))
                 })?;
-
-            ingredients_breakdown.push(IngredientCost {
+            names.insert(
                 ingredient_id,
-                ingredient_name: catalog_ingredient.name(Language::En).to_string(),
-                quantity: recipe_ingredient.quantity(),
-                unit_price,
-                total_cost: ingredient_cost,
-            });
+                catalog_ingredient.name(Language::En).to_string(),
+            );
         }
 
-        // Build RecipeCost
-        let recipe_cost = RecipeCost::new(
-            recipe.id(),
-            recipe.name().as_str().to_string(),
-            ingredients_breakdown,
-            vec![], // components_breakdown: component recipes not supported in V1
-            recipe.servings().count(),
-        )?;
+        let ingredient_ids: Vec<CatalogIngredientId> = names.keys().copied().collect();
+
+        // Unit prices according to the costing strategy
+        let strategy = match strategy {
+            Some(strategy) => strategy,
+            None => self.inventory_repo.costing_strategy(tenant_id).await?,
+        };
+        let price_map = self
+            .inventory_repo
+            .unit_prices(tenant_id, strategy, &ingredient_ids)
+            .await?;
 
-        Ok(recipe_cost)
+        // Edible yields (tenant override → catalog default → 100%)
+        let yields = self
+            .catalog_repo
+            .effective_yield_percents(tenant_id, &ingredient_ids)
+            .await?;
+
+        Ok(CostingInputs {
+            graph,
+            names,
+            price_map,
+            yields,
+        })
     }
 
     /// 🔒 TENANT ISOLATION: Update recipe ingredients within tenant
@@ -194,6 +295,7 @@ impl RecipeService {
         recipe_id: RecipeId,
         ingredients: Vec<RecipeIngredient>,
         tenant_id: TenantId,
+        user_id: UserId,
     ) -> AppResult<()> {
         // Validate that all ingredients exist in catalog
         for ingredient in &ingredients {
@@ -211,12 +313,199 @@ impl RecipeService {
             }
         }
 
+        let before = self
+            .recipe_repo
+            .find_by_id(recipe_id, tenant_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Recipe not found"))?;
         self.recipe_repo
             .update_ingredients(recipe_id, ingredients, tenant_id)
-            .await
+            .await?;
+
+        if let Some(after) = self.recipe_repo.find_by_id(recipe_id, tenant_id).await? {
+            self.audit
+                .record(
+                    AuditEvent::new(
+                        tenant_id,
+                        user_id,
+                        AuditEntityType::Recipe,
+                        recipe_id.as_uuid(),
+                        AuditAction::Update,
+                    )
+                    .before(audit_snapshot(&before))
+                    .after(audit_snapshot(&after)),
+                )
+                .await;
+        }
+        Ok(())
     }
 }
 
+fn audit_snapshot(recipe: &Recipe) -> serde_json::Value {
+    serde_json::json!({
+        "name": recipe.name().as_str(),
+        "recipe_type": recipe.recipe_type().as_str(),
+        "servings": recipe.servings().count(),
+        "instructions": recipe.instructions(),
+        "ingredients": recipe
+            .ingredients()
+            .iter()
+            .map(|i| serde_json::json!({
+                "catalog_ingredient_id": i.catalog_ingredient_id().as_uuid(),
+                "quantity": i.quantity().decimal(),
+            }))
+            .collect::<Vec<_>>(),
+        "components": recipe
+            .components()
+            .iter()
+            .map(|c| serde_json::json!({
+                "recipe_id": c.component_recipe_id().as_uuid(),
+                "quantity": c.quantity(),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Load `root` and all of its component recipes (recursively) into a map.
+/// The graph is validated: cycles and too deep nesting are rejected.
+pub(crate) async fn load_component_graph(
+    recipe_repo: &dyn RecipeRepositoryTrait,
+    root: Recipe,
+    tenant_id: TenantId,
+) -> AppResult<HashMap<RecipeId, Recipe>> {
+    let root_id = root.id();
+    let mut graph: HashMap<RecipeId, Recipe> = HashMap::new();
+    let mut pending: Vec<(RecipeId, usize)> = root
+        .components()
+        .iter()
+        .map(|c| (c.component_recipe_id(), 1))
+        .collect();
+    graph.insert(root_id, root);
+
+    while let Some((id, depth)) = pending.pop() {
+        if graph.contains_key(&id) {
+            continue;
+        }
+        if depth > MAX_COMPONENT_DEPTH {
+            return Err(AppError::validation(format!(
+                "Component nesting is deeper than {} levels",
+                MAX_COMPONENT_DEPTH
+            )));
+        }
+        let component = recipe_repo
+            .find_by_id(id, tenant_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::validation(format!("Component recipe {} not found", id.as_uuid()))
+            })?;
+        pending.extend(
+            component
+                .components()
+                .iter()
+                .map(|c| (c.component_recipe_id(), depth + 1)),
+        );
+        graph.insert(id, component);
+    }
+
+    validate_component_graph(root_id, &graph)?;
+    Ok(graph)
+}
+
+/// Recipe tree with catalog names, unit prices and edible yields
+struct CostingInputs {
+    graph: HashMap<RecipeId, Recipe>,
+    names: HashMap<CatalogIngredientId, String>,
+    price_map: HashMap<CatalogIngredientId, Money>,
+    yields: HashMap<CatalogIngredientId, Decimal>,
+}
+
+/// Build RecipeCost for `recipe_id`, costing components recursively.
+/// The graph must already be validated (no cycles).
+fn cost_from_graph(
+    recipe_id: RecipeId,
+    graph: &HashMap<RecipeId, Recipe>,
+    price_map: &HashMap<CatalogIngredientId, Money>,
+    names: &HashMap<CatalogIngredientId, String>,
+    yields: &HashMap<CatalogIngredientId, Decimal>,
+) -> AppResult<RecipeCost> {
+    let recipe = graph.get(&recipe_id).ok_or_else(|| {
+        AppError::validation(format!(
+            "Component recipe {} not found",
+            recipe_id.as_uuid()
+        ))
+    })?;
+
+    // Calculate cost for each ingredient
+    let mut ingredients_breakdown = Vec::new();
+
+    for recipe_ingredient in recipe.ingredients() {
+        let ingredient_id = recipe_ingredient.catalog_ingredient_id();
+
+        // Get inventory price data
+        let inventory_price = price_map.get(&ingredient_id).ok_or_else(|| {
+            AppError::NotFound(format!(
+                "No inventory data for ingredient {}. Cannot calculate cost.",
+                ingredient_id.as_uuid()
+            ))
+        })?;
+
+        let unit_price_cents = inventory_price.as_cents() as f64;
+        let unit_price = Money::from_cents(unit_price_cents.round() as i64)?;
+
+        // Trim loss: pay for the as-purchased weight, not the usable one
+        let yield_percent = yields
+            .get(&ingredient_id)
+            .copied()
+            .unwrap_or(Decimal::ONE_HUNDRED);
+        let gross = Quantity::from_decimal(gross_quantity(
+            recipe_ingredient.quantity().decimal(),
+            yield_percent,
+        ))?;
+
+        let ingredient_cost_cents = gross.value() * unit_price_cents;
+        let ingredient_cost = Money::from_cents(ingredient_cost_cents.round() as i64)?;
+
+        ingredients_breakdown.push(IngredientCost {
+            ingredient_id,
+            ingredient_name: names.get(&ingredient_id).cloned().unwrap_or_default(),
+            quantity: recipe_ingredient.quantity(),
+            yield_percent,
+            gross_quantity: gross,
+            unit_price,
+            total_cost: ingredient_cost,
+        });
+    }
+
+    // Components: full batch cost of the prep × fraction used
+    let mut components_breakdown = Vec::new();
+
+    for component in recipe.components() {
+        let component_cost = cost_from_graph(
+            component.component_recipe_id(),
+            graph,
+            price_map,
+            names,
+            yields,
+        )?;
+
+        components_breakdown.push(ComponentCost {
+            component_id: component.component_recipe_id(),
+            component_name: component_cost.recipe_name.clone(),
+            recipe_cost_per_serving: component_cost.cost_per_serving,
+            quantity: component.quantity(),
+            total_cost: component_cost.total_cost.multiply(component.quantity())?,
+        });
+    }
+
+    RecipeCost::new(
+        recipe.id(),
+        recipe.name().as_str().to_string(),
+        ingredients_breakdown,
+        components_breakdown,
+        recipe.servings().count(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -1,4 +1,5 @@
 // Recipe V2 Service - Recipe management with automatic translations
+use crate::application::audit::{AuditAction, AuditEntityType, AuditEvent, AuditLog};
 use crate::application::recipe_translation_service::RecipeTranslationService;
 use crate::domain::recipe_v2::{Recipe, RecipeId, RecipeIngredient, RecipeStatus};
 use crate::domain::CatalogIngredientId;
@@ -81,6 +82,7 @@ pub struct RecipeV2Service {
     translation_service: Arc<RecipeTranslationService>,
     r2_client: R2Client,
     pool: PgPool,
+    audit: AuditLog,
 }
 
 impl RecipeV2Service {
@@ -98,6 +100,7 @@ impl RecipeV2Service {
             catalog_repo,
             translation_service,
             r2_client,
+            audit: AuditLog::new(pool.clone()),
             pool,
         }
     }
@@ -272,6 +275,19 @@ impl RecipeV2Service {
             });
         }
 
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::Recipe,
+                    recipe_id.as_uuid(),
+                    AuditAction::Create,
+                )
+                .after(audit_snapshot(&recipe, &response_ingredients)),
+            )
+            .await;
+
         // Trigger automatic translation to all other languages (async, non-blocking)
         let translation_service = self.translation_service.clone();
         let default_language = dto.language;
@@ -424,12 +440,21 @@ impl RecipeV2Service {
     }
 
     /// Publish recipe (make it public or active)
-    pub async fn publish_recipe(&self, recipe_id: RecipeId, tenant_id: TenantId) -> AppResult<()> {
+    pub async fn publish_recipe(
+        &self,
+        recipe_id: RecipeId,
+        tenant_id: TenantId,
+        user_id: UserId,
+    ) -> AppResult<()> {
         let mut recipe = self
             .recipe_repo
             .find_by_id(recipe_id, tenant_id)
             .await?
             .ok_or_else(|| AppError::not_found("Recipe"))?;
+        let ingredients = self
+            .get_response_ingredients(recipe_id, recipe.language_default)
+            .await?;
+        let before = audit_snapshot(&recipe, &ingredients);
 
         recipe.status = RecipeStatus::Published;
         recipe.is_public = true;
@@ -437,23 +462,53 @@ impl RecipeV2Service {
         recipe.updated_at = OffsetDateTime::now_utc();
 
         self.recipe_repo.update(&recipe).await?;
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::Recipe,
+                    recipe_id.as_uuid(),
+                    AuditAction::Publish,
+                )
+                .before(before)
+                .after(audit_snapshot(&recipe, &ingredients)),
+            )
+            .await;
         Ok(())
     }
 
     /// Delete recipe
-    pub async fn delete_recipe(&self, recipe_id: RecipeId, tenant_id: TenantId) -> AppResult<()> {
+    pub async fn delete_recipe(
+        &self,
+        recipe_id: RecipeId,
+        tenant_id: TenantId,
+        user_id: UserId,
+    ) -> AppResult<()> {
         // Enforce tenant isolation by checking if it exists for this tenant
-        let exists = self
+        let recipe = self
             .recipe_repo
             .find_by_id(recipe_id, tenant_id)
             .await?
-            .is_some();
-
-        if !exists {
-            return Err(AppError::not_found("Recipe"));
-        }
+            .ok_or_else(|| AppError::not_found("Recipe"))?;
+        let ingredients = self
+            .get_response_ingredients(recipe_id, recipe.language_default)
+            .await?;
 
-        self.recipe_repo.delete(recipe_id, tenant_id).await
+        self.recipe_repo.delete(recipe_id, tenant_id).await?;
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::Recipe,
+                    recipe_id.as_uuid(),
+                    AuditAction::Delete,
+                )
+                .before(audit_snapshot(&recipe, &ingredients)),
+            )
+            .await;
+        Ok(())
     }
 
     /// Update an existing recipe
@@ -462,12 +517,19 @@ impl RecipeV2Service {
         id: RecipeId, // Use id here
         dto: UpdateRecipeDto,
         tenant_id: TenantId,
+        user_id: UserId,
     ) -> AppResult<RecipeResponseDto> {
         let mut recipe = self
             .recipe_repo
             .find_by_id(id, tenant_id)
             .await?
             .ok_or_else(|| AppError::not_found("Recipe"))?;
+        let before = audit_snapshot(
+            &recipe,
+            &self
+                .get_response_ingredients(id, recipe.language_default)
+                .await?,
+        );
 
         // Update basic fields
         recipe.name_default = dto.name;
@@ -520,6 +582,20 @@ impl RecipeV2Service {
             });
         }
 
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::Recipe,
+                    id.as_uuid(),
+                    AuditAction::Update,
+                )
+                .before(before)
+                .after(audit_snapshot(&recipe, &response_ingredients)),
+            )
+            .await;
+
         // Trigger automatic re-translation
         let translation_service = self.translation_service.clone();
         let default_language = dto.language;
@@ -577,3 +653,27 @@ impl RecipeV2Service {
             .collect())
     }
 }
+
+/// Editable content only; costs and timestamps are left out
+fn audit_snapshot(
+    recipe: &Recipe,
+    ingredients: &[RecipeIngredientResponseDto],
+) -> serde_json::Value {
+    serde_json::json!({
+        "name": recipe.name_default,
+        "instructions": recipe.instructions_default,
+        "language": recipe.language_default.code(),
+        "servings": recipe.servings,
+        "image_url": recipe.image_url,
+        "status": recipe.status.as_str(),
+        "is_public": recipe.is_public,
+        "ingredients": ingredients
+            .iter()
+            .map(|i| serde_json::json!({
+                "catalog_ingredient_id": i.catalog_ingredient_id,
+                "quantity": i.quantity,
+                "unit": i.unit,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
@@ -0,0 +1,447 @@
+//! Automatic reorder proposals.
+//!
+//! Projects consumption from the last N days of `OUT_SALE` movements, compares the
+//! stock expected at delivery with each ingredient's reorder point and turns the
+//! shortfall into purchase drafts — one per supplier, rounded to pack sizes.
+//! Undelivered quantities on open drafts count as stock on order, so repeated runs
+//! (on demand or from the hourly scheduler) do not duplicate proposals.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::application::purchase_draft::{
+    CreatePurchaseDraftInput, PurchaseDraftItemInput, PurchaseDraftService,
+};
+use crate::domain::reorder::{ReorderPolicy, StockPosition};
+use crate::domain::supplier::{Supplier, SupplierId};
+use crate::infrastructure::persistence::{SupplierRepository, SupplierRepositoryTrait};
+use crate::shared::{AppError, AppResult, TenantId, UserId};
+
+/// Lead time assumed for ingredients without a preferred supplier
+const DEFAULT_LEAD_TIME_DAYS: i32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorderSettings {
+    /// Generate drafts from the hourly scheduler
+    pub auto_reorder_enabled: bool,
+    /// Days of OUT_SALE history used for the consumption projection
+    pub lookback_days: i32,
+}
+
+impl Default for ReorderSettings {
+    fn default() -> Self {
+        Self {
+            auto_reorder_enabled: false,
+            lookback_days: 14,
+        }
+    }
+}
+
+/// One ingredient that should be reordered
+#[derive(Debug, Clone, Serialize)]
+pub struct ReorderSuggestion {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub unit: String,
+    pub supplier_id: Option<Uuid>,
+    pub supplier_name: Option<String>,
+    pub on_hand: f64,
+    pub on_order: f64,
+    pub avg_daily_consumption: f64,
+    pub lead_time_days: i32,
+    pub projected_at_delivery: f64,
+    pub reorder_point: f64,
+    pub par_level: f64,
+    pub pack_size: Option<f64>,
+    pub order_quantity: f64,
+    pub price_per_unit_cents: Option<i64>,
+    pub estimated_cost_cents: i64,
+}
+
+/// One generated purchase draft
+#[derive(Debug, Clone, Serialize)]
+pub struct ReorderDraftSummary {
+    pub draft_id: Uuid,
+    pub supplier_id: Option<Uuid>,
+    pub supplier_name: Option<String>,
+    pub delivery_date: Option<time::Date>,
+    pub items: usize,
+    pub total_cost_cents: i64,
+    /// Draft total is below the supplier's minimum order value
+    pub below_min_order: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReorderRunResult {
+    pub lookback_days: i32,
+    pub drafts: Vec<ReorderDraftSummary>,
+}
+
+#[derive(Clone)]
+pub struct ReorderService {
+    pool: PgPool,
+    suppliers: Arc<dyn SupplierRepositoryTrait>,
+    drafts: Arc<PurchaseDraftService>,
+}
+
+impl ReorderService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            suppliers: Arc::new(SupplierRepository::new(pool.clone())),
+            drafts: Arc::new(PurchaseDraftService::new(pool.clone())),
+            pool,
+        }
+    }
+
+    pub async fn get_settings(&self, tenant_id: TenantId) -> AppResult<ReorderSettings> {
+        let row = sqlx::query(
+            "SELECT auto_reorder_enabled, reorder_lookback_days
+             FROM tenant_inventory_settings WHERE tenant_id = $1",
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(ReorderSettings {
+                auto_reorder_enabled: row.try_get("auto_reorder_enabled")?,
+                lookback_days: row.try_get("reorder_lookback_days")?,
+            }),
+            None => Ok(ReorderSettings::default()),
+        }
+    }
+
+    pub async fn set_settings(
+        &self,
+        tenant_id: TenantId,
+        settings: ReorderSettings,
+    ) -> AppResult<ReorderSettings> {
+        if !(1..=90).contains(&settings.lookback_days) {
+            return Err(AppError::validation(
+                "Lookback must be between 1 and 90 days",
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_inventory_settings (tenant_id, auto_reorder_enabled, reorder_lookback_days)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                auto_reorder_enabled = EXCLUDED.auto_reorder_enabled,
+                reorder_lookback_days = EXCLUDED.reorder_lookback_days
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(settings.auto_reorder_enabled)
+        .bind(settings.lookback_days)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(settings)
+    }
+
+    /// Ingredients whose projected stock at delivery is at or below the reorder point
+    pub async fn suggestions(
+        &self,
+        tenant_id: TenantId,
+        lookback_days: i32,
+        lang_code: &str,
+    ) -> AppResult<Vec<ReorderSuggestion>> {
+        let lookback_days = lookback_days.clamp(1, 90);
+        let suppliers: HashMap<SupplierId, Supplier> = self
+            .suppliers
+            .list_by_tenant(tenant_id, false)
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s))
+            .collect();
+
+        let query = r#"
+            WITH stock AS (
+                SELECT catalog_ingredient_id, SUM(remaining_quantity) AS on_hand
+                FROM inventory_batches
+                WHERE tenant_id = $1 AND status = 'active'
+                GROUP BY catalog_ingredient_id
+            ),
+            consumption AS (
+                SELECT ib.catalog_ingredient_id, SUM(im.quantity) AS consumed
+                FROM inventory_movements im
+                JOIN inventory_batches ib ON im.batch_id = ib.id
+                WHERE ib.tenant_id = $1
+                  AND im.type = 'OUT_SALE'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM inventory_movements u
+                      WHERE u.tenant_id = im.tenant_id AND u.reference_id = im.reference_id
+                        AND u.reference_type = 'copilot_undo'
+                  )
+                  AND im.created_at > NOW() - ($2 * INTERVAL '1 day')
+                GROUP BY ib.catalog_ingredient_id
+            ),
+            on_order AS (
+                SELECT pdi.catalog_ingredient_id,
+                       SUM(GREATEST(pdi.quantity - pdi.received_quantity, 0))::NUMERIC AS on_order
+                FROM purchase_draft_items pdi
+                JOIN purchase_drafts pd ON pd.id = pdi.draft_id
+                WHERE pd.tenant_id = $1 AND pd.status IN ('draft', 'sent', 'partially_received')
+                GROUP BY pdi.catalog_ingredient_id
+            ),
+            last_price AS (
+                SELECT DISTINCT ON (catalog_ingredient_id)
+                       catalog_ingredient_id, price_per_unit_cents
+                FROM ingredient_price_history
+                WHERE tenant_id = $1
+                ORDER BY catalog_ingredient_id, recorded_at DESC
+            )
+            SELECT
+                ti.catalog_ingredient_id,
+                CASE
+                    WHEN $3 = 'ru' THEN COALESCE(ci.name_ru, ci.name_en)
+                    WHEN $3 = 'pl' THEN COALESCE(ci.name_pl, ci.name_en)
+                    WHEN $3 = 'uk' THEN COALESCE(ci.name_uk, ci.name_en)
+                    ELSE ci.name_en
+                END AS ingredient_name,
+                COALESCE(ti.custom_unit::TEXT, ci.default_unit::TEXT) AS unit,
+                ti.supplier_id,
+                ti.par_level,
+                ti.reorder_point,
+                ti.pack_size,
+                ci.min_stock_threshold,
+                COALESCE(s.on_hand, 0) AS on_hand,
+                COALESCE(c.consumed, 0) AS consumed,
+                COALESCE(o.on_order, 0) AS on_order,
+                COALESCE(lp.price_per_unit_cents, ROUND(ti.price * 100)::BIGINT) AS price_per_unit_cents
+            FROM tenant_ingredients ti
+            JOIN catalog_ingredients ci ON ci.id = ti.catalog_ingredient_id
+            LEFT JOIN stock s ON s.catalog_ingredient_id = ti.catalog_ingredient_id
+            LEFT JOIN consumption c ON c.catalog_ingredient_id = ti.catalog_ingredient_id
+            LEFT JOIN on_order o ON o.catalog_ingredient_id = ti.catalog_ingredient_id
+            LEFT JOIN last_price lp ON lp.catalog_ingredient_id = ti.catalog_ingredient_id
+            WHERE ti.tenant_id = $1
+              AND ti.is_active = true
+              AND (ti.par_level IS NOT NULL
+                   OR ti.reorder_point IS NOT NULL
+                   OR ci.min_stock_threshold > 0)
+            ORDER BY ingredient_name
+        "#;
+
+        let rows = sqlx::query(query)
+            .bind(tenant_id.as_uuid())
+            .bind(lookback_days)
+            .bind(lang_code)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut suggestions = Vec::new();
+        for row in rows {
+            let supplier = row
+                .try_get::<Option<Uuid>, _>("supplier_id")?
+                .and_then(|id| suppliers.get(&SupplierId::from_uuid(id)));
+
+            let policy = ReorderPolicy {
+                par_level: row.try_get("par_level")?,
+                reorder_point: row.try_get("reorder_point")?,
+                pack_size: row.try_get("pack_size")?,
+                min_stock_threshold: row
+                    .try_get::<Option<Decimal>, _>("min_stock_threshold")?
+                    .unwrap_or(Decimal::ZERO),
+            };
+            let consumed: Decimal = row.try_get("consumed")?;
+            let position = StockPosition {
+                on_hand: row.try_get("on_hand")?,
+                on_order: row.try_get("on_order")?,
+                avg_daily_consumption: consumed / Decimal::from(lookback_days),
+                lead_time_days: supplier
+                    .map(|s| s.lead_time_days)
+                    .unwrap_or(DEFAULT_LEAD_TIME_DAYS),
+            };
+
+            let Some(order_quantity) = policy.reorder_quantity(&position) else {
+                continue;
+            };
+
+            let price_per_unit_cents: Option<i64> = row.try_get("price_per_unit_cents")?;
+            let estimated_cost_cents = price_per_unit_cents
+                .map(|p| {
+                    (order_quantity * Decimal::from(p))
+                        .round()
+                        .to_i64()
+                        .unwrap_or(0)
+                })
+                .unwrap_or(0);
+
+            suggestions.push(ReorderSuggestion {
+                ingredient_id: row.try_get("catalog_ingredient_id")?,
+                ingredient_name: row.try_get("ingredient_name")?,
+                unit: row.try_get("unit")?,
+                supplier_id: supplier.map(|s| s.id.as_uuid()),
+                supplier_name: supplier.map(|s| s.name.clone()),
+                on_hand: position.on_hand.to_f64().unwrap_or(0.0),
+                on_order: position.on_order.to_f64().unwrap_or(0.0),
+                avg_daily_consumption: position.avg_daily_consumption.to_f64().unwrap_or(0.0),
+                lead_time_days: position.lead_time_days,
+                projected_at_delivery: position.projected_at_delivery().to_f64().unwrap_or(0.0),
+                reorder_point: policy.effective_reorder_point().to_f64().unwrap_or(0.0),
+                par_level: policy
+                    .effective_par_level(position.avg_daily_consumption)
+                    .to_f64()
+                    .unwrap_or(0.0),
+                pack_size: policy.pack_size.and_then(|p| p.to_f64()),
+                order_quantity: order_quantity.to_f64().unwrap_or(0.0),
+                price_per_unit_cents,
+                estimated_cost_cents,
+            });
+        }
+
+        Ok(suggestions)
+    }
+
+    /// Turn current suggestions into purchase drafts, one per supplier
+    pub async fn generate_drafts(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        lookback_days: i32,
+        lang_code: &str,
+    ) -> AppResult<ReorderRunResult> {
+        let lookback_days = lookback_days.clamp(1, 90);
+        let suggestions = self
+            .suggestions(tenant_id, lookback_days, lang_code)
+            .await?;
+
+        // None = ingredients without a preferred supplier
+        let mut by_supplier: BTreeMap<Option<Uuid>, Vec<ReorderSuggestion>> = BTreeMap::new();
+        for suggestion in suggestions {
+            by_supplier
+                .entry(suggestion.supplier_id)
+                .or_default()
+                .push(suggestion);
+        }
+
+        let today = OffsetDateTime::now_utc().date();
+        let mut drafts = Vec::with_capacity(by_supplier.len());
+        for (supplier_id, lines) in by_supplier {
+            let supplier = match supplier_id {
+                Some(id) => {
+                    self.suppliers
+                        .find_by_id(SupplierId::from_uuid(id), tenant_id)
+                        .await?
+                }
+                None => None,
+            };
+            let delivery_date = supplier.as_ref().map(|s| s.next_delivery_date(today));
+            let total_cost_cents: i64 = lines.iter().map(|l| l.estimated_cost_cents).sum();
+            let below_min_order = supplier
+                .as_ref()
+                .is_some_and(|s| total_cost_cents < s.min_order_cents);
+            let supplier_name = supplier.as_ref().map(|s| s.name.clone());
+
+            let items = lines
+                .iter()
+                .map(|l| PurchaseDraftItemInput {
+                    catalog_ingredient_id: Some(l.ingredient_id),
+                    ingredient_name: l.ingredient_name.clone(),
+                    quantity: l.order_quantity,
+                    unit: l.unit.clone(),
+                    price_per_unit_cents: l.price_per_unit_cents,
+                })
+                .collect::<Vec<_>>();
+            let item_count = items.len();
+
+            let draft_id = self
+                .drafts
+                .create(
+                    user_id,
+                    tenant_id,
+                    CreatePurchaseDraftInput {
+                        supplier_id,
+                        supplier_name: supplier_name.clone(),
+                        delivery_date,
+                        note: Some(format!(
+                            "Auto-reorder: projected from the last {} days of sales",
+                            lookback_days
+                        )),
+                        items,
+                    },
+                )
+                .await?;
+
+            drafts.push(ReorderDraftSummary {
+                draft_id,
+                supplier_id,
+                supplier_name,
+                delivery_date,
+                items: item_count,
+                total_cost_cents,
+                below_min_order,
+            });
+        }
+
+        tracing::info!(
+            "🛒 Auto-reorder for tenant {}: {} draft(s)",
+            tenant_id.as_uuid(),
+            drafts.len()
+        );
+
+        Ok(ReorderRunResult {
+            lookback_days,
+            drafts,
+        })
+    }
+
+    /// Scheduler entry point: run for every tenant with auto-reorder enabled.
+    /// Drafts are created on behalf of the tenant owner (oldest user as fallback).
+    pub async fn run_scheduled(&self) -> AppResult<String> {
+        let rows = sqlx::query(
+            r#"
+            SELECT s.tenant_id, s.reorder_lookback_days, u.id AS user_id, u.language
+            FROM tenant_inventory_settings s
+            JOIN LATERAL (
+                SELECT id, language FROM users
+                WHERE tenant_id = s.tenant_id
+                ORDER BY (role = 'owner') DESC, created_at ASC
+                LIMIT 1
+            ) u ON true
+            WHERE s.auto_reorder_enabled = true
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let (mut tenants, mut drafts, mut failed) = (0usize, 0usize, 0usize);
+        for row in rows {
+            let tenant_id = TenantId::from_uuid(row.try_get("tenant_id")?);
+            let user_id = UserId::from_uuid(row.try_get("user_id")?);
+            let lookback_days: i32 = row.try_get("reorder_lookback_days")?;
+            let language: String = row.try_get("language")?;
+
+            match self
+                .generate_drafts(tenant_id, user_id, lookback_days, &language)
+                .await
+            {
+                Ok(result) => {
+                    tenants += 1;
+                    drafts += result.drafts.len();
+                }
+                Err(e) => {
+                    failed += 1;
+                    tracing::error!(
+                        "❌ Auto-reorder failed for tenant {}: {}",
+                        tenant_id.as_uuid(),
+                        e
+                    );
+                }
+            }
+        }
+
+        Ok(format!(
+            "auto-reorder: {} tenant(s), {} draft(s) created, {} failed",
+            tenants, drafts, failed
+        ))
+    }
+}
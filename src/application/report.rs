@@ -1,10 +1,16 @@
 use crate::application::{DishService, InventoryService, MenuEngineeringService};
+use crate::domain::modifier::ModifierOptionId;
 use crate::domain::report::{
-    DishHighlight, EngineeringAgg, InventoryAgg, MenuAgg, SalesAgg, TenantSummary,
+    DishHighlight, DishUsageInput, EngineeringAgg, IngredientUsageInput, InventoryAgg, MenuAgg,
+    SalesAgg, TenantSummary, UsageVarianceReport,
 };
-use crate::domain::MenuCategory;
+use crate::domain::{CatalogIngredientId, DishId, MenuCategory};
 use crate::shared::{AppResult, Language, PaginationParams, TenantId, UserId};
-use sqlx::PgPool;
+use rust_decimal::Decimal;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct ReportService {
@@ -62,6 +68,247 @@ impl ReportService {
         ))
     }
 
+    /// GET /api/reports/usage-variance — theoretical vs actual food cost.
+    ///
+    /// Theoretical usage = one serving of each dish's current recipe, with
+    /// the modifiers recorded on the sale applied, × portions sold in the
+    /// period (the basis the sale was costed on).
+    /// Actual usage = opening + receipts − closing book stock, where book
+    /// stock at a point in time is rebuilt from the movement log.
+    pub async fn get_usage_variance(
+        &self,
+        tenant_id: TenantId,
+        language: Language,
+        period_days: u32,
+    ) -> AppResult<UsageVarianceReport> {
+        let period_end = OffsetDateTime::now_utc();
+        let period_start = period_end - Duration::days(period_days as i64);
+
+        // 1. Theoretical usage from sales, per dish and recorded modifier set
+        let sales = sqlx::query_as::<_, (Uuid, Option<String>, Vec<Uuid>, i64)>(
+            r#"
+            SELECT sales.dish_id, d.name, sales.option_ids, SUM(sales.quantity)::BIGINT
+            FROM (
+                SELECT ds.dish_id,
+                       ds.quantity,
+                       ARRAY(
+                           SELECT m.option_id
+                           FROM dish_sale_modifiers m
+                           WHERE m.sale_id = ds.id
+                             AND m.tenant_id = ds.tenant_id
+                             AND m.option_id IS NOT NULL
+                           ORDER BY m.option_id
+                       ) AS option_ids
+                FROM dish_sales ds
+                WHERE ds.tenant_id = $1
+                  AND ds.sold_at >= $2
+                  AND ds.sold_at < $3
+            ) sales
+            LEFT JOIN dishes d ON d.id = sales.dish_id AND d.tenant_id = $1
+            GROUP BY sales.dish_id, d.name, sales.option_ids
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        type Variants = Vec<(Vec<ModifierOptionId>, i64)>;
+        let mut by_dish: HashMap<Uuid, (String, Variants)> = HashMap::new();
+        for (dish_id, dish_name, option_ids, sold) in sales {
+            let option_ids = option_ids
+                .into_iter()
+                .map(ModifierOptionId::from_uuid)
+                .collect();
+            by_dish
+                .entry(dish_id)
+                .or_insert_with(|| (dish_name.unwrap_or_default(), Vec::new()))
+                .1
+                .push((option_ids, sold));
+        }
+
+        let mut theoretical: HashMap<CatalogIngredientId, Decimal> = HashMap::new();
+        let mut dishes_sold = 0i64;
+        let mut unresolved_dishes = 0u32;
+        let mut dish_inputs = Vec::with_capacity(by_dish.len());
+        for (dish_id, (dish_name, variants)) in by_dish {
+            let portions_sold: i64 = variants.iter().map(|(_, sold)| sold).sum();
+            dishes_sold += portions_sold;
+            let dish_id = DishId::from_uuid(dish_id);
+            let per_portion = match self
+                .menu_engineering_service
+                .raw_ingredients_per_portion(tenant_id, dish_id)
+                .await
+            {
+                Ok(items) => items,
+                Err(e) => {
+                    tracing::warn!(
+                        "⚠️ Usage variance: dish {} skipped ({}), its usage counts as unexplained",
+                        dish_id.as_uuid(),
+                        e
+                    );
+                    unresolved_dishes += 1;
+                    continue;
+                }
+            };
+            // Removed ingredients, sizes and add-ons as recorded on the sale
+            let mut dish_theoretical: HashMap<CatalogIngredientId, Decimal> = HashMap::new();
+            for (option_ids, sold) in variants {
+                let portion = if option_ids.is_empty() {
+                    per_portion.clone()
+                } else {
+                    self.menu_engineering_service
+                        .apply_recorded_modifiers(tenant_id, dish_id, &per_portion, &option_ids)
+                        .await?
+                };
+                for (ingredient_id, quantity) in portion {
+                    *dish_theoretical.entry(ingredient_id).or_default() +=
+                        quantity * Decimal::from(sold);
+                }
+            }
+            for (ingredient_id, quantity) in &dish_theoretical {
+                *theoretical.entry(*ingredient_id).or_default() += *quantity;
+            }
+            dish_inputs.push(DishUsageInput {
+                dish_id: dish_id.as_uuid(),
+                dish_name,
+                portions_sold,
+                theoretical: dish_theoretical
+                    .into_iter()
+                    .map(|(id, quantity)| (id.as_uuid(), quantity))
+                    .collect(),
+            });
+        }
+
+        // 2. Book stock flow per ingredient. Signed movements: IN, TRANSFER_IN
+        //    and (already signed) ADJUSTMENT add stock, everything else removes
+        //    it. Transfers cancel out at tenant level.
+        let theoretical_ids: Vec<Uuid> = theoretical.keys().map(|id| id.as_uuid()).collect();
+        let rows = sqlx::query(
+            r#"
+            WITH stock AS (
+                SELECT catalog_ingredient_id AS ingredient_id,
+                       SUM(remaining_quantity) AS on_hand
+                FROM inventory_batches
+                WHERE tenant_id = $1
+                GROUP BY 1
+            ),
+            signed AS (
+                SELECT ib.catalog_ingredient_id AS ingredient_id,
+                       im.type,
+                       im.created_at,
+                       im.quantity,
+                       CASE WHEN im.type IN ('IN', 'TRANSFER_IN', 'ADJUSTMENT')
+                            THEN im.quantity ELSE -im.quantity END AS delta,
+                       EXISTS (
+                           SELECT 1 FROM inventory_movements u
+                           WHERE u.tenant_id = im.tenant_id AND u.reference_id = im.reference_id
+                             AND u.reference_type = 'copilot_undo'
+                       ) AS reversed
+                FROM inventory_movements im
+                JOIN inventory_batches ib ON ib.id = im.batch_id
+                WHERE im.tenant_id = $1
+                  AND im.created_at >= $2
+            ),
+            flows AS (
+                SELECT ingredient_id,
+                       SUM(delta) AS net_since_start,
+                       SUM(CASE WHEN created_at >= $3 THEN delta ELSE 0 END) AS net_since_end,
+                       SUM(CASE WHEN type = 'IN' AND created_at < $3
+                                THEN quantity ELSE 0 END) AS receipts,
+                       SUM(CASE WHEN type IN ('OUT_EXPIRE', 'OUT_WASTE') AND NOT reversed
+                                     AND created_at < $3
+                                THEN quantity ELSE 0 END) AS recorded_loss
+                FROM signed
+                GROUP BY 1
+            )
+            SELECT
+                ci.id AS ingredient_id,
+                CASE
+                    WHEN $5 = 'ru' THEN COALESCE(ci.name_ru, ci.name_en)
+                    WHEN $5 = 'pl' THEN COALESCE(ci.name_pl, ci.name_en)
+                    WHEN $5 = 'uk' THEN COALESCE(ci.name_uk, ci.name_en)
+                    ELSE ci.name_en
+                END AS ingredient_name,
+                CASE
+                    WHEN $5 = 'ru' THEN cc.name_ru
+                    WHEN $5 = 'pl' THEN cc.name_pl
+                    WHEN $5 = 'uk' THEN cc.name_uk
+                    ELSE cc.name_en
+                END AS category_name,
+                ci.default_unit::TEXT AS unit,
+                COALESCE(s.on_hand, 0) AS on_hand,
+                COALESCE(f.net_since_start, 0) AS net_since_start,
+                COALESCE(f.net_since_end, 0) AS net_since_end,
+                COALESCE(f.receipts, 0) AS receipts,
+                COALESCE(f.recorded_loss, 0) AS recorded_loss
+            FROM catalog_ingredients ci
+            LEFT JOIN stock s ON s.ingredient_id = ci.id
+            LEFT JOIN flows f ON f.ingredient_id = ci.id
+            LEFT JOIN catalog_categories cc ON cc.id = ci.category_id
+            WHERE f.ingredient_id IS NOT NULL
+               OR ci.id = ANY($4)
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(period_start)
+        .bind(period_end)
+        .bind(&theoretical_ids)
+        .bind(language.code())
+        .fetch_all(&self.pool)
+        .await?;
+
+        // 3. Both usages are priced at the same unit price, so the variance
+        //    cost reflects quantity only
+        let ingredient_ids: Vec<CatalogIngredientId> = rows
+            .iter()
+            .map(|row| {
+                row.try_get("ingredient_id")
+                    .map(CatalogIngredientId::from_uuid)
+            })
+            .collect::<Result<_, _>>()?;
+        let prices = self
+            .inventory_service
+            .unit_prices(tenant_id, &ingredient_ids)
+            .await?;
+
+        let mut inputs = Vec::with_capacity(rows.len());
+        for (row, ingredient_id) in rows.iter().zip(ingredient_ids) {
+            let on_hand: Decimal = row.try_get("on_hand")?;
+            let net_since_start: Decimal = row.try_get("net_since_start")?;
+            let net_since_end: Decimal = row.try_get("net_since_end")?;
+            let unit: Option<String> = row.try_get("unit")?;
+            let category_name: Option<String> = row.try_get("category_name")?;
+            let ingredient_name: Option<String> = row.try_get("ingredient_name")?;
+
+            inputs.push(IngredientUsageInput {
+                ingredient_id: ingredient_id.as_uuid(),
+                ingredient_name: ingredient_name.unwrap_or_default(),
+                category_name: category_name.unwrap_or_default(),
+                unit: unit.unwrap_or_default(),
+                opening: on_hand - net_since_start,
+                receipts: row.try_get("receipts")?,
+                closing: on_hand - net_since_end,
+                recorded_loss: row.try_get("recorded_loss")?,
+                theoretical: theoretical.get(&ingredient_id).copied().unwrap_or_default(),
+                unit_price_cents: prices
+                    .get(&ingredient_id)
+                    .map(|price| price.as_cents())
+                    .unwrap_or(0),
+            });
+        }
+
+        Ok(UsageVarianceReport::build(
+            period_start,
+            period_end,
+            dishes_sold,
+            unresolved_dishes,
+            &inputs,
+            &dish_inputs,
+        ))
+    }
+
     /// Aggregate revenue/profit from dish_sales table
     async fn aggregate_sales(&self, tenant_id: TenantId, period_days: u32) -> AppResult<SalesAgg> {
         let row = sqlx::query_as::<_, (Option<i64>, Option<i64>, Option<i64>)>(
@@ -185,7 +432,7 @@ impl ReportService {
     ) -> AppResult<EngineeringAgg> {
         match self
             .menu_engineering_service
-            .analyze_menu(user_id, tenant_id, language, period_days)
+            .analyze_menu(user_id, tenant_id, language, period_days, None)
             .await
         {
             Ok(matrix) => {
@@ -0,0 +1,614 @@
+//! Renders owner reports as CSV, XLSX and an HTML email body.
+//!
+//! Each report is first flattened into titled [`ReportTable`]s; the three
+//! output formats are written from those tables, so a report added here is
+//! available in all of them. CSV puts the tables one after another, XLSX
+//! gives each table its own worksheet.
+
+use std::fmt::Write;
+
+use rust_xlsxwriter::{Format, Workbook};
+
+use crate::application::inventory::LossReport;
+use crate::domain::menu_engineering::{AbcClass, MenuCategory, MenuEngineeringMatrix};
+use crate::domain::report::{TenantSummary, UsageVarianceReport};
+use crate::shared::{AppError, AppResult};
+
+/// Rows of a table shown in the email body; attachments always have all rows
+pub const HTML_ROW_LIMIT: usize = 25;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Int(i64),
+    Number(f64),
+    /// Cents, rendered as a decimal amount
+    Money(i64),
+}
+
+impl Cell {
+    fn text(value: impl Into<String>) -> Self {
+        Self::Text(value.into())
+    }
+
+    fn display(&self) -> String {
+        match self {
+            Self::Text(value) => value.clone(),
+            Self::Int(value) => value.to_string(),
+            Self::Number(value) => format!("{:.1}", value),
+            Self::Money(cents) => money(*cents),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportTable {
+    pub title: &'static str,
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+/// A report flattened into tables, ready to be written in any format
+#[derive(Debug, Clone)]
+pub struct RenderedReport {
+    pub tables: Vec<ReportTable>,
+}
+
+impl RenderedReport {
+    /// UTF-8 BOM first so spreadsheet apps detect the encoding
+    pub fn to_csv(&self) -> Vec<u8> {
+        let mut csv = String::from("\u{feff}");
+        for (i, table) in self.tables.iter().enumerate() {
+            if i > 0 {
+                csv.push_str("\r\n");
+            }
+            csv.push_str(&escape_csv(table.title));
+            csv.push_str("\r\n");
+            csv.push_str(
+                &table
+                    .headers
+                    .iter()
+                    .map(|header| escape_csv(header))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+            csv.push_str("\r\n");
+            for row in &table.rows {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| match cell {
+                        // Full precision; the spreadsheet formats it
+                        Cell::Number(value) => value.to_string(),
+                        other => escape_csv(&other.display()),
+                    })
+                    .collect();
+                csv.push_str(&cells.join(","));
+                csv.push_str("\r\n");
+            }
+        }
+        csv.into_bytes()
+    }
+
+    pub fn to_xlsx(&self) -> AppResult<Vec<u8>> {
+        let xlsx_error = |e: rust_xlsxwriter::XlsxError| AppError::internal(format!("XLSX: {e}"));
+        let bold = Format::new().set_bold();
+        let money_format = Format::new().set_num_format("#,##0.00");
+        let number_format = Format::new().set_num_format("0.0");
+
+        let mut workbook = Workbook::new();
+        for table in &self.tables {
+            let sheet = workbook.add_worksheet();
+            sheet
+                .set_name(sheet_name(table.title))
+                .map_err(xlsx_error)?;
+            for (col, header) in table.headers.iter().enumerate() {
+                sheet
+                    .write_string_with_format(0, col as u16, *header, &bold)
+                    .map_err(xlsx_error)?;
+            }
+            for (i, row) in table.rows.iter().enumerate() {
+                let r = i as u32 + 1;
+                for (col, cell) in row.iter().enumerate() {
+                    let c = col as u16;
+                    match cell {
+                        Cell::Text(value) => sheet.write_string(r, c, value.as_str()),
+                        Cell::Int(value) => sheet.write_number(r, c, *value as f64),
+                        Cell::Number(value) => {
+                            sheet.write_number_with_format(r, c, *value, &number_format)
+                        }
+                        Cell::Money(cents) => sheet.write_number_with_format(
+                            r,
+                            c,
+                            *cents as f64 / 100.0,
+                            &money_format,
+                        ),
+                    }
+                    .map_err(xlsx_error)?;
+                }
+            }
+            sheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+            sheet.autofit();
+        }
+        workbook.save_to_buffer().map_err(xlsx_error)
+    }
+
+    /// Tables for the email body, each cut to [`HTML_ROW_LIMIT`] rows.
+    /// `truncated` is the note shown under a cut table.
+    pub fn to_html_tables(&self, truncated: &str) -> String {
+        let mut html = String::new();
+        for table in &self.tables {
+            let _ = write!(
+                html,
+                "<h3 style=\"margin:20px 0 6px\">{}</h3>",
+                escape_html(table.title)
+            );
+            html.push_str(
+                "<table style=\"border-collapse:collapse;font-size:13px\" cellpadding=\"4\"><tr>",
+            );
+            for header in &table.headers {
+                let _ = write!(
+                    html,
+                    "<th style=\"border-bottom:2px solid #333;text-align:left\">{}</th>",
+                    escape_html(header)
+                );
+            }
+            html.push_str("</tr>");
+            for row in table.rows.iter().take(HTML_ROW_LIMIT) {
+                html.push_str("<tr>");
+                for cell in row {
+                    let align = match cell {
+                        Cell::Text(_) => "left",
+                        _ => "right",
+                    };
+                    let _ = write!(
+                        html,
+                        "<td style=\"border-bottom:1px solid #ddd;text-align:{}\">{}</td>",
+                        align,
+                        escape_html(&cell.display())
+                    );
+                }
+                html.push_str("</tr>");
+            }
+            html.push_str("</table>");
+            if table.rows.len() > HTML_ROW_LIMIT {
+                let _ = write!(
+                    html,
+                    "<p style=\"color:#666;font-size:12px\">{}</p>",
+                    escape_html(&truncated.replace("{rows}", &HTML_ROW_LIMIT.to_string()))
+                );
+            }
+        }
+        html
+    }
+}
+
+pub fn summary_report(summary: &TenantSummary) -> RenderedReport {
+    let highlight = |dish: &Option<crate::domain::report::DishHighlight>| match dish {
+        Some(dish) => Cell::text(format!(
+            "{} ({:.1}%)",
+            dish.name, dish.profit_margin_percent
+        )),
+        None => Cell::text("—"),
+    };
+    let rows = vec![
+        vec![
+            Cell::text("Period, days"),
+            Cell::Int(summary.period_days as i64),
+        ],
+        vec![
+            Cell::text("Revenue"),
+            Cell::Money(summary.total_revenue_cents),
+        ],
+        vec![
+            Cell::text("Profit"),
+            Cell::Money(summary.total_profit_cents),
+        ],
+        vec![Cell::text("Orders"), Cell::Int(summary.total_orders as i64)],
+        vec![
+            Cell::text("Average order profit"),
+            Cell::Money(summary.avg_order_profit_cents),
+        ],
+        vec![Cell::text("Dishes"), Cell::Int(summary.total_dishes as i64)],
+        vec![
+            Cell::text("Dishes with cost"),
+            Cell::Int(summary.dishes_with_cost as i64),
+        ],
+        vec![
+            Cell::text("Average food cost, %"),
+            Cell::Number(summary.avg_food_cost_percent),
+        ],
+        vec![
+            Cell::text("Average dish margin, %"),
+            Cell::Number(summary.avg_profit_margin_percent),
+        ],
+        vec![Cell::text("Best dish"), highlight(&summary.best_dish)],
+        vec![Cell::text("Worst dish"), highlight(&summary.worst_dish)],
+        vec![
+            Cell::text("Inventory health score"),
+            Cell::Int(summary.inventory_health_score as i64),
+        ],
+        vec![
+            Cell::text("Expired products"),
+            Cell::Int(summary.expired_products as i64),
+        ],
+        vec![
+            Cell::text("Critical products"),
+            Cell::Int(summary.critical_products as i64),
+        ],
+        vec![Cell::text("Waste"), Cell::Money(summary.waste_cents)],
+        vec![Cell::text("Waste, %"), Cell::Number(summary.waste_percent)],
+        vec![Cell::text("Stars"), Cell::Int(summary.stars as i64)],
+        vec![
+            Cell::text("Plowhorses"),
+            Cell::Int(summary.plowhorses as i64),
+        ],
+        vec![Cell::text("Puzzles"), Cell::Int(summary.puzzles as i64)],
+        vec![Cell::text("Dogs"), Cell::Int(summary.dogs as i64)],
+    ];
+
+    RenderedReport {
+        tables: vec![ReportTable {
+            title: "Summary",
+            headers: vec!["Metric", "Value"],
+            rows,
+        }],
+    }
+}
+
+pub fn loss_report(report: &LossReport) -> RenderedReport {
+    let totals = ReportTable {
+        title: "Totals",
+        headers: vec!["Metric", "Value"],
+        rows: vec![
+            vec![
+                Cell::text("Period, days"),
+                Cell::Int(report.period_days as i64),
+            ],
+            vec![Cell::text("Loss"), Cell::Money(report.total_loss_cents)],
+            vec![
+                Cell::text("Purchased"),
+                Cell::Money(report.total_purchased_cents),
+            ],
+            vec![
+                Cell::text("Waste, %"),
+                Cell::Number(report.waste_percentage),
+            ],
+        ],
+    };
+    let by_reason = ReportTable {
+        title: "By reason",
+        headers: vec!["Reason", "Entries", "Loss"],
+        rows: report
+            .by_reason
+            .iter()
+            .map(|reason| {
+                vec![
+                    Cell::text(&reason.label),
+                    Cell::Int(reason.entries),
+                    Cell::Money(reason.loss_value_cents),
+                ]
+            })
+            .collect(),
+    };
+    let items = ReportTable {
+        title: "By ingredient",
+        headers: vec!["Ingredient", "Lost quantity", "Loss"],
+        rows: report
+            .items
+            .iter()
+            .map(|item| {
+                vec![
+                    Cell::text(&item.ingredient_name),
+                    Cell::Number(item.lost_quantity),
+                    Cell::Money(item.loss_value_cents),
+                ]
+            })
+            .collect(),
+    };
+
+    RenderedReport {
+        tables: vec![totals, by_reason, items],
+    }
+}
+
+pub fn menu_engineering_report(matrix: &MenuEngineeringMatrix) -> RenderedReport {
+    let overview = ReportTable {
+        title: "Overview",
+        headers: vec!["Metric", "Value"],
+        rows: vec![
+            vec![Cell::text("Dishes"), Cell::Int(matrix.total_dishes as i64)],
+            vec![Cell::text("Stars"), Cell::Int(matrix.stars as i64)],
+            vec![
+                Cell::text("Plowhorses"),
+                Cell::Int(matrix.plowhorses as i64),
+            ],
+            vec![Cell::text("Puzzles"), Cell::Int(matrix.puzzles as i64)],
+            vec![Cell::text("Dogs"), Cell::Int(matrix.dogs as i64)],
+            vec![
+                Cell::text("Revenue"),
+                Cell::Money(matrix.total_revenue_cents),
+            ],
+            vec![Cell::text("Profit"), Cell::Money(matrix.total_profit_cents)],
+            vec![
+                Cell::text("Average margin, %"),
+                Cell::Number(matrix.avg_profit_margin),
+            ],
+        ],
+    };
+    let dishes = ReportTable {
+        title: "Dishes",
+        headers: vec![
+            "Dish",
+            "Section",
+            "Category",
+            "ABC",
+            "Sold",
+            "Revenue",
+            "Profit",
+            "Margin, %",
+            "Menu mix, %",
+        ],
+        rows: matrix
+            .dishes
+            .iter()
+            .map(|dish| {
+                vec![
+                    Cell::text(&dish.dish_name),
+                    Cell::text(dish.menu_section_name.clone().unwrap_or_default()),
+                    Cell::text(category_name(&dish.category)),
+                    Cell::text(abc_name(&dish.abc_class)),
+                    Cell::Int(dish.sales_volume as i64),
+                    Cell::Money(dish.total_revenue_cents),
+                    Cell::Money(dish.total_profit_cents),
+                    Cell::Number(dish.profit_margin_percent),
+                    Cell::Number(dish.popularity_score * 100.0),
+                ]
+            })
+            .collect(),
+    };
+
+    RenderedReport {
+        tables: vec![overview, dishes],
+    }
+}
+
+pub fn usage_variance_report(report: &UsageVarianceReport) -> RenderedReport {
+    let percent = |value: Option<f64>| match value {
+        Some(value) => Cell::Number(value),
+        None => Cell::text(""),
+    };
+    let totals = ReportTable {
+        title: "Totals",
+        headers: vec!["Metric", "Value"],
+        rows: vec![
+            vec![Cell::text("Dishes sold"), Cell::Int(report.dishes_sold)],
+            vec![
+                Cell::text("Dishes without a resolvable recipe"),
+                Cell::Int(report.unresolved_dishes as i64),
+            ],
+            vec![
+                Cell::text("Theoretical cost"),
+                Cell::Money(report.theoretical_cost_cents),
+            ],
+            vec![
+                Cell::text("Actual cost"),
+                Cell::Money(report.actual_cost_cents),
+            ],
+            vec![
+                Cell::text("Variance"),
+                Cell::Money(report.variance_cost_cents),
+            ],
+            vec![
+                Cell::text("Unexplained variance"),
+                Cell::Money(report.unexplained_cost_cents),
+            ],
+            vec![Cell::text("Variance, %"), percent(report.variance_percent)],
+            vec![
+                Cell::text("Not used by any sold dish"),
+                Cell::Money(report.unattributed_cost_cents),
+            ],
+        ],
+    };
+    let categories = ReportTable {
+        title: "By category",
+        headers: vec![
+            "Category",
+            "Ingredients",
+            "Theoretical cost",
+            "Actual cost",
+            "Variance",
+            "Unexplained",
+            "Variance, %",
+        ],
+        rows: report
+            .categories
+            .iter()
+            .map(|category| {
+                vec![
+                    Cell::text(&category.category_name),
+                    Cell::Int(category.ingredients as i64),
+                    Cell::Money(category.theoretical_cost_cents),
+                    Cell::Money(category.actual_cost_cents),
+                    Cell::Money(category.variance_cost_cents),
+                    Cell::Money(category.unexplained_cost_cents),
+                    percent(category.variance_percent),
+                ]
+            })
+            .collect(),
+    };
+    let dishes = ReportTable {
+        title: "By dish",
+        headers: vec![
+            "Dish",
+            "Portions sold",
+            "Theoretical cost",
+            "Actual cost",
+            "Variance",
+            "Variance, %",
+        ],
+        rows: report
+            .dishes
+            .iter()
+            .map(|dish| {
+                vec![
+                    Cell::text(&dish.dish_name),
+                    Cell::Int(dish.portions_sold),
+                    Cell::Money(dish.theoretical_cost_cents),
+                    Cell::Money(dish.actual_cost_cents),
+                    Cell::Money(dish.variance_cost_cents),
+                    percent(dish.variance_percent),
+                ]
+            })
+            .collect(),
+    };
+    let ingredients = ReportTable {
+        title: "By ingredient",
+        headers: vec![
+            "Ingredient",
+            "Category",
+            "Unit",
+            "Opening",
+            "Receipts",
+            "Closing",
+            "Theoretical",
+            "Actual",
+            "Recorded loss",
+            "Unexplained",
+            "Variance cost",
+            "Variance, %",
+        ],
+        rows: report
+            .ingredients
+            .iter()
+            .map(|item| {
+                vec![
+                    Cell::text(&item.ingredient_name),
+                    Cell::text(&item.category_name),
+                    Cell::text(&item.unit),
+                    Cell::Number(item.opening_quantity),
+                    Cell::Number(item.receipts_quantity),
+                    Cell::Number(item.closing_quantity),
+                    Cell::Number(item.theoretical_quantity),
+                    Cell::Number(item.actual_quantity),
+                    Cell::Number(item.recorded_loss_quantity),
+                    Cell::Number(item.unexplained_quantity),
+                    Cell::Money(item.variance_cost_cents),
+                    percent(item.variance_percent),
+                ]
+            })
+            .collect(),
+    };
+
+    RenderedReport {
+        tables: vec![totals, categories, dishes, ingredients],
+    }
+}
+
+fn category_name(category: &MenuCategory) -> &'static str {
+    match category {
+        MenuCategory::Star => "Star",
+        MenuCategory::Plowhorse => "Plowhorse",
+        MenuCategory::Puzzle => "Puzzle",
+        MenuCategory::Dog => "Dog",
+    }
+}
+
+fn abc_name(class: &AbcClass) -> &'static str {
+    match class {
+        AbcClass::A => "A",
+        AbcClass::B => "B",
+        AbcClass::C => "C",
+    }
+}
+
+fn money(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{}{}.{:02}", sign, cents / 100, cents % 100)
+}
+
+/// Excel sheet names: at most 31 characters, none of `[]:*?/\`
+fn sheet_name(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect()
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub(crate) fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> RenderedReport {
+        RenderedReport {
+            tables: vec![
+                ReportTable {
+                    title: "Totals",
+                    headers: vec!["Metric", "Value"],
+                    rows: vec![vec![Cell::text("Loss"), Cell::Money(-1205)]],
+                },
+                ReportTable {
+                    title: "By ingredient",
+                    headers: vec!["Ingredient", "Lost quantity"],
+                    rows: vec![vec![Cell::text("Butter, salted"), Cell::Number(1.25)]],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn csv_lists_tables_one_after_another() {
+        let csv = String::from_utf8(sample().to_csv()).unwrap();
+        assert_eq!(
+            csv,
+            "\u{feff}Totals\r\nMetric,Value\r\nLoss,-12.05\r\n\r\n\
+             By ingredient\r\nIngredient,Lost quantity\r\n\"Butter, salted\",1.25\r\n"
+        );
+    }
+
+    #[test]
+    fn xlsx_is_a_zip_package() {
+        let xlsx = sample().to_xlsx().unwrap();
+        assert_eq!(&xlsx[..2], b"PK");
+    }
+
+    #[test]
+    fn html_cuts_long_tables() {
+        let report = RenderedReport {
+            tables: vec![ReportTable {
+                title: "Dishes",
+                headers: vec!["Dish"],
+                rows: (0..HTML_ROW_LIMIT + 5)
+                    .map(|i| vec![Cell::text(format!("<dish {}>", i))])
+                    .collect(),
+            }],
+        };
+        let html = report.to_html_tables("First {rows} rows shown.");
+        assert_eq!(html.matches("<td").count(), HTML_ROW_LIMIT);
+        assert!(html.contains("&lt;dish 0&gt;"));
+        assert!(html.contains("First 25 rows shown."));
+    }
+
+    #[test]
+    fn sheet_names_drop_forbidden_characters() {
+        assert_eq!(sheet_name("Loss: by [reason]"), "Loss by reason");
+        assert_eq!(sheet_name(&"x".repeat(40)).len(), 31);
+    }
+}
@@ -0,0 +1,718 @@
+//! Scheduled owner reports delivered by email.
+//!
+//! A subscription names a report (summary, loss report, menu engineering,
+//! usage variance), a weekly or monthly schedule and a list of recipients.
+//! [`ReportSubscriptionService::run_due`] is called by the background job in
+//! `routes.rs`: it claims each due subscription by moving its `next_run_at`
+//! forward, renders the report to CSV, XLSX and an HTML body and sends it
+//! through the [`Mailer`]. Every attempt, failed or not, is stored in
+//! `report_deliveries` together with the rendered files.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::application::report::ReportService;
+use crate::application::report_export::{self, escape_html, RenderedReport};
+use crate::application::{InventoryService, MenuEngineeringService};
+use crate::domain::report_subscription::{ReportFrequency, ReportKind, ReportSchedule};
+use crate::domain::Email;
+use crate::infrastructure::mail::{Attachment, EmailMessage, Mailer};
+use crate::shared::{
+    translate_email_text, AppError, AppResult, EmailText, Language, TenantId, UserId,
+};
+
+const MAX_RECIPIENTS: usize = 10;
+/// Subscriptions handled per scheduler tick; the rest wait for the next one
+const RUN_BATCH: i64 = 50;
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+#[derive(Debug, Deserialize)]
+pub struct CreateReportSubscription {
+    pub report_kind: ReportKind,
+    pub frequency: ReportFrequency,
+    /// ISO weekday (1 = Monday) or day of month (1–28)
+    pub day: u8,
+    #[serde(default = "default_hour_utc")]
+    pub hour_utc: u8,
+    pub recipients: Vec<String>,
+    /// Defaults to the creator's language
+    #[serde(default)]
+    pub language: Option<Language>,
+}
+
+fn default_hour_utc() -> u8 {
+    6
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateReportSubscription {
+    pub frequency: Option<ReportFrequency>,
+    pub day: Option<u8>,
+    pub hour_utc: Option<u8>,
+    pub recipients: Option<Vec<String>>,
+    pub language: Option<Language>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSubscription {
+    pub id: Uuid,
+    pub report_kind: ReportKind,
+    #[serde(flatten)]
+    pub schedule: ReportSchedule,
+    pub recipients: Vec<String>,
+    pub language: Language,
+    pub active: bool,
+    pub created_by: Uuid,
+    #[serde(with = "time::serde::rfc3339")]
+    pub next_run_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_run_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Sent,
+    /// Rendering failed, or at least one recipient could not be reached
+    Failed,
+}
+
+impl DeliveryStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sent => "sent",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// History entry, without the stored files
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportDelivery {
+    pub id: Uuid,
+    pub subscription_id: Option<Uuid>,
+    pub report_kind: ReportKind,
+    #[serde(with = "time::serde::rfc3339")]
+    pub period_start: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub period_end: OffsetDateTime,
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+    /// CSV/XLSX/HTML can be downloaded again
+    pub has_files: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DeliveryFilter {
+    pub subscription_id: Option<Uuid>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryFormat {
+    Csv,
+    Xlsx,
+    Html,
+}
+
+/// A stored file of a past delivery
+pub struct DeliveryFile {
+    pub filename: String,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// Everything sent for one delivery
+struct RenderedDelivery {
+    html_body: String,
+    text_body: String,
+    csv: Vec<u8>,
+    xlsx: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct ReportSubscriptionService {
+    pool: PgPool,
+    report_service: ReportService,
+    inventory_service: InventoryService,
+    menu_engineering_service: MenuEngineeringService,
+    mailer: Arc<dyn Mailer>,
+}
+
+impl ReportSubscriptionService {
+    pub fn new(
+        pool: PgPool,
+        report_service: ReportService,
+        inventory_service: InventoryService,
+        menu_engineering_service: MenuEngineeringService,
+        mailer: Arc<dyn Mailer>,
+    ) -> Self {
+        Self {
+            pool,
+            report_service,
+            inventory_service,
+            menu_engineering_service,
+            mailer,
+        }
+    }
+
+    pub async fn list(&self, tenant_id: TenantId) -> AppResult<Vec<ReportSubscription>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {SUBSCRIPTION_COLUMNS} FROM report_subscriptions WHERE tenant_id = $1 ORDER BY created_at"
+        ))
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(subscription_from_row).collect()
+    }
+
+    pub async fn create(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        language: Language,
+        command: CreateReportSubscription,
+    ) -> AppResult<ReportSubscription> {
+        let schedule = ReportSchedule::new(command.frequency, command.day, command.hour_utc)?;
+        let recipients = normalize_recipients(command.recipients)?;
+        let language = command.language.unwrap_or(language);
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO report_subscriptions
+                (id, tenant_id, created_by, report_kind, frequency, send_day, send_hour_utc,
+                 recipients, language, next_run_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING {SUBSCRIPTION_COLUMNS}
+            "#
+        ))
+        .bind(Uuid::new_v4())
+        .bind(tenant_id.as_uuid())
+        .bind(user_id.as_uuid())
+        .bind(command.report_kind.as_str())
+        .bind(schedule.frequency.as_str())
+        .bind(schedule.day as i16)
+        .bind(schedule.hour_utc as i16)
+        .bind(&recipients)
+        .bind(language.code())
+        .bind(schedule.next_run_after(OffsetDateTime::now_utc()))
+        .fetch_one(&self.pool)
+        .await?;
+        subscription_from_row(&row)
+    }
+
+    /// Changing anything recomputes the next run from now
+    pub async fn update(
+        &self,
+        tenant_id: TenantId,
+        id: Uuid,
+        command: UpdateReportSubscription,
+    ) -> AppResult<ReportSubscription> {
+        let current = self.get(tenant_id, id).await?;
+        let schedule = ReportSchedule::new(
+            command.frequency.unwrap_or(current.schedule.frequency),
+            command.day.unwrap_or(current.schedule.day),
+            command.hour_utc.unwrap_or(current.schedule.hour_utc),
+        )?;
+        let recipients = match command.recipients {
+            Some(recipients) => normalize_recipients(recipients)?,
+            None => current.recipients,
+        };
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE report_subscriptions
+            SET frequency = $3, send_day = $4, send_hour_utc = $5, recipients = $6,
+                language = $7, active = $8, next_run_at = $9, updated_at = NOW()
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING {SUBSCRIPTION_COLUMNS}
+            "#
+        ))
+        .bind(tenant_id.as_uuid())
+        .bind(id)
+        .bind(schedule.frequency.as_str())
+        .bind(schedule.day as i16)
+        .bind(schedule.hour_utc as i16)
+        .bind(&recipients)
+        .bind(command.language.unwrap_or(current.language).code())
+        .bind(command.active.unwrap_or(current.active))
+        .bind(schedule.next_run_after(OffsetDateTime::now_utc()))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Report subscription not found"))?;
+        subscription_from_row(&row)
+    }
+
+    /// Past deliveries stay in the history
+    pub async fn delete(&self, tenant_id: TenantId, id: Uuid) -> AppResult<()> {
+        let result =
+            sqlx::query("DELETE FROM report_subscriptions WHERE tenant_id = $1 AND id = $2")
+                .bind(tenant_id.as_uuid())
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("Report subscription not found"));
+        }
+        Ok(())
+    }
+
+    /// Deliver right away, outside the schedule (`next_run_at` is unchanged)
+    pub async fn send_now(&self, tenant_id: TenantId, id: Uuid) -> AppResult<ReportDelivery> {
+        let subscription = self.get(tenant_id, id).await?;
+        self.deliver(tenant_id, &subscription).await
+    }
+
+    /// Newest first
+    pub async fn list_deliveries(
+        &self,
+        tenant_id: TenantId,
+        filter: DeliveryFilter,
+    ) -> AppResult<Vec<ReportDelivery>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, subscription_id, report_kind, period_start, period_end, recipients,
+                   subject, status, error, csv_data IS NOT NULL AS has_files, created_at
+            FROM report_deliveries
+            WHERE tenant_id = $1
+              AND ($2::UUID IS NULL OR subscription_id = $2)
+            ORDER BY created_at DESC, id
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(filter.subscription_id)
+        .bind(filter.limit.unwrap_or(50).clamp(1, 200))
+        .bind(filter.offset.unwrap_or(0).max(0))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(delivery_from_row).collect()
+    }
+
+    pub async fn delivery_file(
+        &self,
+        tenant_id: TenantId,
+        id: Uuid,
+        format: DeliveryFormat,
+    ) -> AppResult<DeliveryFile> {
+        let row = sqlx::query(
+            r#"
+            SELECT report_kind, period_end, csv_data, xlsx_data, html_body
+            FROM report_deliveries
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Report delivery not found"))?;
+
+        let kind = ReportKind::parse(row.try_get("report_kind")?)?;
+        let period_end: OffsetDateTime = row.try_get("period_end")?;
+        let (data, content_type, extension) = match format {
+            DeliveryFormat::Csv => (
+                row.try_get::<Option<Vec<u8>>, _>("csv_data")?,
+                "text/csv; charset=utf-8",
+                "csv",
+            ),
+            DeliveryFormat::Xlsx => (
+                row.try_get::<Option<Vec<u8>>, _>("xlsx_data")?,
+                XLSX_CONTENT_TYPE,
+                "xlsx",
+            ),
+            DeliveryFormat::Html => (
+                row.try_get::<Option<String>, _>("html_body")?
+                    .map(String::into_bytes),
+                "text/html; charset=utf-8",
+                "html",
+            ),
+        };
+
+        Ok(DeliveryFile {
+            filename: file_name(kind, period_end, extension),
+            content_type,
+            data: data.ok_or_else(|| {
+                AppError::not_found("This delivery failed before its files were rendered")
+            })?,
+        })
+    }
+
+    /// Deliver every due subscription; returns how many were sent.
+    ///
+    /// A subscription is claimed by moving `next_run_at` forward only if it
+    /// still has the value just read, so two instances never send the same
+    /// report twice. Runs missed while the server was down collapse into one.
+    pub async fn run_due(&self) -> AppResult<u32> {
+        let now = OffsetDateTime::now_utc();
+        let due = sqlx::query(&format!(
+            r#"
+            SELECT {SUBSCRIPTION_COLUMNS}, tenant_id
+            FROM report_subscriptions
+            WHERE active AND next_run_at <= $1
+            ORDER BY next_run_at
+            LIMIT $2
+            "#
+        ))
+        .bind(now)
+        .bind(RUN_BATCH)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut sent = 0;
+        for row in &due {
+            let subscription = subscription_from_row(row)?;
+            let tenant_id = TenantId::from_uuid(row.try_get("tenant_id")?);
+
+            let claimed = sqlx::query(
+                r#"
+                UPDATE report_subscriptions SET next_run_at = $2, last_run_at = $3
+                WHERE id = $1 AND next_run_at = $4
+                "#,
+            )
+            .bind(subscription.id)
+            .bind(subscription.schedule.next_run_after(now))
+            .bind(now)
+            .bind(subscription.next_run_at)
+            .execute(&self.pool)
+            .await?
+            .rows_affected()
+                == 1;
+            if !claimed {
+                continue;
+            }
+
+            match self.deliver(tenant_id, &subscription).await {
+                Ok(delivery) if delivery.status == DeliveryStatus::Sent => sent += 1,
+                Ok(delivery) => tracing::warn!(
+                    "Report subscription {} failed: {}",
+                    subscription.id,
+                    delivery.error.unwrap_or_default()
+                ),
+                Err(e) => tracing::error!(
+                    "Report subscription {} could not be recorded: {}",
+                    subscription.id,
+                    e
+                ),
+            }
+        }
+        Ok(sent)
+    }
+
+    async fn get(&self, tenant_id: TenantId, id: Uuid) -> AppResult<ReportSubscription> {
+        let row = sqlx::query(&format!(
+            "SELECT {SUBSCRIPTION_COLUMNS} FROM report_subscriptions WHERE tenant_id = $1 AND id = $2"
+        ))
+        .bind(tenant_id.as_uuid())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Report subscription not found"))?;
+        subscription_from_row(&row)
+    }
+
+    /// Render, send to every recipient and record the attempt. Only a
+    /// failure to record is returned as an error.
+    async fn deliver(
+        &self,
+        tenant_id: TenantId,
+        subscription: &ReportSubscription,
+    ) -> AppResult<ReportDelivery> {
+        let period_end = OffsetDateTime::now_utc();
+        let period_days = subscription.schedule.period_days(period_end);
+        let period_start = period_end - Duration::days(period_days as i64);
+        let kind = subscription.report_kind;
+        let lang = subscription.language;
+        let subject = format!(
+            "{} · {} – {}",
+            kind.title(lang),
+            period_start.date(),
+            period_end.date()
+        );
+
+        let rendered = self
+            .render(
+                tenant_id,
+                subscription,
+                period_start,
+                period_end,
+                period_days,
+            )
+            .await;
+        let (status, error) = match &rendered {
+            Err(e) => {
+                tracing::error!(
+                    "Report {} for tenant {} failed to render: {:?}",
+                    kind.as_str(),
+                    tenant_id,
+                    e
+                );
+                (DeliveryStatus::Failed, Some(e.to_string()))
+            }
+            Ok(rendered) => {
+                let mut failures = Vec::new();
+                for recipient in &subscription.recipients {
+                    let message = EmailMessage {
+                        to: recipient.clone(),
+                        subject: subject.clone(),
+                        text_body: rendered.text_body.clone(),
+                        html_body: Some(rendered.html_body.clone()),
+                        attachments: vec![
+                            Attachment {
+                                filename: file_name(kind, period_end, "csv"),
+                                content_type: "text/csv".to_string(),
+                                data: rendered.csv.clone(),
+                            },
+                            Attachment {
+                                filename: file_name(kind, period_end, "xlsx"),
+                                content_type: XLSX_CONTENT_TYPE.to_string(),
+                                data: rendered.xlsx.clone(),
+                            },
+                        ],
+                    };
+                    if let Err(e) = self.mailer.send(&message).await {
+                        failures.push(format!("{}: {}", recipient, e));
+                    }
+                }
+                if failures.is_empty() {
+                    (DeliveryStatus::Sent, None)
+                } else {
+                    (DeliveryStatus::Failed, Some(failures.join("; ")))
+                }
+            }
+        };
+        let rendered = rendered.ok();
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO report_deliveries
+                (id, tenant_id, subscription_id, report_kind, period_start, period_end,
+                 recipients, subject, status, error, html_body, csv_data, xlsx_data)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING id, subscription_id, report_kind, period_start, period_end, recipients,
+                      subject, status, error, csv_data IS NOT NULL AS has_files, created_at
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(tenant_id.as_uuid())
+        .bind(subscription.id)
+        .bind(kind.as_str())
+        .bind(period_start)
+        .bind(period_end)
+        .bind(&subscription.recipients)
+        .bind(&subject)
+        .bind(status.as_str())
+        .bind(&error)
+        .bind(rendered.as_ref().map(|r| r.html_body.as_str()))
+        .bind(rendered.as_ref().map(|r| r.csv.as_slice()))
+        .bind(rendered.as_ref().map(|r| r.xlsx.as_slice()))
+        .fetch_one(&self.pool)
+        .await?;
+
+        tracing::info!(
+            "📊 Report {} for tenant {}: {} to {} recipient(s)",
+            kind.as_str(),
+            tenant_id,
+            status.as_str(),
+            subscription.recipients.len()
+        );
+        delivery_from_row(&row)
+    }
+
+    async fn render(
+        &self,
+        tenant_id: TenantId,
+        subscription: &ReportSubscription,
+        period_start: OffsetDateTime,
+        period_end: OffsetDateTime,
+        period_days: u32,
+    ) -> AppResult<RenderedDelivery> {
+        let lang = subscription.language;
+        let user_id = UserId::from_uuid(subscription.created_by);
+
+        let report: RenderedReport = match subscription.report_kind {
+            ReportKind::Summary => report_export::summary_report(
+                &self
+                    .report_service
+                    .get_summary(user_id, tenant_id, lang, period_days)
+                    .await?,
+            ),
+            ReportKind::LossReport => report_export::loss_report(
+                &self
+                    .inventory_service
+                    .get_loss_report(tenant_id, period_days as i32, lang.code())
+                    .await?,
+            ),
+            ReportKind::MenuEngineering => report_export::menu_engineering_report(
+                &self
+                    .menu_engineering_service
+                    .analyze_menu(user_id, tenant_id, lang, period_days, None)
+                    .await?,
+            ),
+            ReportKind::UsageVariance => report_export::usage_variance_report(
+                &self
+                    .report_service
+                    .get_usage_variance(tenant_id, lang, period_days)
+                    .await?,
+            ),
+        };
+
+        let t = |key| translate_email_text(key, lang);
+        let title = subscription.report_kind.title(lang);
+        let intro = t(EmailText::ReportIntro)
+            .replace("{report}", title)
+            .replace("{from}", &period_start.date().to_string())
+            .replace("{to}", &period_end.date().to_string());
+
+        let html_body = format!(
+            "<!DOCTYPE html>\n<html lang=\"{}\"><head><meta charset=\"utf-8\"></head>\
+             <body style=\"font-family:sans-serif;color:#111\"><h2>{}</h2><p>{}</p>{}\
+             <p style=\"color:#666;font-size:12px;margin-top:24px\">{}<br>— {}</p></body></html>\n",
+            lang.code(),
+            escape_html(title),
+            escape_html(&intro),
+            report.to_html_tables(t(EmailText::ReportTruncated)),
+            escape_html(t(EmailText::ReportFooter)),
+            escape_html(t(EmailText::Signature)),
+        );
+        let text_body = format!(
+            "{}\n\n{}\n\n— {}\n",
+            intro,
+            t(EmailText::ReportFooter),
+            t(EmailText::Signature)
+        );
+
+        Ok(RenderedDelivery {
+            html_body,
+            text_body,
+            csv: report.to_csv(),
+            xlsx: report.to_xlsx()?,
+        })
+    }
+}
+
+const SUBSCRIPTION_COLUMNS: &str = "id, report_kind, frequency, send_day, send_hour_utc, \
+    recipients, language, active, created_by, next_run_at, last_run_at, created_at";
+
+fn subscription_from_row(row: &PgRow) -> AppResult<ReportSubscription> {
+    let schedule = ReportSchedule::new(
+        ReportFrequency::parse(row.try_get("frequency")?)?,
+        row.try_get::<i16, _>("send_day")? as u8,
+        row.try_get::<i16, _>("send_hour_utc")? as u8,
+    )?;
+    Ok(ReportSubscription {
+        id: row.try_get("id")?,
+        report_kind: ReportKind::parse(row.try_get("report_kind")?)?,
+        schedule,
+        recipients: row.try_get("recipients")?,
+        language: Language::from_code(row.try_get("language")?).unwrap_or_default(),
+        active: row.try_get("active")?,
+        created_by: row.try_get("created_by")?,
+        next_run_at: row.try_get("next_run_at")?,
+        last_run_at: row.try_get("last_run_at")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn delivery_from_row(row: &PgRow) -> AppResult<ReportDelivery> {
+    let status = match row.try_get::<&str, _>("status")? {
+        "sent" => DeliveryStatus::Sent,
+        _ => DeliveryStatus::Failed,
+    };
+    Ok(ReportDelivery {
+        id: row.try_get("id")?,
+        subscription_id: row.try_get("subscription_id")?,
+        report_kind: ReportKind::parse(row.try_get("report_kind")?)?,
+        period_start: row.try_get("period_start")?,
+        period_end: row.try_get("period_end")?,
+        recipients: row.try_get("recipients")?,
+        subject: row.try_get("subject")?,
+        status,
+        error: row.try_get("error")?,
+        has_files: row.try_get("has_files")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+/// `summary-2026-10-19.xlsx`
+fn file_name(kind: ReportKind, period_end: OffsetDateTime, extension: &str) -> String {
+    format!(
+        "{}-{}.{}",
+        kind.as_str().replace('_', "-"),
+        period_end.date(),
+        extension
+    )
+}
+
+/// Valid, lowercased, without duplicates, 1 to [`MAX_RECIPIENTS`]
+fn normalize_recipients(recipients: Vec<String>) -> AppResult<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        let email = Email::new(recipient)?.as_str().to_string();
+        if !normalized.contains(&email) {
+            normalized.push(email);
+        }
+    }
+    if normalized.is_empty() {
+        return Err(AppError::validation("At least one recipient is required"));
+    }
+    if normalized.len() > MAX_RECIPIENTS {
+        return Err(AppError::validation(format!(
+            "At most {} recipients per subscription",
+            MAX_RECIPIENTS
+        )));
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn recipients_are_normalized_and_deduplicated() {
+        let recipients = normalize_recipients(vec![
+            "Owner@Example.com".to_string(),
+            " owner@example.com ".to_string(),
+            "chef@example.com".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(recipients, vec!["owner@example.com", "chef@example.com"]);
+    }
+
+    #[test]
+    fn recipients_are_required_and_capped() {
+        assert!(normalize_recipients(vec![]).is_err());
+        assert!(normalize_recipients(vec!["not-an-email".to_string()]).is_err());
+        let many = (0..=MAX_RECIPIENTS)
+            .map(|i| format!("cook{}@example.com", i))
+            .collect();
+        assert!(normalize_recipients(many).is_err());
+    }
+
+    #[test]
+    fn file_name_uses_kind_and_period_end() {
+        assert_eq!(
+            file_name(
+                ReportKind::UsageVariance,
+                datetime!(2026-10-19 06:00 UTC),
+                "xlsx"
+            ),
+            "usage-variance-2026-10-19.xlsx"
+        );
+    }
+}
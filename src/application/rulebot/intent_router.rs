@@ -426,7 +426,8 @@ fn score_all_intents(text: &str) -> [(Intent, i32); 9] {
 }
 
 /// Sum weights of all matching keywords.
-fn sum_scores(text: &str, keywords: &[kw::ScoredKeyword]) -> i32 {
+/// Also used by the copilot rule planner with its own keyword tables.
+pub fn sum_scores(text: &str, keywords: &[kw::ScoredKeyword]) -> i32 {
     keywords
         .iter()
         .filter(|(kw, _)| text.contains(kw))
@@ -0,0 +1,622 @@
+//! StocktakeService — физическая инвентаризация.
+//!
+//! Жизненный цикл: open → (closed | cancelled).
+//! Сотрудники постят строки подсчёта; при первом подсчёте ингредиента
+//! запоминаем книжный остаток (сумма `remaining_quantity` активных партий).
+//! При закрытии расхождение считается от этого снимка и проводится
+//! ADJUSTMENT движениями (FIFO) по текущим партиям; отчёт — в деньгах.
+
+use crate::domain::{
+    catalog::CatalogIngredientId,
+    inventory::{BatchStatus, InventoryMovement, MovementType, Quantity},
+    stocktake::{
+        AdjustmentPlan, BatchStock, IngredientVariance, StocktakeStatus, StocktakeVarianceReport,
+    },
+};
+use crate::infrastructure::persistence::{InventoryBatchRepository, InventoryBatchRepositoryTrait};
+use crate::shared::{AppError, AppResult, Language, TenantId, UserId};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// reference_type for movements posted by a stocktake
+pub const STOCKTAKE_REFERENCE_TYPE: &str = "stocktake";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StocktakeSession {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub opened_by: Uuid,
+    pub closed_by: Option<Uuid>,
+    pub status: StocktakeStatus,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub opened_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub closed_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StocktakeCountLine {
+    pub id: Uuid,
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub counted_quantity: f64,
+    pub counted_by: Uuid,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub counted_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StocktakeSessionDetail {
+    #[serde(flatten)]
+    pub session: StocktakeSession,
+    pub counts: Vec<StocktakeCountLine>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StocktakeCountInput {
+    pub catalog_ingredient_id: Uuid,
+    pub counted_quantity: f64,
+    pub note: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct StocktakeService {
+    inventory_repo: Arc<InventoryBatchRepository>,
+    pool: PgPool,
+}
+
+impl StocktakeService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            inventory_repo: Arc::new(InventoryBatchRepository::new(pool.clone())),
+            pool,
+        }
+    }
+
+    /// Open a new count session (one open session per tenant)
+    pub async fn open_session(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        note: Option<String>,
+    ) -> AppResult<StocktakeSession> {
+        let existing: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM stocktake_sessions WHERE tenant_id = $1 AND status = 'open'",
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(id) = existing {
+            return Err(AppError::conflict(format!(
+                "Stocktake session {} is already open",
+                id
+            )));
+        }
+
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO stocktake_sessions (id, tenant_id, opened_by, status, note) \
+             VALUES ($1, $2, $3, 'open', $4)",
+        )
+        .bind(id)
+        .bind(tenant_id.as_uuid())
+        .bind(user_id.as_uuid())
+        .bind(&note)
+        .execute(&self.pool)
+        .await?;
+
+        tracing::info!(
+            "📋 Stocktake session opened: id={} tenant={}",
+            id,
+            tenant_id.as_uuid()
+        );
+
+        self.find_session(tenant_id, id)
+            .await?
+            .ok_or_else(|| AppError::internal("Failed to load created stocktake session"))
+    }
+
+    pub async fn list_sessions(
+        &self,
+        tenant_id: TenantId,
+        limit: i64,
+    ) -> AppResult<Vec<StocktakeSession>> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, opened_by, closed_by, status, note, opened_at, closed_at \
+             FROM stocktake_sessions WHERE tenant_id = $1 ORDER BY opened_at DESC LIMIT $2",
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_session).collect()
+    }
+
+    /// Session with all posted count lines
+    pub async fn get_session(
+        &self,
+        tenant_id: TenantId,
+        session_id: Uuid,
+        language: Language,
+    ) -> AppResult<StocktakeSessionDetail> {
+        let session = self
+            .find_session(tenant_id, session_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Stocktake session not found"))?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                sc.id,
+                sc.catalog_ingredient_id,
+                CASE
+                    WHEN $2 = 'ru' THEN COALESCE(ci.name_ru, ci.name_en, 'Unknown')
+                    WHEN $2 = 'pl' THEN COALESCE(ci.name_pl, ci.name_en, 'Unknown')
+                    WHEN $2 = 'uk' THEN COALESCE(ci.name_uk, ci.name_en, 'Unknown')
+                    ELSE COALESCE(ci.name_en, 'Unknown')
+                END as ingredient_name,
+                sc.counted_quantity,
+                sc.counted_by,
+                sc.note,
+                sc.counted_at
+            FROM stocktake_counts sc
+            JOIN catalog_ingredients ci ON ci.id = sc.catalog_ingredient_id
+            WHERE sc.session_id = $1
+            ORDER BY sc.counted_at ASC
+            "#,
+        )
+        .bind(session_id)
+        .bind(language.code())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut counts = Vec::with_capacity(rows.len());
+        for row in rows {
+            counts.push(StocktakeCountLine {
+                id: row.try_get("id")?,
+                ingredient_id: row.try_get("catalog_ingredient_id")?,
+                ingredient_name: row.try_get("ingredient_name")?,
+                counted_quantity: row
+                    .try_get::<Decimal, _>("counted_quantity")?
+                    .to_f64()
+                    .unwrap_or(0.0),
+                counted_by: row.try_get("counted_by")?,
+                note: row.try_get("note")?,
+                counted_at: row.try_get("counted_at")?,
+            });
+        }
+
+        Ok(StocktakeSessionDetail { session, counts })
+    }
+
+    /// Post count lines. Lines for the same ingredient are summed on close.
+    /// The first line of an ingredient snapshots its book stock; later lines
+    /// reuse that snapshot.
+    pub async fn add_counts(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        session_id: Uuid,
+        lines: Vec<StocktakeCountInput>,
+    ) -> AppResult<usize> {
+        if lines.is_empty() {
+            return Err(AppError::validation("At least one count line is required"));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        Self::lock_open_session(&mut tx, tenant_id, session_id).await?;
+
+        let ingredient_ids: Vec<Uuid> = lines.iter().map(|l| l.catalog_ingredient_id).collect();
+        let unknown: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT requested.id FROM UNNEST($1::UUID[]) AS requested(id)
+            WHERE NOT EXISTS (SELECT 1 FROM catalog_ingredients ci WHERE ci.id = requested.id)
+            LIMIT 1
+            "#,
+        )
+        .bind(&ingredient_ids)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(id) = unknown {
+            return Err(AppError::not_found(format!(
+                "Catalog ingredient {} not found",
+                id
+            )));
+        }
+
+        for line in &lines {
+            let quantity = Quantity::new(line.counted_quantity)?;
+            sqlx::query(
+                r#"
+                INSERT INTO stocktake_counts
+                    (id, session_id, tenant_id, catalog_ingredient_id, counted_quantity, counted_by,
+                     note, expected_quantity)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, COALESCE(
+                    (SELECT expected_quantity FROM stocktake_counts
+                     WHERE session_id = $2 AND catalog_ingredient_id = $4
+                       AND expected_quantity IS NOT NULL
+                     ORDER BY counted_at, id
+                     LIMIT 1),
+                    (SELECT COALESCE(SUM(remaining_quantity), 0) FROM inventory_batches
+                     WHERE tenant_id = $3 AND catalog_ingredient_id = $4 AND status = 'active')
+                ))
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(session_id)
+            .bind(tenant_id.as_uuid())
+            .bind(line.catalog_ingredient_id)
+            .bind(quantity.decimal())
+            .bind(user_id.as_uuid())
+            .bind(&line.note)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(lines.len())
+    }
+
+    /// Remove a mistaken count line while the session is open
+    pub async fn delete_count(
+        &self,
+        tenant_id: TenantId,
+        session_id: Uuid,
+        count_id: Uuid,
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        Self::lock_open_session(&mut tx, tenant_id, session_id).await?;
+
+        let rows = sqlx::query("DELETE FROM stocktake_counts WHERE id = $1 AND session_id = $2")
+            .bind(count_id)
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        if rows == 0 {
+            return Err(AppError::not_found("Count line not found"));
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Cancel an open session without touching stock
+    pub async fn cancel_session(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        session_id: Uuid,
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        Self::lock_open_session(&mut tx, tenant_id, session_id).await?;
+
+        sqlx::query(
+            "UPDATE stocktake_sessions SET status = 'cancelled', closed_by = $1, closed_at = NOW() \
+             WHERE id = $2",
+        )
+        .bind(user_id.as_uuid())
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Close the session: compare counts with the book stock snapshotted at
+    /// count time, post the variance as ADJUSTMENT movements FIFO-style on
+    /// the batches as they are now and freeze the variance report.
+    ///
+    /// Only counted ingredients are adjusted — a partial count never zeroes
+    /// the rest of the store.
+    pub async fn close_session(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        session_id: Uuid,
+        language: Language,
+    ) -> AppResult<StocktakeVarianceReport> {
+        let mut tx = self.pool.begin().await?;
+        Self::lock_open_session(&mut tx, tenant_id, session_id).await?;
+
+        // 1. Aggregate count lines per ingredient, in ID order so batch
+        //    locks are taken in the same order as sale booking
+        let counted_rows = sqlx::query(
+            r#"
+            SELECT sc.catalog_ingredient_id, ci.category_id, SUM(sc.counted_quantity) as counted,
+                   (ARRAY_AGG(sc.expected_quantity ORDER BY sc.counted_at, sc.id)
+                        FILTER (WHERE sc.expected_quantity IS NOT NULL))[1] AS expected
+            FROM stocktake_counts sc
+            JOIN catalog_ingredients ci ON ci.id = sc.catalog_ingredient_id
+            WHERE sc.session_id = $1
+            GROUP BY sc.catalog_ingredient_id, ci.category_id
+            ORDER BY sc.catalog_ingredient_id
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if counted_rows.is_empty() {
+            return Err(AppError::validation(
+                "Cannot close a stocktake without any counts",
+            ));
+        }
+
+        // Prices for variance that no active batch can carry
+        let counted_ids = counted_rows
+            .iter()
+            .map(|row| {
+                row.try_get("catalog_ingredient_id")
+                    .map(CatalogIngredientId::from_uuid)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let strategy = self.inventory_repo.costing_strategy(tenant_id).await?;
+        let fallback_prices = self
+            .inventory_repo
+            .unit_prices(tenant_id, strategy, &counted_ids)
+            .await?;
+
+        let now = OffsetDateTime::now_utc();
+        let mut adjusted_batches = 0;
+
+        for row in counted_rows {
+            let ingredient_id: Uuid = row.try_get("catalog_ingredient_id")?;
+            let category_id: Option<Uuid> = row.try_get("category_id")?;
+            let counted: Decimal = row.try_get("counted")?;
+            let expected: Option<Decimal> = row.try_get("expected")?;
+
+            // 2. Lock active batches (FIFO order) and plan adjustments
+            let mut batches = self
+                .inventory_repo
+                .list_active_by_ingredient_for_update(
+                    &mut tx,
+                    tenant_id,
+                    CatalogIngredientId::from_uuid(ingredient_id),
+                    None,
+                )
+                .await?;
+
+            let stock: Vec<BatchStock> = batches
+                .iter()
+                .map(|b| BatchStock {
+                    batch_id: b.id,
+                    remaining: b.remaining_quantity.decimal(),
+                    price_per_unit_cents: b.price_per_unit.as_cents(),
+                })
+                .collect();
+
+            // Lines posted before snapshots existed compare with stock at close
+            let mut plan = match expected {
+                Some(expected) => AdjustmentPlan::build_against(&stock, expected, counted)?,
+                None => AdjustmentPlan::build(&stock, counted)?,
+            };
+            if stock.is_empty() {
+                plan.unposted_unit_cost_cents = fallback_prices
+                    .get(&CatalogIngredientId::from_uuid(ingredient_id))
+                    .map(|price| price.as_cents())
+                    .unwrap_or(0);
+            }
+
+            // 3. Apply to batches + record ADJUSTMENT movements
+            for adjustment in &plan.adjustments {
+                let Some(batch) = batches.iter_mut().find(|b| b.id == adjustment.batch_id) else {
+                    continue;
+                };
+
+                let new_remaining = batch.remaining_quantity.decimal() + adjustment.delta;
+                batch.remaining_quantity = Quantity::from_decimal(new_remaining)?;
+                if new_remaining <= Decimal::ZERO {
+                    batch.status = BatchStatus::Exhausted;
+                }
+                batch.updated_at = now;
+
+                self.inventory_repo
+                    .update_in_transaction(&mut tx, batch)
+                    .await?;
+
+                // Signed quantity: negative = shrinkage, positive = surplus
+                let mut movement = InventoryMovement::new(
+                    tenant_id,
+                    batch.id,
+                    MovementType::Adjustment,
+                    adjustment.delta,
+                    adjustment.unit_cost_cents,
+                );
+                movement.reference_id = Some(session_id);
+                movement.reference_type = Some(STOCKTAKE_REFERENCE_TYPE.to_string());
+                movement.reason = Some(
+                    if adjustment.delta < Decimal::ZERO {
+                        "shrinkage"
+                    } else {
+                        "surplus"
+                    }
+                    .to_string(),
+                );
+
+                self.inventory_repo
+                    .record_movement(&mut tx, &movement)
+                    .await?;
+                adjusted_batches += 1;
+            }
+
+            // 4. Freeze the variance line
+            sqlx::query(
+                r#"
+                INSERT INTO stocktake_variances
+                    (session_id, tenant_id, catalog_ingredient_id, category_id,
+                     expected_quantity, counted_quantity, variance_quantity,
+                     unposted_quantity, variance_cents)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+            )
+            .bind(session_id)
+            .bind(tenant_id.as_uuid())
+            .bind(ingredient_id)
+            .bind(category_id)
+            .bind(plan.expected)
+            .bind(plan.counted)
+            .bind(plan.variance())
+            .bind(plan.unposted)
+            .bind(plan.variance_cents())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE stocktake_sessions SET status = 'closed', closed_by = $1, closed_at = $2 \
+             WHERE id = $3",
+        )
+        .bind(user_id.as_uuid())
+        .bind(now)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "✅ Stocktake session closed: id={} adjusted_batches={}",
+            session_id,
+            adjusted_batches
+        );
+
+        self.get_report(tenant_id, session_id, language).await
+    }
+
+    /// Variance report of a closed session (per ingredient + per category)
+    pub async fn get_report(
+        &self,
+        tenant_id: TenantId,
+        session_id: Uuid,
+        language: Language,
+    ) -> AppResult<StocktakeVarianceReport> {
+        let session = self
+            .find_session(tenant_id, session_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Stocktake session not found"))?;
+
+        if session.status != StocktakeStatus::Closed {
+            return Err(AppError::validation(format!(
+                "Variance report is available for closed sessions only (status: {})",
+                session.status.as_str()
+            )));
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                sv.catalog_ingredient_id,
+                CASE
+                    WHEN $2 = 'ru' THEN COALESCE(ci.name_ru, ci.name_en, 'Unknown')
+                    WHEN $2 = 'pl' THEN COALESCE(ci.name_pl, ci.name_en, 'Unknown')
+                    WHEN $2 = 'uk' THEN COALESCE(ci.name_uk, ci.name_en, 'Unknown')
+                    ELSE COALESCE(ci.name_en, 'Unknown')
+                END as ingredient_name,
+                sv.category_id,
+                COALESCE(cct_user.name, cct_en.name, 'Unknown') as category_name,
+                sv.expected_quantity,
+                sv.counted_quantity,
+                sv.variance_quantity,
+                sv.unposted_quantity,
+                sv.variance_cents
+            FROM stocktake_variances sv
+            JOIN catalog_ingredients ci ON ci.id = sv.catalog_ingredient_id
+            LEFT JOIN catalog_category_translations cct_user
+                ON cct_user.category_id = sv.category_id AND cct_user.language = $2
+            LEFT JOIN catalog_category_translations cct_en
+                ON cct_en.category_id = sv.category_id AND cct_en.language = 'en'
+            WHERE sv.session_id = $1
+            ORDER BY sv.variance_cents ASC
+            "#,
+        )
+        .bind(session_id)
+        .bind(language.code())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let to_f64 = |v: Decimal| v.to_f64().unwrap_or(0.0);
+        let mut items = Vec::with_capacity(rows.len());
+        for row in rows {
+            items.push(IngredientVariance {
+                ingredient_id: row.try_get("catalog_ingredient_id")?,
+                ingredient_name: row.try_get("ingredient_name")?,
+                category_id: row.try_get("category_id")?,
+                category_name: row.try_get("category_name")?,
+                expected_quantity: to_f64(row.try_get("expected_quantity")?),
+                counted_quantity: to_f64(row.try_get("counted_quantity")?),
+                variance_quantity: to_f64(row.try_get("variance_quantity")?),
+                unposted_quantity: to_f64(row.try_get("unposted_quantity")?),
+                variance_cents: row.try_get("variance_cents")?,
+            });
+        }
+
+        Ok(StocktakeVarianceReport::build(session_id, items))
+    }
+
+    async fn find_session(
+        &self,
+        tenant_id: TenantId,
+        session_id: Uuid,
+    ) -> AppResult<Option<StocktakeSession>> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, opened_by, closed_by, status, note, opened_at, closed_at \
+             FROM stocktake_sessions WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(session_id)
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(Self::row_to_session).transpose()
+    }
+
+    /// Lock the session row and make sure it is still open
+    async fn lock_open_session(
+        tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+        tenant_id: TenantId,
+        session_id: Uuid,
+    ) -> AppResult<()> {
+        let status: Option<String> = sqlx::query_scalar(
+            "SELECT status FROM stocktake_sessions WHERE id = $1 AND tenant_id = $2 FOR UPDATE",
+        )
+        .bind(session_id)
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        match status.as_deref() {
+            None => Err(AppError::not_found("Stocktake session not found")),
+            Some("open") => Ok(()),
+            Some(other) => Err(AppError::validation(format!(
+                "Stocktake session is not open (status: {})",
+                other
+            ))),
+        }
+    }
+
+    fn row_to_session(row: &sqlx::postgres::PgRow) -> AppResult<StocktakeSession> {
+        let status: String = row.try_get("status")?;
+        Ok(StocktakeSession {
+            id: row.try_get("id")?,
+            tenant_id: row.try_get("tenant_id")?,
+            opened_by: row.try_get("opened_by")?,
+            closed_by: row.try_get("closed_by")?,
+            status: StocktakeStatus::parse(&status)?,
+            note: row.try_get("note")?,
+            opened_at: row.try_get("opened_at")?,
+            closed_at: row.try_get("closed_at")?,
+        })
+    }
+}
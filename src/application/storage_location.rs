@@ -0,0 +1,552 @@
+//! Storage locations and inventory transfers within a tenant.
+//!
+//! Every batch lives at a location (central kitchen walk-in, dry store, bar,
+//! outlet). A transfer consumes source batches FIFO and creates destination
+//! batches that keep the original price, receipt and expiry dates, so costing
+//! and expiry tracking are unaffected by moving stock around.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::{
+    catalog::CatalogIngredientId,
+    inventory::{BatchStatus, InventoryBatch, InventoryMovement, MovementType, Quantity},
+    storage_location::{validate_transfer, LocationKind, StorageLocation, StorageLocationId},
+};
+use crate::infrastructure::persistence::{InventoryBatchRepository, InventoryBatchRepositoryTrait};
+use crate::shared::{AppError, AppResult, TenantId, UserId};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateStorageLocationInput {
+    pub name: String,
+    pub kind: LocationKind,
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateStorageLocationInput {
+    pub name: Option<String>,
+    pub kind: Option<LocationKind>,
+    /// Only `true` is meaningful: another location becomes default instead of clearing it
+    pub is_default: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTransferInput {
+    pub from_location_id: Uuid,
+    pub to_location_id: Uuid,
+    pub catalog_ingredient_id: Uuid,
+    pub quantity: f64,
+    pub note: Option<String>,
+}
+
+/// One source batch split by a transfer
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferLine {
+    pub source_batch_id: Uuid,
+    pub destination_batch_id: Uuid,
+    pub quantity: f64,
+    pub price_per_unit_cents: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InventoryTransfer {
+    pub id: Uuid,
+    pub from_location_id: Uuid,
+    pub to_location_id: Uuid,
+    pub catalog_ingredient_id: Uuid,
+    pub quantity: f64,
+    pub total_cost_cents: i64,
+    pub note: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// Filled on creation only (listing returns headers)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<TransferLine>,
+}
+
+#[derive(Clone)]
+pub struct StorageLocationService {
+    pool: PgPool,
+    inventory_repo: Arc<InventoryBatchRepository>,
+}
+
+impl StorageLocationService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            inventory_repo: Arc::new(InventoryBatchRepository::new(pool.clone())),
+            pool,
+        }
+    }
+
+    pub async fn list_locations(
+        &self,
+        tenant_id: TenantId,
+        include_inactive: bool,
+    ) -> AppResult<Vec<StorageLocation>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, tenant_id, name, kind, is_default, is_active, created_at, updated_at
+            FROM storage_locations
+            WHERE tenant_id = $1 AND ($2 OR is_active)
+            ORDER BY is_default DESC, name
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(include_inactive)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(row_to_location).collect()
+    }
+
+    pub async fn get_location(
+        &self,
+        tenant_id: TenantId,
+        id: StorageLocationId,
+    ) -> AppResult<StorageLocation> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, tenant_id, name, kind, is_default, is_active, created_at, updated_at
+            FROM storage_locations
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+        )
+        .bind(id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Storage location not found"))?;
+
+        row_to_location(&row)
+    }
+
+    /// Create a location. The tenant's first location becomes the default and
+    /// adopts batches received before locations existed.
+    pub async fn create_location(
+        &self,
+        tenant_id: TenantId,
+        input: CreateStorageLocationInput,
+    ) -> AppResult<StorageLocation> {
+        let mut location = StorageLocation::new(tenant_id, input.name, input.kind)?;
+
+        let mut tx = self.pool.begin().await?;
+        self.ensure_unique_name(&mut tx, tenant_id, &location.name, None)
+            .await?;
+
+        let has_default: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM storage_locations WHERE tenant_id = $1 AND is_default)",
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_one(&mut *tx)
+        .await?;
+        location.is_default = input.is_default || !has_default;
+
+        if location.is_default {
+            clear_default(&mut tx, tenant_id).await?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO storage_locations
+                (id, tenant_id, name, kind, is_default, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(location.id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .bind(&location.name)
+        .bind(location.kind.as_str())
+        .bind(location.is_default)
+        .bind(location.is_active)
+        .bind(location.created_at)
+        .bind(location.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        if location.is_default {
+            sqlx::query(
+                "UPDATE inventory_batches SET location_id = $2
+                 WHERE tenant_id = $1 AND location_id IS NULL",
+            )
+            .bind(tenant_id.as_uuid())
+            .bind(location.id.as_uuid())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(location)
+    }
+
+    pub async fn update_location(
+        &self,
+        tenant_id: TenantId,
+        id: StorageLocationId,
+        input: UpdateStorageLocationInput,
+    ) -> AppResult<StorageLocation> {
+        let mut location = self.get_location(tenant_id, id).await?;
+        if !location.is_active {
+            return Err(AppError::validation(
+                "Cannot update an inactive storage location",
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        if let Some(name) = input.name {
+            location.rename(name)?;
+            self.ensure_unique_name(&mut tx, tenant_id, &location.name, Some(id))
+                .await?;
+        }
+        if let Some(kind) = input.kind {
+            location.kind = kind;
+        }
+        if input.is_default == Some(true) && !location.is_default {
+            clear_default(&mut tx, tenant_id).await?;
+            location.is_default = true;
+        }
+        location.updated_at = OffsetDateTime::now_utc();
+
+        sqlx::query(
+            r#"
+            UPDATE storage_locations
+            SET name = $3, kind = $4, is_default = $5, updated_at = $6
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+        )
+        .bind(id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .bind(&location.name)
+        .bind(location.kind.as_str())
+        .bind(location.is_default)
+        .bind(location.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(location)
+    }
+
+    /// Deactivate an empty, non-default location
+    pub async fn deactivate_location(
+        &self,
+        tenant_id: TenantId,
+        id: StorageLocationId,
+    ) -> AppResult<()> {
+        let location = self.get_location(tenant_id, id).await?;
+        if location.is_default {
+            return Err(AppError::validation(
+                "Cannot deactivate the default storage location; make another location default first",
+            ));
+        }
+
+        let has_stock: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM inventory_batches
+             WHERE location_id = $1 AND status = 'active' AND remaining_quantity > 0)",
+        )
+        .bind(id.as_uuid())
+        .fetch_one(&self.pool)
+        .await?;
+        if has_stock {
+            return Err(AppError::validation(
+                "Storage location still holds stock; transfer it out first",
+            ));
+        }
+
+        sqlx::query(
+            "UPDATE storage_locations SET is_active = false, updated_at = NOW()
+             WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Move quantity of one ingredient between locations (FIFO).
+    ///
+    /// Each consumed source batch gets a TRANSFER_OUT movement and a
+    /// destination batch with a matching TRANSFER_IN movement; both reference
+    /// the transfer. Transfers never create shortfalls: missing stock rejects
+    /// the whole transfer.
+    pub async fn transfer(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        input: CreateTransferInput,
+    ) -> AppResult<InventoryTransfer> {
+        let from = StorageLocationId::from_uuid(input.from_location_id);
+        let to = StorageLocationId::from_uuid(input.to_location_id);
+        validate_transfer(from, to, input.quantity)?;
+
+        let catalog_id = CatalogIngredientId::from_uuid(input.catalog_ingredient_id);
+        let target_qty = Quantity::new(input.quantity)?.decimal();
+        let transfer_id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let mut tx = self.pool.begin().await?;
+
+        let active: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM storage_locations
+             WHERE tenant_id = $1 AND id = ANY($2) AND is_active",
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(vec![from.as_uuid(), to.as_uuid()])
+        .fetch_one(&mut *tx)
+        .await?;
+        if active != 2 {
+            return Err(AppError::not_found("Storage location not found"));
+        }
+
+        let batches = self
+            .inventory_repo
+            .list_active_by_ingredient_for_update(&mut tx, tenant_id, catalog_id, Some(from))
+            .await?;
+
+        let available: Decimal = batches.iter().map(|b| b.remaining_quantity.decimal()).sum();
+        if available < target_qty {
+            return Err(AppError::validation(format!(
+                "Insufficient stock at source location. Available: {}, requested: {}",
+                available, target_qty
+            )));
+        }
+
+        let mut remaining_to_move = target_qty;
+        let mut total_cost_cents: i64 = 0;
+        let mut lines = Vec::new();
+
+        for mut batch in batches {
+            if remaining_to_move <= Decimal::ZERO {
+                break;
+            }
+
+            let batch_available = batch.remaining_quantity.decimal();
+            let moved = batch_available.min(remaining_to_move);
+
+            // 1. Source batch
+            let new_remaining = batch_available - moved;
+            batch.remaining_quantity = Quantity::from_decimal(new_remaining)?;
+            if new_remaining <= Decimal::ZERO {
+                batch.status = BatchStatus::Exhausted;
+            }
+            batch.updated_at = now;
+            self.inventory_repo
+                .update_in_transaction(&mut tx, &batch)
+                .await?;
+
+            // 2. Destination batch keeps cost, dates and supplier of the source
+            let moved_qty = Quantity::from_decimal(moved)?;
+            let mut destination = InventoryBatch::new(
+                user_id,
+                tenant_id,
+                catalog_id,
+                batch.price_per_unit,
+                moved_qty,
+                batch.received_at,
+                batch.expires_at,
+            );
+            destination.supplier = batch.supplier.clone();
+            destination.supplier_id = batch.supplier_id;
+            destination.invoice_number = batch.invoice_number.clone();
+            destination.location_id = Some(to);
+            self.inventory_repo
+                .create_in_transaction(&mut tx, &destination)
+                .await?;
+
+            // 3. Paired movements
+            let price_cents = batch.price_per_unit.as_cents();
+            let mut out = InventoryMovement::new(
+                tenant_id,
+                batch.id,
+                MovementType::TransferOut,
+                moved,
+                price_cents,
+            );
+            out.reference_id = Some(transfer_id);
+            out.reference_type = Some("transfer".to_string());
+            out.notes = input.note.clone();
+            self.inventory_repo.record_movement(&mut tx, &out).await?;
+
+            let mut inbound = InventoryMovement::new(
+                tenant_id,
+                destination.id,
+                MovementType::TransferIn,
+                moved,
+                price_cents,
+            );
+            inbound.reference_id = Some(transfer_id);
+            inbound.reference_type = Some("transfer".to_string());
+            inbound.notes = input.note.clone();
+            self.inventory_repo
+                .record_movement(&mut tx, &inbound)
+                .await?;
+
+            total_cost_cents += out.total_cost_cents;
+            lines.push(TransferLine {
+                source_batch_id: batch.id.as_uuid(),
+                destination_batch_id: destination.id.as_uuid(),
+                quantity: moved.to_f64().unwrap_or(0.0),
+                price_per_unit_cents: price_cents,
+            });
+            remaining_to_move -= moved;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO inventory_transfers
+                (id, tenant_id, from_location_id, to_location_id, catalog_ingredient_id,
+                 quantity, total_cost_cents, user_id, note, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(transfer_id)
+        .bind(tenant_id.as_uuid())
+        .bind(from.as_uuid())
+        .bind(to.as_uuid())
+        .bind(catalog_id.as_uuid())
+        .bind(target_qty)
+        .bind(total_cost_cents)
+        .bind(user_id.as_uuid())
+        .bind(&input.note)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Transferred {} of ingredient {} from {} to {} ({} batches)",
+            target_qty,
+            catalog_id.as_uuid(),
+            from.as_uuid(),
+            to.as_uuid(),
+            lines.len()
+        );
+
+        Ok(InventoryTransfer {
+            id: transfer_id,
+            from_location_id: from.as_uuid(),
+            to_location_id: to.as_uuid(),
+            catalog_ingredient_id: catalog_id.as_uuid(),
+            quantity: target_qty.to_f64().unwrap_or(0.0),
+            total_cost_cents,
+            note: input.note,
+            created_at: now,
+            lines,
+        })
+    }
+
+    /// Transfer history, newest first; `location` matches either side
+    pub async fn list_transfers(
+        &self,
+        tenant_id: TenantId,
+        location: Option<StorageLocationId>,
+        limit: i64,
+    ) -> AppResult<Vec<InventoryTransfer>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, from_location_id, to_location_id, catalog_ingredient_id,
+                   quantity, total_cost_cents, note, created_at
+            FROM inventory_transfers
+            WHERE tenant_id = $1
+              AND ($2::UUID IS NULL OR from_location_id = $2 OR to_location_id = $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(location.map(|id| id.as_uuid()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut transfers = Vec::with_capacity(rows.len());
+        for row in rows {
+            transfers.push(InventoryTransfer {
+                id: row.try_get("id")?,
+                from_location_id: row.try_get("from_location_id")?,
+                to_location_id: row.try_get("to_location_id")?,
+                catalog_ingredient_id: row.try_get("catalog_ingredient_id")?,
+                quantity: row
+                    .try_get::<Decimal, _>("quantity")?
+                    .to_f64()
+                    .unwrap_or(0.0),
+                total_cost_cents: row.try_get("total_cost_cents")?,
+                note: row.try_get("note")?,
+                created_at: row.try_get("created_at")?,
+                lines: Vec::new(),
+            });
+        }
+
+        Ok(transfers)
+    }
+
+    async fn ensure_unique_name(
+        &self,
+        tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+        tenant_id: TenantId,
+        name: &str,
+        exclude: Option<StorageLocationId>,
+    ) -> AppResult<()> {
+        let taken: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM storage_locations
+                WHERE tenant_id = $1 AND is_active AND LOWER(name) = LOWER($2)
+                  AND ($3::UUID IS NULL OR id <> $3)
+            )
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(name)
+        .bind(exclude.map(|id| id.as_uuid()))
+        .fetch_one(&mut **tx)
+        .await?;
+
+        if taken {
+            return Err(AppError::conflict(format!(
+                "Storage location '{}' already exists",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Drop the current default so a new one can take over (unique per tenant)
+async fn clear_default(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    tenant_id: TenantId,
+) -> AppResult<()> {
+    sqlx::query(
+        "UPDATE storage_locations SET is_default = false, updated_at = NOW()
+         WHERE tenant_id = $1 AND is_default",
+    )
+    .bind(tenant_id.as_uuid())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+fn row_to_location(row: &sqlx::postgres::PgRow) -> AppResult<StorageLocation> {
+    let kind: String = row.try_get("kind")?;
+    Ok(StorageLocation {
+        id: StorageLocationId::from_uuid(row.try_get("id")?),
+        tenant_id: TenantId::from_uuid(row.try_get("tenant_id")?),
+        name: row.try_get("name")?,
+        kind: LocationKind::parse(&kind)?,
+        is_default: row.try_get("is_default")?,
+        is_active: row.try_get("is_active")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
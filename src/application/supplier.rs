@@ -0,0 +1,368 @@
+//! Suppliers and purchase price analytics.
+//!
+//! Every received batch records a price point (`ingredient_price_history`);
+//! comparison and inflation reports are built from those points.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::domain::supplier::{PricePoint, PriceTrend, Supplier, SupplierId};
+use crate::infrastructure::persistence::{SupplierRepository, SupplierRepositoryTrait};
+use crate::shared::{AppError, AppResult, TenantId};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSupplierInput {
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub notes: Option<String>,
+    pub lead_time_days: Option<i32>,
+    pub min_order_cents: Option<i64>,
+    #[serde(default)]
+    pub delivery_weekdays: Vec<i16>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateSupplierInput {
+    pub name: Option<String>,
+    pub contact_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub notes: Option<String>,
+    pub lead_time_days: Option<i32>,
+    pub min_order_cents: Option<i64>,
+    pub delivery_weekdays: Option<Vec<i16>>,
+}
+
+/// Price history of one ingredient
+#[derive(Debug, Clone, Serialize)]
+pub struct IngredientPriceHistory {
+    pub ingredient_id: Uuid,
+    pub period_days: i32,
+    pub points: Vec<PricePoint>,
+    pub trend: Option<PriceTrend>,
+}
+
+/// One supplier's offer for an ingredient (from its purchase history)
+#[derive(Debug, Clone, Serialize)]
+pub struct SupplierQuote {
+    pub supplier_id: Uuid,
+    pub supplier_name: String,
+    pub last_price_cents: i64,
+    pub average_price_cents: i64,
+    pub change_percent: f64,
+    pub purchases: usize,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_purchase_at: time::OffsetDateTime,
+    pub lead_time_days: i32,
+    pub min_order_cents: i64,
+    /// How much more expensive than the cheapest last price (0 = cheapest)
+    pub above_cheapest_percent: f64,
+}
+
+/// Price inflation of one ingredient over the period
+#[derive(Debug, Clone, Serialize)]
+pub struct IngredientInflation {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub trend: PriceTrend,
+}
+
+#[derive(Clone)]
+pub struct SupplierService {
+    repo: Arc<dyn SupplierRepositoryTrait>,
+    pool: PgPool,
+}
+
+impl SupplierService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            repo: Arc::new(SupplierRepository::new(pool.clone())),
+            pool,
+        }
+    }
+
+    /// Create supplier (name must be unique among active suppliers)
+    pub async fn create_supplier(
+        &self,
+        tenant_id: TenantId,
+        input: CreateSupplierInput,
+    ) -> AppResult<Supplier> {
+        let mut supplier = Supplier::new(tenant_id, input.name)?;
+        if self
+            .repo
+            .find_by_name(tenant_id, &supplier.name)
+            .await?
+            .is_some()
+        {
+            return Err(AppError::conflict(format!(
+                "Supplier '{}' already exists",
+                supplier.name
+            )));
+        }
+
+        supplier.contact_name = input.contact_name;
+        supplier.email = input.email;
+        supplier.phone = input.phone;
+        supplier.address = input.address;
+        supplier.notes = input.notes;
+        if let Some(days) = input.lead_time_days {
+            supplier.set_lead_time_days(days)?;
+        }
+        if let Some(cents) = input.min_order_cents {
+            supplier.set_min_order_cents(cents)?;
+        }
+        supplier.set_delivery_weekdays(input.delivery_weekdays)?;
+
+        self.repo.save(&supplier).await?;
+        Ok(supplier)
+    }
+
+    pub async fn list_suppliers(
+        &self,
+        tenant_id: TenantId,
+        include_inactive: bool,
+    ) -> AppResult<Vec<Supplier>> {
+        self.repo.list_by_tenant(tenant_id, include_inactive).await
+    }
+
+    pub async fn get_supplier(&self, tenant_id: TenantId, id: SupplierId) -> AppResult<Supplier> {
+        self.repo
+            .find_by_id(id, tenant_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Supplier not found"))
+    }
+
+    pub async fn update_supplier(
+        &self,
+        tenant_id: TenantId,
+        id: SupplierId,
+        input: UpdateSupplierInput,
+    ) -> AppResult<Supplier> {
+        let mut supplier = self.get_supplier(tenant_id, id).await?;
+
+        if let Some(name) = input.name {
+            supplier.rename(name)?;
+            if let Some(existing) = self.repo.find_by_name(tenant_id, &supplier.name).await? {
+                if existing.id != supplier.id {
+                    return Err(AppError::conflict(format!(
+                        "Supplier '{}' already exists",
+                        supplier.name
+                    )));
+                }
+            }
+        }
+        if let Some(contact_name) = input.contact_name {
+            supplier.contact_name = Some(contact_name);
+        }
+        if let Some(email) = input.email {
+            supplier.email = Some(email);
+        }
+        if let Some(phone) = input.phone {
+            supplier.phone = Some(phone);
+        }
+        if let Some(address) = input.address {
+            supplier.address = Some(address);
+        }
+        if let Some(notes) = input.notes {
+            supplier.notes = Some(notes);
+        }
+        if let Some(days) = input.lead_time_days {
+            supplier.set_lead_time_days(days)?;
+        }
+        if let Some(cents) = input.min_order_cents {
+            supplier.set_min_order_cents(cents)?;
+        }
+        if let Some(weekdays) = input.delivery_weekdays {
+            supplier.set_delivery_weekdays(weekdays)?;
+        }
+
+        self.repo.save(&supplier).await?;
+        Ok(supplier)
+    }
+
+    /// Soft delete (history keeps pointing at the supplier)
+    pub async fn deactivate_supplier(&self, tenant_id: TenantId, id: SupplierId) -> AppResult<()> {
+        let mut supplier = self.get_supplier(tenant_id, id).await?;
+        supplier.is_active = false;
+        self.repo.save(&supplier).await
+    }
+
+    /// Purchase prices of an ingredient over the last N days (oldest first)
+    pub async fn price_history(
+        &self,
+        tenant_id: TenantId,
+        ingredient_id: Uuid,
+        days: i32,
+    ) -> AppResult<IngredientPriceHistory> {
+        let rows = sqlx::query(
+            r#"
+            SELECT h.supplier_id, s.name AS supplier_name,
+                   h.price_per_unit_cents, h.quantity, h.recorded_at
+            FROM ingredient_price_history h
+            LEFT JOIN suppliers s ON s.id = h.supplier_id
+            WHERE h.tenant_id = $1
+              AND h.catalog_ingredient_id = $2
+              AND h.recorded_at >= NOW() - ($3 * INTERVAL '1 day')
+            ORDER BY h.recorded_at ASC
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(ingredient_id)
+        .bind(days)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let points = rows
+            .iter()
+            .map(row_to_price_point)
+            .collect::<AppResult<Vec<_>>>()?;
+        let trend = PriceTrend::from_points(&points);
+
+        Ok(IngredientPriceHistory {
+            ingredient_id,
+            period_days: days,
+            points,
+            trend,
+        })
+    }
+
+    /// Compare suppliers of an ingredient by their purchase history (cheapest first)
+    pub async fn compare_suppliers(
+        &self,
+        tenant_id: TenantId,
+        ingredient_id: Uuid,
+        days: i32,
+    ) -> AppResult<Vec<SupplierQuote>> {
+        let history = self.price_history(tenant_id, ingredient_id, days).await?;
+
+        let mut by_supplier: BTreeMap<Uuid, Vec<PricePoint>> = BTreeMap::new();
+        for point in history.points {
+            if let Some(supplier_id) = point.supplier_id {
+                by_supplier
+                    .entry(supplier_id.as_uuid())
+                    .or_default()
+                    .push(point);
+            }
+        }
+
+        let mut quotes = Vec::with_capacity(by_supplier.len());
+        for (supplier_id, points) in by_supplier {
+            let Some(trend) = PriceTrend::from_points(&points) else {
+                continue;
+            };
+            let Some(supplier) = self
+                .repo
+                .find_by_id(SupplierId::from_uuid(supplier_id), tenant_id)
+                .await?
+            else {
+                continue;
+            };
+            let last_purchase_at = points
+                .last()
+                .map(|p| p.recorded_at)
+                .unwrap_or(supplier.updated_at);
+
+            quotes.push(SupplierQuote {
+                supplier_id,
+                supplier_name: supplier.name,
+                last_price_cents: trend.last_price_cents,
+                average_price_cents: trend.average_price_cents,
+                change_percent: trend.change_percent,
+                purchases: trend.purchases,
+                last_purchase_at,
+                lead_time_days: supplier.lead_time_days,
+                min_order_cents: supplier.min_order_cents,
+                above_cheapest_percent: 0.0,
+            });
+        }
+
+        quotes.sort_by_key(|q| q.last_price_cents);
+        if let Some(cheapest) = quotes.first().map(|q| q.last_price_cents) {
+            for quote in &mut quotes {
+                if cheapest > 0 {
+                    quote.above_cheapest_percent =
+                        (quote.last_price_cents - cheapest) as f64 / cheapest as f64 * 100.0;
+                }
+            }
+        }
+
+        Ok(quotes)
+    }
+
+    /// Price inflation per ingredient over the last N days (largest increase first)
+    pub async fn price_inflation(
+        &self,
+        tenant_id: TenantId,
+        days: i32,
+        lang_code: &str,
+    ) -> AppResult<Vec<IngredientInflation>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT h.catalog_ingredient_id,
+                   CASE
+                       WHEN $3 = 'ru' THEN COALESCE(ci.name_ru, ci.name_en)
+                       WHEN $3 = 'pl' THEN COALESCE(ci.name_pl, ci.name_en)
+                       WHEN $3 = 'uk' THEN COALESCE(ci.name_uk, ci.name_en)
+                       ELSE ci.name_en
+                   END AS ingredient_name,
+                   h.supplier_id, s.name AS supplier_name,
+                   h.price_per_unit_cents, h.quantity, h.recorded_at
+            FROM ingredient_price_history h
+            JOIN catalog_ingredients ci ON ci.id = h.catalog_ingredient_id
+            LEFT JOIN suppliers s ON s.id = h.supplier_id
+            WHERE h.tenant_id = $1
+              AND h.recorded_at >= NOW() - ($2 * INTERVAL '1 day')
+            ORDER BY h.catalog_ingredient_id, h.recorded_at ASC
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(days)
+        .bind(lang_code)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut by_ingredient: BTreeMap<Uuid, (String, Vec<PricePoint>)> = BTreeMap::new();
+        for row in &rows {
+            let ingredient_id: Uuid = row.try_get("catalog_ingredient_id")?;
+            let name: Option<String> = row.try_get("ingredient_name")?;
+            by_ingredient
+                .entry(ingredient_id)
+                .or_insert_with(|| (name.unwrap_or_default(), Vec::new()))
+                .1
+                .push(row_to_price_point(row)?);
+        }
+
+        let mut items: Vec<IngredientInflation> = by_ingredient
+            .into_iter()
+            .filter_map(|(ingredient_id, (ingredient_name, points))| {
+                PriceTrend::from_points(&points).map(|trend| IngredientInflation {
+                    ingredient_id,
+                    ingredient_name,
+                    trend,
+                })
+            })
+            .collect();
+
+        items.sort_by(|a, b| b.trend.change_percent.total_cmp(&a.trend.change_percent));
+        Ok(items)
+    }
+}
+
+fn row_to_price_point(row: &sqlx::postgres::PgRow) -> AppResult<PricePoint> {
+    let supplier_id: Option<Uuid> = row.try_get("supplier_id")?;
+    Ok(PricePoint {
+        supplier_id: supplier_id.map(SupplierId::from_uuid),
+        supplier_name: row.try_get("supplier_name")?,
+        price_per_unit_cents: row.try_get("price_per_unit_cents")?,
+        quantity: row.try_get::<Decimal, _>("quantity")?,
+        recorded_at: row.try_get("recorded_at")?,
+    })
+}
@@ -0,0 +1,480 @@
+//! Tenant team management: members, invitations and ownership transfer.
+//!
+//! An owner or manager invites a colleague by email with a role. The raw
+//! token is returned once and only its SHA-256 is stored. Accepting creates
+//! the account, or moves an existing account into the restaurant after its
+//! password is checked, and signs the user in.
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::application::auth::{AuthResponse, AuthService};
+use crate::domain::{ClientInfo, DisplayName, Email, Password, UserRole};
+use crate::infrastructure::PasswordHasher;
+use crate::shared::{AppError, AppResult, Language, TenantId, UserId};
+
+/// How long an invite link stays valid
+const INVITATION_TTL_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamMember {
+    pub user_id: Uuid,
+    pub email: String,
+    pub display_name: Option<String>,
+    pub role: UserRole,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Revoked,
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub email: String,
+    pub role: UserRole,
+    pub status: InvitationStatus,
+    pub invited_by: Option<Uuid>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub accepted_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Returned once on creation — the token is not stored in clear
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedInvitation {
+    #[serde(flatten)]
+    pub invitation: Invitation,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateInvitationInput {
+    pub email: String,
+    pub role: UserRole,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AcceptInvitationCommand {
+    pub token: String,
+    /// New account password, or the current password of an existing account
+    pub password: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub language: Option<Language>,
+}
+
+#[derive(Clone)]
+pub struct TeamService {
+    pool: PgPool,
+    auth_service: AuthService,
+    password_hasher: PasswordHasher,
+}
+
+impl TeamService {
+    pub fn new(pool: PgPool, auth_service: AuthService, password_hasher: PasswordHasher) -> Self {
+        Self {
+            pool,
+            auth_service,
+            password_hasher,
+        }
+    }
+
+    pub async fn list_members(&self, tenant_id: TenantId) -> AppResult<Vec<TeamMember>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, email, display_name, role, created_at
+            FROM users
+            WHERE tenant_id = $1
+            ORDER BY CASE role WHEN 'owner' THEN 0 WHEN 'manager' THEN 1 ELSE 2 END, created_at
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(TeamMember {
+                    user_id: row.try_get("id")?,
+                    email: row.try_get("email")?,
+                    display_name: row.try_get("display_name")?,
+                    role: UserRole::from_str(row.try_get("role")?)?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Change a member's role. Owner is out of reach here — it changes
+    /// hands only through `transfer_ownership`.
+    pub async fn update_member_role(
+        &self,
+        tenant_id: TenantId,
+        actor_role: &UserRole,
+        user_id: UserId,
+        role: UserRole,
+    ) -> AppResult<TeamMember> {
+        let current = self.member_role(tenant_id, user_id).await?;
+        if current == UserRole::Owner {
+            return Err(AppError::validation(
+                "The owner's role changes only through ownership transfer",
+            ));
+        }
+        if !actor_role.can_invite(&current) || !actor_role.can_invite(&role) {
+            return Err(AppError::authorization(format!(
+                "Your role ({}) cannot assign the {} role",
+                actor_role, role
+            )));
+        }
+
+        sqlx::query("UPDATE users SET role = $3 WHERE id = $1 AND tenant_id = $2")
+            .bind(user_id.as_uuid())
+            .bind(tenant_id.as_uuid())
+            .bind(role.as_str())
+            .execute(&self.pool)
+            .await?;
+
+        self.list_members(tenant_id)
+            .await?
+            .into_iter()
+            .find(|m| m.user_id == *user_id.as_uuid())
+            .ok_or_else(|| AppError::not_found("Team member not found"))
+    }
+
+    /// The current owner becomes a manager; the target member becomes owner.
+    pub async fn transfer_ownership(
+        &self,
+        tenant_id: TenantId,
+        owner_id: UserId,
+        new_owner_id: UserId,
+    ) -> AppResult<Vec<TeamMember>> {
+        if owner_id == new_owner_id {
+            return Err(AppError::validation("You already own this restaurant"));
+        }
+        if self.member_role(tenant_id, owner_id).await? != UserRole::Owner {
+            return Err(AppError::authorization(
+                "Only the owner can transfer ownership",
+            ));
+        }
+        self.member_role(tenant_id, new_owner_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE users SET role = 'manager' WHERE id = $1 AND tenant_id = $2")
+            .bind(owner_id.as_uuid())
+            .bind(tenant_id.as_uuid())
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE users SET role = 'owner' WHERE id = $1 AND tenant_id = $2")
+            .bind(new_owner_id.as_uuid())
+            .bind(tenant_id.as_uuid())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.list_members(tenant_id).await
+    }
+
+    pub async fn create_invitation(
+        &self,
+        tenant_id: TenantId,
+        invited_by: UserId,
+        inviter_role: &UserRole,
+        input: CreateInvitationInput,
+    ) -> AppResult<CreatedInvitation> {
+        let email = Email::new(input.email)?;
+        if !inviter_role.can_invite(&input.role) {
+            return Err(AppError::authorization(format!(
+                "Your role ({}) cannot invite a {}",
+                inviter_role, input.role
+            )));
+        }
+
+        let already_member: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM users WHERE email = $1 AND tenant_id = $2)",
+        )
+        .bind(email.as_str())
+        .bind(tenant_id.as_uuid())
+        .fetch_one(&self.pool)
+        .await?;
+        if already_member {
+            return Err(AppError::conflict("This person is already on the team"));
+        }
+
+        // A lapsed invitation must not block a fresh one
+        sqlx::query(
+            r#"
+            UPDATE tenant_invitations SET revoked_at = NOW()
+            WHERE tenant_id = $1 AND email = $2
+              AND accepted_at IS NULL AND revoked_at IS NULL AND expires_at <= NOW()
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(email.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        let token = generate_token();
+        let id = Uuid::new_v4();
+        let expires_at = OffsetDateTime::now_utc() + Duration::days(INVITATION_TTL_DAYS);
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO tenant_invitations (id, tenant_id, email, role, token_hash, invited_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(id)
+        .bind(tenant_id.as_uuid())
+        .bind(email.as_str())
+        .bind(input.role.as_str())
+        .bind(hash_token(&token))
+        .bind(invited_by.as_uuid())
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            return Err(AppError::conflict(
+                "An invitation for this email is already pending",
+            ));
+        }
+
+        let invitation = self.get_invitation(tenant_id, id).await?;
+        Ok(CreatedInvitation { invitation, token })
+    }
+
+    pub async fn list_invitations(&self, tenant_id: TenantId) -> AppResult<Vec<Invitation>> {
+        let rows = sqlx::query(&format!(
+            "{} WHERE tenant_id = $1 ORDER BY created_at DESC",
+            INVITATION_SELECT
+        ))
+        .bind(tenant_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(invitation_from_row).collect()
+    }
+
+    pub async fn revoke_invitation(&self, tenant_id: TenantId, id: Uuid) -> AppResult<()> {
+        let revoked = sqlx::query(
+            r#"
+            UPDATE tenant_invitations SET revoked_at = NOW()
+            WHERE id = $1 AND tenant_id = $2 AND accepted_at IS NULL AND revoked_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(tenant_id.as_uuid())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if revoked == 0 {
+            return Err(AppError::not_found("Pending invitation not found"));
+        }
+        Ok(())
+    }
+
+    /// Join the inviting restaurant and sign in. A new email gets an
+    /// account; an existing account must prove its password and is moved
+    /// over (an owner with colleagues has to transfer ownership first).
+    pub async fn accept_invitation(
+        &self,
+        command: AcceptInvitationCommand,
+        client: ClientInfo,
+    ) -> AppResult<AuthResponse> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT id, tenant_id, email, role, expires_at, accepted_at, revoked_at
+            FROM tenant_invitations
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(hash_token(command.token.trim()))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::not_found("Invitation not found"))?;
+
+        let invitation_id: Uuid = row.try_get("id")?;
+        let tenant_id = TenantId::from_uuid(row.try_get("tenant_id")?);
+        let email: String = row.try_get("email")?;
+        let role = UserRole::from_str(row.try_get("role")?)?;
+        let expires_at: OffsetDateTime = row.try_get("expires_at")?;
+        let accepted_at: Option<OffsetDateTime> = row.try_get("accepted_at")?;
+        let revoked_at: Option<OffsetDateTime> = row.try_get("revoked_at")?;
+        if accepted_at.is_some() {
+            return Err(AppError::conflict("Invitation has already been accepted"));
+        }
+        if revoked_at.is_some() {
+            return Err(AppError::validation("Invitation has been revoked"));
+        }
+        if expires_at <= OffsetDateTime::now_utc() {
+            return Err(AppError::validation("Invitation has expired"));
+        }
+
+        let existing = sqlx::query(
+            "SELECT id, tenant_id, password_hash, role FROM users WHERE email = $1 FOR UPDATE",
+        )
+        .bind(&email)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let user_id = match existing {
+            Some(user) => {
+                let user_id = UserId::from_uuid(user.try_get("id")?);
+                let current_tenant: Uuid = user.try_get("tenant_id")?;
+                let password_hash: String = user.try_get("password_hash")?;
+                if !self
+                    .password_hasher
+                    .verify_password(&command.password, &password_hash)?
+                {
+                    return Err(AppError::authentication("Invalid email or password"));
+                }
+                if current_tenant == *tenant_id.as_uuid() {
+                    return Err(AppError::conflict("You are already on this team"));
+                }
+                if user.try_get::<String, _>("role")? == UserRole::Owner.as_str() {
+                    let colleagues: i64 = sqlx::query_scalar(
+                        "SELECT COUNT(*) FROM users WHERE tenant_id = $1 AND id <> $2",
+                    )
+                    .bind(current_tenant)
+                    .bind(user_id.as_uuid())
+                    .fetch_one(&mut *tx)
+                    .await?;
+                    if colleagues > 0 {
+                        return Err(AppError::conflict(
+                            "Transfer ownership of your current restaurant before joining another",
+                        ));
+                    }
+                }
+
+                sqlx::query("UPDATE users SET tenant_id = $2, role = $3 WHERE id = $1")
+                    .bind(user_id.as_uuid())
+                    .bind(tenant_id.as_uuid())
+                    .bind(role.as_str())
+                    .execute(&mut *tx)
+                    .await?;
+                user_id
+            }
+            None => {
+                let password = Password::new(command.password)?;
+                let display_name = command.display_name.map(DisplayName::new).transpose()?;
+                let language = command.language.unwrap_or_default();
+                let password_hash = self.password_hasher.hash_password(password.as_str())?;
+                let user_id = UserId::new();
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO users (id, tenant_id, email, password_hash, display_name, role, language, created_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+                    "#,
+                )
+                .bind(user_id.as_uuid())
+                .bind(tenant_id.as_uuid())
+                .bind(&email)
+                .bind(password_hash)
+                .bind(display_name.as_ref().map(|n| n.as_str()))
+                .bind(role.as_str())
+                .bind(language.code())
+                .execute(&mut *tx)
+                .await?;
+                user_id
+            }
+        };
+
+        sqlx::query(
+            "UPDATE tenant_invitations SET accepted_at = NOW(), accepted_by = $2 WHERE id = $1",
+        )
+        .bind(invitation_id)
+        .bind(user_id.as_uuid())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.auth_service
+            .issue_tokens(user_id, tenant_id, client)
+            .await
+    }
+
+    async fn member_role(&self, tenant_id: TenantId, user_id: UserId) -> AppResult<UserRole> {
+        let role: String =
+            sqlx::query_scalar("SELECT role FROM users WHERE id = $1 AND tenant_id = $2")
+                .bind(user_id.as_uuid())
+                .bind(tenant_id.as_uuid())
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::not_found("Team member not found"))?;
+        UserRole::from_str(&role)
+    }
+
+    async fn get_invitation(&self, tenant_id: TenantId, id: Uuid) -> AppResult<Invitation> {
+        let row = sqlx::query(&format!(
+            "{} WHERE id = $1 AND tenant_id = $2",
+            INVITATION_SELECT
+        ))
+        .bind(id)
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Invitation not found"))?;
+        invitation_from_row(&row)
+    }
+}
+
+const INVITATION_SELECT: &str = r#"
+    SELECT id, email, role, invited_by, expires_at, accepted_at, revoked_at, created_at
+    FROM tenant_invitations"#;
+
+fn invitation_from_row(row: &sqlx::postgres::PgRow) -> AppResult<Invitation> {
+    let expires_at: OffsetDateTime = row.try_get("expires_at")?;
+    let accepted_at: Option<OffsetDateTime> = row.try_get("accepted_at")?;
+    let revoked_at: Option<OffsetDateTime> = row.try_get("revoked_at")?;
+    let status = if accepted_at.is_some() {
+        InvitationStatus::Accepted
+    } else if revoked_at.is_some() {
+        InvitationStatus::Revoked
+    } else if expires_at <= OffsetDateTime::now_utc() {
+        InvitationStatus::Expired
+    } else {
+        InvitationStatus::Pending
+    };
+
+    Ok(Invitation {
+        id: row.try_get("id")?,
+        email: row.try_get("email")?,
+        role: UserRole::from_str(row.try_get("role")?)?,
+        status,
+        invited_by: row.try_get("invited_by")?,
+        expires_at,
+        accepted_at,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("inv_{}", hex::encode(bytes))
+}
+
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
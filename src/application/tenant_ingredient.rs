@@ -1,7 +1,11 @@
+use crate::application::audit::{
+    update_action, AuditAction, AuditEntityType, AuditEvent, AuditLog,
+};
 use crate::domain::catalog::{CatalogIngredientId, Unit};
+use crate::domain::supplier::SupplierId;
 use crate::domain::tenant_ingredient::{TenantIngredient, TenantIngredientId};
 use crate::infrastructure::persistence::TenantIngredientRepositoryTrait;
-use crate::shared::{AppError, AppResult, Language, TenantId};
+use crate::shared::{AppError, AppResult, Language, TenantId, UserId};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -11,17 +15,19 @@ use uuid::Uuid;
 #[derive(Clone)]
 pub struct TenantIngredientService {
     repository: Arc<dyn TenantIngredientRepositoryTrait>,
+    audit: AuditLog,
 }
 
 impl TenantIngredientService {
-    pub fn new(repository: Arc<dyn TenantIngredientRepositoryTrait>) -> Self {
-        Self { repository }
+    pub fn new(repository: Arc<dyn TenantIngredientRepositoryTrait>, audit: AuditLog) -> Self {
+        Self { repository, audit }
     }
 
     /// Add ingredient from master catalog to tenant's catalog
     pub async fn add_ingredient(
         &self,
         tenant_id: TenantId,
+        user_id: UserId,
         _language: Language,
         req: AddTenantIngredientRequest,
     ) -> AppResult<Uuid> {
@@ -45,9 +51,24 @@ impl TenantIngredientService {
             ingredient.custom_unit = Some(Unit::from_str(&unit_str)?);
         }
         ingredient.custom_expiration_days = req.custom_expiration_days;
+        ingredient.set_yield_percent(req.yield_percent)?;
+        ingredient.supplier_id = req.supplier_id.map(SupplierId::from_uuid);
+        ingredient.set_reorder_policy(req.par_level, req.reorder_point, req.pack_size)?;
         ingredient.notes = req.notes;
 
         self.repository.save(&ingredient).await?;
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::TenantIngredient,
+                    ingredient.id.as_uuid(),
+                    AuditAction::Create,
+                )
+                .after(audit_snapshot(&ingredient)),
+            )
+            .await;
 
         Ok(ingredient.id.as_uuid())
     }
@@ -77,6 +98,11 @@ impl TenantIngredientService {
                 supplier: i.supplier,
                 custom_unit: i.custom_unit.map(|u| u.as_str().to_string()),
                 custom_expiration_days: i.custom_expiration_days,
+                yield_percent: i.yield_percent,
+                supplier_id: i.supplier_id.map(|id| id.as_uuid()),
+                par_level: i.par_level,
+                reorder_point: i.reorder_point,
+                pack_size: i.pack_size,
                 notes: i.notes,
             })
             .collect())
@@ -110,6 +136,11 @@ impl TenantIngredientService {
             supplier: ingredient.supplier,
             custom_unit: ingredient.custom_unit.map(|u| u.as_str().to_string()),
             custom_expiration_days: ingredient.custom_expiration_days,
+            yield_percent: ingredient.yield_percent,
+            supplier_id: ingredient.supplier_id.map(|id| id.as_uuid()),
+            par_level: ingredient.par_level,
+            reorder_point: ingredient.reorder_point,
+            pack_size: ingredient.pack_size,
             notes: ingredient.notes,
         })
     }
@@ -117,6 +148,7 @@ impl TenantIngredientService {
     pub async fn update_ingredient(
         &self,
         tenant_id: TenantId,
+        user_id: UserId,
         id: Uuid,
         _language: Language,
         req: UpdateTenantIngredientRequest,
@@ -126,6 +158,7 @@ impl TenantIngredientService {
             .iter_mut()
             .find(|i| i.id.as_uuid() == id)
             .ok_or_else(|| AppError::not_found("Tenant ingredient not found"))?;
+        let before = audit_snapshot(ingredient);
 
         if let Some(price) = req.price {
             ingredient.price = Some(price);
@@ -139,11 +172,45 @@ impl TenantIngredientService {
         if let Some(days) = req.custom_expiration_days {
             ingredient.custom_expiration_days = Some(days);
         }
+        if req.clear_yield_percent {
+            if req.yield_percent.is_some() {
+                return Err(AppError::validation(
+                    "Set yield_percent or clear_yield_percent, not both",
+                ));
+            }
+            ingredient.set_yield_percent(None)?;
+        } else if let Some(yield_percent) = req.yield_percent {
+            ingredient.set_yield_percent(Some(yield_percent))?;
+        }
+        if let Some(supplier_id) = req.supplier_id {
+            ingredient.supplier_id = Some(SupplierId::from_uuid(supplier_id));
+        }
+        if req.par_level.is_some() || req.reorder_point.is_some() || req.pack_size.is_some() {
+            ingredient.set_reorder_policy(
+                req.par_level.or(ingredient.par_level),
+                req.reorder_point.or(ingredient.reorder_point),
+                req.pack_size.or(ingredient.pack_size),
+            )?;
+        }
         if let Some(notes) = req.notes {
             ingredient.notes = Some(notes);
         }
 
         self.repository.save(ingredient).await?;
+        let after = audit_snapshot(ingredient);
+        self.audit
+            .record(
+                AuditEvent::new(
+                    tenant_id,
+                    user_id,
+                    AuditEntityType::TenantIngredient,
+                    id,
+                    update_action(&before, &after, "price"),
+                )
+                .before(before)
+                .after(after),
+            )
+            .await;
 
         Ok(TenantIngredientResponse {
             id: ingredient.id.as_uuid(),
@@ -159,14 +226,45 @@ impl TenantIngredientService {
             supplier: ingredient.supplier.clone(),
             custom_unit: ingredient.custom_unit.map(|u| u.as_str().to_string()),
             custom_expiration_days: ingredient.custom_expiration_days,
+            yield_percent: ingredient.yield_percent,
+            supplier_id: ingredient.supplier_id.map(|id| id.as_uuid()),
+            par_level: ingredient.par_level,
+            reorder_point: ingredient.reorder_point,
+            pack_size: ingredient.pack_size,
             notes: ingredient.notes.clone(),
         })
     }
 
-    pub async fn remove_ingredient(&self, tenant_id: TenantId, id: Uuid) -> AppResult<()> {
+    pub async fn remove_ingredient(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        id: Uuid,
+    ) -> AppResult<()> {
+        let existing = self
+            .repository
+            .list_by_tenant(tenant_id)
+            .await?
+            .into_iter()
+            .find(|i| i.id.as_uuid() == id);
         self.repository
             .delete(TenantIngredientId::from_uuid(id), tenant_id)
             .await?;
+
+        if let Some(ingredient) = existing {
+            self.audit
+                .record(
+                    AuditEvent::new(
+                        tenant_id,
+                        user_id,
+                        AuditEntityType::TenantIngredient,
+                        id,
+                        AuditAction::Delete,
+                    )
+                    .before(audit_snapshot(&ingredient)),
+                )
+                .await;
+        }
         Ok(())
     }
 
@@ -189,6 +287,13 @@ pub struct AddTenantIngredientRequest {
     pub supplier: Option<String>,
     pub custom_unit: Option<String>,
     pub custom_expiration_days: Option<i32>,
+    /// Edible yield % override (0 < x <= 100)
+    pub yield_percent: Option<Decimal>,
+    /// Preferred supplier for automatic reorders
+    pub supplier_id: Option<Uuid>,
+    pub par_level: Option<Decimal>,
+    pub reorder_point: Option<Decimal>,
+    pub pack_size: Option<Decimal>,
     pub notes: Option<String>,
 }
 
@@ -198,6 +303,14 @@ pub struct UpdateTenantIngredientRequest {
     pub supplier: Option<String>,
     pub custom_unit: Option<String>,
     pub custom_expiration_days: Option<i32>,
+    pub yield_percent: Option<Decimal>,
+    /// Drop the override and fall back to the catalog's edible yield
+    #[serde(default)]
+    pub clear_yield_percent: bool,
+    pub supplier_id: Option<Uuid>,
+    pub par_level: Option<Decimal>,
+    pub reorder_point: Option<Decimal>,
+    pub pack_size: Option<Decimal>,
     pub notes: Option<String>,
 }
 
@@ -213,5 +326,26 @@ pub struct TenantIngredientResponse {
     pub supplier: Option<String>,
     pub custom_unit: Option<String>,
     pub custom_expiration_days: Option<i32>,
+    pub yield_percent: Option<Decimal>,
+    pub supplier_id: Option<Uuid>,
+    pub par_level: Option<Decimal>,
+    pub reorder_point: Option<Decimal>,
+    pub pack_size: Option<Decimal>,
     pub notes: Option<String>,
 }
+
+fn audit_snapshot(ingredient: &TenantIngredient) -> serde_json::Value {
+    serde_json::json!({
+        "catalog_ingredient_id": ingredient.catalog_ingredient_id.as_uuid(),
+        "price": ingredient.price,
+        "supplier": ingredient.supplier,
+        "supplier_id": ingredient.supplier_id.map(|id| id.as_uuid()),
+        "custom_unit": ingredient.custom_unit.map(|u| u.as_str()),
+        "custom_expiration_days": ingredient.custom_expiration_days,
+        "yield_percent": ingredient.yield_percent,
+        "par_level": ingredient.par_level,
+        "reorder_point": ingredient.reorder_point,
+        "pack_size": ingredient.pack_size,
+        "notes": ingredient.notes,
+    })
+}
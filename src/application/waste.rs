@@ -0,0 +1,396 @@
+//! Manual waste log.
+//!
+//! Staff log spoilage, breakage, staff meals, returned plates etc. with a reason
+//! code and an optional photo (uploaded straight to R2 via a presigned URL).
+//! Each entry writes the stock off FIFO — or from one chosen batch — with
+//! `OUT_WASTE` movements that reference the entry, so the loss report and
+//! `TenantSummary::waste_cents` include it next to auto-expired batches.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::application::inventory::restore_movements;
+use crate::application::user::AvatarUploadResponse;
+use crate::domain::{
+    catalog::CatalogIngredientId,
+    inventory::{BatchStatus, InventoryBatchId, InventoryMovement, MovementType, Quantity},
+    storage_location::StorageLocationId,
+    waste::WasteReason,
+};
+use crate::infrastructure::persistence::{InventoryBatchRepository, InventoryBatchRepositoryTrait};
+use crate::infrastructure::R2Client;
+use crate::shared::{AppError, AppResult, Language, TenantId, UserId};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogWasteInput {
+    pub catalog_ingredient_id: Uuid,
+    pub quantity: f64,
+    pub reason: WasteReason,
+    /// Write off this batch only (FIFO across batches when omitted)
+    pub batch_id: Option<Uuid>,
+    /// Restrict FIFO to one storage location
+    pub location_id: Option<Uuid>,
+    /// Public URL returned by the photo upload-URL endpoint
+    pub photo_url: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WasteFilter {
+    pub days: Option<i32>,
+    pub reason: Option<WasteReason>,
+    pub ingredient_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WasteEntry {
+    pub id: Uuid,
+    pub catalog_ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub batch_id: Option<Uuid>,
+    pub location_id: Option<Uuid>,
+    pub reason: WasteReason,
+    pub reason_label: String,
+    pub quantity: f64,
+    pub cost_cents: i64,
+    pub photo_url: Option<String>,
+    pub note: Option<String>,
+    /// Who logged it
+    pub user_id: Uuid,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Reason taxonomy entry for pickers
+#[derive(Debug, Clone, Serialize)]
+pub struct WasteReasonView {
+    pub code: WasteReason,
+    pub label: String,
+}
+
+#[derive(Clone)]
+pub struct WasteService {
+    pool: PgPool,
+    inventory_repo: Arc<InventoryBatchRepository>,
+    r2_client: R2Client,
+}
+
+impl WasteService {
+    pub fn new(pool: PgPool, r2_client: R2Client) -> Self {
+        Self {
+            inventory_repo: Arc::new(InventoryBatchRepository::new(pool.clone())),
+            pool,
+            r2_client,
+        }
+    }
+
+    pub fn reasons(&self, language: Language) -> Vec<WasteReasonView> {
+        WasteReason::ALL
+            .into_iter()
+            .map(|code| WasteReasonView {
+                code,
+                label: code.label(language).to_string(),
+            })
+            .collect()
+    }
+
+    /// Presigned R2 upload URL for a waste photo
+    pub async fn get_photo_upload_url(
+        &self,
+        tenant_id: TenantId,
+        content_type: &str,
+    ) -> AppResult<AvatarUploadResponse> {
+        let extension = match content_type {
+            "image/webp" => "webp",
+            "image/jpeg" => "jpg",
+            "image/png" => "png",
+            _ => {
+                return Err(AppError::validation(
+                    "Unsupported content type. Use image/webp, image/jpeg or image/png",
+                ))
+            }
+        };
+        let key = format!(
+            "waste/{}/{}.{}",
+            tenant_id.as_uuid(),
+            Uuid::new_v4(),
+            extension
+        );
+
+        let upload_url = self
+            .r2_client
+            .generate_presigned_upload_url(&key, content_type)
+            .await?;
+        let public_url = self.r2_client.get_public_url(&key);
+
+        Ok(AvatarUploadResponse {
+            upload_url,
+            public_url,
+        })
+    }
+
+    /// Log waste and write the stock off in one transaction
+    pub async fn log_waste(
+        &self,
+        tenant_id: TenantId,
+        user_id: UserId,
+        input: LogWasteInput,
+        language: Language,
+    ) -> AppResult<WasteEntry> {
+        let target_qty = Quantity::new(input.quantity)?.decimal();
+        if target_qty <= Decimal::ZERO {
+            return Err(AppError::validation(
+                "Waste quantity must be greater than 0",
+            ));
+        }
+        if let Some(url) = &input.photo_url {
+            if !self.r2_client.is_public_url(url) {
+                return Err(AppError::validation(
+                    "photo_url must come from the waste photo upload endpoint",
+                ));
+            }
+        }
+
+        let catalog_id = CatalogIngredientId::from_uuid(input.catalog_ingredient_id);
+        let location = input.location_id.map(StorageLocationId::from_uuid);
+        let entry_id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let mut tx = self.pool.begin().await?;
+
+        let batches = match input.batch_id {
+            Some(batch_id) => {
+                let batch = self
+                    .inventory_repo
+                    .find_by_id_for_update(
+                        &mut tx,
+                        InventoryBatchId::from_uuid(batch_id),
+                        tenant_id,
+                    )
+                    .await?
+                    .filter(|b| b.catalog_ingredient_id == catalog_id)
+                    .ok_or_else(|| AppError::not_found("Inventory batch not found"))?;
+                if batch.status != BatchStatus::Active {
+                    return Err(AppError::validation("Inventory batch is not active"));
+                }
+                vec![batch]
+            }
+            None => {
+                self.inventory_repo
+                    .list_active_by_ingredient_for_update(&mut tx, tenant_id, catalog_id, location)
+                    .await?
+            }
+        };
+
+        // Waste is physical stock leaving the kitchen: it cannot exceed what is on hand
+        let available: Decimal = batches.iter().map(|b| b.remaining_quantity.decimal()).sum();
+        if available < target_qty {
+            return Err(AppError::validation(format!(
+                "Insufficient stock to write off. Available: {}, requested: {}",
+                available, target_qty
+            )));
+        }
+
+        let mut remaining_to_write_off = target_qty;
+        let mut cost_cents: i64 = 0;
+        for mut batch in batches {
+            if remaining_to_write_off <= Decimal::ZERO {
+                break;
+            }
+
+            let batch_available = batch.remaining_quantity.decimal();
+            let written_off = batch_available.min(remaining_to_write_off);
+
+            let new_remaining = batch_available - written_off;
+            batch.remaining_quantity = Quantity::from_decimal(new_remaining)?;
+            if new_remaining <= Decimal::ZERO {
+                batch.status = BatchStatus::Exhausted;
+            }
+            batch.updated_at = now;
+            self.inventory_repo
+                .update_in_transaction(&mut tx, &batch)
+                .await?;
+
+            let mut movement = InventoryMovement::new(
+                tenant_id,
+                batch.id,
+                MovementType::OutWaste,
+                written_off,
+                batch.price_per_unit.as_cents(),
+            );
+            movement.reason = Some(input.reason.as_str().to_string());
+            movement.reference_id = Some(entry_id);
+            movement.reference_type = Some("waste".to_string());
+            movement.notes = input.note.clone();
+            self.inventory_repo
+                .record_movement(&mut tx, &movement)
+                .await?;
+
+            cost_cents += movement.total_cost_cents;
+            remaining_to_write_off -= written_off;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO waste_entries
+                (id, tenant_id, catalog_ingredient_id, batch_id, location_id, reason,
+                 quantity, cost_cents, photo_url, note, user_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+        )
+        .bind(entry_id)
+        .bind(tenant_id.as_uuid())
+        .bind(catalog_id.as_uuid())
+        .bind(input.batch_id)
+        .bind(input.location_id)
+        .bind(input.reason.as_str())
+        .bind(target_qty)
+        .bind(cost_cents)
+        .bind(&input.photo_url)
+        .bind(&input.note)
+        .bind(user_id.as_uuid())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Waste logged: {} of ingredient {} ({}), {} cents",
+            target_qty,
+            catalog_id.as_uuid(),
+            input.reason.as_str(),
+            cost_cents
+        );
+
+        self.get_entry(tenant_id, entry_id, language).await
+    }
+
+    pub async fn list_entries(
+        &self,
+        tenant_id: TenantId,
+        filter: WasteFilter,
+        language: Language,
+    ) -> AppResult<Vec<WasteEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT we.id, we.catalog_ingredient_id, we.batch_id, we.location_id, we.reason,
+                   we.quantity, we.cost_cents, we.photo_url, we.note, we.user_id, we.created_at,
+                   CASE
+                       WHEN $2 = 'ru' THEN COALESCE(ci.name_ru, ci.name_en)
+                       WHEN $2 = 'pl' THEN COALESCE(ci.name_pl, ci.name_en)
+                       WHEN $2 = 'uk' THEN COALESCE(ci.name_uk, ci.name_en)
+                       ELSE ci.name_en
+                   END AS ingredient_name
+            FROM waste_entries we
+            JOIN catalog_ingredients ci ON ci.id = we.catalog_ingredient_id
+            WHERE we.tenant_id = $1
+              AND we.voided_at IS NULL
+              AND we.created_at >= NOW() - ($3 * INTERVAL '1 day')
+              AND ($4::TEXT IS NULL OR we.reason = $4)
+              AND ($5::UUID IS NULL OR we.catalog_ingredient_id = $5)
+              AND ($6::UUID IS NULL OR we.location_id = $6)
+            ORDER BY we.created_at DESC
+            LIMIT $7
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(language.code())
+        .bind(filter.days.unwrap_or(30).clamp(1, 365))
+        .bind(filter.reason.map(|r| r.as_str()))
+        .bind(filter.ingredient_id)
+        .bind(filter.location_id)
+        .bind(filter.limit.unwrap_or(100).clamp(1, 500))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(|row| row_to_entry(row, language)).collect()
+    }
+
+    /// Void a waste entry: its stock goes back to the batches it came from
+    /// and the entry stays in the table with `voided_at` set, hidden from
+    /// the waste log
+    pub async fn void_entry(&self, tenant_id: TenantId, entry_id: Uuid) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let voided = sqlx::query(
+            r#"
+            UPDATE waste_entries SET voided_at = NOW()
+            WHERE id = $1 AND tenant_id = $2 AND voided_at IS NULL
+            "#,
+        )
+        .bind(entry_id)
+        .bind(tenant_id.as_uuid())
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if voided == 0 {
+            return Err(AppError::not_found("Waste entry not found"));
+        }
+
+        restore_movements(&self.inventory_repo, &mut tx, tenant_id, entry_id).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_entry(
+        &self,
+        tenant_id: TenantId,
+        id: Uuid,
+        language: Language,
+    ) -> AppResult<WasteEntry> {
+        let row = sqlx::query(
+            r#"
+            SELECT we.id, we.catalog_ingredient_id, we.batch_id, we.location_id, we.reason,
+                   we.quantity, we.cost_cents, we.photo_url, we.note, we.user_id, we.created_at,
+                   CASE
+                       WHEN $3 = 'ru' THEN COALESCE(ci.name_ru, ci.name_en)
+                       WHEN $3 = 'pl' THEN COALESCE(ci.name_pl, ci.name_en)
+                       WHEN $3 = 'uk' THEN COALESCE(ci.name_uk, ci.name_en)
+                       ELSE ci.name_en
+                   END AS ingredient_name
+            FROM waste_entries we
+            JOIN catalog_ingredients ci ON ci.id = we.catalog_ingredient_id
+            WHERE we.id = $1 AND we.tenant_id = $2 AND we.voided_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .bind(tenant_id.as_uuid())
+        .bind(language.code())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::not_found("Waste entry not found"))?;
+
+        row_to_entry(&row, language)
+    }
+}
+
+fn row_to_entry(row: &sqlx::postgres::PgRow, language: Language) -> AppResult<WasteEntry> {
+    let reason = WasteReason::parse(&row.try_get::<String, _>("reason")?)?;
+    Ok(WasteEntry {
+        id: row.try_get("id")?,
+        catalog_ingredient_id: row.try_get("catalog_ingredient_id")?,
+        ingredient_name: row.try_get("ingredient_name")?,
+        batch_id: row.try_get("batch_id")?,
+        location_id: row.try_get("location_id")?,
+        reason,
+        reason_label: reason.label(language).to_string(),
+        quantity: row
+            .try_get::<Decimal, _>("quantity")?
+            .to_f64()
+            .unwrap_or(0.0),
+        cost_cents: row.try_get("cost_cents")?,
+        photo_url: row.try_get("photo_url")?,
+        note: row.try_get("note")?,
+        user_id: row.try_get("user_id")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
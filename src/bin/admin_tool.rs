@@ -10,7 +10,9 @@ use restaurant_backend::application::{
     AdminCatalogService,
 };
 use restaurant_backend::infrastructure::{
-    persistence::AiCacheRepository, GeminiService, LlmAdapter, R2Client, Repositories,
+    llm::{build_gateway, LlmConfig},
+    persistence::AiCacheRepository,
+    GeminiService, LlmAdapter, R2Client, Repositories,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPoolOptions;
@@ -77,22 +79,22 @@ async fn main() -> AnyResult<()> {
             print_json(&service.generate_all_states().await?)?;
         }
         "catalog-audit" => {
-            let service = build_admin_catalog_service(pool).await;
+            let service = build_admin_catalog_service(pool).await?;
             print_json(&service.ai_audit().await?)?;
         }
         "autofill-product" => {
             let id = required_uuid(args.first(), "product_id")?;
-            let service = build_admin_catalog_service(pool).await;
+            let service = build_admin_catalog_service(pool).await?;
             print_json(&service.ai_autofill(id).await?)?;
         }
         "generate-seo" => {
             let id = required_uuid(args.first(), "product_id")?;
-            let service = build_admin_catalog_service(pool).await;
+            let service = build_admin_catalog_service(pool).await?;
             print_json(&service.ai_generate_seo(id).await?)?;
         }
         "generate-pairings" => {
             let id = required_uuid(args.first(), "product_id")?;
-            let service = build_admin_catalog_service(pool).await;
+            let service = build_admin_catalog_service(pool).await?;
             print_json(&service.ai_generate_pairings(id).await?)?;
         }
         "suggest-products" => {
@@ -100,7 +102,7 @@ async fn main() -> AnyResult<()> {
                 .first()
                 .map(String::as_str)
                 .unwrap_or("Suggest useful missing products for a culinary catalog");
-            let service = build_admin_catalog_service(pool).await;
+            let service = build_admin_catalog_service(pool).await?;
             print_json(
                 &service
                     .ai_suggest_products(SuggestProductsRequest {
@@ -111,7 +113,7 @@ async fn main() -> AnyResult<()> {
         }
         "create-product-draft" => {
             let input = required_text(args.first(), "name_or_description")?;
-            let service = build_admin_catalog_service(pool).await;
+            let service = build_admin_catalog_service(pool).await?;
             print_json(
                 &service
                     .ai_create_product_draft(CreateDraftRequest {
@@ -123,14 +125,14 @@ async fn main() -> AnyResult<()> {
         "generate-product-image" => {
             let name = required_text(args.first(), "name")?;
             let description = args.get(1).map(String::as_str);
-            let service = build_admin_catalog_service(pool).await;
+            let service = build_admin_catalog_service(pool).await?;
             let url = service
                 .generate_product_draft_image(name, description, false)
                 .await?;
             print_json(&serde_json::json!({ "image_url": url }))?;
         }
         "run-intent-scheduler" => {
-            let service = build_intent_pages_service(pool).await;
+            let service = build_intent_pages_service(pool).await?;
             print_json(&service.run_scheduled_publish().await?)?;
         }
         other => {
@@ -159,7 +161,7 @@ async fn connect_pool() -> AnyResult<sqlx::PgPool> {
         .await?)
 }
 
-async fn build_admin_catalog_service(pool: sqlx::PgPool) -> AdminCatalogService {
+async fn build_admin_catalog_service(pool: sqlx::PgPool) -> AnyResult<AdminCatalogService> {
     let repositories = Repositories::new(pool.clone());
     let r2_client = R2Client::new(
         env::var("CLOUDFLARE_ACCOUNT_ID").unwrap_or_default(),
@@ -169,46 +171,41 @@ async fn build_admin_catalog_service(pool: sqlx::PgPool) -> AdminCatalogService
         env::var("CLOUDFLARE_R2_PUBLIC_URL").unwrap_or_default(),
     )
     .await;
-    let gemini_service = Arc::new(GeminiService::new(
-        env::var("GEMINI_API_KEY").unwrap_or_default(),
-    ));
-    let llm_adapter = Arc::new(LlmAdapter::new(
-        gemini_service,
-        Arc::new(repositories.ai_cache.clone()),
-        Arc::new(repositories.ai_usage_stats.clone()),
-    ));
+    let llm_adapter = build_llm_adapter(&repositories)?;
 
-    AdminCatalogService::new(
+    Ok(AdminCatalogService::new(
         pool,
         r2_client,
         repositories.dictionary.clone(),
         llm_adapter,
-    )
+    ))
 }
 
-async fn build_intent_pages_service(pool: sqlx::PgPool) -> Arc<IntentPagesService> {
+async fn build_intent_pages_service(pool: sqlx::PgPool) -> AnyResult<Arc<IntentPagesService>> {
     let repositories = Repositories::new(pool.clone());
-    let llm_adapter = build_llm_adapter(&repositories);
+    let llm_adapter = build_llm_adapter(&repositories)?;
     let seo_content = Arc::new(PublicSeoContentService::new(
         llm_adapter,
         AiCacheRepository::new(pool.clone()),
     ));
-    Arc::new(IntentPagesService::new(
+    Ok(Arc::new(IntentPagesService::new(
         pool,
         seo_content,
         build_r2_client().await,
-    ))
+    )))
 }
 
-fn build_llm_adapter(repositories: &Repositories) -> Arc<LlmAdapter> {
+fn build_llm_adapter(repositories: &Repositories) -> AnyResult<Arc<LlmAdapter>> {
     let gemini_service = Arc::new(GeminiService::new(
         env::var("GEMINI_API_KEY").unwrap_or_default(),
     ));
-    Arc::new(LlmAdapter::new(
+    let usage_repo = Arc::new(repositories.ai_usage_stats.clone());
+    Ok(Arc::new(LlmAdapter::new(
         gemini_service,
+        build_gateway(&LlmConfig::from_env()?, Some(Arc::clone(&usage_repo))),
         Arc::new(repositories.ai_cache.clone()),
-        Arc::new(repositories.ai_usage_stats.clone()),
-    ))
+        usage_repo,
+    )))
 }
 
 async fn build_r2_client() -> R2Client {
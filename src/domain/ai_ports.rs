@@ -5,7 +5,7 @@
 //!
 //! Это даёт:
 //! ✔ Unit tests с mock AI
-//! ✔ Fallback models (Groq → OpenAI → local)
+//! ✔ Fallback models (Groq → OpenAI → local) — `infrastructure::llm` provider chain
 //! ✔ Cheap mode (10% cost — smaller model)
 //! ✔ Dry-run режим для CI
 
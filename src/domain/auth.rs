@@ -1,29 +1,62 @@
 use crate::shared::{RefreshTokenId, UserId};
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use uuid::Uuid;
 
+/// Refresh tokens are single-use. Every refresh replaces the token with a
+/// successor in the same family (one family = one signed-in device), so a
+/// replaced token showing up again means it was copied.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RefreshToken {
     pub id: RefreshTokenId,
     pub user_id: UserId,
+    pub family_id: Uuid,
     pub token_hash: String,
     pub expires_at: OffsetDateTime,
     pub revoked_at: Option<OffsetDateTime>,
+    /// Successor issued when this token was rotated
+    pub replaced_by: Option<RefreshTokenId>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
     pub created_at: OffsetDateTime,
 }
 
 impl RefreshToken {
-    pub fn new(user_id: UserId, token_hash: String, expires_at: OffsetDateTime) -> Self {
+    /// First token of a new session (login, registration, invitation)
+    pub fn new(
+        user_id: UserId,
+        token_hash: String,
+        expires_at: OffsetDateTime,
+        client: ClientInfo,
+    ) -> Self {
+        let id = RefreshTokenId::new();
         Self {
-            id: RefreshTokenId::new(),
+            id,
             user_id,
+            family_id: *id.as_uuid(),
             token_hash,
             expires_at,
             revoked_at: None,
+            replaced_by: None,
+            user_agent: client.user_agent,
+            ip_address: client.ip_address,
             created_at: OffsetDateTime::now_utc(),
         }
     }
 
+    /// Successor in the same family
+    pub fn rotate(
+        &self,
+        token_hash: String,
+        expires_at: OffsetDateTime,
+        client: ClientInfo,
+    ) -> Self {
+        Self {
+            family_id: self.family_id,
+            ..Self::new(self.user_id, token_hash, expires_at, client)
+        }
+    }
+
     pub fn is_expired(&self) -> bool {
         OffsetDateTime::now_utc() > self.expires_at
     }
@@ -32,6 +65,11 @@ impl RefreshToken {
         self.revoked_at.is_some()
     }
 
+    /// Already exchanged for a successor — presenting it again is reuse
+    pub fn is_rotated(&self) -> bool {
+        self.replaced_by.is_some()
+    }
+
     pub fn is_valid(&self) -> bool {
         !self.is_expired() && !self.is_revoked()
     }
@@ -40,3 +78,86 @@ impl RefreshToken {
         self.revoked_at = Some(OffsetDateTime::now_utc());
     }
 }
+
+/// Where a session was opened from, shown in the session list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    const MAX_CHARS: usize = 512;
+
+    pub fn new(user_agent: Option<&str>, ip_address: Option<&str>) -> Self {
+        let clean = |value: Option<&str>| {
+            value
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(|v| v.chars().take(Self::MAX_CHARS).collect())
+        };
+        Self {
+            user_agent: clean(user_agent),
+            ip_address: clean(ip_address),
+        }
+    }
+}
+
+/// An active token family, i.e. a device that is still signed in
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthSession {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// Last sign-in or refresh
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_used_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    fn token() -> RefreshToken {
+        RefreshToken::new(
+            UserId::new(),
+            "hash".to_string(),
+            OffsetDateTime::now_utc() + Duration::days(30),
+            ClientInfo::new(Some("Safari"), Some("10.0.0.1")),
+        )
+    }
+
+    #[test]
+    fn new_token_starts_its_own_family() {
+        let first = token();
+        assert_eq!(first.family_id, *first.id.as_uuid());
+        assert!(first.is_valid());
+        assert!(!first.is_rotated());
+    }
+
+    #[test]
+    fn rotation_keeps_family_and_user() {
+        let first = token();
+        let next = first.rotate(
+            "next".to_string(),
+            OffsetDateTime::now_utc() + Duration::days(30),
+            ClientInfo::default(),
+        );
+        assert_eq!(next.family_id, first.family_id);
+        assert_eq!(next.user_id, first.user_id);
+        assert_ne!(next.id, first.id);
+        assert_eq!(next.token_hash, "next");
+    }
+
+    #[test]
+    fn client_info_drops_blank_values() {
+        let client = ClientInfo::new(Some("  "), Some(" 1.2.3.4 "));
+        assert!(client.user_agent.is_none());
+        assert_eq!(client.ip_address.as_deref(), Some("1.2.3.4"));
+    }
+}
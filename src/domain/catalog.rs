@@ -181,7 +181,7 @@ impl fmt::Display for Unit {
 }
 
 /// Allergen types
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Allergen {
     Milk,
     Eggs,
@@ -0,0 +1,523 @@
+//! Dish allergen declaration and nutrition label (EU FIC, Regulation 1169/2011).
+//!
+//! A dish's recipe is walked down through its component recipes. Catalog
+//! allergens of every ingredient make the dish *contain* the allergen;
+//! "may contain" declarations (supplier trace warnings on an ingredient,
+//! kitchen cross-contact on a recipe) make it *may contain*. Every hit keeps
+//! its provenance so staff can answer "why?" at the table.
+
+use crate::domain::catalog::{Allergen, CatalogIngredientId, Unit};
+use crate::domain::recipe::{Recipe, RecipeId};
+use crate::shared::{AppError, AppResult};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The 14 allergens of Annex II, in the order of the regulation
+pub const EU_ALLERGENS: [Allergen; 14] = [
+    Allergen::Wheat,
+    Allergen::Shellfish,
+    Allergen::Eggs,
+    Allergen::Fish,
+    Allergen::Peanuts,
+    Allergen::Soybeans,
+    Allergen::Milk,
+    Allergen::TreeNuts,
+    Allergen::Celery,
+    Allergen::Mustard,
+    Allergen::Sesame,
+    Allergen::Sulfites,
+    Allergen::Lupin,
+    Allergen::Molluscs,
+];
+
+/// How an allergen ends up in a dish; `Contains` outranks `MayContain`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllergenPresence {
+    MayContain,
+    Contains,
+}
+
+impl AllergenPresence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MayContain => "may_contain",
+            Self::Contains => "contains",
+        }
+    }
+}
+
+/// A "may contain" declaration on an ingredient or a recipe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllergenTrace {
+    pub allergen: Allergen,
+    pub note: Option<String>,
+}
+
+/// Where one allergen hit comes from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AllergenSource {
+    pub presence: AllergenPresence,
+    /// Ingredient (or, for cross-contact, recipe) name
+    pub source: String,
+    /// Component recipes between the dish and the source, outermost first
+    pub via: Vec<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DishAllergen {
+    pub allergen: Allergen,
+    /// Name in the label language
+    pub label: String,
+    pub presence: AllergenPresence,
+    pub sources: Vec<AllergenSource>,
+}
+
+/// Nutrition values per 100 g of an ingredient; `None` = unknown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct NutrientsPer100g {
+    pub energy_kcal: Option<f64>,
+    pub fat_g: Option<f64>,
+    pub saturated_fat_g: Option<f64>,
+    pub carbs_g: Option<f64>,
+    pub sugars_g: Option<f64>,
+    pub fiber_g: Option<f64>,
+    pub protein_g: Option<f64>,
+    pub salt_g: Option<f64>,
+}
+
+/// Catalog data of one ingredient needed for labelling
+#[derive(Debug, Clone)]
+pub struct LabelIngredient {
+    /// Name in the label language
+    pub name: String,
+    pub allergens: Vec<Allergen>,
+    pub traces: Vec<AllergenTrace>,
+    pub unit: Unit,
+    pub density_g_per_ml: Option<Decimal>,
+    pub typical_portion_g: Option<Decimal>,
+    pub nutrients: NutrientsPer100g,
+}
+
+impl LabelIngredient {
+    /// Grams in one recipe unit of the ingredient. Volumes use the density
+    /// (water if unknown); counted units use the typical portion weight.
+    pub fn grams_per_unit(&self) -> Option<Decimal> {
+        let density = self.density_g_per_ml.unwrap_or(Decimal::ONE);
+        match self.unit {
+            Unit::Gram => Some(Decimal::ONE),
+            Unit::Kilogram => Some(Decimal::ONE_THOUSAND),
+            Unit::Milliliter => Some(density),
+            Unit::Liter => Some(Decimal::ONE_THOUSAND * density),
+            Unit::Piece | Unit::Bunch | Unit::Can | Unit::Bottle | Unit::Package => {
+                self.typical_portion_g.filter(|g| *g > Decimal::ZERO)
+            }
+        }
+    }
+}
+
+/// Absolute nutrition values (a portion or 100 g of the dish)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct NutritionFacts {
+    pub energy_kj: f64,
+    pub energy_kcal: f64,
+    pub fat_g: f64,
+    pub saturated_fat_g: f64,
+    pub carbs_g: f64,
+    pub sugars_g: f64,
+    pub fiber_g: f64,
+    pub protein_g: f64,
+    pub salt_g: f64,
+}
+
+impl NutritionFacts {
+    fn add(&mut self, per_100g: &NutrientsPer100g, grams: f64) {
+        let f = grams / 100.0;
+        let kcal = per_100g.energy_kcal.unwrap_or(0.0) * f;
+        self.energy_kcal += kcal;
+        self.energy_kj += kcal * 4.184;
+        self.fat_g += per_100g.fat_g.unwrap_or(0.0) * f;
+        self.saturated_fat_g += per_100g.saturated_fat_g.unwrap_or(0.0) * f;
+        self.carbs_g += per_100g.carbs_g.unwrap_or(0.0) * f;
+        self.sugars_g += per_100g.sugars_g.unwrap_or(0.0) * f;
+        self.fiber_g += per_100g.fiber_g.unwrap_or(0.0) * f;
+        self.protein_g += per_100g.protein_g.unwrap_or(0.0) * f;
+        self.salt_g += per_100g.salt_g.unwrap_or(0.0) * f;
+    }
+
+    fn scaled(&self, factor: f64) -> Self {
+        Self {
+            energy_kj: self.energy_kj * factor,
+            energy_kcal: self.energy_kcal * factor,
+            fat_g: self.fat_g * factor,
+            saturated_fat_g: self.saturated_fat_g * factor,
+            carbs_g: self.carbs_g * factor,
+            sugars_g: self.sugars_g * factor,
+            fiber_g: self.fiber_g * factor,
+            protein_g: self.protein_g * factor,
+            salt_g: self.salt_g * factor,
+        }
+    }
+
+    /// Rounded the way labels print them: energy to 1, nutrients to 0.1
+    fn rounded(&self) -> Self {
+        let r1 = |v: f64| (v * 10.0).round() / 10.0;
+        Self {
+            energy_kj: self.energy_kj.round(),
+            energy_kcal: self.energy_kcal.round(),
+            fat_g: r1(self.fat_g),
+            saturated_fat_g: r1(self.saturated_fat_g),
+            carbs_g: r1(self.carbs_g),
+            sugars_g: r1(self.sugars_g),
+            fiber_g: r1(self.fiber_g),
+            protein_g: r1(self.protein_g),
+            salt_g: r1(self.salt_g),
+        }
+    }
+}
+
+/// Nutrition of one portion, computed on raw (uncooked) net weights
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NutritionLabel {
+    pub portion_weight_g: f64,
+    pub per_portion: NutritionFacts,
+    pub per_100g: NutritionFacts,
+    /// Ingredients left out: no weight conversion or no energy/macros.
+    /// Saturates, sugars, fibre and salt count only where the catalog has them.
+    pub incomplete_ingredients: Vec<String>,
+}
+
+/// Allergens of a recipe tree, in Annex II order. A missing ingredient or
+/// component recipe is an error rather than a shorter declaration.
+pub fn resolve_allergens(
+    root: RecipeId,
+    recipes: &HashMap<RecipeId, Recipe>,
+    ingredients: &HashMap<CatalogIngredientId, LabelIngredient>,
+    recipe_traces: &HashMap<RecipeId, Vec<AllergenTrace>>,
+    label: impl Fn(Allergen) -> String,
+) -> AppResult<Vec<DishAllergen>> {
+    fn walk(
+        id: RecipeId,
+        path: &mut Vec<String>,
+        recipes: &HashMap<RecipeId, Recipe>,
+        ingredients: &HashMap<CatalogIngredientId, LabelIngredient>,
+        recipe_traces: &HashMap<RecipeId, Vec<AllergenTrace>>,
+        hits: &mut Vec<(Allergen, AllergenSource)>,
+    ) -> AppResult<()> {
+        // A gap in the tree would drop allergens from a legal declaration
+        let recipe = recipes
+            .get(&id)
+            .ok_or_else(|| AppError::not_found(format!("Recipe {} not found", id.as_uuid())))?;
+        let mut push = |allergen: Allergen, source: AllergenSource| {
+            if !hits.iter().any(|(a, s)| *a == allergen && *s == source) {
+                hits.push((allergen, source));
+            }
+        };
+
+        for trace in recipe_traces.get(&id).into_iter().flatten() {
+            // The recipe itself is the source; `via` stops above it
+            push(
+                trace.allergen,
+                AllergenSource {
+                    presence: AllergenPresence::MayContain,
+                    source: recipe.name().as_str().to_string(),
+                    via: path
+                        .iter()
+                        .take(path.len().saturating_sub(1))
+                        .cloned()
+                        .collect(),
+                    note: trace.note.clone(),
+                },
+            );
+        }
+        for item in recipe.ingredients() {
+            let id = item.catalog_ingredient_id();
+            let ingredient = ingredients.get(&id).ok_or_else(|| {
+                AppError::not_found(format!("Catalog ingredient {} not found", id.as_uuid()))
+            })?;
+            for allergen in &ingredient.allergens {
+                push(
+                    *allergen,
+                    AllergenSource {
+                        presence: AllergenPresence::Contains,
+                        source: ingredient.name.clone(),
+                        via: path.clone(),
+                        note: None,
+                    },
+                );
+            }
+            for trace in &ingredient.traces {
+                push(
+                    trace.allergen,
+                    AllergenSource {
+                        presence: AllergenPresence::MayContain,
+                        source: ingredient.name.clone(),
+                        via: path.clone(),
+                        note: trace.note.clone(),
+                    },
+                );
+            }
+        }
+        for component in recipe.components() {
+            let child = component.component_recipe_id();
+            let child_recipe = recipes.get(&child).ok_or_else(|| {
+                AppError::not_found(format!("Component recipe {} not found", child.as_uuid()))
+            })?;
+            path.push(child_recipe.name().as_str().to_string());
+            walk(child, path, recipes, ingredients, recipe_traces, hits)?;
+            path.pop();
+        }
+        Ok(())
+    }
+
+    let mut hits = Vec::new();
+    walk(
+        root,
+        &mut Vec::new(),
+        recipes,
+        ingredients,
+        recipe_traces,
+        &mut hits,
+    )?;
+
+    Ok(EU_ALLERGENS
+        .iter()
+        .filter_map(|allergen| {
+            let sources: Vec<AllergenSource> = hits
+                .iter()
+                .filter(|(a, _)| a == allergen)
+                .map(|(_, s)| s.clone())
+                .collect();
+            let presence = sources.iter().map(|s| s.presence).max()?;
+            Some(DishAllergen {
+                allergen: *allergen,
+                label: label(*allergen),
+                presence,
+                sources,
+            })
+        })
+        .collect())
+}
+
+/// Nutrition of one portion from the net ingredient quantities of a portion
+pub fn portion_nutrition(
+    portion: &[(CatalogIngredientId, Decimal)],
+    ingredients: &HashMap<CatalogIngredientId, LabelIngredient>,
+) -> AppResult<NutritionLabel> {
+    let mut total = NutritionFacts::default();
+    let mut weight = 0.0;
+    let mut incomplete = Vec::new();
+
+    for (id, quantity) in portion {
+        let ingredient = ingredients.get(id).ok_or_else(|| {
+            AppError::not_found(format!("Catalog ingredient {} not found", id.as_uuid()))
+        })?;
+        let nutrients = &ingredient.nutrients;
+        let known = nutrients.energy_kcal.is_some()
+            && nutrients.fat_g.is_some()
+            && nutrients.carbs_g.is_some()
+            && nutrients.protein_g.is_some();
+        let grams = ingredient
+            .grams_per_unit()
+            .and_then(|g| (g * *quantity).to_f64());
+        match grams {
+            Some(grams) if known => {
+                total.add(nutrients, grams);
+                weight += grams;
+            }
+            _ => incomplete.push(ingredient.name.clone()),
+        }
+    }
+
+    let per_100g = if weight > 0.0 {
+        total.scaled(100.0 / weight)
+    } else {
+        NutritionFacts::default()
+    };
+    Ok(NutritionLabel {
+        portion_weight_g: weight.round(),
+        per_portion: total.rounded(),
+        per_100g: per_100g.rounded(),
+        incomplete_ingredients: incomplete,
+    })
+}
+
+/// Net ingredient quantities of one portion (batch divided by servings)
+pub fn per_portion(
+    batch: Vec<(CatalogIngredientId, Decimal)>,
+    servings: u32,
+) -> Vec<(CatalogIngredientId, Decimal)> {
+    let servings = Decimal::from(servings.max(1));
+    batch
+        .into_iter()
+        .map(|(id, qty)| (id, qty / servings))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::inventory::Quantity;
+    use crate::domain::recipe::{
+        RecipeComponent, RecipeIngredient, RecipeName, RecipeType, Servings,
+    };
+    use crate::shared::{TenantId, UserId};
+
+    fn ingredient(name: &str, allergens: Vec<Allergen>, kcal: Option<f64>) -> LabelIngredient {
+        LabelIngredient {
+            name: name.to_string(),
+            allergens,
+            traces: Vec::new(),
+            unit: Unit::Kilogram,
+            density_g_per_ml: None,
+            typical_portion_g: None,
+            nutrients: NutrientsPer100g {
+                energy_kcal: kcal,
+                fat_g: Some(10.0),
+                carbs_g: Some(20.0),
+                protein_g: Some(5.0),
+                salt_g: Some(1.0),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn recipe(
+        name: &str,
+        servings: u32,
+        ingredients: Vec<(CatalogIngredientId, f64)>,
+        components: Vec<RecipeComponent>,
+    ) -> Recipe {
+        Recipe::new(
+            UserId::new(),
+            TenantId::new(),
+            RecipeName::new(name).unwrap(),
+            RecipeType::Final,
+            Servings::new(servings).unwrap(),
+            ingredients
+                .into_iter()
+                .map(|(id, q)| RecipeIngredient::new(id, Quantity::new(q).unwrap()))
+                .collect(),
+            components,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_allergens_with_provenance() {
+        let flour = CatalogIngredientId::new();
+        let butter = CatalogIngredientId::new();
+        let salmon = CatalogIngredientId::new();
+        let mut ingredients = HashMap::new();
+        ingredients.insert(
+            flour,
+            ingredient("Flour", vec![Allergen::Wheat], Some(350.0)),
+        );
+        let mut butter_data = ingredient("Butter", vec![Allergen::Milk], Some(717.0));
+        butter_data.traces.push(AllergenTrace {
+            allergen: Allergen::TreeNuts,
+            note: Some("Supplier spec".to_string()),
+        });
+        ingredients.insert(butter, butter_data);
+        ingredients.insert(
+            salmon,
+            ingredient("Salmon", vec![Allergen::Fish], Some(208.0)),
+        );
+
+        let pastry = recipe("Pastry", 1, vec![(flour, 0.2), (butter, 0.1)], vec![]);
+        let dish = recipe(
+            "Salmon en croûte",
+            2,
+            vec![(salmon, 0.3)],
+            vec![RecipeComponent::new(pastry.id(), Decimal::ONE).unwrap()],
+        );
+        let mut traces = HashMap::new();
+        traces.insert(
+            dish.id(),
+            vec![AllergenTrace {
+                allergen: Allergen::Sesame,
+                note: Some("Shared fryer".to_string()),
+            }],
+        );
+        let root = dish.id();
+        let recipes: HashMap<_, _> = [(dish.id(), dish), (pastry.id(), pastry)].into();
+
+        let allergens = resolve_allergens(root, &recipes, &ingredients, &traces, |a| {
+            a.as_str().to_string()
+        })
+        .unwrap();
+        let order: Vec<_> = allergens.iter().map(|a| (a.allergen, a.presence)).collect();
+        assert_eq!(
+            order,
+            vec![
+                (Allergen::Wheat, AllergenPresence::Contains),
+                (Allergen::Fish, AllergenPresence::Contains),
+                (Allergen::Milk, AllergenPresence::Contains),
+                (Allergen::TreeNuts, AllergenPresence::MayContain),
+                (Allergen::Sesame, AllergenPresence::MayContain),
+            ]
+        );
+        assert_eq!(allergens[0].sources[0].source, "Flour");
+        assert_eq!(allergens[0].sources[0].via, vec!["Pastry".to_string()]);
+        assert_eq!(allergens[4].sources[0].source, "Salmon en croûte");
+        assert!(allergens[4].sources[0].via.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_allergens_missing_data_is_an_error() {
+        let flour = CatalogIngredientId::new();
+        let unknown = CatalogIngredientId::new();
+        let mut ingredients = HashMap::new();
+        ingredients.insert(
+            flour,
+            ingredient("Flour", vec![Allergen::Wheat], Some(350.0)),
+        );
+
+        let bread = recipe("Bread", 1, vec![(flour, 0.2), (unknown, 0.1)], vec![]);
+        let root = bread.id();
+        let recipes: HashMap<_, _> = [(bread.id(), bread)].into();
+        let result = resolve_allergens(root, &recipes, &ingredients, &HashMap::new(), |a| {
+            a.as_str().to_string()
+        });
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        let pastry = recipe("Pastry", 1, vec![(flour, 0.2)], vec![]);
+        let pie = recipe(
+            "Pie",
+            1,
+            vec![],
+            vec![RecipeComponent::new(pastry.id(), Decimal::ONE).unwrap()],
+        );
+        let root = pie.id();
+        let recipes: HashMap<_, _> = [(pie.id(), pie)].into();
+        let result = resolve_allergens(root, &recipes, &ingredients, &HashMap::new(), |a| {
+            a.as_str().to_string()
+        });
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_portion_nutrition() {
+        let rice = CatalogIngredientId::new();
+        let herb = CatalogIngredientId::new();
+        let mut ingredients = HashMap::new();
+        ingredients.insert(rice, ingredient("Rice", vec![], Some(130.0)));
+        let mut herb_data = ingredient("Dill", vec![], Some(43.0));
+        herb_data.unit = Unit::Bunch;
+        ingredients.insert(herb, herb_data);
+
+        // 0.4 kg for 2 servings → 200 g a portion; the bunch has no weight
+        let portion = per_portion(vec![(rice, Decimal::new(4, 1)), (herb, Decimal::ONE)], 2);
+        let label = portion_nutrition(&portion, &ingredients).unwrap();
+        assert_eq!(label.portion_weight_g, 200.0);
+        assert_eq!(label.per_portion.energy_kcal, 260.0);
+        assert_eq!(label.per_portion.energy_kj, 1088.0);
+        assert_eq!(label.per_portion.salt_g, 2.0);
+        assert_eq!(label.per_100g.energy_kcal, 130.0);
+        assert_eq!(label.incomplete_ingredients, vec!["Dill".to_string()]);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::domain::catalog::CatalogIngredientId;
+use crate::domain::storage_location::StorageLocationId;
+use crate::domain::supplier::SupplierId;
 use crate::shared::{AppError, AppResult, TenantId, UserId};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
@@ -181,6 +183,12 @@ pub struct InventoryBatch {
     /// Supplier information
     pub supplier: Option<String>,
 
+    /// Supplier entity (free-text `supplier` is kept for display)
+    pub supplier_id: Option<SupplierId>,
+
+    /// Storage location holding this batch (`None` → tenant default)
+    pub location_id: Option<StorageLocationId>,
+
     /// Invoice/Document reference
     pub invoice_number: Option<String>,
 
@@ -222,6 +230,8 @@ impl InventoryBatch {
             quantity,
             remaining_quantity: quantity,
             supplier: None,
+            supplier_id: None,
+            location_id: None,
             invoice_number: None,
             status: BatchStatus::Active,
             received_at,
@@ -257,6 +267,8 @@ impl InventoryBatch {
             quantity,
             remaining_quantity,
             supplier,
+            supplier_id: None,
+            location_id: None,
             invoice_number,
             status,
             received_at,
@@ -300,14 +312,20 @@ impl InventoryBatch {
     }
 }
 
-/// Movement type (IN/OUT_SALE/OUT_EXPIRE/ADJUSTMENT)
+/// Movement type (IN/OUT_SALE/OUT_EXPIRE/OUT_WASTE/ADJUSTMENT/TRANSFER_OUT/TRANSFER_IN)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum MovementType {
     In,
     OutSale,
     OutExpire,
+    /// Manually logged waste (see `waste_entries`)
+    OutWaste,
     Adjustment,
+    /// Stock leaving a location as part of an inventory transfer
+    TransferOut,
+    /// Stock arriving at a location as part of an inventory transfer
+    TransferIn,
 }
 
 impl std::fmt::Display for MovementType {
@@ -316,7 +334,10 @@ impl std::fmt::Display for MovementType {
             Self::In => write!(f, "IN"),
             Self::OutSale => write!(f, "OUT_SALE"),
             Self::OutExpire => write!(f, "OUT_EXPIRE"),
+            Self::OutWaste => write!(f, "OUT_WASTE"),
             Self::Adjustment => write!(f, "ADJUSTMENT"),
+            Self::TransferOut => write!(f, "TRANSFER_OUT"),
+            Self::TransferIn => write!(f, "TRANSFER_IN"),
         }
     }
 }
@@ -327,6 +348,8 @@ impl std::fmt::Display for MovementType {
 pub enum InventoryAlertType {
     ExpiringBatch,
     LowStock,
+    /// Sold without stock on hand (open shortfall ledger entries)
+    Shortfall,
 }
 
 /// Alert severity
@@ -399,6 +422,122 @@ impl InventoryMovement {
     }
 }
 
+/// What a sale does when FIFO batches cannot cover it (tenant setting)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StockPolicy {
+    /// Roll back the whole sale
+    Reject,
+    /// Deduct what is available, ignore the rest
+    Allow,
+    /// Deduct what is available, log the rest as a shortfall
+    #[default]
+    RecordShortfall,
+}
+
+impl StockPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Reject => "reject",
+            Self::Allow => "allow",
+            Self::RecordShortfall => "record_shortfall",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        match value {
+            "reject" => Ok(Self::Reject),
+            "allow" => Ok(Self::Allow),
+            "record_shortfall" => Ok(Self::RecordShortfall),
+            other => Err(AppError::validation(format!(
+                "Unknown stock policy '{}'. Allowed: reject, allow, record_shortfall",
+                other
+            ))),
+        }
+    }
+}
+
+/// Which unit price recipe costing uses (tenant setting)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostingStrategy {
+    /// Price of the batch FIFO would consume next
+    #[default]
+    FifoActual,
+    /// Most recent purchase price
+    LastPrice,
+    /// Average of stock on hand, weighted by remaining quantity
+    WeightedAverage,
+    /// Lowest latest price among suppliers
+    CheapestSupplier,
+}
+
+impl CostingStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FifoActual => "fifo_actual",
+            Self::LastPrice => "last_price",
+            Self::WeightedAverage => "weighted_average",
+            Self::CheapestSupplier => "cheapest_supplier",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        match value {
+            "fifo_actual" => Ok(Self::FifoActual),
+            "last_price" => Ok(Self::LastPrice),
+            "weighted_average" => Ok(Self::WeightedAverage),
+            "cheapest_supplier" => Ok(Self::CheapestSupplier),
+            other => Err(AppError::validation(format!(
+                "Unknown costing strategy '{}'. Allowed: fifo_actual, last_price, weighted_average, cheapest_supplier",
+                other
+            ))),
+        }
+    }
+}
+
+/// Negative-stock ledger entry: quantity consumed without backing batches
+#[derive(Debug, Clone, Serialize)]
+pub struct InventoryShortfall {
+    pub id: Uuid,
+    pub tenant_id: TenantId,
+    pub catalog_ingredient_id: CatalogIngredientId,
+    pub quantity: Decimal,
+    pub unit_cost_cents: i64,
+    pub estimated_cost_cents: i64,
+    pub reference_id: Option<Uuid>,
+    pub reference_type: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: OffsetDateTime,
+}
+
+impl InventoryShortfall {
+    pub fn new(
+        tenant_id: TenantId,
+        catalog_ingredient_id: CatalogIngredientId,
+        quantity: Decimal,
+        unit_cost_cents: i64,
+    ) -> Self {
+        let estimated_cost = (quantity * Decimal::from(unit_cost_cents))
+            .round()
+            .to_i64()
+            .unwrap_or(0);
+
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id,
+            catalog_ingredient_id,
+            quantity,
+            unit_cost_cents,
+            estimated_cost_cents: estimated_cost,
+            reference_id: None,
+            reference_type: None,
+            notes: None,
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
 /// Expiration severity levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -484,4 +623,39 @@ mod tests {
         let total = product.total_cost().unwrap();
         assert_eq!(total.as_major(), 25.0);
     }
+
+    #[test]
+    fn test_stock_policy_parse() {
+        assert_eq!(StockPolicy::parse("reject").unwrap(), StockPolicy::Reject);
+        assert_eq!(
+            StockPolicy::parse(StockPolicy::RecordShortfall.as_str()).unwrap(),
+            StockPolicy::RecordShortfall
+        );
+        assert!(StockPolicy::parse("negative").is_err());
+        assert_eq!(StockPolicy::default(), StockPolicy::RecordShortfall);
+    }
+
+    #[test]
+    fn test_costing_strategy_parse() {
+        for strategy in [
+            CostingStrategy::FifoActual,
+            CostingStrategy::LastPrice,
+            CostingStrategy::WeightedAverage,
+            CostingStrategy::CheapestSupplier,
+        ] {
+            assert_eq!(CostingStrategy::parse(strategy.as_str()).unwrap(), strategy);
+        }
+        assert!(CostingStrategy::parse("lifo").is_err());
+    }
+
+    #[test]
+    fn test_shortfall_estimated_cost() {
+        let shortfall = InventoryShortfall::new(
+            TenantId::new(),
+            CatalogIngredientId::new(),
+            Decimal::new(15, 1), // 1.5
+            1250,
+        );
+        assert_eq!(shortfall.estimated_cost_cents, 1875);
+    }
 }
@@ -0,0 +1,261 @@
+use crate::shared::{AppError, AppResult, TenantId};
+use serde::{Deserialize, Serialize};
+use time::{OffsetDateTime, Time, Weekday};
+use uuid::Uuid;
+
+/// Menu ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MenuId(Uuid);
+
+impl MenuId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl Default for MenuId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Menu section ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MenuSectionId(Uuid);
+
+impl MenuSectionId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl Default for MenuSectionId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// "HH:MM" on the wire
+time::serde::format_description!(hour_minute, Time, "[hour]:[minute]");
+
+/// When a menu is served.
+///
+/// `days` are ISO weekdays (1 = Monday … 7 = Sunday), empty = every day.
+/// Without `from`/`until` the menu is served all day; `until` earlier than
+/// `from` wraps past midnight (bar 18:00 → 02:00).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailabilityWindow {
+    #[serde(default)]
+    pub days: Vec<u8>,
+    #[serde(default, with = "hour_minute::option")]
+    pub from: Option<Time>,
+    #[serde(default, with = "hour_minute::option")]
+    pub until: Option<Time>,
+}
+
+impl AvailabilityWindow {
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(day) = self.days.iter().find(|d| !(1..=7).contains(*d)) {
+            return Err(AppError::validation(format!(
+                "Invalid weekday {} (expected 1 = Monday … 7 = Sunday)",
+                day
+            )));
+        }
+        match (self.from, self.until) {
+            (Some(from), Some(until)) if from == until => Err(AppError::validation(
+                "Availability window start and end must differ",
+            )),
+            (Some(_), None) | (None, Some(_)) => Err(AppError::validation(
+                "Availability window needs both start and end time",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the menu is served at a given weekday and time of day
+    pub fn covers(&self, weekday: Weekday, time: Time) -> bool {
+        let (Some(from), Some(until)) = (self.from, self.until) else {
+            return self.serves_on(weekday);
+        };
+        if from < until {
+            self.serves_on(weekday) && time >= from && time < until
+        } else if time >= from {
+            self.serves_on(weekday)
+        } else {
+            // After midnight: belongs to the previous day's service
+            time < until && self.serves_on(weekday.previous())
+        }
+    }
+
+    pub fn covers_datetime(&self, at: OffsetDateTime) -> bool {
+        self.covers(at.weekday(), at.time())
+    }
+
+    fn serves_on(&self, weekday: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&weekday.number_from_monday())
+    }
+}
+
+/// Menu (lunch, dinner, bar) — an ordered list of sections
+#[derive(Debug, Clone, Serialize)]
+pub struct Menu {
+    pub id: MenuId,
+    pub tenant_id: TenantId,
+    pub name: String,
+    pub description: Option<String>,
+    pub sort_order: i32,
+    pub is_active: bool,
+    pub availability: AvailabilityWindow,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl Menu {
+    pub fn new(
+        tenant_id: TenantId,
+        name: impl Into<String>,
+        availability: AvailabilityWindow,
+    ) -> AppResult<Self> {
+        availability.validate()?;
+        let now = OffsetDateTime::now_utc();
+        let mut menu = Self {
+            id: MenuId::new(),
+            tenant_id,
+            name: String::new(),
+            description: None,
+            sort_order: 0,
+            is_active: true,
+            availability,
+            created_at: now,
+            updated_at: now,
+        };
+        menu.rename(name)?;
+        Ok(menu)
+    }
+
+    /// Update name (1..=100 characters)
+    pub fn rename(&mut self, name: impl Into<String>) -> AppResult<()> {
+        self.name = validate_name(name.into(), "Menu")?;
+        Ok(())
+    }
+}
+
+/// Section of a menu (starters, mains, desserts); dishes are ordered within it
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuSection {
+    pub id: MenuSectionId,
+    pub menu_id: MenuId,
+    pub name: String,
+    pub sort_order: i32,
+}
+
+impl MenuSection {
+    pub fn new(menu_id: MenuId, name: impl Into<String>, sort_order: i32) -> AppResult<Self> {
+        Ok(Self {
+            id: MenuSectionId::new(),
+            menu_id,
+            name: validate_name(name.into(), "Section")?,
+            sort_order,
+        })
+    }
+
+    /// Update name (1..=100 characters)
+    pub fn rename(&mut self, name: impl Into<String>) -> AppResult<()> {
+        self.name = validate_name(name.into(), "Section")?;
+        Ok(())
+    }
+}
+
+fn validate_name(name: String, what: &str) -> AppResult<String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::validation(format!(
+            "{} name cannot be empty",
+            what
+        )));
+    }
+    if name.chars().count() > 100 {
+        return Err(AppError::validation(format!(
+            "{} name cannot exceed 100 characters",
+            what
+        )));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::time;
+
+    #[test]
+    fn test_availability_window_validation() {
+        assert!(AvailabilityWindow::default().validate().is_ok());
+        let bad_day = AvailabilityWindow {
+            days: vec![0, 3],
+            ..Default::default()
+        };
+        assert!(bad_day.validate().is_err());
+        let half_open = AvailabilityWindow {
+            from: Some(time!(11:00)),
+            ..Default::default()
+        };
+        assert!(half_open.validate().is_err());
+
+        assert!(Menu::new(TenantId::new(), "  ", AvailabilityWindow::default()).is_err());
+        let menu = Menu::new(TenantId::new(), " Lunch ", AvailabilityWindow::default()).unwrap();
+        assert_eq!(menu.name, "Lunch");
+    }
+
+    #[test]
+    fn test_availability_window_covers() {
+        // Weekday lunch 11:00–15:00
+        let lunch = AvailabilityWindow {
+            days: vec![1, 2, 3, 4, 5],
+            from: Some(time!(11:00)),
+            until: Some(time!(15:00)),
+        };
+        assert!(lunch.covers(Weekday::Monday, time!(12:30)));
+        assert!(!lunch.covers(Weekday::Monday, time!(15:00)));
+        assert!(!lunch.covers(Weekday::Saturday, time!(12:30)));
+
+        // Friday/Saturday bar 18:00–02:00
+        let bar = AvailabilityWindow {
+            days: vec![5, 6],
+            from: Some(time!(18:00)),
+            until: Some(time!(02:00)),
+        };
+        assert!(bar.covers(Weekday::Friday, time!(23:00)));
+        assert!(bar.covers(Weekday::Saturday, time!(01:30)));
+        assert!(bar.covers(Weekday::Sunday, time!(01:30)));
+        assert!(!bar.covers(Weekday::Friday, time!(01:30)));
+        assert!(!bar.covers(Weekday::Saturday, time!(03:00)));
+
+        // All-day, every day
+        assert!(AvailabilityWindow::default().covers(Weekday::Sunday, time!(04:00)));
+
+        let json = serde_json::to_value(&lunch).unwrap();
+        assert_eq!(json["from"], "11:00");
+        let parsed: AvailabilityWindow =
+            serde_json::from_str(r#"{"days":[1,2,3,4,5],"from":"11:00","until":"15:00"}"#).unwrap();
+        assert_eq!(parsed, lunch);
+    }
+}
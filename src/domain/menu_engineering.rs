@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
 
 use crate::domain::DishId;
 
+/// Kasavana–Smith popularity cut-off: 70% of an item's fair share of the mix
+pub const POPULARITY_FAIR_SHARE_FACTOR: f64 = 0.7;
+
 /// ABC Classification (Pareto Principle - 80/20 rule)
 ///
 /// Classification based on revenue contribution:
@@ -89,6 +94,28 @@ impl MenuCategory {
         }
     }
 
+    /// Classic Kasavana–Smith classification within one menu section:
+    /// popular when the item's menu-mix share reaches 70% of the fair share
+    /// (1 / items in section), profitable when its contribution margin per
+    /// item reaches the section's weighted average.
+    pub fn classify_kasavana_smith(
+        menu_mix_share: f64,
+        items_in_section: usize,
+        margin_per_item_cents: i64,
+        section_avg_margin_cents: f64,
+    ) -> Self {
+        let fair_share = 1.0 / items_in_section.max(1) as f64;
+        let is_popular = menu_mix_share >= fair_share * POPULARITY_FAIR_SHARE_FACTOR;
+        let is_profitable = margin_per_item_cents as f64 >= section_avg_margin_cents;
+
+        match (is_profitable, is_popular) {
+            (true, true) => MenuCategory::Star,
+            (false, true) => MenuCategory::Plowhorse,
+            (true, false) => MenuCategory::Puzzle,
+            (false, false) => MenuCategory::Dog,
+        }
+    }
+
     /// Get emoji representation
     pub fn emoji(&self) -> &'static str {
         match self {
@@ -248,7 +275,7 @@ pub struct DishPerformance {
     /// Profit margin percentage
     pub profit_margin_percent: f64,
 
-    /// Popularity score (0.0 to 1.0, normalized against all dishes)
+    /// Popularity score: menu-mix share within the dish's section (0.0 to 1.0)
     pub popularity_score: f64,
 
     /// Total sales volume (number of orders)
@@ -263,6 +290,14 @@ pub struct DishPerformance {
     /// Contribution margin (profit × volume)
     pub contribution_margin_cents: i64,
 
+    /// Contribution margin of one portion (selling price − food cost)
+    pub contribution_margin_per_item_cents: i64,
+
+    /// Menu section the dish competes in (`None` = not on any menu)
+    pub menu_section_id: Option<Uuid>,
+    pub menu_section_name: Option<String>,
+    pub menu_name: Option<String>,
+
     /// Cumulative revenue share (for ABC analysis)
     pub cumulative_revenue_share: f64,
 
@@ -288,6 +323,11 @@ impl DishPerformance {
         let category = MenuCategory::classify(profit_margin_percent, popularity_score);
         let abc_class = AbcClass::classify(cumulative_revenue_share);
         let contribution_margin_cents = total_profit_cents;
+        let contribution_margin_per_item_cents = if sales_volume > 0 {
+            total_profit_cents / sales_volume as i64
+        } else {
+            0
+        };
         let recommendation = category.recommendation(language);
         let strategy = MenuCategory::combined_strategy(category, abc_class, language);
 
@@ -302,12 +342,32 @@ impl DishPerformance {
             total_revenue_cents,
             total_profit_cents,
             contribution_margin_cents,
+            contribution_margin_per_item_cents,
+            menu_section_id: None,
+            menu_section_name: None,
+            menu_name: None,
             cumulative_revenue_share,
             recommendation,
             strategy,
         }
     }
 
+    /// Place the dish in a menu section for section-level classification
+    pub fn in_section(mut self, section_id: Uuid, section_name: String, menu_name: String) -> Self {
+        self.menu_section_id = Some(section_id);
+        self.menu_section_name = Some(section_name);
+        self.menu_name = Some(menu_name);
+        self
+    }
+
+    /// Re-derive category and advice from the final classification inputs
+    fn reclassify(&mut self, category: MenuCategory, language: crate::shared::Language) {
+        self.category = category;
+        self.abc_class = AbcClass::classify(self.cumulative_revenue_share);
+        self.recommendation = category.recommendation(language);
+        self.strategy = MenuCategory::combined_strategy(category, self.abc_class, language);
+    }
+
     /// Check if dish is a Star
     pub fn is_star(&self) -> bool {
         matches!(self.category, MenuCategory::Star)
@@ -319,6 +379,25 @@ impl DishPerformance {
     }
 }
 
+/// Kasavana–Smith thresholds and counts of one menu section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuSectionAnalysis {
+    /// `None` = dishes not assigned to any menu section
+    pub menu_section_id: Option<Uuid>,
+    pub menu_section_name: Option<String>,
+    pub menu_name: Option<String>,
+    pub total_dishes: usize,
+    pub items_sold: u32,
+    /// 70% of the fair share (1 / dishes), as a menu-mix share
+    pub popularity_threshold: f64,
+    /// Weighted average contribution margin per item sold
+    pub avg_contribution_margin_cents: f64,
+    pub stars: usize,
+    pub plowhorses: usize,
+    pub puzzles: usize,
+    pub dogs: usize,
+}
+
 /// Menu Engineering Matrix - aggregated analysis
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MenuEngineeringMatrix {
@@ -334,6 +413,9 @@ pub struct MenuEngineeringMatrix {
     /// Performance by dish
     pub dishes: Vec<DishPerformance>,
 
+    /// Section thresholds, in order of first appearance in `dishes`
+    pub sections: Vec<MenuSectionAnalysis>,
+
     /// Summary statistics
     pub avg_profit_margin: f64,
     pub total_revenue_cents: i64,
@@ -341,7 +423,81 @@ pub struct MenuEngineeringMatrix {
 }
 
 impl MenuEngineeringMatrix {
-    pub fn analyze(dishes: Vec<DishPerformance>) -> Self {
+    /// Classify dishes with the Kasavana–Smith thresholds of their own
+    /// section, so a dessert competes with desserts and not with steaks
+    pub fn analyze(mut dishes: Vec<DishPerformance>, language: crate::shared::Language) -> Self {
+        let mut order: Vec<Option<Uuid>> = Vec::new();
+        let mut groups: HashMap<Option<Uuid>, Vec<usize>> = HashMap::new();
+        for (index, dish) in dishes.iter().enumerate() {
+            groups
+                .entry(dish.menu_section_id)
+                .or_insert_with(|| {
+                    order.push(dish.menu_section_id);
+                    Vec::new()
+                })
+                .push(index);
+        }
+
+        let mut sections = Vec::with_capacity(order.len());
+        for section_id in order {
+            let members = &groups[&section_id];
+            let items_sold: u32 = members.iter().map(|&i| dishes[i].sales_volume).sum();
+            let total_margin: i64 = members
+                .iter()
+                .map(|&i| {
+                    dishes[i].contribution_margin_per_item_cents * dishes[i].sales_volume as i64
+                })
+                .sum();
+            let avg_margin = if items_sold > 0 {
+                total_margin as f64 / items_sold as f64
+            } else {
+                // Nothing sold: compare against the plain average
+                members
+                    .iter()
+                    .map(|&i| dishes[i].contribution_margin_per_item_cents as f64)
+                    .sum::<f64>()
+                    / members.len() as f64
+            };
+
+            let mut analysis = MenuSectionAnalysis {
+                menu_section_id: section_id,
+                menu_section_name: dishes[members[0]].menu_section_name.clone(),
+                menu_name: dishes[members[0]].menu_name.clone(),
+                total_dishes: members.len(),
+                items_sold,
+                popularity_threshold: POPULARITY_FAIR_SHARE_FACTOR / members.len() as f64,
+                avg_contribution_margin_cents: avg_margin,
+                stars: 0,
+                plowhorses: 0,
+                puzzles: 0,
+                dogs: 0,
+            };
+
+            for &i in members {
+                let dish = &mut dishes[i];
+                let mix_share = if items_sold > 0 {
+                    dish.sales_volume as f64 / items_sold as f64
+                } else {
+                    0.0
+                };
+                dish.popularity_score = mix_share;
+                let category = MenuCategory::classify_kasavana_smith(
+                    mix_share,
+                    members.len(),
+                    dish.contribution_margin_per_item_cents,
+                    avg_margin,
+                );
+                dish.reclassify(category, language);
+                match category {
+                    MenuCategory::Star => analysis.stars += 1,
+                    MenuCategory::Plowhorse => analysis.plowhorses += 1,
+                    MenuCategory::Puzzle => analysis.puzzles += 1,
+                    MenuCategory::Dog => analysis.dogs += 1,
+                }
+            }
+            sections.push(analysis);
+        }
+
         let total_dishes = dishes.len();
 
         let stars = dishes
@@ -377,6 +533,7 @@ impl MenuEngineeringMatrix {
             puzzles,
             dogs,
             dishes,
+            sections,
             avg_profit_margin,
             total_revenue_cents,
             total_profit_cents,
@@ -422,4 +579,79 @@ mod tests {
         assert_eq!(perf.category, MenuCategory::Star);
         assert_eq!(perf.abc_class, AbcClass::A);
     }
+
+    fn sold(name: &str, volume: u32, margin_per_item: i64, section: Uuid) -> DishPerformance {
+        let revenue = margin_per_item * 3 * volume as i64;
+        DishPerformance::new(
+            DishId::from_uuid(uuid::Uuid::new_v4()),
+            name.to_string(),
+            66.0,
+            0.0,
+            volume,
+            revenue,
+            margin_per_item * volume as i64,
+            0.5,
+            crate::shared::Language::En,
+        )
+        .in_section(section, "Section".to_string(), "Dinner".to_string())
+    }
+
+    #[test]
+    fn test_kasavana_smith_thresholds() {
+        // 4 items → fair share 25%, popularity cut-off 17.5%
+        assert_eq!(
+            MenuCategory::classify_kasavana_smith(0.20, 4, 900, 800.0),
+            MenuCategory::Star
+        );
+        assert_eq!(
+            MenuCategory::classify_kasavana_smith(0.20, 4, 700, 800.0),
+            MenuCategory::Plowhorse
+        );
+        assert_eq!(
+            MenuCategory::classify_kasavana_smith(0.15, 4, 900, 800.0),
+            MenuCategory::Puzzle
+        );
+        assert_eq!(
+            MenuCategory::classify_kasavana_smith(0.15, 4, 700, 800.0),
+            MenuCategory::Dog
+        );
+    }
+
+    #[test]
+    fn test_matrix_classifies_within_sections() {
+        let mains = uuid::Uuid::new_v4();
+        let desserts = uuid::Uuid::new_v4();
+        let dishes = vec![
+            sold("Steak", 100, 3_000, mains),
+            sold("Burger", 300, 1_200, mains),
+            sold("Fish", 20, 2_500, mains),
+            sold("Tiramisu", 80, 900, desserts),
+            sold("Sorbet", 20, 600, desserts),
+        ];
+
+        let matrix = MenuEngineeringMatrix::analyze(dishes, crate::shared::Language::En);
+        let category = |name: &str| {
+            matrix
+                .dishes
+                .iter()
+                .find(|d| d.dish_name == name)
+                .unwrap()
+                .category
+        };
+
+        // Mains: avg CM (300k + 360k + 50k) / 420 ≈ 1690, cut-off 23.3%
+        assert_eq!(category("Steak"), MenuCategory::Star);
+        assert_eq!(category("Burger"), MenuCategory::Plowhorse);
+        assert_eq!(category("Fish"), MenuCategory::Puzzle);
+        // Desserts are judged against desserts only: cut-off 35%, avg CM 840
+        assert_eq!(category("Tiramisu"), MenuCategory::Star);
+        assert_eq!(category("Sorbet"), MenuCategory::Dog);
+
+        assert_eq!(matrix.sections.len(), 2);
+        assert_eq!(matrix.sections[0].menu_section_id, Some(mains));
+        assert_eq!(matrix.sections[0].items_sold, 420);
+        assert!((matrix.sections[1].popularity_threshold - 0.35).abs() < 1e-9);
+        assert_eq!(matrix.stars, 2);
+        assert_eq!(matrix.dogs, 1);
+    }
 }
@@ -5,21 +5,33 @@ pub mod auth;
 pub mod catalog;
 pub mod classification_rules; // 🆕 Added classification rules
 pub mod dish;
+pub mod dish_label; // 🆕 EU-14 allergen declaration + nutrition label per dish
 pub mod engines; // 🆕 Culinary Intelligence Platform — 5 engine traits + registry
 pub mod inventory;
 pub mod matter; // 🆕 Precision sketch — re-exports geometry_engine::sketch
+pub mod menu; // 🆕 Menus, ordered sections, availability windows
 pub mod menu_engineering;
+pub mod modifier; // 🆕 Dish modifier groups/options (sizes, add-ons, removals)
+pub mod pos_import; // 🆕 POS tickets, CSV parsing, webhook signatures
+pub mod pricing; // 🆕 Price targets, psychological endings, what-if inputs
 pub mod processing_state; // 🆕 Product states (raw, boiled, fried, etc.)
+pub mod purchase_receipt; // 🆕 Goods-received notes (delivery discrepancies)
 pub mod recipe;
 pub mod recipe_ai_insights; // AI-generated insights
 pub mod recipe_v2; // V2 with translation support
+pub mod reorder; // 🆕 Par levels / reorder points projection
 pub mod report;
+pub mod report_subscription; // 🆕 Emailed report subscriptions (kinds, weekly/monthly schedule)
+pub mod stocktake; // 🆕 Physical stocktake (count sessions + variance)
+pub mod storage_location; // 🆕 Multi-location stock (walk-in, bar, outlets)
+pub mod supplier; // 🆕 Suppliers + purchase price history
 pub mod tenant;
 pub mod tenant_ingredient;
 pub mod tools; // 🆕 Chef tools domain (unit converter, yield, scale)
 pub mod usage; // ChefOS iOS usage tracking + monetization
 pub mod user;
 pub mod user_preferences; // ChefOS user health/diet/lifestyle preferences
+pub mod waste; // 🆕 Waste log reasons + loss report buckets
 
 pub use admin::*;
 pub use ai_ports::*;
@@ -0,0 +1,524 @@
+use crate::domain::{CatalogIngredientId, DishId};
+use crate::shared::{AppError, AppResult};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Modifier group ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ModifierGroupId(Uuid);
+
+impl ModifierGroupId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl Default for ModifierGroupId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Modifier option ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ModifierOptionId(Uuid);
+
+impl ModifierOptionId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl Default for ModifierOptionId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How an option changes one ingredient of the dish
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum IngredientDelta {
+    /// Extra ingredient; net quantity, trim loss is added like in recipes
+    Add {
+        catalog_ingredient_id: CatalogIngredientId,
+        quantity: Decimal,
+    },
+    /// Leave the ingredient out entirely ("no onion")
+    Remove {
+        catalog_ingredient_id: CatalogIngredientId,
+    },
+}
+
+impl IngredientDelta {
+    pub fn catalog_ingredient_id(&self) -> CatalogIngredientId {
+        match *self {
+            Self::Add {
+                catalog_ingredient_id,
+                ..
+            }
+            | Self::Remove {
+                catalog_ingredient_id,
+            } => catalog_ingredient_id,
+        }
+    }
+}
+
+/// Option of a modifier group: a size, an add-on or a removal
+#[derive(Debug, Clone, Serialize)]
+pub struct ModifierOption {
+    pub id: ModifierOptionId,
+    pub group_id: ModifierGroupId,
+    pub name: String,
+    /// Added to the dish price (negative = discount)
+    pub price_delta_cents: i64,
+    /// Scales the whole base recipe (L = 1.5); `None` = unchanged
+    pub quantity_scale: Option<Decimal>,
+    pub ingredients: Vec<IngredientDelta>,
+    pub sort_order: i32,
+    pub is_active: bool,
+}
+
+impl ModifierOption {
+    pub fn new(
+        group_id: ModifierGroupId,
+        name: impl Into<String>,
+        price_delta_cents: i64,
+        quantity_scale: Option<Decimal>,
+        ingredients: Vec<IngredientDelta>,
+    ) -> AppResult<Self> {
+        let option = Self {
+            id: ModifierOptionId::new(),
+            group_id,
+            name: validate_name(name.into(), "Modifier option")?,
+            price_delta_cents,
+            quantity_scale,
+            ingredients,
+            sort_order: 0,
+            is_active: true,
+        };
+        option.validate()?;
+        Ok(option)
+    }
+
+    pub fn validate(&self) -> AppResult<()> {
+        if let Some(scale) = self.quantity_scale {
+            if scale <= Decimal::ZERO {
+                return Err(AppError::validation(
+                    "Quantity scale must be greater than 0",
+                ));
+            }
+        }
+        for delta in &self.ingredients {
+            if let IngredientDelta::Add { quantity, .. } = delta {
+                if *quantity <= Decimal::ZERO {
+                    return Err(AppError::validation(
+                        "Added ingredient quantity must be greater than 0",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Group of options offered with a dish ("Size", "Extras", "Without")
+#[derive(Debug, Clone, Serialize)]
+pub struct ModifierGroup {
+    pub id: ModifierGroupId,
+    pub dish_id: DishId,
+    pub name: String,
+    /// Options a guest must pick (1 for sizes)
+    pub min_select: i32,
+    /// Most options a guest may pick
+    pub max_select: i32,
+    pub sort_order: i32,
+    pub options: Vec<ModifierOption>,
+}
+
+impl ModifierGroup {
+    pub fn new(
+        dish_id: DishId,
+        name: impl Into<String>,
+        min_select: i32,
+        max_select: i32,
+    ) -> AppResult<Self> {
+        let group = Self {
+            id: ModifierGroupId::new(),
+            dish_id,
+            name: validate_name(name.into(), "Modifier group")?,
+            min_select,
+            max_select,
+            sort_order: 0,
+            options: Vec::new(),
+        };
+        group.validate_limits()?;
+        Ok(group)
+    }
+
+    pub fn validate_limits(&self) -> AppResult<()> {
+        if self.min_select < 0 || self.max_select < 1 || self.min_select > self.max_select {
+            return Err(AppError::validation(
+                "Modifier group needs 0 <= min_select <= max_select and max_select >= 1",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Update name (1..=100 characters)
+    pub fn rename(&mut self, name: impl Into<String>) -> AppResult<()> {
+        self.name = validate_name(name.into(), "Modifier group")?;
+        Ok(())
+    }
+}
+
+/// Resolve the options chosen for one sale of a dish.
+///
+/// Every option must be an active option of the dish and each group's
+/// `max_select` holds. An empty selection is the plain dish; once anything
+/// is selected, `min_select` of every group applies too.
+pub fn resolve_selection<'a>(
+    groups: &'a [ModifierGroup],
+    selected: &[ModifierOptionId],
+) -> AppResult<Vec<&'a ModifierOption>> {
+    let mut options = Vec::with_capacity(selected.len());
+    let mut per_group: HashMap<ModifierGroupId, i32> = HashMap::new();
+    for id in selected {
+        if options.iter().any(|o: &&ModifierOption| o.id == *id) {
+            return Err(AppError::validation("Modifier option selected twice"));
+        }
+        let option = groups
+            .iter()
+            .flat_map(|g| g.options.iter())
+            .find(|o| o.id == *id && o.is_active)
+            .ok_or_else(|| AppError::validation("Modifier option is not offered with this dish"))?;
+        *per_group.entry(option.group_id).or_default() += 1;
+        options.push(option);
+    }
+
+    if selected.is_empty() {
+        return Ok(options);
+    }
+    for group in groups {
+        let count = per_group.get(&group.id).copied().unwrap_or(0);
+        if count < group.min_select || count > group.max_select {
+            return Err(AppError::validation(format!(
+                "Pick between {} and {} options for '{}'",
+                group.min_select, group.max_select, group.name
+            )));
+        }
+    }
+    Ok(options)
+}
+
+/// Options recorded on a past sale, looked up without re-checking the
+/// selection rules: the menu may have changed since. Deactivated options
+/// still count; deleted ones (and repeats) are skipped.
+pub fn recorded_options<'a>(
+    groups: &'a [ModifierGroup],
+    recorded: &[ModifierOptionId],
+) -> Vec<&'a ModifierOption> {
+    let mut options: Vec<&ModifierOption> = Vec::with_capacity(recorded.len());
+    for id in recorded {
+        if options.iter().any(|o| o.id == *id) {
+            continue;
+        }
+        if let Some(option) = groups
+            .iter()
+            .flat_map(|g| g.options.iter())
+            .find(|o| o.id == *id)
+        {
+            options.push(option);
+        }
+    }
+    options
+}
+
+/// As-purchased ingredients of one portion with modifiers applied.
+///
+/// Scales multiply the base recipe first, then removals drop ingredients,
+/// then add-ons are added (not scaled). `yields` converts added net
+/// quantities to gross; missing yields count as 100%.
+pub fn apply_modifiers(
+    base: &[(CatalogIngredientId, Decimal)],
+    options: &[&ModifierOption],
+    yields: &HashMap<CatalogIngredientId, Decimal>,
+) -> Vec<(CatalogIngredientId, Decimal)> {
+    let scale: Decimal = options.iter().filter_map(|o| o.quantity_scale).product();
+    let deltas = || options.iter().flat_map(|o| o.ingredients.iter());
+
+    let mut out: Vec<(CatalogIngredientId, Decimal)> = base
+        .iter()
+        .filter(|(id, _)| {
+            !deltas().any(|d| matches!(d, IngredientDelta::Remove { catalog_ingredient_id } if catalog_ingredient_id == id))
+        })
+        .map(|(id, qty)| (*id, *qty * scale))
+        .collect();
+
+    for delta in deltas() {
+        if let IngredientDelta::Add {
+            catalog_ingredient_id,
+            quantity,
+        } = *delta
+        {
+            let gross = match yields.get(&catalog_ingredient_id) {
+                Some(yield_percent) => super::recipe::gross_quantity(quantity, *yield_percent),
+                None => quantity,
+            };
+            match out.iter_mut().find(|(id, _)| *id == catalog_ingredient_id) {
+                Some((_, total)) => *total += gross,
+                None => out.push((catalog_ingredient_id, gross)),
+            }
+        }
+    }
+    out
+}
+
+/// Cost difference (cents, per portion) between two ingredient lists.
+/// Ingredients without a unit price are an error, as in recipe costing.
+pub fn cost_delta_cents(
+    base: &[(CatalogIngredientId, Decimal)],
+    modified: &[(CatalogIngredientId, Decimal)],
+    unit_prices_cents: &HashMap<CatalogIngredientId, i64>,
+) -> AppResult<i64> {
+    let mut quantity_delta: HashMap<CatalogIngredientId, Decimal> = HashMap::new();
+    for (id, qty) in modified {
+        *quantity_delta.entry(*id).or_default() += *qty;
+    }
+    for (id, qty) in base {
+        *quantity_delta.entry(*id).or_default() -= *qty;
+    }
+
+    let mut total = Decimal::ZERO;
+    for (id, delta) in quantity_delta {
+        if delta.is_zero() {
+            continue;
+        }
+        let price = unit_prices_cents.get(&id).ok_or_else(|| {
+            AppError::NotFound(format!(
+                "No inventory data for ingredient {}. Cannot calculate cost.",
+                id.as_uuid()
+            ))
+        })?;
+        total += delta * Decimal::from(*price);
+    }
+    total
+        .round()
+        .to_i64()
+        .ok_or_else(|| AppError::internal("Modifier cost out of range"))
+}
+
+/// Attach rate and margin of one modifier option over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifierPerformance {
+    pub dish_id: DishId,
+    pub dish_name: String,
+    pub group_name: String,
+    pub option_id: Option<ModifierOptionId>,
+    pub option_name: String,
+    /// Portions of the dish sold in the period
+    pub dish_units_sold: i64,
+    /// Portions sold with this option
+    pub attached_units: i64,
+    /// attached_units / dish_units_sold
+    pub attach_rate: f64,
+    pub revenue_cents: i64,
+    pub cost_cents: i64,
+    /// Price delta − cost delta, summed over attached portions
+    pub margin_cents: i64,
+    pub margin_per_attach_cents: i64,
+}
+
+impl ModifierPerformance {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        dish_id: DishId,
+        dish_name: String,
+        group_name: String,
+        option_id: Option<ModifierOptionId>,
+        option_name: String,
+        dish_units_sold: i64,
+        attached_units: i64,
+        revenue_cents: i64,
+        cost_cents: i64,
+    ) -> Self {
+        let margin_cents = revenue_cents - cost_cents;
+        Self {
+            dish_id,
+            dish_name,
+            group_name,
+            option_id,
+            option_name,
+            dish_units_sold,
+            attached_units,
+            attach_rate: if dish_units_sold > 0 {
+                attached_units as f64 / dish_units_sold as f64
+            } else {
+                0.0
+            },
+            revenue_cents,
+            cost_cents,
+            margin_cents,
+            margin_per_attach_cents: if attached_units > 0 {
+                margin_cents / attached_units
+            } else {
+                0
+            },
+        }
+    }
+}
+
+fn validate_name(name: String, what: &str) -> AppResult<String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::validation(format!(
+            "{} name cannot be empty",
+            what
+        )));
+    }
+    if name.chars().count() > 100 {
+        return Err(AppError::validation(format!(
+            "{} name cannot exceed 100 characters",
+            what
+        )));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(
+        group: &ModifierGroup,
+        name: &str,
+        scale: Option<Decimal>,
+        deltas: Vec<IngredientDelta>,
+    ) -> ModifierOption {
+        ModifierOption::new(group.id, name, 0, scale, deltas).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_selection() {
+        let dish = DishId::new();
+        let mut size = ModifierGroup::new(dish, "Size", 1, 1).unwrap();
+        let small = option(&size, "S", Some(Decimal::new(75, 2)), vec![]);
+        let large = option(&size, "L", Some(Decimal::new(15, 1)), vec![]);
+        size.options = vec![small.clone(), large.clone()];
+        let mut extras = ModifierGroup::new(dish, "Extras", 0, 2).unwrap();
+        let cheese = option(&extras, "Extra cheese", None, vec![]);
+        extras.options = vec![cheese.clone()];
+        let groups = vec![size, extras];
+
+        assert!(resolve_selection(&groups, &[]).unwrap().is_empty());
+        assert_eq!(
+            resolve_selection(&groups, &[large.id, cheese.id])
+                .unwrap()
+                .len(),
+            2
+        );
+        // Two sizes, a missing size once something is picked, foreign option
+        assert!(resolve_selection(&groups, &[small.id, large.id]).is_err());
+        assert!(resolve_selection(&groups, &[cheese.id]).is_err());
+        assert!(resolve_selection(&groups, &[ModifierOptionId::new()]).is_err());
+        assert!(resolve_selection(&groups, &[large.id, large.id]).is_err());
+
+        assert!(ModifierGroup::new(dish, "Bad", 2, 1).is_err());
+        assert!(ModifierOption::new(groups[0].id, "Zero", 0, Some(Decimal::ZERO), vec![]).is_err());
+    }
+
+    #[test]
+    fn test_recorded_options_ignore_current_rules() {
+        let dish = DishId::new();
+        let mut size = ModifierGroup::new(dish, "Size", 1, 1).unwrap();
+        let mut large = option(&size, "L", Some(Decimal::new(15, 1)), vec![]);
+        large.is_active = false;
+        size.options = vec![large.clone()];
+        let groups = vec![size];
+
+        // Deactivated since the sale, min_select no longer checked
+        assert!(resolve_selection(&groups, &[large.id]).is_err());
+        let deleted = ModifierOptionId::new();
+        let options = recorded_options(&groups, &[large.id, deleted, large.id]);
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].id, large.id);
+        assert!(recorded_options(&groups, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_apply_modifiers_and_cost_delta() {
+        let dish = DishId::new();
+        let group = ModifierGroup::new(dish, "Burger", 0, 3).unwrap();
+        let bun = CatalogIngredientId::new();
+        let onion = CatalogIngredientId::new();
+        let cheese = CatalogIngredientId::new();
+        let base = vec![(bun, Decimal::ONE), (onion, Decimal::new(5, 2))];
+
+        let large = option(&group, "L", Some(Decimal::new(15, 1)), vec![]);
+        let no_onion = option(
+            &group,
+            "No onion",
+            None,
+            vec![IngredientDelta::Remove {
+                catalog_ingredient_id: onion,
+            }],
+        );
+        let extra_cheese = option(
+            &group,
+            "Extra cheese",
+            None,
+            vec![IngredientDelta::Add {
+                catalog_ingredient_id: cheese,
+                quantity: Decimal::new(3, 2),
+            }],
+        );
+        // Cheese at 75% yield: 0.03 kg net → 0.04 kg gross
+        let yields = HashMap::from([(cheese, Decimal::from(75))]);
+
+        let modified = apply_modifiers(&base, &[&large, &no_onion, &extra_cheese], &yields);
+        assert_eq!(
+            modified,
+            vec![(bun, Decimal::new(15, 1)), (cheese, Decimal::new(4, 2))]
+        );
+
+        // bun 100¢/pc, onion 200¢/kg, cheese 2000¢/kg
+        let prices = HashMap::from([(bun, 100), (onion, 200), (cheese, 2000)]);
+        // +0.5 bun (+50) − 0.05 onion (−10) + 0.04 cheese (+80)
+        assert_eq!(cost_delta_cents(&base, &modified, &prices).unwrap(), 120);
+        assert_eq!(cost_delta_cents(&base, &base, &HashMap::new()).unwrap(), 0);
+
+        let perf = ModifierPerformance::new(
+            dish,
+            "Burger".into(),
+            "Extras".into(),
+            Some(extra_cheese.id),
+            "Extra cheese".into(),
+            40,
+            10,
+            1500,
+            800,
+        );
+        assert_eq!(perf.attach_rate, 0.25);
+        assert_eq!(perf.margin_cents, 700);
+        assert_eq!(perf.margin_per_attach_cents, 70);
+    }
+}
@@ -0,0 +1,475 @@
+use crate::shared::{AppError, AppResult};
+use hmac::{Hmac, Mac};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::str::FromStr;
+use subtle::ConstantTimeEq;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Webhooks signed more than 5 minutes ago (or ahead) are rejected
+pub const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+/// How a ticket reached us
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PosImportSource {
+    Csv,
+    Webhook,
+}
+
+impl PosImportSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Webhook => "webhook",
+        }
+    }
+}
+
+/// Why a ticket line waits in the review queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PosReviewReason {
+    /// No dish mapped to the PLU / item name
+    Unmapped,
+    /// Mapped, but booking the sale failed (e.g. stock policy rejected it)
+    Failed,
+}
+
+impl PosReviewReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unmapped => "unmapped",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        match value {
+            "unmapped" => Ok(Self::Unmapped),
+            "failed" => Ok(Self::Failed),
+            _ => Err(AppError::internal(format!(
+                "Unknown POS review reason: {}",
+                value
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PosReviewStatus {
+    Pending,
+    Booked,
+    Ignored,
+}
+
+impl PosReviewStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Booked => "booked",
+            Self::Ignored => "ignored",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "booked" => Ok(Self::Booked),
+            "ignored" => Ok(Self::Ignored),
+            _ => Err(AppError::internal(format!(
+                "Unknown POS review status: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Mapping key of a POS item: the PLU when there is one, otherwise the
+/// item name (trimmed, lowercased, inner whitespace collapsed)
+pub fn pos_item_key(plu: Option<&str>, item_name: &str) -> AppResult<String> {
+    if let Some(plu) = plu.map(str::trim).filter(|p| !p.is_empty()) {
+        return Ok(format!("plu:{}", plu));
+    }
+    let name = item_name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    if name.is_empty() {
+        return Err(AppError::validation("POS item needs a PLU or a name"));
+    }
+    Ok(format!("name:{}", name))
+}
+
+/// One line of a POS ticket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PosTicketLine {
+    #[serde(default)]
+    pub plu: Option<String>,
+    #[serde(default)]
+    pub item_name: String,
+    pub quantity: u32,
+    /// Price actually charged per unit
+    pub unit_price_cents: i64,
+}
+
+impl PosTicketLine {
+    /// Keys to look the item up by, most specific first (PLU, then name)
+    pub fn lookup_keys(&self) -> Vec<String> {
+        let mut keys = Vec::with_capacity(2);
+        if let Ok(key) = pos_item_key(self.plu.as_deref(), &self.item_name) {
+            keys.push(key);
+        }
+        if let Ok(key) = pos_item_key(None, &self.item_name) {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+
+    /// Name shown in the review queue (falls back to the PLU)
+    pub fn display_name(&self) -> String {
+        let name = self.item_name.trim();
+        if name.is_empty() {
+            self.plu.clone().unwrap_or_default()
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+/// A POS ticket (receipt) with its lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PosTicket {
+    /// Ticket ID assigned by the POS — the idempotency key
+    pub ticket_id: String,
+    /// Defaults to the time of import
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub sold_at: Option<OffsetDateTime>,
+    /// Selling outlet (storage location ingredients are deducted from)
+    #[serde(default)]
+    pub location_id: Option<Uuid>,
+    pub lines: Vec<PosTicketLine>,
+}
+
+impl PosTicket {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.ticket_id.trim().is_empty() {
+            return Err(AppError::validation("POS ticket ID cannot be empty"));
+        }
+        if self.lines.is_empty() {
+            return Err(AppError::validation(format!(
+                "POS ticket {} has no lines",
+                self.ticket_id
+            )));
+        }
+        for line in &self.lines {
+            pos_item_key(line.plu.as_deref(), &line.item_name)?;
+            if line.quantity == 0 {
+                return Err(AppError::validation(format!(
+                    "POS ticket {}: quantity must be greater than 0",
+                    self.ticket_id
+                )));
+            }
+            // Zero is a comped line
+            if line.unit_price_cents < 0 || line.unit_price_cents > i32::MAX as i64 {
+                return Err(AppError::validation(format!(
+                    "POS ticket {}: invalid unit price",
+                    self.ticket_id
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Body of the POS webhook
+#[derive(Debug, Clone, Deserialize)]
+pub struct PosWebhookPayload {
+    pub tickets: Vec<PosTicket>,
+}
+
+/// Verify a `X-Pos-Signature: t=<unix>,v1=<hex>` header — the Stripe scheme:
+/// HMAC-SHA256 over `timestamp.body`, any `v1` may match, timestamp within
+/// [`SIGNATURE_TOLERANCE_SECS`] of `now_unix`.
+pub fn verify_pos_signature(
+    secret: &str,
+    payload: &[u8],
+    header: &str,
+    now_unix: i64,
+) -> AppResult<()> {
+    let mut timestamp: Option<&str> = None;
+    let mut signatures: Vec<&str> = Vec::new();
+    for part in header.split(',') {
+        let mut kv = part.trim().splitn(2, '=');
+        match (kv.next(), kv.next()) {
+            (Some("t"), Some(v)) => timestamp = Some(v),
+            (Some("v1"), Some(v)) => signatures.push(v),
+            _ => {}
+        }
+    }
+    let timestamp = timestamp
+        .ok_or_else(|| AppError::authentication("Missing timestamp in X-Pos-Signature header"))?;
+    if signatures.is_empty() {
+        return Err(AppError::authentication(
+            "No v1 signatures in X-Pos-Signature header",
+        ));
+    }
+
+    let ts: i64 = timestamp
+        .parse()
+        .map_err(|_| AppError::authentication("Bad POS signature timestamp"))?;
+    if (now_unix - ts).abs() > SIGNATURE_TOLERANCE_SECS {
+        return Err(AppError::authentication(
+            "POS webhook timestamp outside tolerance window",
+        ));
+    }
+
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|_| AppError::internal("HMAC key error"))?;
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+    let expected = mac.finalize().into_bytes();
+
+    for sig_hex in signatures {
+        if let Ok(sig_bytes) = hex::decode(sig_hex) {
+            if sig_bytes.len() == expected.len() && sig_bytes.ct_eq(expected.as_slice()).into() {
+                return Ok(());
+            }
+        }
+    }
+    Err(AppError::authentication("POS signature mismatch"))
+}
+
+/// Parse a POS export into tickets.
+///
+/// The first row is a header; columns are matched case-insensitively:
+/// `ticket_id`, `sold_at` (RFC 3339, optional), `plu` (optional),
+/// `item_name`, `quantity` and either `unit_price_cents` or `unit_price`
+/// (`12.50` / `12,50`). Comma- and semicolon-separated files are accepted.
+/// Rows of the same ticket are grouped in order of first appearance.
+pub fn parse_pos_csv(text: &str) -> AppResult<Vec<PosTicket>> {
+    let text = text.trim_start_matches('\u{feff}');
+    let delimiter = match text.lines().next() {
+        Some(header) if header.matches(';').count() > header.matches(',').count() => ';',
+        _ => ',',
+    };
+    let mut rows = split_csv_rows(text, delimiter)?.into_iter();
+
+    let header = rows
+        .next()
+        .ok_or_else(|| AppError::validation("CSV file is empty"))?;
+    let columns: HashMap<String, usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.trim().to_lowercase(), i))
+        .collect();
+    let column = |name: &str| columns.get(name).copied();
+    let ticket_col = column("ticket_id")
+        .ok_or_else(|| AppError::validation("CSV is missing the ticket_id column"))?;
+    let quantity_col = column("quantity")
+        .ok_or_else(|| AppError::validation("CSV is missing the quantity column"))?;
+    let name_col = column("item_name");
+    let plu_col = column("plu");
+    if name_col.is_none() && plu_col.is_none() {
+        return Err(AppError::validation(
+            "CSV needs an item_name or a plu column",
+        ));
+    }
+    let price_cents_col = column("unit_price_cents");
+    let price_col = column("unit_price");
+    if price_cents_col.is_none() && price_col.is_none() {
+        return Err(AppError::validation(
+            "CSV needs a unit_price or unit_price_cents column",
+        ));
+    }
+    let sold_at_col = column("sold_at");
+
+    let mut tickets: Vec<PosTicket> = Vec::new();
+    let mut index_by_id: HashMap<String, usize> = HashMap::new();
+    for (row_index, row) in rows.enumerate() {
+        if row.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let line_no = row_index + 2;
+        let cell = |col: Option<usize>| -> &str {
+            col.and_then(|c| row.get(c)).map(|s| s.trim()).unwrap_or("")
+        };
+        let invalid = |what: &str| AppError::validation(format!("CSV line {}: {}", line_no, what));
+
+        let ticket_id = cell(Some(ticket_col)).to_string();
+        let quantity: u32 = cell(Some(quantity_col))
+            .parse()
+            .map_err(|_| invalid("invalid quantity"))?;
+        let unit_price_cents = match price_cents_col {
+            Some(col) => cell(Some(col))
+                .parse::<i64>()
+                .map_err(|_| invalid("invalid unit_price_cents"))?,
+            None => Decimal::from_str(&cell(price_col).replace(',', "."))
+                .ok()
+                .and_then(|price| (price * Decimal::from(100)).round().to_i64())
+                .ok_or_else(|| invalid("invalid unit_price"))?,
+        };
+        let sold_at = match cell(sold_at_col) {
+            "" => None,
+            value => Some(
+                OffsetDateTime::parse(value, &Rfc3339)
+                    .map_err(|_| invalid("sold_at must be an RFC 3339 timestamp"))?,
+            ),
+        };
+        let plu = Some(cell(plu_col).to_string()).filter(|p| !p.is_empty());
+        let line = PosTicketLine {
+            plu,
+            item_name: cell(name_col).to_string(),
+            quantity,
+            unit_price_cents,
+        };
+
+        match index_by_id.get(&ticket_id) {
+            Some(&i) => tickets[i].lines.push(line),
+            None => {
+                index_by_id.insert(ticket_id.clone(), tickets.len());
+                tickets.push(PosTicket {
+                    ticket_id,
+                    sold_at,
+                    location_id: None,
+                    lines: vec![line],
+                });
+            }
+        }
+    }
+
+    for ticket in &tickets {
+        ticket.validate()?;
+    }
+    Ok(tickets)
+}
+
+/// RFC 4180 splitting: quoted fields may contain delimiters, newlines and
+/// doubled quotes
+fn split_csv_rows(text: &str, delimiter: char) -> AppResult<Vec<Vec<String>>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(AppError::validation("CSV has an unterminated quoted field"));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pos_item_key() {
+        assert_eq!(pos_item_key(Some(" 1042 "), "Burger").unwrap(), "plu:1042");
+        assert_eq!(
+            pos_item_key(None, "  Cheese   Burger ").unwrap(),
+            "name:cheese burger"
+        );
+        assert_eq!(pos_item_key(Some(""), "Fries").unwrap(), "name:fries");
+        assert!(pos_item_key(None, "   ").is_err());
+
+        let line = PosTicketLine {
+            plu: Some("1042".into()),
+            item_name: "Burger".into(),
+            quantity: 1,
+            unit_price_cents: 1500,
+        };
+        assert_eq!(line.lookup_keys(), vec!["plu:1042", "name:burger"]);
+    }
+
+    #[test]
+    fn test_parse_pos_csv() {
+        let csv = "\u{feff}Ticket_ID;sold_at;PLU;item_name;quantity;unit_price\r\n\
+                   T-1;2026-10-01T12:30:00Z;1042;Burger;2;15,50\r\n\
+                   T-2;;;\"Fish; chips\";1;12\r\n\
+                   T-1;2026-10-01T12:30:00Z;;Cola;3;4.00\r\n\
+                   \r\n";
+        let tickets = parse_pos_csv(csv).unwrap();
+        assert_eq!(tickets.len(), 2);
+        assert_eq!(tickets[0].ticket_id, "T-1");
+        assert_eq!(tickets[0].lines.len(), 2);
+        assert_eq!(tickets[0].lines[0].unit_price_cents, 1550);
+        assert_eq!(tickets[0].lines[0].plu.as_deref(), Some("1042"));
+        assert_eq!(tickets[0].lines[1].plu, None);
+        assert!(tickets[0].sold_at.is_some());
+        assert_eq!(tickets[1].lines[0].item_name, "Fish; chips");
+        assert_eq!(tickets[1].sold_at, None);
+
+        let bad_quantity = "ticket_id,item_name,quantity,unit_price_cents\nT-1,Burger,0,100\n";
+        assert!(parse_pos_csv(bad_quantity).is_err());
+        let comped = "ticket_id,item_name,quantity,unit_price_cents\nT-1,Burger,1,0\n";
+        assert_eq!(
+            parse_pos_csv(comped).unwrap()[0].lines[0].unit_price_cents,
+            0
+        );
+        let negative = "ticket_id,item_name,quantity,unit_price_cents\nT-1,Burger,1,-100\n";
+        assert!(parse_pos_csv(negative).is_err());
+        assert!(parse_pos_csv("ticket_id,item_name,quantity\nT-1,Burger,1\n").is_err());
+        assert!(
+            parse_pos_csv("ticket_id,item_name,quantity,unit_price\n\"T-1,Burger,1,2\n").is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_pos_signature() {
+        let secret = "whsec_test";
+        let body = br#"{"tickets":[]}"#;
+        let now = 1_760_000_000;
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}.", now).as_bytes());
+        mac.update(body);
+        let signature = hex::encode(mac.finalize().into_bytes());
+
+        let header = format!("t={},v1=deadbeef,v1={}", now, signature);
+        assert!(verify_pos_signature(secret, body, &header, now + 10).is_ok());
+        // Tampered body, wrong secret, replayed too late
+        assert!(verify_pos_signature(secret, b"{}", &header, now).is_err());
+        assert!(verify_pos_signature("other", body, &header, now).is_err());
+        assert!(verify_pos_signature(secret, body, &header, now + 301).is_err());
+        assert!(verify_pos_signature(secret, body, "v1=abc", now).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::domain::catalog::CatalogIngredientId;
+use crate::shared::{AppError, AppResult};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// What a proposed selling price should achieve
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum PricingTarget {
+    /// Recipe cost as % of the selling price (e.g. 30)
+    FoodCostPercent(f64),
+    /// Profit as % of the selling price (e.g. 70)
+    MarginPercent(f64),
+    /// Profit per portion in cents (e.g. 1500)
+    MarginCents(i64),
+}
+
+impl PricingTarget {
+    pub fn validate(&self) -> AppResult<()> {
+        match *self {
+            Self::FoodCostPercent(p) if !(p > 0.0 && p < 100.0) => Err(AppError::validation(
+                "Target food cost must be between 0 and 100%",
+            )),
+            Self::MarginPercent(p) if !(p > 0.0 && p < 100.0) => Err(AppError::validation(
+                "Target margin must be between 0 and 100%",
+            )),
+            Self::MarginCents(c) if c <= 0 => Err(AppError::validation(
+                "Target margin per portion must be greater than 0",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Exact price (in cents, before endings) that meets the target
+    pub fn price_for_cost(&self, cost_cents: i64) -> i64 {
+        let cost = cost_cents as f64;
+        let price = match *self {
+            Self::FoodCostPercent(p) => cost / (p / 100.0),
+            Self::MarginPercent(p) => cost / (1.0 - p / 100.0),
+            Self::MarginCents(c) => cost + c as f64,
+        };
+        price.ceil() as i64
+    }
+}
+
+/// Psychological price ending, applied by rounding *up* so the target is
+/// never undercut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceEnding {
+    /// Keep the exact price
+    #[default]
+    Exact,
+    /// 24.99
+    Cents99,
+    /// 24.90
+    Cents90,
+    /// 24.50 / 25.00
+    Half,
+    /// 25.00
+    Whole,
+}
+
+impl PriceEnding {
+    pub fn apply(&self, price_cents: i64) -> i64 {
+        let round_up_to = |step: i64| (price_cents + step - 1).div_euclid(step) * step;
+        let with_cents = |ending: i64| {
+            let candidate = price_cents.div_euclid(100) * 100 + ending;
+            if candidate >= price_cents {
+                candidate
+            } else {
+                candidate + 100
+            }
+        };
+        match self {
+            Self::Exact => price_cents,
+            Self::Cents99 => with_cents(99),
+            Self::Cents90 => with_cents(90),
+            Self::Half => round_up_to(50),
+            Self::Whole => round_up_to(100),
+        }
+    }
+}
+
+/// Hypothetical unit price change of one ingredient ("butter +18%")
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct IngredientPriceChange {
+    pub catalog_ingredient_id: CatalogIngredientId,
+    pub change_percent: f64,
+}
+
+impl IngredientPriceChange {
+    /// Multiplier on the unit price (+18% → 1.18)
+    pub fn factor(&self) -> AppResult<Decimal> {
+        if !self.change_percent.is_finite() || self.change_percent <= -100.0 {
+            return Err(AppError::validation(
+                "Ingredient price change must be greater than -100%",
+            ));
+        }
+        Decimal::from_f64(1.0 + self.change_percent / 100.0)
+            .ok_or_else(|| AppError::validation("Invalid ingredient price change"))
+    }
+}
+
+/// Food cost % of a price (0 when the price is 0)
+pub fn food_cost_percent(cost_cents: i64, price_cents: i64) -> f64 {
+    if price_cents > 0 {
+        (cost_cents as f64 / price_cents as f64 * 1000.0).round() / 10.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_targets() {
+        // Cost 6.00 at 30% food cost → 20.00
+        assert_eq!(
+            PricingTarget::FoodCostPercent(30.0).price_for_cost(600),
+            2000
+        );
+        // 75% margin ↔ 25% food cost
+        assert_eq!(PricingTarget::MarginPercent(75.0).price_for_cost(600), 2400);
+        assert_eq!(PricingTarget::MarginCents(1500).price_for_cost(600), 2100);
+
+        assert!(PricingTarget::FoodCostPercent(0.0).validate().is_err());
+        assert!(PricingTarget::MarginPercent(100.0).validate().is_err());
+        assert!(PricingTarget::MarginCents(-5).validate().is_err());
+        assert!(PricingTarget::FoodCostPercent(28.0).validate().is_ok());
+    }
+
+    #[test]
+    fn test_price_endings_round_up() {
+        assert_eq!(PriceEnding::Exact.apply(2413), 2413);
+        assert_eq!(PriceEnding::Cents99.apply(2413), 2499);
+        assert_eq!(PriceEnding::Cents99.apply(2499), 2499);
+        assert_eq!(PriceEnding::Cents90.apply(2495), 2590);
+        assert_eq!(PriceEnding::Half.apply(2413), 2450);
+        assert_eq!(PriceEnding::Half.apply(2451), 2500);
+        assert_eq!(PriceEnding::Whole.apply(2401), 2500);
+        assert_eq!(PriceEnding::Whole.apply(2500), 2500);
+    }
+
+    #[test]
+    fn test_ingredient_price_change_factor() {
+        let change = IngredientPriceChange {
+            catalog_ingredient_id: CatalogIngredientId::new(),
+            change_percent: 18.0,
+        };
+        assert_eq!(change.factor().unwrap(), Decimal::new(118, 2));
+        let wipe_out = IngredientPriceChange {
+            change_percent: -100.0,
+            ..change
+        };
+        assert!(wipe_out.factor().is_err());
+        assert_eq!(food_cost_percent(600, 2000), 30.0);
+        assert_eq!(food_cost_percent(600, 0), 0.0);
+    }
+}
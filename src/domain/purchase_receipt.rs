@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+/// Quantity difference tolerated before a line is flagged (2%)
+pub const QUANTITY_TOLERANCE_PERCENT: f64 = 2.0;
+
+/// Price difference tolerated before a line is flagged (5%)
+pub const PRICE_TOLERANCE_PERCENT: f64 = 5.0;
+
+/// Discrepancy between what was ordered and what was delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptLineFlag {
+    OverDelivery,
+    UnderDelivery,
+    PriceIncrease,
+    PriceDecrease,
+}
+
+impl ReceiptLineFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OverDelivery => "over_delivery",
+            Self::UnderDelivery => "under_delivery",
+            Self::PriceIncrease => "price_increase",
+            Self::PriceDecrease => "price_decrease",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "over_delivery" => Some(Self::OverDelivery),
+            "under_delivery" => Some(Self::UnderDelivery),
+            "price_increase" => Some(Self::PriceIncrease),
+            "price_decrease" => Some(Self::PriceDecrease),
+            _ => None,
+        }
+    }
+}
+
+/// Purchase draft lifecycle after receiving goods
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceivedStatus {
+    PartiallyReceived,
+    Received,
+}
+
+impl ReceivedStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PartiallyReceived => "partially_received",
+            Self::Received => "received",
+        }
+    }
+}
+
+/// Statuses a draft can be received from
+pub const RECEIVABLE_STATUSES: [&str; 3] = ["draft", "sent", "partially_received"];
+
+/// Signed price deviation in percent (`None` without an ordered price)
+pub fn price_deviation_percent(ordered_cents: Option<i64>, actual_cents: i64) -> Option<f64> {
+    match ordered_cents {
+        Some(ordered) if ordered > 0 => {
+            Some((actual_cents - ordered) as f64 / ordered as f64 * 100.0)
+        }
+        _ => None,
+    }
+}
+
+/// Compare a delivered line with the outstanding order quantity and price
+pub fn evaluate_line(
+    expected_quantity: f64,
+    received_quantity: f64,
+    ordered_price_cents: Option<i64>,
+    actual_price_cents: i64,
+) -> Vec<ReceiptLineFlag> {
+    let mut flags = Vec::new();
+
+    let tolerance = expected_quantity.max(0.0) * QUANTITY_TOLERANCE_PERCENT / 100.0;
+    if received_quantity > expected_quantity + tolerance {
+        flags.push(ReceiptLineFlag::OverDelivery);
+    } else if received_quantity < expected_quantity - tolerance {
+        flags.push(ReceiptLineFlag::UnderDelivery);
+    }
+
+    if let Some(deviation) = price_deviation_percent(ordered_price_cents, actual_price_cents) {
+        if deviation > PRICE_TOLERANCE_PERCENT {
+            flags.push(ReceiptLineFlag::PriceIncrease);
+        } else if deviation < -PRICE_TOLERANCE_PERCENT {
+            flags.push(ReceiptLineFlag::PriceDecrease);
+        }
+    }
+
+    flags
+}
+
+/// Draft status from (ordered, received so far) per item
+pub fn received_status(items: &[(f64, f64)]) -> ReceivedStatus {
+    let complete = items.iter().all(|(ordered, received)| {
+        let tolerance = ordered.max(0.0) * QUANTITY_TOLERANCE_PERCENT / 100.0;
+        *received >= ordered - tolerance
+    });
+    if complete {
+        ReceivedStatus::Received
+    } else {
+        ReceivedStatus::PartiallyReceived
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_line_flags() {
+        // Within tolerance on both quantity and price
+        assert!(evaluate_line(10.0, 10.1, Some(1000), 1040).is_empty());
+
+        assert_eq!(
+            evaluate_line(10.0, 12.0, Some(1000), 1100),
+            vec![
+                ReceiptLineFlag::OverDelivery,
+                ReceiptLineFlag::PriceIncrease
+            ]
+        );
+        assert_eq!(
+            evaluate_line(10.0, 0.0, Some(1000), 900),
+            vec![
+                ReceiptLineFlag::UnderDelivery,
+                ReceiptLineFlag::PriceDecrease
+            ]
+        );
+
+        // Unknown ordered price → only quantity is checked
+        assert!(evaluate_line(5.0, 5.0, None, 9999).is_empty());
+    }
+
+    #[test]
+    fn test_received_status() {
+        assert_eq!(
+            received_status(&[(10.0, 10.0), (2.0, 1.99)]),
+            ReceivedStatus::Received
+        );
+        assert_eq!(
+            received_status(&[(10.0, 10.0), (2.0, 1.0)]),
+            ReceivedStatus::PartiallyReceived
+        );
+        assert_eq!(
+            ReceiptLineFlag::parse("price_increase"),
+            Some(ReceiptLineFlag::PriceIncrease)
+        );
+    }
+}
@@ -3,7 +3,9 @@ use crate::domain::{
     inventory::{Money, Quantity},
 };
 use crate::shared::{AppError, AppResult, TenantId, UserId};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
@@ -309,12 +311,144 @@ impl Recipe {
     }
 }
 
+/// Maximum nesting of component recipes (dish → sauce → stock → ...)
+pub const MAX_COMPONENT_DEPTH: usize = 5;
+
+/// Walk the component graph from `root` depth-first.
+/// Fails with a validation error on cycles, on nesting deeper than
+/// `MAX_COMPONENT_DEPTH` and on components missing from `recipes`.
+pub fn validate_component_graph(
+    root: RecipeId,
+    recipes: &HashMap<RecipeId, Recipe>,
+) -> AppResult<()> {
+    fn visit(
+        id: RecipeId,
+        recipes: &HashMap<RecipeId, Recipe>,
+        path: &mut Vec<RecipeId>,
+    ) -> AppResult<()> {
+        if path.contains(&id) {
+            return Err(AppError::validation(format!(
+                "Component cycle detected: recipe {} includes itself",
+                id.as_uuid()
+            )));
+        }
+        // path.len() == number of component levels above `id`
+        if path.len() > MAX_COMPONENT_DEPTH {
+            return Err(AppError::validation(format!(
+                "Component nesting is deeper than {} levels",
+                MAX_COMPONENT_DEPTH
+            )));
+        }
+        let recipe = recipes.get(&id).ok_or_else(|| {
+            AppError::validation(format!("Component recipe {} not found", id.as_uuid()))
+        })?;
+
+        path.push(id);
+        for component in recipe.components() {
+            visit(component.component_recipe_id(), recipes, path)?;
+        }
+        path.pop();
+        Ok(())
+    }
+
+    visit(root, recipes, &mut Vec::new())
+}
+
+/// Expand a recipe into raw catalog ingredients for one full batch.
+/// Components contribute their own ingredients scaled by the batch fraction,
+/// recursively. Result is aggregated per ingredient (first-seen order).
+pub fn flatten_ingredients(
+    root: RecipeId,
+    recipes: &HashMap<RecipeId, Recipe>,
+) -> AppResult<Vec<(CatalogIngredientId, Decimal)>> {
+    validate_component_graph(root, recipes)?;
+
+    fn collect(
+        id: RecipeId,
+        factor: Decimal,
+        recipes: &HashMap<RecipeId, Recipe>,
+        out: &mut Vec<(CatalogIngredientId, Decimal)>,
+    ) {
+        let Some(recipe) = recipes.get(&id) else {
+            return;
+        };
+        for ingredient in recipe.ingredients() {
+            let qty = ingredient.quantity().decimal() * factor;
+            match out
+                .iter_mut()
+                .find(|(cid, _)| *cid == ingredient.catalog_ingredient_id())
+            {
+                Some((_, total)) => *total += qty,
+                None => out.push((ingredient.catalog_ingredient_id(), qty)),
+            }
+        }
+        for component in recipe.components() {
+            collect(
+                component.component_recipe_id(),
+                factor * component.quantity(),
+                recipes,
+                out,
+            );
+        }
+    }
+
+    let mut out = Vec::new();
+    collect(root, Decimal::ONE, recipes, &mut out);
+    Ok(out)
+}
+
+/// Gross (as-purchased) quantity needed to end up with `net` usable quantity
+/// at the given edible yield, e.g. 1 kg fillet at 50% yield → 2 kg whole fish.
+/// Yields outside (0, 100] are treated as 100%.
+pub fn gross_quantity(net: Decimal, yield_percent: Decimal) -> Decimal {
+    if yield_percent <= Decimal::ZERO || yield_percent >= Decimal::ONE_HUNDRED {
+        return net;
+    }
+    net * Decimal::ONE_HUNDRED / yield_percent
+}
+
+/// Convert net (usable) ingredient quantities into gross (as-purchased) ones.
+/// Ingredients without a known yield are taken at 100%.
+pub fn apply_yields(
+    items: Vec<(CatalogIngredientId, Decimal)>,
+    yields: &HashMap<CatalogIngredientId, Decimal>,
+) -> Vec<(CatalogIngredientId, Decimal)> {
+    items
+        .into_iter()
+        .map(|(id, net)| match yields.get(&id) {
+            Some(yield_percent) => (id, gross_quantity(net, *yield_percent)),
+            None => (id, net),
+        })
+        .collect()
+}
+
+/// As-purchased ingredients of one serving of `root`: the batch flattened,
+/// trim loss added back, then divided by the root recipe's servings — the
+/// same basis as `RecipeCost::cost_per_serving`.
+pub fn portion_ingredients(
+    root: RecipeId,
+    recipes: &HashMap<RecipeId, Recipe>,
+    yields: &HashMap<CatalogIngredientId, Decimal>,
+) -> AppResult<Vec<(CatalogIngredientId, Decimal)>> {
+    let servings = recipes
+        .get(&root)
+        .map(|r| r.servings().count())
+        .unwrap_or(1);
+    let batch = apply_yields(flatten_ingredients(root, recipes)?, yields);
+    Ok(super::dish_label::per_portion(batch, servings))
+}
+
 /// Cost breakdown for a single ingredient in recipe
 #[derive(Debug, Clone, Serialize)]
 pub struct IngredientCost {
     pub ingredient_id: CatalogIngredientId,
     pub ingredient_name: String,
+    /// Net (usable) quantity as written in the recipe
     pub quantity: Quantity,
+    /// Edible yield % applied (100 = no trim loss)
+    pub yield_percent: Decimal,
+    /// As-purchased quantity the cost is based on
+    pub gross_quantity: Quantity,
     pub unit_price: Money,
     pub total_cost: Money,
 }
@@ -408,6 +542,8 @@ mod tests {
             ingredient_id: CatalogIngredientId::from_uuid(Uuid::new_v4()),
             ingredient_name: "Milk".to_string(),
             quantity: Quantity::new(1.0).unwrap(),
+            yield_percent: Decimal::ONE_HUNDRED,
+            gross_quantity: Quantity::new(1.0).unwrap(),
             unit_price: Money::from_cents(450).unwrap(),
             total_cost: Money::from_cents(450).unwrap(),
         };
@@ -416,6 +552,8 @@ mod tests {
             ingredient_id: CatalogIngredientId::from_uuid(Uuid::new_v4()),
             ingredient_name: "Eggs".to_string(),
             quantity: Quantity::new(3.0).unwrap(),
+            yield_percent: Decimal::ONE_HUNDRED,
+            gross_quantity: Quantity::new(3.0).unwrap(),
             unit_price: Money::from_cents(50).unwrap(),
             total_cost: Money::from_cents(150).unwrap(),
         };
@@ -442,6 +580,8 @@ mod tests {
                 ingredient_id: CatalogIngredientId::from_uuid(Uuid::new_v4()),
                 ingredient_name: "Test".to_string(),
                 quantity: Quantity::new(1.0).unwrap(),
+                yield_percent: Decimal::ONE_HUNDRED,
+                gross_quantity: Quantity::new(1.0).unwrap(),
                 unit_price: Money::from_cents(300).unwrap(),
                 total_cost: Money::from_cents(300).unwrap(),
             }],
@@ -455,4 +595,158 @@ mod tests {
         let food_cost_pct = recipe_cost.food_cost_percentage(selling_price);
         assert!((food_cost_pct - 30.0).abs() < 0.01);
     }
+
+    fn test_recipe(ingredients: Vec<RecipeIngredient>, components: Vec<RecipeComponent>) -> Recipe {
+        Recipe::new(
+            UserId::new(),
+            TenantId::new(),
+            RecipeName::new("Test").unwrap(),
+            RecipeType::Preparation,
+            Servings::new(1).unwrap(),
+            ingredients,
+            components,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_flatten_nested_components() {
+        let flour = CatalogIngredientId::from_uuid(Uuid::new_v4());
+        let bones = CatalogIngredientId::from_uuid(Uuid::new_v4());
+
+        let stock = test_recipe(
+            vec![RecipeIngredient::new(bones, Quantity::new(2.0).unwrap())],
+            vec![],
+        );
+        let sauce = test_recipe(
+            vec![RecipeIngredient::new(flour, Quantity::new(0.1).unwrap())],
+            vec![RecipeComponent::new(stock.id(), Decimal::new(5, 1)).unwrap()],
+        );
+        let dish = test_recipe(
+            vec![RecipeIngredient::new(flour, Quantity::new(0.2).unwrap())],
+            vec![RecipeComponent::new(sauce.id(), Decimal::new(25, 2)).unwrap()],
+        );
+
+        let dish_id = dish.id();
+        let recipes: HashMap<RecipeId, Recipe> = [stock, sauce, dish]
+            .into_iter()
+            .map(|r| (r.id(), r))
+            .collect();
+
+        let flat = flatten_ingredients(dish_id, &recipes).unwrap();
+        assert_eq!(flat.len(), 2);
+        // flour: 0.2 + 0.25 * 0.1 = 0.225
+        assert_eq!(flat[0], (flour, Decimal::new(225, 3)));
+        // bones: 0.25 * 0.5 * 2 = 0.25
+        assert_eq!(flat[1].1.normalize(), Decimal::new(25, 2));
+    }
+
+    #[test]
+    fn test_component_cycle_detected() {
+        let ing = CatalogIngredientId::from_uuid(Uuid::new_v4());
+        let mut a = test_recipe(
+            vec![RecipeIngredient::new(ing, Quantity::new(1.0).unwrap())],
+            vec![],
+        );
+        let b = test_recipe(
+            vec![],
+            vec![RecipeComponent::new(a.id(), Decimal::ONE).unwrap()],
+        );
+        a.components = vec![RecipeComponent::new(b.id(), Decimal::ONE).unwrap()];
+
+        let a_id = a.id();
+        let recipes: HashMap<RecipeId, Recipe> = [a, b].into_iter().map(|r| (r.id(), r)).collect();
+
+        let err = flatten_ingredients(a_id, &recipes).unwrap_err();
+        assert!(matches!(err, AppError::Validation(msg) if msg.contains("cycle")));
+    }
+
+    #[test]
+    fn test_component_depth_limit() {
+        let ing = CatalogIngredientId::from_uuid(Uuid::new_v4());
+        let mut recipes = HashMap::new();
+        let mut child = test_recipe(
+            vec![RecipeIngredient::new(ing, Quantity::new(1.0).unwrap())],
+            vec![],
+        );
+
+        // Chain of MAX_COMPONENT_DEPTH + 1 component levels
+        for _ in 0..=MAX_COMPONENT_DEPTH {
+            let parent = test_recipe(
+                vec![],
+                vec![RecipeComponent::new(child.id(), Decimal::ONE).unwrap()],
+            );
+            recipes.insert(child.id(), child);
+            child = parent;
+        }
+        let root_id = child.id();
+        recipes.insert(root_id, child);
+
+        let err = validate_component_graph(root_id, &recipes).unwrap_err();
+        assert!(matches!(err, AppError::Validation(msg) if msg.contains("deeper")));
+    }
+
+    #[test]
+    fn test_gross_quantity_from_yield() {
+        // 1 kg fillet at 50% yield → 2 kg whole fish
+        assert_eq!(
+            gross_quantity(Decimal::ONE, Decimal::new(50, 0)),
+            Decimal::new(2, 0)
+        );
+        // 0.3 kg peeled potato at 80% → 0.375 kg
+        assert_eq!(
+            gross_quantity(Decimal::new(3, 1), Decimal::new(80, 0)),
+            Decimal::new(375, 3)
+        );
+        // Invalid yields fall back to 100%
+        assert_eq!(gross_quantity(Decimal::ONE, Decimal::ZERO), Decimal::ONE);
+        assert_eq!(
+            gross_quantity(Decimal::ONE, Decimal::new(120, 0)),
+            Decimal::ONE
+        );
+    }
+
+    #[test]
+    fn test_apply_yields_keeps_unknown_at_full_weight() {
+        let salmon = CatalogIngredientId::from_uuid(Uuid::new_v4());
+        let salt = CatalogIngredientId::from_uuid(Uuid::new_v4());
+        let yields = HashMap::from([(salmon, Decimal::new(60, 0))]);
+
+        let gross = apply_yields(
+            vec![(salmon, Decimal::new(3, 1)), (salt, Decimal::new(1, 2))],
+            &yields,
+        );
+        assert_eq!(gross[0], (salmon, Decimal::new(5, 1)));
+        assert_eq!(gross[1], (salt, Decimal::new(1, 2)));
+    }
+
+    #[test]
+    fn test_portion_ingredients_divides_batch_by_servings() {
+        let salmon = CatalogIngredientId::from_uuid(Uuid::new_v4());
+        let bones = CatalogIngredientId::from_uuid(Uuid::new_v4());
+
+        let stock = test_recipe(
+            vec![RecipeIngredient::new(bones, Quantity::new(2.0).unwrap())],
+            vec![],
+        );
+        // Batch of 4 servings: 1.2 kg fillet (60% yield) + half the stock
+        let mut dish = test_recipe(
+            vec![RecipeIngredient::new(salmon, Quantity::new(1.2).unwrap())],
+            vec![RecipeComponent::new(stock.id(), Decimal::new(5, 1)).unwrap()],
+        );
+        dish.servings = Servings::new(4).unwrap();
+
+        let dish_id = dish.id();
+        let recipes: HashMap<RecipeId, Recipe> =
+            [stock, dish].into_iter().map(|r| (r.id(), r)).collect();
+        let yields = HashMap::from([(salmon, Decimal::new(60, 0))]);
+
+        let portion = portion_ingredients(dish_id, &recipes, &yields).unwrap();
+        // salmon: 1.2 / 0.6 = 2 kg gross for the batch → 0.5 kg a serving
+        assert_eq!(portion[0].0, salmon);
+        assert_eq!(portion[0].1.normalize(), Decimal::new(5, 1));
+        // bones: 0.5 * 2 = 1 kg for the batch → 0.25 kg a serving
+        assert_eq!(portion[1].1.normalize(), Decimal::new(25, 2));
+    }
 }
@@ -0,0 +1,137 @@
+use rust_decimal::Decimal;
+
+/// Days of consumption to cover above the reorder point when no par level is set
+pub const DEFAULT_COVER_DAYS: i64 = 7;
+
+/// Replenishment settings of one ingredient
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReorderPolicy {
+    pub par_level: Option<Decimal>,
+    pub reorder_point: Option<Decimal>,
+    pub pack_size: Option<Decimal>,
+    /// Catalog low-stock threshold, used when no reorder point is set
+    pub min_stock_threshold: Decimal,
+}
+
+/// Current stock position of one ingredient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StockPosition {
+    pub on_hand: Decimal,
+    /// Quantity on open (draft / sent) purchase drafts
+    pub on_order: Decimal,
+    pub avg_daily_consumption: Decimal,
+    pub lead_time_days: i32,
+}
+
+impl StockPosition {
+    /// Stock expected when the next delivery arrives
+    pub fn projected_at_delivery(&self) -> Decimal {
+        let consumed = self.avg_daily_consumption * Decimal::from(self.lead_time_days.max(0));
+        (self.on_hand + self.on_order - consumed).max(Decimal::ZERO)
+    }
+}
+
+impl ReorderPolicy {
+    pub fn effective_reorder_point(&self) -> Decimal {
+        self.reorder_point.unwrap_or(self.min_stock_threshold)
+    }
+
+    /// Par level, or reorder point + a week of consumption when not set
+    pub fn effective_par_level(&self, avg_daily_consumption: Decimal) -> Decimal {
+        self.par_level.unwrap_or_else(|| {
+            self.effective_reorder_point()
+                + avg_daily_consumption * Decimal::from(DEFAULT_COVER_DAYS)
+        })
+    }
+
+    /// Quantity to order, rounded up to the pack size.
+    /// `None` when the ingredient is untracked or the projection stays above the reorder point.
+    pub fn reorder_quantity(&self, position: &StockPosition) -> Option<Decimal> {
+        let tracked = self.par_level.is_some()
+            || self.reorder_point.is_some()
+            || self.min_stock_threshold > Decimal::ZERO;
+        if !tracked {
+            return None;
+        }
+
+        let projected = position.projected_at_delivery();
+        if projected > self.effective_reorder_point() {
+            return None;
+        }
+
+        let need = self.effective_par_level(position.avg_daily_consumption) - projected;
+        if need <= Decimal::ZERO {
+            return None;
+        }
+        Some(round_up_to_pack(need, self.pack_size))
+    }
+}
+
+/// Round quantity up to a whole number of packs (unchanged without pack size)
+pub fn round_up_to_pack(quantity: Decimal, pack_size: Option<Decimal>) -> Decimal {
+    match pack_size {
+        Some(pack) if pack > Decimal::ZERO => (quantity / pack).ceil() * pack,
+        _ => quantity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_round_up_to_pack() {
+        assert_eq!(round_up_to_pack(dec("7.2"), Some(dec("2.5"))), dec("7.5"));
+        assert_eq!(round_up_to_pack(dec("5"), Some(dec("2.5"))), dec("5"));
+        assert_eq!(round_up_to_pack(dec("3.3"), None), dec("3.3"));
+    }
+
+    #[test]
+    fn test_reorder_quantity_projects_lead_time_consumption() {
+        let policy = ReorderPolicy {
+            par_level: Some(dec("20")),
+            reorder_point: Some(dec("5")),
+            pack_size: Some(dec("5")),
+            min_stock_threshold: Decimal::ZERO,
+        };
+
+        // 10 on hand, 2/day, 3 days lead time → 4 left at delivery → order 16 → 20 (packs of 5)
+        let position = StockPosition {
+            on_hand: dec("10"),
+            on_order: Decimal::ZERO,
+            avg_daily_consumption: dec("2"),
+            lead_time_days: 3,
+        };
+        assert_eq!(policy.reorder_quantity(&position), Some(dec("20")));
+
+        // Already on order → projection above reorder point → nothing to do
+        let covered = StockPosition {
+            on_order: dec("10"),
+            ..position
+        };
+        assert_eq!(policy.reorder_quantity(&covered), None);
+    }
+
+    #[test]
+    fn test_reorder_quantity_fallbacks() {
+        // Untracked ingredient
+        let position = StockPosition {
+            on_hand: Decimal::ZERO,
+            on_order: Decimal::ZERO,
+            avg_daily_consumption: dec("1"),
+            lead_time_days: 1,
+        };
+        assert_eq!(ReorderPolicy::default().reorder_quantity(&position), None);
+
+        // Catalog threshold 2 → par = 2 + 7 days × 1 = 9
+        let policy = ReorderPolicy {
+            min_stock_threshold: dec("2"),
+            ..Default::default()
+        };
+        assert_eq!(policy.reorder_quantity(&position), Some(dec("9")));
+    }
+}
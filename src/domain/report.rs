@@ -1,4 +1,9 @@
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use time::OffsetDateTime;
+use uuid::Uuid;
 
 /// Executive summary for a restaurant tenant.
 /// One endpoint, one glance — the owner sees everything.
@@ -116,6 +121,321 @@ pub struct EngineeringAgg {
     pub dogs: u32,
 }
 
+// ── Usage variance: theoretical (recipes × sales) vs actual (stock flow) ──
+
+/// How many of the biggest variances are pulled out as highlights
+pub const USAGE_VARIANCE_HIGHLIGHTS: usize = 5;
+
+/// Book stock and recipe usage of one ingredient over a report period
+#[derive(Debug, Clone)]
+pub struct IngredientUsageInput {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub category_name: String,
+    pub unit: String,
+    pub opening: Decimal,
+    pub receipts: Decimal,
+    pub closing: Decimal,
+    /// Logged waste + auto expiry in the period (already part of actual usage)
+    pub recorded_loss: Decimal,
+    /// Recipe quantities × dishes sold
+    pub theoretical: Decimal,
+    pub unit_price_cents: i64,
+}
+
+impl IngredientUsageInput {
+    /// Opening stock + receipts − closing stock
+    pub fn actual(&self) -> Decimal {
+        self.opening + self.receipts - self.closing
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IngredientUsageVariance {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub category_name: String,
+    pub unit: String,
+    pub opening_quantity: f64,
+    pub receipts_quantity: f64,
+    pub closing_quantity: f64,
+    pub theoretical_quantity: f64,
+    pub actual_quantity: f64,
+    pub recorded_loss_quantity: f64,
+    /// actual − theoretical (positive = more used than recipes explain)
+    pub variance_quantity: f64,
+    /// variance − recorded loss: over-portioning, theft, unlogged waste
+    pub unexplained_quantity: f64,
+    /// Relative to theoretical usage; `None` when nothing was expected
+    pub variance_percent: Option<f64>,
+    pub theoretical_cost_cents: i64,
+    pub actual_cost_cents: i64,
+    pub variance_cost_cents: i64,
+    pub unexplained_cost_cents: i64,
+}
+
+/// Recipe usage of one sold dish over a report period
+#[derive(Debug, Clone)]
+pub struct DishUsageInput {
+    pub dish_id: Uuid,
+    pub dish_name: String,
+    pub portions_sold: i64,
+    /// Ingredient id → recipe quantity × portions sold (modifiers applied)
+    pub theoretical: Vec<(Uuid, Decimal)>,
+}
+
+/// Theoretical vs actual cost of one dish. Actual usage of an ingredient is
+/// shared between the dishes in proportion to their theoretical usage of it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DishUsageVariance {
+    pub dish_id: Uuid,
+    pub dish_name: String,
+    pub portions_sold: i64,
+    pub theoretical_cost_cents: i64,
+    pub actual_cost_cents: i64,
+    pub variance_cost_cents: i64,
+    pub variance_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryUsageVariance {
+    pub category_name: String,
+    pub ingredients: u32,
+    pub theoretical_cost_cents: i64,
+    pub actual_cost_cents: i64,
+    pub variance_cost_cents: i64,
+    pub unexplained_cost_cents: i64,
+    pub variance_percent: Option<f64>,
+}
+
+/// Theoretical vs actual food cost over a period
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageVarianceReport {
+    #[serde(with = "time::serde::rfc3339")]
+    pub period_start: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub period_end: OffsetDateTime,
+    pub dishes_sold: i64,
+    /// Sold dishes whose recipe could not be resolved (deleted dish/recipe)
+    pub unresolved_dishes: u32,
+    pub theoretical_cost_cents: i64,
+    pub actual_cost_cents: i64,
+    pub variance_cost_cents: i64,
+    pub unexplained_cost_cents: i64,
+    pub variance_percent: Option<f64>,
+    /// Sorted by absolute unexplained cost, biggest first
+    pub ingredients: Vec<IngredientUsageVariance>,
+    /// Grouped by catalog category, biggest absolute unexplained cost first
+    pub categories: Vec<CategoryUsageVariance>,
+    /// Per sold dish, biggest absolute variance cost first
+    pub dishes: Vec<DishUsageVariance>,
+    /// Actual cost of ingredients no sold dish uses, left out of `dishes`
+    pub unattributed_cost_cents: i64,
+    /// Largest unexplained overuse (over-portioning / theft suspects)
+    pub top_overuse: Vec<IngredientUsageVariance>,
+    /// Largest unexplained underuse (under-portioning, unrecorded receipts)
+    pub top_underuse: Vec<IngredientUsageVariance>,
+}
+
+fn cost_cents(quantity: Decimal, unit_price_cents: i64) -> i64 {
+    (quantity * Decimal::from(unit_price_cents))
+        .round()
+        .to_i64()
+        .unwrap_or(0)
+}
+
+fn variance_percent(variance_cents: i64, theoretical_cents: i64) -> Option<f64> {
+    (theoretical_cents != 0)
+        .then(|| (variance_cents as f64 / theoretical_cents as f64 * 1000.0).round() / 10.0)
+}
+
+impl IngredientUsageVariance {
+    pub fn from_input(input: &IngredientUsageInput) -> Self {
+        let actual = input.actual();
+        let variance = actual - input.theoretical;
+        let unexplained = variance - input.recorded_loss;
+        let price = input.unit_price_cents;
+
+        let variance_percent = (!input.theoretical.is_zero()).then(|| {
+            (variance / input.theoretical * Decimal::from(100))
+                .round_dp(1)
+                .to_f64()
+                .unwrap_or(0.0)
+        });
+
+        Self {
+            ingredient_id: input.ingredient_id,
+            ingredient_name: input.ingredient_name.clone(),
+            category_name: input.category_name.clone(),
+            unit: input.unit.clone(),
+            opening_quantity: input.opening.to_f64().unwrap_or(0.0),
+            receipts_quantity: input.receipts.to_f64().unwrap_or(0.0),
+            closing_quantity: input.closing.to_f64().unwrap_or(0.0),
+            theoretical_quantity: input.theoretical.to_f64().unwrap_or(0.0),
+            actual_quantity: actual.to_f64().unwrap_or(0.0),
+            recorded_loss_quantity: input.recorded_loss.to_f64().unwrap_or(0.0),
+            variance_quantity: variance.to_f64().unwrap_or(0.0),
+            unexplained_quantity: unexplained.to_f64().unwrap_or(0.0),
+            variance_percent,
+            theoretical_cost_cents: cost_cents(input.theoretical, price),
+            actual_cost_cents: cost_cents(actual, price),
+            variance_cost_cents: cost_cents(variance, price),
+            unexplained_cost_cents: cost_cents(unexplained, price),
+        }
+    }
+}
+
+impl DishUsageVariance {
+    /// `ingredients`: ingredient id → its period input
+    fn from_input(
+        input: &DishUsageInput,
+        ingredients: &HashMap<Uuid, &IngredientUsageInput>,
+    ) -> Self {
+        let mut theoretical_cost_cents = 0;
+        let mut actual_cost_cents = 0;
+        for (ingredient_id, quantity) in &input.theoretical {
+            let Some(ingredient) = ingredients.get(ingredient_id) else {
+                continue;
+            };
+            if ingredient.theoretical.is_zero() {
+                continue;
+            }
+            let share = *quantity / ingredient.theoretical;
+            theoretical_cost_cents += cost_cents(*quantity, ingredient.unit_price_cents);
+            actual_cost_cents +=
+                cost_cents(ingredient.actual() * share, ingredient.unit_price_cents);
+        }
+        let variance_cost_cents = actual_cost_cents - theoretical_cost_cents;
+
+        Self {
+            dish_id: input.dish_id,
+            dish_name: input.dish_name.clone(),
+            portions_sold: input.portions_sold,
+            theoretical_cost_cents,
+            actual_cost_cents,
+            variance_cost_cents,
+            variance_percent: variance_percent(variance_cost_cents, theoretical_cost_cents),
+        }
+    }
+}
+
+impl UsageVarianceReport {
+    /// Build the report from per-ingredient and per-dish inputs.
+    /// Ingredients neither used nor expected are left out.
+    pub fn build(
+        period_start: OffsetDateTime,
+        period_end: OffsetDateTime,
+        dishes_sold: i64,
+        unresolved_dishes: u32,
+        inputs: &[IngredientUsageInput],
+        dish_inputs: &[DishUsageInput],
+    ) -> Self {
+        let mut ingredients: Vec<IngredientUsageVariance> = inputs
+            .iter()
+            .filter(|input| !input.actual().is_zero() || !input.theoretical.is_zero())
+            .map(IngredientUsageVariance::from_input)
+            .collect();
+        ingredients.sort_by(|a, b| {
+            b.unexplained_cost_cents
+                .abs()
+                .cmp(&a.unexplained_cost_cents.abs())
+                .then_with(|| a.ingredient_name.cmp(&b.ingredient_name))
+        });
+
+        let mut by_category: BTreeMap<&str, CategoryUsageVariance> = BTreeMap::new();
+        for item in &ingredients {
+            let entry = by_category
+                .entry(item.category_name.as_str())
+                .or_insert_with(|| CategoryUsageVariance {
+                    category_name: item.category_name.clone(),
+                    ingredients: 0,
+                    theoretical_cost_cents: 0,
+                    actual_cost_cents: 0,
+                    variance_cost_cents: 0,
+                    unexplained_cost_cents: 0,
+                    variance_percent: None,
+                });
+            entry.ingredients += 1;
+            entry.theoretical_cost_cents += item.theoretical_cost_cents;
+            entry.actual_cost_cents += item.actual_cost_cents;
+            entry.variance_cost_cents += item.variance_cost_cents;
+            entry.unexplained_cost_cents += item.unexplained_cost_cents;
+        }
+        let mut categories: Vec<CategoryUsageVariance> = by_category
+            .into_values()
+            .map(|mut category| {
+                category.variance_percent = variance_percent(
+                    category.variance_cost_cents,
+                    category.theoretical_cost_cents,
+                );
+                category
+            })
+            .collect();
+        categories.sort_by(|a, b| {
+            b.unexplained_cost_cents
+                .abs()
+                .cmp(&a.unexplained_cost_cents.abs())
+        });
+
+        let by_ingredient: HashMap<Uuid, &IngredientUsageInput> = inputs
+            .iter()
+            .map(|input| (input.ingredient_id, input))
+            .collect();
+        let mut dishes: Vec<DishUsageVariance> = dish_inputs
+            .iter()
+            .map(|input| DishUsageVariance::from_input(input, &by_ingredient))
+            .collect();
+        dishes.sort_by(|a, b| {
+            b.variance_cost_cents
+                .abs()
+                .cmp(&a.variance_cost_cents.abs())
+                .then_with(|| a.dish_name.cmp(&b.dish_name))
+        });
+        let unattributed_cost_cents = inputs
+            .iter()
+            .filter(|input| input.theoretical.is_zero())
+            .map(|input| cost_cents(input.actual(), input.unit_price_cents))
+            .sum();
+
+        let theoretical_cost_cents = ingredients.iter().map(|i| i.theoretical_cost_cents).sum();
+        let actual_cost_cents = ingredients.iter().map(|i| i.actual_cost_cents).sum();
+        let variance_cost_cents = ingredients.iter().map(|i| i.variance_cost_cents).sum();
+        let unexplained_cost_cents = ingredients.iter().map(|i| i.unexplained_cost_cents).sum();
+
+        // `ingredients` is sorted by |unexplained|, so the filters keep that order
+        let top_overuse = ingredients
+            .iter()
+            .filter(|i| i.unexplained_cost_cents > 0)
+            .take(USAGE_VARIANCE_HIGHLIGHTS)
+            .cloned()
+            .collect();
+        let top_underuse = ingredients
+            .iter()
+            .filter(|i| i.unexplained_cost_cents < 0)
+            .take(USAGE_VARIANCE_HIGHLIGHTS)
+            .cloned()
+            .collect();
+
+        Self {
+            period_start,
+            period_end,
+            dishes_sold,
+            unresolved_dishes,
+            theoretical_cost_cents,
+            actual_cost_cents,
+            variance_cost_cents,
+            unexplained_cost_cents,
+            variance_percent: variance_percent(variance_cost_cents, theoretical_cost_cents),
+            ingredients,
+            categories,
+            dishes,
+            unattributed_cost_cents,
+            top_overuse,
+            top_underuse,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,4 +509,140 @@ mod tests {
         assert_eq!(summary.total_orders, 0);
         assert!(summary.best_dish.is_none());
     }
+
+    #[allow(clippy::too_many_arguments)]
+    fn usage_input(
+        name: &str,
+        category: &str,
+        opening: i64,
+        receipts: i64,
+        closing: i64,
+        recorded_loss: i64,
+        theoretical: i64,
+        unit_price_cents: i64,
+    ) -> IngredientUsageInput {
+        IngredientUsageInput {
+            ingredient_id: Uuid::new_v4(),
+            ingredient_name: name.to_string(),
+            category_name: category.to_string(),
+            unit: "kilogram".to_string(),
+            opening: Decimal::from(opening),
+            receipts: Decimal::from(receipts),
+            closing: Decimal::from(closing),
+            recorded_loss: Decimal::from(recorded_loss),
+            theoretical: Decimal::from(theoretical),
+            unit_price_cents,
+        }
+    }
+
+    #[test]
+    fn test_usage_variance_per_ingredient() {
+        // 10 + 20 − 8 = 22 used, recipes explain 18, 1 logged as waste
+        let input = usage_input("Salmon", "Fish & Seafood", 10, 20, 8, 1, 18, 5_000);
+        let item = IngredientUsageVariance::from_input(&input);
+
+        assert_eq!(item.actual_quantity, 22.0);
+        assert_eq!(item.variance_quantity, 4.0);
+        assert_eq!(item.unexplained_quantity, 3.0);
+        assert_eq!(item.theoretical_cost_cents, 90_000);
+        assert_eq!(item.actual_cost_cents, 110_000);
+        assert_eq!(item.variance_cost_cents, 20_000);
+        assert_eq!(item.unexplained_cost_cents, 15_000);
+        assert_eq!(item.variance_percent, Some(22.2));
+    }
+
+    #[test]
+    fn test_usage_variance_per_dish() {
+        let start = OffsetDateTime::UNIX_EPOCH;
+        let end = start + time::Duration::days(7);
+        let salmon = usage_input("Salmon", "Fish & Seafood", 10, 20, 8, 0, 18, 5_000); // 22 used
+        let rice = usage_input("Rice", "Grains & Pasta", 50, 0, 40, 0, 12, 500); // 10 used
+        let vodka = usage_input("Vodka", "Beverages", 2, 0, 0, 0, 0, 3_000); // nothing sold
+        let dish = |name: &str, portions: i64, theoretical: Vec<(Uuid, i64)>| DishUsageInput {
+            dish_id: Uuid::new_v4(),
+            dish_name: name.to_string(),
+            portions_sold: portions,
+            theoretical: theoretical
+                .into_iter()
+                .map(|(id, quantity)| (id, Decimal::from(quantity)))
+                .collect(),
+        };
+        let dishes = vec![
+            dish("Sashimi", 6, vec![(salmon.ingredient_id, 6)]),
+            dish(
+                "Poke",
+                12,
+                vec![(salmon.ingredient_id, 12), (rice.ingredient_id, 12)],
+            ),
+        ];
+
+        let report = UsageVarianceReport::build(
+            start,
+            end,
+            18,
+            0,
+            &[salmon.clone(), rice.clone(), vodka],
+            &dishes,
+        );
+
+        // Poke gets 12/18 of the salmon used and all of the rice
+        let poke = &report.dishes[0];
+        assert_eq!(poke.dish_name, "Poke");
+        assert_eq!(poke.portions_sold, 12);
+        assert_eq!(poke.theoretical_cost_cents, 60_000 + 6_000);
+        assert_eq!(poke.actual_cost_cents, 73_333 + 5_000);
+        assert_eq!(poke.variance_cost_cents, 12_333);
+        assert_eq!(poke.variance_percent, Some(18.7));
+        let sashimi = &report.dishes[1];
+        assert_eq!(sashimi.theoretical_cost_cents, 30_000);
+        assert_eq!(sashimi.actual_cost_cents, 36_667);
+
+        // Vodka is in the totals but no dish explains it
+        assert_eq!(report.unattributed_cost_cents, 6_000);
+        assert_eq!(
+            poke.actual_cost_cents + sashimi.actual_cost_cents + report.unattributed_cost_cents,
+            report.actual_cost_cents
+        );
+    }
+
+    #[test]
+    fn test_usage_variance_report_highlights_and_categories() {
+        let start = OffsetDateTime::UNIX_EPOCH;
+        let end = start + time::Duration::days(7);
+        let inputs = vec![
+            usage_input("Salmon", "Fish & Seafood", 10, 20, 8, 0, 18, 5_000), // +4 → +20000
+            usage_input("Rice", "Grains & Pasta", 50, 0, 40, 0, 12, 500),     // −2 → −1000
+            usage_input("Beef", "Meat & Poultry", 5, 10, 5, 0, 9, 4_000),     // +1 → +4000
+            usage_input("Flour", "Grains & Pasta", 3, 0, 3, 0, 0, 200),       // untouched
+            usage_input("Vodka", "Beverages", 2, 0, 0, 0, 0, 3_000),          // +2, nothing sold
+        ];
+
+        let report = UsageVarianceReport::build(start, end, 40, 1, &inputs, &[]);
+
+        assert_eq!(report.ingredients.len(), 4);
+        assert_eq!(report.ingredients[0].ingredient_name, "Salmon");
+        assert_eq!(report.ingredients[1].ingredient_name, "Vodka");
+        assert_eq!(report.ingredients[1].variance_percent, None);
+        assert_eq!(report.variance_cost_cents, 20_000 - 1_000 + 4_000 + 6_000);
+        assert_eq!(
+            report
+                .top_overuse
+                .iter()
+                .map(|i| i.ingredient_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Salmon", "Vodka", "Beef"]
+        );
+        assert_eq!(report.top_underuse.len(), 1);
+        assert_eq!(report.top_underuse[0].ingredient_name, "Rice");
+
+        assert_eq!(report.categories[0].category_name, "Fish & Seafood");
+        let grains = report
+            .categories
+            .iter()
+            .find(|c| c.category_name == "Grains & Pasta")
+            .unwrap();
+        assert_eq!(grains.ingredients, 1);
+        assert_eq!(grains.variance_cost_cents, -1_000);
+        assert_eq!(grains.variance_percent, Some(-16.7));
+    }
 }
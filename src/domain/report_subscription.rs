@@ -0,0 +1,239 @@
+use crate::shared::{AppError, AppResult, Language};
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, Month, OffsetDateTime, Time};
+
+/// Report an owner can subscribe to by email
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    /// `GET /reports/summary`
+    Summary,
+    /// `GET /inventory/reports/loss`
+    LossReport,
+    /// `GET /menu-engineering/analysis`
+    MenuEngineering,
+    /// `GET /reports/usage-variance`
+    UsageVariance,
+}
+
+impl ReportKind {
+    pub const ALL: [ReportKind; 4] = [
+        Self::Summary,
+        Self::LossReport,
+        Self::MenuEngineering,
+        Self::UsageVariance,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Summary => "summary",
+            Self::LossReport => "loss_report",
+            Self::MenuEngineering => "menu_engineering",
+            Self::UsageVariance => "usage_variance",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+            .ok_or_else(|| AppError::validation(format!("Unknown report: {}", value)))
+    }
+
+    pub fn title(&self, lang: Language) -> &'static str {
+        match (self, lang) {
+            (Self::Summary, Language::Ru) => "Сводка по ресторану",
+            (Self::Summary, Language::Pl) => "Podsumowanie restauracji",
+            (Self::Summary, Language::Uk) => "Зведення по ресторану",
+            (Self::Summary, Language::En) => "Restaurant summary",
+            (Self::LossReport, Language::Ru) => "Отчёт о списаниях",
+            (Self::LossReport, Language::Pl) => "Raport strat",
+            (Self::LossReport, Language::Uk) => "Звіт про списання",
+            (Self::LossReport, Language::En) => "Loss report",
+            (Self::MenuEngineering, Language::Ru) => "Инжиниринг меню",
+            (Self::MenuEngineering, Language::Pl) => "Inżynieria menu",
+            (Self::MenuEngineering, Language::Uk) => "Інжиніринг меню",
+            (Self::MenuEngineering, Language::En) => "Menu engineering",
+            (Self::UsageVariance, Language::Ru) => "Теоретический и фактический расход",
+            (Self::UsageVariance, Language::Pl) => "Zużycie teoretyczne a rzeczywiste",
+            (Self::UsageVariance, Language::Uk) => "Теоретична та фактична витрата",
+            (Self::UsageVariance, Language::En) => "Usage variance",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFrequency {
+    Weekly,
+    Monthly,
+}
+
+impl ReportFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        match value {
+            "weekly" => Ok(Self::Weekly),
+            "monthly" => Ok(Self::Monthly),
+            other => Err(AppError::validation(format!(
+                "Unknown report frequency: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// When a subscription is delivered. `day` is the ISO weekday (1 = Monday)
+/// for weekly reports and the day of the month (1–28, so every month has
+/// it) for monthly ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportSchedule {
+    pub frequency: ReportFrequency,
+    pub day: u8,
+    pub hour_utc: u8,
+}
+
+impl ReportSchedule {
+    pub fn new(frequency: ReportFrequency, day: u8, hour_utc: u8) -> AppResult<Self> {
+        let max_day = match frequency {
+            ReportFrequency::Weekly => 7,
+            ReportFrequency::Monthly => 28,
+        };
+        if !(1..=max_day).contains(&day) {
+            return Err(AppError::validation(format!(
+                "day must be between 1 and {} for {} reports",
+                max_day,
+                frequency.as_str()
+            )));
+        }
+        if hour_utc > 23 {
+            return Err(AppError::validation("hour_utc must be between 0 and 23"));
+        }
+        Ok(Self {
+            frequency,
+            day,
+            hour_utc,
+        })
+    }
+
+    /// First delivery time strictly after `now`
+    pub fn next_run_after(&self, now: OffsetDateTime) -> OffsetDateTime {
+        let now = now.to_offset(time::UtcOffset::UTC);
+        let at = Time::from_hms(self.hour_utc, 0, 0).unwrap_or(Time::MIDNIGHT);
+
+        match self.frequency {
+            ReportFrequency::Weekly => {
+                let today = now.weekday().number_from_monday();
+                let ahead = (self.day as i64 - today as i64).rem_euclid(7);
+                let candidate = (now.date() + Duration::days(ahead))
+                    .with_time(at)
+                    .assume_utc();
+                if candidate > now {
+                    candidate
+                } else {
+                    candidate + Duration::weeks(1)
+                }
+            }
+            ReportFrequency::Monthly => {
+                let (year, month) = (now.year(), now.month());
+                let candidate = month_day(year, month, self.day).with_time(at).assume_utc();
+                if candidate > now {
+                    candidate
+                } else {
+                    let (year, month) = next_month(year, month);
+                    month_day(year, month, self.day).with_time(at).assume_utc()
+                }
+            }
+        }
+    }
+
+    /// Days covered by a delivery at `run_at`: the last week, or the whole
+    /// previous calendar month's length
+    pub fn period_days(&self, run_at: OffsetDateTime) -> u32 {
+        match self.frequency {
+            ReportFrequency::Weekly => 7,
+            ReportFrequency::Monthly => {
+                let (year, month) = match run_at.month() {
+                    Month::January => (run_at.year() - 1, Month::December),
+                    month => (run_at.year(), month.previous()),
+                };
+                time::util::days_in_year_month(year, month) as u32
+            }
+        }
+    }
+}
+
+fn next_month(year: i32, month: Month) -> (i32, Month) {
+    match month {
+        Month::December => (year + 1, Month::January),
+        month => (year, month.next()),
+    }
+}
+
+fn month_day(year: i32, month: Month, day: u8) -> Date {
+    // `day` is at most 28, valid in every month
+    Date::from_calendar_date(year, month, day).unwrap_or(Date::MIN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[test]
+    fn weekly_runs_on_the_next_matching_weekday() {
+        // 2026-10-14 is a Wednesday
+        let schedule = ReportSchedule::new(ReportFrequency::Weekly, 1, 7).unwrap();
+        assert_eq!(
+            schedule.next_run_after(datetime!(2026-10-14 12:00 UTC)),
+            datetime!(2026-10-19 07:00 UTC)
+        );
+
+        // Same weekday, hour not reached yet → today
+        let schedule = ReportSchedule::new(ReportFrequency::Weekly, 3, 18).unwrap();
+        assert_eq!(
+            schedule.next_run_after(datetime!(2026-10-14 12:00 UTC)),
+            datetime!(2026-10-14 18:00 UTC)
+        );
+
+        // Exactly at the run time → next week
+        assert_eq!(
+            schedule.next_run_after(datetime!(2026-10-14 18:00 UTC)),
+            datetime!(2026-10-21 18:00 UTC)
+        );
+    }
+
+    #[test]
+    fn monthly_rolls_over_the_year() {
+        let schedule = ReportSchedule::new(ReportFrequency::Monthly, 1, 6).unwrap();
+        assert_eq!(
+            schedule.next_run_after(datetime!(2026-12-15 09:00 UTC)),
+            datetime!(2027-01-01 06:00 UTC)
+        );
+        assert_eq!(
+            schedule.next_run_after(datetime!(2026-11-01 05:59 UTC)),
+            datetime!(2026-11-01 06:00 UTC)
+        );
+    }
+
+    #[test]
+    fn monthly_period_is_the_previous_month() {
+        let schedule = ReportSchedule::new(ReportFrequency::Monthly, 1, 6).unwrap();
+        assert_eq!(schedule.period_days(datetime!(2026-03-01 06:00 UTC)), 28);
+        assert_eq!(schedule.period_days(datetime!(2027-01-01 06:00 UTC)), 31);
+    }
+
+    #[test]
+    fn schedule_rejects_days_out_of_range() {
+        assert!(ReportSchedule::new(ReportFrequency::Weekly, 8, 6).is_err());
+        assert!(ReportSchedule::new(ReportFrequency::Monthly, 31, 6).is_err());
+        assert!(ReportSchedule::new(ReportFrequency::Monthly, 28, 24).is_err());
+        assert!(ReportSchedule::new(ReportFrequency::Monthly, 28, 23).is_ok());
+    }
+}
@@ -0,0 +1,364 @@
+use crate::domain::inventory::InventoryBatchId;
+use crate::shared::{AppError, AppResult};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Stocktake session status (open → closed | cancelled)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StocktakeStatus {
+    Open,
+    Closed,
+    Cancelled,
+}
+
+impl StocktakeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Closed => "closed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        match value {
+            "open" => Ok(Self::Open),
+            "closed" => Ok(Self::Closed),
+            "cancelled" => Ok(Self::Cancelled),
+            other => Err(AppError::internal(format!(
+                "Unknown stocktake status: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Active batch snapshot used to plan adjustments (already in FIFO order)
+#[derive(Debug, Clone)]
+pub struct BatchStock {
+    pub batch_id: InventoryBatchId,
+    pub remaining: Decimal,
+    pub price_per_unit_cents: i64,
+}
+
+/// Planned change for a single batch.
+/// `delta` is signed: negative = shrinkage, positive = surplus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchAdjustment {
+    pub batch_id: InventoryBatchId,
+    pub delta: Decimal,
+    pub unit_cost_cents: i64,
+}
+
+impl BatchAdjustment {
+    /// Signed money value of the adjustment
+    pub fn value_cents(&self) -> i64 {
+        (self.delta * Decimal::from(self.unit_cost_cents))
+            .round()
+            .to_i64()
+            .unwrap_or(0)
+    }
+}
+
+/// Result of comparing a physical count with the book stock of one ingredient
+#[derive(Debug, Clone)]
+pub struct AdjustmentPlan {
+    pub expected: Decimal,
+    pub counted: Decimal,
+    pub adjustments: Vec<BatchAdjustment>,
+    /// Variance that could not be attached to any batch: surplus with no
+    /// active stock, or shrinkage beyond what is left (negative)
+    pub unposted: Decimal,
+    /// Price the unposted variance is valued at: the newest batch's, or
+    /// 0 until the caller supplies one when there is no active stock
+    pub unposted_unit_cost_cents: i64,
+}
+
+impl AdjustmentPlan {
+    /// Build the plan FIFO-style:
+    /// shrinkage is taken from the oldest batches first,
+    /// surplus goes to the newest active batch.
+    pub fn build(batches: &[BatchStock], counted: Decimal) -> AppResult<Self> {
+        let expected = batches.iter().map(|b| b.remaining).sum();
+        Self::build_against(batches, expected, counted)
+    }
+
+    /// Same, for a count taken when the book stock was `expected`: the
+    /// variance `counted - expected` is applied to the batches as they are
+    /// now, so sales and receipts between the count and the close are not
+    /// booked as shrinkage or surplus.
+    pub fn build_against(
+        batches: &[BatchStock],
+        expected: Decimal,
+        counted: Decimal,
+    ) -> AppResult<Self> {
+        if counted < Decimal::ZERO {
+            return Err(AppError::validation("Counted quantity cannot be negative"));
+        }
+
+        let variance = counted - expected;
+        let mut adjustments = Vec::new();
+        let mut unposted = Decimal::ZERO;
+
+        if variance < Decimal::ZERO {
+            let mut to_remove = -variance;
+            for batch in batches {
+                if to_remove <= Decimal::ZERO {
+                    break;
+                }
+                let take = batch.remaining.min(to_remove);
+                if take > Decimal::ZERO {
+                    adjustments.push(BatchAdjustment {
+                        batch_id: batch.batch_id,
+                        delta: -take,
+                        unit_cost_cents: batch.price_per_unit_cents,
+                    });
+                    to_remove -= take;
+                }
+            }
+            unposted = -to_remove;
+        } else if variance > Decimal::ZERO {
+            match batches.last() {
+                Some(newest) => adjustments.push(BatchAdjustment {
+                    batch_id: newest.batch_id,
+                    delta: variance,
+                    unit_cost_cents: newest.price_per_unit_cents,
+                }),
+                None => unposted = variance,
+            }
+        }
+
+        Ok(Self {
+            expected,
+            counted,
+            adjustments,
+            unposted,
+            unposted_unit_cost_cents: batches.last().map(|b| b.price_per_unit_cents).unwrap_or(0),
+        })
+    }
+
+    pub fn variance(&self) -> Decimal {
+        self.counted - self.expected
+    }
+
+    /// Signed money variance (negative = shrinkage), unposted part included
+    pub fn variance_cents(&self) -> i64 {
+        let unposted = (self.unposted * Decimal::from(self.unposted_unit_cost_cents))
+            .round()
+            .to_i64()
+            .unwrap_or(0);
+        self.adjustments
+            .iter()
+            .map(|a| a.value_cents())
+            .sum::<i64>()
+            + unposted
+    }
+}
+
+/// Variance report line for one ingredient
+#[derive(Debug, Clone, Serialize)]
+pub struct IngredientVariance {
+    pub ingredient_id: Uuid,
+    pub ingredient_name: String,
+    pub category_id: Option<Uuid>,
+    pub category_name: String,
+    pub expected_quantity: f64,
+    pub counted_quantity: f64,
+    pub variance_quantity: f64,
+    pub unposted_quantity: f64,
+    pub variance_cents: i64,
+}
+
+/// Variance aggregated per catalog category
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryVariance {
+    pub category_id: Option<Uuid>,
+    pub category_name: String,
+    pub items_counted: usize,
+    pub shrinkage_cents: i64,
+    pub surplus_cents: i64,
+    pub net_variance_cents: i64,
+}
+
+/// Full variance report of a closed stocktake
+#[derive(Debug, Clone, Serialize)]
+pub struct StocktakeVarianceReport {
+    pub session_id: Uuid,
+    pub items: Vec<IngredientVariance>,
+    pub categories: Vec<CategoryVariance>,
+    pub shrinkage_cents: i64,
+    pub surplus_cents: i64,
+    pub net_variance_cents: i64,
+}
+
+impl StocktakeVarianceReport {
+    pub fn build(session_id: Uuid, items: Vec<IngredientVariance>) -> Self {
+        let mut by_category: BTreeMap<String, CategoryVariance> = BTreeMap::new();
+        let mut shrinkage_cents = 0;
+        let mut surplus_cents = 0;
+
+        for item in &items {
+            let entry = by_category
+                .entry(item.category_name.clone())
+                .or_insert_with(|| CategoryVariance {
+                    category_id: item.category_id,
+                    category_name: item.category_name.clone(),
+                    items_counted: 0,
+                    shrinkage_cents: 0,
+                    surplus_cents: 0,
+                    net_variance_cents: 0,
+                });
+            entry.items_counted += 1;
+            if item.variance_cents < 0 {
+                entry.shrinkage_cents += -item.variance_cents;
+                shrinkage_cents += -item.variance_cents;
+            } else {
+                entry.surplus_cents += item.variance_cents;
+                surplus_cents += item.variance_cents;
+            }
+            entry.net_variance_cents += item.variance_cents;
+        }
+
+        // Biggest losses first
+        let mut categories: Vec<CategoryVariance> = by_category.into_values().collect();
+        categories.sort_by_key(|c| c.net_variance_cents);
+
+        Self {
+            session_id,
+            items,
+            categories,
+            shrinkage_cents,
+            surplus_cents,
+            net_variance_cents: surplus_cents - shrinkage_cents,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(v: &str) -> Decimal {
+        v.parse().unwrap()
+    }
+
+    fn batch(remaining: Decimal, price: i64) -> BatchStock {
+        BatchStock {
+            batch_id: InventoryBatchId::new(),
+            remaining,
+            price_per_unit_cents: price,
+        }
+    }
+
+    #[test]
+    fn test_shrinkage_is_taken_fifo() {
+        let batches = vec![batch(dec("2"), 1000), batch(dec("3"), 1200)];
+        let plan = AdjustmentPlan::build(&batches, dec("1.5")).unwrap();
+
+        assert_eq!(plan.expected, dec("5"));
+        assert_eq!(plan.variance(), dec("-3.5"));
+        assert_eq!(plan.adjustments.len(), 2);
+        assert_eq!(plan.adjustments[0].batch_id, batches[0].batch_id);
+        assert_eq!(plan.adjustments[0].delta, dec("-2"));
+        assert_eq!(plan.adjustments[1].delta, dec("-1.5"));
+        // 2 * 10.00 + 1.5 * 12.00 = 38.00
+        assert_eq!(plan.variance_cents(), -3800);
+    }
+
+    #[test]
+    fn test_surplus_goes_to_newest_batch() {
+        let batches = vec![batch(dec("1"), 500), batch(dec("1"), 700)];
+        let plan = AdjustmentPlan::build(&batches, dec("2.5")).unwrap();
+
+        assert_eq!(plan.adjustments.len(), 1);
+        assert_eq!(plan.adjustments[0].batch_id, batches[1].batch_id);
+        assert_eq!(plan.adjustments[0].delta, dec("0.5"));
+        assert_eq!(plan.variance_cents(), 350);
+        assert_eq!(plan.unposted, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_surplus_without_stock_is_unposted() {
+        let plan = AdjustmentPlan::build(&[], dec("4")).unwrap();
+        assert!(plan.adjustments.is_empty());
+        assert_eq!(plan.unposted, dec("4"));
+        assert_eq!(plan.variance_cents(), 0);
+
+        let mut plan = plan;
+        plan.unposted_unit_cost_cents = 250;
+        assert_eq!(plan.variance_cents(), 1000);
+    }
+
+    #[test]
+    fn test_exact_count_has_no_adjustments() {
+        let batches = vec![batch(dec("3"), 100)];
+        let plan = AdjustmentPlan::build(&batches, dec("3")).unwrap();
+        assert!(plan.adjustments.is_empty());
+        assert_eq!(plan.variance(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_variance_is_measured_against_stock_at_count_time() {
+        // Counted 4 when the book said 5; 2 were sold before the close
+        let batches = vec![batch(dec("1"), 1000), batch(dec("2"), 1200)];
+        let plan = AdjustmentPlan::build_against(&batches, dec("5"), dec("4")).unwrap();
+
+        assert_eq!(plan.variance(), dec("-1"));
+        assert_eq!(plan.adjustments.len(), 1);
+        assert_eq!(plan.adjustments[0].delta, dec("-1"));
+        assert_eq!(plan.unposted, Decimal::ZERO);
+
+        // Shrinkage larger than what is left at close: the 2 missing units
+        // are valued at the newest batch price
+        let plan = AdjustmentPlan::build_against(&batches, dec("10"), dec("5")).unwrap();
+        assert_eq!(plan.variance(), dec("-5"));
+        assert_eq!(plan.unposted, dec("-2"));
+        assert_eq!(plan.variance_cents(), -1000 - 2400 - 2400);
+    }
+
+    #[test]
+    fn test_negative_count_rejected() {
+        assert!(AdjustmentPlan::build(&[], dec("-1")).is_err());
+    }
+
+    #[test]
+    fn test_report_groups_by_category() {
+        let line = |cat: &str, cents: i64| IngredientVariance {
+            ingredient_id: Uuid::new_v4(),
+            ingredient_name: "X".to_string(),
+            category_id: None,
+            category_name: cat.to_string(),
+            expected_quantity: 0.0,
+            counted_quantity: 0.0,
+            variance_quantity: 0.0,
+            unposted_quantity: 0.0,
+            variance_cents: cents,
+        };
+        let report = StocktakeVarianceReport::build(
+            Uuid::new_v4(),
+            vec![line("Meat", -5000), line("Meat", 1000), line("Dairy", -200)],
+        );
+
+        assert_eq!(report.shrinkage_cents, 5200);
+        assert_eq!(report.surplus_cents, 1000);
+        assert_eq!(report.net_variance_cents, -4200);
+        assert_eq!(report.categories[0].category_name, "Meat");
+        assert_eq!(report.categories[0].items_counted, 2);
+        assert_eq!(report.categories[0].net_variance_cents, -4000);
+    }
+
+    #[test]
+    fn test_status_roundtrip() {
+        for s in [
+            StocktakeStatus::Open,
+            StocktakeStatus::Closed,
+            StocktakeStatus::Cancelled,
+        ] {
+            assert_eq!(StocktakeStatus::parse(s.as_str()).unwrap(), s);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::shared::{AppError, AppResult, TenantId};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Storage location ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StorageLocationId(Uuid);
+
+impl StorageLocationId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl Default for StorageLocationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Kind of storage location
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocationKind {
+    WalkIn,
+    Freezer,
+    DryStore,
+    Bar,
+    /// Point of sale (restaurant outlet) — dish sales deduct from here
+    Outlet,
+    Other,
+}
+
+impl LocationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WalkIn => "walk_in",
+            Self::Freezer => "freezer",
+            Self::DryStore => "dry_store",
+            Self::Bar => "bar",
+            Self::Outlet => "outlet",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        match value {
+            "walk_in" => Ok(Self::WalkIn),
+            "freezer" => Ok(Self::Freezer),
+            "dry_store" => Ok(Self::DryStore),
+            "bar" => Ok(Self::Bar),
+            "outlet" => Ok(Self::Outlet),
+            "other" => Ok(Self::Other),
+            _ => Err(AppError::validation(format!(
+                "Unknown location kind: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Place where inventory batches are stored (central kitchen walk-in, outlet bar, ...)
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageLocation {
+    pub id: StorageLocationId,
+    pub tenant_id: TenantId,
+    pub name: String,
+    pub kind: LocationKind,
+    /// Receives batches created without an explicit location
+    pub is_default: bool,
+    pub is_active: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl StorageLocation {
+    pub fn new(
+        tenant_id: TenantId,
+        name: impl Into<String>,
+        kind: LocationKind,
+    ) -> AppResult<Self> {
+        let now = OffsetDateTime::now_utc();
+        let mut location = Self {
+            id: StorageLocationId::new(),
+            tenant_id,
+            name: String::new(),
+            kind,
+            is_default: false,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        };
+        location.rename(name)?;
+        Ok(location)
+    }
+
+    /// Update name (1..=100 characters)
+    pub fn rename(&mut self, name: impl Into<String>) -> AppResult<()> {
+        let name = name.into().trim().to_string();
+        if name.is_empty() {
+            return Err(AppError::validation("Location name cannot be empty"));
+        }
+        if name.chars().count() > 100 {
+            return Err(AppError::validation(
+                "Location name cannot exceed 100 characters",
+            ));
+        }
+        self.name = name;
+        Ok(())
+    }
+}
+
+/// Validate a transfer request before touching stock
+pub fn validate_transfer(
+    from: StorageLocationId,
+    to: StorageLocationId,
+    quantity: f64,
+) -> AppResult<()> {
+    if from == to {
+        return Err(AppError::validation(
+            "Source and destination locations must differ",
+        ));
+    }
+    if !quantity.is_finite() || quantity <= 0.0 {
+        return Err(AppError::validation(
+            "Transfer quantity must be greater than 0",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_kind_roundtrip_and_validation() {
+        for kind in [
+            LocationKind::WalkIn,
+            LocationKind::Freezer,
+            LocationKind::DryStore,
+            LocationKind::Bar,
+            LocationKind::Outlet,
+            LocationKind::Other,
+        ] {
+            assert_eq!(LocationKind::parse(kind.as_str()).unwrap(), kind);
+        }
+        assert!(LocationKind::parse("garage").is_err());
+
+        assert!(StorageLocation::new(TenantId::new(), "  ", LocationKind::Bar).is_err());
+        let location =
+            StorageLocation::new(TenantId::new(), " Outlet 1 ", LocationKind::Outlet).unwrap();
+        assert_eq!(location.name, "Outlet 1");
+        assert!(!location.is_default);
+    }
+
+    #[test]
+    fn test_validate_transfer() {
+        let a = StorageLocationId::new();
+        let b = StorageLocationId::new();
+        assert!(validate_transfer(a, b, 2.5).is_ok());
+        assert!(validate_transfer(a, a, 2.5).is_err());
+        assert!(validate_transfer(a, b, 0.0).is_err());
+        assert!(validate_transfer(a, b, f64::NAN).is_err());
+    }
+}
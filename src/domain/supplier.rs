@@ -0,0 +1,299 @@
+use crate::shared::{AppError, AppResult, TenantId};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+/// Supplier ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SupplierId(Uuid);
+
+impl SupplierId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self(uuid)
+    }
+
+    pub fn as_uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl Default for SupplierId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tenant supplier (where ingredients are bought)
+#[derive(Debug, Clone, Serialize)]
+pub struct Supplier {
+    pub id: SupplierId,
+    pub tenant_id: TenantId,
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub notes: Option<String>,
+    /// Days between order and delivery
+    pub lead_time_days: i32,
+    /// Minimum order value (in smallest currency unit)
+    pub min_order_cents: i64,
+    /// ISO weekdays with deliveries (1 = Monday … 7 = Sunday); empty = any day
+    pub delivery_weekdays: Vec<i16>,
+    pub is_active: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl Supplier {
+    /// Create new supplier with default terms (next-day delivery, no minimum)
+    pub fn new(tenant_id: TenantId, name: impl Into<String>) -> AppResult<Self> {
+        let now = OffsetDateTime::now_utc();
+        let mut supplier = Self {
+            id: SupplierId::new(),
+            tenant_id,
+            name: String::new(),
+            contact_name: None,
+            email: None,
+            phone: None,
+            address: None,
+            notes: None,
+            lead_time_days: 1,
+            min_order_cents: 0,
+            delivery_weekdays: Vec::new(),
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        };
+        supplier.rename(name)?;
+        Ok(supplier)
+    }
+
+    /// Update name (1..=200 characters)
+    pub fn rename(&mut self, name: impl Into<String>) -> AppResult<()> {
+        let name = name.into().trim().to_string();
+        if name.is_empty() {
+            return Err(AppError::validation("Supplier name cannot be empty"));
+        }
+        if name.chars().count() > 200 {
+            return Err(AppError::validation(
+                "Supplier name cannot exceed 200 characters",
+            ));
+        }
+        self.name = name;
+        Ok(())
+    }
+
+    /// Update lead time (0..=90 days)
+    pub fn set_lead_time_days(&mut self, days: i32) -> AppResult<()> {
+        if !(0..=90).contains(&days) {
+            return Err(AppError::validation(
+                "Lead time must be between 0 and 90 days",
+            ));
+        }
+        self.lead_time_days = days;
+        Ok(())
+    }
+
+    /// Update minimum order value
+    pub fn set_min_order_cents(&mut self, cents: i64) -> AppResult<()> {
+        if cents < 0 {
+            return Err(AppError::validation("Minimum order cannot be negative"));
+        }
+        self.min_order_cents = cents;
+        Ok(())
+    }
+
+    /// Update delivery weekdays (ISO 1..=7, stored sorted and unique)
+    pub fn set_delivery_weekdays(&mut self, mut weekdays: Vec<i16>) -> AppResult<()> {
+        if weekdays.iter().any(|d| !(1..=7).contains(d)) {
+            return Err(AppError::validation(
+                "Delivery weekdays must be between 1 (Monday) and 7 (Sunday)",
+            ));
+        }
+        weekdays.sort_unstable();
+        weekdays.dedup();
+        self.delivery_weekdays = weekdays;
+        Ok(())
+    }
+
+    /// Does the supplier deliver on this date?
+    pub fn delivers_on(&self, date: Date) -> bool {
+        self.delivery_weekdays.is_empty()
+            || self
+                .delivery_weekdays
+                .contains(&(date.weekday().number_from_monday() as i16))
+    }
+
+    /// Earliest delivery date for an order placed on `order_date`:
+    /// lead time first, then the next delivery weekday.
+    pub fn next_delivery_date(&self, order_date: Date) -> Date {
+        let mut date = order_date + Duration::days(self.lead_time_days as i64);
+        for _ in 0..7 {
+            if self.delivers_on(date) {
+                break;
+            }
+            date += Duration::days(1);
+        }
+        date
+    }
+}
+
+/// One recorded purchase price of an ingredient
+#[derive(Debug, Clone, Serialize)]
+pub struct PricePoint {
+    pub supplier_id: Option<SupplierId>,
+    pub supplier_name: Option<String>,
+    pub price_per_unit_cents: i64,
+    pub quantity: Decimal,
+    #[serde(with = "time::serde::rfc3339")]
+    pub recorded_at: OffsetDateTime,
+}
+
+/// Price movement of an ingredient over a period (points in chronological order)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceTrend {
+    pub first_price_cents: i64,
+    pub last_price_cents: i64,
+    pub min_price_cents: i64,
+    pub max_price_cents: i64,
+    /// Quantity-weighted average purchase price
+    pub average_price_cents: i64,
+    /// (last - first) / first × 100
+    pub change_percent: f64,
+    pub purchases: usize,
+}
+
+impl PriceTrend {
+    /// Build the trend from chronologically ordered price points
+    pub fn from_points(points: &[PricePoint]) -> Option<Self> {
+        let first = points.first()?;
+        let last = points.last()?;
+
+        let min_price_cents = points.iter().map(|p| p.price_per_unit_cents).min()?;
+        let max_price_cents = points.iter().map(|p| p.price_per_unit_cents).max()?;
+
+        let change_percent = if first.price_per_unit_cents > 0 {
+            (last.price_per_unit_cents - first.price_per_unit_cents) as f64
+                / first.price_per_unit_cents as f64
+                * 100.0
+        } else {
+            0.0
+        };
+
+        Some(Self {
+            first_price_cents: first.price_per_unit_cents,
+            last_price_cents: last.price_per_unit_cents,
+            min_price_cents,
+            max_price_cents,
+            average_price_cents: weighted_average_cents(points),
+            change_percent,
+            purchases: points.len(),
+        })
+    }
+}
+
+/// Quantity-weighted average price; plain average when no quantities are known
+pub fn weighted_average_cents(points: &[PricePoint]) -> i64 {
+    if points.is_empty() {
+        return 0;
+    }
+
+    let total_qty: Decimal = points.iter().map(|p| p.quantity).sum();
+    if total_qty > Decimal::ZERO {
+        let total_value: Decimal = points
+            .iter()
+            .map(|p| p.quantity * Decimal::from(p.price_per_unit_cents))
+            .sum();
+        return (total_value / total_qty).round().to_i64().unwrap_or(0);
+    }
+
+    let sum: i64 = points.iter().map(|p| p.price_per_unit_cents).sum();
+    (Decimal::from(sum) / Decimal::from(points.len() as i64))
+        .round()
+        .to_i64()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    fn point(cents: i64, qty: i64) -> PricePoint {
+        PricePoint {
+            supplier_id: None,
+            supplier_name: None,
+            price_per_unit_cents: cents,
+            quantity: Decimal::from(qty),
+            recorded_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    #[test]
+    fn test_supplier_validation() {
+        let tenant_id = TenantId::new();
+        assert!(Supplier::new(tenant_id, "   ").is_err());
+
+        let mut supplier = Supplier::new(tenant_id, " Metro ").unwrap();
+        assert_eq!(supplier.name, "Metro");
+        assert!(supplier.set_lead_time_days(-1).is_err());
+        assert!(supplier.set_min_order_cents(-100).is_err());
+        assert!(supplier.set_delivery_weekdays(vec![0, 3]).is_err());
+
+        supplier.set_delivery_weekdays(vec![5, 1, 5]).unwrap();
+        assert_eq!(supplier.delivery_weekdays, vec![1, 5]);
+    }
+
+    #[test]
+    fn test_next_delivery_date_respects_weekdays() {
+        let mut supplier = Supplier::new(TenantId::new(), "Fish Market").unwrap();
+        supplier.set_lead_time_days(1).unwrap();
+        // Tuesday and Friday deliveries
+        supplier.set_delivery_weekdays(vec![2, 5]).unwrap();
+
+        // Ordered Tuesday → +1 day = Wednesday → first delivery Friday
+        assert_eq!(
+            supplier.next_delivery_date(date!(2026 - 10 - 13)),
+            date!(2026 - 10 - 16)
+        );
+        // Ordered Monday → Tuesday fits
+        assert_eq!(
+            supplier.next_delivery_date(date!(2026 - 10 - 12)),
+            date!(2026 - 10 - 13)
+        );
+
+        // No weekday restriction → lead time only
+        supplier.set_delivery_weekdays(vec![]).unwrap();
+        assert_eq!(
+            supplier.next_delivery_date(date!(2026 - 10 - 13)),
+            date!(2026 - 10 - 14)
+        );
+    }
+
+    #[test]
+    fn test_price_trend_and_weighted_average() {
+        let points = vec![point(1000, 10), point(1100, 30), point(1200, 10)];
+        let trend = PriceTrend::from_points(&points).unwrap();
+
+        assert_eq!(trend.first_price_cents, 1000);
+        assert_eq!(trend.last_price_cents, 1200);
+        assert_eq!(trend.min_price_cents, 1000);
+        assert_eq!(trend.max_price_cents, 1200);
+        // (10×1000 + 30×1100 + 10×1200) / 50 = 1100
+        assert_eq!(trend.average_price_cents, 1100);
+        assert!((trend.change_percent - 20.0).abs() < 1e-9);
+
+        assert!(PriceTrend::from_points(&[]).is_none());
+        // Unknown quantities → plain average
+        assert_eq!(weighted_average_cents(&[point(100, 0), point(300, 0)]), 200);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::domain::catalog::{CatalogIngredientId, Unit};
-use crate::shared::TenantId;
+use crate::domain::supplier::SupplierId;
+use crate::shared::{AppError, AppResult, TenantId};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -41,8 +42,20 @@ pub struct TenantIngredient {
     pub supplier: Option<String>,
     pub custom_unit: Option<Unit>,
     pub custom_expiration_days: Option<i32>,
+    /// Edible yield % override (trim loss), e.g. 55 for whole salmon
+    pub yield_percent: Option<Decimal>,
     pub notes: Option<String>,
 
+    // Replenishment (automatic reorder proposals)
+    /// Preferred supplier
+    pub supplier_id: Option<SupplierId>,
+    /// Target stock level after a delivery
+    pub par_level: Option<Decimal>,
+    /// Reorder when projected stock drops to this level
+    pub reorder_point: Option<Decimal>,
+    /// Supplier pack size; order quantities are rounded up to it
+    pub pack_size: Option<Decimal>,
+
     pub is_active: bool,
     pub created_at: time::OffsetDateTime,
     pub updated_at: time::OffsetDateTime,
@@ -65,7 +78,12 @@ impl TenantIngredient {
             supplier,
             custom_unit: None,
             custom_expiration_days: None,
+            yield_percent: None,
             notes: None,
+            supplier_id: None,
+            par_level: None,
+            reorder_point: None,
+            pack_size: None,
             is_active: true,
             created_at: now,
             updated_at: now,
@@ -81,6 +99,7 @@ impl TenantIngredient {
         supplier: Option<String>,
         custom_unit: Option<Unit>,
         custom_expiration_days: Option<i32>,
+        yield_percent: Option<Decimal>,
         notes: Option<String>,
         is_active: bool,
         created_at: time::OffsetDateTime,
@@ -94,7 +113,12 @@ impl TenantIngredient {
             supplier,
             custom_unit,
             custom_expiration_days,
+            yield_percent,
             notes,
+            supplier_id: None,
+            par_level: None,
+            reorder_point: None,
+            pack_size: None,
             is_active,
             created_at,
             updated_at,
@@ -111,6 +135,57 @@ impl TenantIngredient {
         self.custom_expiration_days.or(catalog_days)
     }
 
+    /// Get effective yield % (custom or default from catalog, 100 when unknown)
+    pub fn effective_yield_percent(&self, catalog_yield: Option<Decimal>) -> Decimal {
+        self.yield_percent
+            .or(catalog_yield)
+            .unwrap_or(Decimal::ONE_HUNDRED)
+    }
+
+    /// Update yield override (must be in (0, 100])
+    pub fn set_yield_percent(&mut self, yield_percent: Option<Decimal>) -> AppResult<()> {
+        if let Some(value) = yield_percent {
+            if value <= Decimal::ZERO || value > Decimal::ONE_HUNDRED {
+                return Err(AppError::validation(
+                    "Yield percent must be greater than 0 and at most 100",
+                ));
+            }
+        }
+        self.yield_percent = yield_percent;
+        Ok(())
+    }
+
+    /// Update par level / reorder point / pack size.
+    /// Reorder point must not exceed par level; pack size must be positive.
+    pub fn set_reorder_policy(
+        &mut self,
+        par_level: Option<Decimal>,
+        reorder_point: Option<Decimal>,
+        pack_size: Option<Decimal>,
+    ) -> AppResult<()> {
+        if par_level.is_some_and(|v| v < Decimal::ZERO)
+            || reorder_point.is_some_and(|v| v < Decimal::ZERO)
+        {
+            return Err(AppError::validation(
+                "Par level and reorder point cannot be negative",
+            ));
+        }
+        if let (Some(par), Some(point)) = (par_level, reorder_point) {
+            if point > par {
+                return Err(AppError::validation(
+                    "Reorder point cannot exceed par level",
+                ));
+            }
+        }
+        if pack_size.is_some_and(|v| v <= Decimal::ZERO) {
+            return Err(AppError::validation("Pack size must be greater than 0"));
+        }
+        self.par_level = par_level;
+        self.reorder_point = reorder_point;
+        self.pack_size = pack_size;
+        Ok(())
+    }
+
     /// Update price
     pub fn set_price(&mut self, price: Option<Decimal>) {
         self.price = price;
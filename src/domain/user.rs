@@ -99,6 +99,98 @@ impl std::fmt::Display for UserRole {
     }
 }
 
+impl UserRole {
+    /// Permission matrix: Owner can do everything, Manager everything except
+    /// ownership transfer, Staff covers day-to-day kitchen work only.
+    pub fn allows(&self, permission: Permission) -> bool {
+        match self {
+            UserRole::Owner => true,
+            UserRole::Manager => !matches!(permission, Permission::TransferOwnership),
+            UserRole::Staff => matches!(
+                permission,
+                Permission::ViewInventory
+                    | Permission::LogWaste
+                    | Permission::RecordCounts
+                    | Permission::ViewRecipes
+                    | Permission::ViewDishes
+                    | Permission::RecordSales
+            ),
+        }
+    }
+
+    pub fn require(&self, permission: Permission) -> AppResult<()> {
+        if self.allows(permission) {
+            Ok(())
+        } else {
+            Err(AppError::authorization(format!(
+                "Your role ({}) does not allow {}",
+                self,
+                permission.as_str()
+            )))
+        }
+    }
+
+    /// Roles this role may hand out through an invitation. Owner is never
+    /// invited — it changes hands via ownership transfer.
+    pub fn can_invite(&self, role: &UserRole) -> bool {
+        match self {
+            UserRole::Owner => matches!(role, UserRole::Manager | UserRole::Staff),
+            UserRole::Manager => matches!(role, UserRole::Staff),
+            UserRole::Staff => false,
+        }
+    }
+}
+
+/// Actions guarded per role in the HTTP layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    ViewInventory,
+    /// Products, batches, stock settings, shortfalls
+    ManageInventory,
+    LogWaste,
+    /// Post count lines to an open stocktake (opening and closing it is
+    /// `ManageInventory`)
+    RecordCounts,
+    ViewRecipes,
+    ManageRecipes,
+    ViewDishes,
+    /// Create dishes and recalculate costs
+    ManageDishes,
+    /// Selling prices and the price simulator
+    ChangePrices,
+    RecordSales,
+    ViewMenuEngineering,
+    ViewReports,
+    /// Invitations and member roles
+    ManageTeam,
+    /// Who changed what (`GET /audit`)
+    ViewAuditLog,
+    TransferOwnership,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ViewInventory => "view_inventory",
+            Permission::ManageInventory => "manage_inventory",
+            Permission::LogWaste => "log_waste",
+            Permission::RecordCounts => "record_counts",
+            Permission::ViewRecipes => "view_recipes",
+            Permission::ManageRecipes => "manage_recipes",
+            Permission::ViewDishes => "view_dishes",
+            Permission::ManageDishes => "manage_dishes",
+            Permission::ChangePrices => "change_prices",
+            Permission::RecordSales => "record_sales",
+            Permission::ViewMenuEngineering => "view_menu_engineering",
+            Permission::ViewReports => "view_reports",
+            Permission::ManageTeam => "manage_team",
+            Permission::ViewAuditLog => "view_audit_log",
+            Permission::TransferOwnership => "transfer_ownership",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Email(String);
 
@@ -110,6 +202,11 @@ impl Email {
             return Err(AppError::validation("Email cannot be empty"));
         }
 
+        // Addresses end up in mail headers and SMTP commands
+        if normalized.chars().any(char::is_control) {
+            return Err(AppError::validation("Invalid email format"));
+        }
+
         // Simple email validation
         if !normalized.contains('@') || !normalized.contains('.') {
             return Err(AppError::validation("Invalid email format"));
@@ -218,3 +315,51 @@ impl Password {
         self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn staff_can_log_waste_but_not_change_prices() {
+        let staff = UserRole::Staff;
+        assert!(staff.allows(Permission::LogWaste));
+        assert!(staff.allows(Permission::RecordCounts));
+        assert!(staff.allows(Permission::ViewInventory));
+        assert!(!staff.allows(Permission::ChangePrices));
+        assert!(!staff.allows(Permission::ManageInventory));
+        assert!(!staff.allows(Permission::ViewReports));
+        assert!(!staff.allows(Permission::ManageTeam));
+    }
+
+    #[test]
+    fn only_owner_transfers_ownership() {
+        assert!(UserRole::Owner.allows(Permission::TransferOwnership));
+        assert!(!UserRole::Manager.allows(Permission::TransferOwnership));
+        assert!(UserRole::Manager.allows(Permission::ChangePrices));
+        assert!(UserRole::Manager.allows(Permission::ManageTeam));
+        assert!(UserRole::Manager.allows(Permission::ViewAuditLog));
+        assert!(!UserRole::Staff.allows(Permission::ViewAuditLog));
+    }
+
+    #[test]
+    fn invitable_roles() {
+        assert!(UserRole::Owner.can_invite(&UserRole::Manager));
+        assert!(!UserRole::Owner.can_invite(&UserRole::Owner));
+        assert!(UserRole::Manager.can_invite(&UserRole::Staff));
+        assert!(!UserRole::Manager.can_invite(&UserRole::Manager));
+        assert!(!UserRole::Staff.can_invite(&UserRole::Staff));
+    }
+
+    #[test]
+    fn email_rejects_control_characters() {
+        assert_eq!(
+            Email::new(" Cook@Example.com ".to_string())
+                .unwrap()
+                .as_str(),
+            "cook@example.com"
+        );
+        assert!(Email::new("cook@example.com\r\nBcc: x@evil.com".to_string()).is_err());
+        assert!(Email::new("cook@exa\u{0}mple.com".to_string()).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::shared::{AppError, AppResult, Language};
+use serde::{Deserialize, Serialize};
+
+/// Loss-report reason for batches exhausted by `process_expirations`
+/// (not a loggable waste reason)
+pub const AUTO_EXPIRY_REASON: &str = "auto_expiry";
+
+/// Why stock was thrown away (manual waste log)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WasteReason {
+    /// Went off before expiry (bad storage, cold chain)
+    Spoilage,
+    /// Found expired and binned by staff
+    Expired,
+    /// Dropped, broken, contaminated
+    Breakage,
+    StaffMeal,
+    /// Plate sent back by a guest
+    ReturnedPlate,
+    /// Prepared but not sold
+    Overproduction,
+    Other,
+}
+
+impl WasteReason {
+    pub const ALL: [WasteReason; 7] = [
+        Self::Spoilage,
+        Self::Expired,
+        Self::Breakage,
+        Self::StaffMeal,
+        Self::ReturnedPlate,
+        Self::Overproduction,
+        Self::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Spoilage => "spoilage",
+            Self::Expired => "expired",
+            Self::Breakage => "breakage",
+            Self::StaffMeal => "staff_meal",
+            Self::ReturnedPlate => "returned_plate",
+            Self::Overproduction => "overproduction",
+            Self::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> AppResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == value)
+            .ok_or_else(|| AppError::validation(format!("Unknown waste reason: {}", value)))
+    }
+
+    pub fn label(&self, lang: Language) -> &'static str {
+        match (self, lang) {
+            (Self::Spoilage, Language::Ru) => "Порча",
+            (Self::Spoilage, Language::Pl) => "Zepsucie",
+            (Self::Spoilage, Language::Uk) => "Псування",
+            (Self::Spoilage, Language::En) => "Spoilage",
+            (Self::Expired, Language::Ru) => "Истёк срок годности",
+            (Self::Expired, Language::Pl) => "Przeterminowane",
+            (Self::Expired, Language::Uk) => "Минув термін придатності",
+            (Self::Expired, Language::En) => "Expired",
+            (Self::Breakage, Language::Ru) => "Бой / повреждение",
+            (Self::Breakage, Language::Pl) => "Stłuczka / uszkodzenie",
+            (Self::Breakage, Language::Uk) => "Бій / пошкодження",
+            (Self::Breakage, Language::En) => "Breakage",
+            (Self::StaffMeal, Language::Ru) => "Питание персонала",
+            (Self::StaffMeal, Language::Pl) => "Posiłek pracowniczy",
+            (Self::StaffMeal, Language::Uk) => "Харчування персоналу",
+            (Self::StaffMeal, Language::En) => "Staff meal",
+            (Self::ReturnedPlate, Language::Ru) => "Возврат блюда",
+            (Self::ReturnedPlate, Language::Pl) => "Zwrot dania",
+            (Self::ReturnedPlate, Language::Uk) => "Повернення страви",
+            (Self::ReturnedPlate, Language::En) => "Returned plate",
+            (Self::Overproduction, Language::Ru) => "Перепроизводство",
+            (Self::Overproduction, Language::Pl) => "Nadprodukcja",
+            (Self::Overproduction, Language::Uk) => "Перевиробництво",
+            (Self::Overproduction, Language::En) => "Overproduction",
+            (Self::Other, Language::Ru) => "Другое",
+            (Self::Other, Language::Pl) => "Inne",
+            (Self::Other, Language::Uk) => "Інше",
+            (Self::Other, Language::En) => "Other",
+        }
+    }
+}
+
+/// Display label of a loss-report reason code (waste reasons + auto expiry)
+pub fn loss_reason_label(code: &str, lang: Language) -> &'static str {
+    if code == AUTO_EXPIRY_REASON {
+        return match lang {
+            Language::Ru => "Автосписание по сроку годности",
+            Language::Pl => "Automatyczne odpisanie po terminie",
+            Language::Uk => "Автосписання за терміном придатності",
+            Language::En => "Auto-expired",
+        };
+    }
+    WasteReason::parse(code)
+        .unwrap_or(WasteReason::Other)
+        .label(lang)
+}
+
+/// Time bucket of the loss report timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LossBucket {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl LossBucket {
+    /// `date_trunc` field name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    /// Day buckets up to a month, weeks up to a quarter, months beyond
+    pub fn for_period(days: i32) -> Self {
+        match days {
+            d if d <= 31 => Self::Day,
+            d if d <= 92 => Self::Week,
+            _ => Self::Month,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waste_reason_roundtrip() {
+        for reason in WasteReason::ALL {
+            assert_eq!(WasteReason::parse(reason.as_str()).unwrap(), reason);
+            assert!(!reason.label(Language::Uk).is_empty());
+        }
+        assert!(WasteReason::parse(AUTO_EXPIRY_REASON).is_err());
+        assert!(WasteReason::parse("theft").is_err());
+        assert_eq!(
+            loss_reason_label(AUTO_EXPIRY_REASON, Language::En),
+            "Auto-expired"
+        );
+        assert_eq!(loss_reason_label("staff_meal", Language::En), "Staff meal");
+    }
+
+    #[test]
+    fn test_loss_bucket_for_period() {
+        assert_eq!(LossBucket::for_period(7), LossBucket::Day);
+        assert_eq!(LossBucket::for_period(31), LossBucket::Day);
+        assert_eq!(LossBucket::for_period(90), LossBucket::Week);
+        assert_eq!(LossBucket::for_period(365), LossBucket::Month);
+    }
+}
@@ -1,6 +1,9 @@
 use std::env;
 use tracing;
 
+use crate::infrastructure::llm::LlmConfig;
+use crate::infrastructure::mail::MailConfig;
+
 /// Insecure secrets that must never be used in production
 const INSECURE_SECRETS: &[&str] = &[
     "change_me",
@@ -20,6 +23,8 @@ pub struct Config {
     pub admin: AdminConfig,
     pub r2: R2Config,
     pub ai: AiConfig,
+    pub llm: LlmConfig,
+    pub mail: MailConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -155,6 +160,8 @@ impl Config {
                 groq_api_key: env::var("GROQ_API_KEY").unwrap_or_else(|_| "".to_string()),
                 gemini_api_key: env::var("GEMINI_API_KEY").unwrap_or_else(|_| "".to_string()),
             },
+            llm: LlmConfig::from_env()?,
+            mail: MailConfig::from_env()?,
         })
     }
 
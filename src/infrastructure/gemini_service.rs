@@ -63,7 +63,7 @@ impl GeminiService {
 
     /// Strip markdown code fences that Gemini 3 thinking models add around JSON.
     /// Handles ```json\n...\n```, ```\n...\n```, and nested variations.
-    fn strip_markdown_fences(text: &str) -> String {
+    pub(crate) fn strip_markdown_fences(text: &str) -> String {
         let trimmed = text.trim();
         // Check for ```json or ``` prefix
         let without_prefix = if trimmed.starts_with("```json") {
@@ -97,18 +97,7 @@ impl GeminiService {
         &self,
         ingredient_name: &str,
     ) -> Result<GroqTranslationResponse, AppError> {
-        if ingredient_name.len() > 50 {
-            return Err(AppError::validation(
-                "Ingredient name too long for automatic translation",
-            ));
-        }
-
-        let prompt = format!(
-            r#"Translate "{}" to Polish(pl), Russian(ru), Ukrainian(uk).
-Respond with ONLY valid JSON, no other text:
-{{"pl":"<Polish>","ru":"<Russian>","uk":"<Ukrainian>"}}"#,
-            ingredient_name
-        );
+        let prompt = Self::translation_prompt(ingredient_name)?;
 
         // Thinking models (gemini-3-flash) spend ~80% of max_tokens on chain-of-thought,
         // so we need much higher limits than the expected output size.
@@ -118,7 +107,7 @@ Respond with ONLY valid JSON, no other text:
 
         let content = self.send_with_retry(&body, 1).await?;
 
-        let translation: GroqTranslationResponse = self.parse_json_response(&content)?;
+        let translation: GroqTranslationResponse = Self::parse_json_response(&content)?;
 
         if translation.pl.trim().is_empty() {
             tracing::warn!(
@@ -137,37 +126,15 @@ Respond with ONLY valid JSON, no other text:
         text: &str,
         target_lang: &str,
     ) -> Result<String, AppError> {
-        if text.len() > 5000 {
-            return Err(AppError::validation("Text too long for translation"));
-        }
-
-        let prompt = format!(
-            r#"Translate the following text to {}.
-Return ONLY the translated text, nothing else.
-
-Text: {}"#,
-            target_lang, text
-        );
-
+        let prompt = Self::text_translation_prompt(text, target_lang)?;
         let body = self.build_request(&self.fast_model, &prompt, 0.0, 2000);
-
         let content = self.send_with_retry(&body, 1).await?;
-
-        let cleaned = content
-            .trim()
-            .trim_end_matches('.')
-            .trim_end_matches(',')
-            .trim()
-            .to_string();
-
-        Ok(cleaned)
+        Ok(Self::clean_translation(&content))
     }
 
     /// Analyze recipe — generate insights
     pub async fn analyze_recipe(&self, prompt: &str) -> Result<String, AppError> {
-        if prompt.len() > 10000 {
-            return Err(AppError::validation("Prompt too long for AI analysis"));
-        }
+        Self::check_analysis_prompt(prompt)?;
 
         let body = self.build_request(&self.smart_model, prompt, 0.3, 4000);
 
@@ -184,38 +151,7 @@ Text: {}"#,
         name_input: &str,
     ) -> Result<UnifiedProductResponse, AppError> {
         let trimmed = name_input.trim();
-        if trimmed.is_empty() {
-            return Err(AppError::validation("Input cannot be empty"));
-        }
-
-        let prompt = format!(
-            r#"You are a food product data extraction and classification AI.
-
-Input product name (may be in ANY language): "{}"
-
-Extract and classify the product. Return ONLY valid JSON, no other text:
-{{
-  "name_en": "<English product name>",
-  "name_pl": "<Polish translation>",
-  "name_ru": "<Russian translation>",
-  "name_uk": "<Ukrainian translation>",
-  "category_slug": "<category>",
-  "unit": "<unit>",
-  "confidence": 0.95
-}}
-
-Categories: dairy_and_eggs, fruits, vegetables, meat, seafood, grains, beverages
-Units: piece, kilogram, gram, liter, milliliter
-
-Rules:
-1. name_en MUST be in English (translate if needed)
-2. All translations must be single words when possible, but allow 2-3 word compounds
-3. category_slug must be one of the allowed values
-4. unit must be one of the allowed values
-5. confidence must be a float between 0.0 and 1.0 indicating how sure you are about the classification
-6. Do not add explanations, just JSON"#,
-            trimmed
-        );
+        let prompt = Self::unified_prompt(trimmed)?;
 
         let body = self.build_request(&self.fast_model, &prompt, 0.0, 4000);
 
@@ -223,9 +159,9 @@ Rules:
 
         let content = self.send_with_retry(&body, 1).await?;
 
-        let result: UnifiedProductResponse = self.parse_json_response(&content)?;
+        let result: UnifiedProductResponse = Self::parse_json_response(&content)?;
 
-        self.validate_unified_response(&result)?;
+        Self::validate_unified_response(&result)?;
 
         tracing::info!(
             "✅ Gemini unified OK: {} → en={}, cat={}, unit={}",
@@ -263,7 +199,7 @@ Pick the best match. Do not invent values."#,
 
         let body = self.build_request(&self.fast_model, &prompt, 0.0, 2000);
         let content = self.send_with_retry(&body, 1).await?;
-        let classification: AiClassification = self.parse_json_response(&content)?;
+        let classification: AiClassification = Self::parse_json_response(&content)?;
 
         tracing::info!(
             "✅ Gemini classification: cat={}, unit={}",
@@ -993,6 +929,104 @@ STRICTLY EXCLUDE:
             })
     }
 
+    // ── Prompts (shared with LlmAdapter, which sends them via LlmGateway) ──
+
+    pub(crate) fn translation_prompt(ingredient_name: &str) -> Result<String, AppError> {
+        if ingredient_name.len() > 50 {
+            return Err(AppError::validation(
+                "Ingredient name too long for automatic translation",
+            ));
+        }
+        Ok(format!(
+            r#"Translate "{}" to Polish(pl), Russian(ru), Ukrainian(uk).
+Respond with ONLY valid JSON, no other text:
+{{"pl":"<Polish>","ru":"<Russian>","uk":"<Ukrainian>"}}"#,
+            ingredient_name
+        ))
+    }
+
+    pub(crate) fn text_translation_prompt(
+        text: &str,
+        target_lang: &str,
+    ) -> Result<String, AppError> {
+        if text.len() > 5000 {
+            return Err(AppError::validation("Text too long for translation"));
+        }
+        Ok(format!(
+            r#"Translate the following text to {}.
+Return ONLY the translated text, nothing else.
+
+Text: {}"#,
+            target_lang, text
+        ))
+    }
+
+    pub(crate) fn clean_translation(content: &str) -> String {
+        content
+            .trim()
+            .trim_end_matches('.')
+            .trim_end_matches(',')
+            .trim()
+            .to_string()
+    }
+
+    pub(crate) fn check_analysis_prompt(prompt: &str) -> Result<(), AppError> {
+        if prompt.len() > 10000 {
+            return Err(AppError::validation("Prompt too long for AI analysis"));
+        }
+        Ok(())
+    }
+
+    /// `trimmed` — product name already trimmed by the caller
+    pub(crate) fn unified_prompt(trimmed: &str) -> Result<String, AppError> {
+        if trimmed.is_empty() {
+            return Err(AppError::validation("Input cannot be empty"));
+        }
+
+        Ok(format!(
+            r#"You are a food product data extraction and classification AI.
+
+Input product name (may be in ANY language): "{}"
+
+Extract and classify the product. Return ONLY valid JSON, no other text:
+{{
+  "name_en": "<English product name>",
+  "name_pl": "<Polish translation>",
+  "name_ru": "<Russian translation>",
+  "name_uk": "<Ukrainian translation>",
+  "category_slug": "<category>",
+  "unit": "<unit>",
+  "confidence": 0.95
+}}
+
+Categories: dairy_and_eggs, fruits, vegetables, meat, seafood, grains, beverages
+Units: piece, kilogram, gram, liter, milliliter
+
+Rules:
+1. name_en MUST be in English (translate if needed)
+2. All translations must be single words when possible, but allow 2-3 word compounds
+3. category_slug must be one of the allowed values
+4. unit must be one of the allowed values
+5. confidence must be a float between 0.0 and 1.0 indicating how sure you are about the classification
+6. Do not add explanations, just JSON"#,
+            trimmed
+        ))
+    }
+
+    /// Image model used for the given quality (for usage stats)
+    pub fn image_model(&self, enhanced: bool) -> &str {
+        if enhanced {
+            &self.recipe_hero_image_model
+        } else {
+            &self.recipe_image_model
+        }
+    }
+
+    /// Model behind `analyze_image_json` / `analyze_images_json`
+    pub fn vision_model(&self) -> &str {
+        &self.smart_model
+    }
+
     // ── Internal helpers ────────────────────────────────────────────────────
 
     fn build_request(
@@ -1164,8 +1198,7 @@ STRICTLY EXCLUDE:
 
     /// Parse JSON from AI response with fallback extraction.
     /// Handles Gemini 3's tendency to wrap JSON in ```json code blocks.
-    fn parse_json_response<T: serde::de::DeserializeOwned>(
-        &self,
+    pub(crate) fn parse_json_response<T: serde::de::DeserializeOwned>(
         content: &str,
     ) -> Result<T, AppError> {
         // Step 1: Strip markdown code fences (```json ... ``` or ``` ... ```)
@@ -1209,7 +1242,7 @@ STRICTLY EXCLUDE:
             })
     }
 
-    fn validate_unified_response(&self, r: &UnifiedProductResponse) -> Result<(), AppError> {
+    pub(crate) fn validate_unified_response(r: &UnifiedProductResponse) -> Result<(), AppError> {
         if r.name_en.trim().is_empty() {
             return Err(AppError::internal("AI returned empty English name"));
         }
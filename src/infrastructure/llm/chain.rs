@@ -0,0 +1,197 @@
+//! Ordered provider chain with per-provider timeout and circuit breaker.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::{CircuitBreaker, LlmCompletion, LlmGateway, LlmProvider, LlmRequest};
+use crate::infrastructure::persistence::AiUsageStatsRepository;
+use crate::shared::{AppError, AppResult};
+
+struct ChainEntry {
+    provider: Arc<dyn LlmProvider>,
+    timeout: Duration,
+    breaker: CircuitBreaker,
+}
+
+pub struct ProviderChain {
+    entries: Vec<ChainEntry>,
+    usage_repo: Option<Arc<AiUsageStatsRepository>>,
+}
+
+impl ProviderChain {
+    pub fn new(usage_repo: Option<Arc<AiUsageStatsRepository>>) -> Self {
+        Self {
+            entries: Vec::new(),
+            usage_repo,
+        }
+    }
+
+    /// Append a provider; earlier providers are tried first
+    pub fn with_provider(
+        mut self,
+        provider: Arc<dyn LlmProvider>,
+        timeout: Duration,
+        breaker: CircuitBreaker,
+    ) -> Self {
+        self.entries.push(ChainEntry {
+            provider,
+            timeout,
+            breaker,
+        });
+        self
+    }
+
+    pub fn provider_names(&self) -> Vec<&str> {
+        self.entries.iter().map(|e| e.provider.name()).collect()
+    }
+
+    async fn record_usage(&self, endpoint: &str, completion: &LlmCompletion) {
+        let Some(repo) = &self.usage_repo else {
+            return;
+        };
+        if let Err(e) = repo.log_usage(endpoint, &completion.usage).await {
+            tracing::warn!("Failed to record LLM usage for {}: {:?}", endpoint, e);
+        }
+    }
+}
+
+#[async_trait]
+impl LlmGateway for ProviderChain {
+    async fn complete(&self, request: &LlmRequest) -> AppResult<LlmCompletion> {
+        if self.entries.is_empty() {
+            return Err(AppError::validation(
+                "No LLM provider is configured. Set GEMINI_API_KEY, GROQ_API_KEY or OPENAI_COMPAT_BASE_URL.",
+            ));
+        }
+
+        let mut last_error = None;
+        for entry in &self.entries {
+            let name = entry.provider.name();
+            if !entry.breaker.allow() {
+                tracing::debug!("LLM provider {} skipped: circuit open", name);
+                continue;
+            }
+
+            let start = Instant::now();
+            let error = match tokio::time::timeout(entry.timeout, entry.provider.complete(request))
+                .await
+            {
+                Ok(Ok(mut completion)) => {
+                    entry.breaker.record_success();
+                    completion.usage.duration_ms = start.elapsed().as_millis() as i32;
+                    self.record_usage(&request.endpoint, &completion).await;
+                    return Ok(completion);
+                }
+                Ok(Err(e)) => e,
+                Err(_) => {
+                    AppError::internal(format!("{} timeout ({}s)", name, entry.timeout.as_secs()))
+                }
+            };
+
+            entry.breaker.record_failure();
+            tracing::warn!(
+                "LLM provider {} failed for {}: {:?}{}",
+                name,
+                request.endpoint,
+                error,
+                if entry.breaker.is_open() {
+                    " (circuit opened)"
+                } else {
+                    ""
+                }
+            );
+            last_error = Some(error);
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| AppError::internal("All LLM providers are temporarily unavailable")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LlmUsage;
+    use super::*;
+
+    struct FakeProvider {
+        name: &'static str,
+        fails: bool,
+    }
+
+    #[async_trait]
+    impl LlmProvider for FakeProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn complete(&self, _request: &LlmRequest) -> AppResult<LlmCompletion> {
+            if self.fails {
+                return Err(AppError::internal("boom"));
+            }
+            Ok(LlmCompletion {
+                content: format!("from {}", self.name),
+                usage: LlmUsage {
+                    provider: self.name.to_string(),
+                    model: "m".to_string(),
+                    ..LlmUsage::default()
+                },
+            })
+        }
+    }
+
+    fn chain(providers: &[(&'static str, bool)], breaker_failures: u32) -> ProviderChain {
+        providers
+            .iter()
+            .fold(ProviderChain::new(None), |chain, (name, fails)| {
+                chain.with_provider(
+                    Arc::new(FakeProvider {
+                        name,
+                        fails: *fails,
+                    }),
+                    Duration::from_secs(5),
+                    CircuitBreaker::new(breaker_failures, Duration::from_secs(60)),
+                )
+            })
+    }
+
+    fn complete(chain: &ProviderChain) -> AppResult<LlmCompletion> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(chain.complete(&LlmRequest::new("test", "hi")))
+    }
+
+    #[test]
+    fn falls_back_to_next_provider() {
+        let chain = chain(&[("gemini", true), ("groq", false)], 3);
+        let completion = complete(&chain).unwrap();
+        assert_eq!(completion.content, "from groq");
+        assert_eq!(completion.usage.provider, "groq");
+    }
+
+    #[test]
+    fn open_breaker_skips_provider() {
+        let chain = chain(&[("gemini", true), ("groq", false)], 1);
+        complete(&chain).unwrap();
+        assert!(chain.entries[0].breaker.is_open());
+
+        let completion = complete(&chain).unwrap();
+        assert_eq!(completion.usage.provider, "groq");
+    }
+
+    #[test]
+    fn all_failing_returns_last_error() {
+        let chain = chain(&[("gemini", true), ("groq", true)], 3);
+        assert!(complete(&chain).is_err());
+    }
+
+    #[test]
+    fn empty_chain_is_a_configuration_error() {
+        let chain = chain(&[], 3);
+        assert!(matches!(complete(&chain), Err(AppError::Validation(_))));
+        assert!(chain.provider_names().is_empty());
+    }
+}
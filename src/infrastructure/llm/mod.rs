@@ -0,0 +1,266 @@
+//! LLM gateway.
+//!
+//! Every text completion (copilot, cook suggestions, `LlmAdapter`) goes
+//! through [`LlmGateway`] and never sees the provider. [`ProviderChain`]
+//! tries providers in `LLM_PROVIDERS` order; each one has its own timeout
+//! and a [`CircuitBreaker`], so a provider that keeps failing is skipped for
+//! a cooldown instead of adding its timeout to every request. Each answered
+//! call is written to `ai_usage_stats` with provider, model and tokens.
+//!
+//! All providers speak the OpenAI chat completions protocol: Gemini through
+//! its `/v1beta/openai` endpoint, Groq, and any compatible server
+//! (llama.cpp, Ollama, vLLM) configured with `OPENAI_COMPAT_BASE_URL`.
+//! Image generation and vision stay on `GeminiService`.
+
+pub mod chain;
+pub mod circuit_breaker;
+pub mod openai_compat;
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::domain::ai_ports::AiQuality;
+use crate::infrastructure::persistence::AiUsageStatsRepository;
+use crate::shared::AppResult;
+
+pub use chain::ProviderChain;
+pub use circuit_breaker::CircuitBreaker;
+pub use openai_compat::OpenAiCompatProvider;
+
+#[derive(Debug, Clone)]
+pub struct LlmRequest {
+    /// Label for `ai_usage_stats.endpoint`, e.g. `copilot_plan`
+    pub endpoint: String,
+    pub system: Option<String>,
+    pub prompt: String,
+    /// Fast → provider's fast model, Balanced/Best → its smart model
+    pub quality: AiQuality,
+    pub temperature: f32,
+    pub max_tokens: u32,
+    /// Ask for `response_format: json_object`
+    pub json: bool,
+}
+
+impl LlmRequest {
+    pub fn new(endpoint: impl Into<String>, prompt: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            system: None,
+            prompt: prompt.into(),
+            quality: AiQuality::Fast,
+            temperature: 0.0,
+            max_tokens: 2000,
+            json: false,
+        }
+    }
+
+    pub fn system(mut self, system: impl Into<String>) -> Self {
+        self.system = Some(system.into());
+        self
+    }
+
+    pub fn quality(mut self, quality: AiQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn json(mut self) -> Self {
+        self.json = true;
+        self
+    }
+}
+
+/// Who answered and what it cost
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LlmUsage {
+    pub provider: String,
+    pub model: String,
+    pub prompt_tokens: i32,
+    pub completion_tokens: i32,
+    pub total_tokens: i32,
+    pub duration_ms: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmCompletion {
+    pub content: String,
+    pub usage: LlmUsage,
+}
+
+/// What application code calls
+#[async_trait]
+pub trait LlmGateway: Send + Sync {
+    async fn complete(&self, request: &LlmRequest) -> AppResult<LlmCompletion>;
+}
+
+/// One backend in the chain. `duration_ms` is filled in by the chain.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &str;
+    async fn complete(&self, request: &LlmRequest) -> AppResult<LlmCompletion>;
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmProviderConfig {
+    /// `gemini`, `groq` or `openai`
+    pub name: String,
+    /// Base URL without `/chat/completions`
+    pub base_url: String,
+    /// Empty for local servers that need no auth
+    pub api_key: String,
+    pub fast_model: String,
+    pub smart_model: String,
+    pub timeout: Duration,
+}
+
+impl LlmProviderConfig {
+    pub fn model_for(&self, quality: AiQuality) -> &str {
+        match quality {
+            AiQuality::Fast => &self.fast_model,
+            AiQuality::Balanced | AiQuality::Best => &self.smart_model,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    /// In fallback order; providers without credentials are left out
+    pub providers: Vec<LlmProviderConfig>,
+    /// Consecutive failures that open a provider's breaker
+    pub breaker_failures: u32,
+    /// How long an open breaker skips the provider
+    pub breaker_cooldown: Duration,
+}
+
+impl LlmConfig {
+    /// `LLM_PROVIDERS=gemini,groq,openai` (the default) sets the order.
+    /// Credentials: `GEMINI_API_KEY`, `GROQ_API_KEY`, `OPENAI_COMPAT_BASE_URL`
+    /// (+ optional `OPENAI_COMPAT_API_KEY`, `OPENAI_COMPAT_MODEL`,
+    /// `OPENAI_COMPAT_SMART_MODEL`). Timeouts: `LLM_<NAME>_TIMEOUT_SECS`.
+    /// Breaker: `LLM_BREAKER_FAILURES`, `LLM_BREAKER_COOLDOWN_SECS`.
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        let order = env::var("LLM_PROVIDERS").unwrap_or_else(|_| "gemini,groq,openai".to_string());
+
+        let mut providers: Vec<LlmProviderConfig> = Vec::new();
+        for name in order
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+        {
+            let provider = match name.as_str() {
+                "gemini" => Self::provider_from_env(
+                    "gemini",
+                    "https://generativelanguage.googleapis.com/v1beta/openai".to_string(),
+                    env::var("GEMINI_API_KEY").unwrap_or_default(),
+                    ("gemini-3-flash-preview", "gemini-3.1-pro-preview"),
+                    90,
+                ),
+                "groq" => Self::provider_from_env(
+                    "groq",
+                    "https://api.groq.com/openai/v1".to_string(),
+                    env::var("GROQ_API_KEY").unwrap_or_default(),
+                    ("llama-3.1-8b-instant", "llama-3.3-70b-versatile"),
+                    30,
+                ),
+                "openai" => {
+                    let model = env::var("OPENAI_COMPAT_MODEL")
+                        .unwrap_or_else(|_| "gpt-4o-mini".to_string());
+                    let smart_model =
+                        env::var("OPENAI_COMPAT_SMART_MODEL").unwrap_or_else(|_| model.clone());
+                    env::var("OPENAI_COMPAT_BASE_URL")
+                        .ok()
+                        .filter(|url| !url.trim().is_empty())
+                        .map(|url| LlmProviderConfig {
+                            name: "openai".to_string(),
+                            base_url: url.trim().trim_end_matches('/').to_string(),
+                            api_key: env::var("OPENAI_COMPAT_API_KEY").unwrap_or_default(),
+                            fast_model: model,
+                            smart_model,
+                            timeout: timeout_from_env("openai", 120),
+                        })
+                }
+                other => return Err(format!("Unknown LLM provider: {}", other).into()),
+            };
+            if let Some(provider) = provider {
+                if !providers.iter().any(|p| p.name == provider.name) {
+                    providers.push(provider);
+                }
+            }
+        }
+
+        Ok(Self {
+            providers,
+            breaker_failures: env::var("LLM_BREAKER_FAILURES")
+                .ok()
+                .and_then(|v| v.parse::<u32>().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(3),
+            breaker_cooldown: Duration::from_secs(
+                env::var("LLM_BREAKER_COOLDOWN_SECS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(60),
+            ),
+        })
+    }
+
+    /// Hosted providers are only enabled when their API key is set
+    fn provider_from_env(
+        name: &str,
+        base_url: String,
+        api_key: String,
+        (fast_model, smart_model): (&str, &str),
+        default_timeout_secs: u64,
+    ) -> Option<LlmProviderConfig> {
+        if api_key.trim().is_empty() {
+            return None;
+        }
+        Some(LlmProviderConfig {
+            name: name.to_string(),
+            base_url,
+            api_key,
+            fast_model: fast_model.to_string(),
+            smart_model: smart_model.to_string(),
+            timeout: timeout_from_env(name, default_timeout_secs),
+        })
+    }
+}
+
+fn timeout_from_env(name: &str, default_secs: u64) -> Duration {
+    let secs = env::var(format!("LLM_{}_TIMEOUT_SECS", name.to_uppercase()))
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(default_secs);
+    Duration::from_secs(secs)
+}
+
+pub fn build_gateway(
+    config: &LlmConfig,
+    usage_repo: Option<Arc<AiUsageStatsRepository>>,
+) -> Arc<dyn LlmGateway> {
+    let chain = config
+        .providers
+        .iter()
+        .fold(ProviderChain::new(usage_repo), |chain, provider| {
+            chain.with_provider(
+                Arc::new(OpenAiCompatProvider::new(provider.clone())),
+                provider.timeout,
+                CircuitBreaker::new(config.breaker_failures, config.breaker_cooldown),
+            )
+        });
+    Arc::new(chain)
+}
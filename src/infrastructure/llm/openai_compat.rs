@@ -0,0 +1,230 @@
+//! OpenAI chat completions client — Gemini, Groq and self-hosted servers.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{LlmCompletion, LlmProvider, LlmProviderConfig, LlmRequest, LlmUsage};
+use crate::infrastructure::gemini_service::GeminiService;
+use crate::shared::{AppError, AppResult};
+
+pub struct OpenAiCompatProvider {
+    config: LlmProviderConfig,
+    http_client: reqwest::Client,
+}
+
+impl OpenAiCompatProvider {
+    pub fn new(config: LlmProviderConfig) -> Self {
+        // Total time is bounded by the chain's per-provider timeout
+        let http_client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to build HTTP client for LLM provider");
+        Self {
+            config,
+            http_client,
+        }
+    }
+
+    fn request_body(&self, request: &LlmRequest) -> Value {
+        let mut messages = Vec::new();
+        if let Some(system) = &request.system {
+            messages.push(json!({"role": "system", "content": system}));
+        }
+        messages.push(json!({"role": "user", "content": request.prompt}));
+
+        let mut body = json!({
+            "model": self.config.model_for(request.quality),
+            "messages": messages,
+            "temperature": request.temperature,
+            "max_tokens": request.max_tokens,
+        });
+        if request.json {
+            body["response_format"] = json!({"type": "json_object"});
+        }
+        body
+    }
+
+    fn parse_response(&self, model: &str, data: ChatResponse) -> AppResult<LlmCompletion> {
+        let name = &self.config.name;
+        let choice = data
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::internal(format!("No response from {}", name)))?;
+
+        let finish_reason = choice.finish_reason.as_deref().unwrap_or("unknown");
+        if finish_reason == "length" {
+            tracing::warn!(
+                "⚠️ {} output truncated (finish_reason=length) model={}",
+                name,
+                model
+            );
+        }
+
+        let content = choice.message.content.unwrap_or_default();
+        if content.trim().is_empty() {
+            return Err(AppError::internal(format!(
+                "{} returned empty response (finish_reason={})",
+                name, finish_reason
+            )));
+        }
+
+        let usage = data.usage.unwrap_or_default();
+        Ok(LlmCompletion {
+            // Thinking models wrap JSON in ```json fences; strip them once here
+            content: GeminiService::strip_markdown_fences(&content),
+            usage: LlmUsage {
+                provider: name.clone(),
+                model: data.model.unwrap_or_else(|| model.to_string()),
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                total_tokens: usage.total_tokens,
+                duration_ms: 0,
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatProvider {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn complete(&self, request: &LlmRequest) -> AppResult<LlmCompletion> {
+        let name = &self.config.name;
+        let model = self.config.model_for(request.quality);
+        tracing::debug!("📤 Sending {} request: model={}", name, model);
+
+        let mut http_request = self
+            .http_client
+            .post(format!("{}/chat/completions", self.config.base_url))
+            .json(&self.request_body(request));
+        if !self.config.api_key.is_empty() {
+            http_request =
+                http_request.header("Authorization", format!("Bearer {}", self.config.api_key));
+        }
+
+        let response = http_request
+            .send()
+            .await
+            .map_err(|e| AppError::internal(format!("{} API error: {}", name, e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unable to read error body".to_string());
+            return Err(AppError::internal(format!(
+                "{} API returned error: {} - {}",
+                name, status, error_body
+            )));
+        }
+
+        let data: ChatResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to parse {} response: {}", name, e)))?;
+        self.parse_response(model, data)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    #[serde(default)]
+    model: Option<String>,
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+/// Thinking models may return `content: null`
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: i32,
+    #[serde(default)]
+    completion_tokens: i32,
+    #[serde(default)]
+    total_tokens: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ai_ports::AiQuality;
+
+    fn provider() -> OpenAiCompatProvider {
+        OpenAiCompatProvider::new(LlmProviderConfig {
+            name: "groq".to_string(),
+            base_url: "http://localhost:1".to_string(),
+            api_key: String::new(),
+            fast_model: "small".to_string(),
+            smart_model: "large".to_string(),
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    #[test]
+    fn request_body_picks_model_by_quality() {
+        let request = LlmRequest::new("test", "hello")
+            .system("be brief")
+            .quality(AiQuality::Best)
+            .max_tokens(100)
+            .json();
+        let body = provider().request_body(&request);
+
+        assert_eq!(body["model"], "large");
+        assert_eq!(body["max_tokens"], 100);
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "hello");
+        assert_eq!(body["response_format"]["type"], "json_object");
+
+        let body = provider().request_body(&LlmRequest::new("test", "hello"));
+        assert_eq!(body["model"], "small");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+        assert!(body.get("response_format").is_none());
+    }
+
+    #[test]
+    fn parses_content_and_token_usage() {
+        let data: ChatResponse = serde_json::from_value(json!({
+            "model": "llama-3.1-8b-instant",
+            "choices": [{"message": {"content": "```json\n{\"a\":1}\n```"}, "finish_reason": "stop"}],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 5, "total_tokens": 17}
+        }))
+        .unwrap();
+        let completion = provider().parse_response("small", data).unwrap();
+
+        assert_eq!(completion.content, "{\"a\":1}");
+        assert_eq!(completion.usage.provider, "groq");
+        assert_eq!(completion.usage.model, "llama-3.1-8b-instant");
+        assert_eq!(completion.usage.total_tokens, 17);
+    }
+
+    #[test]
+    fn empty_content_is_an_error() {
+        let data: ChatResponse = serde_json::from_value(json!({
+            "choices": [{"message": {"content": null}, "finish_reason": "length"}]
+        }))
+        .unwrap();
+        assert!(provider().parse_response("small", data).is_err());
+    }
+}
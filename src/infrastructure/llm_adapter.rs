@@ -1,6 +1,8 @@
+use crate::domain::ai_ports::AiQuality;
 use crate::domain::classification_rules::ClassificationRules;
 use crate::infrastructure::gemini_service::GeminiService;
 use crate::infrastructure::groq_service::{GroqTranslationResponse, UnifiedProductResponse};
+use crate::infrastructure::llm::{LlmCompletion, LlmGateway, LlmRequest, LlmUsage};
 use crate::infrastructure::persistence::{AiCacheRepository, AiUsageStatsRepository};
 use crate::shared::AppError;
 use serde_json::to_value;
@@ -8,9 +10,12 @@ use std::sync::Arc;
 use std::time::Instant;
 use tokio::time::{timeout, Duration};
 
+/// Text generation goes through the `LlmGateway` provider chain (which logs
+/// its own usage); images and vision are Gemini-only.
 #[derive(Clone)]
 pub struct LlmAdapter {
     gemini_service: Arc<GeminiService>,
+    gateway: Arc<dyn LlmGateway>,
     cache_repo: Arc<AiCacheRepository>,
     usage_repo: Arc<AiUsageStatsRepository>,
 }
@@ -18,22 +23,40 @@ pub struct LlmAdapter {
 impl LlmAdapter {
     pub fn new(
         gemini_service: Arc<GeminiService>,
+        gateway: Arc<dyn LlmGateway>,
         cache_repo: Arc<AiCacheRepository>,
         usage_repo: Arc<AiUsageStatsRepository>,
     ) -> Self {
         Self {
             gemini_service,
+            gateway,
             cache_repo,
             usage_repo,
         }
     }
 
-    /// Helper to log usage stats
-    async fn log_usage(&self, endpoint: &str, duration_ms: i32) {
-        let _ = self
-            .usage_repo
-            .log_usage(endpoint, 0, 0, 0, duration_ms)
-            .await;
+    /// Helper to log usage stats of Gemini image / vision calls
+    async fn log_usage(&self, endpoint: &str, model: &str, duration_ms: i32) {
+        let usage = LlmUsage {
+            provider: "gemini".to_string(),
+            model: model.to_string(),
+            duration_ms,
+            ..LlmUsage::default()
+        };
+        let _ = self.usage_repo.log_usage(endpoint, &usage).await;
+    }
+
+    /// Text completion through the provider chain
+    pub async fn complete(&self, request: LlmRequest) -> Result<String, AppError> {
+        Ok(self.complete_with_usage(request).await?.content)
+    }
+
+    /// Like `complete`, but also returns which provider/model answered
+    pub async fn complete_with_usage(
+        &self,
+        request: LlmRequest,
+    ) -> Result<LlmCompletion, AppError> {
+        self.gateway.complete(&request).await
     }
 
     /// Translation for a product to all supported UI languages (using cache)
@@ -50,20 +73,24 @@ impl LlmAdapter {
             }
         }
 
-        let start = Instant::now();
-        let response = timeout(
-            Duration::from_secs(10),
-            self.gemini_service.translate(ingredient_name),
-        )
-        .await
-        .map_err(|_| AppError::internal("LLM Timeout: Translation took too long"))??;
-
-        let duration_ms = start.elapsed().as_millis() as i32;
-        self.log_usage("translate_all", duration_ms).await;
+        // Thinking models spend most of max_tokens on chain-of-thought
+        let request = LlmRequest::new(
+            "translate_all",
+            GeminiService::translation_prompt(ingredient_name)?,
+        );
+        let completion = self.complete_with_usage(request).await?;
+        let response: GroqTranslationResponse =
+            GeminiService::parse_json_response(&completion.content)?;
 
         let val = to_value(&response).map_err(|e| AppError::internal(e.to_string()))?;
         self.cache_repo
-            .set(&cache_key, val, "gemini", "gemini-3-flash-preview", 90)
+            .set(
+                &cache_key,
+                val,
+                &completion.usage.provider,
+                &completion.usage.model,
+                90,
+            )
             .await?;
 
         Ok(response)
@@ -90,16 +117,17 @@ impl LlmAdapter {
             tracing::info!("🚀 Rule Engine matched category/unit for: {}", name);
         }
 
-        // 3. LLM (Gemini) — ALWAYS called for translations
-        tracing::info!("🔮 Gemini LLM call for: {}", name);
-        let start = Instant::now();
-
-        let mut response = timeout(
-            Duration::from_secs(15),
-            self.gemini_service.process_unified(name),
+        // 3. LLM — ALWAYS called for translations
+        tracing::info!("🔮 LLM call for: {}", name);
+        let request = LlmRequest::new(
+            "process_unified",
+            GeminiService::unified_prompt(name.trim())?,
         )
-        .await
-        .map_err(|_| AppError::internal("LLM Timeout: Unified processing took too long"))??;
+        .max_tokens(4000);
+        let completion = self.complete_with_usage(request).await?;
+        let mut response: UnifiedProductResponse =
+            GeminiService::parse_json_response(&completion.content)?;
+        GeminiService::validate_unified_response(&response)?;
 
         // Override category/unit with Rule Engine hints if available (more reliable)
         if let Some(rule) = rule_hint {
@@ -113,12 +141,15 @@ impl LlmAdapter {
             response.confidence = 1.0;
         }
 
-        let duration_ms = start.elapsed().as_millis() as i32;
-        self.log_usage("process_unified", duration_ms).await;
-
         let val = to_value(&response).map_err(|e| AppError::internal(e.to_string()))?;
         self.cache_repo
-            .set(&cache_key, val, "gemini", "gemini-3-flash-preview", 90)
+            .set(
+                &cache_key,
+                val,
+                &completion.usage.provider,
+                &completion.usage.model,
+                90,
+            )
             .await?;
 
         Ok(response)
@@ -139,23 +170,19 @@ impl LlmAdapter {
             }
         }
 
-        let start = Instant::now();
-        let translated = timeout(
-            Duration::from_secs(15),
-            self.gemini_service.translate_to_language(text, target_lang),
-        )
-        .await
-        .map_err(|_| AppError::internal("LLM Timeout: Text translation took too long"))??;
-
-        let duration_ms = start.elapsed().as_millis() as i32;
-        self.log_usage("translate_to_language", duration_ms).await;
+        let request = LlmRequest::new(
+            "translate_to_language",
+            GeminiService::text_translation_prompt(text, target_lang)?,
+        );
+        let completion = self.complete_with_usage(request).await?;
+        let translated = GeminiService::clean_translation(&completion.content);
 
         self.cache_repo
             .set(
                 &cache_key,
                 serde_json::Value::String(translated.clone()),
-                "gemini",
-                "gemini-3-flash-preview",
+                &completion.usage.provider,
+                &completion.usage.model,
                 90,
             )
             .await?;
@@ -174,23 +201,20 @@ impl LlmAdapter {
             }
         }
 
-        let start = Instant::now();
-        let analysis = timeout(
-            Duration::from_secs(30),
-            self.gemini_service.analyze_recipe(prompt),
-        )
-        .await
-        .map_err(|_| AppError::internal("LLM Timeout: Recipe analysis took too long"))??;
-
-        let duration_ms = start.elapsed().as_millis() as i32;
-        self.log_usage("analyze_recipe", duration_ms).await;
+        GeminiService::check_analysis_prompt(prompt)?;
+        let request = LlmRequest::new("analyze_recipe", prompt)
+            .quality(AiQuality::Balanced)
+            .temperature(0.3)
+            .max_tokens(4000);
+        let completion = self.complete_with_usage(request).await?;
+        let analysis = completion.content;
 
         self.cache_repo
             .set(
                 &cache_key,
                 serde_json::Value::String(analysis.clone()),
-                "gemini",
-                "gemini-3.1-pro-preview",
+                &completion.usage.provider,
+                &completion.usage.model,
                 30,
             )
             .await?;
@@ -229,7 +253,12 @@ impl LlmAdapter {
         .map_err(|_| AppError::internal("LLM Timeout: Dish image generation took too long"))??;
 
         let duration_ms = start.elapsed().as_millis() as i32;
-        self.log_usage("generate_dish_image", duration_ms).await;
+        self.log_usage(
+            "generate_dish_image",
+            self.gemini_service.image_model(false),
+            duration_ms,
+        )
+        .await;
 
         // Cache for 90 days — dish images rarely need refreshing
         self.cache_repo
@@ -270,6 +299,7 @@ impl LlmAdapter {
 
         self.log_usage(
             "generate_catalog_product_image",
+            self.gemini_service.image_model(false),
             start.elapsed().as_millis() as i32,
         )
         .await;
@@ -316,6 +346,7 @@ impl LlmAdapter {
             } else {
                 "generate_blog_article_image"
             },
+            self.gemini_service.image_model(enhanced),
             start.elapsed().as_millis() as i32,
         )
         .await;
@@ -345,6 +376,7 @@ impl LlmAdapter {
         })??;
         self.log_usage(
             "generate_icon_product_mockup_image",
+            &result.1,
             start.elapsed().as_millis() as i32,
         )
         .await;
@@ -369,6 +401,7 @@ impl LlmAdapter {
         })??;
         self.log_usage(
             "generate_material_scene_image",
+            self.gemini_service.image_model(false),
             start.elapsed().as_millis() as i32,
         )
         .await;
@@ -393,6 +426,7 @@ impl LlmAdapter {
         })??;
         self.log_usage(
             "generate_calendar_day_image",
+            self.gemini_service.image_model(false),
             start.elapsed().as_millis() as i32,
         )
         .await;
@@ -426,6 +460,7 @@ impl LlmAdapter {
         })??;
         self.log_usage(
             "generate_construction_project_image",
+            self.gemini_service.image_model(enhanced),
             start.elapsed().as_millis() as i32,
         )
         .await;
@@ -451,34 +486,23 @@ impl LlmAdapter {
         max_tokens: u32,
         model: &str,
     ) -> Result<String, AppError> {
-        let start = Instant::now();
-
-        // Thinking models (Pro) sometimes leak chain-of-thought text into the output.
-        // A system message with strict "JSON only" instruction reduces this.
-        let messages = if model.contains("pro") {
-            serde_json::json!([
-                {"role": "system", "content": "You are a JSON API. Return ONLY valid JSON — no markdown, no explanations, no thinking, no commentary. If the user asks for an array, return [...]. If the user asks for an object, return {...}."},
-                {"role": "user", "content": prompt}
-            ])
+        // The model name picks a tier; each provider maps it to its own model
+        let quality = if model.contains("pro") {
+            AiQuality::Balanced
         } else {
-            serde_json::json!([{"role": "user", "content": prompt}])
+            AiQuality::Fast
         };
+        let mut request = LlmRequest::new(format!("raw_{}", model), prompt)
+            .quality(quality)
+            .temperature(0.1)
+            .max_tokens(max_tokens);
 
-        let request_body = serde_json::json!({
-            "model": model,
-            "messages": messages,
-            "temperature": 0.1,
-            "max_tokens": max_tokens,
-        });
-        let result = tokio::time::timeout(
-            Duration::from_secs(120),
-            self.gemini_service.send_raw_request(&request_body),
-        )
-        .await
-        .map_err(|_| AppError::internal(&format!("AI timeout (120s) for model {}", model)))??;
-        let duration_ms = start.elapsed().as_millis() as i32;
-        self.log_usage(&format!("raw_{}", model), duration_ms).await;
-        Ok(result)
+        // Thinking models (Pro) sometimes leak chain-of-thought text into the output.
+        // A system message with strict "JSON only" instruction reduces this.
+        if quality != AiQuality::Fast {
+            request = request.system("You are a JSON API. Return ONLY valid JSON — no markdown, no explanations, no thinking, no commentary. If the user asks for an array, return [...]. If the user asks for an object, return {...}.");
+        }
+        self.complete(request).await
     }
 
     pub async fn analyze_image_json(
@@ -495,8 +519,12 @@ impl LlmAdapter {
         )
         .await
         .map_err(|_| AppError::internal("AI timeout (125s) for Gemini Vision"))??;
-        self.log_usage("gemini_vision_json", start.elapsed().as_millis() as i32)
-            .await;
+        self.log_usage(
+            "gemini_vision_json",
+            self.gemini_service.vision_model(),
+            start.elapsed().as_millis() as i32,
+        )
+        .await;
         Ok(result)
     }
 
@@ -512,8 +540,12 @@ impl LlmAdapter {
         )
         .await
         .map_err(|_| AppError::internal("AI timeout (125s) for Gemini Vision"))??;
-        self.log_usage("gemini_vision_json", start.elapsed().as_millis() as i32)
-            .await;
+        self.log_usage(
+            "gemini_vision_json",
+            self.gemini_service.vision_model(),
+            start.elapsed().as_millis() as i32,
+        )
+        .await;
         Ok(result)
     }
 }
@@ -0,0 +1,310 @@
+//! Outgoing email.
+//!
+//! Application code sends through the [`Mailer`] trait and never sees the
+//! transport. [`SmtpMailer`] talks to a real relay; [`OutboxMailer`] writes
+//! `.eml` files (or only logs) for development and tests. `MAIL_TRANSPORT`
+//! picks one at startup.
+
+pub mod outbox;
+pub mod smtp;
+
+use std::env;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::Engine;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
+
+use crate::shared::{AppError, AppResult};
+
+pub use outbox::OutboxMailer;
+pub use smtp::{SmtpConfig, SmtpMailer, SmtpSecurity};
+
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: Option<String>,
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> AppResult<()>;
+}
+
+#[derive(Debug, Clone)]
+pub enum MailTransport {
+    /// Log only, nothing is written
+    Log,
+    /// One `.eml` file per message in this directory
+    File(String),
+    Smtp(SmtpConfig),
+}
+
+#[derive(Debug, Clone)]
+pub struct MailConfig {
+    pub transport: MailTransport,
+    /// `From:` header, e.g. `Kitchen <no-reply@example.com>`
+    pub from: String,
+    /// Frontend base URL used in links sent by email
+    pub app_url: String,
+}
+
+impl MailConfig {
+    /// `MAIL_TRANSPORT=log|file|smtp` (default `log`), plus `MAIL_FROM`,
+    /// `APP_PUBLIC_URL`, `MAIL_OUTBOX_DIR` and `SMTP_*` for the transport.
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        let transport = match env::var("MAIL_TRANSPORT")
+            .unwrap_or_else(|_| "log".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "smtp" => MailTransport::Smtp(SmtpConfig::from_env()?),
+            "file" => MailTransport::File(
+                env::var("MAIL_OUTBOX_DIR").unwrap_or_else(|_| "./outbox".to_string()),
+            ),
+            "log" => MailTransport::Log,
+            other => return Err(format!("Unknown MAIL_TRANSPORT: {}", other).into()),
+        };
+
+        Ok(Self {
+            transport,
+            from: env::var("MAIL_FROM").unwrap_or_else(|_| "no-reply@localhost".to_string()),
+            app_url: env::var("APP_PUBLIC_URL")
+                .unwrap_or_else(|_| "http://localhost:5173".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+        })
+    }
+}
+
+pub fn build_mailer(config: &MailConfig) -> Arc<dyn Mailer> {
+    match &config.transport {
+        MailTransport::Log => Arc::new(OutboxMailer::log_only(&config.from)),
+        MailTransport::File(dir) => Arc::new(OutboxMailer::new(dir, &config.from)),
+        MailTransport::Smtp(smtp) => Arc::new(SmtpMailer::new(smtp.clone(), &config.from)),
+    }
+}
+
+impl EmailMessage {
+    /// Every transport calls this before anything is written: a CR or LF in
+    /// a header value would start a new header (or SMTP command).
+    pub fn validate(&self, from: &str) -> AppResult<()> {
+        check_header("From", from)?;
+        check_header("To", &self.to)?;
+        check_header("Subject", &self.subject)?;
+        for attachment in &self.attachments {
+            check_header("Attachment filename", &attachment.filename)?;
+            check_header("Attachment content type", &attachment.content_type)?;
+        }
+        Ok(())
+    }
+
+    /// RFC 5322 message: UTF-8 subject, `multipart/alternative` when an
+    /// HTML body is present, `multipart/mixed` around it when there are
+    /// attachments, base64 bodies so line length never matters.
+    pub fn to_mime(&self, from: &str) -> AppResult<String> {
+        self.validate(from)?;
+        let now = OffsetDateTime::now_utc();
+        let date = now.format(&Rfc2822).unwrap_or_default();
+        let domain = from
+            .rsplit('@')
+            .next()
+            .map(|d| d.trim_end_matches('>'))
+            .unwrap_or("localhost");
+
+        let mut mime = String::new();
+        mime.push_str(&format!("From: {}\r\n", from));
+        mime.push_str(&format!("To: {}\r\n", self.to));
+        mime.push_str(&format!("Subject: {}\r\n", encode_header(&self.subject)));
+        mime.push_str(&format!("Date: {}\r\n", date));
+        mime.push_str(&format!(
+            "Message-ID: <{}@{}>\r\n",
+            uuid::Uuid::new_v4(),
+            domain
+        ));
+        mime.push_str("MIME-Version: 1.0\r\n");
+
+        if self.attachments.is_empty() {
+            mime.push_str(&self.body_part());
+            return Ok(mime);
+        }
+
+        let boundary = format!("=_{}", uuid::Uuid::new_v4().simple());
+        mime.push_str(&format!(
+            "Content-Type: multipart/mixed; boundary=\"{}\"\r\n\r\n",
+            boundary
+        ));
+        mime.push_str(&format!("--{}\r\n", boundary));
+        mime.push_str(&self.body_part());
+        for attachment in &self.attachments {
+            let filename = encode_header(&attachment.filename);
+            mime.push_str(&format!("--{}\r\n", boundary));
+            mime.push_str(&format!(
+                "Content-Type: {}; name=\"{}\"\r\n",
+                attachment.content_type, filename
+            ));
+            mime.push_str(&format!(
+                "Content-Disposition: attachment; filename=\"{}\"\r\n",
+                filename
+            ));
+            mime.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+            mime.push_str(&encode_body(&attachment.data));
+        }
+        mime.push_str(&format!("--{}--\r\n", boundary));
+        Ok(mime)
+    }
+
+    /// Text body, or text + HTML as `multipart/alternative`
+    fn body_part(&self) -> String {
+        let mut part = String::new();
+        match &self.html_body {
+            None => {
+                part.push_str(&part_headers("text/plain"));
+                part.push_str(&encode_body(self.text_body.as_bytes()));
+            }
+            Some(html) => {
+                let boundary = format!("=_{}", uuid::Uuid::new_v4().simple());
+                part.push_str(&format!(
+                    "Content-Type: multipart/alternative; boundary=\"{}\"\r\n\r\n",
+                    boundary
+                ));
+                for (content_type, body) in [("text/plain", &self.text_body), ("text/html", html)] {
+                    part.push_str(&format!("--{}\r\n", boundary));
+                    part.push_str(&part_headers(content_type));
+                    part.push_str(&encode_body(body.as_bytes()));
+                }
+                part.push_str(&format!("--{}--\r\n", boundary));
+            }
+        }
+        part
+    }
+}
+
+fn part_headers(content_type: &str) -> String {
+    format!(
+        "Content-Type: {}; charset=utf-8\r\nContent-Transfer-Encoding: base64\r\n\r\n",
+        content_type
+    )
+}
+
+fn check_header(name: &str, value: &str) -> AppResult<()> {
+    if value.chars().any(char::is_control) {
+        return Err(AppError::validation(format!(
+            "{} must not contain control characters",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// RFC 2047 encoded-word for non-ASCII header values, and for values with
+/// quotes that would end a quoted `filename="..."` parameter
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() && !value.contains(['"', '\\']) {
+        value.to_string()
+    } else {
+        format!(
+            "=?UTF-8?B?{}?=",
+            base64::engine::general_purpose::STANDARD.encode(value.as_bytes())
+        )
+    }
+}
+
+/// Base64 wrapped at 76 characters per line
+fn encode_body(body: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(body);
+    let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 76 * 2 + 2);
+    for chunk in encoded.as_bytes().chunks(76) {
+        wrapped.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        wrapped.push_str("\r\n");
+    }
+    wrapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mime_encodes_utf8_subject_and_alternative_parts() {
+        let message = EmailMessage {
+            to: "cook@example.com".to_string(),
+            subject: "Сброс пароля".to_string(),
+            text_body: "plain".to_string(),
+            html_body: Some("<p>html</p>".to_string()),
+            attachments: Vec::new(),
+        };
+        let mime = message.to_mime("Kitchen <no-reply@example.com>").unwrap();
+
+        assert!(mime.contains("Subject: =?UTF-8?B?"));
+        assert!(mime.contains("@example.com>\r\n"));
+        assert!(mime.contains("multipart/alternative"));
+        assert_eq!(mime.matches("Content-Transfer-Encoding: base64").count(), 2);
+        assert!(mime.lines().all(|line| line.len() <= 998));
+    }
+
+    #[test]
+    fn attachments_make_a_mixed_message() {
+        let message = EmailMessage {
+            to: "owner@example.com".to_string(),
+            subject: "Weekly summary".to_string(),
+            text_body: "see attached".to_string(),
+            html_body: None,
+            attachments: vec![Attachment {
+                filename: "summary.csv".to_string(),
+                content_type: "text/csv".to_string(),
+                data: b"a,b\r\n1,2\r\n".to_vec(),
+            }],
+        };
+        let mime = message.to_mime("no-reply@example.com").unwrap();
+
+        assert!(mime.contains("multipart/mixed"));
+        assert!(mime.contains("Content-Disposition: attachment; filename=\"summary.csv\""));
+        assert_eq!(mime.matches("Content-Transfer-Encoding: base64").count(), 2);
+    }
+
+    #[test]
+    fn ascii_subject_is_left_as_is() {
+        assert_eq!(encode_header("Reset your password"), "Reset your password");
+        assert!(encode_header("a\".csv").starts_with("=?UTF-8?B?"));
+    }
+
+    #[test]
+    fn line_breaks_in_headers_are_refused() {
+        let message = |to: &str, subject: &str, filename: &str| EmailMessage {
+            to: to.to_string(),
+            subject: subject.to_string(),
+            text_body: "body\r\nwith lines".to_string(),
+            html_body: None,
+            attachments: vec![Attachment {
+                filename: filename.to_string(),
+                content_type: "text/csv".to_string(),
+                data: Vec::new(),
+            }],
+        };
+        let from = "no-reply@example.com";
+
+        assert!(message("cook@example.com", "Hi", "a.csv").to_mime(from).is_ok());
+        for bad in [
+            message("cook@example.com\r\nBcc: x@evil.com", "Hi", "a.csv"),
+            message("cook@example.com", "Hi\nBcc: x@evil.com", "a.csv"),
+            message("cook@example.com", "Hi", "a.csv\r\nX-Evil: 1"),
+        ] {
+            assert!(matches!(bad.to_mime(from), Err(AppError::Validation(_))));
+        }
+        assert!(message("cook@example.com", "Hi", "a.csv")
+            .to_mime("no-reply@example.com\r\nBcc: x@evil.com")
+            .is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Development/test [`Mailer`]: logs every message and, when a directory is
+//! configured, writes it there as an `.eml` file that any mail client opens.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use tokio::fs;
+
+use super::{EmailMessage, Mailer};
+use crate::shared::{AppError, AppResult};
+
+#[derive(Clone)]
+pub struct OutboxMailer {
+    dir: Option<PathBuf>,
+    from: String,
+}
+
+impl OutboxMailer {
+    pub fn new(dir: impl Into<PathBuf>, from: &str) -> Self {
+        Self {
+            dir: Some(dir.into()),
+            from: from.to_string(),
+        }
+    }
+
+    pub fn log_only(from: &str) -> Self {
+        Self {
+            dir: None,
+            from: from.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for OutboxMailer {
+    async fn send(&self, message: &EmailMessage) -> AppResult<()> {
+        message.validate(&self.from)?;
+        tracing::info!(
+            "📧 Mail to={} subject={:?}\n{}",
+            message.to,
+            message.subject,
+            message.text_body
+        );
+
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        fs::create_dir_all(dir)
+            .await
+            .map_err(|e| AppError::internal(format!("mail outbox: create {dir:?}: {e}")))?;
+
+        let file = dir.join(format!(
+            "{}-{}.eml",
+            OffsetDateTime::now_utc().unix_timestamp(),
+            uuid::Uuid::new_v4().simple()
+        ));
+        fs::write(&file, message.to_mime(&self.from)?)
+            .await
+            .map_err(|e| AppError::internal(format!("mail outbox: write {file:?}: {e}")))?;
+        Ok(())
+    }
+}
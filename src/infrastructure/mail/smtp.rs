@@ -0,0 +1,319 @@
+//! Minimal SMTP submission client (RFC 5321) over rustls.
+//!
+//! One connection per message: EHLO, optional STARTTLS, AUTH PLAIN/LOGIN,
+//! MAIL FROM / RCPT TO / DATA, QUIT. Volume here is a handful of account
+//! emails per day, so pooling is not worth the complexity.
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::Engine;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{self, ClientConfig, OwnedTrustAnchor, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+use super::{EmailMessage, Mailer};
+use crate::shared::{AppError, AppResult};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// TLS from the first byte (port 465)
+    Tls,
+    /// Plain connect, then upgrade (port 587)
+    StartTls,
+    /// Local relays and test servers only
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub security: SmtpSecurity,
+}
+
+impl SmtpConfig {
+    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        let security = match env::var("SMTP_SECURITY")
+            .unwrap_or_else(|_| "starttls".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "tls" | "ssl" => SmtpSecurity::Tls,
+            "starttls" => SmtpSecurity::StartTls,
+            "none" => SmtpSecurity::None,
+            other => return Err(format!("Unknown SMTP_SECURITY: {}", other).into()),
+        };
+        let default_port = match security {
+            SmtpSecurity::Tls => "465",
+            SmtpSecurity::StartTls => "587",
+            SmtpSecurity::None => "25",
+        };
+
+        Ok(Self {
+            host: env::var("SMTP_HOST")?,
+            port: env::var("SMTP_PORT")
+                .unwrap_or_else(|_| default_port.to_string())
+                .parse()?,
+            username: env::var("SMTP_USERNAME").ok().filter(|v| !v.is_empty()),
+            password: env::var("SMTP_PASSWORD").ok().filter(|v| !v.is_empty()),
+            security,
+        })
+    }
+}
+
+trait Io: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Io for T {}
+
+#[derive(Clone)]
+pub struct SmtpMailer {
+    config: SmtpConfig,
+    from: String,
+    tls: TlsConnector,
+}
+
+impl SmtpMailer {
+    pub fn new(config: SmtpConfig, from: &str) -> Self {
+        let mut roots = RootCertStore::empty();
+        roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }));
+        let tls_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Self {
+            config,
+            from: from.to_string(),
+            tls: TlsConnector::from(Arc::new(tls_config)),
+        }
+    }
+
+    async fn deliver(&self, message: &EmailMessage) -> AppResult<()> {
+        // Everything that goes on the wire is checked before connecting
+        let mime = message.to_mime(&self.from)?;
+        let from = envelope_address(&self.from)?;
+        let to = envelope_address(&message.to)?;
+
+        let tcp = TcpStream::connect((self.config.host.as_str(), self.config.port))
+            .await
+            .map_err(|e| smtp_error(format!("connect: {e}")))?;
+
+        let stream: Box<dyn Io> = match self.config.security {
+            SmtpSecurity::Tls => Box::new(self.handshake(tcp).await?),
+            _ => Box::new(tcp),
+        };
+        let mut conn = Connection {
+            stream: BufReader::new(stream),
+        };
+        conn.expect(220).await?;
+        let mut features = conn.ehlo().await?;
+
+        if self.config.security == SmtpSecurity::StartTls {
+            conn.command("STARTTLS", 220).await?;
+            let plain = conn.stream.into_inner();
+            let tls = self.handshake(plain).await?;
+            conn = Connection {
+                stream: BufReader::new(Box::new(tls)),
+            };
+            features = conn.ehlo().await?;
+        }
+
+        if let (Some(user), Some(pass)) = (&self.config.username, &self.config.password) {
+            conn.authenticate(&features, user, pass).await?;
+        }
+
+        conn.command(&format!("MAIL FROM:<{}>", from), 250).await?;
+        conn.command(&format!("RCPT TO:<{}>", to), 250).await?;
+        conn.command("DATA", 354).await?;
+        conn.write(&dot_stuff(&mime)).await?;
+        conn.command(".", 250).await?;
+        // The message is accepted at this point; a failed QUIT changes nothing
+        let _ = conn.command("QUIT", 221).await;
+        Ok(())
+    }
+
+    async fn handshake<S: Io>(&self, stream: S) -> AppResult<tokio_rustls::client::TlsStream<S>> {
+        let server_name = rustls::ServerName::try_from(self.config.host.as_str())
+            .map_err(|e| smtp_error(format!("invalid host: {e}")))?;
+        self.tls
+            .connect(server_name, stream)
+            .await
+            .map_err(|e| smtp_error(format!("TLS: {e}")))
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: &EmailMessage) -> AppResult<()> {
+        tokio::time::timeout(TIMEOUT, self.deliver(message))
+            .await
+            .map_err(|_| smtp_error("timed out"))?
+    }
+}
+
+struct Connection {
+    stream: BufReader<Box<dyn Io>>,
+}
+
+impl Connection {
+    async fn write(&mut self, data: &str) -> AppResult<()> {
+        let stream = self.stream.get_mut();
+        stream
+            .write_all(data.as_bytes())
+            .await
+            .map_err(|e| smtp_error(format!("write: {e}")))?;
+        stream
+            .flush()
+            .await
+            .map_err(|e| smtp_error(format!("write: {e}")))
+    }
+
+    /// Reads a (possibly multi-line) reply and returns its text lines
+    async fn expect(&mut self, code: u16) -> AppResult<Vec<String>> {
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            let read = self
+                .stream
+                .read_line(&mut line)
+                .await
+                .map_err(|e| smtp_error(format!("read: {e}")))?;
+            if read == 0 {
+                return Err(smtp_error("connection closed"));
+            }
+            let line = line.trim_end();
+            let reply: u16 = line
+                .get(..3)
+                .and_then(|c| c.parse().ok())
+                .ok_or_else(|| smtp_error(format!("bad reply: {line}")))?;
+            if reply != code {
+                return Err(smtp_error(format!("expected {code}, got: {line}")));
+            }
+            // "250-..." continues, "250 ..." (or a bare "250") ends the reply
+            let more = line.as_bytes().get(3) == Some(&b'-');
+            lines.push(line.get(4..).unwrap_or_default().to_string());
+            if !more {
+                return Ok(lines);
+            }
+        }
+    }
+
+    async fn command(&mut self, command: &str, code: u16) -> AppResult<Vec<String>> {
+        self.write(&format!("{command}\r\n")).await?;
+        self.expect(code).await
+    }
+
+    async fn ehlo(&mut self) -> AppResult<Vec<String>> {
+        self.command("EHLO localhost", 250).await
+    }
+
+    async fn authenticate(&mut self, features: &[String], user: &str, pass: &str) -> AppResult<()> {
+        let b64 = |s: &str| base64::engine::general_purpose::STANDARD.encode(s);
+        let mechanisms = features
+            .iter()
+            .find(|f| f.to_uppercase().starts_with("AUTH"))
+            .map(|f| f.to_uppercase())
+            .unwrap_or_default();
+
+        if mechanisms.contains("PLAIN") || !mechanisms.contains("LOGIN") {
+            self.command(
+                &format!("AUTH PLAIN {}", b64(&format!("\0{user}\0{pass}"))),
+                235,
+            )
+            .await?;
+        } else {
+            self.command("AUTH LOGIN", 334).await?;
+            self.command(&b64(user), 334).await?;
+            self.command(&b64(pass), 235).await?;
+        }
+        Ok(())
+    }
+}
+
+fn smtp_error(msg: impl std::fmt::Display) -> AppError {
+    AppError::internal(format!("SMTP: {msg}"))
+}
+
+/// `Name <addr@host>` → `addr@host`
+fn address(mailbox: &str) -> &str {
+    match (mailbox.rfind('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        _ => mailbox.trim(),
+    }
+}
+
+/// Address for `MAIL FROM` / `RCPT TO`; anything that could end the
+/// command or add parameters to it is refused
+fn envelope_address(mailbox: &str) -> AppResult<&str> {
+    let addr = address(mailbox);
+    if addr.is_empty()
+        || mailbox.chars().any(char::is_control)
+        || addr
+            .chars()
+            .any(|c| c.is_whitespace() || c == '<' || c == '>')
+    {
+        return Err(AppError::validation(format!(
+            "Invalid email address: {:?}",
+            mailbox
+        )));
+    }
+    Ok(addr)
+}
+
+/// Escapes lines starting with `.` so they cannot end DATA early
+fn dot_stuff(mime: &str) -> String {
+    let mut out = String::with_capacity(mime.len() + 8);
+    for line in mime.split_inclusive("\r\n") {
+        if line.starts_with('.') {
+            out.push('.');
+        }
+        out.push_str(line);
+    }
+    if !out.ends_with("\r\n") {
+        out.push_str("\r\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_stuffing_escapes_leading_dots() {
+        assert_eq!(dot_stuff("a\r\n.b\r\n..c"), "a\r\n..b\r\n...c\r\n");
+    }
+
+    #[test]
+    fn address_extracts_angle_bracket_part() {
+        assert_eq!(
+            address("Kitchen <no-reply@example.com>"),
+            "no-reply@example.com"
+        );
+        assert_eq!(address(" cook@example.com "), "cook@example.com");
+    }
+
+    #[test]
+    fn envelope_address_refuses_smuggled_parameters() {
+        assert_eq!(
+            envelope_address("Kitchen <no-reply@example.com>").unwrap(),
+            "no-reply@example.com"
+        );
+        assert!(envelope_address("cook@example.com> NOTIFY=SUCCESS").is_err());
+        assert!(envelope_address("cook@example.com\r\nRCPT TO:<x@evil.com>").is_err());
+        assert!(envelope_address("").is_err());
+    }
+}
@@ -3,10 +3,12 @@ pub mod cache; // 🆕 In-memory cache for public endpoints (saves Neon CU)
 pub mod config;
 pub mod gemini; // 🆕 New vertical-slice Gemini adapters
 pub mod gemini_service; // 🆕 Google Gemini AI (replaces Groq for generation)
-pub mod groq_service; // Legacy — types re-exported by gemini_service
+pub mod groq_service; // Legacy — types re-exported by gemini_service; Groq calls go through llm
 pub mod icon_image_prompts;
 pub mod ingredient_cache; // 🆕 In-memory ingredient catalog for Sous-Chef (0 SQL)
+pub mod llm; // 🆕 LLM gateway: provider chain (Gemini → Groq → OpenAI-compatible)
 pub mod llm_adapter;
+pub mod mail; // 🆕 Outgoing email: Mailer port, SMTP + outbox transports
 pub mod persistence;
 pub mod r2_client;
 pub mod security;
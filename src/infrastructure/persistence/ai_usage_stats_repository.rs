@@ -1,3 +1,4 @@
+use crate::infrastructure::llm::LlmUsage;
 use crate::shared::AppError;
 use sqlx::PgPool;
 
@@ -11,23 +12,18 @@ impl AiUsageStatsRepository {
         Self { pool }
     }
 
-    pub async fn log_usage(
-        &self,
-        endpoint: &str,
-        prompt_tokens: i32,
-        completion_tokens: i32,
-        total_tokens: i32,
-        duration_ms: i32,
-    ) -> Result<(), AppError> {
+    pub async fn log_usage(&self, endpoint: &str, usage: &LlmUsage) -> Result<(), AppError> {
         sqlx::query(
-            "INSERT INTO ai_usage_stats (endpoint, prompt_tokens, completion_tokens, total_tokens, duration_ms) 
-             VALUES ($1, $2, $3, $4, $5)"
+            "INSERT INTO ai_usage_stats (endpoint, provider, model, prompt_tokens, completion_tokens, total_tokens, duration_ms) 
+             VALUES ($1, $2, $3, $4, $5, $6, $7)"
         )
         .bind(endpoint)
-        .bind(prompt_tokens)
-        .bind(completion_tokens)
-        .bind(total_tokens)
-        .bind(duration_ms)
+        .bind(&usage.provider)
+        .bind(&usage.model)
+        .bind(usage.prompt_tokens)
+        .bind(usage.completion_tokens)
+        .bind(usage.total_tokens)
+        .bind(usage.duration_ms)
         .execute(&self.pool)
         .await
         .map_err(AppError::from)?;
@@ -1,8 +1,9 @@
 use crate::domain::catalog::*;
-use crate::shared::{AppResult, Language};
+use crate::shared::{AppResult, Language, TenantId};
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 use sqlx::{PgPool, Row};
+use std::collections::HashMap;
 
 #[async_trait]
 pub trait CatalogIngredientRepositoryTrait: Send + Sync {
@@ -33,6 +34,14 @@ pub trait CatalogIngredientRepositoryTrait: Send + Sync {
         offset: i64,
         limit: i64,
     ) -> AppResult<Vec<CatalogIngredient>>;
+
+    /// Effective edible yield % per ingredient for a tenant:
+    /// tenant override → catalog `edible_yield_percent` → 100
+    async fn effective_yield_percents(
+        &self,
+        tenant_id: TenantId,
+        ids: &[CatalogIngredientId],
+    ) -> AppResult<HashMap<CatalogIngredientId, Decimal>>;
 }
 
 #[derive(Clone)]
@@ -299,6 +308,43 @@ impl CatalogIngredientRepositoryTrait for CatalogIngredientRepository {
 
         rows.iter().map(Self::row_to_ingredient).collect()
     }
+
+    async fn effective_yield_percents(
+        &self,
+        tenant_id: TenantId,
+        ids: &[CatalogIngredientId],
+    ) -> AppResult<HashMap<CatalogIngredientId, Decimal>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let uuids: Vec<uuid::Uuid> = ids.iter().map(|id| id.as_uuid()).collect();
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                ci.id,
+                COALESCE(ti.yield_percent, ci.edible_yield_percent, 100) AS yield_percent
+            FROM catalog_ingredients ci
+            LEFT JOIN tenant_ingredients ti
+                ON ti.catalog_ingredient_id = ci.id
+               AND ti.tenant_id = $1
+               AND ti.is_active = true
+            WHERE ci.id = ANY($2)
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(&uuids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut yields = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let id: uuid::Uuid = row.try_get("id")?;
+            let yield_percent: Decimal = row.try_get("yield_percent")?;
+            yields.insert(CatalogIngredientId::from_uuid(id), yield_percent);
+        }
+        Ok(yields)
+    }
 }
 
 // ── Public reference query (SEO / public API) ─────────────────────────────────
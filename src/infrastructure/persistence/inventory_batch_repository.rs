@@ -1,14 +1,17 @@
 use crate::domain::{
     catalog::CatalogIngredientId,
     inventory::{
-        BatchStatus, InventoryBatch, InventoryBatchId, InventoryMovement, Money, MovementType,
-        Quantity,
+        BatchStatus, CostingStrategy, InventoryBatch, InventoryBatchId, InventoryMovement,
+        InventoryShortfall, Money, MovementType, Quantity,
     },
+    storage_location::StorageLocationId,
+    supplier::SupplierId,
 };
 use crate::shared::{AppError, AppResult, TenantId, UserId};
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::collections::HashMap;
 use time::OffsetDateTime;
 
 #[async_trait]
@@ -30,18 +33,28 @@ pub trait InventoryBatchRepositoryTrait: Send + Sync {
         tenant_id: TenantId,
     ) -> AppResult<Option<InventoryBatch>>;
 
+    /// Find batch by ID with LOCK
+    async fn find_by_id_for_update(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        id: InventoryBatchId,
+        tenant_id: TenantId,
+    ) -> AppResult<Option<InventoryBatch>>;
+
     /// List all batches for tenant
     async fn list_by_tenant(&self, tenant_id: TenantId) -> AppResult<Vec<InventoryBatch>>;
 
     /// Count batches for tenant
     async fn count_by_tenant(&self, tenant_id: TenantId) -> AppResult<i64>;
 
-    /// List active batches for specific ingredient with LOCK (FIFO order)
+    /// List active batches for specific ingredient with LOCK (FIFO order).
+    /// `location` restricts the batches to one storage location.
     async fn list_active_by_ingredient_for_update(
         &self,
         tx: &mut Transaction<'static, Postgres>,
         tenant_id: TenantId,
         catalog_id: CatalogIngredientId,
+        location: Option<StorageLocationId>,
     ) -> AppResult<Vec<InventoryBatch>>;
 
     /// Update batch quantity and status (simple)
@@ -63,6 +76,40 @@ pub trait InventoryBatchRepositoryTrait: Send + Sync {
         tx: &mut Transaction<'static, Postgres>,
         movement: &InventoryMovement,
     ) -> AppResult<()>;
+
+    /// Record unbacked consumption (negative-stock ledger)
+    async fn record_shortfall(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        shortfall: &InventoryShortfall,
+    ) -> AppResult<()>;
+
+    /// Latest purchase price of an ingredient (any batch status)
+    async fn last_known_price(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        tenant_id: TenantId,
+        catalog_id: CatalogIngredientId,
+    ) -> AppResult<Option<Money>>;
+
+    /// Record the purchase price of a received batch (price history)
+    async fn record_price_point(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        batch: &InventoryBatch,
+    ) -> AppResult<()>;
+
+    /// Tenant costing strategy (defaults to FIFO actual when not configured)
+    async fn costing_strategy(&self, tenant_id: TenantId) -> AppResult<CostingStrategy>;
+
+    /// Unit price per ingredient according to the strategy.
+    /// Ingredients the strategy cannot price fall back to the latest batch price.
+    async fn unit_prices(
+        &self,
+        tenant_id: TenantId,
+        strategy: CostingStrategy,
+        ids: &[CatalogIngredientId],
+    ) -> AppResult<HashMap<CatalogIngredientId, Money>>;
 }
 
 #[derive(Clone)]
@@ -100,6 +147,12 @@ impl InventoryBatchRepository {
         let supplier: Option<String> = row
             .try_get("supplier")
             .map_err(|e| AppError::internal(&format!("DB Error: {}", e)))?;
+        let supplier_id: Option<uuid::Uuid> = row
+            .try_get("supplier_id")
+            .map_err(|e| AppError::internal(format!("DB Error: {}", e)))?;
+        let location_id: Option<uuid::Uuid> = row
+            .try_get("location_id")
+            .map_err(|e| AppError::internal(format!("DB Error: {}", e)))?;
         let invoice: Option<String> = row
             .try_get("invoice_number")
             .map_err(|e| AppError::internal(&format!("DB Error: {}", e)))?;
@@ -126,7 +179,7 @@ impl InventoryBatchRepository {
             _ => BatchStatus::Active,
         };
 
-        Ok(InventoryBatch::from_parts(
+        let mut batch = InventoryBatch::from_parts(
             InventoryBatchId::from_uuid(id),
             UserId::from_uuid(user_id),
             TenantId::from_uuid(tenant_id),
@@ -141,7 +194,11 @@ impl InventoryBatchRepository {
             expires_at,
             created_at,
             updated_at,
-        ))
+        );
+        batch.supplier_id = supplier_id.map(SupplierId::from_uuid);
+        batch.location_id = location_id.map(StorageLocationId::from_uuid);
+
+        Ok(batch)
     }
 }
 
@@ -155,9 +212,11 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
             r#"
             INSERT INTO inventory_batches 
                 (id, user_id, tenant_id, catalog_ingredient_id, price_per_unit_cents, 
-                 quantity, remaining_quantity, supplier, invoice_number, status,
-                 received_at, expires_at, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                 quantity, remaining_quantity, supplier, supplier_id, invoice_number, status,
+                 received_at, expires_at, created_at, updated_at, location_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
+                    COALESCE($16, (SELECT id FROM storage_locations
+                                   WHERE tenant_id = $3 AND is_default AND is_active)))
             "#,
         )
         .bind(batch.id.as_uuid())
@@ -168,6 +227,7 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
         .bind(batch.quantity.decimal())
         .bind(batch.remaining_quantity.decimal())
         .bind(&batch.supplier)
+        .bind(batch.supplier_id.map(|id| id.as_uuid()))
         .bind(&batch.invoice_number)
         .bind(match batch.status {
             BatchStatus::Active => "active",
@@ -178,6 +238,7 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
         .bind(batch.expires_at)
         .bind(batch.created_at)
         .bind(batch.updated_at)
+        .bind(batch.location_id.map(|id| id.as_uuid()))
         .execute(&self.pool)
         .await?;
 
@@ -192,8 +253,8 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
         let row = sqlx::query(
             r#"
             SELECT id, user_id, tenant_id, catalog_ingredient_id, price_per_unit_cents, 
-                   quantity, remaining_quantity, supplier, invoice_number, status,
-                   received_at, expires_at, created_at, updated_at
+                   quantity, remaining_quantity, supplier, supplier_id, invoice_number, status,
+                   received_at, expires_at, created_at, updated_at, location_id
             FROM inventory_batches
             WHERE id = $1 AND tenant_id = $2
             "#,
@@ -209,12 +270,39 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
         }
     }
 
+    async fn find_by_id_for_update(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        id: InventoryBatchId,
+        tenant_id: TenantId,
+    ) -> AppResult<Option<InventoryBatch>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, user_id, tenant_id, catalog_ingredient_id, price_per_unit_cents, 
+                   quantity, remaining_quantity, supplier, supplier_id, invoice_number, status,
+                   received_at, expires_at, created_at, updated_at, location_id
+            FROM inventory_batches
+            WHERE id = $1 AND tenant_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        match row {
+            Some(ref r) => Ok(Some(Self::row_to_batch(r)?)),
+            None => Ok(None),
+        }
+    }
+
     async fn list_by_tenant(&self, tenant_id: TenantId) -> AppResult<Vec<InventoryBatch>> {
         let rows = sqlx::query(
             r#"
             SELECT id, user_id, tenant_id, catalog_ingredient_id, price_per_unit_cents, 
-                   quantity, remaining_quantity, supplier, invoice_number, status,
-                   received_at, expires_at, created_at, updated_at
+                   quantity, remaining_quantity, supplier, supplier_id, invoice_number, status,
+                   received_at, expires_at, created_at, updated_at, location_id
             FROM inventory_batches
             WHERE tenant_id = $1
             ORDER BY received_at DESC
@@ -246,20 +334,23 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
         tx: &mut Transaction<'static, Postgres>,
         tenant_id: TenantId,
         catalog_id: CatalogIngredientId,
+        location: Option<StorageLocationId>,
     ) -> AppResult<Vec<InventoryBatch>> {
         let rows = sqlx::query(
             r#"
             SELECT id, user_id, tenant_id, catalog_ingredient_id, price_per_unit_cents, 
-                   quantity, remaining_quantity, supplier, invoice_number, status,
-                   received_at, expires_at, created_at, updated_at
+                   quantity, remaining_quantity, supplier, supplier_id, invoice_number, status,
+                   received_at, expires_at, created_at, updated_at, location_id
             FROM inventory_batches
             WHERE tenant_id = $1 AND catalog_ingredient_id = $2 AND status = 'active' AND remaining_quantity > 0
+              AND ($3::UUID IS NULL OR location_id = $3)
             ORDER BY expires_at NULLS LAST, received_at ASC
             FOR UPDATE
             "#
         )
         .bind(tenant_id.as_uuid())
         .bind(catalog_id.as_uuid())
+        .bind(location.map(|id| id.as_uuid()))
         .fetch_all(&mut **tx)
         .await?;
 
@@ -359,7 +450,10 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
             MovementType::In => "IN",
             MovementType::OutSale => "OUT_SALE",
             MovementType::OutExpire => "OUT_EXPIRE",
+            MovementType::OutWaste => "OUT_WASTE",
             MovementType::Adjustment => "ADJUSTMENT",
+            MovementType::TransferOut => "TRANSFER_OUT",
+            MovementType::TransferIn => "TRANSFER_IN",
         })
         .bind(movement.quantity)
         .bind(movement.unit_cost_cents)
@@ -375,6 +469,58 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
         Ok(())
     }
 
+    async fn record_shortfall(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        shortfall: &InventoryShortfall,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO inventory_shortfalls
+                (id, tenant_id, catalog_ingredient_id, quantity, unit_cost_cents,
+                 estimated_cost_cents, reference_id, reference_type, notes, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(shortfall.id)
+        .bind(shortfall.tenant_id.as_uuid())
+        .bind(shortfall.catalog_ingredient_id.as_uuid())
+        .bind(shortfall.quantity)
+        .bind(shortfall.unit_cost_cents)
+        .bind(shortfall.estimated_cost_cents)
+        .bind(shortfall.reference_id)
+        .bind(&shortfall.reference_type)
+        .bind(&shortfall.notes)
+        .bind(shortfall.created_at)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn last_known_price(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        tenant_id: TenantId,
+        catalog_id: CatalogIngredientId,
+    ) -> AppResult<Option<Money>> {
+        let price: Option<i64> = sqlx::query_scalar(
+            r#"
+            SELECT price_per_unit_cents
+            FROM inventory_batches
+            WHERE tenant_id = $1 AND catalog_ingredient_id = $2
+            ORDER BY received_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id.as_uuid())
+        .bind(catalog_id.as_uuid())
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        price.map(Money::from_cents).transpose()
+    }
+
     async fn create_in_transaction(
         &self,
         tx: &mut Transaction<'static, Postgres>,
@@ -384,9 +530,11 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
             r#"
             INSERT INTO inventory_batches 
                 (id, user_id, tenant_id, catalog_ingredient_id, price_per_unit_cents, 
-                 quantity, remaining_quantity, supplier, invoice_number, status,
-                 received_at, expires_at, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                 quantity, remaining_quantity, supplier, supplier_id, invoice_number, status,
+                 received_at, expires_at, created_at, updated_at, location_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
+                    COALESCE($16, (SELECT id FROM storage_locations
+                                   WHERE tenant_id = $3 AND is_default AND is_active)))
             "#,
         )
         .bind(batch.id.as_uuid())
@@ -397,6 +545,7 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
         .bind(batch.quantity.decimal())
         .bind(batch.remaining_quantity.decimal())
         .bind(&batch.supplier)
+        .bind(batch.supplier_id.map(|id| id.as_uuid()))
         .bind(&batch.invoice_number)
         .bind(match batch.status {
             BatchStatus::Active => "active",
@@ -407,9 +556,160 @@ impl InventoryBatchRepositoryTrait for InventoryBatchRepository {
         .bind(batch.expires_at)
         .bind(batch.created_at)
         .bind(batch.updated_at)
+        .bind(batch.location_id.map(|id| id.as_uuid()))
         .execute(&mut **tx)
         .await?;
 
         Ok(())
     }
+
+    async fn record_price_point(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        batch: &InventoryBatch,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ingredient_price_history
+                (id, tenant_id, catalog_ingredient_id, supplier_id, batch_id,
+                 price_per_unit_cents, quantity, recorded_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(uuid::Uuid::new_v4())
+        .bind(batch.tenant_id.as_uuid())
+        .bind(batch.catalog_ingredient_id.as_uuid())
+        .bind(batch.supplier_id.map(|id| id.as_uuid()))
+        .bind(batch.id.as_uuid())
+        .bind(batch.price_per_unit.as_cents())
+        .bind(batch.quantity.decimal())
+        .bind(batch.received_at)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn costing_strategy(&self, tenant_id: TenantId) -> AppResult<CostingStrategy> {
+        let strategy: Option<String> = sqlx::query_scalar(
+            "SELECT costing_strategy FROM tenant_inventory_settings WHERE tenant_id = $1",
+        )
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match strategy {
+            Some(value) => CostingStrategy::parse(&value),
+            None => Ok(CostingStrategy::default()),
+        }
+    }
+
+    async fn unit_prices(
+        &self,
+        tenant_id: TenantId,
+        strategy: CostingStrategy,
+        ids: &[CatalogIngredientId],
+    ) -> AppResult<HashMap<CatalogIngredientId, Money>> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let strategy_query = match strategy {
+            // Batch FIFO would consume next
+            CostingStrategy::FifoActual => {
+                r#"
+                SELECT DISTINCT ON (catalog_ingredient_id)
+                       catalog_ingredient_id, price_per_unit_cents
+                FROM inventory_batches
+                WHERE tenant_id = $1 AND catalog_ingredient_id = ANY($2)
+                  AND status = 'active' AND remaining_quantity > 0
+                ORDER BY catalog_ingredient_id, expires_at NULLS LAST, received_at ASC
+                "#
+            }
+            CostingStrategy::LastPrice => {
+                r#"
+                SELECT DISTINCT ON (catalog_ingredient_id)
+                       catalog_ingredient_id, price_per_unit_cents
+                FROM ingredient_price_history
+                WHERE tenant_id = $1 AND catalog_ingredient_id = ANY($2)
+                ORDER BY catalog_ingredient_id, recorded_at DESC
+                "#
+            }
+            CostingStrategy::WeightedAverage => {
+                r#"
+                SELECT catalog_ingredient_id,
+                       ROUND(SUM(remaining_quantity * price_per_unit_cents)
+                             / SUM(remaining_quantity))::BIGINT AS price_per_unit_cents
+                FROM inventory_batches
+                WHERE tenant_id = $1 AND catalog_ingredient_id = ANY($2)
+                  AND status = 'active' AND remaining_quantity > 0
+                GROUP BY catalog_ingredient_id
+                "#
+            }
+            // Lowest of each supplier's latest price
+            CostingStrategy::CheapestSupplier => {
+                r#"
+                SELECT catalog_ingredient_id, MIN(price_per_unit_cents)::BIGINT AS price_per_unit_cents
+                FROM (
+                    SELECT DISTINCT ON (catalog_ingredient_id, supplier_id)
+                           catalog_ingredient_id, price_per_unit_cents
+                    FROM ingredient_price_history
+                    WHERE tenant_id = $1 AND catalog_ingredient_id = ANY($2)
+                      AND supplier_id IS NOT NULL
+                    ORDER BY catalog_ingredient_id, supplier_id, recorded_at DESC
+                ) latest
+                GROUP BY catalog_ingredient_id
+                "#
+            }
+        };
+
+        let uuids: Vec<uuid::Uuid> = ids.iter().map(|id| id.as_uuid()).collect();
+        let mut prices = HashMap::with_capacity(ids.len());
+
+        let rows = sqlx::query(strategy_query)
+            .bind(tenant_id.as_uuid())
+            .bind(&uuids)
+            .fetch_all(&self.pool)
+            .await?;
+        for row in rows {
+            let id: uuid::Uuid = row.try_get("catalog_ingredient_id")?;
+            let cents: i64 = row.try_get("price_per_unit_cents")?;
+            prices.insert(
+                CatalogIngredientId::from_uuid(id),
+                Money::from_cents(cents)?,
+            );
+        }
+
+        // Fallback: latest batch price (e.g. no stock on hand, no supplier prices yet)
+        let missing: Vec<uuid::Uuid> = ids
+            .iter()
+            .filter(|id| !prices.contains_key(*id))
+            .map(|id| id.as_uuid())
+            .collect();
+        if !missing.is_empty() {
+            let rows = sqlx::query(
+                r#"
+                SELECT DISTINCT ON (catalog_ingredient_id)
+                       catalog_ingredient_id, price_per_unit_cents
+                FROM inventory_batches
+                WHERE tenant_id = $1 AND catalog_ingredient_id = ANY($2)
+                ORDER BY catalog_ingredient_id, received_at DESC
+                "#,
+            )
+            .bind(tenant_id.as_uuid())
+            .bind(&missing)
+            .fetch_all(&self.pool)
+            .await?;
+            for row in rows {
+                let id: uuid::Uuid = row.try_get("catalog_ingredient_id")?;
+                let cents: i64 = row.try_get("price_per_unit_cents")?;
+                prices.insert(
+                    CatalogIngredientId::from_uuid(id),
+                    Money::from_cents(cents)?,
+                );
+            }
+        }
+
+        Ok(prices)
+    }
 }
@@ -13,6 +13,7 @@ pub mod recipe_translation_repository;
 pub mod recipe_v2_repository; // V2 with translation support
 pub mod refresh_token_repository;
 pub mod slug_alias_repository;
+pub mod supplier_repository; // 🆕 Tenant suppliers
 pub mod tenant_ingredient_repository;
 pub mod tenant_repository;
 pub mod user_repository;
@@ -31,6 +32,7 @@ pub use recipe_translation_repository::*;
 pub use recipe_v2_repository::*;
 pub use refresh_token_repository::*;
 pub use slug_alias_repository::{SlugAliasRepository, SlugResolution};
+pub use supplier_repository::*;
 pub use tenant_ingredient_repository::*;
 pub use tenant_repository::*;
 pub use user_repository::*;
@@ -1,4 +1,4 @@
-use crate::domain::RefreshToken;
+use crate::domain::{AuthSession, RefreshToken};
 use crate::shared::{AppResult, RefreshTokenId, UserId};
 use async_trait::async_trait;
 use sqlx::{PgPool, Row};
@@ -11,6 +11,13 @@ pub trait RefreshTokenRepositoryTrait: Send + Sync {
     async fn find_by_token_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>>;
     async fn revoke(&self, id: RefreshTokenId) -> AppResult<()>;
     async fn revoke_all_for_user(&self, user_id: UserId) -> AppResult<()>;
+    /// Revoke `id`, link and store its successor in one transaction;
+    /// false (nothing written) if `id` was already spent
+    async fn rotate(&self, id: RefreshTokenId, next: &RefreshToken) -> AppResult<bool>;
+    async fn revoke_family(&self, family_id: Uuid) -> AppResult<()>;
+    async fn revoke_family_for_user(&self, user_id: UserId, family_id: Uuid) -> AppResult<bool>;
+    async fn list_sessions(&self, user_id: UserId) -> AppResult<Vec<AuthSession>>;
+    async fn delete_expired_for_user(&self, user_id: UserId) -> AppResult<()>;
 }
 
 #[derive(Clone)]
@@ -29,15 +36,20 @@ impl RefreshTokenRepositoryTrait for RefreshTokenRepository {
     async fn create(&self, token: &RefreshToken) -> AppResult<()> {
         sqlx::query(
             r#"
-            INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, revoked_at, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, revoked_at,
+                                        replaced_by, user_agent, ip_address, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             "#,
         )
         .bind(token.id.as_uuid())
         .bind(token.user_id.as_uuid())
+        .bind(token.family_id)
         .bind(&token.token_hash)
         .bind(token.expires_at)
         .bind(token.revoked_at)
+        .bind(token.replaced_by.map(|id| *id.as_uuid()))
+        .bind(&token.user_agent)
+        .bind(&token.ip_address)
         .bind(token.created_at)
         .execute(&self.pool)
         .await?;
@@ -48,7 +60,8 @@ impl RefreshTokenRepositoryTrait for RefreshTokenRepository {
     async fn find_by_token_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>> {
         let result = sqlx::query(
             r#"
-            SELECT id, user_id, token_hash, expires_at, revoked_at, created_at
+            SELECT id, user_id, family_id, token_hash, expires_at, revoked_at,
+                   replaced_by, user_agent, ip_address, created_at
             FROM refresh_tokens
             WHERE token_hash = $1
             "#,
@@ -63,14 +76,19 @@ impl RefreshTokenRepositoryTrait for RefreshTokenRepository {
             let token_hash: String = row.get("token_hash");
             let expires_at: OffsetDateTime = row.get("expires_at");
             let revoked_at: Option<OffsetDateTime> = row.get("revoked_at");
+            let replaced_by: Option<Uuid> = row.get("replaced_by");
             let created_at: OffsetDateTime = row.get("created_at");
 
             RefreshToken {
                 id: RefreshTokenId::from_uuid(id),
                 user_id: UserId::from_uuid(user_id),
+                family_id: row.get("family_id"),
                 token_hash,
                 expires_at,
                 revoked_at,
+                replaced_by: replaced_by.map(RefreshTokenId::from_uuid),
+                user_agent: row.get("user_agent"),
+                ip_address: row.get("ip_address"),
                 created_at,
             }
         }))
@@ -107,4 +125,124 @@ impl RefreshTokenRepositoryTrait for RefreshTokenRepository {
 
         Ok(())
     }
+
+    async fn rotate(&self, id: RefreshTokenId, next: &RefreshToken) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        // Conditional so two concurrent refreshes cannot both win
+        let result = sqlx::query(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = $1, replaced_by = $2
+            WHERE id = $3 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(OffsetDateTime::now_utc())
+        .bind(next.id.as_uuid())
+        .bind(id.as_uuid())
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() != 1 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, revoked_at,
+                                        replaced_by, user_agent, ip_address, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(next.id.as_uuid())
+        .bind(next.user_id.as_uuid())
+        .bind(next.family_id)
+        .bind(&next.token_hash)
+        .bind(next.expires_at)
+        .bind(next.revoked_at)
+        .bind(next.replaced_by.map(|id| *id.as_uuid()))
+        .bind(&next.user_agent)
+        .bind(&next.ip_address)
+        .bind(next.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn revoke_family(&self, family_id: Uuid) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = $1
+            WHERE family_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(OffsetDateTime::now_utc())
+        .bind(family_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_family_for_user(&self, user_id: UserId, family_id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = $1
+            WHERE family_id = $2 AND user_id = $3 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(OffsetDateTime::now_utc())
+        .bind(family_id)
+        .bind(user_id.as_uuid())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_sessions(&self, user_id: UserId) -> AppResult<Vec<AuthSession>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT family_id,
+                   MIN(created_at) AS created_at,
+                   MAX(created_at) AS last_used_at,
+                   MAX(expires_at) AS expires_at,
+                   (ARRAY_AGG(user_agent ORDER BY created_at DESC))[1] AS user_agent,
+                   (ARRAY_AGG(ip_address ORDER BY created_at DESC))[1] AS ip_address
+            FROM refresh_tokens
+            WHERE user_id = $1
+            GROUP BY family_id
+            HAVING BOOL_OR(revoked_at IS NULL AND expires_at > NOW())
+            ORDER BY last_used_at DESC
+            "#,
+        )
+        .bind(user_id.as_uuid())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| AuthSession {
+                id: row.get("family_id"),
+                user_agent: row.get("user_agent"),
+                ip_address: row.get("ip_address"),
+                created_at: row.get("created_at"),
+                last_used_at: row.get("last_used_at"),
+                expires_at: row.get("expires_at"),
+            })
+            .collect())
+    }
+
+    async fn delete_expired_for_user(&self, user_id: UserId) -> AppResult<()> {
+        sqlx::query("DELETE FROM refresh_tokens WHERE user_id = $1 AND expires_at < $2")
+            .bind(user_id.as_uuid())
+            .bind(OffsetDateTime::now_utc())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
 }
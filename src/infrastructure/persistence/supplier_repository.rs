@@ -0,0 +1,147 @@
+use crate::domain::supplier::{Supplier, SupplierId};
+use crate::shared::{AppResult, TenantId};
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+
+#[async_trait]
+pub trait SupplierRepositoryTrait: Send + Sync {
+    /// Insert or update supplier
+    async fn save(&self, supplier: &Supplier) -> AppResult<()>;
+
+    /// Find supplier by ID within tenant
+    async fn find_by_id(&self, id: SupplierId, tenant_id: TenantId) -> AppResult<Option<Supplier>>;
+
+    /// Find active supplier by name (case-insensitive)
+    async fn find_by_name(&self, tenant_id: TenantId, name: &str) -> AppResult<Option<Supplier>>;
+
+    /// List suppliers for tenant (alphabetical)
+    async fn list_by_tenant(
+        &self,
+        tenant_id: TenantId,
+        include_inactive: bool,
+    ) -> AppResult<Vec<Supplier>>;
+}
+
+#[derive(Clone)]
+pub struct SupplierRepository {
+    pool: PgPool,
+}
+
+impl SupplierRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    fn row_to_supplier(row: &sqlx::postgres::PgRow) -> AppResult<Supplier> {
+        Ok(Supplier {
+            id: SupplierId::from_uuid(row.try_get("id")?),
+            tenant_id: TenantId::from_uuid(row.try_get("tenant_id")?),
+            name: row.try_get("name")?,
+            contact_name: row.try_get("contact_name")?,
+            email: row.try_get("email")?,
+            phone: row.try_get("phone")?,
+            address: row.try_get("address")?,
+            notes: row.try_get("notes")?,
+            lead_time_days: row.try_get("lead_time_days")?,
+            min_order_cents: row.try_get("min_order_cents")?,
+            delivery_weekdays: row.try_get("delivery_weekdays")?,
+            is_active: row.try_get("is_active")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+const SUPPLIER_COLUMNS: &str = "id, tenant_id, name, contact_name, email, phone, address, notes, \
+     lead_time_days, min_order_cents, delivery_weekdays, is_active, created_at, updated_at";
+
+#[async_trait]
+impl SupplierRepositoryTrait for SupplierRepository {
+    async fn save(&self, supplier: &Supplier) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO suppliers (
+                id, tenant_id, name, contact_name, email, phone, address, notes,
+                lead_time_days, min_order_cents, delivery_weekdays, is_active,
+                created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                contact_name = EXCLUDED.contact_name,
+                email = EXCLUDED.email,
+                phone = EXCLUDED.phone,
+                address = EXCLUDED.address,
+                notes = EXCLUDED.notes,
+                lead_time_days = EXCLUDED.lead_time_days,
+                min_order_cents = EXCLUDED.min_order_cents,
+                delivery_weekdays = EXCLUDED.delivery_weekdays,
+                is_active = EXCLUDED.is_active
+            "#,
+        )
+        .bind(supplier.id.as_uuid())
+        .bind(supplier.tenant_id.as_uuid())
+        .bind(&supplier.name)
+        .bind(&supplier.contact_name)
+        .bind(&supplier.email)
+        .bind(&supplier.phone)
+        .bind(&supplier.address)
+        .bind(&supplier.notes)
+        .bind(supplier.lead_time_days)
+        .bind(supplier.min_order_cents)
+        .bind(&supplier.delivery_weekdays)
+        .bind(supplier.is_active)
+        .bind(supplier.created_at)
+        .bind(supplier.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_by_id(&self, id: SupplierId, tenant_id: TenantId) -> AppResult<Option<Supplier>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM suppliers WHERE id = $1 AND tenant_id = $2",
+            SUPPLIER_COLUMNS
+        ))
+        .bind(id.as_uuid())
+        .bind(tenant_id.as_uuid())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(Self::row_to_supplier).transpose()
+    }
+
+    async fn find_by_name(&self, tenant_id: TenantId, name: &str) -> AppResult<Option<Supplier>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM suppliers \
+             WHERE tenant_id = $1 AND LOWER(name) = LOWER($2) AND is_active = true",
+            SUPPLIER_COLUMNS
+        ))
+        .bind(tenant_id.as_uuid())
+        .bind(name.trim())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(Self::row_to_supplier).transpose()
+    }
+
+    async fn list_by_tenant(
+        &self,
+        tenant_id: TenantId,
+        include_inactive: bool,
+    ) -> AppResult<Vec<Supplier>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM suppliers \
+             WHERE tenant_id = $1 AND ($2 OR is_active = true) \
+             ORDER BY LOWER(name)",
+            SUPPLIER_COLUMNS
+        ))
+        .bind(tenant_id.as_uuid())
+        .bind(include_inactive)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_supplier).collect()
+    }
+}
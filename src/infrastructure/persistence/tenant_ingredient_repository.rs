@@ -1,4 +1,5 @@
 use crate::domain::catalog::{CatalogIngredientId, Unit};
+use crate::domain::supplier::SupplierId;
 use crate::domain::tenant_ingredient::{TenantIngredient, TenantIngredientId};
 use crate::shared::{AppError, AppResult, TenantId};
 use async_trait::async_trait;
@@ -35,7 +36,7 @@ impl TenantIngredientRepository {
             None => None,
         };
 
-        Ok(TenantIngredient::from_parts(
+        let mut ingredient = TenantIngredient::from_parts(
             TenantIngredientId::from_uuid(
                 row.try_get("id")
                     .map_err(|e| AppError::internal(&format!("DB Error: {}", e)))?,
@@ -55,6 +56,8 @@ impl TenantIngredientRepository {
             custom_unit,
             row.try_get("custom_expiration_days")
                 .map_err(|e| AppError::internal(&format!("DB Error: {}", e)))?,
+            row.try_get("yield_percent")
+                .map_err(|e| AppError::internal(format!("DB Error: {}", e)))?,
             row.try_get("notes")
                 .map_err(|e| AppError::internal(&format!("DB Error: {}", e)))?,
             row.try_get("is_active")
@@ -63,7 +66,23 @@ impl TenantIngredientRepository {
                 .map_err(|e| AppError::internal(&format!("DB Error: {}", e)))?,
             row.try_get("updated_at")
                 .map_err(|e| AppError::internal(&format!("DB Error: {}", e)))?,
-        ))
+        );
+
+        let supplier_id: Option<uuid::Uuid> = row
+            .try_get("supplier_id")
+            .map_err(|e| AppError::internal(format!("DB Error: {}", e)))?;
+        ingredient.supplier_id = supplier_id.map(SupplierId::from_uuid);
+        ingredient.par_level = row
+            .try_get("par_level")
+            .map_err(|e| AppError::internal(format!("DB Error: {}", e)))?;
+        ingredient.reorder_point = row
+            .try_get("reorder_point")
+            .map_err(|e| AppError::internal(format!("DB Error: {}", e)))?;
+        ingredient.pack_size = row
+            .try_get("pack_size")
+            .map_err(|e| AppError::internal(format!("DB Error: {}", e)))?;
+
+        Ok(ingredient)
     }
 }
 
@@ -75,9 +94,10 @@ impl TenantIngredientRepositoryTrait for TenantIngredientRepository {
             INSERT INTO tenant_ingredients (
                 id, tenant_id, catalog_ingredient_id, price, 
                 supplier, custom_unit, custom_expiration_days, notes, is_active,
-                created_at, updated_at
+                created_at, updated_at, yield_percent,
+                supplier_id, par_level, reorder_point, pack_size
             )
-            VALUES ($1, $2, $3, $4, $5, $6::unit_type, $7, $8, $9, $10, $11)
+            VALUES ($1, $2, $3, $4, $5, $6::unit_type, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
             ON CONFLICT (tenant_id, catalog_ingredient_id) WHERE is_active = true
             DO UPDATE SET
                 price = EXCLUDED.price,
@@ -86,7 +106,12 @@ impl TenantIngredientRepositoryTrait for TenantIngredientRepository {
                 custom_expiration_days = EXCLUDED.custom_expiration_days,
                 notes = EXCLUDED.notes,
                 is_active = EXCLUDED.is_active,
-                updated_at = EXCLUDED.updated_at
+                updated_at = EXCLUDED.updated_at,
+                yield_percent = EXCLUDED.yield_percent,
+                supplier_id = EXCLUDED.supplier_id,
+                par_level = EXCLUDED.par_level,
+                reorder_point = EXCLUDED.reorder_point,
+                pack_size = EXCLUDED.pack_size
             "#,
         )
         .bind(ingredient.id.as_uuid())
@@ -100,6 +125,11 @@ impl TenantIngredientRepositoryTrait for TenantIngredientRepository {
         .bind(ingredient.is_active)
         .bind(ingredient.created_at)
         .bind(ingredient.updated_at)
+        .bind(ingredient.yield_percent)
+        .bind(ingredient.supplier_id.map(|id| id.as_uuid()))
+        .bind(ingredient.par_level)
+        .bind(ingredient.reorder_point)
+        .bind(ingredient.pack_size)
         .execute(&self.pool)
         .await?;
 
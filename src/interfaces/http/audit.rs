@@ -0,0 +1,24 @@
+//! HTTP handler for the tenant audit trail.
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+use crate::application::audit::{AuditEntry, AuditFilter, AuditLog};
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+/// `GET /api/audit?entity_type=dish&entity_id=..&actor_id=..&action=price_change&from=..&to=..&limit=100&offset=0`
+///
+/// `from`/`to` are RFC 3339 timestamps; newest entries first.
+pub async fn list_audit_log(
+    State(audit): State<AuditLog>,
+    auth: AuthUser,
+    Query(filter): Query<AuditFilter>,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    auth.require(Permission::ViewAuditLog)?;
+    let entries = audit.list(auth.tenant_id, filter).await?;
+    Ok(Json(entries))
+}
@@ -1,6 +1,15 @@
+use crate::application::account::{
+    ForgotPasswordCommand, ResetPasswordCommand, VerifyEmailCommand,
+};
 use crate::application::{AuthService, LoginCommand, RefreshCommand, RegisterCommand};
+use crate::domain::{AuthSession, ClientInfo};
+use crate::interfaces::http::middleware::AuthUser;
 use crate::shared::Language;
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
@@ -42,6 +51,7 @@ pub struct AuthTokenResponse {
 
 pub async fn register_handler(
     State(auth_service): State<AuthService>,
+    headers: HeaderMap,
     Json(req): Json<RegisterRequest>,
 ) -> Result<Json<AuthTokenResponse>, crate::shared::AppError> {
     // Validate request
@@ -56,7 +66,9 @@ pub async fn register_handler(
         language: req.language,
     };
 
-    let response = auth_service.register(command).await?;
+    let response = auth_service
+        .register(command, client_info(&headers))
+        .await?;
 
     Ok(Json(AuthTokenResponse {
         access_token: response.access_token,
@@ -69,6 +81,7 @@ pub async fn register_handler(
 
 pub async fn login_handler(
     State(auth_service): State<AuthService>,
+    headers: HeaderMap,
     Json(req): Json<LoginRequest>,
 ) -> Result<Json<AuthTokenResponse>, crate::shared::AppError> {
     // Validate request
@@ -80,7 +93,7 @@ pub async fn login_handler(
         password: req.password,
     };
 
-    let response = auth_service.login(command).await?;
+    let response = auth_service.login(command, client_info(&headers)).await?;
 
     Ok(Json(AuthTokenResponse {
         access_token: response.access_token,
@@ -93,6 +106,7 @@ pub async fn login_handler(
 
 pub async fn refresh_handler(
     State(auth_service): State<AuthService>,
+    headers: HeaderMap,
     Json(req): Json<RefreshRequest>,
 ) -> Result<Json<AuthTokenResponse>, crate::shared::AppError> {
     // Validate request
@@ -103,7 +117,7 @@ pub async fn refresh_handler(
         refresh_token: req.refresh_token,
     };
 
-    let response = auth_service.refresh(command).await?;
+    let response = auth_service.refresh(command, client_info(&headers)).await?;
 
     Ok(Json(AuthTokenResponse {
         access_token: response.access_token,
@@ -113,3 +127,88 @@ pub async fn refresh_handler(
         tenant_id: response.tenant_id.to_string(),
     }))
 }
+
+/// POST /api/auth/logout — ends the session of the given refresh token
+pub async fn logout_handler(
+    State(auth_service): State<AuthService>,
+    Json(req): Json<RefreshRequest>,
+) -> Result<StatusCode, crate::shared::AppError> {
+    auth_service.logout(&req.refresh_token).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/auth/logout-all — signs out every device
+pub async fn logout_all_handler(
+    State(auth_service): State<AuthService>,
+    auth: AuthUser,
+) -> Result<StatusCode, crate::shared::AppError> {
+    auth_service.logout_all(auth.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/auth/sessions — devices still signed in
+pub async fn list_sessions_handler(
+    State(auth_service): State<AuthService>,
+    auth: AuthUser,
+) -> Result<Json<Vec<AuthSession>>, crate::shared::AppError> {
+    let sessions = auth_service.list_sessions(auth.user_id).await?;
+    Ok(Json(sessions))
+}
+
+/// DELETE /api/auth/sessions/:id
+pub async fn revoke_session_handler(
+    State(auth_service): State<AuthService>,
+    auth: AuthUser,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<StatusCode, crate::shared::AppError> {
+    auth_service.revoke_session(auth.user_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/auth/password/forgot — answers 202 whether or not the email exists
+pub async fn forgot_password_handler(
+    State(auth_service): State<AuthService>,
+    Json(req): Json<ForgotPasswordCommand>,
+) -> Result<StatusCode, crate::shared::AppError> {
+    auth_service.account().request_password_reset(req).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// POST /api/auth/password/reset — signs out every device on success
+pub async fn reset_password_handler(
+    State(auth_service): State<AuthService>,
+    Json(req): Json<ResetPasswordCommand>,
+) -> Result<StatusCode, crate::shared::AppError> {
+    auth_service.account().reset_password(req).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/auth/email/verify
+pub async fn verify_email_handler(
+    State(auth_service): State<AuthService>,
+    Json(req): Json<VerifyEmailCommand>,
+) -> Result<StatusCode, crate::shared::AppError> {
+    auth_service.account().verify_email(req).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/auth/email/verification — sends a new verification link
+pub async fn resend_verification_handler(
+    State(auth_service): State<AuthService>,
+    auth: AuthUser,
+) -> Result<StatusCode, crate::shared::AppError> {
+    auth_service
+        .account()
+        .send_verification(auth.user_id)
+        .await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// User-agent and client IP (first X-Forwarded-For hop behind the proxy)
+pub(crate) fn client_info(headers: &HeaderMap) -> ClientInfo {
+    let header_str = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let ip = header_str("x-forwarded-for")
+        .and_then(|value| value.split(',').next())
+        .or_else(|| header_str("x-real-ip"));
+    ClientInfo::new(header_str(header::USER_AGENT.as_str()), ip)
+}
@@ -1,9 +1,14 @@
 //! POST /api/copilot/message    — главный endpoint Copilot-а
 //! POST /api/copilot/actions/{id}/confirm — подтверждение write action
+//! POST /api/copilot/actions/{id}/undo    — откат выполненного action
 //! DELETE /api/copilot/actions/{id}       — отмена action
+//! GET    /api/copilot/threads            — треды разговора пользователя
+//! GET    /api/copilot/threads/{id}       — тред со всеми ходами
+//! DELETE /api/copilot/threads/{id}       — удалить тред
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::StatusCode,
     response::IntoResponse,
     Json,
 };
@@ -15,6 +20,7 @@ use crate::application::copilot::{
     context::{CopilotContext, CopilotPermission, CopilotScreen},
     CopilotEngine, CopilotResponse,
 };
+use crate::domain::UserRole;
 use crate::interfaces::http::middleware::AuthUser;
 use crate::shared::AppError;
 
@@ -41,6 +47,15 @@ pub struct CopilotMessageRequest {
     /// Явный locale из запроса (приоритет над user.language).
     /// Поддерживается: "ru", "en", "pl", "uk".
     pub locale: Option<String>,
+
+    /// Тред из предыдущего ответа; без него начинается новый.
+    pub thread_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThreadListQuery {
+    /// Сколько тредов вернуть (1–100, по умолчанию 20).
+    pub limit: Option<i64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -74,7 +89,7 @@ pub async fn handle_message(
 
     // Построить CopilotContext из JWT + request.
     // ai_actions_balance — заполним через usage service внутри engine.
-    // permissions — owner получает всё (расширить через roles в следующей итерации).
+    // permissions — из users.role (см. copilot_permissions).
     // Приоритет language: request.locale > user.language (JWT).
     let resolved_locale = req
         .locale
@@ -89,7 +104,7 @@ pub async fn handle_message(
         screen: req.screen,
         selected_entity_id: req.entity_id,
         ai_actions_balance: 999, // engine обновит через billing
-        permissions: default_owner_permissions(),
+        permissions: copilot_permissions(&auth.role),
     };
 
     tracing::info!(
@@ -99,7 +114,11 @@ pub async fn handle_message(
         message.len(),
     );
 
-    let response = state.engine.handle_message(&ctx, &message).await;
+    let thread = state
+        .engine
+        .open_thread(&ctx, req.thread_id, &message)
+        .await?;
+    let response = state.engine.handle_message(&ctx, &message, &thread).await;
 
     Ok(Json(CopilotMessageApiResponse { response }))
 }
@@ -117,7 +136,7 @@ pub async fn confirm_action(
         screen: CopilotScreen::Dashboard, // screen не важен для confirm
         selected_entity_id: None,
         ai_actions_balance: 0,
-        permissions: default_owner_permissions(),
+        permissions: copilot_permissions(&auth.role),
     };
 
     tracing::info!(
@@ -130,6 +149,34 @@ pub async fn confirm_action(
     Ok(Json(result))
 }
 
+/// POST /api/copilot/actions/{action_id}/undo
+///
+/// 409 Conflict — сущность изменилась после выполнения, откатывать вручную.
+pub async fn undo_action(
+    State(state): State<CopilotState>,
+    auth: AuthUser,
+    Path(action_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let ctx = CopilotContext {
+        user_id: auth.user_id,
+        tenant_id: auth.tenant_id,
+        locale: auth.language,
+        screen: CopilotScreen::Dashboard,
+        selected_entity_id: None,
+        ai_actions_balance: 0,
+        permissions: copilot_permissions(&auth.role),
+    };
+
+    tracing::info!(
+        "↩️ Copilot undo: user={} action_id={}",
+        *auth.user_id.as_uuid(),
+        action_id,
+    );
+
+    let result = state.engine.undo_action(&ctx, action_id).await?;
+    Ok(Json(result))
+}
+
 /// DELETE /api/copilot/actions/{action_id}
 pub async fn cancel_action(
     State(state): State<CopilotState>,
@@ -149,20 +196,67 @@ pub async fn cancel_action(
     Ok(Json(result))
 }
 
+/// GET /api/copilot/threads?limit=20
+pub async fn list_threads(
+    State(state): State<CopilotState>,
+    auth: AuthUser,
+    Query(query): Query<ThreadListQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let threads = state
+        .engine
+        .list_threads(auth.user_id, auth.tenant_id, limit)
+        .await?;
+    Ok(Json(threads))
+}
+
+/// GET /api/copilot/threads/{thread_id}
+pub async fn get_thread(
+    State(state): State<CopilotState>,
+    auth: AuthUser,
+    Path(thread_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let thread = state
+        .engine
+        .get_thread(auth.user_id, auth.tenant_id, thread_id)
+        .await?;
+    Ok(Json(thread))
+}
+
+/// DELETE /api/copilot/threads/{thread_id}
+pub async fn delete_thread(
+    State(state): State<CopilotState>,
+    auth: AuthUser,
+    Path(thread_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .engine
+        .delete_thread(auth.user_id, auth.tenant_id, thread_id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
-/// По умолчанию owner получает все права.
-/// В следующей итерации — загружать из users.role.
-fn default_owner_permissions() -> Vec<CopilotPermission> {
-    vec![
-        CopilotPermission::ReadInventory,
-        CopilotPermission::WriteInventory,
-        CopilotPermission::ReadDishes,
-        CopilotPermission::WriteDishes,
-        CopilotPermission::ReadRecipes,
-        CopilotPermission::WriteRecipes,
-        CopilotPermission::ReadLaboratory,
-        CopilotPermission::WriteLaboratory,
-        CopilotPermission::ManagePricing,
-    ]
+/// Права Copilot из users.role: owner и manager — всё, staff — только чтение.
+fn copilot_permissions(role: &UserRole) -> Vec<CopilotPermission> {
+    match role {
+        UserRole::Staff => vec![
+            CopilotPermission::ReadInventory,
+            CopilotPermission::ReadDishes,
+            CopilotPermission::ReadRecipes,
+            CopilotPermission::ReadLaboratory,
+        ],
+        UserRole::Owner | UserRole::Manager => vec![
+            CopilotPermission::ReadInventory,
+            CopilotPermission::WriteInventory,
+            CopilotPermission::ReadDishes,
+            CopilotPermission::WriteDishes,
+            CopilotPermission::ReadRecipes,
+            CopilotPermission::WriteRecipes,
+            CopilotPermission::ReadLaboratory,
+            CopilotPermission::WriteLaboratory,
+            CopilotPermission::ManagePricing,
+        ],
+    }
 }
@@ -8,7 +8,7 @@ use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::application::DishService;
-use crate::domain::{DishName, Money, RecipeId};
+use crate::domain::{DishName, Money, Permission, RecipeId};
 use crate::interfaces::http::middleware::AuthUser;
 use crate::shared::{AppError, PaginatedResponse, PaginationParams};
 
@@ -43,10 +43,12 @@ pub async fn create_dish(
     AuthUser {
         tenant_id,
         language: _,
-        user_id: _,
+        user_id,
+        role,
     }: AuthUser,
     Json(payload): Json<CreateDishRequest>,
 ) -> Result<impl IntoResponse, AppError> {
+    role.require(Permission::ManageDishes)?;
     let dish_name = DishName::new(payload.name)?;
     let selling_price = Money::from_cents(payload.selling_price_cents as i64)?;
     let recipe_id = RecipeId::from_uuid(payload.recipe_id);
@@ -54,6 +56,7 @@ pub async fn create_dish(
     let dish = service
         .create_dish(
             tenant_id,
+            user_id,
             recipe_id,
             dish_name,
             payload.description,
@@ -85,6 +88,7 @@ pub async fn list_dishes(
     auth: AuthUser,
     Query(query): Query<ListDishesQuery>,
 ) -> Result<impl IntoResponse, AppError> {
+    auth.require(Permission::ViewDishes)?;
     let pagination = PaginationParams {
         page: query.page,
         per_page: query.per_page,
@@ -113,6 +117,7 @@ pub async fn recalculate_all_costs(
     State(service): State<DishService>,
     auth: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
+    auth.require(Permission::ManageDishes)?;
     let result = service.recalculate_all_costs(auth.tenant_id).await?;
 
     Ok(Json(serde_json::json!({
@@ -0,0 +1,357 @@
+//! HTTP handlers for the allergen matrix, dish labels and "may contain"
+//! declarations.
+//!
+//! Mounted inside the protected router. Matrix and label come as JSON
+//! (default), CSV (matrix only) or a printable HTML page via `?format=`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use std::fmt::Write;
+use uuid::Uuid;
+
+use crate::application::dish_label::{
+    AllergenMatrix, AllergenTraceDeclaration, DeclareTraceInput, DishLabel, DishLabelService,
+};
+use crate::domain::dish_label::{AllergenPresence, NutritionFacts};
+use crate::domain::menu::MenuId;
+use crate::domain::{DishId, Permission};
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::{translate_label_text, AppError, LabelText, Language};
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+    Html,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MatrixQuery {
+    /// Defaults to the user's language
+    #[serde(default)]
+    pub language: Option<Language>,
+    /// Only dishes listed on this menu
+    #[serde(default)]
+    pub menu_id: Option<Uuid>,
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LabelQuery {
+    #[serde(default)]
+    pub language: Option<Language>,
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// `GET /api/allergens/matrix?language=&menu_id=&format=json|csv|html`
+pub async fn allergen_matrix(
+    State(service): State<DishLabelService>,
+    auth: AuthUser,
+    Query(query): Query<MatrixQuery>,
+) -> Result<Response, AppError> {
+    auth.require(Permission::ViewDishes)?;
+    let language = query.language.unwrap_or(auth.language);
+    let matrix = service
+        .allergen_matrix(
+            auth.tenant_id,
+            language,
+            query.menu_id.map(MenuId::from_uuid),
+        )
+        .await?;
+
+    Ok(match query.format {
+        ExportFormat::Json => Json(matrix).into_response(),
+        ExportFormat::Csv => (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"allergen-matrix.csv\"",
+                ),
+            ],
+            matrix_csv(&matrix),
+        )
+            .into_response(),
+        ExportFormat::Html => html_response(matrix_html(&matrix)),
+    })
+}
+
+/// `GET /api/dishes/:id/label?language=&format=json|html`
+pub async fn dish_label(
+    State(service): State<DishLabelService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<LabelQuery>,
+) -> Result<Response, AppError> {
+    auth.require(Permission::ViewDishes)?;
+    let language = query.language.unwrap_or(auth.language);
+    let label = service
+        .dish_label(auth.tenant_id, DishId::from_uuid(id), language)
+        .await?;
+
+    Ok(match query.format {
+        ExportFormat::Json => Json(label).into_response(),
+        ExportFormat::Html => html_response(label_html(&label)),
+        ExportFormat::Csv => {
+            return Err(AppError::validation(
+                "CSV export is available for the allergen matrix only",
+            ))
+        }
+    })
+}
+
+/// `GET /api/allergens/traces`
+pub async fn list_traces(
+    State(service): State<DishLabelService>,
+    auth: AuthUser,
+) -> Result<Json<Vec<AllergenTraceDeclaration>>, AppError> {
+    auth.require(Permission::ViewRecipes)?;
+    let traces = service.list_traces(auth.tenant_id).await?;
+    Ok(Json(traces))
+}
+
+/// `POST /api/allergens/traces` — "may contain" on an ingredient or recipe
+pub async fn declare_trace(
+    State(service): State<DishLabelService>,
+    auth: AuthUser,
+    Json(req): Json<DeclareTraceInput>,
+) -> Result<(StatusCode, Json<AllergenTraceDeclaration>), AppError> {
+    auth.require(Permission::ManageRecipes)?;
+    let trace = service.declare_trace(auth.tenant_id, req).await?;
+    Ok((StatusCode::CREATED, Json(trace)))
+}
+
+/// `DELETE /api/allergens/traces/:id`
+pub async fn delete_trace(
+    State(service): State<DishLabelService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageRecipes)?;
+    service.delete_trace(auth.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn html_response(body: String) -> Response {
+    ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], body).into_response()
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn presence_text(presence: Option<AllergenPresence>, lang: Language) -> &'static str {
+    match presence {
+        Some(AllergenPresence::Contains) => translate_label_text(LabelText::Contains, lang),
+        Some(AllergenPresence::MayContain) => translate_label_text(LabelText::MayContain, lang),
+        None => "",
+    }
+}
+
+/// UTF-8 BOM first so spreadsheet apps detect the encoding
+fn matrix_csv(matrix: &AllergenMatrix) -> String {
+    let lang = matrix.language;
+    let mut csv = String::from("\u{feff}");
+    csv.push_str(&escape_csv(translate_label_text(LabelText::Dish, lang)));
+    for column in &matrix.columns {
+        csv.push(',');
+        csv.push_str(&escape_csv(&column.label));
+    }
+    csv.push_str("\r\n");
+    for row in &matrix.rows {
+        csv.push_str(&escape_csv(&row.dish_name));
+        for cell in &row.cells {
+            csv.push(',');
+            csv.push_str(&escape_csv(presence_text(*cell, lang)));
+        }
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+const PRINT_CSS: &str = "body{font-family:sans-serif;margin:24px;color:#111}\
+table{border-collapse:collapse;width:100%}\
+th,td{border:1px solid #999;padding:4px 6px;font-size:12px;text-align:center}\
+td:first-child,th:first-child{text-align:left}\
+th.rot{writing-mode:vertical-rl;transform:rotate(180deg);white-space:nowrap}\
+.contains{background:#111;color:#fff;font-weight:bold}\
+.may{background:#ddd}\
+.legend span{display:inline-block;padding:2px 8px;margin-right:8px;border:1px solid #999}\
+@media print{body{margin:0}}";
+
+fn page(lang: Language, title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\"><head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head><body>{}</body></html>\n",
+        lang.code(),
+        escape_html(title),
+        PRINT_CSS,
+        body
+    )
+}
+
+fn matrix_html(matrix: &AllergenMatrix) -> String {
+    let lang = matrix.language;
+    let contains = translate_label_text(LabelText::Contains, lang);
+    let may_contain = translate_label_text(LabelText::MayContain, lang);
+    let title = translate_label_text(LabelText::Allergens, lang);
+
+    let mut body = format!("<h1>{}</h1>", escape_html(title));
+    let _ = write!(
+        body,
+        "<p class=\"legend\"><span class=\"contains\">●</span>{}<span class=\"may\">○</span>{}</p>",
+        escape_html(contains),
+        escape_html(may_contain)
+    );
+    let _ = write!(
+        body,
+        "<table><thead><tr><th>{}</th>",
+        escape_html(translate_label_text(LabelText::Dish, lang))
+    );
+    for column in &matrix.columns {
+        let _ = write!(
+            body,
+            "<th class=\"rot\">{}</th>",
+            escape_html(&column.label)
+        );
+    }
+    body.push_str("</tr></thead><tbody>");
+    for row in &matrix.rows {
+        let _ = write!(body, "<tr><td>{}</td>", escape_html(&row.dish_name));
+        for cell in &row.cells {
+            body.push_str(match cell {
+                Some(AllergenPresence::Contains) => "<td class=\"contains\">●</td>",
+                Some(AllergenPresence::MayContain) => "<td class=\"may\">○</td>",
+                None => "<td></td>",
+            });
+        }
+        body.push_str("</tr>");
+    }
+    body.push_str("</tbody></table>");
+    page(lang, title, &body)
+}
+
+/// Label heading and how to print the value
+type NutrientRow = (LabelText, fn(&NutritionFacts) -> String);
+
+fn label_html(label: &DishLabel) -> String {
+    let lang = label.language;
+    let t = |key| escape_html(translate_label_text(key, lang));
+    let mut body = format!("<h1>{}</h1>", escape_html(&label.dish_name));
+
+    let _ = write!(body, "<h2>{}</h2>", t(LabelText::Allergens));
+    let list = |presence: AllergenPresence| -> Vec<String> {
+        label
+            .allergens
+            .iter()
+            .filter(|a| a.presence == presence)
+            .map(|a| escape_html(&a.label))
+            .collect()
+    };
+    let contained = list(AllergenPresence::Contains);
+    let traces = list(AllergenPresence::MayContain);
+    if contained.is_empty() && traces.is_empty() {
+        let _ = write!(body, "<p>{}</p>", t(LabelText::NoAllergens));
+    }
+    if !contained.is_empty() {
+        let _ = write!(
+            body,
+            "<p><strong>{}:</strong> {}</p>",
+            t(LabelText::Contains),
+            contained.join(", ")
+        );
+    }
+    if !traces.is_empty() {
+        let _ = write!(
+            body,
+            "<p>{}: {}</p>",
+            t(LabelText::MayContain),
+            traces.join(", ")
+        );
+    }
+    for modifier in &label.modifier_allergens {
+        let labels: Vec<String> = modifier.labels.iter().map(|l| escape_html(l)).collect();
+        let _ = write!(
+            body,
+            "<p>+ {} ({}): {}</p>",
+            escape_html(&modifier.option_name),
+            escape_html(&modifier.group_name),
+            labels.join(", ")
+        );
+    }
+
+    let nutrition = &label.nutrition;
+    let _ = write!(
+        body,
+        "<h2>{}</h2><table><thead><tr><th></th><th>{}</th><th>{} ({} g)</th></tr></thead><tbody>",
+        t(LabelText::Nutrition),
+        t(LabelText::Per100g),
+        t(LabelText::PerPortion),
+        nutrition.portion_weight_g
+    );
+    let rows: [NutrientRow; 8] = [
+        (LabelText::Energy, |n| {
+            format!("{} kJ / {} kcal", n.energy_kj, n.energy_kcal)
+        }),
+        (LabelText::Fat, |n| format!("{:.1} g", n.fat_g)),
+        (LabelText::Saturates, |n| {
+            format!("{:.1} g", n.saturated_fat_g)
+        }),
+        (LabelText::Carbohydrate, |n| format!("{:.1} g", n.carbs_g)),
+        (LabelText::Sugars, |n| format!("{:.1} g", n.sugars_g)),
+        (LabelText::Fibre, |n| format!("{:.1} g", n.fiber_g)),
+        (LabelText::Protein, |n| format!("{:.1} g", n.protein_g)),
+        (LabelText::Salt, |n| format!("{:.1} g", n.salt_g)),
+    ];
+    for (key, value) in rows {
+        let _ = write!(
+            body,
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+            t(key),
+            value(&nutrition.per_100g),
+            value(&nutrition.per_portion)
+        );
+    }
+    body.push_str("</tbody></table>");
+    if !nutrition.incomplete_ingredients.is_empty() {
+        let names: Vec<String> = nutrition
+            .incomplete_ingredients
+            .iter()
+            .map(|n| escape_html(n))
+            .collect();
+        let _ = write!(
+            body,
+            "<p><small>{}: {}</small></p>",
+            t(LabelText::IncompleteData),
+            names.join(", ")
+        );
+    }
+    page(lang, &label.dish_name, &body)
+}
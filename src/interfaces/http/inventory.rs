@@ -8,10 +8,16 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::application::inventory::{InventoryService, InventoryStatus, InventoryView, LossReport};
+use crate::application::inventory::{
+    InventoryService, InventoryStatus, InventoryView, LossReport, ShortfallView,
+};
 use crate::domain::{
     catalog::CatalogIngredientId,
-    inventory::{InventoryAlert, InventoryBatch, InventoryBatchId},
+    inventory::{CostingStrategy, InventoryAlert, InventoryBatch, InventoryBatchId, StockPolicy},
+    storage_location::StorageLocationId,
+    supplier::SupplierId,
+    waste::LossBucket,
+    Permission,
 };
 use crate::interfaces::http::middleware::AuthUser;
 use crate::shared::{AppError, PaginationParams};
@@ -31,6 +37,22 @@ pub struct AddProductRequest {
     /// Expiration date (дата просрочки)
     #[serde(with = "time::serde::rfc3339")]
     pub expires_at: OffsetDateTime,
+    /// Supplier entity (preferred over free-text `supplier`)
+    #[serde(default)]
+    pub supplier_id: Option<Uuid>,
+    #[serde(default)]
+    pub supplier: Option<String>,
+    #[serde(default)]
+    pub invoice_number: Option<String>,
+    /// Storage location (tenant default when omitted)
+    #[serde(default)]
+    pub location_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocationQuery {
+    /// Restrict to one storage location
+    pub location_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -83,6 +105,7 @@ pub async fn list_products(
     auth: AuthUser,
     Query(pagination): Query<PaginationParams>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::ViewInventory)?;
     // 🎯 Backend = source of truth для языка!
     let products = service
         .list_products_with_details(auth.user_id, auth.tenant_id, auth.language)
@@ -123,13 +146,18 @@ pub async fn add_product(
     auth: AuthUser,
     Json(req): Json<AddProductRequest>,
 ) -> Result<(StatusCode, Json<InventoryView>), AppError> {
+    auth.require(Permission::ManageInventory)?;
     let product_id = service
-        .add_product(
+        .add_batch(
             auth.user_id,
             auth.tenant_id,
             CatalogIngredientId::from_uuid(req.catalog_ingredient_id),
             req.price_per_unit_cents,
             req.quantity,
+            req.supplier_id.map(SupplierId::from_uuid),
+            req.supplier,
+            req.invoice_number,
+            req.location_id.map(StorageLocationId::from_uuid),
             req.received_at,
             req.expires_at,
         )
@@ -157,6 +185,7 @@ pub async fn update_product(
     Path(id): Path<Uuid>,
     Json(req): Json<UpdateProductRequest>,
 ) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageInventory)?;
     service
         .update_product(
             InventoryBatchId::from_uuid(id),
@@ -177,6 +206,7 @@ pub async fn delete_product(
     auth: AuthUser,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageInventory)?;
     service
         .delete_product(
             InventoryBatchId::from_uuid(id),
@@ -188,13 +218,21 @@ pub async fn delete_product(
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// GET /api/inventory/dashboard
+/// GET /api/inventory/dashboard?location_id=...
 /// Comprehensive dashboard for the owner
 pub async fn get_dashboard(
     State(service): State<InventoryService>,
     auth: AuthUser,
+    Query(query): Query<LocationQuery>,
 ) -> Result<Json<crate::application::inventory::InventoryDashboard>, AppError> {
-    let dashboard = service.get_dashboard(auth.tenant_id, auth.language).await?;
+    auth.require(Permission::ViewInventory)?;
+    let dashboard = service
+        .get_dashboard_at(
+            auth.tenant_id,
+            auth.language,
+            query.location_id.map(StorageLocationId::from_uuid),
+        )
+        .await?;
     Ok(Json(dashboard))
 }
 
@@ -204,6 +242,7 @@ pub async fn get_health(
     State(service): State<InventoryService>,
     auth: AuthUser,
 ) -> Result<Json<InventoryStatus>, AppError> {
+    auth.require(Permission::ViewInventory)?;
     let status = service.get_status(auth.tenant_id).await?;
     Ok(Json(status))
 }
@@ -214,6 +253,7 @@ pub async fn get_alerts(
     State(service): State<InventoryService>,
     auth: AuthUser,
 ) -> Result<Json<Vec<InventoryAlert>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
     let alerts = service.get_alerts(auth.tenant_id).await?;
     Ok(Json(alerts))
 }
@@ -224,6 +264,7 @@ pub async fn process_expirations(
     State(service): State<InventoryService>,
     auth: AuthUser,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    auth.require(Permission::ManageInventory)?;
     let count = service.process_expirations(auth.tenant_id).await?;
     Ok(Json(serde_json::json!({ "processed_count": count })))
 }
@@ -232,25 +273,122 @@ pub async fn process_expirations(
 pub struct LossReportQuery {
     #[serde(default = "default_loss_report_days")]
     pub days: i32,
+    /// Timeline bucket (day/week/month); derived from `days` when omitted
+    pub bucket: Option<LossBucket>,
 }
 
 fn default_loss_report_days() -> i32 {
     30
 }
 
-/// GET /api/inventory/reports/loss
-/// Financial loss report (expired quantities/costs)
+/// GET /api/inventory/reports/loss?days=30&bucket=day
+/// Financial loss report (auto-expired + logged waste) by ingredient, reason and time
 pub async fn get_loss_report(
     State(service): State<InventoryService>,
     auth: AuthUser,
     axum::extract::Query(query): axum::extract::Query<LossReportQuery>,
 ) -> Result<Json<LossReport>, AppError> {
+    auth.require(Permission::ViewReports)?;
+    let bucket = query
+        .bucket
+        .unwrap_or_else(|| LossBucket::for_period(query.days));
     let report = service
-        .get_loss_report(auth.tenant_id, query.days, auth.language.code())
+        .get_loss_report_bucketed(auth.tenant_id, query.days, auth.language.code(), bucket)
         .await?;
     Ok(Json(report))
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockPolicyPayload {
+    pub stock_policy: StockPolicy,
+}
+
+/// GET /api/inventory/settings/stock-policy
+/// What a sale does when FIFO stock runs out
+pub async fn get_stock_policy(
+    State(service): State<InventoryService>,
+    auth: AuthUser,
+) -> Result<Json<StockPolicyPayload>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let stock_policy = service.get_stock_policy(auth.tenant_id).await?;
+    Ok(Json(StockPolicyPayload { stock_policy }))
+}
+
+/// PUT /api/inventory/settings/stock-policy
+pub async fn set_stock_policy(
+    State(service): State<InventoryService>,
+    auth: AuthUser,
+    Json(req): Json<StockPolicyPayload>,
+) -> Result<Json<StockPolicyPayload>, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let stock_policy = service
+        .set_stock_policy(auth.tenant_id, req.stock_policy)
+        .await?;
+    Ok(Json(StockPolicyPayload { stock_policy }))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CostingStrategyPayload {
+    pub costing_strategy: CostingStrategy,
+}
+
+/// GET /api/inventory/settings/costing-strategy
+/// Which unit price recipe costing uses
+pub async fn get_costing_strategy(
+    State(service): State<InventoryService>,
+    auth: AuthUser,
+) -> Result<Json<CostingStrategyPayload>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let costing_strategy = service.get_costing_strategy(auth.tenant_id).await?;
+    Ok(Json(CostingStrategyPayload { costing_strategy }))
+}
+
+/// PUT /api/inventory/settings/costing-strategy
+pub async fn set_costing_strategy(
+    State(service): State<InventoryService>,
+    auth: AuthUser,
+    Json(req): Json<CostingStrategyPayload>,
+) -> Result<Json<CostingStrategyPayload>, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let costing_strategy = service
+        .set_costing_strategy(auth.tenant_id, req.costing_strategy)
+        .await?;
+    Ok(Json(CostingStrategyPayload { costing_strategy }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShortfallsQuery {
+    #[serde(default)]
+    pub include_resolved: bool,
+}
+
+/// GET /api/inventory/shortfalls?include_resolved=false
+/// Quantities sold without stock on hand
+pub async fn list_shortfalls(
+    State(service): State<InventoryService>,
+    auth: AuthUser,
+    Query(query): Query<ShortfallsQuery>,
+) -> Result<Json<Vec<ShortfallView>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let items = service
+        .list_shortfalls(auth.tenant_id, query.include_resolved, auth.language.code())
+        .await?;
+    Ok(Json(items))
+}
+
+/// POST /api/inventory/shortfalls/:id/resolve
+pub async fn resolve_shortfall(
+    State(service): State<InventoryService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    service
+        .resolve_shortfall(auth.tenant_id, id, auth.user_id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 pub fn router(service: InventoryService) -> Router {
     Router::new()
         .route("/products", get(list_products))
@@ -262,5 +400,11 @@ pub fn router(service: InventoryService) -> Router {
         .route("/dashboard", get(get_dashboard)) // New ownership dashboard
         .route("/reports/loss", get(get_loss_report))
         .route("/process-expirations", post(process_expirations))
+        .route("/settings/stock-policy", get(get_stock_policy))
+        .route("/settings/stock-policy", put(set_stock_policy))
+        .route("/settings/costing-strategy", get(get_costing_strategy))
+        .route("/settings/costing-strategy", put(set_costing_strategy))
+        .route("/shortfalls", get(list_shortfalls))
+        .route("/shortfalls/:id/resolve", post(resolve_shortfall))
         .with_state(service)
 }
@@ -0,0 +1,159 @@
+//! HTTP handlers for menus, menu sections and section dishes.
+//!
+//! Mounted under `/api/menus` inside the protected router.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::application::menu::{
+    CreateMenuInput, CreateMenuSectionInput, MenuSectionView, MenuService, MenuView,
+    SetSectionDishesInput, UpdateMenuInput, UpdateMenuSectionInput,
+};
+use crate::domain::menu::{MenuId, MenuSection, MenuSectionId};
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct ListMenusQuery {
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+/// `GET /api/menus?include_inactive=false`
+pub async fn list_menus(
+    State(service): State<MenuService>,
+    auth: AuthUser,
+    Query(query): Query<ListMenusQuery>,
+) -> Result<Json<Vec<MenuView>>, AppError> {
+    auth.require(Permission::ViewDishes)?;
+    let menus = service
+        .list_menus(auth.tenant_id, query.include_inactive)
+        .await?;
+    Ok(Json(menus))
+}
+
+/// `POST /api/menus`
+pub async fn create_menu(
+    State(service): State<MenuService>,
+    auth: AuthUser,
+    Json(req): Json<CreateMenuInput>,
+) -> Result<(StatusCode, Json<MenuView>), AppError> {
+    auth.require(Permission::ManageDishes)?;
+    let menu = service.create_menu(auth.tenant_id, req).await?;
+    Ok((StatusCode::CREATED, Json(menu)))
+}
+
+/// `GET /api/menus/:id`
+pub async fn get_menu(
+    State(service): State<MenuService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MenuView>, AppError> {
+    auth.require(Permission::ViewDishes)?;
+    let menu = service
+        .get_menu(auth.tenant_id, MenuId::from_uuid(id))
+        .await?;
+    Ok(Json(menu))
+}
+
+/// `PUT /api/menus/:id`
+pub async fn update_menu(
+    State(service): State<MenuService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateMenuInput>,
+) -> Result<Json<MenuView>, AppError> {
+    auth.require(Permission::ManageDishes)?;
+    let menu = service
+        .update_menu(auth.tenant_id, MenuId::from_uuid(id), req)
+        .await?;
+    Ok(Json(menu))
+}
+
+/// `DELETE /api/menus/:id`
+pub async fn delete_menu(
+    State(service): State<MenuService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageDishes)?;
+    service
+        .delete_menu(auth.tenant_id, MenuId::from_uuid(id))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /api/menus/:id/sections`
+pub async fn create_section(
+    State(service): State<MenuService>,
+    auth: AuthUser,
+    Path(menu_id): Path<Uuid>,
+    Json(req): Json<CreateMenuSectionInput>,
+) -> Result<(StatusCode, Json<MenuSection>), AppError> {
+    auth.require(Permission::ManageDishes)?;
+    let section = service
+        .create_section(auth.tenant_id, MenuId::from_uuid(menu_id), req)
+        .await?;
+    Ok((StatusCode::CREATED, Json(section)))
+}
+
+/// `PUT /api/menus/:id/sections/:section_id`
+pub async fn update_section(
+    State(service): State<MenuService>,
+    auth: AuthUser,
+    Path((menu_id, section_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<UpdateMenuSectionInput>,
+) -> Result<Json<MenuSection>, AppError> {
+    auth.require(Permission::ManageDishes)?;
+    let section = service
+        .update_section(
+            auth.tenant_id,
+            MenuId::from_uuid(menu_id),
+            MenuSectionId::from_uuid(section_id),
+            req,
+        )
+        .await?;
+    Ok(Json(section))
+}
+
+/// `DELETE /api/menus/:id/sections/:section_id`
+pub async fn delete_section(
+    State(service): State<MenuService>,
+    auth: AuthUser,
+    Path((menu_id, section_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageDishes)?;
+    service
+        .delete_section(
+            auth.tenant_id,
+            MenuId::from_uuid(menu_id),
+            MenuSectionId::from_uuid(section_id),
+        )
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `PUT /api/menus/:id/sections/:section_id/dishes` — replaces the section's dishes
+pub async fn set_section_dishes(
+    State(service): State<MenuService>,
+    auth: AuthUser,
+    Path((menu_id, section_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<SetSectionDishesInput>,
+) -> Result<Json<MenuSectionView>, AppError> {
+    auth.require(Permission::ManageDishes)?;
+    let section = service
+        .set_section_dishes(
+            auth.tenant_id,
+            MenuId::from_uuid(menu_id),
+            MenuSectionId::from_uuid(section_id),
+            req,
+        )
+        .await?;
+    Ok(Json(section))
+}
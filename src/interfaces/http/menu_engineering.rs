@@ -7,6 +7,10 @@ use axum::{
 use serde::Deserialize;
 
 use crate::application::MenuEngineeringService;
+use crate::domain::menu::{MenuId, MenuSectionId};
+use crate::domain::modifier::ModifierOptionId;
+use crate::domain::storage_location::StorageLocationId;
+use crate::domain::Permission;
 use crate::interfaces::http::middleware::AuthUser;
 use crate::shared::{AppError, Language};
 
@@ -20,6 +24,10 @@ pub struct AnalysisQuery {
     /// Language for recommendations (default: en)
     #[serde(default)]
     language: Language,
+
+    /// Only analyse the sections of this menu
+    #[serde(default)]
+    menu_id: Option<uuid::Uuid>,
 }
 
 fn default_period() -> u32 {
@@ -28,32 +36,75 @@ fn default_period() -> u32 {
 
 /// GET /api/menu-engineering/analysis
 ///
-/// Returns Menu Engineering Matrix with all dishes classified
+/// Returns Menu Engineering Matrix with all dishes classified within their
+/// menu section (Kasavana–Smith)
 pub async fn analyze_menu(
     State(service): State<MenuEngineeringService>,
     AuthUser {
         user_id,
         tenant_id,
         language: _,
+        role,
     }: AuthUser, // 🎯 Игнорируем language (используем из query)
     Query(params): Query<AnalysisQuery>,
 ) -> Result<impl IntoResponse, AppError> {
+    role.require(Permission::ViewMenuEngineering)?;
     let matrix = service
-        .analyze_menu(user_id, tenant_id, params.language, params.period_days)
+        .analyze_menu(
+            user_id,
+            tenant_id,
+            params.language,
+            params.period_days,
+            params.menu_id.map(MenuId::from_uuid),
+        )
         .await?;
 
     Ok(Json(matrix))
 }
 
+/// Query parameters for the modifier report
+#[derive(Debug, Deserialize)]
+pub struct ModifierReportQuery {
+    /// Period in days (default: 30)
+    #[serde(default = "default_period")]
+    period_days: u32,
+}
+
+/// GET /api/menu-engineering/modifiers
+///
+/// Attach rate and margin per modifier option over the period
+pub async fn analyze_modifiers(
+    State(service): State<MenuEngineeringService>,
+    auth: AuthUser,
+    Query(params): Query<ModifierReportQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require(Permission::ViewMenuEngineering)?;
+    let report = service
+        .analyze_modifiers(auth.tenant_id, params.period_days)
+        .await?;
+
+    Ok(Json(report))
+}
+
 /// POST /api/menu-engineering/sales
 ///
-/// Record a dish sale (normally called from POS/order system)
+/// Record a dish sale (normally called from POS/order system). The recipe
+/// cost is computed server-side; a `recipe_cost_cents` field is ignored.
 #[derive(Debug, Deserialize)]
 pub struct RecordSaleRequest {
     pub dish_id: uuid::Uuid,
     pub quantity: u32,
     pub selling_price_cents: i32,
-    pub recipe_cost_cents: i32,
+    /// Selling outlet; ingredients are deducted from its storage location
+    #[serde(default)]
+    pub location_id: Option<uuid::Uuid>,
+    /// Menu section the dish was sold from
+    #[serde(default)]
+    pub menu_section_id: Option<uuid::Uuid>,
+    /// Modifier options chosen (size, add-ons, removals); the selling
+    /// price is the price charged with them
+    #[serde(default)]
+    pub modifier_option_ids: Vec<uuid::Uuid>,
 }
 
 pub async fn record_sale(
@@ -62,9 +113,11 @@ pub async fn record_sale(
         user_id,
         tenant_id,
         language: _,
+        role,
     }: AuthUser, // 🎯 Игнорируем language
     Json(payload): Json<RecordSaleRequest>,
 ) -> Result<impl IntoResponse, AppError> {
+    role.require(Permission::RecordSales)?;
     service
         .record_sale(
             tenant_id,
@@ -72,7 +125,13 @@ pub async fn record_sale(
             user_id,
             payload.quantity,
             payload.selling_price_cents,
-            payload.recipe_cost_cents,
+            payload.location_id.map(StorageLocationId::from_uuid),
+            payload.menu_section_id.map(MenuSectionId::from_uuid),
+            payload
+                .modifier_option_ids
+                .into_iter()
+                .map(ModifierOptionId::from_uuid)
+                .collect(),
         )
         .await?;
 
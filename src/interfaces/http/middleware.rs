@@ -1,5 +1,5 @@
 use crate::application::AdminAuthService;
-use crate::domain::AdminClaims;
+use crate::domain::{AdminClaims, Permission, UserRole};
 use crate::infrastructure::JwtService;
 use crate::shared::{AppError, Language, TenantId, UserId};
 use axum::{
@@ -19,6 +19,14 @@ pub struct AuthUser {
     pub user_id: UserId,
     pub tenant_id: TenantId,
     pub language: Language, // 🎯 ДОБАВЛЕНО: источник языка = backend!
+    pub role: UserRole,
+}
+
+impl AuthUser {
+    /// Reject the request unless the user's role grants `permission`.
+    pub fn require(&self, permission: Permission) -> Result<(), AppError> {
+        self.role.require(permission)
+    }
 }
 
 #[async_trait]
@@ -54,17 +62,30 @@ where
             .ok_or_else(|| AppError::internal("Database pool not configured"))?
             .clone();
 
-        let language = sqlx::query_scalar::<_, String>("SELECT language FROM users WHERE id = $1")
-            .bind(user_id.as_uuid())
-            .fetch_optional(&pool)
-            .await?
-            .and_then(|lang| Language::from_str(&lang).ok())
-            .unwrap_or(Language::En); // Fallback to English
+        // Language and role come from the database (source of truth!). The
+        // tenant check rejects tokens issued before the user changed restaurant.
+        let (language, role, user_tenant) = sqlx::query_as::<_, (String, String, uuid::Uuid)>(
+            "SELECT language, role, tenant_id FROM users WHERE id = $1",
+        )
+        .bind(user_id.as_uuid())
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::authentication("User not found"))?;
+
+        if &user_tenant != tenant_id.as_uuid() {
+            return Err(AppError::authentication(
+                "Session is no longer valid for this restaurant, please sign in again",
+            ));
+        }
+
+        let language = Language::from_str(&language).unwrap_or(Language::En); // Fallback to English
+        let role = UserRole::from_str(&role)?;
 
         Ok(AuthUser {
             user_id,
             tenant_id,
             language, // 🎯 Backend = source of truth!
+            role,
         })
     }
 }
@@ -120,3 +141,270 @@ pub async fn require_super_admin(
 
     Ok(next.run(req).await)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::audit::AuditLog;
+    use crate::application::menu_engineering::MenuEngineeringService;
+    use crate::application::pos_import::PosImportService;
+    use crate::application::purchase_receipt::PurchaseReceiptService;
+    use crate::application::reorder::{ReorderService, ReorderSettings};
+    use crate::application::stocktake::StocktakeService;
+    use crate::application::storage_location::StorageLocationService;
+    use crate::application::supplier::SupplierService;
+    use crate::application::TenantIngredientService;
+    use crate::infrastructure::persistence::Repositories;
+    use crate::interfaces::http::{
+        pos_import, purchase_receipt, reorder, stocktake, storage_location, supplier,
+        tenant_ingredient,
+    };
+    use axum::extract::{Path, Query};
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use axum::Json;
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn staff() -> AuthUser {
+        AuthUser {
+            user_id: UserId::new(),
+            tenant_id: TenantId::new(),
+            language: Language::En,
+            role: UserRole::Staff,
+        }
+    }
+
+    fn body<T: DeserializeOwned>(value: serde_json::Value) -> Json<T> {
+        Json(serde_json::from_value(value).unwrap())
+    }
+
+    fn assert_forbidden<T>(route: &str, result: Result<T, AppError>) {
+        match result {
+            Ok(_) => panic!("{} is open to staff", route),
+            Err(e) => assert_eq!(
+                e.into_response().status(),
+                StatusCode::FORBIDDEN,
+                "{}",
+                route
+            ),
+        }
+    }
+
+    /// The permission check runs before any query, so a pool that never
+    /// connects is enough.
+    #[test]
+    fn staff_is_forbidden_on_back_office_writes() {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+                let repos = Repositories::new(pool.clone());
+                let id = Uuid::new_v4();
+
+                let stocktakes = State(StocktakeService::new(pool.clone()));
+                assert_forbidden(
+                    "POST /api/inventory/stocktakes",
+                    stocktake::open_session(
+                        stocktakes.clone(),
+                        staff(),
+                        Json(stocktake::OpenStocktakeRequest { note: None }),
+                    )
+                    .await,
+                );
+                // Staff count, a manager opens and closes: an empty count
+                // gets past the permission check to input validation
+                let counts = stocktake::add_counts(
+                    stocktakes.clone(),
+                    staff(),
+                    Path(id),
+                    Json(stocktake::AddCountsRequest { counts: vec![] }),
+                )
+                .await;
+                assert!(matches!(counts, Err(AppError::Validation(_))));
+                assert_forbidden(
+                    "DELETE /api/inventory/stocktakes/:id/counts/:count_id",
+                    stocktake::delete_count(stocktakes.clone(), staff(), Path((id, id))).await,
+                );
+                assert_forbidden(
+                    "POST /api/inventory/stocktakes/:id/close",
+                    stocktake::close_session(stocktakes.clone(), staff(), Path(id)).await,
+                );
+                assert_forbidden(
+                    "POST /api/inventory/stocktakes/:id/cancel",
+                    stocktake::cancel_session(stocktakes, staff(), Path(id)).await,
+                );
+
+                assert_forbidden(
+                    "POST /api/purchase-drafts/:id/receive",
+                    purchase_receipt::receive_draft(
+                        State(PurchaseReceiptService::new(pool.clone())),
+                        staff(),
+                        Path(id),
+                        body(json!({ "items": [] })),
+                    )
+                    .await,
+                );
+
+                let locations = State(StorageLocationService::new(pool.clone()));
+                assert_forbidden(
+                    "POST /api/inventory/locations",
+                    storage_location::create_location(
+                        locations.clone(),
+                        staff(),
+                        body(json!({ "name": "Cellar", "kind": "dry_store" })),
+                    )
+                    .await,
+                );
+                assert_forbidden(
+                    "PUT /api/inventory/locations/:id",
+                    storage_location::update_location(
+                        locations.clone(),
+                        staff(),
+                        Path(id),
+                        body(json!({ "name": "Cellar" })),
+                    )
+                    .await,
+                );
+                assert_forbidden(
+                    "DELETE /api/inventory/locations/:id",
+                    storage_location::deactivate_location(locations.clone(), staff(), Path(id))
+                        .await,
+                );
+                assert_forbidden(
+                    "POST /api/inventory/transfers",
+                    storage_location::create_transfer(
+                        locations,
+                        staff(),
+                        body(json!({
+                            "from_location_id": id,
+                            "to_location_id": id,
+                            "catalog_ingredient_id": id,
+                            "quantity": 1.0
+                        })),
+                    )
+                    .await,
+                );
+
+                let suppliers = State(SupplierService::new(pool.clone()));
+                assert_forbidden(
+                    "POST /api/suppliers",
+                    supplier::create_supplier(
+                        suppliers.clone(),
+                        staff(),
+                        body(json!({ "name": "Metro" })),
+                    )
+                    .await,
+                );
+                assert_forbidden(
+                    "PUT /api/suppliers/:id",
+                    supplier::update_supplier(
+                        suppliers.clone(),
+                        staff(),
+                        Path(id),
+                        body(json!({ "name": "Metro" })),
+                    )
+                    .await,
+                );
+                assert_forbidden(
+                    "DELETE /api/suppliers/:id",
+                    supplier::delete_supplier(suppliers, staff(), Path(id)).await,
+                );
+
+                let reorders = State(ReorderService::new(pool.clone()));
+                assert_forbidden(
+                    "POST /api/inventory/reorder/run",
+                    reorder::run_reorder(reorders.clone(), staff(), None).await,
+                );
+                assert_forbidden(
+                    "PUT /api/inventory/settings/auto-reorder",
+                    reorder::set_settings(reorders, staff(), Json(ReorderSettings::default()))
+                        .await,
+                );
+
+                let pos = State(PosImportService::new(
+                    pool.clone(),
+                    MenuEngineeringService::new(
+                        pool.clone(),
+                        Arc::new(repos.inventory_product.clone()),
+                        Arc::new(repos.dish.clone()),
+                        Arc::new(repos.recipe.clone()),
+                        Arc::new(repos.catalog_ingredient.clone()),
+                    ),
+                ));
+                assert_forbidden(
+                    "POST /api/pos/webhook-secret",
+                    pos_import::rotate_webhook_secret(pos.clone(), staff()).await,
+                );
+                assert_forbidden(
+                    "POST /api/pos/import/csv",
+                    pos_import::import_csv(
+                        pos.clone(),
+                        staff(),
+                        Query(pos_import::CsvImportQuery { location_id: None }),
+                        String::new(),
+                    )
+                    .await,
+                );
+                assert_forbidden(
+                    "POST /api/pos/mappings",
+                    pos_import::upsert_mapping(
+                        pos.clone(),
+                        staff(),
+                        body(json!({ "plu": "42", "dish_id": id })),
+                    )
+                    .await,
+                );
+                assert_forbidden(
+                    "DELETE /api/pos/mappings/:id",
+                    pos_import::delete_mapping(pos.clone(), staff(), Path(id)).await,
+                );
+                assert_forbidden(
+                    "POST /api/pos/review/:id/resolve",
+                    pos_import::resolve_review_line(
+                        pos.clone(),
+                        staff(),
+                        Path(id),
+                        body(json!({ "dish_id": id })),
+                    )
+                    .await,
+                );
+                assert_forbidden(
+                    "POST /api/pos/review/:id/ignore",
+                    pos_import::ignore_review_line(pos, staff(), Path(id)).await,
+                );
+
+                let ingredients = State(TenantIngredientService::new(
+                    Arc::new(repos.tenant_ingredient.clone()),
+                    AuditLog::new(pool.clone()),
+                ));
+                assert_forbidden(
+                    "POST /api/tenant/ingredients",
+                    tenant_ingredient::add_ingredient(
+                        staff(),
+                        ingredients.clone(),
+                        body(json!({ "catalog_ingredient_id": id })),
+                    )
+                    .await,
+                );
+                assert_forbidden(
+                    "PUT /api/tenant/ingredients/:id",
+                    tenant_ingredient::update_ingredient(
+                        staff(),
+                        ingredients.clone(),
+                        Path(id),
+                        body(json!({ "price": "1.50" })),
+                    )
+                    .await,
+                );
+                assert_forbidden(
+                    "DELETE /api/tenant/ingredients/:id",
+                    tenant_ingredient::remove_ingredient(staff(), ingredients, Path(id)).await,
+                );
+            });
+    }
+}
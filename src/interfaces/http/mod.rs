@@ -12,6 +12,7 @@ pub mod admin_users;
 pub mod admin_version;
 pub mod almabuild;
 pub mod assistant;
+pub mod audit; // 🆕 Audit trail — GET /api/audit
 pub mod auth;
 pub mod billing; // 🆕 Stripe Checkout + Webhook
 pub mod catalog;
@@ -22,23 +23,36 @@ pub mod church_prayer_visualizer;
 pub mod cook_suggestions; // 🆕 Smart recipe suggestions from inventory
 pub mod copilot; // 🆕 Copilot — главный LLM Brain (POST /api/copilot/message)
 pub mod dish;
+pub mod dish_label; // 🆕 Allergen matrix + dish labels — /api/allergens, /api/dishes/:id/label
 pub mod error;
 pub mod health;
 pub mod icons_site;
 pub mod inventory;
 pub mod laboratory; // 🆕 Food-tech Laboratory HTTP handlers
+pub mod menu; // 🆕 Menus + sections — /api/menus
 pub mod menu_engineering;
 pub mod middleware;
+pub mod modifier; // 🆕 Dish modifiers — /api/dishes/:id/modifier-groups, /api/modifier-options
+pub mod pos_import; // 🆕 POS import — /api/pos, /webhooks/pos/:tenant_id
 pub mod preferences;
+pub mod pricing; // 🆕 What-if pricing — POST /api/pricing/simulate
 pub mod public;
+pub mod purchase_receipt; // 🆕 Goods received — /api/purchase-drafts/:id/receive
 pub mod recipe;
 pub mod recipe_ai_insights; // AI insights for recipes
 pub mod recipe_v2; // V2 with translations
+pub mod reorder; // 🆕 Reorder proposals — /api/inventory/reorder
 pub mod report;
+pub mod report_subscription; // 🆕 Report subscriptions + delivery history — /api/reports/subscriptions, /api/reports/deliveries
 pub mod routes;
 pub mod site_context;
 pub mod smart; // 🆕 SmartService — POST /api/smart/ingredient
 pub mod smart_parse; // 🆕 SmartParse — POST /api/smart/parse
+pub mod stocktake; // 🆕 Stocktake sessions — /api/inventory/stocktakes
+pub mod storage_location; // 🆕 Locations + transfers — /api/inventory/locations, /api/inventory/transfers
+pub mod supplier; // 🆕 Suppliers — /api/suppliers + price history
+pub mod team; // 🆕 Team — /api/team (members, invitations, ownership) + invite acceptance
 pub mod tenant_ingredient;
 pub mod usage; // ChefOS iOS usage endpoints
+pub mod waste; // 🆕 Waste log — /api/inventory/waste
 pub mod user; // ChefOS user preferences endpoints
@@ -0,0 +1,114 @@
+//! HTTP handlers for dish modifier groups and options.
+//!
+//! Mounted inside the protected router under `/api/dishes/:id/modifier-groups`,
+//! `/api/modifier-groups` and `/api/modifier-options`.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::application::modifier::{
+    CreateModifierGroupInput, ModifierOptionInput, ModifierService, UpdateModifierGroupInput,
+};
+use crate::domain::modifier::{ModifierGroup, ModifierGroupId, ModifierOption, ModifierOptionId};
+use crate::domain::{DishId, Permission};
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+/// `GET /api/dishes/:id/modifier-groups`
+pub async fn list_groups(
+    State(service): State<ModifierService>,
+    auth: AuthUser,
+    Path(dish_id): Path<Uuid>,
+) -> Result<Json<Vec<ModifierGroup>>, AppError> {
+    auth.require(Permission::ViewDishes)?;
+    let groups = service
+        .list_groups(auth.tenant_id, DishId::from_uuid(dish_id))
+        .await?;
+    Ok(Json(groups))
+}
+
+/// `POST /api/dishes/:id/modifier-groups` — group with its options
+pub async fn create_group(
+    State(service): State<ModifierService>,
+    auth: AuthUser,
+    Path(dish_id): Path<Uuid>,
+    Json(req): Json<CreateModifierGroupInput>,
+) -> Result<(StatusCode, Json<ModifierGroup>), AppError> {
+    auth.require(Permission::ChangePrices)?;
+    let group = service
+        .create_group(auth.tenant_id, DishId::from_uuid(dish_id), req)
+        .await?;
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/// `PUT /api/modifier-groups/:id`
+pub async fn update_group(
+    State(service): State<ModifierService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateModifierGroupInput>,
+) -> Result<Json<ModifierGroup>, AppError> {
+    auth.require(Permission::ChangePrices)?;
+    let group = service
+        .update_group(auth.tenant_id, ModifierGroupId::from_uuid(id), req)
+        .await?;
+    Ok(Json(group))
+}
+
+/// `DELETE /api/modifier-groups/:id`
+pub async fn delete_group(
+    State(service): State<ModifierService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ChangePrices)?;
+    service
+        .delete_group(auth.tenant_id, ModifierGroupId::from_uuid(id))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /api/modifier-groups/:id/options`
+pub async fn add_option(
+    State(service): State<ModifierService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ModifierOptionInput>,
+) -> Result<(StatusCode, Json<ModifierOption>), AppError> {
+    auth.require(Permission::ChangePrices)?;
+    let option = service
+        .add_option(auth.tenant_id, ModifierGroupId::from_uuid(id), req)
+        .await?;
+    Ok((StatusCode::CREATED, Json(option)))
+}
+
+/// `PUT /api/modifier-options/:id` — replaces name, deltas and flags
+pub async fn update_option(
+    State(service): State<ModifierService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ModifierOptionInput>,
+) -> Result<Json<ModifierOption>, AppError> {
+    auth.require(Permission::ChangePrices)?;
+    let option = service
+        .update_option(auth.tenant_id, ModifierOptionId::from_uuid(id), req)
+        .await?;
+    Ok(Json(option))
+}
+
+/// `DELETE /api/modifier-options/:id`
+pub async fn delete_option(
+    State(service): State<ModifierService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ChangePrices)?;
+    service
+        .delete_option(auth.tenant_id, ModifierOptionId::from_uuid(id))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
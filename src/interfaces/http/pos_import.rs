@@ -0,0 +1,162 @@
+//! HTTP handlers for POS sales import, item mappings and the review queue.
+//!
+//! Mounted under `/api/pos` inside the protected router; the signed webhook
+//! `POST /webhooks/pos/:tenant_id` is root-level and never under JWT.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::application::pos_import::{
+    PosImportService, PosImportSummary, PosItemMapping, PosMappingResult, PosReplaySummary,
+    PosReviewLine, PosWebhookSecret, ResolveReviewLineInput, UpsertPosMappingInput,
+    POS_SIGNATURE_HEADER,
+};
+use crate::domain::pos_import::PosReviewStatus;
+use crate::domain::storage_location::StorageLocationId;
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct CsvImportQuery {
+    /// Selling outlet for every ticket in the file
+    #[serde(default)]
+    pub location_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewQueueQuery {
+    /// Defaults to `pending`
+    #[serde(default)]
+    pub status: Option<PosReviewStatus>,
+}
+
+/// `POST /api/pos/webhook-secret` — generate or rotate the webhook secret
+pub async fn rotate_webhook_secret(
+    State(service): State<PosImportService>,
+    auth: AuthUser,
+) -> Result<Json<PosWebhookSecret>, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let secret = service
+        .rotate_webhook_secret(auth.tenant_id, auth.user_id)
+        .await?;
+    Ok(Json(secret))
+}
+
+/// `POST /api/pos/import/csv?location_id=` — raw CSV export as the body
+pub async fn import_csv(
+    State(service): State<PosImportService>,
+    auth: AuthUser,
+    Query(query): Query<CsvImportQuery>,
+    body: String,
+) -> Result<Json<PosImportSummary>, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let summary = service
+        .import_csv(
+            auth.tenant_id,
+            auth.user_id,
+            &body,
+            query.location_id.map(StorageLocationId::from_uuid),
+        )
+        .await?;
+    Ok(Json(summary))
+}
+
+/// `POST /webhooks/pos/:tenant_id` — public; HMAC-verified against the
+/// tenant's secret. The raw body is needed because the signature covers
+/// the exact payload.
+pub async fn pos_webhook(
+    State(service): State<PosImportService>,
+    Path(tenant_id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<PosImportSummary>, AppError> {
+    let signature = headers
+        .get(POS_SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| AppError::authentication("Missing X-Pos-Signature header"))?;
+
+    let summary = service
+        .ingest_webhook(tenant_id, signature, &body)
+        .await
+        .inspect_err(|e| tracing::warn!("POS webhook for tenant {} rejected: {}", tenant_id, e))?;
+    Ok(Json(summary))
+}
+
+/// `GET /api/pos/mappings`
+pub async fn list_mappings(
+    State(service): State<PosImportService>,
+    auth: AuthUser,
+) -> Result<Json<Vec<PosItemMapping>>, AppError> {
+    auth.require(Permission::ViewDishes)?;
+    let mappings = service.list_mappings(auth.tenant_id).await?;
+    Ok(Json(mappings))
+}
+
+/// `POST /api/pos/mappings` — map a PLU or item name to a dish
+pub async fn upsert_mapping(
+    State(service): State<PosImportService>,
+    auth: AuthUser,
+    Json(req): Json<UpsertPosMappingInput>,
+) -> Result<Json<PosMappingResult>, AppError> {
+    auth.require(Permission::ManageDishes)?;
+    let result = service
+        .upsert_mapping(auth.tenant_id, auth.user_id, req)
+        .await?;
+    Ok(Json(result))
+}
+
+/// `DELETE /api/pos/mappings/:id`
+pub async fn delete_mapping(
+    State(service): State<PosImportService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageDishes)?;
+    service.delete_mapping(auth.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /api/pos/review?status=pending`
+pub async fn list_review_queue(
+    State(service): State<PosImportService>,
+    auth: AuthUser,
+    Query(query): Query<ReviewQueueQuery>,
+) -> Result<Json<Vec<PosReviewLine>>, AppError> {
+    auth.require(Permission::ViewDishes)?;
+    let lines = service
+        .list_review_queue(auth.tenant_id, query.status)
+        .await?;
+    Ok(Json(lines))
+}
+
+/// `POST /api/pos/review/:id/resolve` — book the line as a dish
+pub async fn resolve_review_line(
+    State(service): State<PosImportService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ResolveReviewLineInput>,
+) -> Result<Json<PosReplaySummary>, AppError> {
+    auth.require(Permission::ManageDishes)?;
+    let summary = service
+        .resolve_review_line(auth.tenant_id, auth.user_id, id, req)
+        .await?;
+    Ok(Json(summary))
+}
+
+/// `POST /api/pos/review/:id/ignore`
+pub async fn ignore_review_line(
+    State(service): State<PosImportService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageDishes)?;
+    service.ignore_review_line(auth.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,25 @@
+use axum::{extract::State, response::IntoResponse, Json};
+
+use crate::application::pricing::{PriceSimulationInput, PricingService};
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+/// POST /api/pricing/simulate
+/// What-if simulator: proposed prices for a food-cost / margin target and
+/// price ending, hypothetical ingredient price changes, menu engineering
+/// categories and projected profit. Nothing is saved.
+pub async fn simulate(
+    State(service): State<PricingService>,
+    auth: AuthUser,
+    Json(mut input): Json<PriceSimulationInput>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require(Permission::ChangePrices)?;
+    input.period_days = input.period_days.clamp(1, 365);
+
+    let result = service
+        .simulate(auth.user_id, auth.tenant_id, auth.language, input)
+        .await?;
+
+    Ok(Json(result))
+}
@@ -0,0 +1,68 @@
+//! HTTP handlers for purchase drafts and goods-received notes.
+//!
+//! Mounted under `/api/purchase-drafts/...` inside the protected router.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::application::purchase_draft::PurchaseDraft;
+use crate::application::purchase_receipt::{
+    PurchaseReceipt, PurchaseReceiptService, ReceivePurchaseDraftInput,
+};
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct ListDraftsQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+/// `GET /api/purchase-drafts?limit=50`
+pub async fn list_drafts(
+    State(service): State<PurchaseReceiptService>,
+    auth: AuthUser,
+    Query(query): Query<ListDraftsQuery>,
+) -> Result<Json<Vec<PurchaseDraft>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let drafts = service
+        .list_drafts(auth.tenant_id, query.limit.clamp(1, 200))
+        .await?;
+    Ok(Json(drafts))
+}
+
+/// `POST /api/purchase-drafts/:id/receive`
+/// Creates batches for delivered items and returns the flagged receipt
+pub async fn receive_draft(
+    State(service): State<PurchaseReceiptService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ReceivePurchaseDraftInput>,
+) -> Result<(StatusCode, Json<PurchaseReceipt>), AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let receipt = service
+        .receive(auth.tenant_id, auth.user_id, id, req)
+        .await?;
+    Ok((StatusCode::CREATED, Json(receipt)))
+}
+
+/// `GET /api/purchase-drafts/:id/receipts`
+pub async fn list_receipts(
+    State(service): State<PurchaseReceiptService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<PurchaseReceipt>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let receipts = service.list_receipts(auth.tenant_id, id).await?;
+    Ok(Json(receipts))
+}
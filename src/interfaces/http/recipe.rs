@@ -4,12 +4,14 @@ use axum::{
     response::IntoResponse,
     Json,
 };
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::application::RecipeService;
 use crate::domain::{
-    CatalogIngredientId, Quantity, RecipeId, RecipeIngredient, RecipeName, Servings,
+    CatalogIngredientId, CostingStrategy, Permission, Quantity, RecipeId, RecipeIngredient,
+    RecipeName, Servings,
 };
 use crate::interfaces::http::middleware::AuthUser;
 use crate::shared::{AppError, PaginationParams};
@@ -74,16 +76,26 @@ pub struct IngredientCostResponse {
     pub ingredient_id: Uuid,
     pub ingredient_name: String,
     pub quantity: f64,
+    /// As-purchased quantity after trim loss
+    pub gross_quantity: f64,
+    pub yield_percent: f64,
     pub unit_price_cents: i64,
     pub total_cost_cents: i64,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RecipeCostQuery {
+    /// Override the tenant costing strategy for this calculation
+    pub strategy: Option<CostingStrategy>,
+}
+
 /// POST /api/recipes - Create new recipe
 pub async fn create_recipe(
     auth_user: AuthUser,
     State(recipe_service): State<RecipeService>,
     Json(payload): Json<CreateRecipeRequest>,
 ) -> Result<impl IntoResponse, AppError> {
+    auth_user.require(Permission::ManageRecipes)?;
     let user_id = auth_user.user_id;
     let tenant_id = auth_user.tenant_id;
 
@@ -153,6 +165,7 @@ pub async fn get_recipe(
     State(recipe_service): State<RecipeService>,
     Path(recipe_id): Path<Uuid>,
 ) -> Result<impl IntoResponse, AppError> {
+    auth_user.require(Permission::ViewRecipes)?;
     let tenant_id = auth_user.tenant_id;
     let recipe_id = RecipeId::from_uuid(recipe_id);
 
@@ -193,6 +206,7 @@ pub async fn list_recipes(
     State(recipe_service): State<RecipeService>,
     Query(pagination): Query<PaginationParams>,
 ) -> Result<impl IntoResponse, AppError> {
+    auth_user.require(Permission::ViewRecipes)?;
     let tenant_id = auth_user.tenant_id;
 
     let paginated = recipe_service.list_recipes(tenant_id, &pagination).await?;
@@ -240,10 +254,13 @@ pub async fn delete_recipe(
     State(recipe_service): State<RecipeService>,
     Path(recipe_id): Path<Uuid>,
 ) -> Result<impl IntoResponse, AppError> {
+    auth_user.require(Permission::ManageRecipes)?;
     let tenant_id = auth_user.tenant_id;
     let recipe_id = RecipeId::from_uuid(recipe_id);
 
-    let deleted = recipe_service.delete_recipe(recipe_id, tenant_id).await?;
+    let deleted = recipe_service
+        .delete_recipe(recipe_id, tenant_id, auth_user.user_id)
+        .await?;
 
     if deleted {
         Ok(StatusCode::NO_CONTENT)
@@ -252,18 +269,22 @@ pub async fn delete_recipe(
     }
 }
 
-/// GET /api/recipes/:id/cost - Calculate recipe cost
+/// GET /api/recipes/:id/cost?strategy=last_price - Calculate recipe cost
 /// 🔒 TENANT ISOLATION: uses tenant_id from JWT
 pub async fn calculate_recipe_cost(
     auth_user: AuthUser,
     State(recipe_service): State<RecipeService>,
     Path(recipe_id): Path<Uuid>,
+    Query(query): Query<RecipeCostQuery>,
 ) -> Result<impl IntoResponse, AppError> {
+    auth_user.require(Permission::ViewRecipes)?;
     let tenant_id = auth_user.tenant_id;
     let recipe_id = RecipeId::from_uuid(recipe_id);
 
     // Calculate cost using RecipeService
-    let recipe_cost = recipe_service.calculate_cost(recipe_id, tenant_id).await?;
+    let recipe_cost = recipe_service
+        .calculate_cost_with_strategy(recipe_id, tenant_id, query.strategy)
+        .await?;
 
     // Build response
     let response = RecipeCostResponse {
@@ -276,6 +297,8 @@ pub async fn calculate_recipe_cost(
                 ingredient_id: ing.ingredient_id.as_uuid(),
                 ingredient_name: ing.ingredient_name.clone(),
                 quantity: ing.quantity.value(),
+                gross_quantity: ing.gross_quantity.value(),
+                yield_percent: ing.yield_percent.to_f64().unwrap_or(100.0),
                 unit_price_cents: ing.unit_price.as_cents(),
                 total_cost_cents: ing.total_cost.as_cents(),
             })
@@ -320,6 +343,8 @@ mod tests {
                 ingredient_id: Uuid::new_v4(),
                 ingredient_name: "Tomatoes".to_string(),
                 quantity: 0.5,
+                gross_quantity: 0.5,
+                yield_percent: 100.0,
                 unit_price_cents: 500,
                 total_cost_cents: 250,
             }],
@@ -21,6 +21,7 @@ pub async fn get_or_generate_insights(
         user_id: _,
         tenant_id,
         language: _,
+        role: _,
     }: AuthUser,
     Path((recipe_id, target_language)): Path<(Uuid, String)>,
 ) -> AppResult<Json<RecipeAIInsightsResponse>> {
@@ -40,6 +41,7 @@ pub async fn refresh_insights(
         user_id: _,
         tenant_id,
         language: _,
+        role: _,
     }: AuthUser,
     Path((recipe_id, target_language)): Path<(Uuid, String)>,
 ) -> AppResult<(StatusCode, Json<RecipeAIInsightsResponse>)> {
@@ -59,6 +61,7 @@ pub async fn get_all_insights(
         user_id: _,
         tenant_id: _,
         language: _,
+        role: _,
     }: AuthUser,
     Path(recipe_id): Path<Uuid>,
 ) -> AppResult<Json<Vec<RecipeAIInsightsResponse>>> {
@@ -3,6 +3,7 @@ use crate::application::recipe_v2_service::{
     CreateRecipeDto, RecipeResponseDto, RecipeV2Service, UpdateRecipeDto,
 };
 use crate::domain::recipe_v2::RecipeId;
+use crate::domain::Permission;
 use crate::interfaces::http::middleware::AuthUser;
 use crate::shared::AppResult;
 use axum::{
@@ -23,9 +24,11 @@ pub async fn create_recipe(
         user_id,
         tenant_id,
         language: _,
+        role,
     }: AuthUser,
     Json(dto): Json<CreateRecipeDto>,
 ) -> AppResult<(StatusCode, Json<RecipeResponseDto>)> {
+    role.require(Permission::ManageRecipes)?;
     let recipe = service.create_recipe(dto, user_id, tenant_id).await?;
     Ok((StatusCode::CREATED, Json(recipe)))
 }
@@ -39,9 +42,11 @@ pub async fn get_recipe(
         user_id: _,
         tenant_id,
         language,
+        role,
     }: AuthUser,
     Path(recipe_id): Path<Uuid>,
 ) -> AppResult<Json<RecipeResponseDto>> {
+    role.require(Permission::ViewRecipes)?;
     let recipe = service
         .get_recipe(RecipeId(recipe_id), tenant_id, language)
         .await?;
@@ -63,9 +68,11 @@ pub async fn list_recipes(
         user_id,
         tenant_id,
         language,
+        role,
     }: AuthUser,
     Query(params): Query<RecipeListParams>,
 ) -> AppResult<Json<Vec<RecipeResponseDto>>> {
+    role.require(Permission::ViewRecipes)?;
     // For now, we use a simple implementation that ignores search but respects tenant isolation
     // The repository method find_by_user_id will be used
     let recipes = service
@@ -94,15 +101,17 @@ pub async fn list_recipes(
 pub async fn update_recipe(
     State(service): State<Arc<RecipeV2Service>>,
     AuthUser {
-        user_id: _,
+        user_id,
         tenant_id,
         language: _,
+        role,
     }: AuthUser,
     Path(recipe_id): Path<Uuid>,
     Json(dto): Json<UpdateRecipeDto>,
 ) -> AppResult<Json<RecipeResponseDto>> {
+    role.require(Permission::ManageRecipes)?;
     let recipe = service
-        .update_recipe(RecipeId(recipe_id), dto, tenant_id)
+        .update_recipe(RecipeId(recipe_id), dto, tenant_id, user_id)
         .await?;
     Ok(Json(recipe))
 }
@@ -113,14 +122,16 @@ pub async fn update_recipe(
 pub async fn publish_recipe(
     State(service): State<Arc<RecipeV2Service>>,
     AuthUser {
-        user_id: _,
+        user_id,
         tenant_id,
         language: _,
+        role,
     }: AuthUser,
     Path(recipe_id): Path<Uuid>,
 ) -> AppResult<StatusCode> {
+    role.require(Permission::ManageRecipes)?;
     service
-        .publish_recipe(RecipeId(recipe_id), tenant_id)
+        .publish_recipe(RecipeId(recipe_id), tenant_id, user_id)
         .await?;
     Ok(StatusCode::OK)
 }
@@ -131,14 +142,16 @@ pub async fn publish_recipe(
 pub async fn delete_recipe(
     State(service): State<Arc<RecipeV2Service>>,
     AuthUser {
-        user_id: _,
+        user_id,
         tenant_id,
         language: _,
+        role,
     }: AuthUser,
     Path(recipe_id): Path<Uuid>,
 ) -> AppResult<StatusCode> {
+    role.require(Permission::ManageRecipes)?;
     service
-        .delete_recipe(RecipeId(recipe_id), tenant_id)
+        .delete_recipe(RecipeId(recipe_id), tenant_id, user_id)
         .await?;
     Ok(StatusCode::NO_CONTENT)
 }
@@ -151,10 +164,12 @@ pub async fn upload_recipe_image(
         user_id: _,
         tenant_id,
         language: _,
+        role,
     }: AuthUser,
     Path(recipe_id): Path<Uuid>,
     mut multipart: Multipart,
 ) -> AppResult<Json<crate::interfaces::http::admin_catalog::ImageUrlResponse>> {
+    role.require(Permission::ManageRecipes)?;
     let mut file_data = None;
     let mut content_type = None;
 
@@ -200,10 +215,12 @@ pub async fn get_recipe_image_upload_url(
         user_id: _,
         tenant_id,
         language: _,
+        role,
     }: AuthUser,
     Path(recipe_id): Path<Uuid>,
     Query(query): Query<crate::interfaces::http::admin_catalog::GetUploadUrlQuery>,
 ) -> AppResult<Json<crate::application::user::AvatarUploadResponse>> {
+    role.require(Permission::ManageRecipes)?;
     let content_type = query
         .content_type
         .unwrap_or_else(|| "image/webp".to_string());
@@ -228,10 +245,12 @@ pub async fn save_recipe_image_url(
         user_id: _,
         tenant_id,
         language: _,
+        role,
     }: AuthUser,
     Path(recipe_id): Path<Uuid>,
     Json(req): Json<SaveImageUrlRequest>,
 ) -> AppResult<StatusCode> {
+    role.require(Permission::ManageRecipes)?;
     service
         .save_image_url(RecipeId(recipe_id), tenant_id, req.image_url)
         .await?;
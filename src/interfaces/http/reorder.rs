@@ -0,0 +1,83 @@
+//! HTTP handlers for par-level based reorder proposals.
+//!
+//! Mounted under `/api/inventory/reorder/...` inside the protected router.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::application::reorder::{
+    ReorderRunResult, ReorderService, ReorderSettings, ReorderSuggestion,
+};
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct ReorderQuery {
+    /// Consumption lookback; tenant setting when omitted
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RunReorderRequest {
+    pub days: Option<i32>,
+}
+
+/// `GET /api/inventory/reorder/suggestions?days=14`
+pub async fn get_suggestions(
+    State(service): State<ReorderService>,
+    auth: AuthUser,
+    Query(query): Query<ReorderQuery>,
+) -> Result<Json<Vec<ReorderSuggestion>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let days = match query.days {
+        Some(days) => days,
+        None => service.get_settings(auth.tenant_id).await?.lookback_days,
+    };
+    let suggestions = service
+        .suggestions(auth.tenant_id, days, auth.language.code())
+        .await?;
+    Ok(Json(suggestions))
+}
+
+/// `POST /api/inventory/reorder/run`
+/// Creates one purchase draft per supplier from the current suggestions
+pub async fn run_reorder(
+    State(service): State<ReorderService>,
+    auth: AuthUser,
+    req: Option<Json<RunReorderRequest>>,
+) -> Result<(StatusCode, Json<ReorderRunResult>), AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    let days = match req.days {
+        Some(days) => days,
+        None => service.get_settings(auth.tenant_id).await?.lookback_days,
+    };
+    let result = service
+        .generate_drafts(auth.tenant_id, auth.user_id, days, auth.language.code())
+        .await?;
+    Ok((StatusCode::CREATED, Json(result)))
+}
+
+/// `GET /api/inventory/settings/auto-reorder`
+pub async fn get_settings(
+    State(service): State<ReorderService>,
+    auth: AuthUser,
+) -> Result<Json<ReorderSettings>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    Ok(Json(service.get_settings(auth.tenant_id).await?))
+}
+
+/// `PUT /api/inventory/settings/auto-reorder`
+pub async fn set_settings(
+    State(service): State<ReorderService>,
+    auth: AuthUser,
+    Json(req): Json<ReorderSettings>,
+) -> Result<Json<ReorderSettings>, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    Ok(Json(service.set_settings(auth.tenant_id, req).await?))
+}
@@ -2,6 +2,7 @@ use axum::{extract::State, response::IntoResponse, Json};
 use serde::Deserialize;
 
 use crate::application::report::ReportService;
+use crate::domain::Permission;
 use crate::interfaces::http::middleware::AuthUser;
 use crate::shared::AppError;
 
@@ -22,6 +23,7 @@ pub async fn get_summary(
     auth: AuthUser,
     axum::extract::Query(query): axum::extract::Query<ReportQuery>,
 ) -> Result<impl IntoResponse, AppError> {
+    auth.require(Permission::ViewReports)?;
     let period_days = query.period_days.max(1).min(365);
 
     let summary = service
@@ -30,3 +32,21 @@ pub async fn get_summary(
 
     Ok(Json(summary))
 }
+
+/// GET /api/reports/usage-variance?period_days=30
+/// Theoretical (recipes × sales) vs actual (stock flow) usage and cost,
+/// per ingredient and category, biggest unexplained variances first.
+pub async fn get_usage_variance(
+    State(service): State<ReportService>,
+    auth: AuthUser,
+    axum::extract::Query(query): axum::extract::Query<ReportQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require(Permission::ViewReports)?;
+    let period_days = query.period_days.clamp(1, 365);
+
+    let report = service
+        .get_usage_variance(auth.tenant_id, auth.language, period_days)
+        .await?;
+
+    Ok(Json(report))
+}
@@ -0,0 +1,109 @@
+//! HTTP handlers for scheduled report subscriptions and their delivery
+//! history. Everything here requires `ViewReports`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::application::report_subscription::{
+    CreateReportSubscription, DeliveryFilter, DeliveryFormat, ReportDelivery, ReportSubscription,
+    ReportSubscriptionService, UpdateReportSubscription,
+};
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+/// `GET /api/reports/subscriptions`
+pub async fn list_subscriptions(
+    State(service): State<ReportSubscriptionService>,
+    auth: AuthUser,
+) -> Result<Json<Vec<ReportSubscription>>, AppError> {
+    auth.require(Permission::ViewReports)?;
+    Ok(Json(service.list(auth.tenant_id).await?))
+}
+
+/// `POST /api/reports/subscriptions`
+///
+/// `{"report_kind":"summary","frequency":"weekly","day":1,"hour_utc":6,"recipients":["owner@example.com"]}`
+pub async fn create_subscription(
+    State(service): State<ReportSubscriptionService>,
+    auth: AuthUser,
+    Json(command): Json<CreateReportSubscription>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require(Permission::ViewReports)?;
+    let subscription = service
+        .create(auth.tenant_id, auth.user_id, auth.language, command)
+        .await?;
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+/// `PUT /api/reports/subscriptions/:id` — any subset of schedule, recipients,
+/// language and `active`
+pub async fn update_subscription(
+    State(service): State<ReportSubscriptionService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(command): Json<UpdateReportSubscription>,
+) -> Result<Json<ReportSubscription>, AppError> {
+    auth.require(Permission::ViewReports)?;
+    Ok(Json(service.update(auth.tenant_id, id, command).await?))
+}
+
+/// `DELETE /api/reports/subscriptions/:id`
+pub async fn delete_subscription(
+    State(service): State<ReportSubscriptionService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ViewReports)?;
+    service.delete(auth.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /api/reports/subscriptions/:id/send` — deliver now, off schedule
+pub async fn send_subscription_now(
+    State(service): State<ReportSubscriptionService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ReportDelivery>, AppError> {
+    auth.require(Permission::ViewReports)?;
+    Ok(Json(service.send_now(auth.tenant_id, id).await?))
+}
+
+/// `GET /api/reports/deliveries?subscription_id=&limit=50&offset=0`
+pub async fn list_deliveries(
+    State(service): State<ReportSubscriptionService>,
+    auth: AuthUser,
+    Query(filter): Query<DeliveryFilter>,
+) -> Result<Json<Vec<ReportDelivery>>, AppError> {
+    auth.require(Permission::ViewReports)?;
+    Ok(Json(service.list_deliveries(auth.tenant_id, filter).await?))
+}
+
+/// `GET /api/reports/deliveries/:id/:format` — `csv`, `xlsx` or `html`,
+/// exactly as it was sent
+pub async fn download_delivery(
+    State(service): State<ReportSubscriptionService>,
+    auth: AuthUser,
+    Path((id, format)): Path<(Uuid, DeliveryFormat)>,
+) -> Result<Response, AppError> {
+    auth.require(Permission::ViewReports)?;
+    let file = service.delivery_file(auth.tenant_id, id, format).await?;
+
+    let disposition = match format {
+        DeliveryFormat::Html => format!("inline; filename=\"{}\"", file.filename),
+        _ => format!("attachment; filename=\"{}\"", file.filename),
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, file.content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        file.data,
+    )
+        .into_response())
+}
@@ -37,7 +37,10 @@ use crate::interfaces::http::{
     admin_version,
     almabuild,
     assistant::{get_state, send_command},
-    auth::{login_handler, refresh_handler, register_handler},
+    auth::{
+        forgot_password_handler, login_handler, logout_handler, refresh_handler, register_handler,
+        reset_password_handler, verify_email_handler,
+    },
     catalog::{
         get_categories, get_categories_public, get_ingredient_detail_public, search_ingredients,
         search_ingredients_public, CatalogState, PublicNutritionState,
@@ -49,10 +52,11 @@ use crate::interfaces::http::{
     dish::{create_dish, list_dishes, recalculate_all_costs},
     icons_site,
     inventory::{
-        add_product, delete_product, get_alerts, get_dashboard, get_health, get_loss_report,
-        list_products, process_expirations, update_product,
+        add_product, delete_product, get_alerts, get_costing_strategy, get_dashboard, get_health,
+        get_loss_report, get_stock_policy, list_products, list_shortfalls, process_expirations,
+        resolve_shortfall, set_costing_strategy, set_stock_policy, update_product,
     },
-    menu_engineering::{analyze_menu, record_sale},
+    menu_engineering::{analyze_menu, analyze_modifiers, record_sale},
     middleware::AuthUser,
     preferences::{get_preferences, save_preferences},
     public::{
@@ -83,7 +87,7 @@ use crate::interfaces::http::{
     recipe::{calculate_recipe_cost, create_recipe, delete_recipe, get_recipe, list_recipes},
     recipe_ai_insights, // AI insights handlers
     recipe_v2,          // V2 handlers with translations
-    report::get_summary,
+    report::{get_summary, get_usage_variance},
     smart::smart_autocomplete,    // 🆕 SmartService autocomplete
     smart::smart_ingredient,      // 🆕 SmartService handler
     smart_parse::smart_from_text, // 🆕 SmartParse from-text handler
@@ -131,7 +135,8 @@ pub fn create_router(
     r2_client: crate::infrastructure::R2Client, // 🆕 for CMS image upload
     llm_adapter: Arc<crate::infrastructure::llm_adapter::LlmAdapter>, // 🆕 for public AI SEO content
     ingredient_cache: Arc<crate::infrastructure::IngredientCache>,    // 🆕 for ChefOS Chat
-    gemini_for_copilot: Arc<crate::infrastructure::gemini_service::GeminiService>, // 🆕 Copilot Brain
+    llm_gateway: Arc<dyn crate::infrastructure::llm::LlmGateway>,     // 🆕 Copilot Brain
+    mailer: Arc<dyn crate::infrastructure::mail::Mailer>,             // 🆕 Scheduled report emails
     allowed_origins: Vec<String>,
     rate_limit_per_second: u32,
 ) -> Router {
@@ -157,6 +162,31 @@ pub fn create_router(
         inventory_service.clone(),
         menu_engineering_service.clone(),
     );
+    let report_subscription_service =
+        crate::application::report_subscription::ReportSubscriptionService::new(
+            pool.clone(),
+            report_service.clone(),
+            inventory_service.clone(),
+            menu_engineering_service.clone(),
+            mailer,
+        );
+    let pricing_service = crate::application::pricing::PricingService::new(
+        dish_service.clone(),
+        recipe_service.clone(),
+        menu_engineering_service.clone(),
+    );
+    let pos_import_service = crate::application::pos_import::PosImportService::new(
+        pool.clone(),
+        menu_engineering_service.clone(),
+    );
+    let pos_import_for_webhook = pos_import_service.clone();
+    let auth_service_for_sessions = auth_service.clone();
+    let team_service = crate::application::team::TeamService::new(
+        pool.clone(),
+        auth_service.clone(),
+        crate::infrastructure::PasswordHasher::new(),
+    );
+    let audit_log = crate::application::audit::AuditLog::new(pool.clone());
 
     // 🆕 Pre-clone services for Copilot (they are consumed in their own router blocks)
     let dish_service_for_copilot = dish_service.clone();
@@ -178,7 +208,19 @@ pub fn create_router(
         .route("/register", post(register_handler))
         .route("/login", post(login_handler))
         .route("/refresh", post(refresh_handler))
-        .with_state(auth_service); // 🎯 УДАЛЕНО ВРЕМЕННО Rate Limit для тестов
+        .route("/logout", post(logout_handler))
+        .route("/password/forgot", post(forgot_password_handler))
+        .route("/password/reset", post(reset_password_handler))
+        .route("/email/verify", post(verify_email_handler))
+        .with_state(auth_service) // 🎯 УДАЛЕНО ВРЕМЕННО Rate Limit для тестов
+        .merge(
+            Router::new()
+                .route(
+                    "/invitations/accept",
+                    post(crate::interfaces::http::team::accept_invitation),
+                )
+                .with_state(team_service.clone()),
+        );
 
     // Admin auth routes
     let admin_service_for_middleware = admin_auth_service.clone();
@@ -714,6 +756,7 @@ pub fn create_router(
     let pool_for_cms = pool.clone();
     let pool_for_prefs = pool.clone(); // User preferences
     let pool_for_billing = pool.clone(); // 🆕 Stripe billing
+    let reorder_service = crate::application::reorder::ReorderService::new(pool.clone()); // 🆕 Auto-reorder (routes + scheduler)
     let cms_service = CmsService::new(pool_for_cms, r2_client.clone(), Arc::clone(&llm_adapter));
 
     // 🆕 Stripe service — optional. If env vars are missing the billing
@@ -841,8 +884,264 @@ pub fn create_router(
                 .route("/inventory/dashboard", get(get_dashboard)) // New ownership dashboard
                 .route("/inventory/alerts", get(get_alerts))
                 .route("/inventory/health", get(get_health))
+                .route(
+                    "/inventory/settings/stock-policy",
+                    get(get_stock_policy).put(set_stock_policy),
+                )
+                .route(
+                    "/inventory/settings/costing-strategy",
+                    get(get_costing_strategy).put(set_costing_strategy),
+                )
+                .route("/inventory/shortfalls", get(list_shortfalls))
+                .route("/inventory/shortfalls/:id/resolve", post(resolve_shortfall))
                 .with_state(inventory_service.clone()),
         )
+        // 🆕 Stocktake — physical count sessions with variance report
+        .merge({
+            use crate::interfaces::http::stocktake;
+            Router::new()
+                .route(
+                    "/inventory/stocktakes",
+                    post(stocktake::open_session).get(stocktake::list_sessions),
+                )
+                .route("/inventory/stocktakes/:id", get(stocktake::get_session))
+                .route(
+                    "/inventory/stocktakes/:id/counts",
+                    post(stocktake::add_counts),
+                )
+                .route(
+                    "/inventory/stocktakes/:id/counts/:count_id",
+                    axum::routing::delete(stocktake::delete_count),
+                )
+                .route(
+                    "/inventory/stocktakes/:id/close",
+                    post(stocktake::close_session),
+                )
+                .route(
+                    "/inventory/stocktakes/:id/cancel",
+                    post(stocktake::cancel_session),
+                )
+                .route(
+                    "/inventory/stocktakes/:id/report",
+                    get(stocktake::get_report),
+                )
+                .with_state(crate::application::stocktake::StocktakeService::new(
+                    pool_for_prefs.clone(),
+                ))
+        })
+        // 🆕 Suppliers — supplier directory, price history, supplier comparison
+        .merge({
+            use crate::interfaces::http::supplier;
+            Router::new()
+                .route(
+                    "/suppliers",
+                    get(supplier::list_suppliers).post(supplier::create_supplier),
+                )
+                .route("/suppliers/compare", get(supplier::compare_suppliers))
+                .route(
+                    "/suppliers/:id",
+                    get(supplier::get_supplier)
+                        .put(supplier::update_supplier)
+                        .delete(supplier::delete_supplier),
+                )
+                .route(
+                    "/inventory/price-history/:ingredient_id",
+                    get(supplier::price_history),
+                )
+                .route("/inventory/price-inflation", get(supplier::price_inflation))
+                .with_state(crate::application::supplier::SupplierService::new(
+                    pool_for_prefs.clone(),
+                ))
+        })
+        // 🆕 Reorder — par levels → purchase drafts per supplier
+        .merge({
+            use crate::interfaces::http::reorder;
+            Router::new()
+                .route(
+                    "/inventory/reorder/suggestions",
+                    get(reorder::get_suggestions),
+                )
+                .route("/inventory/reorder/run", post(reorder::run_reorder))
+                .route(
+                    "/inventory/settings/auto-reorder",
+                    get(reorder::get_settings).put(reorder::set_settings),
+                )
+                .with_state(reorder_service.clone())
+        })
+        // 🆕 Purchase receiving — goods-received notes → inventory batches
+        .merge({
+            use crate::interfaces::http::purchase_receipt;
+            Router::new()
+                .route("/purchase-drafts", get(purchase_receipt::list_drafts))
+                .route(
+                    "/purchase-drafts/:id/receive",
+                    post(purchase_receipt::receive_draft),
+                )
+                .route(
+                    "/purchase-drafts/:id/receipts",
+                    get(purchase_receipt::list_receipts),
+                )
+                .with_state(
+                    crate::application::purchase_receipt::PurchaseReceiptService::new(
+                        pool_for_prefs.clone(),
+                    ),
+                )
+        })
+        // 🆕 Storage locations — multi-location stock + FIFO transfers
+        .merge({
+            use crate::interfaces::http::storage_location;
+            Router::new()
+                .route(
+                    "/inventory/locations",
+                    get(storage_location::list_locations).post(storage_location::create_location),
+                )
+                .route(
+                    "/inventory/locations/:id",
+                    axum::routing::put(storage_location::update_location)
+                        .delete(storage_location::deactivate_location),
+                )
+                .route(
+                    "/inventory/transfers",
+                    get(storage_location::list_transfers).post(storage_location::create_transfer),
+                )
+                .with_state(
+                    crate::application::storage_location::StorageLocationService::new(
+                        pool_for_prefs.clone(),
+                    ),
+                )
+                .merge(
+                    Router::new()
+                        .route(
+                            "/inventory/locations/:id/dashboard",
+                            get(storage_location::location_dashboard),
+                        )
+                        .route(
+                            "/inventory/locations/:id/stock",
+                            get(storage_location::location_stock),
+                        )
+                        .with_state(crate::application::inventory::InventoryService::new(
+                            pool_for_prefs.clone(),
+                        )),
+                )
+        })
+        // 🆕 Waste log — reasons, photo upload, FIFO write-off
+        .merge({
+            use crate::interfaces::http::waste;
+            Router::new()
+                .route(
+                    "/inventory/waste",
+                    get(waste::list_waste).post(waste::log_waste),
+                )
+                .route("/inventory/waste/reasons", get(waste::list_reasons))
+                .route(
+                    "/inventory/waste/photo-upload-url",
+                    get(waste::get_photo_upload_url),
+                )
+                .with_state(crate::application::waste::WasteService::new(
+                    pool_for_prefs.clone(),
+                    r2_client.clone(),
+                ))
+        })
+        // 🆕 Menus — ordered sections, availability windows, dish assignments
+        .merge({
+            use crate::interfaces::http::menu;
+            Router::new()
+                .route("/menus", get(menu::list_menus).post(menu::create_menu))
+                .route(
+                    "/menus/:id",
+                    get(menu::get_menu)
+                        .put(menu::update_menu)
+                        .delete(menu::delete_menu),
+                )
+                .route("/menus/:id/sections", post(menu::create_section))
+                .route(
+                    "/menus/:id/sections/:section_id",
+                    axum::routing::put(menu::update_section).delete(menu::delete_section),
+                )
+                .route(
+                    "/menus/:id/sections/:section_id/dishes",
+                    axum::routing::put(menu::set_section_dishes),
+                )
+                .with_state(crate::application::menu::MenuService::new(
+                    pool_for_prefs.clone(),
+                ))
+        })
+        // 🆕 Dish modifiers — sizes, add-ons, removals
+        .merge({
+            use crate::interfaces::http::modifier;
+            Router::new()
+                .route(
+                    "/dishes/:id/modifier-groups",
+                    get(modifier::list_groups).post(modifier::create_group),
+                )
+                .route(
+                    "/modifier-groups/:id",
+                    axum::routing::put(modifier::update_group).delete(modifier::delete_group),
+                )
+                .route("/modifier-groups/:id/options", post(modifier::add_option))
+                .route(
+                    "/modifier-options/:id",
+                    axum::routing::put(modifier::update_option).delete(modifier::delete_option),
+                )
+                .with_state(crate::application::modifier::ModifierService::new(
+                    pool_for_prefs.clone(),
+                ))
+        })
+        // 🆕 Allergen matrix, dish labels and "may contain" declarations
+        .merge({
+            use crate::interfaces::http::dish_label;
+            Router::new()
+                .route("/allergens/matrix", get(dish_label::allergen_matrix))
+                .route(
+                    "/allergens/traces",
+                    get(dish_label::list_traces).post(dish_label::declare_trace),
+                )
+                .route("/allergens/traces/:id", delete(dish_label::delete_trace))
+                .route("/dishes/:id/label", get(dish_label::dish_label))
+                .with_state(crate::application::dish_label::DishLabelService::new(
+                    pool_for_prefs.clone(),
+                    Arc::new(crate::infrastructure::persistence::RecipeRepository::new(
+                        pool_for_prefs.clone(),
+                    )),
+                ))
+        })
+        // 🆕 Sessions — active devices, sign out one or all
+        .merge({
+            use crate::interfaces::http::auth;
+            Router::new()
+                .route("/auth/sessions", get(auth::list_sessions_handler))
+                .route("/auth/sessions/:id", delete(auth::revoke_session_handler))
+                .route("/auth/logout-all", post(auth::logout_all_handler))
+                .route(
+                    "/auth/email/verification",
+                    post(auth::resend_verification_handler),
+                )
+                .with_state(auth_service_for_sessions)
+        })
+        // 🆕 Team — members, role changes, invitations, ownership transfer
+        .merge({
+            use crate::interfaces::http::team;
+            Router::new()
+                .route("/team/members", get(team::list_members))
+                .route(
+                    "/team/members/:id/role",
+                    axum::routing::put(team::update_member_role),
+                )
+                .route(
+                    "/team/invitations",
+                    get(team::list_invitations).post(team::create_invitation),
+                )
+                .route("/team/invitations/:id", delete(team::revoke_invitation))
+                .route("/team/transfer-ownership", post(team::transfer_ownership))
+                .with_state(team_service)
+        })
+        // 🆕 Audit trail — who changed what, with before/after diff
+        .merge({
+            use crate::interfaces::http::audit;
+            Router::new()
+                .route("/audit", get(audit::list_audit_log))
+                .with_state(audit_log.clone())
+        })
         // 🆕 Cook Suggestions — smart recipe suggestions from inventory
         .merge({
             let cook_service = Arc::new(
@@ -916,13 +1215,71 @@ pub fn create_router(
             Router::new()
                 .route("/menu-engineering/analysis", get(analyze_menu))
                 .route("/menu-engineering/sales", post(record_sale))
+                .route("/menu-engineering/modifiers", get(analyze_modifiers))
                 .with_state(menu_engineering_service),
         )
         .merge(
             Router::new()
                 .route("/reports/summary", get(get_summary))
+                .route("/reports/usage-variance", get(get_usage_variance))
                 .with_state(report_service),
         )
+        // 🆕 Scheduled report emails — subscriptions and delivery history
+        .merge({
+            use crate::interfaces::http::report_subscription as subscriptions;
+            Router::new()
+                .route(
+                    "/reports/subscriptions",
+                    get(subscriptions::list_subscriptions).post(subscriptions::create_subscription),
+                )
+                .route(
+                    "/reports/subscriptions/:id",
+                    axum::routing::put(subscriptions::update_subscription)
+                        .delete(subscriptions::delete_subscription),
+                )
+                .route(
+                    "/reports/subscriptions/:id/send",
+                    post(subscriptions::send_subscription_now),
+                )
+                .route("/reports/deliveries", get(subscriptions::list_deliveries))
+                .route(
+                    "/reports/deliveries/:id/:format",
+                    get(subscriptions::download_delivery),
+                )
+                .with_state(report_subscription_service.clone())
+        })
+        .merge(
+            Router::new()
+                .route(
+                    "/pricing/simulate",
+                    post(crate::interfaces::http::pricing::simulate),
+                )
+                .with_state(pricing_service),
+        )
+        .merge({
+            use crate::interfaces::http::pos_import;
+            Router::new()
+                .route(
+                    "/pos/webhook-secret",
+                    post(pos_import::rotate_webhook_secret),
+                )
+                .route("/pos/import/csv", post(pos_import::import_csv))
+                .route(
+                    "/pos/mappings",
+                    get(pos_import::list_mappings).post(pos_import::upsert_mapping),
+                )
+                .route("/pos/mappings/:id", delete(pos_import::delete_mapping))
+                .route("/pos/review", get(pos_import::list_review_queue))
+                .route(
+                    "/pos/review/:id/resolve",
+                    post(pos_import::resolve_review_line),
+                )
+                .route(
+                    "/pos/review/:id/ignore",
+                    post(pos_import::ignore_review_line),
+                )
+                .with_state(pos_import_service)
+        })
         .merge(
             Router::new()
                 .route(
@@ -985,12 +1342,15 @@ pub fn create_router(
         // 🆕 Copilot — главный LLM Brain
         .merge({
             use crate::application::copilot::tool_executor::ToolExecutorServices;
-            use crate::application::copilot::{CopilotAuditService, CopilotEngine};
+            use crate::application::copilot::{
+                CopilotAuditService, CopilotEngine, CopilotMemoryService,
+            };
             use crate::application::sous_chef::SousChefPlannerService;
             use crate::application::usage_service::UsageService;
             use crate::infrastructure::persistence::AiCacheRepository;
             use crate::interfaces::http::copilot::{
-                cancel_action, confirm_action, handle_message, CopilotState,
+                cancel_action, confirm_action, delete_thread, get_thread, handle_message,
+                list_threads, undo_action, CopilotState,
             };
 
             let copilot_services = ToolExecutorServices {
@@ -1021,12 +1381,22 @@ pub fn create_router(
                         pool_for_prefs.clone(),
                     ),
                 ),
+                waste: Arc::new(crate::application::waste::WasteService::new(
+                    pool_for_prefs.clone(),
+                    r2_client.clone(),
+                )),
             };
             let copilot_engine = Arc::new(CopilotEngine::new(
-                Arc::clone(&gemini_for_copilot),
+                Arc::clone(&llm_gateway),
                 copilot_services,
                 UsageService::new(pool_for_prefs.clone()),
                 CopilotAuditService::new(pool_for_prefs.clone()),
+                CopilotMemoryService::new(pool_for_prefs.clone()),
+                std::env::var("COPILOT_UNDO_WINDOW_MINUTES")
+                    .ok()
+                    .and_then(|v| v.parse::<i64>().ok())
+                    .filter(|minutes| *minutes > 0)
+                    .unwrap_or(crate::application::copilot::undo::DEFAULT_UNDO_WINDOW_MINUTES),
             ));
             Router::new()
                 .route("/copilot/message", axum::routing::post(handle_message))
@@ -1034,7 +1404,16 @@ pub fn create_router(
                     "/copilot/actions/:id/confirm",
                     axum::routing::post(confirm_action),
                 )
+                .route(
+                    "/copilot/actions/:id/undo",
+                    axum::routing::post(undo_action),
+                )
                 .route("/copilot/actions/:id", axum::routing::delete(cancel_action))
+                .route("/copilot/threads", get(list_threads))
+                .route(
+                    "/copilot/threads/:id",
+                    get(get_thread).delete(delete_thread),
+                )
                 .with_state(CopilotState {
                     engine: copilot_engine,
                 })
@@ -1334,6 +1713,82 @@ pub fn create_router(
         )
         .with_state(intent_pages_svc.clone());
 
+    // ── Background scheduler: automatic reorder drafts every hour ────────────
+    if env_bool("ENABLE_AUTO_REORDER_SCHEDULER", true) {
+        let svc = reorder_service.clone();
+        tokio::spawn(async move {
+            // Wait 60s after startup before first run
+            tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+            tracing::info!("🕐 Auto-reorder scheduler started (runs every 1h)");
+
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                match svc.run_scheduled().await {
+                    Ok(result) => {
+                        tracing::info!("🕐 Auto-reorder result: {}", result);
+                    }
+                    Err(e) => {
+                        tracing::error!("❌ Auto-reorder scheduler error: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    // ── Background scheduler: scheduled report emails every 15 min ──────────
+    if env_bool("ENABLE_REPORT_SCHEDULER", true) {
+        let svc = report_subscription_service.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(tokio::time::Duration::from_secs(90)).await;
+            tracing::info!("🕐 Report scheduler started (checks every 15 min)");
+
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(900));
+            loop {
+                interval.tick().await;
+                match svc.run_due().await {
+                    Ok(0) => {}
+                    Ok(sent) => {
+                        tracing::info!("🕐 Report scheduler sent {} report(s)", sent);
+                    }
+                    Err(e) => {
+                        tracing::error!("❌ Report scheduler error: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    // ── Background scheduler: purge expired audit entries daily ──────────────
+    if env_bool("ENABLE_AUDIT_RETENTION", true) {
+        let retention_days = std::env::var("AUDIT_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(crate::application::audit::DEFAULT_RETENTION_DAYS);
+        tokio::spawn(async move {
+            tokio::time::sleep(tokio::time::Duration::from_secs(120)).await;
+            tracing::info!(
+                "🕐 Audit retention started (keeps {} days, runs every 24h)",
+                retention_days
+            );
+
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(86400));
+            loop {
+                interval.tick().await;
+                match audit_log.purge_expired(retention_days).await {
+                    Ok(0) => {}
+                    Ok(purged) => {
+                        tracing::info!("🕐 Audit retention purged {} entries", purged);
+                    }
+                    Err(e) => {
+                        tracing::error!("❌ Audit retention error: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
     // ── Background scheduler: publish queued pages every hour ────────────────
     if heavy_admin_enabled && env_bool("ENABLE_INTENT_PAGES_SCHEDULER", true) {
         let svc = intent_pages_svc.clone();
@@ -1913,6 +2368,16 @@ pub fn create_router(
         router = router.merge(r); // 💳 /webhooks/stripe — root-level
     }
 
+    // 🆕 POS sales webhook — HMAC-verified per tenant, never under JWT
+    router = router.merge(
+        Router::new()
+            .route(
+                "/webhooks/pos/:tenant_id",
+                post(crate::interfaces::http::pos_import::pos_webhook),
+            )
+            .with_state(pos_import_for_webhook),
+    );
+
     router.layer(cors)
 }
 
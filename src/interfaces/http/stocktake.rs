@@ -0,0 +1,146 @@
+//! HTTP handlers for physical stocktake sessions.
+//!
+//! Mounted under `/api/inventory/stocktakes/...` inside the protected router.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::application::stocktake::{
+    StocktakeCountInput, StocktakeService, StocktakeSession, StocktakeSessionDetail,
+};
+use crate::domain::stocktake::StocktakeVarianceReport;
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct OpenStocktakeRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddCountsRequest {
+    pub counts: Vec<StocktakeCountInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListStocktakesQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+/// `POST /api/inventory/stocktakes`
+pub async fn open_session(
+    State(service): State<StocktakeService>,
+    auth: AuthUser,
+    Json(req): Json<OpenStocktakeRequest>,
+) -> Result<(StatusCode, Json<StocktakeSession>), AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let session = service
+        .open_session(auth.tenant_id, auth.user_id, req.note)
+        .await?;
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+/// `GET /api/inventory/stocktakes?limit=20`
+pub async fn list_sessions(
+    State(service): State<StocktakeService>,
+    auth: AuthUser,
+    Query(query): Query<ListStocktakesQuery>,
+) -> Result<Json<Vec<StocktakeSession>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let sessions = service
+        .list_sessions(auth.tenant_id, query.limit.clamp(1, 100))
+        .await?;
+    Ok(Json(sessions))
+}
+
+/// `GET /api/inventory/stocktakes/:id`
+pub async fn get_session(
+    State(service): State<StocktakeService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<StocktakeSessionDetail>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let detail = service
+        .get_session(auth.tenant_id, id, auth.language)
+        .await?;
+    Ok(Json(detail))
+}
+
+/// `POST /api/inventory/stocktakes/:id/counts`
+pub async fn add_counts(
+    State(service): State<StocktakeService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<AddCountsRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), AppError> {
+    auth.require(Permission::RecordCounts)?;
+    let added = service
+        .add_counts(auth.tenant_id, auth.user_id, id, req.counts)
+        .await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({ "added": added })),
+    ))
+}
+
+/// `DELETE /api/inventory/stocktakes/:id/counts/:count_id`
+pub async fn delete_count(
+    State(service): State<StocktakeService>,
+    auth: AuthUser,
+    Path((id, count_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    service.delete_count(auth.tenant_id, id, count_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /api/inventory/stocktakes/:id/close`
+/// Posts ADJUSTMENT movements and returns the variance report
+pub async fn close_session(
+    State(service): State<StocktakeService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<StocktakeVarianceReport>, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let report = service
+        .close_session(auth.tenant_id, auth.user_id, id, auth.language)
+        .await?;
+    Ok(Json(report))
+}
+
+/// `POST /api/inventory/stocktakes/:id/cancel`
+pub async fn cancel_session(
+    State(service): State<StocktakeService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    service
+        .cancel_session(auth.tenant_id, auth.user_id, id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /api/inventory/stocktakes/:id/report`
+pub async fn get_report(
+    State(service): State<StocktakeService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<StocktakeVarianceReport>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let report = service
+        .get_report(auth.tenant_id, id, auth.language)
+        .await?;
+    Ok(Json(report))
+}
@@ -0,0 +1,148 @@
+//! HTTP handlers for storage locations and inventory transfers.
+//!
+//! Mounted under `/api/inventory/locations` and `/api/inventory/transfers`
+//! inside the protected router.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::application::inventory::{InventoryDashboard, InventoryService, StockSummary};
+use crate::application::storage_location::{
+    CreateStorageLocationInput, CreateTransferInput, InventoryTransfer, StorageLocationService,
+    UpdateStorageLocationInput,
+};
+use crate::domain::storage_location::{StorageLocation, StorageLocationId};
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct ListLocationsQuery {
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTransfersQuery {
+    pub location_id: Option<Uuid>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+/// `GET /api/inventory/locations?include_inactive=false`
+pub async fn list_locations(
+    State(service): State<StorageLocationService>,
+    auth: AuthUser,
+    Query(query): Query<ListLocationsQuery>,
+) -> Result<Json<Vec<StorageLocation>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let locations = service
+        .list_locations(auth.tenant_id, query.include_inactive)
+        .await?;
+    Ok(Json(locations))
+}
+
+/// `POST /api/inventory/locations`
+pub async fn create_location(
+    State(service): State<StorageLocationService>,
+    auth: AuthUser,
+    Json(req): Json<CreateStorageLocationInput>,
+) -> Result<(StatusCode, Json<StorageLocation>), AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let location = service.create_location(auth.tenant_id, req).await?;
+    Ok((StatusCode::CREATED, Json(location)))
+}
+
+/// `PUT /api/inventory/locations/:id`
+pub async fn update_location(
+    State(service): State<StorageLocationService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateStorageLocationInput>,
+) -> Result<Json<StorageLocation>, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let location = service
+        .update_location(auth.tenant_id, StorageLocationId::from_uuid(id), req)
+        .await?;
+    Ok(Json(location))
+}
+
+/// `DELETE /api/inventory/locations/:id` — deactivates an empty location
+pub async fn deactivate_location(
+    State(service): State<StorageLocationService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    service
+        .deactivate_location(auth.tenant_id, StorageLocationId::from_uuid(id))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /api/inventory/transfers`
+pub async fn create_transfer(
+    State(service): State<StorageLocationService>,
+    auth: AuthUser,
+    Json(req): Json<CreateTransferInput>,
+) -> Result<(StatusCode, Json<InventoryTransfer>), AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let transfer = service.transfer(auth.tenant_id, auth.user_id, req).await?;
+    Ok((StatusCode::CREATED, Json(transfer)))
+}
+
+/// `GET /api/inventory/transfers?location_id=...&limit=50`
+pub async fn list_transfers(
+    State(service): State<StorageLocationService>,
+    auth: AuthUser,
+    Query(query): Query<ListTransfersQuery>,
+) -> Result<Json<Vec<InventoryTransfer>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let transfers = service
+        .list_transfers(
+            auth.tenant_id,
+            query.location_id.map(StorageLocationId::from_uuid),
+            query.limit.clamp(1, 200),
+        )
+        .await?;
+    Ok(Json(transfers))
+}
+
+/// `GET /api/inventory/locations/:id/dashboard`
+pub async fn location_dashboard(
+    State(service): State<InventoryService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<InventoryDashboard>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let dashboard = service
+        .get_dashboard_at(
+            auth.tenant_id,
+            auth.language,
+            Some(StorageLocationId::from_uuid(id)),
+        )
+        .await?;
+    Ok(Json(dashboard))
+}
+
+/// `GET /api/inventory/locations/:id/stock`
+pub async fn location_stock(
+    State(service): State<InventoryService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<StockSummary>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let summary = service
+        .get_stock_summary_at(auth.tenant_id, Some(StorageLocationId::from_uuid(id)))
+        .await?;
+    Ok(Json(summary))
+}
@@ -0,0 +1,155 @@
+//! HTTP handlers for suppliers and purchase price history.
+//!
+//! Mounted under `/api/suppliers/...` and `/api/inventory/price-*` inside the protected router.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::application::supplier::{
+    CreateSupplierInput, IngredientInflation, IngredientPriceHistory, SupplierQuote,
+    SupplierService, UpdateSupplierInput,
+};
+use crate::domain::supplier::{Supplier, SupplierId};
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct ListSuppliersQuery {
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PeriodQuery {
+    #[serde(default = "default_days")]
+    pub days: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareSuppliersQuery {
+    pub ingredient_id: Uuid,
+    #[serde(default = "default_days")]
+    pub days: i32,
+}
+
+fn default_days() -> i32 {
+    90
+}
+
+/// `POST /api/suppliers`
+pub async fn create_supplier(
+    State(service): State<SupplierService>,
+    auth: AuthUser,
+    Json(req): Json<CreateSupplierInput>,
+) -> Result<(StatusCode, Json<Supplier>), AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let supplier = service.create_supplier(auth.tenant_id, req).await?;
+    Ok((StatusCode::CREATED, Json(supplier)))
+}
+
+/// `GET /api/suppliers?include_inactive=false`
+pub async fn list_suppliers(
+    State(service): State<SupplierService>,
+    auth: AuthUser,
+    Query(query): Query<ListSuppliersQuery>,
+) -> Result<Json<Vec<Supplier>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let suppliers = service
+        .list_suppliers(auth.tenant_id, query.include_inactive)
+        .await?;
+    Ok(Json(suppliers))
+}
+
+/// `GET /api/suppliers/:id`
+pub async fn get_supplier(
+    State(service): State<SupplierService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Supplier>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let supplier = service
+        .get_supplier(auth.tenant_id, SupplierId::from_uuid(id))
+        .await?;
+    Ok(Json(supplier))
+}
+
+/// `PUT /api/suppliers/:id`
+pub async fn update_supplier(
+    State(service): State<SupplierService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateSupplierInput>,
+) -> Result<Json<Supplier>, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    let supplier = service
+        .update_supplier(auth.tenant_id, SupplierId::from_uuid(id), req)
+        .await?;
+    Ok(Json(supplier))
+}
+
+/// `DELETE /api/suppliers/:id` — soft delete, price history is kept
+pub async fn delete_supplier(
+    State(service): State<SupplierService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageInventory)?;
+    service
+        .deactivate_supplier(auth.tenant_id, SupplierId::from_uuid(id))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /api/suppliers/compare?ingredient_id=...&days=90`
+pub async fn compare_suppliers(
+    State(service): State<SupplierService>,
+    auth: AuthUser,
+    Query(query): Query<CompareSuppliersQuery>,
+) -> Result<Json<Vec<SupplierQuote>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let quotes = service
+        .compare_suppliers(
+            auth.tenant_id,
+            query.ingredient_id,
+            query.days.clamp(1, 730),
+        )
+        .await?;
+    Ok(Json(quotes))
+}
+
+/// `GET /api/inventory/price-history/:ingredient_id?days=90`
+pub async fn price_history(
+    State(service): State<SupplierService>,
+    auth: AuthUser,
+    Path(ingredient_id): Path<Uuid>,
+    Query(query): Query<PeriodQuery>,
+) -> Result<Json<IngredientPriceHistory>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let history = service
+        .price_history(auth.tenant_id, ingredient_id, query.days.clamp(1, 730))
+        .await?;
+    Ok(Json(history))
+}
+
+/// `GET /api/inventory/price-inflation?days=90`
+pub async fn price_inflation(
+    State(service): State<SupplierService>,
+    auth: AuthUser,
+    Query(query): Query<PeriodQuery>,
+) -> Result<Json<Vec<IngredientInflation>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let items = service
+        .price_inflation(
+            auth.tenant_id,
+            query.days.clamp(1, 730),
+            auth.language.code(),
+        )
+        .await?;
+    Ok(Json(items))
+}
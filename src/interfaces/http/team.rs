@@ -0,0 +1,122 @@
+//! HTTP handlers for team management.
+//!
+//! Members, invitations and ownership transfer are mounted under `/api/team`
+//! inside the protected router; accepting an invitation is public
+//! (`/api/auth/invitations/accept`) because the invitee may have no account.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::application::team::{
+    AcceptInvitationCommand, CreateInvitationInput, CreatedInvitation, Invitation, TeamMember,
+    TeamService,
+};
+use crate::domain::{Permission, UserRole};
+use crate::interfaces::http::auth::{client_info, AuthTokenResponse};
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::{AppError, UserId};
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub role: UserRole,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferOwnershipRequest {
+    pub user_id: Uuid,
+}
+
+/// `GET /api/team/members`
+pub async fn list_members(
+    State(service): State<TeamService>,
+    auth: AuthUser,
+) -> Result<Json<Vec<TeamMember>>, AppError> {
+    auth.require(Permission::ManageTeam)?;
+    let members = service.list_members(auth.tenant_id).await?;
+    Ok(Json(members))
+}
+
+/// `PUT /api/team/members/:id/role`
+pub async fn update_member_role(
+    State(service): State<TeamService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateRoleRequest>,
+) -> Result<Json<TeamMember>, AppError> {
+    auth.require(Permission::ManageTeam)?;
+    let member = service
+        .update_member_role(auth.tenant_id, &auth.role, UserId::from_uuid(id), req.role)
+        .await?;
+    Ok(Json(member))
+}
+
+/// `POST /api/team/transfer-ownership` — the caller steps down to manager
+pub async fn transfer_ownership(
+    State(service): State<TeamService>,
+    auth: AuthUser,
+    Json(req): Json<TransferOwnershipRequest>,
+) -> Result<Json<Vec<TeamMember>>, AppError> {
+    auth.require(Permission::TransferOwnership)?;
+    let members = service
+        .transfer_ownership(auth.tenant_id, auth.user_id, UserId::from_uuid(req.user_id))
+        .await?;
+    Ok(Json(members))
+}
+
+/// `GET /api/team/invitations`
+pub async fn list_invitations(
+    State(service): State<TeamService>,
+    auth: AuthUser,
+) -> Result<Json<Vec<Invitation>>, AppError> {
+    auth.require(Permission::ManageTeam)?;
+    let invitations = service.list_invitations(auth.tenant_id).await?;
+    Ok(Json(invitations))
+}
+
+/// `POST /api/team/invitations` — the token is in the response only once
+pub async fn create_invitation(
+    State(service): State<TeamService>,
+    auth: AuthUser,
+    Json(req): Json<CreateInvitationInput>,
+) -> Result<(StatusCode, Json<CreatedInvitation>), AppError> {
+    auth.require(Permission::ManageTeam)?;
+    let invitation = service
+        .create_invitation(auth.tenant_id, auth.user_id, &auth.role, req)
+        .await?;
+    Ok((StatusCode::CREATED, Json(invitation)))
+}
+
+/// `DELETE /api/team/invitations/:id`
+pub async fn revoke_invitation(
+    State(service): State<TeamService>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require(Permission::ManageTeam)?;
+    service.revoke_invitation(auth.tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /api/auth/invitations/accept` — joins the team and signs in
+pub async fn accept_invitation(
+    State(service): State<TeamService>,
+    headers: HeaderMap,
+    Json(req): Json<AcceptInvitationCommand>,
+) -> Result<Json<AuthTokenResponse>, AppError> {
+    let response = service
+        .accept_invitation(req, client_info(&headers))
+        .await?;
+
+    Ok(Json(AuthTokenResponse {
+        access_token: response.access_token,
+        refresh_token: response.refresh_token,
+        token_type: "Bearer".to_string(),
+        user_id: response.user_id.to_string(),
+        tenant_id: response.tenant_id.to_string(),
+    }))
+}
@@ -1,6 +1,7 @@
 use crate::application::{
     AddTenantIngredientRequest, TenantIngredientService, UpdateTenantIngredientRequest,
 };
+use crate::domain::Permission;
 use crate::interfaces::http::middleware::AuthUser;
 use crate::shared::AppResult;
 use axum::{
@@ -21,14 +22,18 @@ pub struct SearchQuery {
 /// POST /api/tenant/ingredients
 pub async fn add_ingredient(
     AuthUser {
-        user_id: _,
+        user_id,
         tenant_id,
         language,
+        role,
     }: AuthUser,
     State(service): State<TenantIngredientService>,
     Json(req): Json<AddTenantIngredientRequest>,
 ) -> AppResult<impl IntoResponse> {
-    let id = service.add_ingredient(tenant_id, language, req).await?;
+    role.require(Permission::ManageInventory)?;
+    let id = service
+        .add_ingredient(tenant_id, user_id, language, req)
+        .await?;
     Ok((StatusCode::CREATED, Json(serde_json::json!({ "id": id }))))
 }
 
@@ -39,9 +44,11 @@ pub async fn list_ingredients(
         user_id: _,
         tenant_id,
         language,
+        role,
     }: AuthUser,
     State(service): State<TenantIngredientService>,
 ) -> AppResult<Json<serde_json::Value>> {
+    role.require(Permission::ViewInventory)?;
     let ingredients = service.list_ingredients(tenant_id, language).await?;
     Ok(Json(serde_json::json!({
         "ingredients": ingredients
@@ -55,10 +62,12 @@ pub async fn get_ingredient(
         user_id: _,
         tenant_id,
         language,
+        role,
     }: AuthUser,
     State(service): State<TenantIngredientService>,
     Path(id): Path<Uuid>,
 ) -> AppResult<impl IntoResponse> {
+    role.require(Permission::ViewInventory)?;
     let ingredient = service.get_ingredient(tenant_id, id, language).await?;
     Ok(Json(ingredient))
 }
@@ -67,16 +76,18 @@ pub async fn get_ingredient(
 /// PUT /api/tenant/ingredients/:id
 pub async fn update_ingredient(
     AuthUser {
-        user_id: _,
+        user_id,
         tenant_id,
         language,
+        role,
     }: AuthUser,
     State(service): State<TenantIngredientService>,
     Path(id): Path<Uuid>,
     Json(req): Json<UpdateTenantIngredientRequest>,
 ) -> AppResult<impl IntoResponse> {
+    role.require(Permission::ManageInventory)?;
     let ingredient = service
-        .update_ingredient(tenant_id, id, language, req)
+        .update_ingredient(tenant_id, user_id, id, language, req)
         .await?;
     Ok(Json(ingredient))
 }
@@ -85,14 +96,16 @@ pub async fn update_ingredient(
 /// DELETE /api/tenant/ingredients/:id
 pub async fn remove_ingredient(
     AuthUser {
-        user_id: _,
+        user_id,
         tenant_id,
         language: _,
+        role,
     }: AuthUser,
     State(service): State<TenantIngredientService>,
     Path(id): Path<Uuid>,
 ) -> AppResult<impl IntoResponse> {
-    service.remove_ingredient(tenant_id, id).await?;
+    role.require(Permission::ManageInventory)?;
+    service.remove_ingredient(tenant_id, user_id, id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -103,10 +116,12 @@ pub async fn search_available_ingredients(
         user_id: _,
         tenant_id,
         language,
+        role,
     }: AuthUser,
     State(service): State<TenantIngredientService>,
     Query(query): Query<SearchQuery>,
 ) -> AppResult<Json<serde_json::Value>> {
+    role.require(Permission::ViewInventory)?;
     let ingredients = service
         .search_available_ingredients(tenant_id, language, &query.q)
         .await?;
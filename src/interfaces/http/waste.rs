@@ -0,0 +1,74 @@
+//! HTTP handlers for the manual waste log.
+//!
+//! Mounted under `/api/inventory/waste` inside the protected router.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::application::user::AvatarUploadResponse;
+use crate::application::waste::{
+    LogWasteInput, WasteEntry, WasteFilter, WasteReasonView, WasteService,
+};
+use crate::domain::Permission;
+use crate::interfaces::http::middleware::AuthUser;
+use crate::shared::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct PhotoUploadQuery {
+    pub content_type: Option<String>,
+}
+
+/// `GET /api/inventory/waste/reasons`
+pub async fn list_reasons(
+    State(service): State<WasteService>,
+    auth: AuthUser,
+) -> Json<Vec<WasteReasonView>> {
+    Json(service.reasons(auth.language))
+}
+
+/// `GET /api/inventory/waste/photo-upload-url?content_type=image/webp`
+/// Presigned URL for direct R2 upload; send `public_url` back as `photo_url`
+pub async fn get_photo_upload_url(
+    State(service): State<WasteService>,
+    auth: AuthUser,
+    Query(query): Query<PhotoUploadQuery>,
+) -> Result<Json<AvatarUploadResponse>, AppError> {
+    auth.require(Permission::LogWaste)?;
+    let content_type = query
+        .content_type
+        .unwrap_or_else(|| "image/webp".to_string());
+    let response = service
+        .get_photo_upload_url(auth.tenant_id, &content_type)
+        .await?;
+    Ok(Json(response))
+}
+
+/// `POST /api/inventory/waste`
+pub async fn log_waste(
+    State(service): State<WasteService>,
+    auth: AuthUser,
+    Json(req): Json<LogWasteInput>,
+) -> Result<(StatusCode, Json<WasteEntry>), AppError> {
+    auth.require(Permission::LogWaste)?;
+    let entry = service
+        .log_waste(auth.tenant_id, auth.user_id, req, auth.language)
+        .await?;
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+/// `GET /api/inventory/waste?days=30&reason=spoilage&ingredient_id=...&location_id=...`
+pub async fn list_waste(
+    State(service): State<WasteService>,
+    auth: AuthUser,
+    Query(filter): Query<WasteFilter>,
+) -> Result<Json<Vec<WasteEntry>>, AppError> {
+    auth.require(Permission::ViewInventory)?;
+    let entries = service
+        .list_entries(auth.tenant_id, filter, auth.language)
+        .await?;
+    Ok(Json(entries))
+}
@@ -1,8 +1,12 @@
+use restaurant_backend::application::account::AccountService;
+use restaurant_backend::application::audit::AuditLog;
 use restaurant_backend::application::{
     AdminAuthService, AdminCatalogService, AdminNutritionService, AnalyticsService,
     AssistantService, AuthService, CatalogService, DishService, InventoryService,
     MenuEngineeringService, RecipeService, TenantIngredientService, UserService,
 };
+use restaurant_backend::infrastructure::llm::build_gateway;
+use restaurant_backend::infrastructure::mail::build_mailer;
 use restaurant_backend::infrastructure::{
     Config, JwtService, LlmAdapter, PasswordHasher, R2Client, Repositories,
 };
@@ -89,28 +93,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Initialize application services
+    let mailer = build_mailer(&config.mail);
+    let account_service = AccountService::new(
+        repositories.pool.clone(),
+        mailer.clone(),
+        password_hasher.clone(),
+        repositories.refresh_token.clone(),
+        config.mail.app_url.clone(),
+    );
     let auth_service = AuthService::new(
         repositories.user.clone(),
         repositories.tenant.clone(),
         repositories.refresh_token.clone(),
         password_hasher,
         jwt_service.clone(),
+        account_service,
     );
 
     let inventory_service = InventoryService::new(repositories.pool.clone());
 
     let catalog_service = CatalogService::new(repositories.pool.clone());
 
+    // Audit trail shared by services that write business entities
+    let audit_log = AuditLog::new(repositories.pool.clone());
+
     // Create RecipeService with all dependencies
     let recipe_service = RecipeService::new(
         Arc::new(repositories.recipe.clone()),
         Arc::new(repositories.inventory_product.clone()),
         Arc::new(repositories.catalog_ingredient.clone()),
+        audit_log.clone(),
     );
 
     // Create DishService
-    let dish_service =
-        DishService::new(Arc::new(repositories.dish.clone()), recipe_service.clone());
+    let dish_service = DishService::new(
+        Arc::new(repositories.dish.clone()),
+        recipe_service.clone(),
+        audit_log.clone(),
+    );
 
     // Create MenuEngineeringService
     let menu_engineering_service = MenuEngineeringService::new(
@@ -118,6 +138,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Arc::new(repositories.inventory_product.clone()),
         Arc::new(repositories.dish.clone()),
         Arc::new(repositories.recipe.clone()),
+        Arc::new(repositories.catalog_ingredient.clone()),
     );
 
     // Create AssistantService with all services
@@ -163,20 +184,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config.ai.gemini_api_key.clone(),
     ));
 
-    // Clone Arc for Copilot Brain (gemini_service is moved into LlmAdapter below)
-    let gemini_for_copilot = Arc::clone(&gemini_service);
+    // LLM gateway: text completions go through the provider chain
+    // (LLM_PROVIDERS order, per-provider timeout + circuit breaker)
+    let llm_gateway = build_gateway(
+        &config.llm,
+        Some(Arc::new(repositories.ai_usage_stats.clone())),
+    );
 
     // Create LLM Adapter (Rule Engine -> Cache -> LLM)
     let llm_adapter = Arc::new(LlmAdapter::new(
         gemini_service,
+        Arc::clone(&llm_gateway),
         Arc::new(repositories.ai_cache.clone()),
         Arc::new(repositories.ai_usage_stats.clone()),
     ));
 
-    if config.ai.gemini_api_key.is_empty() {
-        tracing::warn!("⚠️ GEMINI_API_KEY not set - AI-dependent features will not work");
+    if config.llm.providers.is_empty() {
+        tracing::warn!("⚠️ No LLM provider configured (GEMINI_API_KEY / GROQ_API_KEY / OPENAI_COMPAT_BASE_URL) - AI-dependent features will not work");
     } else {
-        tracing::info!("✅ AI Services (LlmAdapter + Gemini) initialized");
+        let chain: Vec<&str> = config
+            .llm
+            .providers
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        tracing::info!(
+            "✅ AI Services initialized (LLM chain: {})",
+            chain.join(" → ")
+        );
+    }
+    if config.ai.gemini_api_key.is_empty() {
+        tracing::warn!("⚠️ GEMINI_API_KEY not set - image generation and vision will not work");
     }
 
     // Create AdminCatalogService
@@ -195,7 +233,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create TenantIngredientService
     let tenant_ingredient_service =
-        TenantIngredientService::new(Arc::new(repositories.tenant_ingredient.clone()));
+        TenantIngredientService::new(Arc::new(repositories.tenant_ingredient.clone()), audit_log);
 
     // Create Recipe V2 & AI Insights Services
     let recipe_translation_service = Arc::new(
@@ -267,7 +305,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         r2_client,
         llm_adapter,
         ingredient_cache,
-        gemini_for_copilot,
+        llm_gateway,
+        mailer,
         cors_origins,
         rate_limit_per_second,
     );
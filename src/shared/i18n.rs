@@ -160,7 +160,7 @@ pub fn translate_allergen(allergen: &str, lang: Language) -> String {
             Language::Ru => "арахис",
             Language::Uk => "арахіс",
         },
-        "soy" | "soya" => match lang {
+        "soy" | "soya" | "soybeans" => match lang {
             Language::En => "soy",
             Language::Pl => "soja",
             Language::Ru => "соя",
@@ -184,6 +184,30 @@ pub fn translate_allergen(allergen: &str, lang: Language) -> String {
             Language::Ru => "горчица",
             Language::Uk => "гірчиця",
         },
+        "celery" => match lang {
+            Language::En => "celery",
+            Language::Pl => "seler",
+            Language::Ru => "сельдерей",
+            Language::Uk => "селера",
+        },
+        "sulfites" | "sulphites" => match lang {
+            Language::En => "sulphites",
+            Language::Pl => "siarczyny",
+            Language::Ru => "сульфиты",
+            Language::Uk => "сульфіти",
+        },
+        "lupin" => match lang {
+            Language::En => "lupin",
+            Language::Pl => "łubin",
+            Language::Ru => "люпин",
+            Language::Uk => "люпин",
+        },
+        "molluscs" => match lang {
+            Language::En => "molluscs",
+            Language::Pl => "mięczaki",
+            Language::Ru => "моллюски",
+            Language::Uk => "молюски",
+        },
         _ => return allergen.to_string(),
     };
     label.to_string()
@@ -196,6 +220,227 @@ pub fn translate_allergens(allergens: &[String], lang: Language) -> Vec<String>
         .collect()
 }
 
+// ── Dish label texts ──────────────────────────────────────────────────────────
+
+/// Headings of the allergen matrix and the dish nutrition label
+#[derive(Debug, Clone, Copy)]
+pub enum LabelText {
+    Dish,
+    Allergens,
+    Contains,
+    MayContain,
+    NoAllergens,
+    Nutrition,
+    Per100g,
+    PerPortion,
+    PortionWeight,
+    Energy,
+    Fat,
+    Saturates,
+    Carbohydrate,
+    Sugars,
+    Fibre,
+    Protein,
+    Salt,
+    IncompleteData,
+}
+
+pub fn translate_label_text(key: LabelText, lang: Language) -> &'static str {
+    use LabelText::*;
+    match (key, lang) {
+        (Dish, Language::En) => "Dish",
+        (Dish, Language::Pl) => "Danie",
+        (Dish, Language::Ru) => "Блюдо",
+        (Dish, Language::Uk) => "Страва",
+        (Allergens, Language::En) => "Allergens",
+        (Allergens, Language::Pl) => "Alergeny",
+        (Allergens, Language::Ru) => "Аллергены",
+        (Allergens, Language::Uk) => "Алергени",
+        (Contains, Language::En) => "Contains",
+        (Contains, Language::Pl) => "Zawiera",
+        (Contains, Language::Ru) => "Содержит",
+        (Contains, Language::Uk) => "Містить",
+        (MayContain, Language::En) => "May contain",
+        (MayContain, Language::Pl) => "Może zawierać",
+        (MayContain, Language::Ru) => "Может содержать",
+        (MayContain, Language::Uk) => "Може містити",
+        (NoAllergens, Language::En) => "No declared allergens",
+        (NoAllergens, Language::Pl) => "Brak deklarowanych alergenów",
+        (NoAllergens, Language::Ru) => "Заявленных аллергенов нет",
+        (NoAllergens, Language::Uk) => "Задекларованих алергенів немає",
+        (Nutrition, Language::En) => "Nutrition declaration",
+        (Nutrition, Language::Pl) => "Wartość odżywcza",
+        (Nutrition, Language::Ru) => "Пищевая ценность",
+        (Nutrition, Language::Uk) => "Харчова цінність",
+        (Per100g, Language::En) => "per 100 g",
+        (Per100g, Language::Pl) => "w 100 g",
+        (Per100g, Language::Ru) => "на 100 г",
+        (Per100g, Language::Uk) => "на 100 г",
+        (PerPortion, Language::En) => "per portion",
+        (PerPortion, Language::Pl) => "w porcji",
+        (PerPortion, Language::Ru) => "на порцию",
+        (PerPortion, Language::Uk) => "на порцію",
+        (PortionWeight, Language::En) => "Portion weight",
+        (PortionWeight, Language::Pl) => "Masa porcji",
+        (PortionWeight, Language::Ru) => "Вес порции",
+        (PortionWeight, Language::Uk) => "Вага порції",
+        (Energy, Language::En) => "Energy",
+        (Energy, Language::Pl) => "Wartość energetyczna",
+        (Energy, Language::Ru) => "Энергетическая ценность",
+        (Energy, Language::Uk) => "Енергетична цінність",
+        (Fat, Language::En) => "Fat",
+        (Fat, Language::Pl) => "Tłuszcz",
+        (Fat, Language::Ru) => "Жиры",
+        (Fat, Language::Uk) => "Жири",
+        (Saturates, Language::En) => "of which saturates",
+        (Saturates, Language::Pl) => "w tym kwasy tłuszczowe nasycone",
+        (Saturates, Language::Ru) => "в т.ч. насыщенные жирные кислоты",
+        (Saturates, Language::Uk) => "у т.ч. насичені жирні кислоти",
+        (Carbohydrate, Language::En) => "Carbohydrate",
+        (Carbohydrate, Language::Pl) => "Węglowodany",
+        (Carbohydrate, Language::Ru) => "Углеводы",
+        (Carbohydrate, Language::Uk) => "Вуглеводи",
+        (Sugars, Language::En) => "of which sugars",
+        (Sugars, Language::Pl) => "w tym cukry",
+        (Sugars, Language::Ru) => "в т.ч. сахара",
+        (Sugars, Language::Uk) => "у т.ч. цукри",
+        (Fibre, Language::En) => "Fibre",
+        (Fibre, Language::Pl) => "Błonnik",
+        (Fibre, Language::Ru) => "Пищевые волокна",
+        (Fibre, Language::Uk) => "Харчові волокна",
+        (Protein, Language::En) => "Protein",
+        (Protein, Language::Pl) => "Białko",
+        (Protein, Language::Ru) => "Белки",
+        (Protein, Language::Uk) => "Білки",
+        (Salt, Language::En) => "Salt",
+        (Salt, Language::Pl) => "Sól",
+        (Salt, Language::Ru) => "Соль",
+        (Salt, Language::Uk) => "Сіль",
+        (IncompleteData, Language::En) => "No nutrition data for",
+        (IncompleteData, Language::Pl) => "Brak danych odżywczych dla",
+        (IncompleteData, Language::Ru) => "Нет данных о пищевой ценности для",
+        (IncompleteData, Language::Uk) => "Немає даних про харчову цінність для",
+    }
+}
+
+// ── Account and report emails ─────────────────────────────────────────────────
+
+/// Lines of the password-reset, email-verification and scheduled report emails
+#[derive(Debug, Clone, Copy)]
+pub enum EmailText {
+    Greeting,
+    ResetSubject,
+    ResetIntro,
+    ResetAction,
+    ResetIgnore,
+    VerifySubject,
+    VerifyIntro,
+    VerifyAction,
+    VerifyIgnore,
+    /// `{hours}` is replaced with the link lifetime
+    LinkExpires,
+    Signature,
+    /// `{report}`, `{from}` and `{to}` are replaced
+    ReportIntro,
+    /// `{rows}` is replaced with the number of rows shown
+    ReportTruncated,
+    ReportFooter,
+}
+
+pub fn translate_email_text(key: EmailText, lang: Language) -> &'static str {
+    use EmailText::*;
+    match (key, lang) {
+        (Greeting, Language::En) => "Hello",
+        (Greeting, Language::Pl) => "Dzień dobry",
+        (Greeting, Language::Ru) => "Здравствуйте",
+        (Greeting, Language::Uk) => "Вітаємо",
+        (ResetSubject, Language::En) => "Reset your password",
+        (ResetSubject, Language::Pl) => "Zresetuj hasło",
+        (ResetSubject, Language::Ru) => "Сброс пароля",
+        (ResetSubject, Language::Uk) => "Скидання пароля",
+        (ResetIntro, Language::En) => {
+            "We received a request to reset the password for your account."
+        }
+        (ResetIntro, Language::Pl) => "Otrzymaliśmy prośbę o zresetowanie hasła do Twojego konta.",
+        (ResetIntro, Language::Ru) => "Мы получили запрос на сброс пароля вашей учётной записи.",
+        (ResetIntro, Language::Uk) => {
+            "Ми отримали запит на скидання пароля вашого облікового запису."
+        }
+        (ResetAction, Language::En) => "Choose a new password",
+        (ResetAction, Language::Pl) => "Ustaw nowe hasło",
+        (ResetAction, Language::Ru) => "Задать новый пароль",
+        (ResetAction, Language::Uk) => "Встановити новий пароль",
+        (ResetIgnore, Language::En) => {
+            "If you did not ask for this, ignore this email — your password stays the same."
+        }
+        (ResetIgnore, Language::Pl) => {
+            "Jeśli to nie Ty, zignoruj tę wiadomość — hasło pozostanie bez zmian."
+        }
+        (ResetIgnore, Language::Ru) => {
+            "Если вы не запрашивали сброс, просто проигнорируйте это письмо — пароль не изменится."
+        }
+        (ResetIgnore, Language::Uk) => {
+            "Якщо ви не надсилали запит, проігноруйте цей лист — пароль не зміниться."
+        }
+        (VerifySubject, Language::En) => "Confirm your email address",
+        (VerifySubject, Language::Pl) => "Potwierdź adres e-mail",
+        (VerifySubject, Language::Ru) => "Подтвердите адрес электронной почты",
+        (VerifySubject, Language::Uk) => "Підтвердьте адресу електронної пошти",
+        (VerifyIntro, Language::En) => "Please confirm that this address belongs to you.",
+        (VerifyIntro, Language::Pl) => "Potwierdź, że ten adres należy do Ciebie.",
+        (VerifyIntro, Language::Ru) => "Пожалуйста, подтвердите, что этот адрес принадлежит вам.",
+        (VerifyIntro, Language::Uk) => "Будь ласка, підтвердьте, що ця адреса належить вам.",
+        (VerifyAction, Language::En) => "Confirm email",
+        (VerifyAction, Language::Pl) => "Potwierdź e-mail",
+        (VerifyAction, Language::Ru) => "Подтвердить почту",
+        (VerifyAction, Language::Uk) => "Підтвердити пошту",
+        (VerifyIgnore, Language::En) => "If you did not create an account, ignore this email.",
+        (VerifyIgnore, Language::Pl) => "Jeśli nie zakładałeś konta, zignoruj tę wiadomość.",
+        (VerifyIgnore, Language::Ru) => {
+            "Если вы не создавали учётную запись, проигнорируйте это письмо."
+        }
+        (VerifyIgnore, Language::Uk) => {
+            "Якщо ви не створювали обліковий запис, проігноруйте цей лист."
+        }
+        (LinkExpires, Language::En) => "The link works once and expires in {hours} h.",
+        (LinkExpires, Language::Pl) => "Link działa jednorazowo i wygasa za {hours} godz.",
+        (LinkExpires, Language::Ru) => "Ссылка одноразовая и действует {hours} ч.",
+        (LinkExpires, Language::Uk) => "Посилання одноразове й діє {hours} год.",
+        (Signature, Language::En) => "Your kitchen team",
+        (Signature, Language::Pl) => "Twój zespół kuchni",
+        (Signature, Language::Ru) => "Ваша команда кухни",
+        (Signature, Language::Uk) => "Ваша команда кухні",
+        (ReportIntro, Language::En) => {
+            "{report} for {from} – {to}. The full tables are attached as CSV and XLSX."
+        }
+        (ReportIntro, Language::Pl) => {
+            "{report} za okres {from} – {to}. Pełne tabele w załącznikach CSV i XLSX."
+        }
+        (ReportIntro, Language::Ru) => {
+            "{report} за период {from} – {to}. Полные таблицы во вложениях CSV и XLSX."
+        }
+        (ReportIntro, Language::Uk) => {
+            "{report} за період {from} – {to}. Повні таблиці у вкладеннях CSV і XLSX."
+        }
+        (ReportTruncated, Language::En) => "First {rows} rows shown.",
+        (ReportTruncated, Language::Pl) => "Pokazano pierwsze {rows} wierszy.",
+        (ReportTruncated, Language::Ru) => "Показаны первые {rows} строк.",
+        (ReportTruncated, Language::Uk) => "Показано перші {rows} рядків.",
+        (ReportFooter, Language::En) => {
+            "You receive this email because of a report subscription in your restaurant account."
+        }
+        (ReportFooter, Language::Pl) => {
+            "Otrzymujesz tę wiadomość, ponieważ w koncie restauracji ustawiono subskrypcję raportu."
+        }
+        (ReportFooter, Language::Ru) => {
+            "Вы получили это письмо, потому что в аккаунте ресторана настроена подписка на отчёт."
+        }
+        (ReportFooter, Language::Uk) => {
+            "Ви отримали цей лист, бо в акаунті ресторану налаштовано підписку на звіт."
+        }
+    }
+}
+
 /// Message keys for assistant messages
 #[derive(Debug, Clone, Copy)]
 pub enum AssistantMessage {
@@ -482,6 +727,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_translate_email_texts_all_languages() {
+        let keys = [
+            EmailText::ResetSubject,
+            EmailText::VerifyAction,
+            EmailText::LinkExpires,
+        ];
+
+        for key in keys {
+            for lang in [Language::En, Language::Pl, Language::Uk, Language::Ru] {
+                assert!(!translate_email_text(key, lang).is_empty());
+            }
+        }
+    }
+
     #[test]
     fn test_translate_hints_all_languages() {
         let keys = [AssistantHint::InventoryWhy, AssistantHint::RecipeWhy];